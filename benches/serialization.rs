@@ -0,0 +1,105 @@
+//! Deserialization cost of `VaultRecord` and `VaultInstruction` across encodings.
+//!
+//! `borsh` is what the processor does today; `fixed_offsets` is a `Pack`-style copy out of
+//! known offsets; `zero_copy` borrows fields in place from the account data.
+use {
+    borsh::BorshDeserialize,
+    criterion::{black_box, criterion_group, criterion_main, Criterion},
+    solana_program::pubkey::Pubkey,
+    vault::{
+        instruction::VaultInstruction,
+        state::{VaultRecord, VaultStatus},
+    },
+};
+
+const VERSION: usize = 8;
+const AUTHORITY: usize = VERSION + 1;
+const DART: usize = AUTHORITY + 32;
+const STATUS: usize = DART + 32;
+
+fn status_from_byte(byte: u8) -> Option<VaultStatus> {
+    Some(match byte {
+        0 => VaultStatus::Active,
+        1 => VaultStatus::Frozen,
+        2 => VaultStatus::PendingTransfer,
+        3 => VaultStatus::Seized,
+        4 => VaultStatus::Closed,
+        _ => return None,
+    })
+}
+
+fn unpack_fixed_offsets(data: &[u8]) -> Option<VaultRecord> {
+    if data.len() != VaultRecord::LEN {
+        return None;
+    }
+    let authority = Pubkey::try_from(&data[AUTHORITY..DART]).ok()?;
+    let dart = Pubkey::try_from(&data[DART..STATUS]).ok()?;
+    let mut record = VaultRecord::new(authority, dart);
+    record.version = data[VERSION];
+    record.status = status_from_byte(data[STATUS])?;
+    Some(record)
+}
+
+struct VaultRecordView<'a>(&'a [u8]);
+
+impl<'a> VaultRecordView<'a> {
+    fn new(data: &'a [u8]) -> Option<Self> {
+        (data.len() == VaultRecord::LEN).then_some(Self(data))
+    }
+
+    fn authority(&self) -> &'a Pubkey {
+        bytemuck::from_bytes(&self.0[AUTHORITY..DART])
+    }
+
+    fn dart(&self) -> &'a Pubkey {
+        bytemuck::from_bytes(&self.0[DART..STATUS])
+    }
+
+    fn status(&self) -> Option<VaultStatus> {
+        status_from_byte(self.0[STATUS])
+    }
+}
+
+fn record_data() -> Vec<u8> {
+    let record = VaultRecord::new(Pubkey::new_unique(), Pubkey::new_unique());
+    borsh::to_vec(&record).unwrap()
+}
+
+fn bench_record(c: &mut Criterion) {
+    let data = record_data();
+    let mut group = c.benchmark_group("vault_record");
+    group.bench_function("borsh", |b| {
+        b.iter(|| VaultRecord::try_from_slice(black_box(&data)).unwrap())
+    });
+    group.bench_function("fixed_offsets", |b| {
+        b.iter(|| unpack_fixed_offsets(black_box(&data)).unwrap())
+    });
+    group.bench_function("zero_copy", |b| {
+        b.iter(|| {
+            let view = VaultRecordView::new(black_box(&data)).unwrap();
+            (*view.authority(), *view.dart(), view.status().unwrap())
+        })
+    });
+    group.finish();
+}
+
+fn bench_instruction(c: &mut Criterion) {
+    let data = borsh::to_vec(&VaultInstruction::TransferAuthority).unwrap();
+    let mut group = c.benchmark_group("vault_instruction");
+    group.bench_function("borsh", |b| {
+        b.iter(|| VaultInstruction::try_from_slice(black_box(&data)).unwrap())
+    });
+    group.bench_function("tag_match", |b| {
+        b.iter(|| match black_box(&data).as_slice() {
+            [0] => VaultInstruction::Initialize,
+            [1] => VaultInstruction::TransferAuthority,
+            [2] => VaultInstruction::CloseAccount,
+            [3] => VaultInstruction::Reopen,
+            _ => unreachable!(),
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_record, bench_instruction);
+criterion_main!(benches);
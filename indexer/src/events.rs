@@ -0,0 +1,202 @@
+//! Vault events recovered from transaction logs.
+use {
+    base64::{engine::general_purpose::STANDARD, Engine},
+    borsh::BorshDeserialize,
+    solana_program::{hash::Hash, pubkey::Pubkey},
+    spl_discriminator::SplDiscriminate,
+    vault::{
+        event::{
+            AuthorityTransferred, CloseReceipt, CorporateActionApplied, FeesCollected, NoteAdded,
+            VaultClosed, VaultRekeyed, VotingDelegated,
+        },
+        state::CorporateActionType,
+    },
+};
+
+pub use vault::event::program_data;
+
+/// An event logged by the vault program.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum VaultEvent {
+    AuthorityTransferred(AuthorityTransferred),
+    VaultClosed(VaultClosed),
+    VaultRekeyed(VaultRekeyed),
+    NoteAdded(NoteAdded),
+    CorporateActionApplied(CorporateActionApplied),
+    VotingDelegated(VotingDelegated),
+    FeesCollected(FeesCollected),
+    CloseReceipt(CloseReceipt),
+}
+
+impl VaultEvent {
+    /// Decode event data: an event discriminator followed by the event's borsh encoding.
+    pub fn decode(data: &[u8]) -> Option<Self> {
+        fn parse<E: BorshDeserialize + SplDiscriminate>(data: &[u8]) -> Option<E> {
+            let body = data.strip_prefix(E::SPL_DISCRIMINATOR_SLICE)?;
+            E::try_from_slice(body).ok()
+        }
+        parse(data)
+            .map(Self::AuthorityTransferred)
+            .or_else(|| parse(data).map(Self::VaultClosed))
+            .or_else(|| parse(data).map(Self::VaultRekeyed))
+            .or_else(|| parse(data).map(Self::NoteAdded))
+            .or_else(|| parse(data).map(Self::CorporateActionApplied))
+            .or_else(|| parse(data).map(Self::VotingDelegated))
+            .or_else(|| parse(data).map(Self::FeesCollected))
+            .or_else(|| parse(data).map(Self::CloseReceipt))
+    }
+
+    /// Decode base64 event data, as logged by `sol_log_data`.
+    pub fn decode_base64(data: &str) -> Option<Self> {
+        Self::decode(&STANDARD.decode(data).ok()?)
+    }
+
+    /// Name of the event.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::AuthorityTransferred(_) => "AuthorityTransferred",
+            Self::VaultClosed(_) => "VaultClosed",
+            Self::VaultRekeyed(_) => "VaultRekeyed",
+            Self::NoteAdded(_) => "NoteAdded",
+            Self::CorporateActionApplied(_) => "CorporateActionApplied",
+            Self::VotingDelegated(_) => "VotingDelegated",
+            Self::FeesCollected(_) => "FeesCollected",
+            Self::CloseReceipt(_) => "CloseReceipt",
+        }
+    }
+
+    /// The vault record the event is about, or the fee config for `FeesCollected`,
+    /// whose history then holds every collection.
+    pub fn vault(&self) -> Pubkey {
+        match self {
+            Self::AuthorityTransferred(event) => event.vault,
+            Self::VaultClosed(event) => event.vault,
+            Self::VaultRekeyed(event) => event.vault,
+            Self::NoteAdded(event) => event.vault,
+            Self::CorporateActionApplied(event) => event.vault,
+            Self::VotingDelegated(event) => event.vault,
+            Self::FeesCollected(event) => event.fee_config,
+            Self::CloseReceipt(event) => event.vault,
+        }
+    }
+
+    /// Human-readable details of the event, kept in the vault's history.
+    pub fn detail(&self) -> String {
+        match self {
+            Self::AuthorityTransferred(event) => format!(
+                "authority {} -> {}",
+                event.previous_authority, event.authority
+            ),
+            Self::VaultClosed(event) => format!(
+                "{} lamports paid to {} by authority {}",
+                event.lamports, event.recipient, event.authority
+            ),
+            Self::VaultRekeyed(event) => format!("moved to {}", event.new_vault),
+            Self::NoteAdded(event) => format!(
+                "note {} of category {}: {}",
+                event.sequence,
+                event.category,
+                Hash::new_from_array(event.hash)
+            ),
+            Self::CorporateActionApplied(event) => {
+                let action = match event.action_type {
+                    CorporateActionType::Split => "split",
+                    CorporateActionType::ReverseSplit => "reverse split",
+                    CorporateActionType::SymbolChange { .. } => "symbol change",
+                };
+                format!(
+                    "{action} by {}: balance {} -> {}, asset {} -> {}",
+                    event.ratio,
+                    event.previous_balance,
+                    event.balance,
+                    Hash::new_from_array(event.previous_asset_id),
+                    Hash::new_from_array(event.asset_id)
+                )
+            }
+            Self::VotingDelegated(event) if event.proxy == Pubkey::default() => {
+                format!("authority {} revoked its voting proxy", event.authority)
+            }
+            Self::VotingDelegated(event) => format!(
+                "authority {} delegated votes to {}",
+                event.authority, event.proxy
+            ),
+            Self::FeesCollected(event) => format!(
+                "{} lamports of fees paid to {}",
+                event.lamports, event.destination
+            ),
+            Self::CloseReceipt(event) => format!(
+                "receipt: {} lamports returned at slot {} by authority {}",
+                event.lamports, event.slot, event.authority
+            ),
+        }
+    }
+}
+
+/// Events logged by `program_id` in `logs`, the log messages of a transaction. Data
+/// logged by other programs, including programs the vault invokes, is skipped.
+pub fn events_in_logs(program_id: &Pubkey, logs: &[String]) -> Vec<VaultEvent> {
+    program_data(program_id, logs)
+        .into_iter()
+        .filter_map(VaultEvent::decode_base64)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        vault::event::{event_data, DATA_LOG_PREFIX},
+    };
+
+    fn data_log(data: &[u8]) -> String {
+        format!("{DATA_LOG_PREFIX}{}", STANDARD.encode(data))
+    }
+
+    fn closed() -> VaultClosed {
+        VaultClosed {
+            vault: Pubkey::new_from_array([1; 32]),
+            authority: Pubkey::new_from_array([2; 32]),
+            recipient: Pubkey::new_from_array([3; 32]),
+            lamports: 42,
+        }
+    }
+
+    #[test]
+    fn decode_events() {
+        let event = VaultEvent::decode(&event_data(&closed())).unwrap();
+        assert_eq!(event, VaultEvent::VaultClosed(closed()));
+        assert_eq!(event.vault(), Pubkey::new_from_array([1; 32]));
+        assert_eq!(event.kind(), "VaultClosed");
+        assert_eq!(VaultEvent::decode(&[0; 8]), None);
+
+        let collected = FeesCollected {
+            fee_config: Pubkey::new_from_array([4; 32]),
+            destination: Pubkey::new_from_array([5; 32]),
+            lamports: 7,
+        };
+        let event = VaultEvent::decode(&event_data(&collected)).unwrap();
+        assert_eq!(event.vault(), collected.fee_config);
+        assert_eq!(event.kind(), "FeesCollected");
+    }
+
+    #[test]
+    fn events_in_logs_skips_other_programs() {
+        let program_id = vault::id();
+        let other = Pubkey::new_from_array([9; 32]);
+        let logs = vec![
+            format!("Program {program_id} invoke [1]"),
+            "Program log: VaultInstruction::CloseAccount".to_string(),
+            format!("Program {other} invoke [2]"),
+            data_log(&event_data(&closed())),
+            format!("Program {other} success"),
+            data_log(&event_data(&closed())),
+            format!("Program {program_id} success"),
+            data_log(&event_data(&closed())),
+        ];
+        assert_eq!(
+            events_in_logs(&program_id, &logs),
+            vec![VaultEvent::VaultClosed(closed())]
+        );
+        assert_eq!(program_data(&program_id, &logs).len(), 1);
+    }
+}
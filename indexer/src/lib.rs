@@ -0,0 +1,7 @@
+//! Indexer for the vault program. It tails the program's transactions over RPC,
+//! decodes the vault events they log, refreshes the vault records they touch, and
+//! keeps the current state of every vault, with its history, in SQLite.
+pub mod events;
+pub mod metrics;
+pub mod store;
+pub mod tail;
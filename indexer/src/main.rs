@@ -0,0 +1,72 @@
+//! Keep a SQLite database of the vaults of a program up to date.
+//!
+//! Usage: `vault-indexer <RPC_URL> <DB_PATH> [--program-id <PUBKEY>] [--interval-secs <N>]
+//! [--metrics-addr <HOST:PORT>]`
+//!
+//! With `--metrics-addr`, Prometheus metrics are served at `/metrics`.
+use {
+    solana_client::rpc_client::RpcClient,
+    solana_program::pubkey::Pubkey,
+    std::{process::exit, str::FromStr, thread::sleep, time::Duration},
+    vault_indexer::{metrics, store::Store, tail::Indexer},
+};
+
+const USAGE: &str = "Usage: vault-indexer <RPC_URL> <DB_PATH> [--program-id <PUBKEY>] \
+                     [--interval-secs <N>] [--metrics-addr <HOST:PORT>]";
+
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let mut positional = vec![];
+    let mut program_id = vault::id();
+    let mut interval = Duration::from_secs(5);
+    let mut metrics_addr = None;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--program-id" => {
+                program_id = args
+                    .next()
+                    .and_then(|key| Pubkey::from_str(&key).ok())
+                    .unwrap_or_else(|| fail("Invalid --program-id"));
+            }
+            "--interval-secs" => {
+                interval = args
+                    .next()
+                    .and_then(|secs| secs.parse().ok())
+                    .map(Duration::from_secs)
+                    .unwrap_or_else(|| fail("Invalid --interval-secs"));
+            }
+            "--metrics-addr" => {
+                metrics_addr = Some(
+                    args.next()
+                        .unwrap_or_else(|| fail("Invalid --metrics-addr")),
+                );
+            }
+            _ if !arg.starts_with('-') => positional.push(arg),
+            _ => fail(USAGE),
+        }
+    }
+    let [url, path] = <[String; 2]>::try_from(positional).unwrap_or_else(|_| fail(USAGE));
+
+    let store = Store::open(path).unwrap_or_else(|error| fail(&error.to_string()));
+    let mut indexer = Indexer::new(RpcClient::new(url), store, program_id);
+    if let Some(addr) = metrics_addr {
+        metrics::serve(addr, indexer.metrics())
+            .unwrap_or_else(|error| fail(&format!("Metrics server: {error}")));
+    }
+    indexer
+        .backfill()
+        .unwrap_or_else(|error| fail(&error.to_string()));
+    loop {
+        match indexer.poll() {
+            Ok(0) => {}
+            Ok(count) => eprintln!("Indexed {count} transactions"),
+            Err(error) => eprintln!("{error}"),
+        }
+        sleep(interval);
+    }
+}
+
+fn fail(message: &str) -> ! {
+    eprintln!("{message}");
+    exit(1)
+}
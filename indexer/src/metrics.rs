@@ -0,0 +1,209 @@
+//! Prometheus metrics of the indexer, served in the text exposition format.
+//!
+//! Transfers and closes are counters: alert on their `rate()`, such as a burst of
+//! `VaultClosed` events or authority transfers nobody scheduled.
+use {
+    crate::events::VaultEvent,
+    std::{
+        fmt::Write as _,
+        io::{self, BufRead, BufReader, Write},
+        net::{TcpListener, ToSocketAddrs},
+        sync::{Arc, Mutex, MutexGuard},
+        thread,
+    },
+};
+
+/// Upper bounds, in seconds, of the close latency histogram buckets.
+const LATENCY_BUCKETS: [f64; 8] = [1.0, 5.0, 15.0, 30.0, 60.0, 300.0, 900.0, 3600.0];
+
+/// Kinds of `VaultEvent`, each with its own event counter.
+const EVENT_KINDS: [&str; 8] = [
+    "AuthorityTransferred",
+    "VaultClosed",
+    "VaultRekeyed",
+    "NoteAdded",
+    "CorporateActionApplied",
+    "VotingDelegated",
+    "FeesCollected",
+    "CloseReceipt",
+];
+
+#[derive(Default)]
+struct Values {
+    vaults: u64,
+    events: [u64; EVENT_KINDS.len()],
+    decode_errors: u64,
+    close_latency_buckets: [u64; LATENCY_BUCKETS.len()],
+    close_latency_sum: f64,
+    close_latency_count: u64,
+}
+
+/// Metrics of an indexer, shared with the thread serving them.
+#[derive(Default)]
+pub struct Metrics {
+    values: Mutex<Values>,
+}
+
+impl Metrics {
+    /// Set the number of vaults in the store.
+    pub fn set_vaults(&self, vaults: u64) {
+        self.values().vaults = vaults;
+    }
+
+    /// Count an indexed event. A `VaultClosed` event also observes `latency_secs`,
+    /// the delay between its transaction's block time and its indexing, if known.
+    pub fn observe_event(&self, event: &VaultEvent, latency_secs: Option<f64>) {
+        let mut values = self.values();
+        if let Some(kind) = EVENT_KINDS.iter().position(|kind| *kind == event.kind()) {
+            values.events[kind] += 1;
+        }
+        if let (VaultEvent::VaultClosed(_), Some(latency)) = (event, latency_secs) {
+            let latency = latency.max(0.0);
+            for (bound, count) in LATENCY_BUCKETS
+                .iter()
+                .zip(&mut values.close_latency_buckets)
+            {
+                if latency <= *bound {
+                    *count += 1;
+                }
+            }
+            values.close_latency_sum += latency;
+            values.close_latency_count += 1;
+        }
+    }
+
+    /// Count event data logged by the program that did not decode as an event.
+    pub fn observe_decode_errors(&self, count: usize) {
+        self.values().decode_errors += count as u64;
+    }
+
+    /// The metrics in the Prometheus text exposition format.
+    pub fn render(&self) -> String {
+        let values = self.values();
+        let mut text = String::new();
+        family(
+            &mut text,
+            "vault_indexer_vaults",
+            "gauge",
+            "Vaults in the store.",
+        );
+        let _ = writeln!(text, "vault_indexer_vaults {}", values.vaults);
+
+        family(
+            &mut text,
+            "vault_indexer_events_total",
+            "counter",
+            "Vault events indexed, by kind.",
+        );
+        for (kind, count) in EVENT_KINDS.iter().zip(values.events) {
+            let _ = writeln!(
+                text,
+                "vault_indexer_events_total{{kind=\"{kind}\"}} {count}"
+            );
+        }
+
+        family(
+            &mut text,
+            "vault_indexer_decode_errors_total",
+            "counter",
+            "Event data logged by the vault program that failed to decode.",
+        );
+        let _ = writeln!(
+            text,
+            "vault_indexer_decode_errors_total {}",
+            values.decode_errors
+        );
+
+        family(
+            &mut text,
+            "vault_indexer_close_latency_seconds",
+            "histogram",
+            "Delay between a vault close landing and its indexing.",
+        );
+        for (bound, count) in LATENCY_BUCKETS.iter().zip(values.close_latency_buckets) {
+            let _ = writeln!(
+                text,
+                "vault_indexer_close_latency_seconds_bucket{{le=\"{bound}\"}} {count}"
+            );
+        }
+        let _ = writeln!(
+            text,
+            "vault_indexer_close_latency_seconds_bucket{{le=\"+Inf\"}} {count}\n\
+             vault_indexer_close_latency_seconds_sum {sum}\n\
+             vault_indexer_close_latency_seconds_count {count}",
+            count = values.close_latency_count,
+            sum = values.close_latency_sum,
+        );
+        text
+    }
+
+    fn values(&self) -> MutexGuard<'_, Values> {
+        self.values
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+// Start the metric family `name` of type `kind`.
+fn family(text: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(text, "# HELP {name} {help}\n# TYPE {name} {kind}");
+}
+
+/// Serve `metrics` at `http://<addr>/metrics` from a background thread.
+pub fn serve<A: ToSocketAddrs>(addr: A, metrics: Arc<Metrics>) -> io::Result<()> {
+    let listener = TcpListener::bind(addr)?;
+    thread::spawn(move || {
+        for mut stream in listener.incoming().flatten() {
+            let mut request = String::new();
+            let _ = BufReader::new(&stream).read_line(&mut request);
+            let response = if request.starts_with("GET /metrics ") {
+                let body = metrics.render();
+                format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: text/plain; version=0.0.4\r\n\
+                     Content-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                    body.len()
+                )
+            } else {
+                "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+                    .to_string()
+            };
+            let _ = stream.write_all(response.as_bytes());
+        }
+    });
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, solana_program::pubkey::Pubkey, vault::event::VaultClosed};
+
+    #[test]
+    fn render_counts() {
+        let metrics = Metrics::default();
+        let closed = VaultEvent::VaultClosed(VaultClosed {
+            vault: Pubkey::new_unique(),
+            authority: Pubkey::new_unique(),
+            recipient: Pubkey::new_unique(),
+            lamports: 1,
+        });
+        metrics.set_vaults(7);
+        metrics.observe_event(&closed, Some(10.0));
+        metrics.observe_event(&closed, None);
+        metrics.observe_decode_errors(2);
+
+        let text = metrics.render();
+        for line in [
+            "vault_indexer_vaults 7",
+            "vault_indexer_events_total{kind=\"VaultClosed\"} 2",
+            "vault_indexer_events_total{kind=\"AuthorityTransferred\"} 0",
+            "vault_indexer_decode_errors_total 2",
+            "vault_indexer_close_latency_seconds_bucket{le=\"5\"} 0",
+            "vault_indexer_close_latency_seconds_bucket{le=\"15\"} 1",
+            "vault_indexer_close_latency_seconds_bucket{le=\"+Inf\"} 1",
+            "vault_indexer_close_latency_seconds_sum 10",
+            "vault_indexer_close_latency_seconds_count 1",
+        ] {
+            assert!(text.lines().any(|rendered| rendered == line), "{line}");
+        }
+    }
+}
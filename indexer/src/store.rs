@@ -0,0 +1,306 @@
+//! SQLite tables of current vault state and its history.
+//!
+//! `vaults` holds a row per live vault record; `vault_history` a row per event and per
+//! observed change of a record, oldest first; `cursor` the last indexed transaction.
+use {
+    crate::events::VaultEvent,
+    rusqlite::{params, Connection, OptionalExtension},
+    solana_program::pubkey::Pubkey,
+    std::path::Path,
+    vault::client::VaultSnapshot,
+};
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS vaults (
+    address TEXT PRIMARY KEY,
+    slot INTEGER NOT NULL,
+    lamports INTEGER NOT NULL,
+    status TEXT NOT NULL,
+    authority TEXT NOT NULL,
+    dart TEXT NOT NULL,
+    tag INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS vault_history (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    address TEXT NOT NULL,
+    slot INTEGER NOT NULL,
+    signature TEXT,
+    kind TEXT NOT NULL,
+    detail TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS vault_history_address ON vault_history (address, id);
+CREATE TABLE IF NOT EXISTS cursor (
+    id INTEGER PRIMARY KEY CHECK (id = 0),
+    signature TEXT NOT NULL
+);
+";
+
+/// The current state of a vault, as stored.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VaultRow {
+    pub address: String,
+    pub slot: u64,
+    pub lamports: u64,
+    pub status: String,
+    pub authority: String,
+    pub dart: String,
+    pub tag: u16,
+}
+
+impl VaultRow {
+    fn new(snapshot: &VaultSnapshot, slot: u64) -> Self {
+        let record = &snapshot.record;
+        Self {
+            address: snapshot.address.to_string(),
+            slot,
+            lamports: snapshot.lamports,
+            status: format!("{:?}", record.status),
+            authority: record.authority.to_string(),
+            dart: record.dart.to_string(),
+            tag: record.tag,
+        }
+    }
+
+    // Whether the rows hold the same state, whenever it was seen.
+    fn same_state(&self, other: &Self) -> bool {
+        (
+            self.lamports,
+            &self.status,
+            &self.authority,
+            &self.dart,
+            self.tag,
+        ) == (
+            other.lamports,
+            &other.status,
+            &other.authority,
+            &other.dart,
+            other.tag,
+        )
+    }
+}
+
+/// Vault state store.
+pub struct Store {
+    connection: Connection,
+}
+
+impl Store {
+    /// Open, creating if needed, the database at `path`.
+    pub fn open<P: AsRef<Path>>(path: P) -> rusqlite::Result<Self> {
+        Self::with_connection(Connection::open(path)?)
+    }
+
+    /// Open a database held in memory.
+    pub fn open_in_memory() -> rusqlite::Result<Self> {
+        Self::with_connection(Connection::open_in_memory()?)
+    }
+
+    fn with_connection(connection: Connection) -> rusqlite::Result<Self> {
+        connection.execute_batch(SCHEMA)?;
+        Ok(Self { connection })
+    }
+
+    /// The stored state of the vault at `address`.
+    pub fn vault(&self, address: &Pubkey) -> rusqlite::Result<Option<VaultRow>> {
+        self.connection
+            .query_row(
+                "SELECT address, slot, lamports, status, authority, dart, tag
+                 FROM vaults WHERE address = ?1",
+                params![address.to_string()],
+                |row| {
+                    Ok(VaultRow {
+                        address: row.get(0)?,
+                        slot: row.get(1)?,
+                        lamports: row.get(2)?,
+                        status: row.get(3)?,
+                        authority: row.get(4)?,
+                        dart: row.get(5)?,
+                        tag: row.get(6)?,
+                    })
+                },
+            )
+            .optional()
+    }
+
+    /// History of the vault at `address`, oldest first: `(slot, kind, detail)`.
+    pub fn history(&self, address: &Pubkey) -> rusqlite::Result<Vec<(u64, String, String)>> {
+        let mut statement = self.connection.prepare(
+            "SELECT slot, kind, detail FROM vault_history WHERE address = ?1 ORDER BY id",
+        )?;
+        let rows = statement.query_map(params![address.to_string()], |row| {
+            Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+        })?;
+        let history = rows.collect::<rusqlite::Result<_>>()?;
+        Ok(history)
+    }
+
+    /// Store the state of a vault seen at `slot`, adding a history row when it changed.
+    pub fn upsert_vault(
+        &self,
+        snapshot: &VaultSnapshot,
+        slot: u64,
+        signature: Option<&str>,
+    ) -> rusqlite::Result<()> {
+        let row = VaultRow::new(snapshot, slot);
+        if let Some(current) = self.vault(&snapshot.address)? {
+            if current.same_state(&row) {
+                return Ok(());
+            }
+        }
+        self.connection.execute(
+            "INSERT INTO vaults (address, slot, lamports, status, authority, dart, tag)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+             ON CONFLICT (address) DO UPDATE SET slot = ?2, lamports = ?3, status = ?4,
+                 authority = ?5, dart = ?6, tag = ?7",
+            params![
+                row.address,
+                row.slot,
+                row.lamports,
+                row.status,
+                row.authority,
+                row.dart,
+                row.tag
+            ],
+        )?;
+        let detail = format!(
+            "status {}, authority {}, dart {}, tag {}, {} lamports",
+            row.status, row.authority, row.dart, row.tag, row.lamports
+        );
+        self.add_history(&snapshot.address, slot, signature, "Updated", &detail)
+    }
+
+    /// Drop the vault at `address`, whose account no longer holds a record.
+    pub fn remove_vault(
+        &self,
+        address: &Pubkey,
+        slot: u64,
+        signature: Option<&str>,
+    ) -> rusqlite::Result<()> {
+        let removed = self.connection.execute(
+            "DELETE FROM vaults WHERE address = ?1",
+            params![address.to_string()],
+        )?;
+        if removed == 0 {
+            return Ok(());
+        }
+        self.add_history(address, slot, signature, "Removed", "account closed")
+    }
+
+    /// Number of stored vaults.
+    pub fn vault_count(&self) -> rusqlite::Result<u64> {
+        self.connection
+            .query_row("SELECT COUNT(*) FROM vaults", [], |row| row.get(0))
+    }
+
+    /// Addresses of the stored vaults among `addresses`.
+    pub fn known_vaults(&self, addresses: &[Pubkey]) -> rusqlite::Result<Vec<Pubkey>> {
+        let mut known = vec![];
+        for address in addresses {
+            if self.vault(address)?.is_some() {
+                known.push(*address);
+            }
+        }
+        Ok(known)
+    }
+
+    /// Record an event logged at `slot` by transaction `signature`.
+    pub fn record_event(
+        &self,
+        event: &VaultEvent,
+        slot: u64,
+        signature: &str,
+    ) -> rusqlite::Result<()> {
+        self.add_history(
+            &event.vault(),
+            slot,
+            Some(signature),
+            event.kind(),
+            &event.detail(),
+        )
+    }
+
+    fn add_history(
+        &self,
+        address: &Pubkey,
+        slot: u64,
+        signature: Option<&str>,
+        kind: &str,
+        detail: &str,
+    ) -> rusqlite::Result<()> {
+        self.connection.execute(
+            "INSERT INTO vault_history (address, slot, signature, kind, detail)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![address.to_string(), slot, signature, kind, detail],
+        )?;
+        Ok(())
+    }
+
+    /// The last indexed transaction.
+    pub fn cursor(&self) -> rusqlite::Result<Option<String>> {
+        self.connection
+            .query_row("SELECT signature FROM cursor WHERE id = 0", [], |row| {
+                row.get(0)
+            })
+            .optional()
+    }
+
+    /// Mark transaction `signature` as the last indexed.
+    pub fn set_cursor(&self, signature: &str) -> rusqlite::Result<()> {
+        self.connection.execute(
+            "INSERT INTO cursor (id, signature) VALUES (0, ?1)
+             ON CONFLICT (id) DO UPDATE SET signature = ?1",
+            params![signature],
+        )?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, vault::state::VaultRecord};
+
+    fn snapshot() -> VaultSnapshot {
+        VaultSnapshot {
+            address: Pubkey::new_from_array([1; 32]),
+            lamports: 42,
+            record: VaultRecord::new(
+                Pubkey::new_from_array([2; 32]),
+                Pubkey::new_from_array([3; 32]),
+            ),
+        }
+    }
+
+    #[test]
+    fn upsert_tracks_changes() {
+        let store = Store::open_in_memory().unwrap();
+        let mut snapshot = snapshot();
+        store.upsert_vault(&snapshot, 10, None).unwrap();
+        store.upsert_vault(&snapshot, 11, Some("sig")).unwrap();
+        let row = store.vault(&snapshot.address).unwrap().unwrap();
+        assert_eq!(row.slot, 10);
+        assert_eq!(row.status, "Active");
+
+        snapshot.record.authority = Pubkey::new_from_array([4; 32]);
+        store.upsert_vault(&snapshot, 12, Some("sig")).unwrap();
+        let row = store.vault(&snapshot.address).unwrap().unwrap();
+        assert_eq!(row.slot, 12);
+        assert_eq!(row.authority, snapshot.record.authority.to_string());
+        assert_eq!(store.history(&snapshot.address).unwrap().len(), 2);
+
+        store.remove_vault(&snapshot.address, 13, None).unwrap();
+        store.remove_vault(&snapshot.address, 14, None).unwrap();
+        assert_eq!(store.vault(&snapshot.address).unwrap(), None);
+        let history = store.history(&snapshot.address).unwrap();
+        assert_eq!(history.len(), 3);
+        assert_eq!(history[2].1, "Removed");
+    }
+
+    #[test]
+    fn cursor_round_trip() {
+        let store = Store::open_in_memory().unwrap();
+        assert_eq!(store.cursor().unwrap(), None);
+        store.set_cursor("a").unwrap();
+        store.set_cursor("b").unwrap();
+        assert_eq!(store.cursor().unwrap().as_deref(), Some("b"));
+    }
+}
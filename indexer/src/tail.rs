@@ -0,0 +1,226 @@
+//! Tailing the vault program over RPC.
+use {
+    crate::{
+        events::{program_data, VaultEvent},
+        metrics::Metrics,
+        store::Store,
+    },
+    solana_client::{
+        client_error::ClientError,
+        rpc_client::{GetConfirmedSignaturesForAddress2Config, RpcClient},
+        rpc_config::RpcTransactionConfig,
+    },
+    solana_program::pubkey::Pubkey,
+    solana_sdk::{commitment_config::CommitmentConfig, signature::Signature},
+    solana_transaction_status::{UiLoadedAddresses, UiTransactionEncoding},
+    std::{
+        fmt,
+        str::FromStr,
+        sync::Arc,
+        time::{SystemTime, UNIX_EPOCH},
+    },
+    vault::client::{export_vaults, fetch_vaults},
+};
+
+/// Most signatures returned by one `getSignaturesForAddress` request.
+const SIGNATURE_PAGE: usize = 1000;
+
+/// Failure to fetch from the chain or to store what was fetched.
+#[derive(Debug)]
+pub enum Error {
+    Rpc(ClientError),
+    Store(rusqlite::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Rpc(error) => write!(f, "RPC error: {error}"),
+            Self::Store(error) => write!(f, "Store error: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<ClientError> for Error {
+    fn from(error: ClientError) -> Self {
+        Self::Rpc(error)
+    }
+}
+
+impl From<rusqlite::Error> for Error {
+    fn from(error: rusqlite::Error) -> Self {
+        Self::Store(error)
+    }
+}
+
+/// Keeps a `Store` up to date with the vaults of a program.
+pub struct Indexer {
+    rpc: RpcClient,
+    store: Store,
+    program_id: Pubkey,
+    metrics: Arc<Metrics>,
+}
+
+impl Indexer {
+    pub fn new(rpc: RpcClient, store: Store, program_id: Pubkey) -> Self {
+        Self {
+            rpc,
+            store,
+            program_id,
+            metrics: Arc::default(),
+        }
+    }
+
+    /// The store being kept up to date.
+    pub fn store(&self) -> &Store {
+        &self.store
+    }
+
+    /// Metrics of the indexing, for `metrics::serve`.
+    pub fn metrics(&self) -> Arc<Metrics> {
+        self.metrics.clone()
+    }
+
+    /// Load every current vault into an empty store, starting the cursor at the
+    /// program's latest transaction. Does nothing once a cursor is set.
+    pub fn backfill(&mut self) -> Result<(), Error> {
+        if self.store.cursor()?.is_some() {
+            return Ok(());
+        }
+        // Take the cursor first: transactions landing during the export are replayed.
+        let latest = self.signatures(None, None, 1)?;
+        let slot = self.rpc.get_slot()?;
+        for snapshot in export_vaults(&self.rpc, &self.program_id)? {
+            self.store.upsert_vault(&snapshot, slot, None)?;
+        }
+        if let Some((signature, _)) = latest.first() {
+            self.store.set_cursor(signature)?;
+        }
+        self.metrics.set_vaults(self.store.vault_count()?);
+        Ok(())
+    }
+
+    /// Index the program's transactions since the cursor, oldest first, returning how
+    /// many were indexed. Events of each successful transaction go to the history, and
+    /// the vault records among its accounts are refreshed from their current state.
+    pub fn poll(&mut self) -> Result<usize, Error> {
+        let until = self
+            .store
+            .cursor()?
+            .and_then(|signature| Signature::from_str(&signature).ok());
+        let mut signatures = vec![];
+        let mut before = None;
+        loop {
+            let page = self.signatures(before, until, SIGNATURE_PAGE)?;
+            let done = page.len() < SIGNATURE_PAGE;
+            before = page
+                .last()
+                .and_then(|(signature, _)| Signature::from_str(signature).ok());
+            signatures.extend(page);
+            if done || before.is_none() {
+                break;
+            }
+        }
+        signatures.reverse();
+
+        let count = signatures.len();
+        for (signature, failed) in signatures {
+            if !failed {
+                self.index_transaction(&signature)?;
+            }
+            self.store.set_cursor(&signature)?;
+        }
+        self.metrics.set_vaults(self.store.vault_count()?);
+        Ok(count)
+    }
+
+    // Signatures of the program's transactions, newest first, with whether each failed.
+    fn signatures(
+        &self,
+        before: Option<Signature>,
+        until: Option<Signature>,
+        limit: usize,
+    ) -> Result<Vec<(String, bool)>, Error> {
+        let statuses = self.rpc.get_signatures_for_address_with_config(
+            &self.program_id,
+            GetConfirmedSignaturesForAddress2Config {
+                before,
+                until,
+                limit: Some(limit),
+                commitment: Some(CommitmentConfig::confirmed()),
+            },
+        )?;
+        Ok(statuses
+            .into_iter()
+            .map(|status| (status.signature, status.err.is_some()))
+            .collect())
+    }
+
+    fn index_transaction(&mut self, signature: &str) -> Result<(), Error> {
+        let Ok(parsed) = Signature::from_str(signature) else {
+            return Ok(());
+        };
+        let transaction = self.rpc.get_transaction_with_config(
+            &parsed,
+            RpcTransactionConfig {
+                encoding: Some(UiTransactionEncoding::Base64),
+                commitment: Some(CommitmentConfig::confirmed()),
+                max_supported_transaction_version: Some(0),
+            },
+        )?;
+        let slot = transaction.slot;
+        let Some(meta) = transaction.transaction.meta else {
+            return Ok(());
+        };
+
+        let logs: Option<Vec<String>> = meta.log_messages.into();
+        let logs = logs.unwrap_or_default();
+        let data = program_data(&self.program_id, &logs);
+        let events: Vec<VaultEvent> = data
+            .iter()
+            .filter_map(|data| VaultEvent::decode_base64(data))
+            .collect();
+        self.metrics
+            .observe_decode_errors(data.len() - events.len());
+        let latency = transaction.block_time.and_then(|block_time| {
+            let now = SystemTime::now().duration_since(UNIX_EPOCH).ok()?;
+            Some(now.as_secs_f64() - block_time as f64)
+        });
+        for event in &events {
+            self.store.record_event(event, slot, signature)?;
+            self.metrics.observe_event(event, latency);
+        }
+
+        let mut accounts: Vec<Pubkey> = transaction
+            .transaction
+            .transaction
+            .decode()
+            .map(|transaction| transaction.message.static_account_keys().to_vec())
+            .unwrap_or_default();
+        let loaded: Option<UiLoadedAddresses> = meta.loaded_addresses.into();
+        accounts.extend(
+            loaded
+                .into_iter()
+                .flat_map(|loaded| loaded.writable)
+                .filter_map(|address| Pubkey::from_str(&address).ok()),
+        );
+        self.refresh(&accounts, slot, signature)
+    }
+
+    // Store the current state of the vault records among `accounts`, and drop stored
+    // vaults whose record is gone.
+    fn refresh(&mut self, accounts: &[Pubkey], slot: u64, signature: &str) -> Result<(), Error> {
+        let snapshots = fetch_vaults(&self.rpc, &self.program_id, accounts)?;
+        for snapshot in &snapshots {
+            self.store.upsert_vault(snapshot, slot, Some(signature))?;
+        }
+        for address in self.store.known_vaults(accounts)? {
+            if !snapshots.iter().any(|snapshot| snapshot.address == address) {
+                self.store.remove_vault(&address, slot, Some(signature))?;
+            }
+        }
+        Ok(())
+    }
+}
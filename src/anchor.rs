@@ -0,0 +1,590 @@
+//! Anchor interoperability: account traits for the program's accounts, and Anchor-style
+//! instruction sighashes, described by `idl/vault.json`.
+use {
+    crate::{
+        instruction::VaultInstruction,
+        state::{
+            ApprovalRoot, AttestationList, AuditTrail, CommitmentRecord, DartIdentity, DartPolicy,
+            DistributionCursor, FeeConfig, InheritanceRecord, NoteRecord, NotesLog, Notification,
+            NotifyRecord, PledgeRecord, PositionRecord, ProgramConfig, ProgramStats, RateLimit,
+            RecoveryRecord, SessionRecord, SnapshotRecord, TransferHookConfig, TransferWindow,
+            VaultIndex, VaultRecord, VotingProxy,
+        },
+    },
+    anchor_lang::{
+        error::ErrorCode, AccountDeserialize, AccountSerialize, Discriminator, Owner, Result,
+    },
+    borsh::{BorshDeserialize, BorshSerialize},
+    solana_program::{program_error::ProgramError, program_pack::IsInitialized, pubkey::Pubkey},
+    std::io::Write,
+};
+
+/// Anchor sighashes (`sha256("global:<name>")[..8]`), indexed by native instruction tag.
+pub const INSTRUCTION_DISCRIMINATORS: [[u8; 8]; 71] = [
+    // initialize
+    [0xaf, 0xaf, 0x6d, 0x1f, 0x0d, 0x98, 0x9b, 0xed],
+    // transfer_authority
+    [0x30, 0xa9, 0x4c, 0x48, 0xe5, 0xb4, 0x37, 0xa1],
+    // close_account
+    [0x7d, 0xff, 0x95, 0x0e, 0x6e, 0x22, 0x48, 0x18],
+    // reopen
+    [0x95, 0xcd, 0x83, 0x26, 0xc0, 0x57, 0x6f, 0xa6],
+    // propose_dart
+    [0xd9, 0x17, 0x52, 0xf5, 0x23, 0x2a, 0xbf, 0x34],
+    // accept_dart
+    [0x60, 0xe6, 0x2b, 0x86, 0x9d, 0x07, 0x1c, 0xb3],
+    // initialize_dart_identity
+    [0x54, 0x00, 0xb5, 0x21, 0x35, 0x3c, 0x6e, 0x30],
+    // set_operational_key
+    [0xf3, 0x85, 0x5c, 0xe7, 0xda, 0xfc, 0x0e, 0x98],
+    // create_session
+    [0xf2, 0xc1, 0x8f, 0xb3, 0x96, 0x19, 0x7a, 0xe3],
+    // add_guardian
+    [0xa7, 0xbd, 0xaa, 0x1b, 0x4a, 0xf0, 0xc9, 0xf1],
+    // remove_guardian
+    [0x48, 0x75, 0xa0, 0xf4, 0x9b, 0xb9, 0x47, 0x12],
+    // initiate_recovery
+    [0x84, 0x94, 0x3c, 0x4a, 0x31, 0xb2, 0xeb, 0xbb],
+    // approve_recovery
+    [0x94, 0x60, 0x29, 0x26, 0x6c, 0xbd, 0x81, 0xd6],
+    // cancel_recovery
+    [0xb0, 0x17, 0xcb, 0x25, 0x79, 0xfb, 0xe3, 0x53],
+    // complete_recovery
+    [0xc3, 0xec, 0x05, 0x05, 0x83, 0x20, 0xc4, 0x6e],
+    // set_beneficiary
+    [0x0a, 0x51, 0xdb, 0x04, 0xed, 0x95, 0x39, 0xf2],
+    // check_in
+    [0xd1, 0xfd, 0x04, 0xd9, 0xfa, 0xf1, 0xcf, 0x32],
+    // claim_inheritance
+    [0xfa, 0x22, 0x09, 0x3f, 0x9b, 0x2b, 0xa5, 0xf9],
+    // set_tag
+    [0x76, 0x89, 0xfc, 0x88, 0x7c, 0x62, 0x19, 0x76],
+    // initialize_sized
+    [0xbd, 0xb9, 0x5c, 0xf7, 0x4c, 0x85, 0x6e, 0x44],
+    // grow_account
+    [0x3d, 0xf6, 0x66, 0x91, 0x5b, 0xc2, 0x5e, 0xed],
+    // close_account_with_refund
+    [0x7c, 0xb5, 0xa8, 0x11, 0xa5, 0xc0, 0x62, 0x9b],
+    // record_attestation
+    [0x94, 0x2b, 0xe1, 0x4d, 0x0f, 0x86, 0xd9, 0x36],
+    // swap_authorities
+    [0xa4, 0x77, 0xb1, 0x4b, 0x47, 0xf6, 0xb7, 0xb5],
+    // propose_transfer
+    [0x8c, 0x56, 0x85, 0x7c, 0xfd, 0xe2, 0xfb, 0xc3],
+    // accept_transfer
+    [0x5e, 0xf9, 0xab, 0x3e, 0xd0, 0x78, 0x31, 0x6e],
+    // expire_transfer
+    [0x78, 0xdc, 0x16, 0xbf, 0xea, 0x46, 0xcd, 0x75],
+    // set_oracle
+    [0xba, 0x80, 0x51, 0x68, 0x4a, 0x4f, 0x12, 0xe0],
+    // mint_receipt
+    [0xeb, 0x2b, 0xc0, 0x59, 0x44, 0x2f, 0x49, 0x32],
+    // set_receipt_authority
+    [0xb0, 0x72, 0xba, 0xbc, 0xea, 0x08, 0x22, 0xad],
+    // initialize_transfer_hook
+    [0x16, 0xa6, 0x13, 0xf7, 0xc1, 0x3e, 0xba, 0x8f],
+    // set_allowlist_entry
+    [0x62, 0x65, 0xb0, 0x77, 0x03, 0x63, 0xc6, 0xca],
+    // rekey
+    [0xdc, 0x78, 0x8d, 0x53, 0xd2, 0x6a, 0xd8, 0x90],
+    // transfer_authority_checked
+    [0xe1, 0x3c, 0xd7, 0x7d, 0xc4, 0xef, 0x96, 0x4b],
+    // close_account_checked
+    [0x46, 0x3c, 0x05, 0xbc, 0xe9, 0xef, 0xfc, 0x80],
+    // set_dart_quorum
+    [0x54, 0x56, 0x72, 0xfc, 0xac, 0xd0, 0x5c, 0xad],
+    // initialize_config
+    [0xd0, 0x7f, 0x15, 0x01, 0xc2, 0xbe, 0xc4, 0x46],
+    // decommission
+    [0xaf, 0x7e, 0x1c, 0x7a, 0x56, 0x10, 0x6b, 0x4f],
+    // migrate_to_program
+    [0xff, 0xb1, 0x74, 0xc9, 0x6e, 0x72, 0x3a, 0x4a],
+    // set_approval_root
+    [0xc8, 0xef, 0x74, 0x3d, 0xd0, 0x70, 0xf8, 0x4c],
+    // transfer_with_proof
+    [0x98, 0x00, 0x57, 0xf6, 0x80, 0x6f, 0x8d, 0xe0],
+    // set_note_key
+    [0xec, 0x14, 0x3a, 0xaa, 0x16, 0x99, 0x96, 0x9e],
+    // set_note
+    [0x9e, 0x91, 0xbc, 0xdb, 0xa8, 0x40, 0x04, 0x7f],
+    // update_commitments
+    [0x3f, 0x47, 0xee, 0x66, 0x82, 0x4d, 0xd7, 0x0e],
+    // add_note
+    [0x26, 0x94, 0x4c, 0x14, 0x21, 0x14, 0xf1, 0xfb],
+    // add_attestation
+    [0xce, 0x52, 0x81, 0xaa, 0x36, 0x9f, 0xa1, 0x9c],
+    // remove_attestation
+    [0x3c, 0x88, 0xa0, 0x9d, 0x77, 0xb6, 0x18, 0xbe],
+    // pledge_as_collateral
+    [0x85, 0xdd, 0x2a, 0x0c, 0xf9, 0xe4, 0x4f, 0x40],
+    // release_collateral
+    [0x28, 0xff, 0x0c, 0xda, 0xf9, 0xc5, 0xb3, 0xa0],
+    // set_position
+    [0x1f, 0x3d, 0x52, 0x1e, 0xf4, 0xe3, 0x5d, 0x56],
+    // apply_corporate_action
+    [0x48, 0xd9, 0x97, 0x17, 0x9c, 0xe9, 0x49, 0x4f],
+    // distribute
+    [0xbf, 0x2c, 0xdf, 0xcf, 0xa4, 0xec, 0x7e, 0x3d],
+    // snapshot
+    [0x90, 0xec, 0x06, 0x85, 0xe9, 0xa0, 0x15, 0x5e],
+    // delegate_voting
+    [0xab, 0x4e, 0x77, 0x79, 0x20, 0x55, 0x53, 0xae],
+    // set_transfer_window
+    [0xae, 0x97, 0xa4, 0xc1, 0x60, 0x8f, 0xfc, 0xd0],
+    // set_dart_transfer_window
+    [0xfd, 0x5d, 0x51, 0xad, 0xe9, 0x97, 0x92, 0x78],
+    // set_rate_limit
+    [0x2a, 0xd4, 0x2c, 0x5b, 0xc6, 0x3a, 0x3c, 0xef],
+    // set_dart_policy
+    [0xcc, 0x98, 0xf2, 0xa5, 0xba, 0x79, 0x99, 0x89],
+    // clone_vault
+    [0xa4, 0xe8, 0xd3, 0x50, 0x39, 0xdd, 0x8c, 0x3e],
+    // create_asset_vault
+    [0x5e, 0x1e, 0xf7, 0x27, 0x7d, 0x83, 0x4a, 0x29],
+    // sync_vault_index
+    [0xa0, 0x35, 0x88, 0x1a, 0x32, 0x18, 0x99, 0x05],
+    // set_crank_rules
+    [0x38, 0xeb, 0x36, 0x7e, 0xd0, 0x72, 0x61, 0x6b],
+    // crank
+    [0x00, 0xe8, 0x03, 0xc3, 0x7c, 0x75, 0x69, 0x35],
+    // set_fee_exemption
+    [0x31, 0xdd, 0x63, 0xb9, 0x16, 0xe4, 0xba, 0xa0],
+    // set_fee_destination
+    [0x49, 0xfa, 0x86, 0x83, 0xde, 0xd3, 0x9a, 0x4e],
+    // collect_fees
+    [0xa4, 0x98, 0xcf, 0x63, 0x1e, 0xba, 0x13, 0xb6],
+    // create_audit_trail
+    [0xca, 0xb0, 0x11, 0x17, 0xe4, 0xb9, 0xdb, 0xc4],
+    // initialize_stats
+    [0x90, 0xc9, 0x75, 0x4c, 0x7f, 0x76, 0xb0, 0x10],
+    // set_close_fees
+    [0x84, 0x5f, 0x58, 0xa7, 0x7c, 0x03, 0xe9, 0x10],
+    // set_notify
+    [0x1d, 0x25, 0x98, 0xb5, 0x3b, 0x32, 0xf6, 0x64],
+    // check_transfer
+    [0xb5, 0x62, 0x03, 0xdb, 0x8f, 0x46, 0x19, 0xd7],
+];
+
+/// Decode instruction data prefixed with either an Anchor sighash or the native tag.
+pub fn unpack_instruction(input: &[u8]) -> std::result::Result<VaultInstruction, ProgramError> {
+    let tag = input.get(..8).and_then(|prefix| {
+        INSTRUCTION_DISCRIMINATORS
+            .iter()
+            .position(|discriminator| discriminator.as_slice() == prefix)
+    });
+    match tag {
+        Some(tag) => {
+            let mut data = Vec::with_capacity(input.len() - 7);
+            data.push(tag as u8);
+            data.extend_from_slice(&input[8..]);
+            VaultInstruction::unpack(&data)
+        }
+        None => VaultInstruction::unpack(input),
+    }
+}
+
+// Anchor account traits for a program-owned, discriminator-prefixed borsh account.
+// With `custom`, the account provides its own serialization impls.
+macro_rules! anchor_account {
+    ($account:ty, $discriminator:expr, custom) => {
+        impl Discriminator for $account {
+            const DISCRIMINATOR: [u8; 8] = $discriminator;
+        }
+
+        impl Owner for $account {
+            fn owner() -> Pubkey {
+                crate::id()
+            }
+        }
+    };
+    ($account:ty, $discriminator:expr) => {
+        anchor_account!($account, $discriminator, custom);
+
+        impl AccountDeserialize for $account {
+            fn try_deserialize(buf: &mut &[u8]) -> Result<Self> {
+                let account = Self::try_deserialize_unchecked(buf)?;
+                if !account.is_initialized() {
+                    return Err(ErrorCode::AccountNotInitialized.into());
+                }
+                Ok(account)
+            }
+
+            fn try_deserialize_unchecked(buf: &mut &[u8]) -> Result<Self> {
+                Self::deserialize(buf).map_err(|_| ErrorCode::AccountDidNotDeserialize.into())
+            }
+        }
+
+        impl AccountSerialize for $account {
+            fn try_serialize<W: Write>(&self, writer: &mut W) -> Result<()> {
+                self.serialize(writer)
+                    .map_err(|_| ErrorCode::AccountDidNotSerialize.into())
+            }
+        }
+    };
+}
+
+// `sha256("account:VaultRecord")[..8]`
+anchor_account!(
+    VaultRecord,
+    [0x2f, 0x01, 0xda, 0x74, 0x52, 0x46, 0x7c, 0x77],
+    custom
+);
+
+// Vault records are read and written in the layout of their version.
+impl AccountDeserialize for VaultRecord {
+    fn try_deserialize(buf: &mut &[u8]) -> Result<Self> {
+        let account = Self::try_deserialize_unchecked(buf)?;
+        if !account.is_initialized() {
+            return Err(ErrorCode::AccountNotInitialized.into());
+        }
+        Ok(account)
+    }
+
+    fn try_deserialize_unchecked(buf: &mut &[u8]) -> Result<Self> {
+        let account = Self::from_account_data(buf)
+            .map_err(|_| anchor_lang::error::Error::from(ErrorCode::AccountDidNotDeserialize))?;
+        *buf = &buf[account.header_len()..];
+        Ok(account)
+    }
+}
+
+impl AccountSerialize for VaultRecord {
+    fn try_serialize<W: Write>(&self, writer: &mut W) -> Result<()> {
+        let mut header = [0; Self::LEN];
+        self.pack_into(&mut header)
+            .and_then(|()| Ok(writer.write_all(&header[..self.header_len()])?))
+            .map_err(|_| ErrorCode::AccountDidNotSerialize.into())
+    }
+}
+// `sha256("account:DartIdentity")[..8]`
+anchor_account!(
+    DartIdentity,
+    [0x93, 0xa4, 0xdb, 0xfd, 0x47, 0x8f, 0xe8, 0x63]
+);
+// `sha256("account:SessionRecord")[..8]`
+anchor_account!(
+    SessionRecord,
+    [0x91, 0x3b, 0xce, 0x77, 0xf2, 0xc3, 0x14, 0xaa]
+);
+// `sha256("account:RecoveryRecord")[..8]`
+anchor_account!(
+    RecoveryRecord,
+    [0x61, 0x42, 0xae, 0x9b, 0x4f, 0xe3, 0x91, 0xc4]
+);
+// `sha256("account:InheritanceRecord")[..8]`
+anchor_account!(
+    InheritanceRecord,
+    [0x7f, 0xfb, 0xb5, 0xf2, 0x94, 0x4a, 0x21, 0x51]
+);
+// `sha256("account:TransferHookConfig")[..8]`
+anchor_account!(
+    TransferHookConfig,
+    [0xa8, 0x7a, 0xbf, 0xdd, 0xcc, 0xc9, 0x95, 0x58]
+);
+// `sha256("account:ProgramConfig")[..8]`
+anchor_account!(
+    ProgramConfig,
+    [0xc4, 0xd2, 0x5a, 0xe7, 0x90, 0x95, 0x8c, 0x3f]
+);
+// `sha256("account:ApprovalRoot")[..8]`
+anchor_account!(
+    ApprovalRoot,
+    [0x02, 0x34, 0xba, 0x1f, 0x28, 0x30, 0xaa, 0x33]
+);
+// `sha256("account:NoteRecord")[..8]`
+anchor_account!(NoteRecord, [0x89, 0xcb, 0xaf, 0x8f, 0xf0, 0x4d, 0xab, 0xda]);
+// `sha256("account:CommitmentRecord")[..8]`
+anchor_account!(
+    CommitmentRecord,
+    [0xd8, 0xc3, 0x8a, 0x63, 0x58, 0xbf, 0x20, 0xf6]
+);
+// `sha256("account:NotesLog")[..8]`
+anchor_account!(NotesLog, [0x27, 0x33, 0xc0, 0xb1, 0x5a, 0x99, 0x8c, 0xef]);
+// `sha256("account:AttestationList")[..8]`
+anchor_account!(
+    AttestationList,
+    [0x06, 0x0a, 0xd8, 0x5e, 0x6d, 0x17, 0x34, 0x19]
+);
+// `sha256("account:PledgeRecord")[..8]`
+anchor_account!(
+    PledgeRecord,
+    [0xe5, 0xb9, 0x43, 0xd9, 0x8e, 0x1f, 0x99, 0xbb]
+);
+// `sha256("account:PositionRecord")[..8]`
+anchor_account!(
+    PositionRecord,
+    [0x6c, 0xcc, 0x2d, 0xcf, 0xa8, 0xff, 0xd5, 0x9e]
+);
+// `sha256("account:DistributionCursor")[..8]`
+anchor_account!(
+    DistributionCursor,
+    [0x28, 0x91, 0x3d, 0xb8, 0x90, 0x7e, 0x85, 0x1b]
+);
+// `sha256("account:SnapshotRecord")[..8]`
+anchor_account!(
+    SnapshotRecord,
+    [0xe0, 0xa9, 0x94, 0x14, 0x4f, 0xc3, 0x20, 0x6a]
+);
+// `sha256("account:VotingProxy")[..8]`
+anchor_account!(
+    VotingProxy,
+    [0xd2, 0xca, 0x5e, 0xda, 0x8e, 0x4f, 0xe0, 0xdc]
+);
+// `sha256("account:TransferWindow")[..8]`
+anchor_account!(
+    TransferWindow,
+    [0x42, 0x64, 0xd2, 0xf5, 0x0e, 0xe8, 0x30, 0x28]
+);
+// `sha256("account:RateLimit")[..8]`
+anchor_account!(RateLimit, [0x87, 0x1e, 0x6b, 0xb7, 0xbb, 0x9f, 0x4b, 0xca]);
+// `sha256("account:DartPolicy")[..8]`
+anchor_account!(DartPolicy, [0x52, 0x77, 0x1f, 0xdb, 0x99, 0x20, 0x83, 0xb4]);
+// `sha256("account:VaultIndex")[..8]`
+anchor_account!(VaultIndex, [0xd3, 0xe1, 0x6e, 0x04, 0xfe, 0xdc, 0x2b, 0xa0]);
+// `sha256("account:FeeConfig")[..8]`
+anchor_account!(FeeConfig, [0x8f, 0x34, 0x92, 0xbb, 0xdb, 0x7b, 0x4c, 0x9b]);
+// `sha256("account:AuditTrail")[..8]`
+anchor_account!(AuditTrail, [0xab, 0xdf, 0xfd, 0xb5, 0x86, 0x58, 0x42, 0x1a]);
+// `sha256("account:ProgramStats")[..8]`
+anchor_account!(
+    ProgramStats,
+    [0x3c, 0xb8, 0x16, 0x07, 0x13, 0x0a, 0x6a, 0x6f]
+);
+// `sha256("account:NotifyRecord")[..8]`
+anchor_account!(
+    NotifyRecord,
+    [0x3d, 0x60, 0x26, 0x21, 0x50, 0xe0, 0x9a, 0x57]
+);
+// `sha256("account:Notification")[..8]`
+anchor_account!(
+    Notification,
+    [0x44, 0x69, 0x2e, 0x77, 0x84, 0x4b, 0xc1, 0xd6]
+);
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*, crate::state::tests::TEST_RECORD_DATA, solana_program::hash::hash,
+        spl_discriminator::SplDiscriminate,
+    };
+
+    fn sighash(preimage: &str) -> [u8; 8] {
+        hash(preimage.as_bytes()).to_bytes()[..8]
+            .try_into()
+            .unwrap()
+    }
+
+    #[test]
+    fn discriminators_match_sighash() {
+        assert_eq!(VaultRecord::DISCRIMINATOR, sighash("account:VaultRecord"));
+        assert_eq!(
+            VaultRecord::DISCRIMINATOR,
+            <VaultRecord as SplDiscriminate>::SPL_DISCRIMINATOR_SLICE
+        );
+        assert_eq!(DartIdentity::DISCRIMINATOR, sighash("account:DartIdentity"));
+        assert_eq!(
+            DartIdentity::DISCRIMINATOR,
+            <DartIdentity as SplDiscriminate>::SPL_DISCRIMINATOR_SLICE
+        );
+        assert_eq!(
+            SessionRecord::DISCRIMINATOR,
+            <SessionRecord as SplDiscriminate>::SPL_DISCRIMINATOR_SLICE
+        );
+        assert_eq!(
+            RecoveryRecord::DISCRIMINATOR,
+            <RecoveryRecord as SplDiscriminate>::SPL_DISCRIMINATOR_SLICE
+        );
+        assert_eq!(
+            InheritanceRecord::DISCRIMINATOR,
+            <InheritanceRecord as SplDiscriminate>::SPL_DISCRIMINATOR_SLICE
+        );
+        assert_eq!(
+            TransferHookConfig::DISCRIMINATOR,
+            <TransferHookConfig as SplDiscriminate>::SPL_DISCRIMINATOR_SLICE
+        );
+        assert_eq!(
+            ProgramConfig::DISCRIMINATOR,
+            <ProgramConfig as SplDiscriminate>::SPL_DISCRIMINATOR_SLICE
+        );
+        assert_eq!(
+            ApprovalRoot::DISCRIMINATOR,
+            <ApprovalRoot as SplDiscriminate>::SPL_DISCRIMINATOR_SLICE
+        );
+        assert_eq!(
+            NoteRecord::DISCRIMINATOR,
+            <NoteRecord as SplDiscriminate>::SPL_DISCRIMINATOR_SLICE
+        );
+        assert_eq!(
+            CommitmentRecord::DISCRIMINATOR,
+            <CommitmentRecord as SplDiscriminate>::SPL_DISCRIMINATOR_SLICE
+        );
+        assert_eq!(
+            NotesLog::DISCRIMINATOR,
+            <NotesLog as SplDiscriminate>::SPL_DISCRIMINATOR_SLICE
+        );
+        assert_eq!(
+            AttestationList::DISCRIMINATOR,
+            <AttestationList as SplDiscriminate>::SPL_DISCRIMINATOR_SLICE
+        );
+        assert_eq!(
+            PledgeRecord::DISCRIMINATOR,
+            <PledgeRecord as SplDiscriminate>::SPL_DISCRIMINATOR_SLICE
+        );
+        assert_eq!(
+            PositionRecord::DISCRIMINATOR,
+            <PositionRecord as SplDiscriminate>::SPL_DISCRIMINATOR_SLICE
+        );
+        assert_eq!(
+            DistributionCursor::DISCRIMINATOR,
+            <DistributionCursor as SplDiscriminate>::SPL_DISCRIMINATOR_SLICE
+        );
+        assert_eq!(
+            SnapshotRecord::DISCRIMINATOR,
+            <SnapshotRecord as SplDiscriminate>::SPL_DISCRIMINATOR_SLICE
+        );
+        assert_eq!(
+            VotingProxy::DISCRIMINATOR,
+            <VotingProxy as SplDiscriminate>::SPL_DISCRIMINATOR_SLICE
+        );
+        assert_eq!(
+            TransferWindow::DISCRIMINATOR,
+            <TransferWindow as SplDiscriminate>::SPL_DISCRIMINATOR_SLICE
+        );
+        assert_eq!(
+            RateLimit::DISCRIMINATOR,
+            <RateLimit as SplDiscriminate>::SPL_DISCRIMINATOR_SLICE
+        );
+        assert_eq!(
+            DartPolicy::DISCRIMINATOR,
+            <DartPolicy as SplDiscriminate>::SPL_DISCRIMINATOR_SLICE
+        );
+        assert_eq!(
+            VaultIndex::DISCRIMINATOR,
+            <VaultIndex as SplDiscriminate>::SPL_DISCRIMINATOR_SLICE
+        );
+        assert_eq!(
+            FeeConfig::DISCRIMINATOR,
+            <FeeConfig as SplDiscriminate>::SPL_DISCRIMINATOR_SLICE
+        );
+        assert_eq!(
+            AuditTrail::DISCRIMINATOR,
+            <AuditTrail as SplDiscriminate>::SPL_DISCRIMINATOR_SLICE
+        );
+        assert_eq!(
+            ProgramStats::DISCRIMINATOR,
+            <ProgramStats as SplDiscriminate>::SPL_DISCRIMINATOR_SLICE
+        );
+        assert_eq!(
+            NotifyRecord::DISCRIMINATOR,
+            <NotifyRecord as SplDiscriminate>::SPL_DISCRIMINATOR_SLICE
+        );
+        assert_eq!(
+            Notification::DISCRIMINATOR,
+            <Notification as SplDiscriminate>::SPL_DISCRIMINATOR_SLICE
+        );
+        let names = [
+            "initialize",
+            "transfer_authority",
+            "close_account",
+            "reopen",
+            "propose_dart",
+            "accept_dart",
+            "initialize_dart_identity",
+            "set_operational_key",
+            "create_session",
+            "add_guardian",
+            "remove_guardian",
+            "initiate_recovery",
+            "approve_recovery",
+            "cancel_recovery",
+            "complete_recovery",
+            "set_beneficiary",
+            "check_in",
+            "claim_inheritance",
+            "set_tag",
+            "initialize_sized",
+            "grow_account",
+            "close_account_with_refund",
+            "record_attestation",
+            "swap_authorities",
+            "propose_transfer",
+            "accept_transfer",
+            "expire_transfer",
+            "set_oracle",
+            "mint_receipt",
+            "set_receipt_authority",
+            "initialize_transfer_hook",
+            "set_allowlist_entry",
+            "rekey",
+            "transfer_authority_checked",
+            "close_account_checked",
+            "set_dart_quorum",
+            "initialize_config",
+            "decommission",
+            "migrate_to_program",
+            "set_approval_root",
+            "transfer_with_proof",
+            "set_note_key",
+            "set_note",
+            "update_commitments",
+            "add_note",
+            "add_attestation",
+            "remove_attestation",
+            "pledge_as_collateral",
+            "release_collateral",
+            "set_position",
+            "apply_corporate_action",
+            "distribute",
+            "snapshot",
+            "delegate_voting",
+            "set_transfer_window",
+            "set_dart_transfer_window",
+            "set_rate_limit",
+            "set_dart_policy",
+            "clone_vault",
+            "create_asset_vault",
+            "sync_vault_index",
+            "set_crank_rules",
+            "crank",
+            "set_fee_exemption",
+            "set_fee_destination",
+            "collect_fees",
+            "create_audit_trail",
+            "initialize_stats",
+            "set_close_fees",
+            "set_notify",
+            "check_transfer",
+        ];
+        for (name, discriminator) in names.iter().zip(INSTRUCTION_DISCRIMINATORS) {
+            assert_eq!(discriminator, sighash(&format!("global:{name}")), "{name}");
+        }
+    }
+
+    #[test]
+    fn unpack_anchor_and_native() {
+        let anchor = INSTRUCTION_DISCRIMINATORS[1];
+        assert_eq!(
+            unpack_instruction(&anchor).unwrap(),
+            VaultInstruction::TransferAuthority
+        );
+        assert_eq!(
+            unpack_instruction(&[1]).unwrap(),
+            VaultInstruction::TransferAuthority
+        );
+        assert!(unpack_instruction(&[0xff; 8]).is_err());
+    }
+
+    #[test]
+    fn account_round_trip() {
+        let mut data = vec![];
+        TEST_RECORD_DATA.try_serialize(&mut data).unwrap();
+        let mut record = VaultRecord::try_deserialize(&mut data.as_slice()).unwrap();
+        // Serializing fills in the checksum.
+        assert_ne!(record.checksum, 0);
+        record.checksum = 0;
+        assert_eq!(record, TEST_RECORD_DATA);
+
+        let zeroed = vec![0; VaultRecord::LEN];
+        assert!(VaultRecord::try_deserialize(&mut zeroed.as_slice()).is_err());
+    }
+}
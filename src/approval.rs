@@ -0,0 +1,318 @@
+//! Canonical message of an offline DART approval, signed by operational keys and
+//! checked on chain through the Ed25519 program (see `quorum`).
+//!
+//! The message is, in order: the `DOMAIN` separator, the program id, the vault (the
+//! instruction's first account), the approval nonce (little endian), the action (the
+//! leading byte of the instruction encoding, its native tag) and the params digest
+//! (`hashv` of the instruction data followed by each account key). The domain keeps
+//! an approval from doubling as a transaction or another protocol's message, and the
+//! program id keeps it from being replayed against another deployment.
+//!
+//! A DART approving transfers in bulk signs a single `SetApprovalRoot` instead: the
+//! Merkle root of their `transfer_leaf`s, each redeemed by `TransferWithProof`. A leaf
+//! binds the vault's approval nonce, which redeeming it bumps, so it is redeemed once.
+//! Nodes hash their children in sorted order, so proofs carry no directions, and leaves
+//! and nodes hash under different prefixes, so a node cannot pass as a leaf.
+#[cfg(feature = "client")]
+use solana_sdk::signature::{Signature, Signer, SignerError};
+use {
+    crate::instruction::unpack_envelope,
+    solana_program::{
+        hash::{hashv, HASH_BYTES},
+        instruction::Instruction,
+        pubkey::{Pubkey, PUBKEY_BYTES},
+    },
+};
+
+/// Domain separator leading every approval message.
+pub const DOMAIN: [u8; 16] = *b"vault/approval/1";
+
+/// Approval message length.
+pub const MESSAGE_LEN: usize = DOMAIN.len() + 2 * PUBKEY_BYTES + 8 + 1 + HASH_BYTES;
+
+/// Longest Merkle proof `TransferWithProof` accepts, for trees of up to 2^32 leaves.
+pub const MAX_PROOF_LEN: usize = 32;
+
+/// Prefix of Merkle leaf hashes.
+const LEAF_PREFIX: &[u8] = &[0];
+
+/// Prefix of Merkle node hashes.
+const NODE_PREFIX: &[u8] = &[1];
+
+/// Fields of an approval message, for signers to show what they approve.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Approval {
+    /// Program the instruction is for
+    pub program_id: Pubkey,
+    /// The vault, the instruction's first account
+    pub vault: Pubkey,
+    /// Approval nonce of the DART identity
+    pub nonce: u64,
+    /// Native tag of the instruction
+    pub action: u8,
+    /// Digest of the instruction data and account keys
+    pub params: [u8; HASH_BYTES],
+}
+
+impl Approval {
+    /// The approval of `instruction` at `nonce`.
+    pub fn new(instruction: &Instruction, nonce: u64) -> Self {
+        let vault = instruction
+            .accounts
+            .first()
+            .map(|account| account.pubkey)
+            .unwrap_or_default();
+        let action = unpack_envelope(&instruction.data)
+            .ok()
+            .and_then(|(payload, _)| payload.first().copied())
+            .unwrap_or_default();
+        let mut params = vec![instruction.data.as_slice()];
+        params.extend(
+            instruction
+                .accounts
+                .iter()
+                .map(|account| account.pubkey.as_ref()),
+        );
+        Self {
+            program_id: instruction.program_id,
+            vault,
+            nonce,
+            action,
+            params: hashv(&params).to_bytes(),
+        }
+    }
+
+    /// The canonical message.
+    pub fn to_message(&self) -> [u8; MESSAGE_LEN] {
+        let mut message = [0; MESSAGE_LEN];
+        let fields: [&[u8]; 6] = [
+            &DOMAIN,
+            self.program_id.as_ref(),
+            self.vault.as_ref(),
+            &self.nonce.to_le_bytes(),
+            &[self.action],
+            &self.params,
+        ];
+        let mut offset = 0;
+        for field in fields {
+            message[offset..offset + field.len()].copy_from_slice(field);
+            offset += field.len();
+        }
+        message
+    }
+
+    /// The approval of a canonical `message`, or `None` if it is not one.
+    pub fn from_message(message: &[u8]) -> Option<Self> {
+        let rest = message.strip_prefix(&DOMAIN)?;
+        if rest.len() != MESSAGE_LEN - DOMAIN.len() {
+            return None;
+        }
+        let (program_id, rest) = rest.split_at(PUBKEY_BYTES);
+        let (vault, rest) = rest.split_at(PUBKEY_BYTES);
+        let (nonce, rest) = rest.split_at(8);
+        Some(Self {
+            program_id: Pubkey::try_from(program_id).ok()?,
+            vault: Pubkey::try_from(vault).ok()?,
+            nonce: u64::from_le_bytes(nonce.try_into().ok()?),
+            action: rest[0],
+            params: rest[1..].try_into().ok()?,
+        })
+    }
+}
+
+/// The canonical message approving `instruction` at `nonce`.
+pub fn message(instruction: &Instruction, nonce: u64) -> [u8; MESSAGE_LEN] {
+    Approval::new(instruction, nonce).to_message()
+}
+
+/// Merkle leaf approving the transfer of `vault` from `authority` to `new_authority`,
+/// redeemable while the vault's `approval_nonce` is `nonce`.
+pub fn transfer_leaf(
+    program_id: &Pubkey,
+    vault: &Pubkey,
+    nonce: u64,
+    authority: &Pubkey,
+    new_authority: &Pubkey,
+) -> [u8; HASH_BYTES] {
+    hashv(&[
+        LEAF_PREFIX,
+        program_id.as_ref(),
+        vault.as_ref(),
+        &nonce.to_le_bytes(),
+        authority.as_ref(),
+        new_authority.as_ref(),
+    ])
+    .to_bytes()
+}
+
+// Parent of two Merkle nodes.
+fn node(a: &[u8; HASH_BYTES], b: &[u8; HASH_BYTES]) -> [u8; HASH_BYTES] {
+    let (low, high) = if a <= b { (a, b) } else { (b, a) };
+    hashv(&[NODE_PREFIX, low, high]).to_bytes()
+}
+
+/// Merkle root of `leaves`; a node without a sibling moves up unchanged. Zero for no
+/// leaves, which `SetApprovalRoot` takes as revoking the approvals.
+pub fn merkle_root(leaves: &[[u8; HASH_BYTES]]) -> [u8; HASH_BYTES] {
+    let mut level = leaves.to_vec();
+    while level.len() > 1 {
+        level = merkle_level(&level);
+    }
+    level.first().copied().unwrap_or_default()
+}
+
+// The level of a Merkle tree above `level`.
+fn merkle_level(level: &[[u8; HASH_BYTES]]) -> Vec<[u8; HASH_BYTES]> {
+    level
+        .chunks(2)
+        .map(|pair| match pair {
+            [a, b] => node(a, b),
+            [a] => *a,
+            _ => unreachable!("chunks of two"),
+        })
+        .collect()
+}
+
+/// Merkle proof of the leaf at `index` of `leaves`, or `None` if out of range.
+pub fn merkle_proof(
+    leaves: &[[u8; HASH_BYTES]],
+    mut index: usize,
+) -> Option<Vec<[u8; HASH_BYTES]>> {
+    if index >= leaves.len() {
+        return None;
+    }
+    let mut proof = Vec::new();
+    let mut level = leaves.to_vec();
+    while level.len() > 1 {
+        if let Some(sibling) = level.get(index ^ 1) {
+            proof.push(*sibling);
+        }
+        level = merkle_level(&level);
+        index /= 2;
+    }
+    Some(proof)
+}
+
+/// Whether `proof` takes `leaf` to `root`.
+pub fn verify_proof(
+    leaf: &[u8; HASH_BYTES],
+    proof: &[[u8; HASH_BYTES]],
+    root: &[u8; HASH_BYTES],
+) -> bool {
+    proof
+        .iter()
+        .fold(*leaf, |hash, sibling| node(&hash, sibling))
+        == *root
+}
+
+/// `signer`'s detached approval of `instruction` at `nonce`, as
+/// `quorum::verify_signatures` takes it.
+#[cfg(feature = "client")]
+pub fn sign(
+    signer: &dyn Signer,
+    instruction: &Instruction,
+    nonce: u64,
+) -> Result<(Pubkey, [u8; 64]), SignerError> {
+    let signature = signer.try_sign_message(&message(instruction, nonce))?;
+    Ok((signer.try_pubkey()?, signature.into()))
+}
+
+/// Whether `signature` is `key`'s approval of `instruction` at `nonce`, for services
+/// checking approvals before they submit them.
+#[cfg(feature = "client")]
+pub fn verify(key: &Pubkey, signature: &[u8; 64], instruction: &Instruction, nonce: u64) -> bool {
+    Signature::from(*signature).verify(key.as_ref(), &message(instruction, nonce))
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, solana_program::instruction::AccountMeta};
+
+    fn instruction() -> Instruction {
+        Instruction {
+            program_id: crate::id(),
+            accounts: vec![
+                AccountMeta::new(Pubkey::new_from_array([1; 32]), false),
+                AccountMeta::new_readonly(Pubkey::new_from_array([2; 32]), false),
+            ],
+            data: vec![1],
+        }
+    }
+
+    #[test]
+    fn message_binds_instruction() {
+        let instruction = instruction();
+        let message = message(&instruction, 7);
+        assert_eq!(message[..16], *b"vault/approval/1");
+        assert_eq!(message[16..48], crate::id().to_bytes());
+        assert_eq!(message[48..80], [1; 32]);
+        assert_eq!(message[80..88], 7u64.to_le_bytes());
+        assert_eq!(message[88], 1);
+
+        assert_ne!(super::message(&instruction, 8), message);
+        let mut other = instruction.clone();
+        other.program_id = Pubkey::new_from_array([3; 32]);
+        assert_ne!(super::message(&other, 7), message);
+        let mut other = instruction.clone();
+        other.accounts[1].pubkey = Pubkey::new_from_array([3; 32]);
+        assert_ne!(super::message(&other, 7), message);
+        let mut other = instruction;
+        other.data = vec![2];
+        assert_ne!(super::message(&other, 7), message);
+    }
+
+    #[test]
+    fn message_round_trip() {
+        let mut instruction = instruction();
+        // The action of versioned data is the tag after the format prefix.
+        instruction.data = crate::instruction::VaultInstruction::CheckIn.pack();
+        let approval = Approval::new(&instruction, 7);
+        assert_eq!(approval.action, instruction.data[2]);
+        let message = approval.to_message();
+        assert_eq!(Approval::from_message(&message), Some(approval));
+        assert_eq!(Approval::from_message(&message[1..]), None);
+        assert_eq!(Approval::from_message(&[0; MESSAGE_LEN]), None);
+    }
+
+    #[test]
+    fn merkle_proofs_verify() {
+        let program_id = crate::id();
+        let keys: Vec<Pubkey> = (0..7).map(|n| Pubkey::new_from_array([n; 32])).collect();
+        let leaves: Vec<_> = keys
+            .windows(3)
+            .map(|keys| transfer_leaf(&program_id, &keys[0], 0, &keys[1], &keys[2]))
+            .collect();
+        let root = merkle_root(&leaves);
+        for (index, leaf) in leaves.iter().enumerate() {
+            let proof = merkle_proof(&leaves, index).unwrap();
+            assert!(proof.len() <= 3);
+            assert!(verify_proof(leaf, &proof, &root));
+        }
+        assert_eq!(merkle_proof(&leaves, leaves.len()), None);
+
+        // Another leaf and a truncated proof fail.
+        let proof = merkle_proof(&leaves, 0).unwrap();
+        let other = transfer_leaf(&program_id, &keys[0], 0, &keys[1], &keys[3]);
+        assert!(!verify_proof(&other, &proof, &root));
+        let redeemed = transfer_leaf(&program_id, &keys[0], 1, &keys[1], &keys[2]);
+        assert!(!verify_proof(&redeemed, &proof, &root));
+        assert!(!verify_proof(&leaves[0], &proof[1..], &root));
+
+        assert_eq!(merkle_root(&leaves[..1]), leaves[0]);
+        assert_eq!(merkle_root(&[]), [0; 32]);
+    }
+
+    #[cfg(feature = "client")]
+    #[test]
+    fn sign_and_verify() {
+        use solana_sdk::signature::Keypair;
+
+        let (key, other) = (Keypair::new(), Keypair::new());
+        let instruction = instruction();
+        let (pubkey, signature) = sign(&key, &instruction, 7).unwrap();
+        assert_eq!(pubkey, key.pubkey());
+        assert!(verify(&pubkey, &signature, &instruction, 7));
+        assert!(!verify(&pubkey, &signature, &instruction, 8));
+        assert!(!verify(&other.pubkey(), &signature, &instruction, 7));
+    }
+}
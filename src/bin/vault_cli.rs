@@ -0,0 +1,89 @@
+//! Offline signing of vault transactions.
+//!
+//! Usage:
+//! - `vault-cli sign --offline --keypair <PATH> <TRANSACTION>` prints the signer's
+//!   detached `PUBKEY=SIGNATURE` over a base64 transaction, without network access.
+//!   The keypair is a file or a hardware wallet path such as `usb://ledger?key=0`.
+//! - `vault-cli submit <RPC_URL> <TRANSACTION> --signatures <PUBKEY=SIGNATURE>...`
+//!   attaches the collected signatures and broadcasts the transaction.
+use {
+    solana_client::rpc_client::RpcClient,
+    std::{env, process::exit},
+    vault::client::{
+        attach_signatures, decode_transaction, format_signature, missing_signers, parse_signature,
+        sign_offline, signer_from_path,
+    },
+};
+
+const USAGE: &str = "Usage:
+  vault-cli sign --offline --keypair <PATH|usb://ledger[?key=N]> <TRANSACTION>
+  vault-cli submit <RPC_URL> <TRANSACTION> --signatures <PUBKEY=SIGNATURE>...";
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    match args.split_first() {
+        Some((command, args)) if command == "sign" => sign(args),
+        Some((command, args)) if command == "submit" => submit(args),
+        _ => fail(USAGE),
+    }
+}
+
+fn sign(args: &[String]) {
+    let mut args = args.iter();
+    let (mut offline, mut keypair, mut transaction) = (false, None, None);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--offline" => offline = true,
+            "--keypair" => keypair = Some(args.next().unwrap_or_else(|| fail(USAGE))),
+            _ if transaction.is_none() && !arg.starts_with('-') => transaction = Some(arg),
+            _ => fail(USAGE),
+        }
+    }
+    if !offline {
+        fail("Only offline signing is supported: pass --offline");
+    }
+    let signer = signer_from_path(keypair.unwrap_or_else(|| fail(USAGE)))
+        .unwrap_or_else(|error| fail(&error.to_string()));
+    let transaction = decode_transaction(transaction.unwrap_or_else(|| fail(USAGE)))
+        .unwrap_or_else(|error| fail(&error.to_string()));
+
+    let signature = sign_offline(&transaction, signer.as_ref())
+        .unwrap_or_else(|error| fail(&error.to_string()));
+    println!("{}", format_signature(&signature));
+}
+
+fn submit(args: &[String]) {
+    let (mut url, mut transaction, mut signatures) = (None, None, vec![]);
+    let mut in_signatures = false;
+    for arg in args {
+        match arg.as_str() {
+            "--signatures" => in_signatures = true,
+            _ if arg.starts_with('-') => fail(USAGE),
+            _ if in_signatures => signatures
+                .push(parse_signature(arg).unwrap_or_else(|error| fail(&error.to_string()))),
+            _ if url.is_none() => url = Some(arg),
+            _ if transaction.is_none() => transaction = Some(arg),
+            _ => fail(USAGE),
+        }
+    }
+    let rpc = RpcClient::new(url.unwrap_or_else(|| fail(USAGE)));
+    let mut transaction = decode_transaction(transaction.unwrap_or_else(|| fail(USAGE)))
+        .unwrap_or_else(|error| fail(&error.to_string()));
+    attach_signatures(&mut transaction, &signatures)
+        .unwrap_or_else(|error| fail(&error.to_string()));
+    let missing = missing_signers(&transaction);
+    if !missing.is_empty() {
+        let missing: Vec<String> = missing.iter().map(ToString::to_string).collect();
+        fail(&format!("Missing signatures of {}", missing.join(", ")));
+    }
+
+    let signature = rpc
+        .send_and_confirm_transaction(&transaction)
+        .unwrap_or_else(|error| fail(&error.to_string()));
+    println!("{signature}");
+}
+
+fn fail(message: &str) -> ! {
+    eprintln!("{message}");
+    exit(1)
+}
@@ -0,0 +1,50 @@
+//! Snapshot every vault record of a program as JSON or CSV, for daily reconciliation.
+//!
+//! Usage: `vault-export <RPC_URL> [--program-id <PUBKEY>] [--format json|csv]`
+use {
+    solana_client::rpc_client::RpcClient,
+    solana_program::pubkey::Pubkey,
+    std::{io, process::exit, str::FromStr},
+    vault::client::{export_vaults, write_csv, write_json},
+};
+
+const USAGE: &str = "Usage: vault-export <RPC_URL> [--program-id <PUBKEY>] [--format json|csv]";
+
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let mut url = None;
+    let mut program_id = vault::id();
+    let mut csv = false;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--program-id" => {
+                program_id = args
+                    .next()
+                    .and_then(|key| Pubkey::from_str(&key).ok())
+                    .unwrap_or_else(|| fail("Invalid --program-id"));
+            }
+            "--format" => match args.next().as_deref() {
+                Some("json") => csv = false,
+                Some("csv") => csv = true,
+                _ => fail("--format must be json or csv"),
+            },
+            _ if url.is_none() && !arg.starts_with('-') => url = Some(arg),
+            _ => fail(USAGE),
+        }
+    }
+    let rpc = RpcClient::new(url.unwrap_or_else(|| fail(USAGE)));
+
+    let snapshots =
+        export_vaults(&rpc, &program_id).unwrap_or_else(|error| fail(&error.to_string()));
+    let written = if csv {
+        write_csv(&snapshots, io::stdout().lock())
+    } else {
+        write_json(&snapshots, io::stdout().lock())
+    };
+    written.unwrap_or_else(|error| fail(&error.to_string()));
+}
+
+fn fail(message: &str) -> ! {
+    eprintln!("{message}");
+    exit(1)
+}
@@ -0,0 +1,103 @@
+//! Hash commitments to confidential vault metadata, such as a position size or client
+//! id. The DART publishes `Opening::commitment`s with `UpdateCommitments` and keeps
+//! the openings; revealing one to an auditor lets them check it against the chain
+//! without the value ever being public. The random blinding keeps low-entropy values
+//! from being guessed, and the slot index keeps an opening from passing for another slot.
+use {
+    crate::state::{CommitmentRecord, MAX_COMMITMENTS},
+    solana_program::hash::{hashv, HASH_BYTES},
+};
+
+/// Domain separator of commitment hashes.
+pub const COMMITMENT_DOMAIN: [u8; 18] = *b"vault/commitment/1";
+
+/// What opens a commitment: the slot, the value and the blinding factor.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Opening {
+    /// Commitment slot, below `MAX_COMMITMENTS`
+    pub slot: u8,
+    /// The committed value
+    pub value: Vec<u8>,
+    /// Random blinding factor
+    pub blinding: [u8; 32],
+}
+
+impl Opening {
+    /// An opening of `value` in `slot` with a fresh random blinding factor.
+    pub fn new(slot: u8, value: &[u8]) -> Self {
+        Self {
+            slot,
+            value: value.to_vec(),
+            blinding: rand::random(),
+        }
+    }
+
+    /// The commitment to publish.
+    pub fn commitment(&self) -> [u8; HASH_BYTES] {
+        hashv(&[
+            &COMMITMENT_DOMAIN,
+            &[self.slot],
+            &self.value,
+            &self.blinding,
+        ])
+        .to_bytes()
+    }
+
+    /// Whether this opens the commitment `record` holds in its slot.
+    pub fn opens(&self, record: &CommitmentRecord) -> bool {
+        record
+            .commitments
+            .get(usize::from(self.slot))
+            .is_some_and(|commitment| *commitment == self.commitment())
+    }
+}
+
+/// The `UpdateCommitments` slots of `openings`, zero where no opening is given.
+pub fn commitments(openings: &[Opening]) -> [[u8; HASH_BYTES]; MAX_COMMITMENTS] {
+    let mut commitments = [[0; HASH_BYTES]; MAX_COMMITMENTS];
+    for opening in openings {
+        if let Some(commitment) = commitments.get_mut(usize::from(opening.slot)) {
+            *commitment = opening.commitment();
+        }
+    }
+    commitments
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, solana_program::pubkey::Pubkey};
+
+    #[test]
+    fn openings_open_their_slot() {
+        let size = Opening::new(0, &1_000_000u64.to_le_bytes());
+        let client = Opening::new(1, b"client-42");
+        let record = CommitmentRecord::new(
+            Pubkey::new_unique(),
+            commitments(&[size.clone(), client.clone()]),
+            1,
+            255,
+        );
+        assert!(size.opens(&record));
+        assert!(client.opens(&record));
+        assert_eq!(record.commitments[2], [0; 32]);
+
+        // Another value, blinding or slot fails.
+        let mut other = size.clone();
+        other.value = 999_999u64.to_le_bytes().to_vec();
+        assert!(!other.opens(&record));
+        let mut other = size.clone();
+        other.blinding = [0; 32];
+        assert!(!other.opens(&record));
+        let mut other = size.clone();
+        other.slot = 1;
+        assert!(!other.opens(&record));
+        other.slot = MAX_COMMITMENTS as u8;
+        assert!(!other.opens(&record));
+
+        // The same value commits differently each time.
+        assert_ne!(
+            Opening::new(0, b"client-42").commitment(),
+            Opening::new(0, b"client-42").commitment()
+        );
+    }
+}
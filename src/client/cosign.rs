@@ -0,0 +1,104 @@
+//! Co-signing with a remote DART. The integrator builds a transaction, signs it with
+//! the keys it holds (fee payer, authority), and sends `encode_transaction` text to the
+//! DART's service, which answers with either its detached `PUBKEY=SIGNATURE`
+//! (`attach_signatures`) or the whole transaction signed (`merge_signatures`).
+use {
+    super::{attach_signatures, custom, sign_offline},
+    solana_client::client_error::ClientError,
+    solana_program::{hash::Hash, instruction::Instruction, message::Message, pubkey::Pubkey},
+    solana_sdk::{
+        signature::{Signature, Signer},
+        transaction::Transaction,
+    },
+};
+
+/// A transaction of `instructions` paid by `payer`, with every signature missing.
+pub fn unsigned_transaction(
+    instructions: &[Instruction],
+    payer: &Pubkey,
+    recent_blockhash: Hash,
+) -> Transaction {
+    Transaction::new_unsigned(Message::new_with_blockhash(
+        instructions,
+        Some(payer),
+        &recent_blockhash,
+    ))
+}
+
+/// Sign `transaction` with the locally available `signers`, leaving the signatures of
+/// the others, such as a remote DART, missing.
+pub fn partially_sign(
+    transaction: &mut Transaction,
+    signers: &[&dyn Signer],
+) -> Result<(), ClientError> {
+    let signatures = signers
+        .iter()
+        .map(|signer| sign_offline(transaction, *signer))
+        .collect::<Result<Vec<_>, _>>()?;
+    attach_signatures(transaction, &signatures)
+}
+
+/// Copy into `transaction` the signatures that `cosigned`, the same transaction as
+/// returned by a co-signer, adds. Each is verified, so a co-signer cannot alter the
+/// message or replace the signatures already collected.
+pub fn merge_signatures(
+    transaction: &mut Transaction,
+    cosigned: &Transaction,
+) -> Result<(), ClientError> {
+    if cosigned.message != transaction.message {
+        return Err(custom(
+            "Co-signed transaction does not match the transaction".to_string(),
+        ));
+    }
+    let signatures: Vec<(Pubkey, Signature)> = cosigned
+        .message
+        .account_keys
+        .iter()
+        .zip(&cosigned.signatures)
+        .zip(&transaction.signatures)
+        .filter(|((_, cosignature), signature)| {
+            **cosignature != Signature::default() && **signature == Signature::default()
+        })
+        .map(|((pubkey, cosignature), _)| (*pubkey, *cosignature))
+        .collect();
+    attach_signatures(transaction, &signatures)
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{
+            client::{decode_transaction, encode_transaction, missing_signers},
+            instruction,
+        },
+        solana_sdk::signature::Keypair,
+    };
+
+    #[test]
+    fn remote_dart_cosigns() {
+        let (payer, authority, dart) = (Keypair::new(), Keypair::new(), Keypair::new());
+        let ix = instruction::transfer_authority(
+            crate::id(),
+            &Pubkey::new_unique(),
+            &dart.pubkey(),
+            &authority.pubkey(),
+            &Pubkey::new_unique(),
+        );
+        let mut transaction = unsigned_transaction(&[ix], &payer.pubkey(), Hash::new_unique());
+        partially_sign(&mut transaction, &[&payer, &authority]).unwrap();
+        assert_eq!(missing_signers(&transaction), [dart.pubkey()]);
+
+        // The DART's service signs what it receives and returns the transaction.
+        let mut cosigned = decode_transaction(&encode_transaction(&transaction)).unwrap();
+        partially_sign(&mut cosigned, &[&dart]).unwrap();
+
+        let mut tampered = cosigned.clone();
+        tampered.message.recent_blockhash = Hash::new_unique();
+        assert!(merge_signatures(&mut transaction.clone(), &tampered).is_err());
+
+        merge_signatures(&mut transaction, &cosigned).unwrap();
+        assert!(missing_signers(&transaction).is_empty());
+        assert!(transaction.verify().is_ok());
+    }
+}
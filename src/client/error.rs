@@ -0,0 +1,63 @@
+//! Decoding vault errors out of failed transactions.
+use {
+    crate::error::VaultError, solana_program::instruction::InstructionError,
+    solana_sdk::transaction::TransactionError, spl_program_error::num_traits::FromPrimitive,
+};
+
+impl VaultError {
+    /// The vault error carried by `error`, if it is a custom error with a vault code.
+    ///
+    /// Custom codes are per program: only decode errors of vault instructions.
+    pub fn from_instruction_error(error: &InstructionError) -> Option<Self> {
+        match error {
+            InstructionError::Custom(code) => Self::from_u32(*code),
+            _ => None,
+        }
+    }
+
+    /// The vault error that failed a transaction, if an instruction failed with one.
+    ///
+    /// Custom codes are per program: only decode errors of transactions whose failing
+    /// instruction (the index carried by `error`) is a vault instruction.
+    pub fn decode(error: &TransactionError) -> Option<Self> {
+        match error {
+            TransactionError::InstructionError(_, error) => Self::from_instruction_error(error),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_vault_errors() {
+        let error = TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(VaultError::StaleState as u32),
+        );
+        assert_eq!(VaultError::decode(&error), Some(VaultError::StaleState));
+        assert_eq!(
+            VaultError::decode(&error).unwrap().to_string(),
+            "Stale vault state"
+        );
+        assert_eq!(
+            VaultError::from_instruction_error(&InstructionError::Custom(0)),
+            Some(VaultError::IncorrectAuthority)
+        );
+    }
+
+    #[test]
+    fn decode_other_errors() {
+        assert_eq!(
+            VaultError::from_instruction_error(&InstructionError::Custom(u32::MAX)),
+            None
+        );
+        assert_eq!(
+            VaultError::from_instruction_error(&InstructionError::MissingRequiredSignature),
+            None
+        );
+        assert_eq!(VaultError::decode(&TransactionError::AccountInUse), None);
+    }
+}
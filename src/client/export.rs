@@ -0,0 +1,155 @@
+//! Snapshots of every vault record of a program, for reconciliation against the
+//! DART's book of record.
+use {
+    super::{fetch_vaults, vault_addresses},
+    crate::state::VaultRecord,
+    solana_client::{client_error::ClientError, rpc_client::RpcClient},
+    solana_program::pubkey::Pubkey,
+    std::io::{self, Write},
+};
+
+/// A vault record and the account holding it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct VaultSnapshot {
+    /// The vault record account
+    pub address: Pubkey,
+    /// Lamports held by the account
+    pub lamports: u64,
+    /// The decoded record
+    pub record: VaultRecord,
+}
+
+/// Columns of an export, in order.
+pub const EXPORT_COLUMNS: [&str; 14] = [
+    "address",
+    "lamports",
+    "version",
+    "status",
+    "authority",
+    "dart",
+    "pending_dart",
+    "pending_authority",
+    "transfer_expires_at_slot",
+    "tag",
+    "rent_payer",
+    "close_recipient",
+    "receipt_mint",
+    "receipt_is_authority",
+];
+
+/// Snapshot every vault record of `program_id`, ordered by address: the records at
+/// `vault_addresses`, fetched with `fetch_vaults`.
+pub fn export_vaults(
+    rpc: &RpcClient,
+    program_id: &Pubkey,
+) -> Result<Vec<VaultSnapshot>, ClientError> {
+    fetch_vaults(rpc, program_id, &vault_addresses(rpc, program_id)?)
+}
+
+impl VaultSnapshot {
+    /// Values of `EXPORT_COLUMNS`, and whether each is text rather than a number.
+    fn values(&self) -> [(String, bool); EXPORT_COLUMNS.len()] {
+        let record = &self.record;
+        [
+            (self.address.to_string(), true),
+            (self.lamports.to_string(), false),
+            (record.version.to_string(), false),
+            (format!("{:?}", record.status), true),
+            (record.authority.to_string(), true),
+            (record.dart.to_string(), true),
+            (record.pending_dart.to_string(), true),
+            (record.pending_authority.to_string(), true),
+            (record.transfer_expires_at_slot.to_string(), false),
+            (record.tag.to_string(), false),
+            (record.rent_payer.to_string(), true),
+            (record.close_recipient.to_string(), true),
+            (record.receipt_mint.to_string(), true),
+            (record.receipt_is_authority.to_string(), false),
+        ]
+    }
+}
+
+/// Write `snapshots` as CSV: a header row of `EXPORT_COLUMNS`, then a row per vault.
+pub fn write_csv<W: Write>(snapshots: &[VaultSnapshot], mut writer: W) -> io::Result<()> {
+    writeln!(writer, "{}", EXPORT_COLUMNS.join(","))?;
+    for snapshot in snapshots {
+        let row: Vec<String> = snapshot
+            .values()
+            .into_iter()
+            .map(|(value, _)| value)
+            .collect();
+        writeln!(writer, "{}", row.join(","))?;
+    }
+    Ok(())
+}
+
+/// Write `snapshots` as a JSON array with an object per vault, keyed by `EXPORT_COLUMNS`.
+pub fn write_json<W: Write>(snapshots: &[VaultSnapshot], mut writer: W) -> io::Result<()> {
+    writeln!(writer, "[")?;
+    for (index, snapshot) in snapshots.iter().enumerate() {
+        let fields: Vec<String> = EXPORT_COLUMNS
+            .iter()
+            .zip(snapshot.values())
+            .map(|(column, (value, text))| {
+                if text {
+                    format!("\"{column}\":\"{value}\"")
+                } else {
+                    format!("\"{column}\":{value}")
+                }
+            })
+            .collect();
+        let separator = if index + 1 < snapshots.len() { "," } else { "" };
+        writeln!(writer, "  {{{}}}{separator}", fields.join(","))?;
+    }
+    writeln!(writer, "]")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot() -> VaultSnapshot {
+        VaultSnapshot {
+            address: Pubkey::new_from_array([1; 32]),
+            lamports: 42,
+            record: VaultRecord::new(
+                Pubkey::new_from_array([2; 32]),
+                Pubkey::new_from_array([3; 32]),
+            ),
+        }
+    }
+
+    #[test]
+    fn csv_has_a_row_per_vault() {
+        let mut csv = vec![];
+        write_csv(&[snapshot(), snapshot()], &mut csv).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        let rows: Vec<&str> = csv.lines().collect();
+        assert_eq!(rows.len(), 3);
+        assert_eq!(rows[0], EXPORT_COLUMNS.join(","));
+        let values: Vec<&str> = rows[1].split(',').collect();
+        assert_eq!(values.len(), EXPORT_COLUMNS.len());
+        assert_eq!(values[0], Pubkey::new_from_array([1; 32]).to_string());
+        assert_eq!(values[1], "42");
+        assert_eq!(values[3], "Active");
+    }
+
+    #[test]
+    fn json_has_an_object_per_vault() {
+        let mut json = vec![];
+        write_json(&[snapshot(), snapshot()], &mut json).unwrap();
+        let json = String::from_utf8(json).unwrap();
+        let rows: Vec<&str> = json.lines().collect();
+        assert_eq!(rows.len(), 4);
+        assert!(rows[1].starts_with(&format!(
+            "  {{\"address\":\"{}\",\"lamports\":42,",
+            Pubkey::new_from_array([1; 32])
+        )));
+        assert!(rows[1].ends_with("\"receipt_is_authority\":false},"));
+        assert!(rows[2].ends_with('}'));
+
+        let mut json = vec![];
+        write_json(&[], &mut json).unwrap();
+        assert_eq!(json, b"[\n]\n");
+    }
+}
@@ -0,0 +1,18 @@
+//! Helpers for off-chain clients of the vault.
+mod commitments;
+mod cosign;
+mod error;
+mod export;
+mod offline;
+mod query;
+mod retry;
+mod signer;
+
+pub use {commitments::*, cosign::*, export::*, offline::*, query::*, retry::*, signer::*};
+
+use solana_client::client_error::{ClientError, ClientErrorKind};
+
+// A client error carrying `message`.
+fn custom(message: String) -> ClientError {
+    ClientErrorKind::Custom(message).into()
+}
@@ -0,0 +1,158 @@
+//! Offline signing. A transaction is built and encoded where its blockhash (or durable
+//! nonce) is known, carried as base64 to each signer, such as a DART key on an
+//! air-gapped machine, and broadcast once every detached signature is collected.
+use {
+    super::custom,
+    base64::{engine::general_purpose::STANDARD, Engine},
+    solana_client::client_error::ClientError,
+    solana_program::pubkey::Pubkey,
+    solana_sdk::{
+        signature::{Signature, Signer},
+        transaction::Transaction,
+    },
+    std::str::FromStr,
+};
+
+/// `transaction`, signed or not, as base64 of its wire format.
+pub fn encode_transaction(transaction: &Transaction) -> String {
+    STANDARD.encode(bincode::serialize(transaction).expect("transactions serialize"))
+}
+
+/// The transaction encoded by `encode_transaction`.
+pub fn decode_transaction(encoded: &str) -> Result<Transaction, ClientError> {
+    let bytes = STANDARD
+        .decode(encoded.trim())
+        .map_err(|error| custom(format!("Invalid base64 transaction: {error}")))?;
+    bincode::deserialize(&bytes).map_err(|error| custom(format!("Invalid transaction: {error}")))
+}
+
+/// The detached signature of `signer` over `transaction`, which must name it as a
+/// signer. Nothing is sent: the signature travels back as `format_signature` text.
+pub fn sign_offline(
+    transaction: &Transaction,
+    signer: &dyn Signer,
+) -> Result<(Pubkey, Signature), ClientError> {
+    let pubkey = signer
+        .try_pubkey()
+        .map_err(|error| custom(error.to_string()))?;
+    signer_position(transaction, &pubkey)?;
+    let signature = signer
+        .try_sign_message(&transaction.message_data())
+        .map_err(|error| custom(error.to_string()))?;
+    Ok((pubkey, signature))
+}
+
+/// `PUBKEY=SIGNATURE`, as printed by `vault-cli sign --offline`.
+pub fn format_signature((pubkey, signature): &(Pubkey, Signature)) -> String {
+    format!("{pubkey}={signature}")
+}
+
+/// The signer and signature of `PUBKEY=SIGNATURE` text.
+pub fn parse_signature(text: &str) -> Result<(Pubkey, Signature), ClientError> {
+    let invalid = || custom(format!("Expected PUBKEY=SIGNATURE, got {text}"));
+    let (pubkey, signature) = text.trim().split_once('=').ok_or_else(invalid)?;
+    Ok((
+        Pubkey::from_str(pubkey).map_err(|_| invalid())?,
+        Signature::from_str(signature).map_err(|_| invalid())?,
+    ))
+}
+
+/// Place detached `signatures` into `transaction`. Each must be by one of its signers
+/// and verify against its message, so a signature over a stale or altered transaction
+/// is rejected here rather than by the cluster.
+pub fn attach_signatures(
+    transaction: &mut Transaction,
+    signatures: &[(Pubkey, Signature)],
+) -> Result<(), ClientError> {
+    let message = transaction.message_data();
+    for (pubkey, signature) in signatures {
+        let position = signer_position(transaction, pubkey)?;
+        if !signature.verify(pubkey.as_ref(), &message) {
+            return Err(custom(format!(
+                "Signature of {pubkey} does not match the transaction"
+            )));
+        }
+        transaction.signatures[position] = *signature;
+    }
+    Ok(())
+}
+
+/// Signers of `transaction` that have not signed yet.
+pub fn missing_signers(transaction: &Transaction) -> Vec<Pubkey> {
+    transaction
+        .message
+        .account_keys
+        .iter()
+        .zip(&transaction.signatures)
+        .filter(|(_, signature)| **signature == Signature::default())
+        .map(|(pubkey, _)| *pubkey)
+        .collect()
+}
+
+// Position of `pubkey` among the signers of `transaction`.
+fn signer_position(transaction: &Transaction, pubkey: &Pubkey) -> Result<usize, ClientError> {
+    let signers = usize::from(transaction.message.header.num_required_signatures);
+    transaction.message.account_keys[..signers]
+        .iter()
+        .position(|key| key == pubkey)
+        .ok_or_else(|| custom(format!("{pubkey} is not a signer of the transaction")))
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        solana_program::{
+            hash::Hash,
+            instruction::{AccountMeta, Instruction},
+            message::Message,
+        },
+        solana_sdk::signature::Keypair,
+    };
+
+    fn unsigned(signers: &[&Keypair]) -> Transaction {
+        let accounts = signers
+            .iter()
+            .map(|signer| AccountMeta::new(signer.pubkey(), true))
+            .collect();
+        let instruction = Instruction::new_with_bytes(crate::id(), &[], accounts);
+        let mut message = Message::new(&[instruction], Some(&signers[0].pubkey()));
+        message.recent_blockhash = Hash::new_unique();
+        Transaction::new_unsigned(message)
+    }
+
+    #[test]
+    fn detached_signatures_combine() {
+        let (authority, dart) = (Keypair::new(), Keypair::new());
+        let transaction = unsigned(&[&authority, &dart]);
+        let encoded = encode_transaction(&transaction);
+        assert_eq!(decode_transaction(&encoded).unwrap(), transaction);
+
+        let signatures = [&authority, &dart].map(|signer| {
+            let offline = decode_transaction(&encoded).unwrap();
+            let text = format_signature(&sign_offline(&offline, signer).unwrap());
+            parse_signature(&text).unwrap()
+        });
+        let mut combined = decode_transaction(&encoded).unwrap();
+        assert_eq!(
+            missing_signers(&combined),
+            [authority.pubkey(), dart.pubkey()]
+        );
+        attach_signatures(&mut combined, &signatures[1..]).unwrap();
+        assert_eq!(missing_signers(&combined), [authority.pubkey()]);
+        attach_signatures(&mut combined, &signatures[..1]).unwrap();
+        assert!(missing_signers(&combined).is_empty());
+        assert!(combined.verify().is_ok());
+    }
+
+    #[test]
+    fn foreign_signatures_rejected() {
+        let (authority, stranger) = (Keypair::new(), Keypair::new());
+        let mut transaction = unsigned(&[&authority]);
+        assert!(sign_offline(&transaction, &stranger).is_err());
+
+        let stale = sign_offline(&unsigned(&[&authority]), &authority).unwrap();
+        assert!(attach_signatures(&mut transaction, &[stale]).is_err());
+        assert!(parse_signature("not-a-signature").is_err());
+    }
+}
@@ -0,0 +1,196 @@
+//! Listing and fetching vault records over RPC. Listings of a program's or a DART's
+//! vaults return addresses only, via `getProgramAccounts` with memcmp filters and an
+//! empty data slice; records are then fetched page by page, so large programs never
+//! return all their data at once. An authority's few vaults are listed in full, or
+//! read through its vault index without scanning the program.
+use {
+    super::{custom, VaultSnapshot},
+    crate::state::{VaultIndex, VaultRecord},
+    borsh::BorshDeserialize,
+    solana_account_decoder::{UiAccountEncoding, UiDataSliceConfig},
+    solana_client::{
+        client_error::ClientError,
+        rpc_client::RpcClient,
+        rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig},
+        rpc_filter::{Memcmp, RpcFilterType},
+        rpc_request::MAX_MULTIPLE_ACCOUNTS,
+    },
+    solana_program::pubkey::Pubkey,
+    solana_sdk::account::Account,
+};
+
+/// Addresses of every vault record of `program_id`, in order.
+pub fn vault_addresses(rpc: &RpcClient, program_id: &Pubkey) -> Result<Vec<Pubkey>, ClientError> {
+    record_addresses(rpc, program_id, None)
+}
+
+/// Addresses of the vault records of `program_id` whose DART is `dart`, in order.
+pub fn vault_addresses_by_dart(
+    rpc: &RpcClient,
+    program_id: &Pubkey,
+    dart: &Pubkey,
+) -> Result<Vec<Pubkey>, ClientError> {
+    record_addresses(rpc, program_id, Some(VaultRecord::dart_filter(dart)))
+}
+
+/// Page `page`, counting from zero, of the vaults of `program_id` whose DART is
+/// `dart`: at most `limit` records, ordered by address. Only the records of the page
+/// are fetched; callers walking every page may instead list the addresses once with
+/// `vault_addresses_by_dart` and fetch pages of them with `fetch_vaults`.
+pub fn list_vaults_by_dart(
+    rpc: &RpcClient,
+    program_id: &Pubkey,
+    dart: &Pubkey,
+    page: usize,
+    limit: usize,
+) -> Result<Vec<VaultSnapshot>, ClientError> {
+    let addresses = vault_addresses_by_dart(rpc, program_id, dart)?;
+    fetch_vaults(rpc, program_id, page_of(&addresses, page, limit))
+}
+
+/// The vault records of `program_id` whose authority is `authority`, with their
+/// addresses, in address order. Records are returned in full: an authority holds few
+/// vaults. A record in receipt-holder mode names the holder last seen on chain.
+pub fn list_vaults_by_authority(
+    rpc: &RpcClient,
+    program_id: &Pubkey,
+    authority: &Pubkey,
+) -> Result<Vec<(Pubkey, VaultRecord)>, ClientError> {
+    let accounts = program_records(
+        rpc,
+        program_id,
+        Some(VaultRecord::authority_filter(authority)),
+        None,
+    )?;
+    accounts
+        .into_iter()
+        .map(|(address, account)| Ok((address, decode_record(&address, &account.data)?)))
+        .collect()
+}
+
+/// Snapshot the vaults listed in the vault index of `authority` that it still holds,
+/// reading only the index and the listed records. Without an index nothing is listed.
+pub fn list_indexed_vaults(
+    rpc: &RpcClient,
+    program_id: &Pubkey,
+    authority: &Pubkey,
+) -> Result<Vec<VaultSnapshot>, ClientError> {
+    let (address, _) = VaultIndex::find_address(program_id, authority);
+    let Some(account) = rpc
+        .get_multiple_accounts(&[address])?
+        .pop()
+        .flatten()
+        .filter(|account| account.owner == *program_id)
+    else {
+        return Ok(Vec::new());
+    };
+    let index = VaultIndex::deserialize(&mut account.data.as_slice())
+        .map_err(|error| custom(format!("Vault index {address}: {error}")))?;
+    let vaults: Vec<Pubkey> = index.vaults().copied().collect();
+    let mut snapshots = fetch_vaults(rpc, program_id, &vaults)?;
+    // Transfers leave the index stale until it is synced.
+    snapshots.retain(|snapshot| snapshot.record.authority == *authority);
+    Ok(snapshots)
+}
+
+// Page `page` of `limit` items, empty past the end.
+fn page_of<T>(items: &[T], page: usize, limit: usize) -> &[T] {
+    let start = page.saturating_mul(limit).min(items.len());
+    &items[start..start.saturating_add(limit).min(items.len())]
+}
+
+// Addresses of the vault records of `program_id` also matching `filter`, in order.
+fn record_addresses(
+    rpc: &RpcClient,
+    program_id: &Pubkey,
+    filter: Option<(usize, [u8; 32])>,
+) -> Result<Vec<Pubkey>, ClientError> {
+    let no_data = UiDataSliceConfig {
+        offset: 0,
+        length: 0,
+    };
+    let accounts = program_records(rpc, program_id, filter, Some(no_data))?;
+    Ok(accounts.into_iter().map(|(address, _)| address).collect())
+}
+
+// Vault record accounts of `program_id` also matching the memcmp `filter`, sorted by
+// address, with the `data_slice` of their data.
+fn program_records(
+    rpc: &RpcClient,
+    program_id: &Pubkey,
+    filter: Option<(usize, [u8; 32])>,
+    data_slice: Option<UiDataSliceConfig>,
+) -> Result<Vec<(Pubkey, Account)>, ClientError> {
+    let (offset, discriminator) = VaultRecord::discriminator_filter();
+    let mut filters = vec![RpcFilterType::Memcmp(Memcmp::new_raw_bytes(
+        offset,
+        discriminator.to_vec(),
+    ))];
+    filters.extend(filter.map(|(offset, bytes)| {
+        RpcFilterType::Memcmp(Memcmp::new_raw_bytes(offset, bytes.to_vec()))
+    }));
+    let mut accounts = rpc.get_program_accounts_with_config(
+        program_id,
+        RpcProgramAccountsConfig {
+            filters: Some(filters),
+            account_config: RpcAccountInfoConfig {
+                encoding: Some(UiAccountEncoding::Base64),
+                data_slice,
+                ..RpcAccountInfoConfig::default()
+            },
+            ..RpcProgramAccountsConfig::default()
+        },
+    )?;
+    accounts.sort_by_key(|(address, _)| *address);
+    Ok(accounts)
+}
+
+/// Snapshot the vault records of `program_id` among `addresses`, fetched
+/// `MAX_MULTIPLE_ACCOUNTS` at a time so no single response carries the whole
+/// program's data. Missing accounts and accounts that are not vault records are left
+/// out; a record that fails to decode fails the fetch.
+pub fn fetch_vaults(
+    rpc: &RpcClient,
+    program_id: &Pubkey,
+    addresses: &[Pubkey],
+) -> Result<Vec<VaultSnapshot>, ClientError> {
+    let (offset, discriminator) = VaultRecord::discriminator_filter();
+    let mut snapshots = Vec::with_capacity(addresses.len());
+    for page in addresses.chunks(MAX_MULTIPLE_ACCOUNTS) {
+        for (address, account) in page.iter().zip(rpc.get_multiple_accounts(page)?) {
+            let Some(account) = account.filter(|account| {
+                account.owner == *program_id
+                    && account.data.get(offset..offset + discriminator.len()) == Some(discriminator)
+            }) else {
+                continue;
+            };
+            let record = decode_record(address, &account.data)?;
+            snapshots.push(VaultSnapshot {
+                address: *address,
+                lamports: account.lamports,
+                record,
+            });
+        }
+    }
+    Ok(snapshots)
+}
+
+// Decode the vault record at `address`, naming it on failure.
+fn decode_record(address: &Pubkey, data: &[u8]) -> Result<VaultRecord, ClientError> {
+    VaultRecord::from_account_data(data)
+        .map_err(|error| custom(format!("Vault record {address}: {error}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pages_split_items() {
+        let items: Vec<u8> = (0..5).collect();
+        assert_eq!(page_of(&items, 0, 2), [0, 1]);
+        assert_eq!(page_of(&items, 2, 2), [4]);
+        assert!(page_of(&items, 3, 2).is_empty());
+        assert!(page_of(&items, usize::MAX, usize::MAX).is_empty());
+    }
+}
@@ -0,0 +1,278 @@
+//! Sending with retries. A transaction whose blockhash expires before it lands is
+//! rebuilt on a fresh blockhash (or the current value of a durable nonce), re-signed
+//! and sent again. An attempt is only replaced by another transaction once it can no
+//! longer land, so a retry never executes the instructions twice.
+use {
+    super::{custom, missing_signers, partially_sign},
+    crate::error::VaultError,
+    solana_client::{client_error::ClientError, rpc_client::RpcClient},
+    solana_program::{
+        hash::Hash,
+        instruction::Instruction,
+        message::Message,
+        nonce::state::{State, Versions},
+        pubkey::Pubkey,
+    },
+    solana_sdk::{
+        signature::{Signature, Signer},
+        transaction::{Transaction, TransactionError},
+    },
+    std::{fmt, thread::sleep, time::Duration},
+};
+
+/// Pause between checks of an attempt that may still land.
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// A durable nonce dating each attempt instead of a recent blockhash.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct NonceConfig {
+    /// The nonce account
+    pub account: Pubkey,
+    /// The nonce authority, which must be among the signers
+    pub authority: Pubkey,
+}
+
+/// How `send_with_retry` sends.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RetryConfig {
+    /// Attempts before giving up, at least one
+    pub max_attempts: usize,
+    /// Durable nonce to date attempts with; `None` uses the latest blockhash
+    pub nonce: Option<NonceConfig>,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            max_attempts: 5,
+            nonce: None,
+        }
+    }
+}
+
+/// Why `send_with_retry` gave up.
+#[derive(Debug)]
+pub enum SendError {
+    /// A vault instruction failed with this error. Failed instructions are not retried.
+    Vault(VaultError),
+    /// Any other failure, or the last one once the attempts ran out.
+    Client(ClientError),
+}
+
+impl fmt::Display for SendError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Vault(error) => write!(f, "Vault error: {error}"),
+            Self::Client(error) => error.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for SendError {}
+
+impl From<ClientError> for SendError {
+    fn from(error: ClientError) -> Self {
+        Self::Client(error)
+    }
+}
+
+/// Send `instructions`, paid by `payer` and signed by `signers`, until confirmed.
+///
+/// Each attempt is dated with the latest blockhash, or with the value of
+/// `config.nonce`, whose advance is then prepended. An attempt that fails to confirm
+/// is replaced once its blockhash expires (a nonce attempt right away: the nonce
+/// advance keeps two attempts from both landing). Errors of the transaction itself
+/// end the retries; custom errors of instructions of `program_id` surface as
+/// `SendError::Vault`.
+pub fn send_with_retry(
+    rpc: &RpcClient,
+    program_id: &Pubkey,
+    instructions: &[Instruction],
+    payer: &Pubkey,
+    signers: &[&dyn Signer],
+    config: &RetryConfig,
+) -> Result<Signature, SendError> {
+    let mut blockhash = recent_blockhash(rpc, config)?;
+    let mut attempt = 0;
+    loop {
+        attempt += 1;
+        let transaction = signed_transaction(instructions, payer, signers, config, blockhash)?;
+        let error = match rpc.send_and_confirm_transaction(&transaction) {
+            Ok(signature) => return Ok(signature),
+            Err(error) => error,
+        };
+        if !is_retryable(&error) || attempt >= config.max_attempts {
+            return Err(send_error(program_id, &transaction, error));
+        }
+        let signature = transaction.signatures[0];
+        let status = match config.nonce {
+            // Read the nonce before the status: an attempt landing in between shows in
+            // the status, and an unadvanced nonce rebuilds the very same transaction.
+            Some(_) => {
+                blockhash = recent_blockhash(rpc, config)?;
+                rpc.get_signature_status(&signature)?
+            }
+            None => {
+                let status = settle(rpc, &signature, &blockhash)?;
+                blockhash = recent_blockhash(rpc, config)?;
+                status
+            }
+        };
+        match status {
+            Some(Ok(())) => return Ok(signature),
+            Some(Err(error)) => return Err(send_error(program_id, &transaction, error.into())),
+            None => {}
+        }
+    }
+}
+
+// The blockhash to date an attempt with: the nonce value, or the latest blockhash.
+fn recent_blockhash(rpc: &RpcClient, config: &RetryConfig) -> Result<Hash, ClientError> {
+    match config.nonce {
+        Some(nonce) => nonce_blockhash(&rpc.get_account(&nonce.account)?.data),
+        None => rpc.get_latest_blockhash(),
+    }
+}
+
+// A transaction of `instructions` dated `blockhash` and signed by every signer.
+fn signed_transaction(
+    instructions: &[Instruction],
+    payer: &Pubkey,
+    signers: &[&dyn Signer],
+    config: &RetryConfig,
+    blockhash: Hash,
+) -> Result<Transaction, ClientError> {
+    let mut message = match config.nonce {
+        Some(nonce) => Message::new_with_nonce(
+            instructions.to_vec(),
+            Some(payer),
+            &nonce.account,
+            &nonce.authority,
+        ),
+        None => Message::new(instructions, Some(payer)),
+    };
+    message.recent_blockhash = blockhash;
+    let mut transaction = Transaction::new_unsigned(message);
+    partially_sign(&mut transaction, signers)?;
+    let missing = missing_signers(&transaction);
+    if !missing.is_empty() {
+        let missing: Vec<String> = missing.iter().map(ToString::to_string).collect();
+        return Err(custom(format!("Missing signers {}", missing.join(", "))));
+    }
+    Ok(transaction)
+}
+
+// The blockhash stored in durable nonce account `data`.
+fn nonce_blockhash(data: &[u8]) -> Result<Hash, ClientError> {
+    let versions: Versions = bincode::deserialize(data)
+        .map_err(|error| custom(format!("Invalid nonce account: {error}")))?;
+    match versions.state() {
+        State::Initialized(data) => Ok(data.blockhash()),
+        State::Uninitialized => Err(custom("Nonce account is not initialized".to_string())),
+    }
+}
+
+// Wait until the attempt `signature`, dated `blockhash`, can no longer land, and
+// return its status: `None` if it never did.
+fn settle(
+    rpc: &RpcClient,
+    signature: &Signature,
+    blockhash: &Hash,
+) -> Result<Option<Result<(), TransactionError>>, ClientError> {
+    loop {
+        let status = rpc.get_signature_status(signature)?;
+        if status.is_some() || !rpc.is_blockhash_valid(blockhash, rpc.commitment())? {
+            return Ok(status);
+        }
+        sleep(POLL_INTERVAL);
+    }
+}
+
+// Whether `error` may pass on another attempt: transport failures and expired
+// blockhashes, not errors of the transaction itself.
+fn is_retryable(error: &ClientError) -> bool {
+    matches!(
+        error.get_transaction_error(),
+        None | Some(TransactionError::BlockhashNotFound)
+    )
+}
+
+// `error` of `transaction`, as a vault error if a vault instruction failed with one.
+fn send_error(program_id: &Pubkey, transaction: &Transaction, error: ClientError) -> SendError {
+    let vault_error = match error.get_transaction_error() {
+        Some(TransactionError::InstructionError(index, instruction_error)) => transaction
+            .message
+            .instructions
+            .get(usize::from(index))
+            .filter(|instruction| {
+                instruction.program_id(&transaction.message.account_keys) == program_id
+            })
+            .and_then(|_| VaultError::from_instruction_error(&instruction_error)),
+        _ => None,
+    };
+    match vault_error {
+        Some(error) => SendError::Vault(error),
+        None => SendError::Client(error),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        solana_program::{
+            instruction::InstructionError,
+            nonce::state::{Data, DurableNonce},
+        },
+    };
+
+    #[test]
+    fn nonce_blockhash_read() {
+        let blockhash = Hash::new_unique();
+        let durable = DurableNonce::from_blockhash(&blockhash);
+        let expected = *durable.as_hash();
+        let data = Data::new(Pubkey::new_unique(), durable, 5_000);
+        let account = bincode::serialize(&Versions::new(State::Initialized(data))).unwrap();
+        assert_eq!(nonce_blockhash(&account).unwrap(), expected);
+
+        let account = bincode::serialize(&Versions::new(State::Uninitialized)).unwrap();
+        assert!(nonce_blockhash(&account).is_err());
+        assert!(nonce_blockhash(&[1, 2]).is_err());
+    }
+
+    #[test]
+    fn only_expired_blockhashes_retried() {
+        assert!(is_retryable(&custom("connection reset".to_string())));
+        assert!(is_retryable(&TransactionError::BlockhashNotFound.into()));
+        assert!(!is_retryable(&TransactionError::AccountInUse.into()));
+    }
+
+    #[test]
+    fn vault_errors_decoded_for_vault_instructions() {
+        let payer = Pubkey::new_unique();
+        let other = Pubkey::new_unique();
+        let instructions = [
+            Instruction::new_with_bytes(other, &[], vec![]),
+            Instruction::new_with_bytes(crate::id(), &[], vec![]),
+        ];
+        let transaction = Transaction::new_unsigned(Message::new(&instructions, Some(&payer)));
+        let failed = |index| {
+            ClientError::from(TransactionError::InstructionError(
+                index,
+                InstructionError::Custom(VaultError::StaleState as u32),
+            ))
+        };
+        assert!(matches!(
+            send_error(&crate::id(), &transaction, failed(1)),
+            SendError::Vault(VaultError::StaleState)
+        ));
+        assert!(matches!(
+            send_error(&crate::id(), &transaction, failed(0)),
+            SendError::Client(_)
+        ));
+        assert!(matches!(
+            send_error(&crate::id(), &transaction, failed(2)),
+            SendError::Client(_)
+        ));
+    }
+}
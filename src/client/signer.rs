@@ -0,0 +1,59 @@
+//! Signers named by path: a keypair file, or a hardware wallet such as
+//! `usb://ledger?key=0/0`, for custody policies that keep authority and DART keys
+//! off disk.
+use {
+    super::custom,
+    solana_client::client_error::ClientError,
+    solana_remote_wallet::{
+        locator::Locator, remote_keypair::generate_remote_keypair,
+        remote_wallet::maybe_wallet_manager,
+    },
+    solana_sdk::{
+        derivation_path::DerivationPath,
+        signature::{read_keypair_file, Signer},
+    },
+    std::fmt::Display,
+};
+
+/// Prefix of hardware wallet paths.
+pub const USB_PREFIX: &str = "usb://";
+
+/// The signer at `path`: a keypair file, or a hardware wallet at
+/// `usb://<manufacturer>[/<pubkey>][?key=<account>[/<change>]]`. Hardware wallets ask
+/// for approval on the device each time they sign.
+pub fn signer_from_path(path: &str) -> Result<Box<dyn Signer>, ClientError> {
+    if !path.starts_with(USB_PREFIX) {
+        let keypair = read_keypair_file(path)
+            .map_err(|error| custom(format!("Keypair file {path}: {error}")))?;
+        return Ok(Box::new(keypair));
+    }
+    let (locator, key) = match path.split_once("?key=") {
+        Some((locator, key)) => (locator, Some(key)),
+        None => (path, None),
+    };
+    let failed = |error: &dyn Display| custom(format!("Hardware wallet {path}: {error}"));
+    let locator = Locator::new_from_path(locator).map_err(|error| failed(&error))?;
+    let derivation_path = key
+        .map(DerivationPath::from_key_str)
+        .transpose()
+        .map_err(|error| failed(&error))?
+        .unwrap_or_default();
+    let wallet_manager = maybe_wallet_manager()
+        .map_err(|error| failed(&error))?
+        .ok_or_else(|| failed(&"no device connected"))?;
+    let keypair = generate_remote_keypair(locator, derivation_path, &wallet_manager, false, path)
+        .map_err(|error| failed(&error))?;
+    Ok(Box::new(keypair))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn invalid_paths_fail_before_the_device() {
+        assert!(signer_from_path("usb://ledger?key=not-a-key").is_err());
+        assert!(signer_from_path("usb://").is_err());
+        assert!(signer_from_path("/nonexistent/keypair.json").is_err());
+    }
+}
@@ -1,10 +1,56 @@
 #![cfg(all(target_os = "solana", not(feature = "no-entrypoint")))]
-use crate::processor::Processor;
+use crate::{error::VaultError, processor::Processor};
 use solana_program::{
-    account_info::AccountInfo, entrypoint, entrypoint::ProgramResult, pubkey::Pubkey,
+    account_info::AccountInfo, entrypoint::ProgramResult, program_error::PrintProgramError,
+    pubkey::Pubkey,
 };
 
-entrypoint!(process_instruction);
+#[cfg(not(feature = "no-alloc-entrypoint"))]
+solana_program::entrypoint!(process_instruction);
+
+// With `no-alloc-entrypoint`, the accounts are deserialized onto the stack rather than
+// into a `Vec`, falling back to `deserialize` past `MAX_STACK_ACCOUNTS`. The allocator
+// and panic handler `entrypoint!` would declare are declared below.
+#[cfg(feature = "no-alloc-entrypoint")]
+#[no_mangle]
+pub unsafe extern "C" fn entrypoint(input: *mut u8) -> u64 {
+    use {
+        crate::input::{self, MAX_STACK_ACCOUNTS},
+        std::mem::MaybeUninit,
+    };
+
+    // An array of `MaybeUninit` needs no initialization.
+    let mut accounts: [MaybeUninit<AccountInfo>; MAX_STACK_ACCOUNTS] =
+        MaybeUninit::uninit().assume_init();
+    let result = match input::deserialize_into(input, &mut accounts) {
+        Some((program_id, len, instruction_data)) => {
+            process_instruction(program_id, input::written(&accounts, len), instruction_data)
+        }
+        None => {
+            let (program_id, accounts, instruction_data) =
+                solana_program::entrypoint::deserialize(input);
+            process_instruction(program_id, &accounts, instruction_data)
+        }
+    };
+    match result {
+        Ok(()) => solana_program::entrypoint::SUCCESS,
+        Err(error) => error.into(),
+    }
+}
+
+#[cfg(feature = "no-alloc-entrypoint")]
+solana_program::custom_heap_default!();
+#[cfg(feature = "no-alloc-entrypoint")]
+solana_program::custom_panic_default!();
+
+// With `custom-heap`, `entrypoint!` skips the default 32KiB allocator in favour of this one.
+#[cfg(feature = "custom-heap")]
+#[global_allocator]
+static ALLOCATOR: solana_program::entrypoint::BumpAllocator =
+    solana_program::entrypoint::BumpAllocator {
+        start: solana_program::entrypoint::HEAP_START_ADDRESS as usize,
+        len: crate::HEAP_LENGTH,
+    };
 
 /// Program entrypoint for processing instructions.
 fn process_instruction(
@@ -12,5 +58,10 @@ fn process_instruction(
     accounts: &[AccountInfo],
     instruction: &[u8],
 ) -> ProgramResult {
-    Processor::process_instruction(program_id, accounts, instruction)
+    if let Err(error) = Processor::process_instruction(program_id, accounts, instruction) {
+        // Catch the error so we can print it
+        error.print::<VaultError>();
+        return Err(error);
+    }
+    Ok(())
 }
@@ -1,25 +1,304 @@
-use num_derive::FromPrimitive;
-use solana_program::{decode_error::DecodeError, program_error::ProgramError};
-use thiserror::Error;
+use spl_program_error::*;
 
 /// Custom errors that may be returned by the program.
-#[derive(Clone, Debug, Eq, Error, FromPrimitive, PartialEq)]
+///
+/// Codes are explicit so they stay stable as variants are added.
+#[spl_program_error]
 pub enum VaultError {
     /// Incorrect authority provided in an instruction.
     #[error("Incorrect authority provided on update or delete")]
-    IncorrectAuthority,
+    IncorrectAuthority = 0,
 
     /// Calculation overflow.
     #[error("Calculation overflow")]
-    Overflow,
-}
-impl From<VaultError> for ProgramError {
-    fn from(e: VaultError) -> Self {
-        ProgramError::Custom(e as u32)
-    }
-}
-impl<T> DecodeError<T> for VaultError {
-    fn type_of() -> &'static str {
-        "Record Error"
-    }
+    Overflow = 1,
+
+    /// Incorrect securities intermediary provided in an instruction.
+    #[error("Incorrect DART provided on update or delete")]
+    IncorrectDart = 2,
+
+    /// New authority is the same as the current authority.
+    #[error("New authority matches the current authority")]
+    AuthorityUnchanged = 3,
+
+    /// New authority is the default pubkey.
+    #[error("New authority must not be the default pubkey")]
+    InvalidAuthority = 4,
+
+    /// New authority is the securities intermediary.
+    #[error("New authority must not be the DART")]
+    AuthorityIsDart = 5,
+
+    /// The same account was provided for more than one role.
+    #[error("Account provided for more than one role")]
+    DuplicateAccount = 6,
+
+    /// An account that must be writable was provided read-only.
+    #[error("Account must be writable")]
+    ReadOnlyAccount = 7,
+
+    /// An executable account was provided for a non-program role.
+    #[error("Account must not be executable")]
+    ExecutableAccount = 8,
+
+    /// The vault record has been closed.
+    #[error("Vault record account is closed")]
+    AccountClosed = 9,
+
+    /// The vault record has not been closed.
+    #[error("Vault record account is not closed")]
+    AccountNotClosed = 10,
+
+    /// The requested status change is not allowed.
+    #[error("Invalid vault status transition")]
+    InvalidStatusTransition = 11,
+
+    /// The vault status does not permit the instruction.
+    #[error("Vault is not active")]
+    VaultNotActive = 12,
+
+    /// New DART is the same as the current DART.
+    #[error("New DART matches the current DART")]
+    DartUnchanged = 13,
+
+    /// New DART is the default pubkey.
+    #[error("New DART must not be the default pubkey")]
+    InvalidDart = 14,
+
+    /// No DART succession has been proposed.
+    #[error("No pending DART to accept")]
+    NoPendingDart = 15,
+
+    /// Operational key slot is out of range.
+    #[error("Invalid operational key slot")]
+    InvalidOperationalKeySlot = 16,
+
+    /// Incorrect DART identity master key provided.
+    #[error("Incorrect DART identity master key")]
+    IncorrectMasterKey = 17,
+
+    /// The session key has expired.
+    #[error("Session key expired")]
+    SessionExpired = 18,
+
+    /// The session scope does not cover the instruction.
+    #[error("Session scope does not permit the instruction")]
+    SessionScopeNotAllowed = 19,
+
+    /// The session key is the default pubkey or one of the vault roles.
+    #[error("Invalid session key")]
+    InvalidSessionKey = 20,
+
+    /// All guardian slots are in use.
+    #[error("Too many guardians")]
+    TooManyGuardians = 21,
+
+    /// The account is not a guardian of the vault.
+    #[error("Guardian not found")]
+    GuardianNotFound = 22,
+
+    /// Guardian is the default pubkey, a vault role, or already a guardian.
+    #[error("Invalid guardian")]
+    InvalidGuardian = 23,
+
+    /// Threshold is zero or exceeds the number of guardians.
+    #[error("Invalid guardian threshold")]
+    InvalidThreshold = 24,
+
+    /// No recovery is pending.
+    #[error("No recovery pending")]
+    RecoveryNotPending = 25,
+
+    /// A recovery is already pending.
+    #[error("Recovery already pending")]
+    RecoveryPending = 26,
+
+    /// Not enough guardians approved the recovery.
+    #[error("Recovery threshold not met")]
+    RecoveryThresholdNotMet = 27,
+
+    /// The recovery challenge period has not elapsed.
+    #[error("Recovery challenge period active")]
+    ChallengePeriodActive = 28,
+
+    /// No beneficiary is designated by the current authority.
+    #[error("No beneficiary designated")]
+    NoBeneficiary = 29,
+
+    /// Incorrect beneficiary provided in an instruction.
+    #[error("Incorrect beneficiary")]
+    IncorrectBeneficiary = 30,
+
+    /// Beneficiary is one of the vault roles.
+    #[error("Invalid beneficiary")]
+    InvalidBeneficiary = 31,
+
+    /// The inactivity period is zero.
+    #[error("Invalid inactivity period")]
+    InvalidInactivityPeriod = 32,
+
+    /// The authority has not been inactive long enough.
+    #[error("Inactivity period active")]
+    InactivityPeriodActive = 33,
+
+    /// Requested account data length is out of range or does not match the account.
+    #[error("Invalid account data length")]
+    InvalidDataLength = 34,
+
+    /// Close recipient is the default pubkey or the vault account.
+    #[error("Invalid close recipient")]
+    InvalidCloseRecipient = 35,
+
+    /// No escrowed authority transfer is pending.
+    #[error("No pending transfer")]
+    NoPendingTransfer = 36,
+
+    /// The escrowed authority transfer was not accepted in time.
+    #[error("Transfer expired")]
+    TransferExpired = 37,
+
+    /// The escrowed authority transfer may still be accepted.
+    #[error("Transfer not expired")]
+    TransferNotExpired = 38,
+
+    /// The transfer timeout is zero.
+    #[error("Invalid transfer timeout")]
+    InvalidTransferTimeout = 39,
+
+    /// The vault's oracle account is missing or not a price feed.
+    #[error("Oracle unavailable")]
+    OracleUnavailable = 40,
+
+    /// The vault's oracle has not published recently enough.
+    #[error("Oracle stale")]
+    OracleStale = 41,
+
+    /// The vault's oracle reports a closed market or a price below the floor.
+    #[error("Oracle rejected the transfer")]
+    OracleRejected = 42,
+
+    /// The vault already has a custody receipt.
+    #[error("Receipt already minted")]
+    ReceiptAlreadyMinted = 43,
+
+    /// The token account holding the vault's receipt was not provided.
+    #[error("Receipt not found")]
+    ReceiptNotFound = 44,
+
+    /// The vault authority moves by transferring its receipt token.
+    #[error("Authority follows the receipt token")]
+    AuthorityFollowsReceipt = 45,
+
+    /// The vault linked to the mint is frozen, seized or closed.
+    #[error("Token transfers restricted")]
+    TransfersRestricted = 46,
+
+    /// The destination owner is not on the mint's allowlist.
+    #[error("Holder not allowed")]
+    HolderNotAllowed = 47,
+
+    /// Allowlist slot is out of range.
+    #[error("Invalid allowlist slot")]
+    InvalidAllowlistSlot = 48,
+
+    /// The vault record does not match its checksum.
+    #[error("Record checksum mismatch")]
+    ChecksumMismatch = 49,
+
+    /// A role key is the default pubkey, the system program or the vault program.
+    #[error("Invalid role key")]
+    InvalidRoleKey = 50,
+
+    /// The record authority is not the one the transaction was built against.
+    #[error("Stale vault state")]
+    StaleState = 51,
+
+    /// The instruction was bounded to a slot that has passed.
+    #[error("Instruction expired")]
+    InstructionExpired = 52,
+
+    /// The quorum exceeds the operational keys of a DART identity.
+    #[error("Invalid DART quorum")]
+    InvalidQuorum = 53,
+
+    /// Too few operational keys signed the DART approval.
+    #[error("DART quorum not met")]
+    QuorumNotMet = 54,
+
+    /// The signer is not the program's upgrade authority.
+    #[error("Not the upgrade authority")]
+    NotUpgradeAuthority = 55,
+
+    /// The program was decommissioned; its successor is logged.
+    #[error("Program migrated")]
+    ProgramMigrated = 56,
+
+    /// The program is not the successor recorded in the program config.
+    #[error("Not the successor program")]
+    NotSuccessor = 57,
+
+    /// The Merkle proof does not take the approval to the DART's approval root.
+    #[error("Invalid approval proof")]
+    InvalidProof = 58,
+
+    /// The vault's current authority has not set a note key.
+    #[error("Note key not set")]
+    NoteKeyNotSet = 59,
+
+    /// Every attestation of the vault is live.
+    #[error("Attestation list full")]
+    AttestationListFull = 60,
+
+    /// The vault has no attestation of the kind and hash.
+    #[error("Attestation not found")]
+    AttestationNotFound = 61,
+
+    /// The vault is pledged as collateral, and the lender has not signed.
+    #[error("Vault pledged")]
+    VaultPledged = 62,
+
+    /// The vault is not pledged as collateral.
+    #[error("Vault not pledged")]
+    NotPledged = 63,
+
+    /// Vaults of a distribution must be paid in ascending order, after its cursor.
+    #[error("Distribution out of order")]
+    DistributionOutOfOrder = 64,
+
+    /// The vault's or its DART's transfer window does not allow transfers at this slot.
+    #[error("Transfer window closed")]
+    TransferWindowClosed = 65,
+
+    /// The vault took the mutations its rate limit allows this epoch.
+    #[error("Rate limited")]
+    RateLimited = 66,
+
+    /// The authority's vault index lists as many vaults as it holds.
+    #[error("Vault index full")]
+    VaultIndexFull = 67,
+
+    /// The crank only sweeps sessions and approval roots past their grace period.
+    #[error("Not sweepable")]
+    NotSweepable = 68,
+
+    /// A position or token account of the vault still holds assets.
+    #[error("Vault not empty")]
+    VaultNotEmpty = 69,
+
+    /// Fee exemption slot is out of range.
+    #[error("Invalid fee exemption slot")]
+    InvalidFeeExemptionSlot = 70,
+
+    /// A sysvar account the instruction reads was not passed, or another account was
+    /// passed in its place.
+    #[error("Incorrect sysvar")]
+    IncorrectSysvar = 71,
+
+    /// A program account the instruction invokes is not the expected program.
+    #[error("Incorrect program")]
+    IncorrectProgram = 72,
+
+    /// The vault's DART policy bars it from the instruction.
+    #[error("Vault restricted")]
+    VaultRestricted = 73,
 }
@@ -0,0 +1,358 @@
+//! Events for off-chain indexers: authority changes, closes, rekeys, notes, corporate
+//! actions, voting delegations and fee collections. Each event is logged with `sol_log_data` as its
+//! Anchor event discriminator followed by its borsh encoding.
+//!
+//! A close also sets its `CloseReceipt`, encoded the same way, as the instruction's
+//! return data, for programs closing vaults by CPI. `CheckTransfer` sets a
+//! `TransferCheck` the same way, without logging it.
+//!
+//! With the `emit-cpi` feature, an instruction passed the event authority and the
+//! program account also records its events as a self-CPI (the Anchor `emit_cpi!`
+//! pattern), whose instruction data survives the log truncation of busy RPC nodes.
+//!
+//! Each event has a protobuf counterpart in `proto` (`proto/vault.proto`), which must
+//! change with it.
+#[cfg(feature = "emit-cpi")]
+use solana_program::{
+    instruction::{AccountMeta, Instruction},
+    msg,
+    program::invoke_signed,
+};
+use {
+    crate::state::CorporateActionType,
+    borsh::{BorshDeserialize, BorshSerialize},
+    solana_program::{
+        account_info::AccountInfo, entrypoint::ProgramResult, log::sol_log_data,
+        program_error::ProgramError, pubkey::Pubkey,
+    },
+    spl_discriminator::SplDiscriminate,
+};
+
+/// Seed of the event authority, the PDA signing self-CPI events: `[EVENT_AUTHORITY_SEED]`.
+pub const EVENT_AUTHORITY_SEED: &[u8] = b"__event_authority";
+
+/// Anchor's `EVENT_IX_TAG_LE`, which prefixes self-CPI event instruction data.
+pub const EVENT_IX_TAG_LE: [u8; 8] = [0xe4, 0x45, 0xa5, 0x2e, 0x51, 0xcb, 0x9a, 0x1d];
+
+/// Prefix of the log line `sol_log_data` writes.
+pub const DATA_LOG_PREFIX: &str = "Program data: ";
+
+/// The authority of a vault changed hands.
+#[derive(Clone, Debug, BorshSerialize, BorshDeserialize, PartialEq, Eq, SplDiscriminate)]
+#[discriminator_hash_input("event:AuthorityTransferred")]
+pub struct AuthorityTransferred {
+    /// The vault record
+    pub vault: Pubkey,
+    /// The authority before the change
+    pub previous_authority: Pubkey,
+    /// The authority after the change
+    pub authority: Pubkey,
+}
+
+/// A vault was closed and its lamports paid out.
+#[derive(Clone, Debug, BorshSerialize, BorshDeserialize, PartialEq, Eq, SplDiscriminate)]
+#[discriminator_hash_input("event:VaultClosed")]
+pub struct VaultClosed {
+    /// The vault record
+    pub vault: Pubkey,
+    /// The authority at close
+    pub authority: Pubkey,
+    /// The account paid the vault's lamports
+    pub recipient: Pubkey,
+    /// Lamports paid out, net of any close fee
+    pub lamports: u64,
+}
+
+/// A vault record moved to a new account, closing the old one.
+#[derive(Clone, Debug, BorshSerialize, BorshDeserialize, PartialEq, Eq, SplDiscriminate)]
+#[discriminator_hash_input("event:VaultRekeyed")]
+pub struct VaultRekeyed {
+    /// The closed vault record account
+    pub vault: Pubkey,
+    /// The account now holding the record
+    pub new_vault: Pubkey,
+    /// The authority at the move
+    pub authority: Pubkey,
+}
+
+/// The DART annotated a vault with `AddNote`.
+#[derive(Clone, Debug, BorshSerialize, BorshDeserialize, PartialEq, Eq, SplDiscriminate)]
+#[discriminator_hash_input("event:NoteAdded")]
+pub struct NoteAdded {
+    /// The vault record
+    pub vault: Pubkey,
+    /// Sequence number of the note in the vault's notes log
+    pub sequence: u64,
+    /// Kind of corporate action
+    pub category: u16,
+    /// Hash of the annotation document
+    pub hash: [u8; 32],
+}
+
+/// The DART applied a corporate action to a vault's position with
+/// `ApplyCorporateAction`, one event per vault of the batch.
+#[derive(Clone, Debug, BorshSerialize, BorshDeserialize, PartialEq, Eq, SplDiscriminate)]
+#[discriminator_hash_input("event:CorporateActionApplied")]
+pub struct CorporateActionApplied {
+    /// The vault record
+    pub vault: Pubkey,
+    /// The corporate action
+    pub action_type: CorporateActionType,
+    /// Ratio of the split or reverse split
+    pub ratio: u64,
+    /// Asset id before the action
+    pub previous_asset_id: [u8; 32],
+    /// Asset id after the action
+    pub asset_id: [u8; 32],
+    /// Balance before the action
+    pub previous_balance: u64,
+    /// Balance after the action
+    pub balance: u64,
+}
+
+/// The authority of a vault delegated its votes with `DelegateVoting`.
+#[derive(Clone, Debug, BorshSerialize, BorshDeserialize, PartialEq, Eq, SplDiscriminate)]
+#[discriminator_hash_input("event:VotingDelegated")]
+pub struct VotingDelegated {
+    /// The vault record
+    pub vault: Pubkey,
+    /// The delegating authority
+    pub authority: Pubkey,
+    /// The proxy, or the default pubkey when the delegation was revoked
+    pub proxy: Pubkey,
+}
+
+/// Settlement receipt of a vault closed with `CloseAccount` or its variants, emitted
+/// and set as the instruction's return data; read it back with `decode`.
+#[derive(Clone, Debug, BorshSerialize, BorshDeserialize, PartialEq, Eq, SplDiscriminate)]
+#[discriminator_hash_input("event:CloseReceipt")]
+pub struct CloseReceipt {
+    /// The vault record
+    pub vault: Pubkey,
+    /// The authority at close
+    pub authority: Pubkey,
+    /// Lamports returned from the vault account, net of any close fee
+    pub lamports: u64,
+    /// Slot of the close
+    pub slot: u64,
+}
+
+/// Outcome of a `CheckTransfer`, set as the instruction's return data; read it back
+/// with `decode`.
+#[derive(Clone, Debug, BorshSerialize, BorshDeserialize, PartialEq, Eq, SplDiscriminate)]
+#[discriminator_hash_input("event:TransferCheck")]
+pub struct TransferCheck {
+    /// The vault record
+    pub vault: Pubkey,
+    /// The authority checked
+    pub authority: Pubkey,
+    /// The new authority checked
+    pub new_authority: Pubkey,
+    /// Zero when the transfer would pass, else the `u64` code of the `ProgramError` it
+    /// would fail with
+    pub error: u64,
+}
+
+impl TransferCheck {
+    /// What the transfer would return.
+    pub fn result(&self) -> Result<(), ProgramError> {
+        match self.error {
+            0 => Ok(()),
+            error => Err(ProgramError::from(error)),
+        }
+    }
+}
+
+/// The config admin collected the accumulated fees with `CollectFees`.
+#[derive(Clone, Debug, BorshSerialize, BorshDeserialize, PartialEq, Eq, SplDiscriminate)]
+#[discriminator_hash_input("event:FeesCollected")]
+pub struct FeesCollected {
+    /// The fee config the fees accumulated in
+    pub fee_config: Pubkey,
+    /// The fee destination paid
+    pub destination: Pubkey,
+    /// Lamports paid out
+    pub lamports: u64,
+}
+
+/// Find the event authority address.
+pub fn find_event_authority_address(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[EVENT_AUTHORITY_SEED], program_id)
+}
+
+/// Discriminator followed by the borsh encoding of `event`.
+pub fn event_data<E: BorshSerialize + SplDiscriminate>(event: &E) -> Vec<u8> {
+    let mut data = E::SPL_DISCRIMINATOR_SLICE.to_vec();
+    // Writing to a `Vec` cannot fail.
+    event.serialize(&mut data).unwrap();
+    data
+}
+
+/// Decode `data`, an event discriminator followed by the event's borsh encoding, as
+/// `E`: logged event data, or the return data of a close.
+pub fn decode<E: BorshDeserialize + SplDiscriminate>(data: &[u8]) -> Option<E> {
+    let body = data.strip_prefix(E::SPL_DISCRIMINATOR_SLICE)?;
+    E::try_from_slice(body).ok()
+}
+
+/// The base64 data logged by `program_id` in `logs`, the log messages of a
+/// transaction, one entry per logged slice, whether or not it decodes as an event.
+/// Data logged by other programs, including programs the vault invokes, is skipped.
+pub fn program_data<'a>(program_id: &Pubkey, logs: &'a [String]) -> Vec<&'a str> {
+    let program = program_id.to_string();
+    let mut stack: Vec<&str> = vec![];
+    let mut data = vec![];
+    for log in logs {
+        if let Some(logged) = log.strip_prefix(DATA_LOG_PREFIX) {
+            if stack.last() == Some(&program.as_str()) {
+                data.extend(logged.split(' '));
+            }
+            continue;
+        }
+        let mut words = log.split(' ');
+        if let (Some("Program"), Some(id), Some(action)) =
+            (words.next(), words.next(), words.next())
+        {
+            if action == "invoke" {
+                stack.push(id);
+            } else if action == "success" || action == "failed:" {
+                stack.pop();
+            }
+        }
+    }
+    data
+}
+
+/// Log `event`, and with `emit-cpi` record it as a self-CPI when `accounts` hold the
+/// event authority.
+#[cfg_attr(not(feature = "emit-cpi"), allow(unused_variables))]
+pub fn emit<E: BorshSerialize + SplDiscriminate>(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    event: &E,
+) -> ProgramResult {
+    let data = event_data(event);
+    sol_log_data(&[&data]);
+    #[cfg(feature = "emit-cpi")]
+    emit_cpi(program_id, accounts, &data)?;
+    Ok(())
+}
+
+#[cfg(feature = "emit-cpi")]
+fn emit_cpi(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let (address, bump) = find_event_authority_address(program_id);
+    let Some(authority) = accounts.iter().find(|account| *account.key == address) else {
+        return Ok(());
+    };
+    let program = accounts
+        .iter()
+        .find(|account| account.key == program_id)
+        .ok_or_else(|| {
+            msg!("Missing program account for the event self-CPI");
+            ProgramError::NotEnoughAccountKeys
+        })?;
+    let mut instruction_data = EVENT_IX_TAG_LE.to_vec();
+    instruction_data.extend_from_slice(data);
+    invoke_signed(
+        &Instruction {
+            program_id: *program_id,
+            accounts: vec![AccountMeta::new_readonly(address, true)],
+            data: instruction_data,
+        },
+        &[authority.clone(), program.clone()],
+        &[&[EVENT_AUTHORITY_SEED, &[bump]]],
+    )
+}
+
+/// Accept a self-CPI event: only the event authority may sign one, so indexers can
+/// trust events found in the program's own inner instructions.
+#[cfg(feature = "emit-cpi")]
+pub fn process_event(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let authority = accounts.first().ok_or(ProgramError::NotEnoughAccountKeys)?;
+    if *authority.key != find_event_authority_address(program_id).0 || !authority.is_signer {
+        msg!("Events may only be emitted by the program");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, solana_program::hash::hash};
+
+    #[test]
+    fn event_ix_tag_matches_anchor() {
+        let mut tag = hash(b"anchor:event").to_bytes()[..8].to_vec();
+        tag.reverse();
+        assert_eq!(EVENT_IX_TAG_LE.to_vec(), tag);
+    }
+
+    #[test]
+    fn serialize_authority_transferred() {
+        let event = AuthorityTransferred {
+            vault: Pubkey::new_from_array([1; 32]),
+            previous_authority: Pubkey::new_from_array([2; 32]),
+            authority: Pubkey::new_from_array([3; 32]),
+        };
+        let data = event_data(&event);
+        assert_eq!(
+            data[..8],
+            hash(b"event:AuthorityTransferred").to_bytes()[..8]
+        );
+        assert_eq!(data.len(), 8 + 96);
+        assert_eq!(
+            AuthorityTransferred::try_from_slice(&data[8..]).unwrap(),
+            event
+        );
+    }
+
+    #[test]
+    fn serialize_vault_closed() {
+        let event = VaultClosed {
+            vault: Pubkey::new_from_array([1; 32]),
+            authority: Pubkey::new_from_array([2; 32]),
+            recipient: Pubkey::new_from_array([3; 32]),
+            lamports: 42,
+        };
+        let data = event_data(&event);
+        assert_eq!(data[..8], hash(b"event:VaultClosed").to_bytes()[..8]);
+        assert_eq!(data[8 + 96..], 42u64.to_le_bytes());
+    }
+
+    #[test]
+    fn decode_close_receipt() {
+        let receipt = CloseReceipt {
+            vault: Pubkey::new_from_array([1; 32]),
+            authority: Pubkey::new_from_array([2; 32]),
+            lamports: 42,
+            slot: 7,
+        };
+        let data = event_data(&receipt);
+        assert_eq!(data[..8], hash(b"event:CloseReceipt").to_bytes()[..8]);
+        assert_eq!(decode::<CloseReceipt>(&data), Some(receipt));
+        assert_eq!(decode::<VaultClosed>(&data), None);
+        assert_eq!(decode::<CloseReceipt>(&data[..40]), None);
+    }
+
+    #[test]
+    fn transfer_check_result() {
+        let mut check = TransferCheck {
+            vault: Pubkey::new_from_array([1; 32]),
+            authority: Pubkey::new_from_array([2; 32]),
+            new_authority: Pubkey::new_from_array([3; 32]),
+            error: 0,
+        };
+        assert_eq!(check.result(), Ok(()));
+        check.error = ProgramError::from(crate::error::VaultError::VaultPledged).into();
+        assert_eq!(
+            check.result(),
+            Err(crate::error::VaultError::VaultPledged.into())
+        );
+        check.error = ProgramError::NotEnoughAccountKeys.into();
+        assert_eq!(check.result(), Err(ProgramError::NotEnoughAccountKeys));
+
+        let data = event_data(&check);
+        assert_eq!(data[..8], hash(b"event:TransferCheck").to_bytes()[..8]);
+        assert_eq!(decode::<TransferCheck>(&data), Some(check));
+    }
+}
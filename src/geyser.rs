@@ -0,0 +1,358 @@
+//! Payloads for Geyser plugins. A plugin copies the fields of the replica account or
+//! transaction it is notified of into an `AccountUpdate` or `TransactionUpdate`, and
+//! `decode` tells whether it concerns the vault program, decoding the account, the
+//! top-level vault instructions and the logged events, so plugins route vault updates
+//! without parsing account layouts or logs themselves.
+use {
+    crate::{
+        event::program_data,
+        instruction::VaultInstruction,
+        state::{
+            ApprovalRoot, AttestationList, AuditTrail, CommitmentRecord, DartIdentity, DartPolicy,
+            DistributionCursor, FeeConfig, InheritanceRecord, NoteRecord, NotesLog, Notification,
+            NotifyRecord, PledgeRecord, PositionRecord, ProgramConfig, ProgramStats, RateLimit,
+            RecoveryRecord, SessionRecord, SnapshotRecord, TransferHookConfig, TransferWindow,
+            VaultIndex, VaultRecord, VotingProxy,
+        },
+    },
+    base64::{engine::general_purpose::STANDARD, Engine},
+    borsh::BorshDeserialize,
+    solana_program::{instruction::CompiledInstruction, pubkey::Pubkey},
+    spl_discriminator::SplDiscriminate,
+};
+
+/// An account update, as carried by a Geyser `ReplicaAccountInfo`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AccountUpdate<'a> {
+    /// The account address
+    pub pubkey: Pubkey,
+    /// The account owner
+    pub owner: Pubkey,
+    /// Lamports held by the account
+    pub lamports: u64,
+    /// The account data
+    pub data: &'a [u8],
+    /// Slot of the update
+    pub slot: u64,
+    /// Write version, ordering updates of the account within a slot
+    pub write_version: u64,
+}
+
+/// A transaction update, as carried by a Geyser `ReplicaTransactionInfo`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TransactionUpdate<'a> {
+    /// The transaction signature
+    pub signature: [u8; 64],
+    /// Slot of the update
+    pub slot: u64,
+    /// Whether the transaction succeeded; failed transactions change no vault state
+    pub succeeded: bool,
+    /// Account keys of the message, loaded addresses included, in order
+    pub account_keys: &'a [Pubkey],
+    /// Top-level instructions of the message
+    pub instructions: &'a [CompiledInstruction],
+    /// Log messages of the transaction
+    pub log_messages: &'a [String],
+}
+
+/// An update notified to a Geyser plugin.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Update<'a> {
+    Account(AccountUpdate<'a>),
+    Transaction(TransactionUpdate<'a>),
+}
+
+/// An account of the vault program, decoded by its discriminator. Accounts are boxed:
+/// their sizes differ widely.
+#[derive(Clone, Debug, PartialEq)]
+pub enum VaultAccount {
+    VaultRecord(Box<VaultRecord>),
+    DartIdentity(Box<DartIdentity>),
+    SessionRecord(Box<SessionRecord>),
+    RecoveryRecord(Box<RecoveryRecord>),
+    InheritanceRecord(Box<InheritanceRecord>),
+    TransferHookConfig(Box<TransferHookConfig>),
+    ProgramConfig(Box<ProgramConfig>),
+    FeeConfig(Box<FeeConfig>),
+    ProgramStats(Box<ProgramStats>),
+    ApprovalRoot(Box<ApprovalRoot>),
+    NoteRecord(Box<NoteRecord>),
+    NotifyRecord(Box<NotifyRecord>),
+    Notification(Box<Notification>),
+    CommitmentRecord(Box<CommitmentRecord>),
+    NotesLog(Box<NotesLog>),
+    AttestationList(Box<AttestationList>),
+    PledgeRecord(Box<PledgeRecord>),
+    PositionRecord(Box<PositionRecord>),
+    DistributionCursor(Box<DistributionCursor>),
+    SnapshotRecord(Box<SnapshotRecord>),
+    VotingProxy(Box<VotingProxy>),
+    TransferWindow(Box<TransferWindow>),
+    RateLimit(Box<RateLimit>),
+    AuditTrail(Box<AuditTrail>),
+    DartPolicy(Box<DartPolicy>),
+    VaultIndex(Box<VaultIndex>),
+}
+
+/// A transaction that invoked the vault program.
+#[derive(Clone, Debug, PartialEq)]
+pub struct VaultTransaction {
+    /// The transaction signature
+    pub signature: [u8; 64],
+    /// Slot of the update
+    pub slot: u64,
+    /// Whether the transaction succeeded
+    pub succeeded: bool,
+    /// Top-level vault instructions, with their index in the message
+    pub instructions: Vec<(usize, VaultInstruction)>,
+    /// Event data logged by the vault, read with `event::decode`
+    pub events: Vec<Vec<u8>>,
+}
+
+/// A decoded update of the vault program.
+#[derive(Clone, Debug, PartialEq)]
+pub enum VaultUpdate {
+    /// An account owned by the program changed
+    Account {
+        /// The account address
+        address: Pubkey,
+        /// Slot of the update
+        slot: u64,
+        /// Write version of the update
+        write_version: u64,
+        /// Lamports held by the account
+        lamports: u64,
+        /// The decoded account
+        account: VaultAccount,
+    },
+    /// A transaction invoked the program
+    Transaction(VaultTransaction),
+}
+
+/// Decode `update` if it concerns the vault program `program_id`: an account it owns
+/// with a known discriminator, or a transaction invoking it at the top level or by
+/// CPI. Any other update returns `None`, so plugins can drop it unparsed. Instructions
+/// the program only received through CPI surface through their events.
+pub fn decode(program_id: &Pubkey, update: &Update) -> Option<VaultUpdate> {
+    match update {
+        Update::Account(update) if update.owner == *program_id => Some(VaultUpdate::Account {
+            address: update.pubkey,
+            slot: update.slot,
+            write_version: update.write_version,
+            lamports: update.lamports,
+            account: decode_account(update.data)?,
+        }),
+        Update::Account(_) => None,
+        Update::Transaction(update) => {
+            decode_transaction(program_id, update).map(VaultUpdate::Transaction)
+        }
+    }
+}
+
+/// Decode vault account `data` by its discriminator. Closed accounts, unknown
+/// discriminators and malformed data return `None`.
+pub fn decode_account(data: &[u8]) -> Option<VaultAccount> {
+    fn parse<T: BorshDeserialize + SplDiscriminate>(data: &[u8]) -> Option<Box<T>> {
+        if !data.starts_with(T::SPL_DISCRIMINATOR_SLICE) {
+            return None;
+        }
+        T::try_from_slice(data).ok().map(Box::new)
+    }
+    if data.starts_with(VaultRecord::SPL_DISCRIMINATOR_SLICE) {
+        return VaultRecord::from_account_data(data)
+            .ok()
+            .map(|record| VaultAccount::VaultRecord(Box::new(record)));
+    }
+    if data.starts_with(AuditTrail::SPL_DISCRIMINATOR_SLICE) {
+        return AuditTrail::from_account_data(data)
+            .ok()
+            .map(|trail| VaultAccount::AuditTrail(Box::new(trail)));
+    }
+    parse(data)
+        .map(VaultAccount::DartIdentity)
+        .or_else(|| parse(data).map(VaultAccount::SessionRecord))
+        .or_else(|| parse(data).map(VaultAccount::RecoveryRecord))
+        .or_else(|| parse(data).map(VaultAccount::InheritanceRecord))
+        .or_else(|| parse(data).map(VaultAccount::TransferHookConfig))
+        .or_else(|| parse(data).map(VaultAccount::ProgramConfig))
+        .or_else(|| parse(data).map(VaultAccount::FeeConfig))
+        .or_else(|| parse(data).map(VaultAccount::ProgramStats))
+        .or_else(|| parse(data).map(VaultAccount::ApprovalRoot))
+        .or_else(|| parse(data).map(VaultAccount::NoteRecord))
+        .or_else(|| parse(data).map(VaultAccount::NotifyRecord))
+        .or_else(|| parse(data).map(VaultAccount::Notification))
+        .or_else(|| parse(data).map(VaultAccount::CommitmentRecord))
+        .or_else(|| parse(data).map(VaultAccount::NotesLog))
+        .or_else(|| parse(data).map(VaultAccount::AttestationList))
+        .or_else(|| parse(data).map(VaultAccount::PledgeRecord))
+        .or_else(|| parse(data).map(VaultAccount::PositionRecord))
+        .or_else(|| parse(data).map(VaultAccount::DistributionCursor))
+        .or_else(|| parse(data).map(VaultAccount::SnapshotRecord))
+        .or_else(|| parse(data).map(VaultAccount::VotingProxy))
+        .or_else(|| parse(data).map(VaultAccount::TransferWindow))
+        .or_else(|| parse(data).map(VaultAccount::RateLimit))
+        .or_else(|| parse(data).map(VaultAccount::DartPolicy))
+        .or_else(|| parse(data).map(VaultAccount::VaultIndex))
+}
+
+/// Decode the top-level vault instructions and vault events of `update`, or `None`
+/// if it has neither.
+pub fn decode_transaction(
+    program_id: &Pubkey,
+    update: &TransactionUpdate,
+) -> Option<VaultTransaction> {
+    let instructions: Vec<_> = update
+        .instructions
+        .iter()
+        .enumerate()
+        .filter(|(_, instruction)| {
+            update
+                .account_keys
+                .get(usize::from(instruction.program_id_index))
+                == Some(program_id)
+        })
+        .filter_map(|(index, instruction)| {
+            VaultInstruction::unpack(&instruction.data)
+                .ok()
+                .map(|decoded| (index, decoded))
+        })
+        .collect();
+    let events: Vec<_> = program_data(program_id, update.log_messages)
+        .into_iter()
+        .filter_map(|data| STANDARD.decode(data).ok())
+        .collect();
+    if instructions.is_empty() && events.is_empty() {
+        return None;
+    }
+    Some(VaultTransaction {
+        signature: update.signature,
+        slot: update.slot,
+        succeeded: update.succeeded,
+        instructions,
+        events,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{
+            event::{self, event_data, VaultClosed, DATA_LOG_PREFIX},
+            state::SessionRecord,
+        },
+    };
+
+    fn account_update<'a>(owner: &Pubkey, data: &'a [u8]) -> Update<'a> {
+        Update::Account(AccountUpdate {
+            pubkey: Pubkey::new_unique(),
+            owner: *owner,
+            lamports: 1,
+            data,
+            slot: 7,
+            write_version: 3,
+        })
+    }
+
+    #[test]
+    fn accounts_decoded_by_discriminator() {
+        let program_id = crate::id();
+        let record = VaultRecord::new(Pubkey::new_unique(), Pubkey::new_unique());
+        let mut data = vec![0; VaultRecord::LEN];
+        record.pack_into(&mut data).unwrap();
+        let Some(VaultUpdate::Account { account, slot, .. }) =
+            decode(&program_id, &account_update(&program_id, &data))
+        else {
+            panic!("vault record not decoded");
+        };
+        assert_eq!(slot, 7);
+        let VaultAccount::VaultRecord(decoded) = account else {
+            panic!("decoded as another account");
+        };
+        assert_eq!(
+            (decoded.authority, decoded.dart),
+            (record.authority, record.dart)
+        );
+
+        let session = SessionRecord::new(
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            10,
+            0,
+            255,
+        );
+        let data = borsh::to_vec(&session).unwrap();
+        assert_eq!(
+            decode_account(&data),
+            Some(VaultAccount::SessionRecord(Box::new(session)))
+        );
+
+        // Other owners, closed accounts and unknown data are dropped.
+        assert_eq!(
+            decode(&program_id, &account_update(&Pubkey::new_unique(), &data)),
+            None
+        );
+        assert_eq!(decode_account(&[0; 64]), None);
+        assert_eq!(decode_account(&data[..8]), None);
+    }
+
+    #[test]
+    fn transactions_decoded_for_the_program() {
+        let program_id = crate::id();
+        let other = Pubkey::new_unique();
+        let account_keys = [Pubkey::new_unique(), other, program_id];
+        let instructions = [
+            CompiledInstruction::new_from_raw_parts(1, vec![1, 2], vec![0]),
+            CompiledInstruction::new_from_raw_parts(
+                2,
+                VaultInstruction::Initialize.pack(),
+                vec![0],
+            ),
+        ];
+        let closed = VaultClosed {
+            vault: Pubkey::new_unique(),
+            authority: Pubkey::new_unique(),
+            recipient: Pubkey::new_unique(),
+            lamports: 42,
+        };
+        let logged = format!("{DATA_LOG_PREFIX}{}", STANDARD.encode(event_data(&closed)));
+        let log_messages = [
+            format!("Program {program_id} invoke [1]"),
+            logged.clone(),
+            format!("Program {program_id} success"),
+            format!("Program {other} invoke [1]"),
+            logged,
+            format!("Program {other} success"),
+        ];
+        let update = TransactionUpdate {
+            signature: [1; 64],
+            slot: 9,
+            succeeded: true,
+            account_keys: &account_keys,
+            instructions: &instructions,
+            log_messages: &log_messages,
+        };
+        let Some(VaultUpdate::Transaction(transaction)) =
+            decode(&program_id, &Update::Transaction(update))
+        else {
+            panic!("vault transaction not decoded");
+        };
+        assert_eq!(
+            transaction.instructions,
+            [(1, VaultInstruction::Initialize)]
+        );
+        assert_eq!(transaction.events.len(), 1);
+        assert_eq!(
+            event::decode::<VaultClosed>(&transaction.events[0]),
+            Some(closed)
+        );
+
+        let update = TransactionUpdate {
+            instructions: &instructions[..1],
+            log_messages: &[],
+            ..update
+        };
+        assert_eq!(decode_transaction(&program_id, &update), None);
+    }
+}
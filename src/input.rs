@@ -0,0 +1,127 @@
+//! Program input deserialization without the accounts `Vec`, for the
+//! `no-alloc-entrypoint` feature.
+//!
+//! `solana_program::entrypoint::deserialize` collects the accounts into a `Vec` sized by
+//! the transaction; `deserialize_into` writes them into a caller's array instead, such as
+//! one on the entrypoint's stack. Each `AccountInfo` still wraps its lamports and data in
+//! `Rc<RefCell<..>>`, two allocations per distinct account, as every handler takes them.
+use {
+    solana_program::{
+        account_info::AccountInfo,
+        entrypoint::{BPF_ALIGN_OF_U128, MAX_PERMITTED_DATA_INCREASE, NON_DUP_MARKER},
+        pubkey::Pubkey,
+    },
+    std::{
+        cell::RefCell,
+        mem::{size_of, MaybeUninit},
+        rc::Rc,
+        slice::{from_raw_parts, from_raw_parts_mut},
+    },
+};
+
+/// Accounts the `no-alloc-entrypoint` entrypoint deserializes onto its stack, which keeps
+/// the array well inside the 4KiB SBF stack frame. Instructions passed more fall back to
+/// `solana_program::entrypoint::deserialize`.
+pub const MAX_STACK_ACCOUNTS: usize = 32;
+
+/// Deserialize the input the runtime serialized at `input`, writing its accounts to the
+/// front of `accounts`: the program id, the number of accounts written and the
+/// instruction data. Returns `None`, writing nothing, when `accounts` is too short.
+///
+/// The accounts written are not dropped with `accounts`; under the SBF bump allocator,
+/// which never frees, that costs nothing.
+///
+/// # Safety
+///
+/// `input` must point to a buffer in the runtime's serialization format, as passed to the
+/// program entrypoint.
+#[allow(clippy::arithmetic_side_effects, clippy::cast_ptr_alignment)]
+pub unsafe fn deserialize_into<'a>(
+    input: *mut u8,
+    accounts: &mut [MaybeUninit<AccountInfo<'a>>],
+) -> Option<(&'a Pubkey, usize, &'a [u8])> {
+    let mut offset = 0;
+
+    let num_accounts = *(input.add(offset) as *const u64) as usize;
+    offset += size_of::<u64>();
+    if num_accounts > accounts.len() {
+        return None;
+    }
+
+    for index in 0..num_accounts {
+        let dup_info = *input.add(offset);
+        offset += size_of::<u8>();
+        if dup_info != NON_DUP_MARKER {
+            offset += 7; // padding
+            let original = accounts[dup_info as usize].assume_init_ref().clone();
+            accounts[index].write(original);
+            continue;
+        }
+
+        let is_signer = *input.add(offset) != 0;
+        offset += size_of::<u8>();
+        let is_writable = *input.add(offset) != 0;
+        offset += size_of::<u8>();
+        let executable = *input.add(offset) != 0;
+        offset += size_of::<u8>();
+
+        // The runtime checks reallocations against the original data length kept in
+        // the padding here, as `deserialize` does.
+        let original_data_len_offset = offset;
+        offset += size_of::<u32>();
+
+        let key = &*(input.add(offset) as *const Pubkey);
+        offset += size_of::<Pubkey>();
+        let owner = &*(input.add(offset) as *const Pubkey);
+        offset += size_of::<Pubkey>();
+
+        let lamports = Rc::new(RefCell::new(&mut *(input.add(offset) as *mut u64)));
+        offset += size_of::<u64>();
+
+        let data_len = *(input.add(offset) as *const u64) as usize;
+        offset += size_of::<u64>();
+        *(input.add(original_data_len_offset) as *mut u32) = data_len as u32;
+
+        let data = Rc::new(RefCell::new(from_raw_parts_mut(
+            input.add(offset),
+            data_len,
+        )));
+        offset += data_len + MAX_PERMITTED_DATA_INCREASE;
+        offset += (offset as *const u8).align_offset(BPF_ALIGN_OF_U128); // padding
+
+        let rent_epoch = *(input.add(offset) as *const u64);
+        offset += size_of::<u64>();
+
+        accounts[index].write(AccountInfo {
+            key,
+            is_signer,
+            is_writable,
+            lamports,
+            data,
+            owner,
+            executable,
+            rent_epoch,
+        });
+    }
+
+    let instruction_data_len = *(input.add(offset) as *const u64) as usize;
+    offset += size_of::<u64>();
+    let instruction_data = from_raw_parts(input.add(offset), instruction_data_len);
+    offset += instruction_data_len;
+
+    let program_id = &*(input.add(offset) as *const Pubkey);
+
+    Some((program_id, num_accounts, instruction_data))
+}
+
+/// The first `len` accounts `deserialize_into` wrote to `accounts`.
+///
+/// # Safety
+///
+/// `deserialize_into` must have written at least `len` accounts to `accounts`.
+pub unsafe fn written<'a, 'b>(
+    accounts: &'b [MaybeUninit<AccountInfo<'a>>],
+    len: usize,
+) -> &'b [AccountInfo<'a>] {
+    from_raw_parts(accounts.as_ptr() as *const AccountInfo<'a>, len)
+}
@@ -1,10 +1,57 @@
+use crate::{
+    event, receipt,
+    state::{
+        find_asset_token_address, find_asset_vault_address, program_data_address, ApprovalRoot,
+        AttestationList, AuditTrail, BlackoutRange, CloseFeeTier, CommitmentRecord,
+        CorporateActionType, DartPolicy, DistributionCursor, FeeConfig, InheritanceRecord,
+        NoteRecord, NotesLog, Notification, NotifyRecord, PledgeRecord, PolicyDefaults,
+        PositionRecord, ProgramConfig, ProgramStats, RateLimit, RecoveryRecord, SessionRecord,
+        SnapshotRecord, TransferHookConfig, TransferWindow, VaultIndex, VotingProxy,
+        ENCRYPTED_NOTE_LEN, MAX_BLACKOUTS, MAX_CLOSE_FEE_TIERS, MAX_COMMITMENTS,
+        TRANSFER_WINDOW_DART, TRANSFER_WINDOW_VAULT,
+    },
+    transfer_hook,
+};
 use borsh::{BorshDeserialize, BorshSerialize};
 use solana_program::{
     instruction::{AccountMeta, Instruction},
+    msg,
+    program_error::ProgramError,
     pubkey::Pubkey,
+    system_program, sysvar,
 };
 
+/// Leading byte of versioned instruction data, `[FORMAT_PREFIX, format_version, ..]`.
+/// No instruction tag takes this value.
+pub const FORMAT_PREFIX: u8 = 0xff;
+
+/// Instruction format version written by `VaultInstruction::pack`.
+pub const FORMAT_VERSION: u8 = 1;
+
+/// Format version whose prefix is followed by the last slot the instruction may
+/// execute in, `[FORMAT_PREFIX, SLOT_BOUNDED_FORMAT_VERSION, valid_until_slot (u64), ..]`.
+pub const SLOT_BOUNDED_FORMAT_VERSION: u8 = 2;
+
 /// Instructions supported by the vault program.
+///
+/// Instruction data is either the borsh encoding of the instruction, as written by
+/// the builders below, or the same encoding behind `[FORMAT_PREFIX, FORMAT_VERSION]`.
+/// The unprefixed layout is frozen: later formats that add arguments take a new
+/// format version, so clients built against an earlier layout keep working.
+/// `SLOT_BOUNDED_FORMAT_VERSION` adds a `valid_until_slot` to any instruction (see
+/// `with_valid_until_slot`), after which it fails with `InstructionExpired`.
+///
+/// Any `[signer]` account may be a program-derived address signed for through
+/// `invoke_signed`, such as an SPL Governance treasury or a Squads vault holding the
+/// authority or DART role. `[writable]` accounts must stay writable through every
+/// caller, and no account standing in a role may be executable.
+///
+/// Besides the accounts listed, every instruction but `InitializeConfig`, `Decommission`
+/// and `MigrateToProgram` takes the program config, `[CONFIG_SEED]`, anywhere after its
+/// fixed accounts, whether or not the config exists yet, and fails with
+/// `NotEnoughAccountKeys` without it (see `requires_program_config`). This broke
+/// hand-built instructions from before `Decommission`, which must now pass it; the
+/// builders below append it.
 #[derive(Clone, Debug, BorshSerialize, BorshDeserialize, PartialEq)]
 pub enum VaultInstruction {
     /// Initialize a vault record (by DART on behalf of a given authority).
@@ -14,6 +61,18 @@ pub enum VaultInstruction {
     /// 0. `[writable]` The vault record account (must be uninitialized).
     /// 1. `[signer]` The securities intermediary (DART)
     /// 2. `[]` The record authority (trader)
+    /// 3. `[]` Optional DART identity; the record's DART is then the identity and
+    ///    account 1 must be one of its operational keys
+    /// 4. `[signer]` Optional rent payer, refunded when the record is closed
+    /// 5. `[]` Optional close recipient, paid when the record is closed whoever
+    ///    signs the close
+    /// 6. `[]` Optional policy of the record's DART, `DartPolicy::find_address(dart)`,
+    ///    whose defaults the record takes; an explicit close recipient prevails
+    /// 7. `[writable]` The vault index of account 2, `VaultIndex::find_address(authority)`,
+    ///    whether or not it exists yet; once it does, the record is listed there
+    ///
+    /// Pass the program id in place of an absent optional account that precedes a
+    /// present one or the vault index.
     Initialize,
 
     /// Transfer ownership of a vault record
@@ -24,122 +83,5174 @@ pub enum VaultInstruction {
     /// 1. `[signer]` The securities intermediary (DART)
     /// 2. `[signer]` The current record authority.
     /// 3. `[]` The new record authority
+    /// 4. `[]` Optional DART identity, when account 1 is one of its operational keys
+    /// 5. `[]` Optional session account, when account 2 is a session key with the
+    ///    `TRANSFER_AUTHORITY` scope
+    /// 6. `[]` The price feed, when the record has an oracle
+    /// 7. `[]` The transfer window of the record's DART,
+    ///    `[TRANSFER_WINDOW_SEED, TRANSFER_WINDOW_DART, dart]`,
+    ///    whether or not it exists yet, and the vault's, when the record is flagged
+    ///    (see `with_transfer_windows`)
+    /// 8. `[]` The vault's notify record, whether or not it exists yet; once it does,
+    ///    `[writable]` the notification account of its key, to notify the transfer
+    ///    (see `with_notify`)
+    /// 9. `[writable]` The vault indexes of the current and the new authority,
+    ///    `VaultIndex::find_address(authority)`, whether or not they exist yet; the
+    ///    vault moves from one to the other
+    ///
+    /// Accounts past 3 are matched by address. All but the DART's transfer window, the
+    /// vault's notify record and the vault indexes may be omitted when not needed.
     TransferAuthority,
 
-    /// Close a vault record account, draining lamports to the current authority.
+    /// Close a vault record account, draining lamports to the close recipient set at
+    /// initialization, else the rent payer recorded then, else the current authority.
+    ///
+    /// The record is marked closed, so an account re-funded within the same
+    /// transaction cannot be used again without a `Reopen`. Emits `VaultClosed` and a
+    /// `CloseReceipt`, which is also set as the instruction's return data.
     ///
     /// Accounts expected by this instruction:
     ///
     /// 0. `[writable]` The vault record account (must be previously initialized).
     /// 1. `[signer]` The securities intermediary (DART)
-    /// 2. `[signer, writable]` The record authority (receiver of account lamports).
+    /// 2. `[signer, writable]` The record authority
+    /// 3. `[]` Optional DART identity, when account 1 is one of its operational keys
+    /// 4. `[writable]` The close recipient or rent payer, when one was recorded
+    /// 5. `[writable]` The token account holding the custody receipt, the receipt
+    ///    mint, and the SPL Token program, to burn the receipt
+    /// 6. `[]` The vault's position account, whether or not it exists yet; once it
+    ///    does, the vault's associated token account for the position's asset (see
+    ///    `with_asset_token_account`); and any other token accounts the vault owns.
+    ///    All must hold nothing, else the close fails with `VaultNotEmpty`
+    /// 7. `[writable]` The fee config, `[FEE_CONFIG_SEED]`, whether or not it exists
+    ///    yet; once it does, its close fee comes out of the lamports paid out
+    /// 8. `[]` The vault's notify record, whether or not it exists yet; once it does,
+    ///    `[writable]` the notification account of its key, to notify the close (see
+    ///    `with_notify`)
+    /// 9. `[writable]` The vault index of the authority the record names, whether or
+    ///    not it exists yet; in receipt-holder mode, the authority last written to the
+    ///    record rather than the holder (see `with_vault_index`)
+    ///
+    /// Accounts past 2 are matched by address. All but the fee config, position,
+    /// notify record and vault index may be omitted; without them the close fails with
+    /// `NotEnoughAccountKeys`.
     CloseAccount,
-}
 
-/// Create a `VaultInstruction::Initialize` instruction
-pub fn initialize(
-    program_id: Pubkey,
-    pda: &Pubkey,
-    dart: &Pubkey,
-    authority: &Pubkey,
-) -> Instruction {
-    Instruction::new_with_borsh(
-        program_id,
-        &VaultInstruction::Initialize,
-        vec![
-            AccountMeta::new(*pda, false),
-            AccountMeta::new_readonly(*dart, true),
-            AccountMeta::new_readonly(*authority, false),
-        ],
-    )
-}
+    /// Reopen a closed vault record whose account has been re-funded to rent
+    /// exemption, keeping its address and authority.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[writable]` The vault record account (must be previously closed).
+    /// 1. `[signer]` The securities intermediary (DART)
+    /// 2. `[]` Optional DART identity, when account 1 is one of its operational keys
+    Reopen,
 
-/// Create a `VaultInstruction::TransferAuthority` instruction
-pub fn transfer_authority(
-    program_id: Pubkey,
-    pda: &Pubkey,
-    dart: &Pubkey,
-    authority: &Pubkey,
-    new_authority: &Pubkey,
-) -> Instruction {
-    Instruction::new_with_borsh(
-        program_id,
-        &VaultInstruction::TransferAuthority,
-        vec![
-            AccountMeta::new(*pda, false),
-            AccountMeta::new_readonly(*dart, true),
-            AccountMeta::new_readonly(*authority, true),
-            AccountMeta::new_readonly(*new_authority, false),
-        ],
-    )
-}
+    /// Propose a successor securities intermediary. The current DART stays in
+    /// control until the successor signs `AcceptDart`; proposing again replaces
+    /// the pending DART.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[writable]` The vault record account (must be previously initialized).
+    /// 1. `[signer]` The current securities intermediary (DART)
+    /// 2. `[]` The proposed DART
+    /// 3. `[]` Optional DART identity, when account 1 is one of its operational keys
+    ProposeDart,
 
-/// Create a `VaultInstruction::CloseAccount` instruction
-pub fn close_account(
-    program_id: Pubkey,
-    pda: &Pubkey,
-    dart: &Pubkey,
-    authority: &Pubkey,
-) -> Instruction {
-    Instruction::new_with_borsh(
-        program_id,
-        &VaultInstruction::CloseAccount,
-        vec![
-            AccountMeta::new(*pda, false),
-            AccountMeta::new_readonly(*dart, true),
-            AccountMeta::new(*authority, true),
-        ],
-    )
-}
+    /// Accept a pending DART succession, completing the handshake.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[writable]` The vault record account (must be previously initialized).
+    /// 1. `[signer]` The proposed DART
+    /// 2. `[]` Optional DART identity, when the proposed DART is an identity and
+    ///    account 1 is one of its operational keys
+    AcceptDart,
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use solana_program::program_error::ProgramError;
+    /// Initialize a DART identity owned by the given master key.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[writable]` The DART identity account (must be uninitialized).
+    /// 1. `[signer]` The master key
+    InitializeDartIdentity,
 
-    /// very small data for easy testing
-    const DATA_SIZE: usize = 8;
+    /// Set or clear (with the default pubkey) an operational key slot of a DART
+    /// identity. Only the master key may rotate operational keys.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[writable]` The DART identity account (must be previously initialized).
+    /// 1. `[signer]` The master key
+    SetOperationalKey {
+        /// Slot to write, below `MAX_OPERATIONAL_KEYS`
+        slot: u8,
+        /// The operational key
+        key: Pubkey,
+    },
 
-    /// Bytes for tests
-    const TEST_BYTES: [u8; DATA_SIZE] = [42; DATA_SIZE];
+    /// Authorize a session key to sign for the authority within `scope` until
+    /// `expires_at_slot`, creating the session account if needed. Re-issuing a
+    /// session replaces it, so an expired or zero-scope session revokes the key.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[]` The vault record account (must be previously initialized).
+    /// 1. `[signer]` The record authority
+    /// 2. `[writable]` The session account, `SessionRecord::find_address(vault, key)`
+    /// 3. `[signer, writable]` The rent payer
+    /// 4. `[]` The system program
+    CreateSession {
+        /// The session key
+        key: Pubkey,
+        /// Last slot at which the session key is accepted
+        expires_at_slot: u64,
+        /// `session_scope` bits
+        scope: u8,
+    },
 
-    #[test]
-    fn serialize_initialize() {
-        let instruction = VaultInstruction::Initialize;
-        let expected = vec![0];
-        assert_eq!(instruction.try_to_vec().unwrap(), expected);
-        assert_eq!(
-            VaultInstruction::try_from_slice(&expected).unwrap(),
-            instruction
-        );
-    }
+    /// Add a guardian and set the recovery threshold, creating the recovery
+    /// account if needed. Clears any pending recovery.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[]` The vault record account (must be previously initialized).
+    /// 1. `[signer]` The record authority
+    /// 2. `[writable]` The recovery account, `RecoveryRecord::find_address(vault)`
+    /// 3. `[]` The guardian
+    /// 4. `[signer, writable]` The rent payer
+    /// 5. `[]` The system program
+    AddGuardian {
+        /// Guardian approvals required to complete a recovery
+        threshold: u8,
+    },
 
-    #[test]
-    fn serialize_transfer_authority() {
-        let instruction = VaultInstruction::TransferAuthority;
-        let expected = vec![1];
-        assert_eq!(instruction.try_to_vec().unwrap(), expected);
-        assert_eq!(
-            VaultInstruction::try_from_slice(&expected).unwrap(),
-            instruction
-        );
-    }
+    /// Remove a guardian and set the recovery threshold. Clears any pending recovery.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[]` The vault record account (must be previously initialized).
+    /// 1. `[signer]` The record authority
+    /// 2. `[writable]` The recovery account
+    /// 3. `[]` The guardian
+    RemoveGuardian {
+        /// Guardian approvals required to complete a recovery
+        threshold: u8,
+    },
 
-    #[test]
-    fn serialize_close_account() {
-        let instruction = VaultInstruction::CloseAccount;
-        let expected = vec![2];
-        assert_eq!(instruction.try_to_vec().unwrap(), expected);
-        assert_eq!(
-            VaultInstruction::try_from_slice(&expected).unwrap(),
-            instruction
-        );
+    /// Start recovering the vault to a new authority (by DART), opening the
+    /// challenge period.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[]` The vault record account (must be previously initialized).
+    /// 1. `[signer]` The securities intermediary (DART)
+    /// 2. `[writable]` The recovery account
+    /// 3. `[]` The new record authority
+    InitiateRecovery,
+
+    /// Approve the pending recovery.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[]` The vault record account (must be previously initialized).
+    /// 1. `[writable]` The recovery account
+    /// 2. `[signer]` The guardian
+    ApproveRecovery,
+
+    /// Cancel the pending recovery.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[]` The vault record account (must be previously initialized).
+    /// 1. `[signer]` The record authority
+    /// 2. `[writable]` The recovery account
+    CancelRecovery,
+
+    /// Rotate the authority to the pending recovery authority (by DART), once
+    /// the threshold of guardians approved and the challenge period elapsed.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[writable]` The vault record account (must be previously initialized).
+    /// 1. `[signer]` The securities intermediary (DART)
+    /// 2. `[writable]` The recovery account
+    /// 3. `[]` The transfer windows, as for `TransferAuthority`
+    /// 4. `[]` The notify record, and the notification account, as for
+    ///    `TransferAuthority`
+    /// 5. `[writable]` The vault indexes of the current and the recovery authority,
+    ///    as for `TransferAuthority`
+    CompleteRecovery,
+
+    /// Designate a beneficiary who may claim the authority role after
+    /// `inactivity_slots` without activity, creating the inheritance account if
+    /// needed. Counts as activity; the default pubkey revokes the designation.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[]` The vault record account (must be previously initialized).
+    /// 1. `[signer]` The record authority
+    /// 2. `[writable]` The inheritance account, `InheritanceRecord::find_address(vault)`
+    /// 3. `[]` The beneficiary
+    /// 4. `[signer, writable]` The rent payer
+    /// 5. `[]` The system program
+    SetBeneficiary {
+        /// Slots without activity after which the beneficiary may claim
+        inactivity_slots: u64,
+    },
+
+    /// Record authority activity, restarting the inactivity period.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[]` The vault record account (must be previously initialized).
+    /// 1. `[signer]` The record authority
+    /// 2. `[writable]` The inheritance account
+    CheckIn,
+
+    /// Claim the authority role for the beneficiary (co-signed by DART) once the
+    /// inactivity period has passed.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[writable]` The vault record account (must be previously initialized).
+    /// 1. `[signer]` The securities intermediary (DART)
+    /// 2. `[writable]` The inheritance account
+    /// 3. `[signer]` The beneficiary
+    /// 4. `[]` The transfer windows, as for `TransferAuthority`
+    /// 5. `[]` The notify record, and the notification account, as for
+    ///    `TransferAuthority`
+    /// 6. `[writable]` The vault indexes of the current authority and the beneficiary,
+    ///    as for `TransferAuthority`
+    ClaimInheritance,
+
+    /// Set the record's category tag (by DART).
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[writable]` The vault record account (must be previously initialized).
+    /// 1. `[signer]` The securities intermediary (DART)
+    SetTag {
+        /// The category tag; zero clears it
+        tag: u16,
+    },
+
+    /// Initialize a vault record in an account of `data_len` bytes, leaving the space
+    /// past the fixed header for metadata. Accounts are as for `Initialize`, which
+    /// is this instruction with `data_len` of `VaultRecord::LEN`.
+    InitializeSized {
+        /// Account data length, at least `VaultRecord::LEN`
+        data_len: u32,
+    },
+
+    /// Grow the vault account toward `target_len` by at most
+    /// `MAX_PERMITTED_DATA_INCREASE` bytes, funding the extra rent from the payer.
+    /// Repeat until the record's `data_len` reaches the target; once it has, the
+    /// instruction is a no-op.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[writable]` The vault record account (must be previously initialized).
+    /// 1. `[signer]` The securities intermediary (DART)
+    /// 2. `[signer, writable]` The rent payer
+    /// 3. `[]` The system program
+    GrowAccount {
+        /// Final account data length
+        target_len: u32,
+    },
+
+    /// Close a vault record account as `CloseAccount` does, optionally sending the
+    /// lamports to the authority even when a rent payer was recorded. A close
+    /// recipient is paid regardless. Accounts are as for `CloseAccount`; the rent
+    /// payer is not needed when `refund_authority` is set.
+    CloseAccountWithRefund {
+        /// Drain lamports to the authority rather than the rent payer
+        refund_authority: bool,
+    },
+
+    /// Anchor a Wormhole VAA to the record (by DART), e.g. a custody event posted
+    /// by the underlying security registry on another chain.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[writable]` The vault record account (must be previously initialized).
+    /// 1. `[signer]` The securities intermediary (DART)
+    /// 2. `[]` Optional DART identity, when account 1 is one of its operational keys
+    RecordAttestation {
+        /// The VAA digest, as computed by `vaa_digest`; zero clears it
+        vaa_hash: [u8; 32],
+    },
+
+    /// Exchange the authorities of two vault records under the same DART, so
+    /// neither transfer can happen without the other.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[writable]` The first vault record account (must be previously initialized).
+    /// 1. `[writable]` The second vault record account (must be previously initialized).
+    /// 2. `[signer]` The securities intermediary (DART) of both records
+    /// 3. `[signer]` The first record's authority
+    /// 4. `[signer]` The second record's authority
+    /// 5. `[]` Optional DART identity, when account 2 is one of its operational keys
+    /// 6. `[]` Optional session accounts, when account 3 or 4 is a session key with
+    ///    the `TRANSFER_AUTHORITY` scope
+    /// 7. `[]` The price feeds of records that have an oracle
+    /// 8. `[]` The transfer window of the DART, whether or not it exists yet, and those
+    ///    of the flagged vaults (see `with_transfer_windows`)
+    /// 9. `[]` The notify records of both vaults, and the notification accounts of
+    ///    their keys, as for `TransferAuthority`
+    /// 10. `[writable]` The vault indexes of both authorities, as for
+    ///     `TransferAuthority`
+    ///
+    /// Accounts past 4 are matched by address. All but the DART's transfer window, the
+    /// notify records and the vault indexes may be omitted when not needed.
+    SwapAuthorities,
+
+    /// Propose a transfer of the vault record to a new authority, who must accept it
+    /// within `timeout_slots`. The vault is `PendingTransfer` until then.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[writable]` The vault record account (must be previously initialized).
+    /// 1. `[signer]` The securities intermediary (DART)
+    /// 2. `[signer]` The current record authority.
+    /// 3. `[]` The proposed record authority
+    /// 4. `[]` Optional DART identity, when account 1 is one of its operational keys
+    /// 5. `[]` Optional session account, when account 2 is a session key with the
+    ///    `TRANSFER_AUTHORITY` scope
+    /// 6. `[]` The transfer windows, as for `TransferAuthority`
+    ///
+    /// Accounts past 3 are matched by address. All but the DART's transfer window may
+    /// be omitted when not needed.
+    ProposeTransfer {
+        /// Slots after this one the proposal may be accepted in
+        timeout_slots: u64,
+    },
+
+    /// Accept a pending transfer before it expires, becoming the record authority.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[writable]` The vault record account (must be pending transfer).
+    /// 1. `[signer]` The proposed record authority
+    /// 2. `[]` The price feed, when the record has an oracle
+    /// 3. `[]` The transfer windows, as for `TransferAuthority`
+    /// 4. `[]` The notify record, and the notification account, as for
+    ///    `TransferAuthority`
+    /// 5. `[writable]` The vault indexes of the proposing and the new authority, as for
+    ///    `TransferAuthority`
+    AcceptTransfer,
+
+    /// Drop a pending transfer that expired unaccepted, returning the vault to
+    /// `Active` under its original authority. Anyone may call this.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[writable]` The vault record account (must be pending transfer).
+    ExpireTransfer,
+
+    /// Gate authority changes on a price feed (by DART): `TransferAuthority`,
+    /// `SwapAuthorities` and `AcceptTransfer` then need the feed account, which must
+    /// report an open market, a price of at least `min_price`, and have published
+    /// within `max_staleness_slots`. Pyth price accounts are supported.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[writable]` The vault record account (must be previously initialized).
+    /// 1. `[signer]` The securities intermediary (DART)
+    /// 2. `[]` The price feed, or the program id to remove the gate
+    /// 3. `[]` Optional DART identity, when account 1 is one of its operational keys
+    SetOracle {
+        /// Slots the feed may go without publishing
+        max_staleness_slots: u64,
+        /// Lowest price, in the feed's exponent
+        min_price: i64,
+    },
+
+    /// Mint a single custody receipt token to the record authority (by DART), so
+    /// wallets show the vault. Usually sent with `Initialize`. The receipt is burned
+    /// on close when the authority passes the token account holding it; a receipt
+    /// left with a former authority is not moved by authority changes.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[writable]` The vault record account (must be previously initialized).
+    /// 1. `[signer]` The securities intermediary (DART)
+    /// 2. `[signer, writable]` The rent payer
+    /// 3. `[writable]` The receipt mint, `[RECEIPT_SEED, vault]`
+    /// 4. `[writable]` The receipt token account, `[RECEIPT_ACCOUNT_SEED, vault]`
+    /// 5. `[]` The SPL Token program
+    /// 6. `[]` The system program
+    /// 7. `[]` Optional DART identity, when account 1 is one of its operational keys
+    MintReceipt,
+
+    /// Switch receipt-holder mode (by DART and authority). While enabled, the
+    /// authority is whoever holds the vault's receipt token: authority-signed
+    /// instructions need the holder's token account among their accounts, and
+    /// ownership moves by token transfer rather than `TransferAuthority`. Disabling
+    /// pins the authority to the current holder.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[writable]` The vault record account (must be previously initialized).
+    /// 1. `[signer]` The securities intermediary (DART)
+    /// 2. `[signer]` The record authority, or the receipt holder when enabled
+    /// 3. `[]` The token account holding the receipt
+    /// 4. `[]` Optional DART identity, when account 1 is one of its operational keys
+    /// 5. `[writable]` When the receipt changed hands since the authority was last
+    ///    written, the vault indexes of that authority and of the holder, as for
+    ///    `TransferAuthority` (see `with_vault_index`)
+    SetReceiptAuthority {
+        /// Whether the receipt holder is the authority
+        enabled: bool,
+    },
+
+    /// Make this program the compliance engine of a Token-2022 mint whose transfer
+    /// hook points here (by DART and mint authority): write the extra account metas
+    /// the token program resolves for `Execute` and link the mint to the vault.
+    /// Transfers then fail while the vault is frozen, seized or closed, or to owners
+    /// off the allowlist once it has entries.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[]` The vault record account (must be previously initialized).
+    /// 1. `[signer]` The securities intermediary (DART)
+    /// 2. `[signer]` The mint authority
+    /// 3. `[signer, writable]` The rent payer
+    /// 4. `[]` The Token-2022 mint
+    /// 5. `[writable]` The extra account metas, `[EXTRA_ACCOUNT_METAS_SEED, mint]`
+    /// 6. `[writable]` The transfer hook config, `[TRANSFER_HOOK_SEED, mint]`
+    /// 7. `[]` The system program
+    /// 8. `[]` Optional DART identity, when account 1 is one of its operational keys
+    InitializeTransferHook,
+
+    /// Set or clear (with the default pubkey) an allowlist slot of a transfer hook
+    /// config (DART only).
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[]` The vault record account linked to the mint.
+    /// 1. `[signer]` The securities intermediary (DART)
+    /// 2. `[writable]` The transfer hook config
+    /// 3. `[]` Optional DART identity, when account 1 is one of its operational keys
+    SetAllowlistEntry {
+        /// Slot to write, below `MAX_ALLOWLIST`
+        slot: u8,
+        /// Owner allowed to receive the token
+        holder: Pubkey,
+    },
+
+    /// Move a vault record to a new account (by DART and authority), e.g. after a
+    /// change of address scheme. The record and any metadata are copied, the old
+    /// account's lamports are paid out as `CloseAccount` would pay them, and the old
+    /// account is wiped. Sessions, guardians, the beneficiary, a transfer hook config,
+    /// the audit trail, the rate limit and the vault's transfer window are keyed by the
+    /// old address and must be set up again; the new record starts without them.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[writable]` The vault record account (must be previously initialized).
+    /// 1. `[writable]` The new vault account: owned by the program, rent exempt,
+    ///    uninitialized and the same length as account 0
+    /// 2. `[signer]` The securities intermediary (DART)
+    /// 3. `[signer, writable]` The record authority
+    /// 4. `[writable]` Optional close recipient or rent payer, when one is recorded
+    /// 5. `[]` Optional DART identity, when account 2 is one of its operational keys
+    /// 6. `[]` The position and token accounts of the vault, as for `CloseAccount`;
+    ///    they stay keyed by the old address, so the vault must be empty
+    /// 7. `[writable]` The vault index of the authority, as for `CloseAccount`, where
+    ///    the new account replaces the old one; in receipt-holder mode, also the
+    ///    holder's, which lists the new account
+    Rekey,
+
+    /// Transfer ownership of a vault record as `TransferAuthority` does, failing
+    /// with `StaleState` unless the record authority is still `expected_authority`.
+    /// Accounts are as for `TransferAuthority`.
+    TransferAuthorityChecked {
+        /// Record authority the transaction was built against
+        expected_authority: Pubkey,
+    },
+
+    /// Close a vault record account as `CloseAccountWithRefund` does, failing with
+    /// `StaleState` unless the record authority is still `expected_authority`, e.g.
+    /// after the receipt token moved in receipt-holder mode. Accounts are as for
+    /// `CloseAccount`.
+    CloseAccountChecked {
+        /// Record authority the transaction was built against
+        expected_authority: Pubkey,
+        /// Drain lamports to the authority rather than the rent payer
+        refund_authority: bool,
+    },
+
+    /// Set how many operational keys of a DART identity must sign an approval for
+    /// the identity to stand in the DART role unsigned (see `quorum`); zero disables
+    /// quorum approval. Only the master key may set the quorum.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[writable]` The DART identity account (must be previously initialized).
+    /// 1. `[signer]` The master key
+    SetDartQuorum {
+        /// Signatures required, at most `MAX_OPERATIONAL_KEYS`
+        quorum: u8,
+    },
+
+    /// Create the program config, administered by the signer, which must be the
+    /// program's upgrade authority as recorded in its `ProgramData` account. Tying the
+    /// admin to the upgrade authority keeps anyone else from claiming the config
+    /// between deployment and bootstrap.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[writable]` The program config, `[CONFIG_SEED]`
+    /// 1. `[signer]` The program upgrade authority, made admin
+    /// 2. `[signer, writable]` The rent payer
+    /// 3. `[]` The program's `ProgramData` account
+    /// 4. `[]` The system program
+    InitializeConfig,
+
+    /// Decommission the program in favor of `successor` (admin only), for cutovers to
+    /// a new program id. Every other instruction but `MigrateToProgram` then fails
+    /// with `ProgramMigrated`, logging the successor. Decommissioning again replaces it.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[writable]` The program config
+    /// 1. `[signer]` The config admin
+    Decommission {
+        /// Program id vaults move to
+        successor: Pubkey,
+    },
+
+    /// Move a vault record to the program's successor (by DART and authority), during
+    /// a staged migration after `Decommission`. The record is initialized in the
+    /// successor through its `Initialize`, which must share this program's interface;
+    /// the old account's lamports are then paid out as `CloseAccount` would pay them,
+    /// and the old account is wiped, in the same instruction. Only the DART and
+    /// authority carry over, and a custody receipt must be burned first. The DART is
+    /// passed to the successor as a signer only when it signed this instruction.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[writable]` The vault record account (must be previously initialized).
+    /// 1. `[writable]` The new vault account, as the successor's `Initialize` expects it
+    /// 2. `[signer]` The securities intermediary (DART)
+    /// 3. `[signer, writable]` The record authority
+    /// 4. `[]` The program config, naming the successor
+    /// 5. `[]` The successor program
+    /// 6. `[writable]` Optional close recipient or rent payer, when one is recorded
+    /// 7. `[]` Optional DART identity, when account 2 is one of its operational keys;
+    ///    the successor checks it in turn
+    /// 8. `[]` The position and token accounts of the vault, as for `CloseAccount`,
+    ///    which must hold nothing
+    /// 9. `[writable]` The vault index of the authority, which unlists the vault, and
+    ///    the successor's vault index of the authority, passed on for the successor
+    ///    to list the new vault
+    MigrateToProgram,
+
+    /// Set the Merkle root of transfers a DART approves in bulk (see `approval`),
+    /// creating the DART's approval root account if needed. Setting it again replaces
+    /// the root, so a zero root revokes every pending approval.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[writable]` The approval root account, `ApprovalRoot::find_address(dart)`
+    /// 1. `[signer]` The securities intermediary (DART)
+    /// 2. `[signer, writable]` The rent payer
+    /// 3. `[]` The system program
+    SetApprovalRoot {
+        /// Merkle root of the approved `approval::transfer_leaf`s
+        root: [u8; 32],
+        /// Last slot the approvals may be redeemed in
+        expires_at_slot: u64,
+    },
+
+    /// Transfer ownership of a vault record as `TransferAuthority` does, with the
+    /// DART's approval proven against its approval root rather than its signature.
+    /// The leaf binds the record's `approval_nonce`, which the transfer bumps, so an
+    /// approval is redeemed once even should the vault return to the same authority.
+    /// Fails with `InvalidAccountData` on records older than version 6.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[writable]` The vault record account (must be previously initialized).
+    /// 1. `[]` The approval root account of the record's DART
+    /// 2. `[signer]` The current record authority.
+    /// 3. `[]` The new record authority
+    /// 4. `[]` Optional session account, when account 2 is a session key with the
+    ///    `TRANSFER_AUTHORITY` scope
+    /// 5. `[]` The price feed, when the record has an oracle
+    /// 6. `[]` The transfer windows, as for `TransferAuthority`
+    /// 7. `[]` The notify record, and the notification account, as for
+    ///    `TransferAuthority`
+    /// 8. `[writable]` The vault indexes, as for `TransferAuthority`
+    ///
+    /// Accounts past 3 are matched by address. All but the DART's transfer window, the
+    /// vault's notify record and the vault indexes may be omitted when not needed.
+    TransferWithProof {
+        /// Merkle proof of the transfer's leaf, at most `approval::MAX_PROOF_LEN` long
+        proof: Vec<[u8; 32]>,
+    },
+
+    /// Set the X25519 key the vault's note is sealed to (authority only), creating
+    /// the note account if needed. A new key clears the note, which the DART must
+    /// seal again.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[]` The vault record account (must be previously initialized).
+    /// 1. `[signer]` The record authority
+    /// 2. `[writable]` The note account, `NoteRecord::find_address(vault)`
+    /// 3. `[signer, writable]` The rent payer
+    /// 4. `[]` The system program
+    SetNoteKey {
+        /// X25519 public key of the authority
+        note_key: [u8; 32],
+    },
+
+    /// Replace the vault's note with one sealed to the note key (DART only). Fails
+    /// with `NoteKeyNotSet` until the current authority sets a key.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[]` The vault record account (must be previously initialized).
+    /// 1. `[signer]` The securities intermediary (DART)
+    /// 2. `[writable]` The note account
+    /// 3. `[]` Optional DART identity, when account 1 is one of its operational keys
+    SetNote {
+        /// The sealed note, zero padded
+        encrypted_note: [u8; ENCRYPTED_NOTE_LEN],
+    },
+
+    /// Replace the commitments to the vault's confidential metadata (DART only),
+    /// creating the commitment account if needed.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[]` The vault record account (must be previously initialized).
+    /// 1. `[signer]` The securities intermediary (DART)
+    /// 2. `[writable]` The commitment account, `CommitmentRecord::find_address(vault)`
+    /// 3. `[signer, writable]` The rent payer
+    /// 4. `[]` The system program
+    /// 5. `[]` Optional DART identity, when account 1 is one of its operational keys
+    UpdateCommitments {
+        /// Commitment slots; zero for unused slots
+        commitments: [[u8; 32]; MAX_COMMITMENTS],
+    },
+
+    /// Annotate the vault with a corporate action affecting the position (DART only),
+    /// appending to its notes log, creating the log if needed, and evicting the oldest
+    /// note once `MAX_LOG_NOTES` are kept. Emits `NoteAdded`.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[]` The vault record account (must be previously initialized).
+    /// 1. `[signer]` The securities intermediary (DART)
+    /// 2. `[writable]` The notes log account, `NotesLog::find_address(vault)`
+    /// 3. `[signer, writable]` The rent payer
+    /// 4. `[]` The system program
+    /// 5. `[]` Optional DART identity, when account 1 is one of its operational keys
+    AddNote {
+        /// Hash of the annotation document
+        hash: [u8; 32],
+        /// Kind of corporate action, assigned by the DART
+        category: u16,
+    },
+
+    /// Attach an attestation to the vault (DART only), renewing the one of the same
+    /// kind and hash or taking an unused or expired entry, and creating the attestation
+    /// list if needed. Fails with `AttestationListFull` when every entry is live.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[]` The vault record account (must be previously initialized).
+    /// 1. `[signer]` The securities intermediary (DART)
+    /// 2. `[writable]` The attestation list, `AttestationList::find_address(vault)`
+    /// 3. `[signer, writable]` The rent payer
+    /// 4. `[]` The system program
+    /// 5. `[]` Optional DART identity, when account 1 is one of its operational keys
+    AddAttestation {
+        /// Kind of attestation, assigned by the DART
+        kind: u16,
+        /// Hash of the attestation document; must not be zero
+        hash: [u8; 32],
+        /// Last slot the attestation holds; must not have passed
+        expires_at_slot: u64,
+    },
+
+    /// Withdraw an attestation of the vault (DART only).
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[]` The vault record account (must be previously initialized).
+    /// 1. `[signer]` The securities intermediary (DART)
+    /// 2. `[writable]` The attestation list, `AttestationList::find_address(vault)`
+    /// 3. `[]` Optional DART identity, when account 1 is one of its operational keys
+    RemoveAttestation {
+        /// Kind of the attestation
+        kind: u16,
+        /// Hash of the attestation document
+        hash: [u8; 32],
+    },
+
+    /// Pledge the vault as collateral to a lender, creating the pledge account if needed.
+    /// Until released, transfers, swaps, recovery, inheritance claims and closing also
+    /// require the lender's signature (see `with_pledge`), and rekeying, migrating and
+    /// minting a receipt are refused with `VaultPledged`. Vaults with a receipt cannot
+    /// be pledged.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[writable]` The vault record account (must be previously initialized).
+    /// 1. `[signer]` The record authority
+    /// 2. `[writable]` The pledge account, `PledgeRecord::find_address(vault)`
+    /// 3. `[signer, writable]` The rent payer
+    /// 4. `[]` The system program
+    PledgeAsCollateral {
+        /// The lender; neither the authority nor the DART
+        lender: Pubkey,
+    },
+
+    /// Release the vault from its pledge, signed by the lender. Fails with `NotPledged`
+    /// when the vault is not pledged.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[writable]` The vault record account (must be previously initialized).
+    /// 1. `[signer]` The lender
+    /// 2. `[writable]` The pledge account, `PledgeRecord::find_address(vault)`
+    ReleaseCollateral,
+
+    /// Book the security the vault holds and its balance (DART only), creating the
+    /// position account if needed.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[]` The vault record account (must be previously initialized).
+    /// 1. `[signer]` The securities intermediary (DART)
+    /// 2. `[writable]` The position account, `PositionRecord::find_address(vault)`
+    /// 3. `[signer, writable]` The rent payer
+    /// 4. `[]` The system program
+    /// 5. `[]` Optional DART identity, when account 1 is one of its operational keys
+    SetPosition {
+        /// Identifier of the security, assigned by the DART
+        asset_id: [u8; 32],
+        /// Units of the security held
+        balance: u64,
+    },
+
+    /// Apply a corporate action to the positions of a batch of vaults under the DART
+    /// (DART only), emitting `CorporateActionApplied` for each vault.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[signer]` The securities intermediary (DART)
+    /// 1. `[]` A vault record account
+    /// 2. `[writable]` Its position account, `PositionRecord::find_address(vault)`
+    /// 3. `..` Further vault record and position account pairs, alike
+    /// 4. `[]` Optional DART identity, after the batch, when account 0 is one of its
+    ///    operational keys
+    /// 5. `[writable]` The rate limit account of each rate-limited vault of the batch,
+    ///    after the batch (see `with_rate_limit`)
+    ApplyCorporateAction {
+        /// The corporate action
+        action_type: CorporateActionType,
+        /// Ratio of a split or reverse split; 1 for a symbol change
+        ratio: u64,
+    },
+
+    /// Pay a batch of vaults their share of a dividend or coupon (DART only):
+    /// `amount_per_unit` tokens per unit of position balance, transferred from the
+    /// DART's token account to a token account of each vault's authority. The first
+    /// batch creates the distribution cursor, paid for by the DART; later batches resume
+    /// after the last vault it paid, with the same terms. Vaults are paid in ascending
+    /// address order, so a vault at or before the cursor fails with
+    /// `DistributionOutOfOrder`.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[signer, writable]` The securities intermediary (DART), owner of account 2
+    /// 1. `[writable]` The cursor, `DistributionCursor::find_address(dart, id)`
+    /// 2. `[writable]` The token account paying the distribution
+    /// 3. `[]` The SPL token program
+    /// 4. `[]` The system program
+    /// 5. `[]` A vault record account
+    /// 6. `[]` Its position account, `PositionRecord::find_address(vault)`
+    /// 7. `[writable]` A token account of its authority, of the mint of account 2
+    /// 8. `..` Further vault, position and token account triples, alike
+    /// 9. `[]` Optional DART identity, last, when account 0 is one of its operational keys
+    Distribute {
+        /// Identifier of the distribution, assigned by the DART
+        id: u64,
+        /// Asset id of the positions entitled to the distribution
+        asset_id: [u8; 32],
+        /// Tokens paid per unit of position balance
+        amount_per_unit: u64,
+    },
+
+    /// Copy the vault's authority and position into an immutable snapshot for a record
+    /// date (DART only), once the record date slot is reached, so entitlements are
+    /// computed against fixed state. Each vault has one snapshot per record date.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[]` The vault record account (must be previously initialized).
+    /// 1. `[signer]` The securities intermediary (DART)
+    /// 2. `[]` The position account, `PositionRecord::find_address(vault)`
+    /// 3. `[writable]` The snapshot, `SnapshotRecord::find_address(vault, record_date_slot)`
+    /// 4. `[signer, writable]` The rent payer
+    /// 5. `[]` The system program
+    /// 6. `[]` Optional DART identity, when account 1 is one of its operational keys
+    Snapshot {
+        /// The record date
+        record_date_slot: u64,
+    },
+
+    /// Delegate the votes of the securities custodied in the vault to a proxy, or revoke
+    /// the delegation with the default pubkey, creating the voting proxy account if
+    /// needed. Emits `VotingDelegated`. The delegation lapses if the authority changes.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[]` The vault record account (must be previously initialized).
+    /// 1. `[signer]` The record authority
+    /// 2. `[writable]` The voting proxy account, `VotingProxy::find_address(vault)`
+    /// 3. `[signer, writable]` The rent payer
+    /// 4. `[]` The system program
+    DelegateVoting {
+        /// The proxy; the default pubkey revokes the delegation
+        proxy: Pubkey,
+    },
+
+    /// Set when the authority of a vault may change (DART only), creating its transfer
+    /// window if needed. The record is flagged while the window restricts transfers,
+    /// and `TransferAuthority` then fails with `TransferWindowClosed` outside it.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[writable]` The vault record account (must be previously initialized).
+    /// 1. `[signer]` The securities intermediary (DART)
+    /// 2. `[writable]` The transfer window account,
+    ///    `TransferWindow::find_address(TRANSFER_WINDOW_VAULT, vault)`
+    /// 3. `[signer, writable]` The rent payer
+    /// 4. `[]` The system program
+    /// 5. `[]` Optional DART identity, when account 1 is one of its operational keys
+    SetTransferWindow {
+        /// First slot transfers are allowed in; zero for no lockup
+        lockup_until_slot: u64,
+        /// Slot ranges transfers are refused in; empty ranges are unused
+        blackouts: [BlackoutRange; MAX_BLACKOUTS],
+    },
+
+    /// Set when the authority of any of the DART's vaults may change, creating the
+    /// DART's transfer window if needed. Transfers passed the window fail with
+    /// `TransferWindowClosed` outside it.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[writable]` The transfer window account,
+    ///    `TransferWindow::find_address(TRANSFER_WINDOW_DART, dart)`
+    /// 1. `[signer]` The securities intermediary (DART)
+    /// 2. `[signer, writable]` The rent payer
+    /// 3. `[]` The system program
+    /// 4. `[]` Optional DART identity, when account 1 is one of its operational keys;
+    ///    the window is then the identity's (see `with_dart_identity`)
+    SetDartTransferWindow {
+        /// First slot transfers are allowed in; zero for no lockup
+        lockup_until_slot: u64,
+        /// Slot ranges transfers are refused in; empty ranges are unused
+        blackouts: [BlackoutRange; MAX_BLACKOUTS],
+    },
+
+    /// Limit the instructions acting on a vault per epoch (DART only), creating its
+    /// rate limit account if needed; zero lifts the limit. While limited, instructions
+    /// for which `is_rate_limited` holds must be passed the rate limit account, writable
+    /// (see `with_rate_limit`), and fail with `RateLimited` once the epoch's allowance
+    /// is used up.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[writable]` The vault record account (must be previously initialized).
+    /// 1. `[signer]` The securities intermediary (DART)
+    /// 2. `[writable]` The rate limit account, `RateLimit::find_address(vault)`
+    /// 3. `[signer, writable]` The rent payer
+    /// 4. `[]` The system program
+    /// 5. `[]` Optional DART identity, when account 1 is one of its operational keys
+    SetRateLimit {
+        /// Mutations allowed per epoch; zero lifts the limit
+        max_mutations_per_epoch: u32,
+    },
+
+    /// Set the defaults the DART's new vaults take when `Initialize` is passed its
+    /// policy (see `with_dart_policy`), creating the policy account if needed. Unknown
+    /// `restriction` bits fail with `InvalidArgument`.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[writable]` The policy account, `DartPolicy::find_address(dart)`
+    /// 1. `[signer]` The securities intermediary (DART)
+    /// 2. `[signer, writable]` The rent payer
+    /// 3. `[]` The system program
+    SetDartPolicy {
+        /// Settings new vaults take
+        defaults: PolicyDefaults,
+    },
+
+    /// Initialize a vault record for a new authority with the tag, oracle gate, DART
+    /// policy settings and metadata of an existing vault (by its DART), for onboarding
+    /// accounts configured alike. The close recipient is copied only when passed (see
+    /// `with_close_recipient`). Companion accounts such as transfer windows and rate
+    /// limits are keyed by the vault and are not copied.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[]` The vault record account to copy (must be previously initialized).
+    /// 1. `[writable]` The new vault account: owned by the program, uninitialized and
+    ///    `VaultRecord::LEN` bytes plus the metadata length of account 0
+    /// 2. `[signer]` The securities intermediary (DART)
+    /// 3. `[]` The new vault's authority
+    /// 4. `[]` Optional DART identity, when account 2 is one of its operational keys
+    /// 5. `[]` Optional close recipient of account 0, to copy it
+    /// 6. `[writable]` The vault index of account 3, as for `Initialize`
+    CloneVault,
+
+    /// Create and initialize the vault of an authority for one asset, at
+    /// `find_asset_vault_address(dart, authority, asset_id)`, so one authority may hold
+    /// a vault per asset under the same DART without a keypair per vault. The DART is
+    /// the identity when account 5 is passed.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[writable]` The vault account, uncreated
+    /// 1. `[signer]` The securities intermediary (DART)
+    /// 2. `[]` The record authority
+    /// 3. `[signer, writable]` The rent payer, refunded when the vault is closed
+    /// 4. `[]` The system program
+    /// 5. `[]` Optional DART identity, when account 1 is one of its operational keys
+    /// 6. `[]` Optional DART policy (see `SetDartPolicy`)
+    /// 7. `[writable]` The vault index of account 2, as for `Initialize`
+    ///
+    /// Pass the program id in place of an absent optional account that precedes a
+    /// present one or the vault index.
+    CreateAssetVault {
+        /// Identifier of the asset the vault holds, e.g. a mint or a padded ISIN
+        asset_id: [u8; 32],
+    },
+
+    /// Bring the vault index of an authority up to date with the given vaults (by
+    /// anyone), creating the index if needed: active vaults of the authority are listed
+    /// and any other account is unlisted. Once it exists, creating, closing, rekeying
+    /// and migrating vaults and changing their authority keep it current; this catches
+    /// up with vaults of the authority from before.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[writable]` The vault index, `VaultIndex::find_address(authority)`
+    /// 1. `[]` The authority
+    /// 2. `[signer, writable]` The rent payer
+    /// 3. `[]` The system program
+    /// 4. ..4+N `[]` The vault records to list or unlist
+    SyncVaultIndex,
+
+    /// Set the lamports `Crank` pays per swept account and the slots past expiry
+    /// before it may sweep one (admin only).
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[writable]` The program config
+    /// 1. `[signer]` The config admin
+    SetCrankRules {
+        /// Bounty per swept account, paid out of its lamports and capped at
+        /// `MAX_CRANK_BOUNTY_BPS` of them
+        bounty_lamports: u64,
+        /// Grace period after expiry
+        grace_slots: u64,
+    },
+
+    /// Sweep expired sessions and approval roots past the config's grace period (by
+    /// anyone). Each account's lamports go to its creator: the authority that created
+    /// a session, or the DART of an approval root. The cranker earns the config's
+    /// bounty per account out of those lamports, capped at `MAX_CRANK_BOUNTY_BPS` of
+    /// them, so sweeping accounts it created itself never pays more than their rent.
+    /// Closed vaults are not swept: closing drains them, and a tombstone re-funded in
+    /// the same transaction is left for `Reopen`.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[]` The program config
+    /// 1. `[signer, writable]` The cranker
+    /// 2. ..2+2N `[writable]` Pairs of an account to sweep and its creator
+    Crank,
+
+    /// Set or clear a slot of the fee exemption list (admin only), creating the fee
+    /// config if needed. Fees are waived on vaults whose DART or authority is listed.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[writable]` The fee config, `[FEE_CONFIG_SEED]`
+    /// 1. `[]` The program config
+    /// 2. `[signer]` The config admin
+    /// 3. `[signer, writable]` The rent payer
+    /// 4. `[]` The system program
+    SetFeeExemption {
+        /// Slot to write, below `MAX_FEE_EXEMPT`
+        slot: u8,
+        /// DART or authority exempted; the default pubkey clears the slot
+        key: Pubkey,
+    },
+
+    /// Set the account `CollectFees` pays the accumulated fees to (admin only),
+    /// creating the fee config if needed.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[writable]` The fee config, `[FEE_CONFIG_SEED]`
+    /// 1. `[]` The program config
+    /// 2. `[signer]` The config admin
+    /// 3. `[signer, writable]` The rent payer
+    /// 4. `[]` The system program
+    SetFeeDestination {
+        /// The fee destination; the default pubkey stops collection
+        destination: Pubkey,
+    },
+
+    /// Pay the fees accumulated in the fee config, its lamports above rent exemption,
+    /// to the fee destination (admin only). Emits `FeesCollected`.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[writable]` The fee config, `[FEE_CONFIG_SEED]`
+    /// 1. `[]` The program config
+    /// 2. `[signer]` The config admin
+    /// 3. `[writable]` The fee destination set with `SetFeeDestination`
+    CollectFees,
+
+    /// Open the vault's audit trail (DART only), holding the latest `capacity` of the
+    /// instructions acting on it (see `is_rate_limited`): the slot, the instruction tag
+    /// and the first signer. The vault is flagged in `VaultRecord::audited`, and from
+    /// then on those instructions must be passed the trail, writable (see
+    /// `with_audit_trail`). Records older than version 6 have no room for the flag.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[writable]` The vault record account (must be previously initialized).
+    /// 1. `[signer]` The securities intermediary (DART)
+    /// 2. `[writable]` The audit trail account, `AuditTrail::find_address(vault)`
+    /// 3. `[signer, writable]` The rent payer
+    /// 4. `[]` The system program
+    /// 5. `[]` Optional DART identity, when account 1 is one of its operational keys
+    CreateAuditTrail {
+        /// Entries the trail holds, from 1 to `MAX_AUDIT_ENTRIES`
+        capacity: u16,
+    },
+
+    /// Create the program stats (admin only). From then on, any instruction passed the
+    /// stats account, writable (see `with_stats`), counts its invocation by tag once it
+    /// succeeds; failed instructions roll the count back with the rest of their writes.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[writable]` The program stats, `[STATS_SEED]`
+    /// 1. `[]` The program config
+    /// 2. `[signer]` The config admin
+    /// 3. `[signer, writable]` The rent payer
+    /// 4. `[]` The system program
+    InitializeStats,
+
+    /// Replace the close fee schedule of the fee config (admin only), creating the fee
+    /// config if needed. A close passed the fee config charges the fee of the first
+    /// active tier of the vault's tag, else `default_lamports`, unless the vault is
+    /// exempt.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[writable]` The fee config, `[FEE_CONFIG_SEED]`
+    /// 1. `[]` The program config
+    /// 2. `[signer]` The config admin
+    /// 3. `[signer, writable]` The rent payer
+    /// 4. `[]` The system program
+    SetCloseFees {
+        /// Lamports charged for vaults matching no tier
+        default_lamports: u64,
+        /// Close fees by vault tag
+        tiers: [CloseFeeTier; MAX_CLOSE_FEE_TIERS],
+    },
+
+    /// Register the key notified of the vault's authority changes and close (authority
+    /// only), creating the notify record and the key's notification account if needed.
+    /// Any instruction changing the authority or closing the vault, passed the notify
+    /// record and the notification account (see `with_notify`), records the change in
+    /// the notification account while the registering authority holds the vault.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[]` The vault record account (must be previously initialized).
+    /// 1. `[signer]` The record authority
+    /// 2. `[writable]` The notify record, `NotifyRecord::find_address(vault)`
+    /// 3. `[writable]` The notification account, `Notification::find_address(notify)`
+    /// 4. `[signer, writable]` The rent payer
+    /// 5. `[]` The system program
+    SetNotify {
+        /// Key notified; the default pubkey stops notifications
+        notify: Pubkey,
+    },
+
+    /// Check whether a `TransferAuthority` of the vault by `authority` to the new
+    /// authority would pass, without signatures or writes: the vault's status, the
+    /// receipt holder mode, the new authority, the price feed, pledges and transfer
+    /// windows. Succeeds either way, setting the outcome, a `TransferCheck`, as the
+    /// instruction's return data, so a UI can simulate it before asking for signatures.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[]` The vault record account
+    /// 1. `[]` The record authority
+    /// 2. `[]` The new record authority
+    /// 3. `[]` The transfer window of the vault's DART, whether or not it exists yet
+    /// 4. `[]` The price feed, pledge and vault transfer window, as the transfer would
+    ///    be passed them
+    CheckTransfer,
+}
+
+// Account indexes by instruction, so that code inspecting transactions does not
+// hardcode positions.
+
+// `Initialize` and `InitializeSized`
+/// The vault record account
+pub const INITIALIZE_PDA_INDEX: usize = 0;
+/// The DART account
+pub const INITIALIZE_DART_INDEX: usize = 1;
+/// The record authority account
+pub const INITIALIZE_AUTHORITY_INDEX: usize = 2;
+/// The optional DART identity account
+pub const INITIALIZE_IDENTITY_INDEX: usize = 3;
+/// The optional rent payer account
+pub const INITIALIZE_RENT_PAYER_INDEX: usize = 4;
+/// The optional close recipient account
+pub const INITIALIZE_CLOSE_RECIPIENT_INDEX: usize = 5;
+/// The record authority's vault index account, past the optional accounts
+pub const INITIALIZE_VAULT_INDEX_INDEX: usize = 7;
+
+// `TransferAuthority` and `TransferAuthorityChecked`
+/// The vault record account
+pub const TRANSFER_PDA_INDEX: usize = 0;
+/// The DART account
+pub const TRANSFER_DART_INDEX: usize = 1;
+/// The record authority account
+pub const TRANSFER_AUTHORITY_INDEX: usize = 2;
+/// The new record authority account
+pub const TRANSFER_NEW_AUTHORITY_INDEX: usize = 3;
+
+// `CloseAccount`, `CloseAccountWithRefund` and `CloseAccountChecked`
+/// The vault record account
+pub const CLOSE_PDA_INDEX: usize = 0;
+/// The DART account
+pub const CLOSE_DART_INDEX: usize = 1;
+/// The record authority account
+pub const CLOSE_AUTHORITY_INDEX: usize = 2;
+
+// `Reopen`
+/// The vault record account
+pub const REOPEN_PDA_INDEX: usize = 0;
+/// The DART account
+pub const REOPEN_DART_INDEX: usize = 1;
+
+// `ProposeDart`
+/// The vault record account
+pub const PROPOSE_DART_PDA_INDEX: usize = 0;
+/// The DART account
+pub const PROPOSE_DART_DART_INDEX: usize = 1;
+/// The proposed DART account
+pub const PROPOSE_DART_NEW_DART_INDEX: usize = 2;
+
+// `AcceptDart`
+/// The vault record account
+pub const ACCEPT_DART_PDA_INDEX: usize = 0;
+/// The proposed DART account
+pub const ACCEPT_DART_NEW_DART_INDEX: usize = 1;
+
+// `InitializeDartIdentity`, `SetOperationalKey` and `SetDartQuorum`
+/// The DART identity account
+pub const DART_IDENTITY_IDENTITY_INDEX: usize = 0;
+/// The master key account
+pub const DART_IDENTITY_MASTER_INDEX: usize = 1;
+
+// `CreateSession`
+/// The vault record account
+pub const CREATE_SESSION_PDA_INDEX: usize = 0;
+/// The record authority account
+pub const CREATE_SESSION_AUTHORITY_INDEX: usize = 1;
+/// The session account
+pub const CREATE_SESSION_SESSION_INDEX: usize = 2;
+/// The rent payer account
+pub const CREATE_SESSION_PAYER_INDEX: usize = 3;
+/// The system program account
+pub const CREATE_SESSION_SYSTEM_PROGRAM_INDEX: usize = 4;
+
+// `AddGuardian`
+/// The vault record account
+pub const ADD_GUARDIAN_PDA_INDEX: usize = 0;
+/// The record authority account
+pub const ADD_GUARDIAN_AUTHORITY_INDEX: usize = 1;
+/// The recovery account
+pub const ADD_GUARDIAN_RECOVERY_INDEX: usize = 2;
+/// The guardian account
+pub const ADD_GUARDIAN_GUARDIAN_INDEX: usize = 3;
+/// The rent payer account
+pub const ADD_GUARDIAN_PAYER_INDEX: usize = 4;
+/// The system program account
+pub const ADD_GUARDIAN_SYSTEM_PROGRAM_INDEX: usize = 5;
+
+// `RemoveGuardian`
+/// The vault record account
+pub const REMOVE_GUARDIAN_PDA_INDEX: usize = 0;
+/// The record authority account
+pub const REMOVE_GUARDIAN_AUTHORITY_INDEX: usize = 1;
+/// The recovery account
+pub const REMOVE_GUARDIAN_RECOVERY_INDEX: usize = 2;
+/// The guardian account
+pub const REMOVE_GUARDIAN_GUARDIAN_INDEX: usize = 3;
+
+// `InitiateRecovery`
+/// The vault record account
+pub const INITIATE_RECOVERY_PDA_INDEX: usize = 0;
+/// The DART account
+pub const INITIATE_RECOVERY_DART_INDEX: usize = 1;
+/// The recovery account
+pub const INITIATE_RECOVERY_RECOVERY_INDEX: usize = 2;
+/// The new record authority account
+pub const INITIATE_RECOVERY_NEW_AUTHORITY_INDEX: usize = 3;
+
+// `ApproveRecovery`
+/// The vault record account
+pub const APPROVE_RECOVERY_PDA_INDEX: usize = 0;
+/// The recovery account
+pub const APPROVE_RECOVERY_RECOVERY_INDEX: usize = 1;
+/// The guardian account
+pub const APPROVE_RECOVERY_GUARDIAN_INDEX: usize = 2;
+
+// `CancelRecovery`
+/// The vault record account
+pub const CANCEL_RECOVERY_PDA_INDEX: usize = 0;
+/// The record authority account
+pub const CANCEL_RECOVERY_AUTHORITY_INDEX: usize = 1;
+/// The recovery account
+pub const CANCEL_RECOVERY_RECOVERY_INDEX: usize = 2;
+
+// `CompleteRecovery`
+/// The vault record account
+pub const COMPLETE_RECOVERY_PDA_INDEX: usize = 0;
+/// The DART account
+pub const COMPLETE_RECOVERY_DART_INDEX: usize = 1;
+/// The recovery account
+pub const COMPLETE_RECOVERY_RECOVERY_INDEX: usize = 2;
+
+// `SetBeneficiary`
+/// The vault record account
+pub const SET_BENEFICIARY_PDA_INDEX: usize = 0;
+/// The record authority account
+pub const SET_BENEFICIARY_AUTHORITY_INDEX: usize = 1;
+/// The inheritance account
+pub const SET_BENEFICIARY_INHERITANCE_INDEX: usize = 2;
+/// The beneficiary account
+pub const SET_BENEFICIARY_BENEFICIARY_INDEX: usize = 3;
+/// The rent payer account
+pub const SET_BENEFICIARY_PAYER_INDEX: usize = 4;
+/// The system program account
+pub const SET_BENEFICIARY_SYSTEM_PROGRAM_INDEX: usize = 5;
+
+// `CheckIn`
+/// The vault record account
+pub const CHECK_IN_PDA_INDEX: usize = 0;
+/// The record authority account
+pub const CHECK_IN_AUTHORITY_INDEX: usize = 1;
+/// The inheritance account
+pub const CHECK_IN_INHERITANCE_INDEX: usize = 2;
+
+// `ClaimInheritance`
+/// The vault record account
+pub const CLAIM_INHERITANCE_PDA_INDEX: usize = 0;
+/// The DART account
+pub const CLAIM_INHERITANCE_DART_INDEX: usize = 1;
+/// The inheritance account
+pub const CLAIM_INHERITANCE_INHERITANCE_INDEX: usize = 2;
+/// The beneficiary account
+pub const CLAIM_INHERITANCE_BENEFICIARY_INDEX: usize = 3;
+
+// `SetTag`
+/// The vault record account
+pub const SET_TAG_PDA_INDEX: usize = 0;
+/// The DART account
+pub const SET_TAG_DART_INDEX: usize = 1;
+
+// `GrowAccount`
+/// The vault record account
+pub const GROW_ACCOUNT_PDA_INDEX: usize = 0;
+/// The DART account
+pub const GROW_ACCOUNT_DART_INDEX: usize = 1;
+/// The rent payer account
+pub const GROW_ACCOUNT_PAYER_INDEX: usize = 2;
+/// The system program account
+pub const GROW_ACCOUNT_SYSTEM_PROGRAM_INDEX: usize = 3;
+
+// `RecordAttestation`
+/// The vault record account
+pub const RECORD_ATTESTATION_PDA_INDEX: usize = 0;
+/// The DART account
+pub const RECORD_ATTESTATION_DART_INDEX: usize = 1;
+
+// `SwapAuthorities`
+/// The first vault record account
+pub const SWAP_PDA_A_INDEX: usize = 0;
+/// The second vault record account
+pub const SWAP_PDA_B_INDEX: usize = 1;
+/// The DART account
+pub const SWAP_DART_INDEX: usize = 2;
+/// The first record authority account
+pub const SWAP_AUTHORITY_A_INDEX: usize = 3;
+/// The second record authority account
+pub const SWAP_AUTHORITY_B_INDEX: usize = 4;
+
+// `ProposeTransfer`
+/// The vault record account
+pub const PROPOSE_TRANSFER_PDA_INDEX: usize = 0;
+/// The DART account
+pub const PROPOSE_TRANSFER_DART_INDEX: usize = 1;
+/// The record authority account
+pub const PROPOSE_TRANSFER_AUTHORITY_INDEX: usize = 2;
+/// The new record authority account
+pub const PROPOSE_TRANSFER_NEW_AUTHORITY_INDEX: usize = 3;
+
+// `AcceptTransfer`
+/// The vault record account
+pub const ACCEPT_TRANSFER_PDA_INDEX: usize = 0;
+/// The new record authority account
+pub const ACCEPT_TRANSFER_NEW_AUTHORITY_INDEX: usize = 1;
+
+// `ExpireTransfer`
+/// The vault record account
+pub const EXPIRE_TRANSFER_PDA_INDEX: usize = 0;
+
+// `SetOracle`
+/// The vault record account
+pub const SET_ORACLE_PDA_INDEX: usize = 0;
+/// The DART account
+pub const SET_ORACLE_DART_INDEX: usize = 1;
+/// The price feed account
+pub const SET_ORACLE_FEED_INDEX: usize = 2;
+
+// `MintReceipt`
+/// The vault record account
+pub const MINT_RECEIPT_PDA_INDEX: usize = 0;
+/// The DART account
+pub const MINT_RECEIPT_DART_INDEX: usize = 1;
+/// The rent payer account
+pub const MINT_RECEIPT_PAYER_INDEX: usize = 2;
+/// The mint account
+pub const MINT_RECEIPT_MINT_INDEX: usize = 3;
+/// The receipt token account
+pub const MINT_RECEIPT_TOKEN_ACCOUNT_INDEX: usize = 4;
+/// The token program account
+pub const MINT_RECEIPT_TOKEN_PROGRAM_INDEX: usize = 5;
+/// The system program account
+pub const MINT_RECEIPT_SYSTEM_PROGRAM_INDEX: usize = 6;
+
+// `SetReceiptAuthority`
+/// The vault record account
+pub const SET_RECEIPT_AUTHORITY_PDA_INDEX: usize = 0;
+/// The DART account
+pub const SET_RECEIPT_AUTHORITY_DART_INDEX: usize = 1;
+/// The record authority account
+pub const SET_RECEIPT_AUTHORITY_AUTHORITY_INDEX: usize = 2;
+/// The receipt token account
+pub const SET_RECEIPT_AUTHORITY_TOKEN_ACCOUNT_INDEX: usize = 3;
+
+// `InitializeTransferHook`
+/// The vault record account
+pub const INITIALIZE_TRANSFER_HOOK_PDA_INDEX: usize = 0;
+/// The DART account
+pub const INITIALIZE_TRANSFER_HOOK_DART_INDEX: usize = 1;
+/// The mint authority account
+pub const INITIALIZE_TRANSFER_HOOK_MINT_AUTHORITY_INDEX: usize = 2;
+/// The rent payer account
+pub const INITIALIZE_TRANSFER_HOOK_PAYER_INDEX: usize = 3;
+/// The mint account
+pub const INITIALIZE_TRANSFER_HOOK_MINT_INDEX: usize = 4;
+/// The extra account metas account
+pub const INITIALIZE_TRANSFER_HOOK_EXTRA_ACCOUNT_METAS_INDEX: usize = 5;
+/// The transfer hook config account
+pub const INITIALIZE_TRANSFER_HOOK_CONFIG_INDEX: usize = 6;
+/// The system program account
+pub const INITIALIZE_TRANSFER_HOOK_SYSTEM_PROGRAM_INDEX: usize = 7;
+
+// `SetAllowlistEntry`
+/// The vault record account
+pub const SET_ALLOWLIST_ENTRY_PDA_INDEX: usize = 0;
+/// The DART account
+pub const SET_ALLOWLIST_ENTRY_DART_INDEX: usize = 1;
+/// The transfer hook config account
+pub const SET_ALLOWLIST_ENTRY_CONFIG_INDEX: usize = 2;
+
+// `Rekey`
+/// The vault record account
+pub const REKEY_PDA_INDEX: usize = 0;
+/// The new vault account
+pub const REKEY_NEW_PDA_INDEX: usize = 1;
+/// The DART account
+pub const REKEY_DART_INDEX: usize = 2;
+/// The record authority account
+pub const REKEY_AUTHORITY_INDEX: usize = 3;
+
+// `InitializeConfig`
+/// The program config account
+pub const INITIALIZE_CONFIG_CONFIG_INDEX: usize = 0;
+/// The upgrade authority account
+pub const INITIALIZE_CONFIG_ADMIN_INDEX: usize = 1;
+/// The rent payer account
+pub const INITIALIZE_CONFIG_PAYER_INDEX: usize = 2;
+/// The program data account
+pub const INITIALIZE_CONFIG_PROGRAM_DATA_INDEX: usize = 3;
+/// The system program account
+pub const INITIALIZE_CONFIG_SYSTEM_PROGRAM_INDEX: usize = 4;
+
+// `Decommission`
+/// The program config account
+pub const DECOMMISSION_CONFIG_INDEX: usize = 0;
+/// The config admin account
+pub const DECOMMISSION_ADMIN_INDEX: usize = 1;
+
+// `MigrateToProgram`
+/// The vault record account
+pub const MIGRATE_TO_PROGRAM_PDA_INDEX: usize = 0;
+/// The new vault account
+pub const MIGRATE_TO_PROGRAM_NEW_PDA_INDEX: usize = 1;
+/// The DART account
+pub const MIGRATE_TO_PROGRAM_DART_INDEX: usize = 2;
+/// The record authority account
+pub const MIGRATE_TO_PROGRAM_AUTHORITY_INDEX: usize = 3;
+/// The program config account
+pub const MIGRATE_TO_PROGRAM_CONFIG_INDEX: usize = 4;
+/// The successor program account
+pub const MIGRATE_TO_PROGRAM_SUCCESSOR_INDEX: usize = 5;
+
+// `SetApprovalRoot`
+/// The approval root account
+pub const SET_APPROVAL_ROOT_APPROVAL_INDEX: usize = 0;
+/// The DART account
+pub const SET_APPROVAL_ROOT_DART_INDEX: usize = 1;
+/// The rent payer account
+pub const SET_APPROVAL_ROOT_PAYER_INDEX: usize = 2;
+
+// `TransferWithProof`
+/// The vault record account
+pub const TRANSFER_WITH_PROOF_PDA_INDEX: usize = 0;
+/// The approval root account
+pub const TRANSFER_WITH_PROOF_APPROVAL_INDEX: usize = 1;
+/// The current record authority account
+pub const TRANSFER_WITH_PROOF_AUTHORITY_INDEX: usize = 2;
+/// The new record authority account
+pub const TRANSFER_WITH_PROOF_NEW_AUTHORITY_INDEX: usize = 3;
+
+// `SetNoteKey`
+/// The vault record account
+pub const SET_NOTE_KEY_PDA_INDEX: usize = 0;
+/// The record authority account
+pub const SET_NOTE_KEY_AUTHORITY_INDEX: usize = 1;
+/// The note account
+pub const SET_NOTE_KEY_NOTE_INDEX: usize = 2;
+/// The rent payer account
+pub const SET_NOTE_KEY_PAYER_INDEX: usize = 3;
+
+// `SetNote`
+/// The vault record account
+pub const SET_NOTE_PDA_INDEX: usize = 0;
+/// The DART account
+pub const SET_NOTE_DART_INDEX: usize = 1;
+/// The note account
+pub const SET_NOTE_NOTE_INDEX: usize = 2;
+
+// `UpdateCommitments`
+/// The vault record account
+pub const UPDATE_COMMITMENTS_PDA_INDEX: usize = 0;
+/// The DART account
+pub const UPDATE_COMMITMENTS_DART_INDEX: usize = 1;
+/// The commitment account
+pub const UPDATE_COMMITMENTS_COMMITMENTS_INDEX: usize = 2;
+/// The rent payer account
+pub const UPDATE_COMMITMENTS_PAYER_INDEX: usize = 3;
+
+// `AddNote`
+/// The vault record account
+pub const ADD_NOTE_PDA_INDEX: usize = 0;
+/// The DART account
+pub const ADD_NOTE_DART_INDEX: usize = 1;
+/// The notes log account
+pub const ADD_NOTE_LOG_INDEX: usize = 2;
+/// The rent payer account
+pub const ADD_NOTE_PAYER_INDEX: usize = 3;
+
+// `AddAttestation`
+/// The vault record account
+pub const ADD_ATTESTATION_PDA_INDEX: usize = 0;
+/// The DART account
+pub const ADD_ATTESTATION_DART_INDEX: usize = 1;
+/// The attestation list account
+pub const ADD_ATTESTATION_LIST_INDEX: usize = 2;
+/// The rent payer account
+pub const ADD_ATTESTATION_PAYER_INDEX: usize = 3;
+
+// `RemoveAttestation`
+/// The vault record account
+pub const REMOVE_ATTESTATION_PDA_INDEX: usize = 0;
+/// The DART account
+pub const REMOVE_ATTESTATION_DART_INDEX: usize = 1;
+/// The attestation list account
+pub const REMOVE_ATTESTATION_LIST_INDEX: usize = 2;
+
+// `PledgeAsCollateral`
+/// The vault record account
+pub const PLEDGE_PDA_INDEX: usize = 0;
+/// The record authority account
+pub const PLEDGE_AUTHORITY_INDEX: usize = 1;
+/// The pledge account
+pub const PLEDGE_PLEDGE_INDEX: usize = 2;
+/// The rent payer account
+pub const PLEDGE_PAYER_INDEX: usize = 3;
+
+// `ReleaseCollateral`
+/// The vault record account
+pub const RELEASE_COLLATERAL_PDA_INDEX: usize = 0;
+/// The lender account
+pub const RELEASE_COLLATERAL_LENDER_INDEX: usize = 1;
+/// The pledge account
+pub const RELEASE_COLLATERAL_PLEDGE_INDEX: usize = 2;
+
+// `SetPosition`
+/// The vault record account
+pub const SET_POSITION_PDA_INDEX: usize = 0;
+/// The DART account
+pub const SET_POSITION_DART_INDEX: usize = 1;
+/// The position account
+pub const SET_POSITION_POSITION_INDEX: usize = 2;
+/// The rent payer account
+pub const SET_POSITION_PAYER_INDEX: usize = 3;
+
+// `ApplyCorporateAction`
+/// The DART account
+pub const APPLY_CORPORATE_ACTION_DART_INDEX: usize = 0;
+/// The first vault record account; vault and position accounts alternate from here
+pub const APPLY_CORPORATE_ACTION_FIRST_PDA_INDEX: usize = 1;
+
+// `Distribute`
+/// The DART account
+pub const DISTRIBUTE_DART_INDEX: usize = 0;
+/// The distribution cursor account
+pub const DISTRIBUTE_CURSOR_INDEX: usize = 1;
+/// The token account paying the distribution
+pub const DISTRIBUTE_SOURCE_INDEX: usize = 2;
+/// The token program account
+pub const DISTRIBUTE_TOKEN_PROGRAM_INDEX: usize = 3;
+/// The system program account
+pub const DISTRIBUTE_SYSTEM_PROGRAM_INDEX: usize = 4;
+/// The first vault record account; vault, position and token accounts repeat from here
+pub const DISTRIBUTE_FIRST_PDA_INDEX: usize = 5;
+
+// `Snapshot`
+/// The vault record account
+pub const SNAPSHOT_PDA_INDEX: usize = 0;
+/// The DART account
+pub const SNAPSHOT_DART_INDEX: usize = 1;
+/// The position account
+pub const SNAPSHOT_POSITION_INDEX: usize = 2;
+/// The snapshot account
+pub const SNAPSHOT_SNAPSHOT_INDEX: usize = 3;
+/// The rent payer account
+pub const SNAPSHOT_PAYER_INDEX: usize = 4;
+
+// `DelegateVoting`
+/// The vault record account
+pub const DELEGATE_VOTING_PDA_INDEX: usize = 0;
+/// The record authority account
+pub const DELEGATE_VOTING_AUTHORITY_INDEX: usize = 1;
+/// The voting proxy account
+pub const DELEGATE_VOTING_PROXY_INDEX: usize = 2;
+/// The rent payer account
+pub const DELEGATE_VOTING_PAYER_INDEX: usize = 3;
+
+// `SetTransferWindow`
+/// The vault record account
+pub const SET_TRANSFER_WINDOW_PDA_INDEX: usize = 0;
+/// The DART account
+pub const SET_TRANSFER_WINDOW_DART_INDEX: usize = 1;
+/// The transfer window account
+pub const SET_TRANSFER_WINDOW_WINDOW_INDEX: usize = 2;
+/// The rent payer account
+pub const SET_TRANSFER_WINDOW_PAYER_INDEX: usize = 3;
+
+// `SetDartTransferWindow`
+/// The transfer window account
+pub const SET_DART_TRANSFER_WINDOW_WINDOW_INDEX: usize = 0;
+/// The DART account
+pub const SET_DART_TRANSFER_WINDOW_DART_INDEX: usize = 1;
+/// The rent payer account
+pub const SET_DART_TRANSFER_WINDOW_PAYER_INDEX: usize = 2;
+
+// `SetRateLimit`
+/// The vault record account
+pub const SET_RATE_LIMIT_PDA_INDEX: usize = 0;
+/// The DART account
+pub const SET_RATE_LIMIT_DART_INDEX: usize = 1;
+/// The rate limit account
+pub const SET_RATE_LIMIT_RATE_LIMIT_INDEX: usize = 2;
+/// The rent payer account
+pub const SET_RATE_LIMIT_PAYER_INDEX: usize = 3;
+
+// `SetDartPolicy`
+/// The policy account
+pub const SET_DART_POLICY_POLICY_INDEX: usize = 0;
+/// The DART account
+pub const SET_DART_POLICY_DART_INDEX: usize = 1;
+/// The rent payer account
+pub const SET_DART_POLICY_PAYER_INDEX: usize = 2;
+
+// `CloneVault`
+/// The vault record account copied
+pub const CLONE_VAULT_PDA_INDEX: usize = 0;
+/// The new vault account
+pub const CLONE_VAULT_NEW_PDA_INDEX: usize = 1;
+/// The DART account
+pub const CLONE_VAULT_DART_INDEX: usize = 2;
+/// The new vault's authority account
+pub const CLONE_VAULT_AUTHORITY_INDEX: usize = 3;
+
+// `CreateAssetVault`
+/// The vault account
+pub const CREATE_ASSET_VAULT_PDA_INDEX: usize = 0;
+/// The DART account
+pub const CREATE_ASSET_VAULT_DART_INDEX: usize = 1;
+/// The record authority account
+pub const CREATE_ASSET_VAULT_AUTHORITY_INDEX: usize = 2;
+/// The rent payer account
+pub const CREATE_ASSET_VAULT_PAYER_INDEX: usize = 3;
+/// The optional DART identity account
+pub const CREATE_ASSET_VAULT_IDENTITY_INDEX: usize = 5;
+/// The record authority's vault index account, past the optional accounts
+pub const CREATE_ASSET_VAULT_VAULT_INDEX_INDEX: usize = 7;
+
+// `SyncVaultIndex`
+/// The vault index account
+pub const SYNC_VAULT_INDEX_INDEX_INDEX: usize = 0;
+/// The authority account
+pub const SYNC_VAULT_INDEX_AUTHORITY_INDEX: usize = 1;
+/// The rent payer account
+pub const SYNC_VAULT_INDEX_PAYER_INDEX: usize = 2;
+
+// `SetCrankRules`
+/// The program config account
+pub const SET_CRANK_RULES_CONFIG_INDEX: usize = 0;
+/// The config admin account
+pub const SET_CRANK_RULES_ADMIN_INDEX: usize = 1;
+
+// `Crank`
+/// The program config account
+pub const CRANK_CONFIG_INDEX: usize = 0;
+/// The cranker account
+pub const CRANK_CRANKER_INDEX: usize = 1;
+
+// `SetFeeExemption`
+/// The fee config account
+pub const SET_FEE_EXEMPTION_FEE_CONFIG_INDEX: usize = 0;
+/// The program config account
+pub const SET_FEE_EXEMPTION_CONFIG_INDEX: usize = 1;
+/// The config admin account
+pub const SET_FEE_EXEMPTION_ADMIN_INDEX: usize = 2;
+/// The rent payer account
+pub const SET_FEE_EXEMPTION_PAYER_INDEX: usize = 3;
+
+// `SetFeeDestination`
+/// The fee config account
+pub const SET_FEE_DESTINATION_FEE_CONFIG_INDEX: usize = 0;
+/// The program config account
+pub const SET_FEE_DESTINATION_CONFIG_INDEX: usize = 1;
+/// The config admin account
+pub const SET_FEE_DESTINATION_ADMIN_INDEX: usize = 2;
+/// The rent payer account
+pub const SET_FEE_DESTINATION_PAYER_INDEX: usize = 3;
+
+// `CollectFees`
+/// The fee config account
+pub const COLLECT_FEES_FEE_CONFIG_INDEX: usize = 0;
+/// The program config account
+pub const COLLECT_FEES_CONFIG_INDEX: usize = 1;
+/// The config admin account
+pub const COLLECT_FEES_ADMIN_INDEX: usize = 2;
+/// The fee destination account
+pub const COLLECT_FEES_DESTINATION_INDEX: usize = 3;
+
+// `CreateAuditTrail`
+/// The vault record account
+pub const CREATE_AUDIT_TRAIL_PDA_INDEX: usize = 0;
+/// The DART account
+pub const CREATE_AUDIT_TRAIL_DART_INDEX: usize = 1;
+/// The audit trail account
+pub const CREATE_AUDIT_TRAIL_TRAIL_INDEX: usize = 2;
+/// The rent payer account
+pub const CREATE_AUDIT_TRAIL_PAYER_INDEX: usize = 3;
+
+// `InitializeStats`
+/// The program stats account
+pub const INITIALIZE_STATS_STATS_INDEX: usize = 0;
+/// The program config account
+pub const INITIALIZE_STATS_CONFIG_INDEX: usize = 1;
+/// The config admin account
+pub const INITIALIZE_STATS_ADMIN_INDEX: usize = 2;
+/// The rent payer account
+pub const INITIALIZE_STATS_PAYER_INDEX: usize = 3;
+
+// `SetCloseFees`
+/// The fee config account
+pub const SET_CLOSE_FEES_FEE_CONFIG_INDEX: usize = 0;
+/// The program config account
+pub const SET_CLOSE_FEES_CONFIG_INDEX: usize = 1;
+/// The config admin account
+pub const SET_CLOSE_FEES_ADMIN_INDEX: usize = 2;
+/// The rent payer account
+pub const SET_CLOSE_FEES_PAYER_INDEX: usize = 3;
+
+// `SetNotify`
+/// The vault record account
+pub const SET_NOTIFY_PDA_INDEX: usize = 0;
+/// The record authority account
+pub const SET_NOTIFY_AUTHORITY_INDEX: usize = 1;
+/// The notify record account
+pub const SET_NOTIFY_NOTIFY_RECORD_INDEX: usize = 2;
+/// The notification account
+pub const SET_NOTIFY_NOTIFICATION_INDEX: usize = 3;
+/// The rent payer account
+pub const SET_NOTIFY_PAYER_INDEX: usize = 4;
+
+// `CheckTransfer`
+/// The vault record account
+pub const CHECK_TRANSFER_PDA_INDEX: usize = 0;
+/// The record authority account
+pub const CHECK_TRANSFER_AUTHORITY_INDEX: usize = 1;
+/// The new record authority account
+pub const CHECK_TRANSFER_NEW_AUTHORITY_INDEX: usize = 2;
+
+impl VaultInstruction {
+    /// Encode the instruction behind the current format version.
+    pub fn pack(&self) -> Vec<u8> {
+        let mut data = vec![FORMAT_PREFIX, FORMAT_VERSION];
+        // Writing to a `Vec` cannot fail.
+        self.serialize(&mut data).unwrap();
+        data
+    }
+
+    /// Decode unprefixed or versioned instruction data, rejecting an unknown format
+    /// version or tag, a truncated payload or trailing bytes with
+    /// `InvalidInstructionData`. Any slot bound is dropped; see `unpack_envelope`.
+    pub fn unpack(input: &[u8]) -> Result<Self, ProgramError> {
+        let (payload, _) = unpack_envelope(input)?;
+        Self::try_from_slice(payload).map_err(|error| {
+            msg!("Malformed instruction data: {}", error);
+            ProgramError::InvalidInstructionData
+        })
+    }
+
+    /// Native tag of the instruction, the first byte of its encoding.
+    pub fn tag(&self) -> u8 {
+        match self {
+            Self::Initialize => 0,
+            Self::TransferAuthority => 1,
+            Self::CloseAccount => 2,
+            Self::Reopen => 3,
+            Self::ProposeDart => 4,
+            Self::AcceptDart => 5,
+            Self::InitializeDartIdentity => 6,
+            Self::SetOperationalKey { .. } => 7,
+            Self::CreateSession { .. } => 8,
+            Self::AddGuardian { .. } => 9,
+            Self::RemoveGuardian { .. } => 10,
+            Self::InitiateRecovery => 11,
+            Self::ApproveRecovery => 12,
+            Self::CancelRecovery => 13,
+            Self::CompleteRecovery => 14,
+            Self::SetBeneficiary { .. } => 15,
+            Self::CheckIn => 16,
+            Self::ClaimInheritance => 17,
+            Self::SetTag { .. } => 18,
+            Self::InitializeSized { .. } => 19,
+            Self::GrowAccount { .. } => 20,
+            Self::CloseAccountWithRefund { .. } => 21,
+            Self::RecordAttestation { .. } => 22,
+            Self::SwapAuthorities => 23,
+            Self::ProposeTransfer { .. } => 24,
+            Self::AcceptTransfer => 25,
+            Self::ExpireTransfer => 26,
+            Self::SetOracle { .. } => 27,
+            Self::MintReceipt => 28,
+            Self::SetReceiptAuthority { .. } => 29,
+            Self::InitializeTransferHook => 30,
+            Self::SetAllowlistEntry { .. } => 31,
+            Self::Rekey => 32,
+            Self::TransferAuthorityChecked { .. } => 33,
+            Self::CloseAccountChecked { .. } => 34,
+            Self::SetDartQuorum { .. } => 35,
+            Self::InitializeConfig => 36,
+            Self::Decommission { .. } => 37,
+            Self::MigrateToProgram => 38,
+            Self::SetApprovalRoot { .. } => 39,
+            Self::TransferWithProof { .. } => 40,
+            Self::SetNoteKey { .. } => 41,
+            Self::SetNote { .. } => 42,
+            Self::UpdateCommitments { .. } => 43,
+            Self::AddNote { .. } => 44,
+            Self::AddAttestation { .. } => 45,
+            Self::RemoveAttestation { .. } => 46,
+            Self::PledgeAsCollateral { .. } => 47,
+            Self::ReleaseCollateral => 48,
+            Self::SetPosition { .. } => 49,
+            Self::ApplyCorporateAction { .. } => 50,
+            Self::Distribute { .. } => 51,
+            Self::Snapshot { .. } => 52,
+            Self::DelegateVoting { .. } => 53,
+            Self::SetTransferWindow { .. } => 54,
+            Self::SetDartTransferWindow { .. } => 55,
+            Self::SetRateLimit { .. } => 56,
+            Self::SetDartPolicy { .. } => 57,
+            Self::CloneVault => 58,
+            Self::CreateAssetVault { .. } => 59,
+            Self::SyncVaultIndex => 60,
+            Self::SetCrankRules { .. } => 61,
+            Self::Crank => 62,
+            Self::SetFeeExemption { .. } => 63,
+            Self::SetFeeDestination { .. } => 64,
+            Self::CollectFees => 65,
+            Self::CreateAuditTrail { .. } => 66,
+            Self::InitializeStats => 67,
+            Self::SetCloseFees { .. } => 68,
+            Self::SetNotify { .. } => 69,
+            Self::CheckTransfer => 70,
+        }
+    }
+
+    /// Whether the instruction must be passed the program config, so that it fails
+    /// once the program is decommissioned. Only the config's own setup and
+    /// decommissioning, and the migration it directs, run without it.
+    pub fn requires_program_config(&self) -> bool {
+        !matches!(
+            self,
+            Self::InitializeConfig | Self::Decommission { .. } | Self::MigrateToProgram
+        )
+    }
+
+    /// Whether the instruction may change the authority of a vault record, so that it
+    /// must be passed the transfer window of the record's DART.
+    pub fn changes_authority(&self) -> bool {
+        matches!(
+            self,
+            Self::TransferAuthority
+                | Self::TransferAuthorityChecked { .. }
+                | Self::TransferWithProof { .. }
+                | Self::ProposeTransfer { .. }
+                | Self::AcceptTransfer
+                | Self::SwapAuthorities
+                | Self::ClaimInheritance
+                | Self::CompleteRecovery
+        )
+    }
+
+    /// Whether the instruction acts on the vault record at account 0, or on each vault
+    /// of a batch, counted against the vault's rate limit and recorded in its audit
+    /// trail. Only instructions that create a vault, lift its limit, only read it, or
+    /// act on accounts other than a vault's are not counted.
+    pub fn is_rate_limited(&self) -> bool {
+        !matches!(
+            self,
+            // Creating a vault, or copying one into a new vault.
+            Self::Initialize
+                | Self::InitializeSized { .. }
+                | Self::CreateAssetVault { .. }
+                | Self::CloneVault
+                // The limit itself can always be lifted.
+                | Self::SetRateLimit { .. }
+                // Read-only.
+                | Self::CheckTransfer
+                // DART, program and fee accounts.
+                | Self::InitializeDartIdentity
+                | Self::SetOperationalKey { .. }
+                | Self::SetDartQuorum { .. }
+                | Self::SetApprovalRoot { .. }
+                | Self::SetDartTransferWindow { .. }
+                | Self::SetDartPolicy { .. }
+                | Self::Distribute { .. }
+                | Self::SyncVaultIndex
+                | Self::InitializeConfig
+                | Self::Decommission { .. }
+                | Self::SetCrankRules { .. }
+                | Self::Crank
+                | Self::InitializeStats
+                | Self::SetFeeExemption { .. }
+                | Self::SetFeeDestination { .. }
+                | Self::SetCloseFees { .. }
+                | Self::CollectFees
+        )
+    }
+}
+
+/// Strip the format prefix from instruction data, returning the instruction
+/// encoding and, for `SLOT_BOUNDED_FORMAT_VERSION`, the last slot it may execute in.
+pub fn unpack_envelope(input: &[u8]) -> Result<(&[u8], Option<u64>), ProgramError> {
+    match input {
+        [FORMAT_PREFIX, FORMAT_VERSION, payload @ ..] => Ok((payload, None)),
+        [FORMAT_PREFIX, SLOT_BOUNDED_FORMAT_VERSION, rest @ ..] if rest.len() >= 8 => {
+            let (slot, payload) = rest.split_at(8);
+            // `split_at(8)` leaves exactly eight bytes.
+            let slot = u64::from_le_bytes(slot.try_into().unwrap());
+            Ok((payload, Some(slot)))
+        }
+        [FORMAT_PREFIX, version @ ..] => {
+            msg!(
+                "Unsupported or truncated instruction format: {:?}",
+                version.first()
+            );
+            Err(ProgramError::InvalidInstructionData)
+        }
+        _ => Ok((input, None)),
+    }
+}
+
+/// Bound an instruction to execute no later than `valid_until_slot`, replacing any
+/// format prefix, so that its signed bytes cannot be replayed after that slot.
+pub fn with_valid_until_slot(mut instruction: Instruction, valid_until_slot: u64) -> Instruction {
+    let payload =
+        unpack_envelope(&instruction.data).map_or(&instruction.data[..], |(payload, _)| payload);
+    let mut data = vec![FORMAT_PREFIX, SLOT_BOUNDED_FORMAT_VERSION];
+    data.extend_from_slice(&valid_until_slot.to_le_bytes());
+    data.extend_from_slice(payload);
+    instruction.data = data;
+    instruction
+}
+
+// The transfer window of `scope`, a vault or DART as `kind` tells. Instructions changing
+// the authority take their DART's whether or not it exists.
+fn transfer_window(program_id: &Pubkey, kind: &[u8], scope: &Pubkey) -> AccountMeta {
+    AccountMeta::new_readonly(
+        TransferWindow::find_address(program_id, kind, scope).0,
+        false,
+    )
+}
+
+// The position account of the vault `pda`. Closing or moving the vault takes it whether
+// or not it exists.
+fn position(program_id: &Pubkey, pda: &Pubkey) -> AccountMeta {
+    AccountMeta::new_readonly(PositionRecord::find_address(program_id, pda).0, false)
+}
+
+// The notify record of the vault `pda`. Changing the authority of the vault or closing it
+// takes it whether or not a key is registered.
+fn notify_record(program_id: &Pubkey, pda: &Pubkey) -> AccountMeta {
+    AccountMeta::new_readonly(NotifyRecord::find_address(program_id, pda).0, false)
+}
+
+// The vault index of `authority`. Creating, closing, rekeying or migrating a vault and
+// changing its authority take the index of each authority involved whether or not it
+// exists.
+fn vault_index(program_id: &Pubkey, authority: &Pubkey) -> AccountMeta {
+    AccountMeta::new(VaultIndex::find_address(program_id, authority).0, false)
+}
+
+// The accounts of an instruction creating a vault: the `fixed` ones, program id
+// placeholders for its optional accounts, and the vault index of `authority` at
+// `position`, past them.
+fn with_index_past_optional(
+    program_id: &Pubkey,
+    mut fixed: Vec<AccountMeta>,
+    position: usize,
+    authority: &Pubkey,
+) -> Vec<AccountMeta> {
+    fixed.resize(position, AccountMeta::new_readonly(*program_id, false));
+    fixed.push(vault_index(program_id, authority));
+    fixed
+}
+
+// Build a vault instruction, appending the program config when it requires one and
+// does not already take it.
+fn vault_instruction(
+    program_id: Pubkey,
+    instruction: &VaultInstruction,
+    mut accounts: Vec<AccountMeta>,
+) -> Instruction {
+    let (config, _) = ProgramConfig::find_address(&program_id);
+    if instruction.requires_program_config() && !accounts.iter().any(|meta| meta.pubkey == config) {
+        accounts.push(AccountMeta::new_readonly(config, false));
+    }
+    Instruction::new_with_borsh(program_id, instruction, accounts)
+}
+
+/// Create a `VaultInstruction::Initialize` instruction
+pub fn initialize(
+    program_id: Pubkey,
+    pda: &Pubkey,
+    dart: &Pubkey,
+    authority: &Pubkey,
+) -> Instruction {
+    vault_instruction(
+        program_id,
+        &VaultInstruction::Initialize,
+        with_index_past_optional(
+            &program_id,
+            vec![
+                AccountMeta::new(*pda, false),
+                AccountMeta::new_readonly(*dart, true),
+                AccountMeta::new_readonly(*authority, false),
+            ],
+            INITIALIZE_VAULT_INDEX_INDEX,
+            authority,
+        ),
+    )
+}
+
+/// Create a `VaultInstruction::InitializeSized` instruction
+pub fn initialize_sized(
+    program_id: Pubkey,
+    pda: &Pubkey,
+    dart: &Pubkey,
+    authority: &Pubkey,
+    data_len: u32,
+) -> Instruction {
+    vault_instruction(
+        program_id,
+        &VaultInstruction::InitializeSized { data_len },
+        with_index_past_optional(
+            &program_id,
+            vec![
+                AccountMeta::new(*pda, false),
+                AccountMeta::new_readonly(*dart, true),
+                AccountMeta::new_readonly(*authority, false),
+            ],
+            INITIALIZE_VAULT_INDEX_INDEX,
+            authority,
+        ),
+    )
+}
+
+/// Create a `VaultInstruction::RecordAttestation` instruction
+pub fn record_attestation(
+    program_id: Pubkey,
+    pda: &Pubkey,
+    dart: &Pubkey,
+    vaa_hash: [u8; 32],
+) -> Instruction {
+    vault_instruction(
+        program_id,
+        &VaultInstruction::RecordAttestation { vaa_hash },
+        vec![
+            AccountMeta::new(*pda, false),
+            AccountMeta::new_readonly(*dart, true),
+        ],
+    )
+}
+
+/// Create a `VaultInstruction::GrowAccount` instruction
+pub fn grow_account(
+    program_id: Pubkey,
+    pda: &Pubkey,
+    dart: &Pubkey,
+    payer: &Pubkey,
+    target_len: u32,
+) -> Instruction {
+    vault_instruction(
+        program_id,
+        &VaultInstruction::GrowAccount { target_len },
+        vec![
+            AccountMeta::new(*pda, false),
+            AccountMeta::new_readonly(*dart, true),
+            AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+/// Create a `VaultInstruction::TransferAuthority` instruction
+pub fn transfer_authority(
+    program_id: Pubkey,
+    pda: &Pubkey,
+    dart: &Pubkey,
+    authority: &Pubkey,
+    new_authority: &Pubkey,
+) -> Instruction {
+    vault_instruction(
+        program_id,
+        &VaultInstruction::TransferAuthority,
+        vec![
+            AccountMeta::new(*pda, false),
+            AccountMeta::new_readonly(*dart, true),
+            AccountMeta::new_readonly(*authority, true),
+            AccountMeta::new_readonly(*new_authority, false),
+            transfer_window(&program_id, TRANSFER_WINDOW_DART, dart),
+            notify_record(&program_id, pda),
+            vault_index(&program_id, authority),
+            vault_index(&program_id, new_authority),
+        ],
+    )
+}
+
+/// Create a `VaultInstruction::TransferAuthorityChecked` instruction, expecting
+/// `authority` to still hold the record.
+pub fn transfer_authority_checked(
+    program_id: Pubkey,
+    pda: &Pubkey,
+    dart: &Pubkey,
+    authority: &Pubkey,
+    new_authority: &Pubkey,
+) -> Instruction {
+    vault_instruction(
+        program_id,
+        &VaultInstruction::TransferAuthorityChecked {
+            expected_authority: *authority,
+        },
+        vec![
+            AccountMeta::new(*pda, false),
+            AccountMeta::new_readonly(*dart, true),
+            AccountMeta::new_readonly(*authority, true),
+            AccountMeta::new_readonly(*new_authority, false),
+            transfer_window(&program_id, TRANSFER_WINDOW_DART, dart),
+            notify_record(&program_id, pda),
+            vault_index(&program_id, authority),
+            vault_index(&program_id, new_authority),
+        ],
+    )
+}
+
+/// Create a `VaultInstruction::SwapAuthorities` instruction
+pub fn swap_authorities(
+    program_id: Pubkey,
+    pda_a: &Pubkey,
+    pda_b: &Pubkey,
+    dart: &Pubkey,
+    authority_a: &Pubkey,
+    authority_b: &Pubkey,
+) -> Instruction {
+    vault_instruction(
+        program_id,
+        &VaultInstruction::SwapAuthorities,
+        vec![
+            AccountMeta::new(*pda_a, false),
+            AccountMeta::new(*pda_b, false),
+            AccountMeta::new_readonly(*dart, true),
+            AccountMeta::new_readonly(*authority_a, true),
+            AccountMeta::new_readonly(*authority_b, true),
+            transfer_window(&program_id, TRANSFER_WINDOW_DART, dart),
+            notify_record(&program_id, pda_a),
+            notify_record(&program_id, pda_b),
+            vault_index(&program_id, authority_a),
+            vault_index(&program_id, authority_b),
+        ],
+    )
+}
+
+/// Create a `VaultInstruction::ProposeTransfer` instruction
+pub fn propose_transfer(
+    program_id: Pubkey,
+    pda: &Pubkey,
+    dart: &Pubkey,
+    authority: &Pubkey,
+    new_authority: &Pubkey,
+    timeout_slots: u64,
+) -> Instruction {
+    vault_instruction(
+        program_id,
+        &VaultInstruction::ProposeTransfer { timeout_slots },
+        vec![
+            AccountMeta::new(*pda, false),
+            AccountMeta::new_readonly(*dart, true),
+            AccountMeta::new_readonly(*authority, true),
+            AccountMeta::new_readonly(*new_authority, false),
+            transfer_window(&program_id, TRANSFER_WINDOW_DART, dart),
+        ],
+    )
+}
+
+/// Create a `VaultInstruction::AcceptTransfer` instruction for the transfer of `pda`
+/// that `authority` proposed.
+pub fn accept_transfer(
+    program_id: Pubkey,
+    pda: &Pubkey,
+    dart: &Pubkey,
+    authority: &Pubkey,
+    new_authority: &Pubkey,
+) -> Instruction {
+    vault_instruction(
+        program_id,
+        &VaultInstruction::AcceptTransfer,
+        vec![
+            AccountMeta::new(*pda, false),
+            AccountMeta::new_readonly(*new_authority, true),
+            transfer_window(&program_id, TRANSFER_WINDOW_DART, dart),
+            notify_record(&program_id, pda),
+            vault_index(&program_id, authority),
+            vault_index(&program_id, new_authority),
+        ],
+    )
+}
+
+/// Create a `VaultInstruction::ExpireTransfer` instruction
+pub fn expire_transfer(program_id: Pubkey, pda: &Pubkey) -> Instruction {
+    vault_instruction(
+        program_id,
+        &VaultInstruction::ExpireTransfer,
+        vec![AccountMeta::new(*pda, false)],
+    )
+}
+
+/// Create a `VaultInstruction::SetOracle` instruction; `oracle` of `None` removes the
+/// gate.
+pub fn set_oracle(
+    program_id: Pubkey,
+    pda: &Pubkey,
+    dart: &Pubkey,
+    oracle: Option<&Pubkey>,
+    max_staleness_slots: u64,
+    min_price: i64,
+) -> Instruction {
+    vault_instruction(
+        program_id,
+        &VaultInstruction::SetOracle {
+            max_staleness_slots,
+            min_price,
+        },
+        vec![
+            AccountMeta::new(*pda, false),
+            AccountMeta::new_readonly(*dart, true),
+            AccountMeta::new_readonly(*oracle.unwrap_or(&program_id), false),
+        ],
+    )
+}
+
+/// Append a price feed to an instruction that changes the authority of a vault with
+/// an oracle.
+pub fn with_oracle(mut instruction: Instruction, oracle: &Pubkey) -> Instruction {
+    instruction
+        .accounts
+        .push(AccountMeta::new_readonly(*oracle, false));
+    instruction
+}
+
+/// Create a `VaultInstruction::MintReceipt` instruction
+pub fn mint_receipt(
+    program_id: Pubkey,
+    pda: &Pubkey,
+    dart: &Pubkey,
+    payer: &Pubkey,
+) -> Instruction {
+    let (mint, _) = receipt::find_mint_address(&program_id, pda);
+    let (token_account, _) = receipt::find_token_account_address(&program_id, pda);
+    vault_instruction(
+        program_id,
+        &VaultInstruction::MintReceipt,
+        vec![
+            AccountMeta::new(*pda, false),
+            AccountMeta::new_readonly(*dart, true),
+            AccountMeta::new(*payer, true),
+            AccountMeta::new(mint, false),
+            AccountMeta::new(token_account, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+/// Create a `VaultInstruction::SetReceiptAuthority` instruction
+pub fn set_receipt_authority(
+    program_id: Pubkey,
+    pda: &Pubkey,
+    dart: &Pubkey,
+    authority: &Pubkey,
+    token_account: &Pubkey,
+    enabled: bool,
+) -> Instruction {
+    vault_instruction(
+        program_id,
+        &VaultInstruction::SetReceiptAuthority { enabled },
+        vec![
+            AccountMeta::new(*pda, false),
+            AccountMeta::new_readonly(*dart, true),
+            AccountMeta::new_readonly(*authority, true),
+            AccountMeta::new_readonly(*token_account, false),
+        ],
+    )
+}
+
+/// Create a `VaultInstruction::InitializeTransferHook` instruction
+pub fn initialize_transfer_hook(
+    program_id: Pubkey,
+    pda: &Pubkey,
+    dart: &Pubkey,
+    mint_authority: &Pubkey,
+    payer: &Pubkey,
+    mint: &Pubkey,
+) -> Instruction {
+    let (extra_account_metas, _) =
+        transfer_hook::find_extra_account_metas_address(&program_id, mint);
+    let (config, _) = TransferHookConfig::find_address(&program_id, mint);
+    vault_instruction(
+        program_id,
+        &VaultInstruction::InitializeTransferHook,
+        vec![
+            AccountMeta::new_readonly(*pda, false),
+            AccountMeta::new_readonly(*dart, true),
+            AccountMeta::new_readonly(*mint_authority, true),
+            AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(*mint, false),
+            AccountMeta::new(extra_account_metas, false),
+            AccountMeta::new(config, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+/// Create a `VaultInstruction::SetAllowlistEntry` instruction
+pub fn set_allowlist_entry(
+    program_id: Pubkey,
+    pda: &Pubkey,
+    dart: &Pubkey,
+    mint: &Pubkey,
+    slot: u8,
+    holder: Pubkey,
+) -> Instruction {
+    let (config, _) = TransferHookConfig::find_address(&program_id, mint);
+    vault_instruction(
+        program_id,
+        &VaultInstruction::SetAllowlistEntry { slot, holder },
+        vec![
+            AccountMeta::new_readonly(*pda, false),
+            AccountMeta::new_readonly(*dart, true),
+            AccountMeta::new(config, false),
+        ],
+    )
+}
+
+/// Create a `VaultInstruction::Rekey` instruction
+pub fn rekey(
+    program_id: Pubkey,
+    pda: &Pubkey,
+    new_pda: &Pubkey,
+    dart: &Pubkey,
+    authority: &Pubkey,
+) -> Instruction {
+    vault_instruction(
+        program_id,
+        &VaultInstruction::Rekey,
+        vec![
+            AccountMeta::new(*pda, false),
+            AccountMeta::new(*new_pda, false),
+            AccountMeta::new_readonly(*dart, true),
+            AccountMeta::new(*authority, true),
+            position(&program_id, pda),
+            vault_index(&program_id, authority),
+        ],
+    )
+}
+
+/// Create a transfer-hook `Execute` instruction, as Token-2022 invokes it during a
+/// transfer of `amount`.
+pub fn transfer_hook_execute(
+    program_id: Pubkey,
+    source: &Pubkey,
+    mint: &Pubkey,
+    destination: &Pubkey,
+    owner: &Pubkey,
+    pda: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    let (extra_account_metas, _) =
+        transfer_hook::find_extra_account_metas_address(&program_id, mint);
+    let (config, _) = TransferHookConfig::find_address(&program_id, mint);
+    let mut data = transfer_hook::EXECUTE_DISCRIMINATOR.to_vec();
+    data.extend_from_slice(&amount.to_le_bytes());
+    Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(*source, false),
+            AccountMeta::new_readonly(*mint, false),
+            AccountMeta::new_readonly(*destination, false),
+            AccountMeta::new_readonly(*owner, false),
+            AccountMeta::new_readonly(extra_account_metas, false),
+            AccountMeta::new_readonly(config, false),
+            AccountMeta::new_readonly(*pda, false),
+        ],
+        data,
+    }
+}
+
+/// Append the event authority and the program to an instruction that emits events
+/// (authority changes, closes and rekeys), so that with `emit-cpi` they are also recorded as
+/// self-CPIs.
+pub fn with_event_authority(mut instruction: Instruction) -> Instruction {
+    let (event_authority, _) = event::find_event_authority_address(&instruction.program_id);
+    instruction.accounts.extend([
+        AccountMeta::new_readonly(event_authority, false),
+        AccountMeta::new_readonly(instruction.program_id, false),
+    ]);
+    instruction
+}
+
+/// Append the token account holding a vault's receipt to an authority-signed
+/// instruction, for vaults in receipt-holder mode.
+pub fn with_receipt_holder(mut instruction: Instruction, token_account: &Pubkey) -> Instruction {
+    instruction
+        .accounts
+        .push(AccountMeta::new_readonly(*token_account, false));
+    instruction
+}
+
+/// Append the custody receipt accounts to a close instruction, burning the receipt
+/// held in `token_account`.
+pub fn with_receipt_burn(
+    mut instruction: Instruction,
+    pda: &Pubkey,
+    token_account: &Pubkey,
+) -> Instruction {
+    let (mint, _) = receipt::find_mint_address(&instruction.program_id, pda);
+    instruction.accounts.extend([
+        AccountMeta::new(*token_account, false),
+        AccountMeta::new(mint, false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+    ]);
+    instruction
+}
+
+/// Create a `VaultInstruction::CloseAccount` instruction
+pub fn close_account(
+    program_id: Pubkey,
+    pda: &Pubkey,
+    dart: &Pubkey,
+    authority: &Pubkey,
+) -> Instruction {
+    vault_instruction(
+        program_id,
+        &VaultInstruction::CloseAccount,
+        vec![
+            AccountMeta::new(*pda, false),
+            AccountMeta::new_readonly(*dart, true),
+            AccountMeta::new(*authority, true),
+            AccountMeta::new(FeeConfig::find_address(&program_id).0, false),
+            position(&program_id, pda),
+            notify_record(&program_id, pda),
+            vault_index(&program_id, authority),
+        ],
+    )
+}
+
+/// Create a `VaultInstruction::CloseAccountWithRefund` instruction
+pub fn close_account_with_refund(
+    program_id: Pubkey,
+    pda: &Pubkey,
+    dart: &Pubkey,
+    authority: &Pubkey,
+    refund_authority: bool,
+) -> Instruction {
+    vault_instruction(
+        program_id,
+        &VaultInstruction::CloseAccountWithRefund { refund_authority },
+        vec![
+            AccountMeta::new(*pda, false),
+            AccountMeta::new_readonly(*dart, true),
+            AccountMeta::new(*authority, true),
+            AccountMeta::new(FeeConfig::find_address(&program_id).0, false),
+            position(&program_id, pda),
+            notify_record(&program_id, pda),
+            vault_index(&program_id, authority),
+        ],
+    )
+}
+
+/// Create a `VaultInstruction::CloseAccountChecked` instruction, expecting
+/// `authority` to still hold the record.
+pub fn close_account_checked(
+    program_id: Pubkey,
+    pda: &Pubkey,
+    dart: &Pubkey,
+    authority: &Pubkey,
+    refund_authority: bool,
+) -> Instruction {
+    vault_instruction(
+        program_id,
+        &VaultInstruction::CloseAccountChecked {
+            expected_authority: *authority,
+            refund_authority,
+        },
+        vec![
+            AccountMeta::new(*pda, false),
+            AccountMeta::new_readonly(*dart, true),
+            AccountMeta::new(*authority, true),
+            AccountMeta::new(FeeConfig::find_address(&program_id).0, false),
+            position(&program_id, pda),
+            notify_record(&program_id, pda),
+            vault_index(&program_id, authority),
+        ],
+    )
+}
+
+/// Create a `VaultInstruction::Reopen` instruction
+pub fn reopen(program_id: Pubkey, pda: &Pubkey, dart: &Pubkey) -> Instruction {
+    vault_instruction(
+        program_id,
+        &VaultInstruction::Reopen,
+        vec![
+            AccountMeta::new(*pda, false),
+            AccountMeta::new_readonly(*dart, true),
+        ],
+    )
+}
+
+/// Create a `VaultInstruction::ProposeDart` instruction
+pub fn propose_dart(
+    program_id: Pubkey,
+    pda: &Pubkey,
+    dart: &Pubkey,
+    new_dart: &Pubkey,
+) -> Instruction {
+    vault_instruction(
+        program_id,
+        &VaultInstruction::ProposeDart,
+        vec![
+            AccountMeta::new(*pda, false),
+            AccountMeta::new_readonly(*dart, true),
+            AccountMeta::new_readonly(*new_dart, false),
+        ],
+    )
+}
+
+/// Create a `VaultInstruction::AcceptDart` instruction
+pub fn accept_dart(program_id: Pubkey, pda: &Pubkey, new_dart: &Pubkey) -> Instruction {
+    vault_instruction(
+        program_id,
+        &VaultInstruction::AcceptDart,
+        vec![
+            AccountMeta::new(*pda, false),
+            AccountMeta::new_readonly(*new_dart, true),
+        ],
+    )
+}
+
+/// Create a `VaultInstruction::InitializeDartIdentity` instruction
+pub fn initialize_dart_identity(
+    program_id: Pubkey,
+    identity: &Pubkey,
+    master: &Pubkey,
+) -> Instruction {
+    vault_instruction(
+        program_id,
+        &VaultInstruction::InitializeDartIdentity,
+        vec![
+            AccountMeta::new(*identity, false),
+            AccountMeta::new_readonly(*master, true),
+        ],
+    )
+}
+
+/// Create a `VaultInstruction::SetOperationalKey` instruction
+pub fn set_operational_key(
+    program_id: Pubkey,
+    identity: &Pubkey,
+    master: &Pubkey,
+    slot: u8,
+    key: Pubkey,
+) -> Instruction {
+    vault_instruction(
+        program_id,
+        &VaultInstruction::SetOperationalKey { slot, key },
+        vec![
+            AccountMeta::new(*identity, false),
+            AccountMeta::new_readonly(*master, true),
+        ],
+    )
+}
+
+/// Create a `VaultInstruction::SetDartQuorum` instruction
+pub fn set_dart_quorum(
+    program_id: Pubkey,
+    identity: &Pubkey,
+    master: &Pubkey,
+    quorum: u8,
+) -> Instruction {
+    vault_instruction(
+        program_id,
+        &VaultInstruction::SetDartQuorum { quorum },
+        vec![
+            AccountMeta::new(*identity, false),
+            AccountMeta::new_readonly(*master, true),
+        ],
+    )
+}
+
+/// Create a `VaultInstruction::InitializeConfig` instruction
+pub fn initialize_config(program_id: Pubkey, admin: &Pubkey, payer: &Pubkey) -> Instruction {
+    let (config, _) = ProgramConfig::find_address(&program_id);
+    vault_instruction(
+        program_id,
+        &VaultInstruction::InitializeConfig,
+        vec![
+            AccountMeta::new(config, false),
+            AccountMeta::new_readonly(*admin, true),
+            AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(program_data_address(&program_id), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+/// Create a `VaultInstruction::Decommission` instruction
+pub fn decommission(program_id: Pubkey, admin: &Pubkey, successor: Pubkey) -> Instruction {
+    let (config, _) = ProgramConfig::find_address(&program_id);
+    vault_instruction(
+        program_id,
+        &VaultInstruction::Decommission { successor },
+        vec![
+            AccountMeta::new(config, false),
+            AccountMeta::new_readonly(*admin, true),
+        ],
+    )
+}
+
+/// Create a `VaultInstruction::MigrateToProgram` instruction
+pub fn migrate_to_program(
+    program_id: Pubkey,
+    pda: &Pubkey,
+    new_pda: &Pubkey,
+    dart: &Pubkey,
+    authority: &Pubkey,
+    successor: &Pubkey,
+) -> Instruction {
+    let (config, _) = ProgramConfig::find_address(&program_id);
+    vault_instruction(
+        program_id,
+        &VaultInstruction::MigrateToProgram,
+        vec![
+            AccountMeta::new(*pda, false),
+            AccountMeta::new(*new_pda, false),
+            AccountMeta::new_readonly(*dart, true),
+            AccountMeta::new(*authority, true),
+            AccountMeta::new_readonly(config, false),
+            AccountMeta::new_readonly(*successor, false),
+            position(&program_id, pda),
+            vault_index(&program_id, authority),
+            vault_index(successor, authority),
+        ],
+    )
+}
+
+/// Create a `VaultInstruction::SetApprovalRoot` instruction
+pub fn set_approval_root(
+    program_id: Pubkey,
+    dart: &Pubkey,
+    payer: &Pubkey,
+    root: [u8; 32],
+    expires_at_slot: u64,
+) -> Instruction {
+    let (approval, _) = ApprovalRoot::find_address(&program_id, dart);
+    vault_instruction(
+        program_id,
+        &VaultInstruction::SetApprovalRoot {
+            root,
+            expires_at_slot,
+        },
+        vec![
+            AccountMeta::new(approval, false),
+            AccountMeta::new_readonly(*dart, true),
+            AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+/// Create a `VaultInstruction::TransferWithProof` instruction
+pub fn transfer_with_proof(
+    program_id: Pubkey,
+    pda: &Pubkey,
+    dart: &Pubkey,
+    authority: &Pubkey,
+    new_authority: &Pubkey,
+    proof: Vec<[u8; 32]>,
+) -> Instruction {
+    let (approval, _) = ApprovalRoot::find_address(&program_id, dart);
+    vault_instruction(
+        program_id,
+        &VaultInstruction::TransferWithProof { proof },
+        vec![
+            AccountMeta::new(*pda, false),
+            AccountMeta::new_readonly(approval, false),
+            AccountMeta::new_readonly(*authority, true),
+            AccountMeta::new_readonly(*new_authority, false),
+            transfer_window(&program_id, TRANSFER_WINDOW_DART, dart),
+            notify_record(&program_id, pda),
+            vault_index(&program_id, authority),
+            vault_index(&program_id, new_authority),
+        ],
+    )
+}
+
+/// Create a `VaultInstruction::SetNoteKey` instruction
+pub fn set_note_key(
+    program_id: Pubkey,
+    pda: &Pubkey,
+    authority: &Pubkey,
+    payer: &Pubkey,
+    note_key: [u8; 32],
+) -> Instruction {
+    let (note, _) = NoteRecord::find_address(&program_id, pda);
+    vault_instruction(
+        program_id,
+        &VaultInstruction::SetNoteKey { note_key },
+        vec![
+            AccountMeta::new_readonly(*pda, false),
+            AccountMeta::new_readonly(*authority, true),
+            AccountMeta::new(note, false),
+            AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+/// Create a `VaultInstruction::SetNote` instruction
+pub fn set_note(
+    program_id: Pubkey,
+    pda: &Pubkey,
+    dart: &Pubkey,
+    encrypted_note: [u8; ENCRYPTED_NOTE_LEN],
+) -> Instruction {
+    let (note, _) = NoteRecord::find_address(&program_id, pda);
+    vault_instruction(
+        program_id,
+        &VaultInstruction::SetNote { encrypted_note },
+        vec![
+            AccountMeta::new_readonly(*pda, false),
+            AccountMeta::new_readonly(*dart, true),
+            AccountMeta::new(note, false),
+        ],
+    )
+}
+
+/// Create a `VaultInstruction::UpdateCommitments` instruction
+pub fn update_commitments(
+    program_id: Pubkey,
+    pda: &Pubkey,
+    dart: &Pubkey,
+    payer: &Pubkey,
+    commitments: [[u8; 32]; MAX_COMMITMENTS],
+) -> Instruction {
+    let (record, _) = CommitmentRecord::find_address(&program_id, pda);
+    vault_instruction(
+        program_id,
+        &VaultInstruction::UpdateCommitments { commitments },
+        vec![
+            AccountMeta::new_readonly(*pda, false),
+            AccountMeta::new_readonly(*dart, true),
+            AccountMeta::new(record, false),
+            AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+/// Create a `VaultInstruction::AddNote` instruction
+pub fn add_note(
+    program_id: Pubkey,
+    pda: &Pubkey,
+    dart: &Pubkey,
+    payer: &Pubkey,
+    hash: [u8; 32],
+    category: u16,
+) -> Instruction {
+    let (log, _) = NotesLog::find_address(&program_id, pda);
+    vault_instruction(
+        program_id,
+        &VaultInstruction::AddNote { hash, category },
+        vec![
+            AccountMeta::new_readonly(*pda, false),
+            AccountMeta::new_readonly(*dart, true),
+            AccountMeta::new(log, false),
+            AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+/// Create a `VaultInstruction::AddAttestation` instruction
+pub fn add_attestation(
+    program_id: Pubkey,
+    pda: &Pubkey,
+    dart: &Pubkey,
+    payer: &Pubkey,
+    kind: u16,
+    hash: [u8; 32],
+    expires_at_slot: u64,
+) -> Instruction {
+    let (list, _) = AttestationList::find_address(&program_id, pda);
+    vault_instruction(
+        program_id,
+        &VaultInstruction::AddAttestation {
+            kind,
+            hash,
+            expires_at_slot,
+        },
+        vec![
+            AccountMeta::new_readonly(*pda, false),
+            AccountMeta::new_readonly(*dart, true),
+            AccountMeta::new(list, false),
+            AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+/// Create a `VaultInstruction::RemoveAttestation` instruction
+pub fn remove_attestation(
+    program_id: Pubkey,
+    pda: &Pubkey,
+    dart: &Pubkey,
+    kind: u16,
+    hash: [u8; 32],
+) -> Instruction {
+    let (list, _) = AttestationList::find_address(&program_id, pda);
+    vault_instruction(
+        program_id,
+        &VaultInstruction::RemoveAttestation { kind, hash },
+        vec![
+            AccountMeta::new_readonly(*pda, false),
+            AccountMeta::new_readonly(*dart, true),
+            AccountMeta::new(list, false),
+        ],
+    )
+}
+
+/// Create a `VaultInstruction::PledgeAsCollateral` instruction
+pub fn pledge_as_collateral(
+    program_id: Pubkey,
+    pda: &Pubkey,
+    authority: &Pubkey,
+    payer: &Pubkey,
+    lender: Pubkey,
+) -> Instruction {
+    let (pledge, _) = PledgeRecord::find_address(&program_id, pda);
+    vault_instruction(
+        program_id,
+        &VaultInstruction::PledgeAsCollateral { lender },
+        vec![
+            AccountMeta::new(*pda, false),
+            AccountMeta::new_readonly(*authority, true),
+            AccountMeta::new(pledge, false),
+            AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+/// Create a `VaultInstruction::ReleaseCollateral` instruction
+pub fn release_collateral(program_id: Pubkey, pda: &Pubkey, lender: &Pubkey) -> Instruction {
+    let (pledge, _) = PledgeRecord::find_address(&program_id, pda);
+    vault_instruction(
+        program_id,
+        &VaultInstruction::ReleaseCollateral,
+        vec![
+            AccountMeta::new(*pda, false),
+            AccountMeta::new_readonly(*lender, true),
+            AccountMeta::new(pledge, false),
+        ],
+    )
+}
+
+/// Create a `VaultInstruction::SetPosition` instruction
+pub fn set_position(
+    program_id: Pubkey,
+    pda: &Pubkey,
+    dart: &Pubkey,
+    payer: &Pubkey,
+    asset_id: [u8; 32],
+    balance: u64,
+) -> Instruction {
+    let (position, _) = PositionRecord::find_address(&program_id, pda);
+    vault_instruction(
+        program_id,
+        &VaultInstruction::SetPosition { asset_id, balance },
+        vec![
+            AccountMeta::new_readonly(*pda, false),
+            AccountMeta::new_readonly(*dart, true),
+            AccountMeta::new(position, false),
+            AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+/// Create a `VaultInstruction::ApplyCorporateAction` instruction over the positions of
+/// `pdas`
+pub fn apply_corporate_action(
+    program_id: Pubkey,
+    dart: &Pubkey,
+    pdas: &[Pubkey],
+    action_type: CorporateActionType,
+    ratio: u64,
+) -> Instruction {
+    let mut accounts = vec![AccountMeta::new_readonly(*dart, true)];
+    for pda in pdas {
+        let (position, _) = PositionRecord::find_address(&program_id, pda);
+        accounts.push(AccountMeta::new_readonly(*pda, false));
+        accounts.push(AccountMeta::new(position, false));
+    }
+    vault_instruction(
+        program_id,
+        &VaultInstruction::ApplyCorporateAction { action_type, ratio },
+        accounts,
+    )
+}
+
+/// Create a `VaultInstruction::Distribute` instruction paying `payments`, pairs of a
+/// vault and a token account of its authority, in ascending vault order
+pub fn distribute(
+    program_id: Pubkey,
+    dart: &Pubkey,
+    source: &Pubkey,
+    id: u64,
+    asset_id: [u8; 32],
+    amount_per_unit: u64,
+    payments: &[(Pubkey, Pubkey)],
+) -> Instruction {
+    let (cursor, _) = DistributionCursor::find_address(&program_id, dart, id);
+    let mut accounts = vec![
+        AccountMeta::new(*dart, true),
+        AccountMeta::new(cursor, false),
+        AccountMeta::new(*source, false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+    for (pda, destination) in payments {
+        let (position, _) = PositionRecord::find_address(&program_id, pda);
+        accounts.push(AccountMeta::new_readonly(*pda, false));
+        accounts.push(AccountMeta::new_readonly(position, false));
+        accounts.push(AccountMeta::new(*destination, false));
+    }
+    vault_instruction(
+        program_id,
+        &VaultInstruction::Distribute {
+            id,
+            asset_id,
+            amount_per_unit,
+        },
+        accounts,
+    )
+}
+
+/// Create a `VaultInstruction::Snapshot` instruction
+pub fn snapshot(
+    program_id: Pubkey,
+    pda: &Pubkey,
+    dart: &Pubkey,
+    payer: &Pubkey,
+    record_date_slot: u64,
+) -> Instruction {
+    let (position, _) = PositionRecord::find_address(&program_id, pda);
+    let (snapshot, _) = SnapshotRecord::find_address(&program_id, pda, record_date_slot);
+    vault_instruction(
+        program_id,
+        &VaultInstruction::Snapshot { record_date_slot },
+        vec![
+            AccountMeta::new_readonly(*pda, false),
+            AccountMeta::new_readonly(*dart, true),
+            AccountMeta::new_readonly(position, false),
+            AccountMeta::new(snapshot, false),
+            AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+/// Create a `VaultInstruction::DelegateVoting` instruction
+pub fn delegate_voting(
+    program_id: Pubkey,
+    pda: &Pubkey,
+    authority: &Pubkey,
+    payer: &Pubkey,
+    proxy: Pubkey,
+) -> Instruction {
+    let (voting, _) = VotingProxy::find_address(&program_id, pda);
+    vault_instruction(
+        program_id,
+        &VaultInstruction::DelegateVoting { proxy },
+        vec![
+            AccountMeta::new_readonly(*pda, false),
+            AccountMeta::new_readonly(*authority, true),
+            AccountMeta::new(voting, false),
+            AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+/// Create a `VaultInstruction::SetTransferWindow` instruction
+pub fn set_transfer_window(
+    program_id: Pubkey,
+    pda: &Pubkey,
+    dart: &Pubkey,
+    payer: &Pubkey,
+    lockup_until_slot: u64,
+    blackouts: [BlackoutRange; MAX_BLACKOUTS],
+) -> Instruction {
+    let (window, _) = TransferWindow::find_address(&program_id, TRANSFER_WINDOW_VAULT, pda);
+    vault_instruction(
+        program_id,
+        &VaultInstruction::SetTransferWindow {
+            lockup_until_slot,
+            blackouts,
+        },
+        vec![
+            AccountMeta::new(*pda, false),
+            AccountMeta::new_readonly(*dart, true),
+            AccountMeta::new(window, false),
+            AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+/// Create a `VaultInstruction::SetDartTransferWindow` instruction
+pub fn set_dart_transfer_window(
+    program_id: Pubkey,
+    dart: &Pubkey,
+    payer: &Pubkey,
+    lockup_until_slot: u64,
+    blackouts: [BlackoutRange; MAX_BLACKOUTS],
+) -> Instruction {
+    let (window, _) = TransferWindow::find_address(&program_id, TRANSFER_WINDOW_DART, dart);
+    vault_instruction(
+        program_id,
+        &VaultInstruction::SetDartTransferWindow {
+            lockup_until_slot,
+            blackouts,
+        },
+        vec![
+            AccountMeta::new(window, false),
+            AccountMeta::new_readonly(*dart, true),
+            AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+/// Create a `VaultInstruction::SetRateLimit` instruction
+pub fn set_rate_limit(
+    program_id: Pubkey,
+    pda: &Pubkey,
+    dart: &Pubkey,
+    payer: &Pubkey,
+    max_mutations_per_epoch: u32,
+) -> Instruction {
+    let (rate_limit, _) = RateLimit::find_address(&program_id, pda);
+    vault_instruction(
+        program_id,
+        &VaultInstruction::SetRateLimit {
+            max_mutations_per_epoch,
+        },
+        vec![
+            AccountMeta::new(*pda, false),
+            AccountMeta::new_readonly(*dart, true),
+            AccountMeta::new(rate_limit, false),
+            AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+/// Create a `VaultInstruction::SetDartPolicy` instruction
+pub fn set_dart_policy(
+    program_id: Pubkey,
+    dart: &Pubkey,
+    payer: &Pubkey,
+    defaults: PolicyDefaults,
+) -> Instruction {
+    let (policy, _) = DartPolicy::find_address(&program_id, dart);
+    vault_instruction(
+        program_id,
+        &VaultInstruction::SetDartPolicy { defaults },
+        vec![
+            AccountMeta::new(policy, false),
+            AccountMeta::new_readonly(*dart, true),
+            AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+/// Create a `VaultInstruction::CloneVault` instruction
+pub fn clone_vault(
+    program_id: Pubkey,
+    pda: &Pubkey,
+    new_pda: &Pubkey,
+    dart: &Pubkey,
+    authority: &Pubkey,
+) -> Instruction {
+    vault_instruction(
+        program_id,
+        &VaultInstruction::CloneVault,
+        vec![
+            AccountMeta::new_readonly(*pda, false),
+            AccountMeta::new(*new_pda, false),
+            AccountMeta::new_readonly(*dart, true),
+            AccountMeta::new_readonly(*authority, false),
+            vault_index(&program_id, authority),
+        ],
+    )
+}
+
+/// Create a `VaultInstruction::CreateAssetVault` instruction for the vault of
+/// `authority` holding `asset_id` through `dart`.
+pub fn create_asset_vault(
+    program_id: Pubkey,
+    dart: &Pubkey,
+    authority: &Pubkey,
+    payer: &Pubkey,
+    asset_id: [u8; 32],
+) -> Instruction {
+    let (pda, _) = find_asset_vault_address(&program_id, dart, authority, &asset_id);
+    vault_instruction(
+        program_id,
+        &VaultInstruction::CreateAssetVault { asset_id },
+        with_index_past_optional(
+            &program_id,
+            vec![
+                AccountMeta::new(pda, false),
+                AccountMeta::new_readonly(*dart, true),
+                AccountMeta::new_readonly(*authority, false),
+                AccountMeta::new(*payer, true),
+                AccountMeta::new_readonly(system_program::id(), false),
+            ],
+            CREATE_ASSET_VAULT_VAULT_INDEX_INDEX,
+            authority,
+        ),
+    )
+}
+
+/// Create a `VaultInstruction::SyncVaultIndex` instruction listing or unlisting
+/// `vaults` in the index of `authority`.
+pub fn sync_vault_index(
+    program_id: Pubkey,
+    authority: &Pubkey,
+    payer: &Pubkey,
+    vaults: &[Pubkey],
+) -> Instruction {
+    let (index, _) = VaultIndex::find_address(&program_id, authority);
+    let mut accounts = vec![
+        AccountMeta::new(index, false),
+        AccountMeta::new_readonly(*authority, false),
+        AccountMeta::new(*payer, true),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+    accounts.extend(
+        vaults
+            .iter()
+            .map(|vault| AccountMeta::new_readonly(*vault, false)),
+    );
+    vault_instruction(program_id, &VaultInstruction::SyncVaultIndex, accounts)
+}
+
+/// Create a `VaultInstruction::SetCrankRules` instruction
+pub fn set_crank_rules(
+    program_id: Pubkey,
+    admin: &Pubkey,
+    bounty_lamports: u64,
+    grace_slots: u64,
+) -> Instruction {
+    let (config, _) = ProgramConfig::find_address(&program_id);
+    vault_instruction(
+        program_id,
+        &VaultInstruction::SetCrankRules {
+            bounty_lamports,
+            grace_slots,
+        },
+        vec![
+            AccountMeta::new(config, false),
+            AccountMeta::new_readonly(*admin, true),
+        ],
+    )
+}
+
+/// Create a `VaultInstruction::Crank` instruction sweeping each `(account, creator)`
+/// of `sweeps`.
+pub fn crank(program_id: Pubkey, cranker: &Pubkey, sweeps: &[(Pubkey, Pubkey)]) -> Instruction {
+    let (config, _) = ProgramConfig::find_address(&program_id);
+    let mut accounts = vec![
+        AccountMeta::new_readonly(config, false),
+        AccountMeta::new(*cranker, true),
+    ];
+    for (account, creator) in sweeps {
+        accounts.push(AccountMeta::new(*account, false));
+        accounts.push(AccountMeta::new(*creator, false));
+    }
+    vault_instruction(program_id, &VaultInstruction::Crank, accounts)
+}
+
+/// Create a `VaultInstruction::SetFeeExemption` instruction
+pub fn set_fee_exemption(
+    program_id: Pubkey,
+    admin: &Pubkey,
+    payer: &Pubkey,
+    slot: u8,
+    key: Pubkey,
+) -> Instruction {
+    let (fee_config, _) = FeeConfig::find_address(&program_id);
+    let (config, _) = ProgramConfig::find_address(&program_id);
+    vault_instruction(
+        program_id,
+        &VaultInstruction::SetFeeExemption { slot, key },
+        vec![
+            AccountMeta::new(fee_config, false),
+            AccountMeta::new_readonly(config, false),
+            AccountMeta::new_readonly(*admin, true),
+            AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+/// Create a `VaultInstruction::SetFeeDestination` instruction
+pub fn set_fee_destination(
+    program_id: Pubkey,
+    admin: &Pubkey,
+    payer: &Pubkey,
+    destination: Pubkey,
+) -> Instruction {
+    let (fee_config, _) = FeeConfig::find_address(&program_id);
+    let (config, _) = ProgramConfig::find_address(&program_id);
+    vault_instruction(
+        program_id,
+        &VaultInstruction::SetFeeDestination { destination },
+        vec![
+            AccountMeta::new(fee_config, false),
+            AccountMeta::new_readonly(config, false),
+            AccountMeta::new_readonly(*admin, true),
+            AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+/// Create a `VaultInstruction::CollectFees` instruction
+pub fn collect_fees(program_id: Pubkey, admin: &Pubkey, destination: &Pubkey) -> Instruction {
+    let (fee_config, _) = FeeConfig::find_address(&program_id);
+    let (config, _) = ProgramConfig::find_address(&program_id);
+    vault_instruction(
+        program_id,
+        &VaultInstruction::CollectFees,
+        vec![
+            AccountMeta::new(fee_config, false),
+            AccountMeta::new_readonly(config, false),
+            AccountMeta::new_readonly(*admin, true),
+            AccountMeta::new(*destination, false),
+        ],
+    )
+}
+
+/// Create a `VaultInstruction::CreateAuditTrail` instruction
+pub fn create_audit_trail(
+    program_id: Pubkey,
+    pda: &Pubkey,
+    dart: &Pubkey,
+    payer: &Pubkey,
+    capacity: u16,
+) -> Instruction {
+    let (trail, _) = AuditTrail::find_address(&program_id, pda);
+    vault_instruction(
+        program_id,
+        &VaultInstruction::CreateAuditTrail { capacity },
+        vec![
+            AccountMeta::new(*pda, false),
+            AccountMeta::new_readonly(*dart, true),
+            AccountMeta::new(trail, false),
+            AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+/// Append the position account of `pda` to a hand-built close, rekey or migration,
+/// which fails while the position holds units. The builders already pass it;
+/// instructions taking it are returned unchanged.
+pub fn with_position(mut instruction: Instruction, pda: &Pubkey) -> Instruction {
+    let position = position(&instruction.program_id, pda);
+    if !instruction.accounts.contains(&position) {
+        instruction.accounts.push(position);
+    }
+    instruction
+}
+
+/// Append the associated token account of `pda` for its position's asset `asset_id`
+/// (see `state::find_asset_token_address`) to a close, rekey or migration, which must
+/// be passed it once the vault has a position and fails while it holds tokens.
+pub fn with_asset_token_account(
+    mut instruction: Instruction,
+    pda: &Pubkey,
+    asset_id: &[u8; 32],
+) -> Instruction {
+    let (token_account, _) = find_asset_token_address(pda, asset_id);
+    instruction
+        .accounts
+        .push(AccountMeta::new_readonly(token_account, false));
+    instruction
+}
+
+/// Append the vault index of `authority` to `instruction`, writable, unless already
+/// there. The builders include the indexes of the authorities they are given; a vault
+/// in receipt-holder mode also needs the index of the authority last written to its
+/// record, once the receipt has changed hands. Like the other appended accounts, it
+/// goes after any optional positional account.
+pub fn with_vault_index(mut instruction: Instruction, authority: &Pubkey) -> Instruction {
+    let index = vault_index(&instruction.program_id, authority);
+    if !instruction.accounts.contains(&index) {
+        instruction.accounts.push(index);
+    }
+    instruction
+}
+
+/// Append the rate limit account of `pda` to `instruction`, writable, so a mutation of a
+/// rate-limited vault is counted. Like the other appended accounts, it goes after any
+/// optional positional account.
+pub fn with_rate_limit(mut instruction: Instruction, pda: &Pubkey) -> Instruction {
+    let (rate_limit, _) = RateLimit::find_address(&instruction.program_id, pda);
+    instruction
+        .accounts
+        .push(AccountMeta::new(rate_limit, false));
+    instruction
+}
+
+/// Create a `VaultInstruction::InitializeStats` instruction
+pub fn initialize_stats(program_id: Pubkey, admin: &Pubkey, payer: &Pubkey) -> Instruction {
+    let (stats, _) = ProgramStats::find_address(&program_id);
+    let (config, _) = ProgramConfig::find_address(&program_id);
+    vault_instruction(
+        program_id,
+        &VaultInstruction::InitializeStats,
+        vec![
+            AccountMeta::new(stats, false),
+            AccountMeta::new_readonly(config, false),
+            AccountMeta::new_readonly(*admin, true),
+            AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+/// Create a `VaultInstruction::SetCloseFees` instruction
+pub fn set_close_fees(
+    program_id: Pubkey,
+    admin: &Pubkey,
+    payer: &Pubkey,
+    default_lamports: u64,
+    tiers: [CloseFeeTier; MAX_CLOSE_FEE_TIERS],
+) -> Instruction {
+    let (fee_config, _) = FeeConfig::find_address(&program_id);
+    let (config, _) = ProgramConfig::find_address(&program_id);
+    vault_instruction(
+        program_id,
+        &VaultInstruction::SetCloseFees {
+            default_lamports,
+            tiers,
+        },
+        vec![
+            AccountMeta::new(fee_config, false),
+            AccountMeta::new_readonly(config, false),
+            AccountMeta::new_readonly(*admin, true),
+            AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+/// Create a `VaultInstruction::SetNotify` instruction
+pub fn set_notify(
+    program_id: Pubkey,
+    pda: &Pubkey,
+    authority: &Pubkey,
+    payer: &Pubkey,
+    notify: Pubkey,
+) -> Instruction {
+    let (notify_record, _) = NotifyRecord::find_address(&program_id, pda);
+    let (notification, _) = Notification::find_address(&program_id, &notify);
+    vault_instruction(
+        program_id,
+        &VaultInstruction::SetNotify { notify },
+        vec![
+            AccountMeta::new_readonly(*pda, false),
+            AccountMeta::new_readonly(*authority, true),
+            AccountMeta::new(notify_record, false),
+            AccountMeta::new(notification, false),
+            AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+/// Create a `VaultInstruction::CheckTransfer` instruction
+pub fn check_transfer(
+    program_id: Pubkey,
+    pda: &Pubkey,
+    dart: &Pubkey,
+    authority: &Pubkey,
+    new_authority: &Pubkey,
+) -> Instruction {
+    vault_instruction(
+        program_id,
+        &VaultInstruction::CheckTransfer,
+        vec![
+            AccountMeta::new_readonly(*pda, false),
+            AccountMeta::new_readonly(*authority, false),
+            AccountMeta::new_readonly(*new_authority, false),
+            transfer_window(&program_id, TRANSFER_WINDOW_DART, dart),
+        ],
+    )
+}
+
+/// Append the notification account of the key `notify` registered for `pda`, writable,
+/// to an instruction changing the authority of `pda` or closing it, so the change is
+/// notified. The notify record of `pda` is appended too, unless already there; the
+/// builders of those instructions include it.
+pub fn with_notify(mut instruction: Instruction, pda: &Pubkey, notify: &Pubkey) -> Instruction {
+    let notify_record = notify_record(&instruction.program_id, pda);
+    let (notification, _) = Notification::find_address(&instruction.program_id, notify);
+    if !instruction.accounts.contains(&notify_record) {
+        instruction.accounts.push(notify_record);
+    }
+    instruction
+        .accounts
+        .push(AccountMeta::new(notification, false));
+    instruction
+}
+
+/// Append the fee config to a close instruction, writable, unless already there. The
+/// close builders include it.
+pub fn with_fee_config(mut instruction: Instruction) -> Instruction {
+    let (fee_config, _) = FeeConfig::find_address(&instruction.program_id);
+    if !instruction
+        .accounts
+        .iter()
+        .any(|meta| meta.pubkey == fee_config)
+    {
+        instruction
+            .accounts
+            .push(AccountMeta::new(fee_config, false));
+    }
+    instruction
+}
+
+/// Append the program stats to `instruction`, writable, so its invocation is counted.
+pub fn with_stats(mut instruction: Instruction) -> Instruction {
+    let (stats, _) = ProgramStats::find_address(&instruction.program_id);
+    instruction.accounts.push(AccountMeta::new(stats, false));
+    instruction
+}
+
+/// Append the audit trail of `pda` to `instruction`, writable, so the instruction is
+/// recorded, as it must be once the vault has a trail. Like the other appended
+/// accounts, it goes after any optional positional account.
+pub fn with_audit_trail(mut instruction: Instruction, pda: &Pubkey) -> Instruction {
+    let (trail, _) = AuditTrail::find_address(&instruction.program_id, pda);
+    instruction.accounts.push(AccountMeta::new(trail, false));
+    instruction
+}
+
+/// Append the transfer windows of `pda` and of its DART `dart` to `instruction`, one
+/// changing the authority, unless already there, so the windows set for either are
+/// checked. Windows never set are skipped. The builders include the DART's window.
+pub fn with_transfer_windows(
+    mut instruction: Instruction,
+    pda: &Pubkey,
+    dart: &Pubkey,
+) -> Instruction {
+    for (kind, scope) in [(TRANSFER_WINDOW_VAULT, pda), (TRANSFER_WINDOW_DART, dart)] {
+        let window = transfer_window(&instruction.program_id, kind, scope);
+        if !instruction.accounts.contains(&window) {
+            instruction.accounts.push(window);
+        }
+    }
+    instruction
+}
+
+/// Append the pledge account of `pda` and the signing `lender` to `instruction`, so it
+/// may change the authority of a vault pledged as collateral.
+pub fn with_pledge(mut instruction: Instruction, pda: &Pubkey, lender: &Pubkey) -> Instruction {
+    let (pledge, _) = PledgeRecord::find_address(&instruction.program_id, pda);
+    instruction
+        .accounts
+        .push(AccountMeta::new_readonly(pledge, false));
+    instruction
+        .accounts
+        .push(AccountMeta::new_readonly(*lender, true));
+    instruction
+}
+
+/// Append the program config to a hand-built `instruction`, which fails with
+/// `NotEnoughAccountKeys` without it, and with `ProgramMigrated`, naming the successor,
+/// once the program is decommissioned. The builders already pass it; instructions
+/// taking it are returned unchanged.
+pub fn with_program_config(mut instruction: Instruction) -> Instruction {
+    let (config, _) = ProgramConfig::find_address(&instruction.program_id);
+    if !instruction
+        .accounts
+        .iter()
+        .any(|meta| meta.pubkey == config)
+    {
+        instruction
+            .accounts
+            .push(AccountMeta::new_readonly(config, false));
+    }
+    instruction
+}
+
+/// Let a DART identity, passed as the DART of `instruction`, approve through a quorum
+/// of operational key signatures rather than sign: the identity becomes writable and
+/// unsigned, and the instructions sysvar is appended. Sign
+/// `approval::message` of the result and precede it with
+/// `quorum::verify_signatures`.
+pub fn with_dart_quorum(mut instruction: Instruction, identity: &Pubkey) -> Instruction {
+    for account in &mut instruction.accounts {
+        if account.pubkey == *identity {
+            account.is_signer = false;
+            account.is_writable = true;
+        }
+    }
+    instruction
+        .accounts
+        .push(AccountMeta::new_readonly(sysvar::instructions::id(), false));
+    instruction
+}
+
+/// Append a DART identity to a DART-signed instruction, so an operational key of the
+/// identity can sign in the DART role, along with the identity's transfer window when
+/// the instruction changes the authority. A `SetDartTransferWindow` instruction is
+/// pointed at the identity's window instead, and instructions creating a vault take
+/// the identity at its optional position.
+pub fn with_dart_identity(mut instruction: Instruction, identity: &Pubkey) -> Instruction {
+    let meta = AccountMeta::new_readonly(*identity, false);
+    let window = transfer_window(&instruction.program_id, TRANSFER_WINDOW_DART, identity);
+    match VaultInstruction::unpack(&instruction.data) {
+        Ok(VaultInstruction::Initialize | VaultInstruction::InitializeSized { .. }) => {
+            return with_optional_account(instruction, INITIALIZE_IDENTITY_INDEX, meta);
+        }
+        Ok(VaultInstruction::CreateAssetVault { .. }) => {
+            return with_optional_account(instruction, CREATE_ASSET_VAULT_IDENTITY_INDEX, meta);
+        }
+        Ok(VaultInstruction::SetDartTransferWindow { .. }) => {
+            instruction.accounts[SET_DART_TRANSFER_WINDOW_WINDOW_INDEX] =
+                AccountMeta::new(window.pubkey, false);
+            instruction.accounts.push(meta);
+        }
+        Ok(decoded) if decoded.changes_authority() => instruction.accounts.extend([meta, window]),
+        _ => instruction.accounts.push(meta),
+    }
+    instruction
+}
+
+/// Create a `VaultInstruction::CreateSession` instruction
+pub fn create_session(
+    program_id: Pubkey,
+    pda: &Pubkey,
+    authority: &Pubkey,
+    payer: &Pubkey,
+    key: Pubkey,
+    expires_at_slot: u64,
+    scope: u8,
+) -> Instruction {
+    let (session, _) = SessionRecord::find_address(&program_id, pda, &key);
+    vault_instruction(
+        program_id,
+        &VaultInstruction::CreateSession {
+            key,
+            expires_at_slot,
+            scope,
+        },
+        vec![
+            AccountMeta::new_readonly(*pda, false),
+            AccountMeta::new_readonly(*authority, true),
+            AccountMeta::new(session, false),
+            AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+/// Create a `VaultInstruction::AddGuardian` instruction
+pub fn add_guardian(
+    program_id: Pubkey,
+    pda: &Pubkey,
+    authority: &Pubkey,
+    guardian: &Pubkey,
+    payer: &Pubkey,
+    threshold: u8,
+) -> Instruction {
+    let (recovery, _) = RecoveryRecord::find_address(&program_id, pda);
+    vault_instruction(
+        program_id,
+        &VaultInstruction::AddGuardian { threshold },
+        vec![
+            AccountMeta::new_readonly(*pda, false),
+            AccountMeta::new_readonly(*authority, true),
+            AccountMeta::new(recovery, false),
+            AccountMeta::new_readonly(*guardian, false),
+            AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+/// Create a `VaultInstruction::RemoveGuardian` instruction
+pub fn remove_guardian(
+    program_id: Pubkey,
+    pda: &Pubkey,
+    authority: &Pubkey,
+    guardian: &Pubkey,
+    threshold: u8,
+) -> Instruction {
+    let (recovery, _) = RecoveryRecord::find_address(&program_id, pda);
+    vault_instruction(
+        program_id,
+        &VaultInstruction::RemoveGuardian { threshold },
+        vec![
+            AccountMeta::new_readonly(*pda, false),
+            AccountMeta::new_readonly(*authority, true),
+            AccountMeta::new(recovery, false),
+            AccountMeta::new_readonly(*guardian, false),
+        ],
+    )
+}
+
+/// Create a `VaultInstruction::InitiateRecovery` instruction
+pub fn initiate_recovery(
+    program_id: Pubkey,
+    pda: &Pubkey,
+    dart: &Pubkey,
+    new_authority: &Pubkey,
+) -> Instruction {
+    let (recovery, _) = RecoveryRecord::find_address(&program_id, pda);
+    vault_instruction(
+        program_id,
+        &VaultInstruction::InitiateRecovery,
+        vec![
+            AccountMeta::new_readonly(*pda, false),
+            AccountMeta::new_readonly(*dart, true),
+            AccountMeta::new(recovery, false),
+            AccountMeta::new_readonly(*new_authority, false),
+        ],
+    )
+}
+
+/// Create a `VaultInstruction::ApproveRecovery` instruction
+pub fn approve_recovery(program_id: Pubkey, pda: &Pubkey, guardian: &Pubkey) -> Instruction {
+    let (recovery, _) = RecoveryRecord::find_address(&program_id, pda);
+    vault_instruction(
+        program_id,
+        &VaultInstruction::ApproveRecovery,
+        vec![
+            AccountMeta::new_readonly(*pda, false),
+            AccountMeta::new(recovery, false),
+            AccountMeta::new_readonly(*guardian, true),
+        ],
+    )
+}
+
+/// Create a `VaultInstruction::CancelRecovery` instruction
+pub fn cancel_recovery(program_id: Pubkey, pda: &Pubkey, authority: &Pubkey) -> Instruction {
+    let (recovery, _) = RecoveryRecord::find_address(&program_id, pda);
+    vault_instruction(
+        program_id,
+        &VaultInstruction::CancelRecovery,
+        vec![
+            AccountMeta::new_readonly(*pda, false),
+            AccountMeta::new_readonly(*authority, true),
+            AccountMeta::new(recovery, false),
+        ],
+    )
+}
+
+/// Create a `VaultInstruction::CompleteRecovery` instruction rotating the authority
+/// of `pda` from `authority` to the pending recovery authority `new_authority`.
+pub fn complete_recovery(
+    program_id: Pubkey,
+    pda: &Pubkey,
+    dart: &Pubkey,
+    authority: &Pubkey,
+    new_authority: &Pubkey,
+) -> Instruction {
+    let (recovery, _) = RecoveryRecord::find_address(&program_id, pda);
+    vault_instruction(
+        program_id,
+        &VaultInstruction::CompleteRecovery,
+        vec![
+            AccountMeta::new(*pda, false),
+            AccountMeta::new_readonly(*dart, true),
+            AccountMeta::new(recovery, false),
+            transfer_window(&program_id, TRANSFER_WINDOW_DART, dart),
+            notify_record(&program_id, pda),
+            vault_index(&program_id, authority),
+            vault_index(&program_id, new_authority),
+        ],
+    )
+}
+
+/// Create a `VaultInstruction::SetTag` instruction
+pub fn set_tag(program_id: Pubkey, pda: &Pubkey, dart: &Pubkey, tag: u16) -> Instruction {
+    vault_instruction(
+        program_id,
+        &VaultInstruction::SetTag { tag },
+        vec![
+            AccountMeta::new(*pda, false),
+            AccountMeta::new_readonly(*dart, true),
+        ],
+    )
+}
+
+/// Create a `VaultInstruction::SetBeneficiary` instruction
+pub fn set_beneficiary(
+    program_id: Pubkey,
+    pda: &Pubkey,
+    authority: &Pubkey,
+    beneficiary: &Pubkey,
+    payer: &Pubkey,
+    inactivity_slots: u64,
+) -> Instruction {
+    let (inheritance, _) = InheritanceRecord::find_address(&program_id, pda);
+    vault_instruction(
+        program_id,
+        &VaultInstruction::SetBeneficiary { inactivity_slots },
+        vec![
+            AccountMeta::new_readonly(*pda, false),
+            AccountMeta::new_readonly(*authority, true),
+            AccountMeta::new(inheritance, false),
+            AccountMeta::new_readonly(*beneficiary, false),
+            AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+/// Create a `VaultInstruction::CheckIn` instruction
+pub fn check_in(program_id: Pubkey, pda: &Pubkey, authority: &Pubkey) -> Instruction {
+    let (inheritance, _) = InheritanceRecord::find_address(&program_id, pda);
+    vault_instruction(
+        program_id,
+        &VaultInstruction::CheckIn,
+        vec![
+            AccountMeta::new_readonly(*pda, false),
+            AccountMeta::new_readonly(*authority, true),
+            AccountMeta::new(inheritance, false),
+        ],
+    )
+}
+
+/// Create a `VaultInstruction::ClaimInheritance` instruction handing `pda` from
+/// `authority` to `beneficiary`.
+pub fn claim_inheritance(
+    program_id: Pubkey,
+    pda: &Pubkey,
+    dart: &Pubkey,
+    authority: &Pubkey,
+    beneficiary: &Pubkey,
+) -> Instruction {
+    let (inheritance, _) = InheritanceRecord::find_address(&program_id, pda);
+    vault_instruction(
+        program_id,
+        &VaultInstruction::ClaimInheritance,
+        vec![
+            AccountMeta::new(*pda, false),
+            AccountMeta::new_readonly(*dart, true),
+            AccountMeta::new(inheritance, false),
+            AccountMeta::new_readonly(*beneficiary, true),
+            transfer_window(&program_id, TRANSFER_WINDOW_DART, dart),
+            notify_record(&program_id, pda),
+            vault_index(&program_id, authority),
+            vault_index(&program_id, beneficiary),
+        ],
+    )
+}
+
+/// Add a rent payer to an `Initialize` or `InitializeSized` instruction, so the
+/// account that funded the record is refunded when it is closed.
+pub fn with_rent_payer(instruction: Instruction, payer: &Pubkey) -> Instruction {
+    with_optional_account(instruction, 4, AccountMeta::new_readonly(*payer, true))
+}
+
+/// Add a close recipient to an `Initialize` or `InitializeSized` instruction, so the
+/// record's lamports go to `recipient` when it is closed, or the copied vault's close
+/// recipient to a `CloneVault` instruction, so the new vault keeps it.
+pub fn with_close_recipient(instruction: Instruction, recipient: &Pubkey) -> Instruction {
+    with_optional_account(instruction, 5, AccountMeta::new_readonly(*recipient, false))
+}
+
+/// Add the policy of `dart`, the record's DART, to an `Initialize`, `InitializeSized` or
+/// `CreateAssetVault` instruction, so the record takes its defaults.
+pub fn with_dart_policy(instruction: Instruction, dart: &Pubkey) -> Instruction {
+    let (policy, _) = DartPolicy::find_address(&instruction.program_id, dart);
+    with_optional_account(instruction, 6, AccountMeta::new_readonly(policy, false))
+}
+
+// Place `meta` at `index`, replacing a program id placeholder there and filling
+// absent optional accounts before it with the program id. The program config the
+// builder appended moves past it, so it is never read as an optional account.
+fn with_optional_account(
+    mut instruction: Instruction,
+    index: usize,
+    meta: AccountMeta,
+) -> Instruction {
+    let (config, _) = ProgramConfig::find_address(&instruction.program_id);
+    let config = instruction
+        .accounts
+        .iter()
+        .position(|meta| meta.pubkey == config)
+        .map(|position| instruction.accounts.remove(position));
+    while instruction.accounts.len() < index {
+        instruction
+            .accounts
+            .push(AccountMeta::new_readonly(instruction.program_id, false));
+    }
+    match instruction.accounts.get_mut(index) {
+        Some(placeholder) if placeholder.pubkey == instruction.program_id => *placeholder = meta,
+        _ => instruction.accounts.insert(index, meta),
+    }
+    instruction.accounts.extend(config);
+    instruction
+}
+
+/// Add the recorded rent payer to a close instruction to receive the account lamports.
+pub fn with_rent_refund(mut instruction: Instruction, payer: &Pubkey) -> Instruction {
+    match instruction
+        .accounts
+        .iter_mut()
+        .find(|meta| meta.pubkey == *payer)
+    {
+        Some(meta) => meta.is_writable = true,
+        None => instruction.accounts.push(AccountMeta::new(*payer, false)),
+    }
+    instruction
+}
+
+/// Append the session account of `key` to an authority-signed instruction, so the
+/// session key can sign in the authority role. A builder given the session key as the
+/// authority passes its vault index, so an authority change signed this way also needs
+/// `with_vault_index` for the recorded authority.
+pub fn with_session(mut instruction: Instruction, pda: &Pubkey, key: &Pubkey) -> Instruction {
+    let (session, _) = SessionRecord::find_address(&instruction.program_id, pda, key);
+    instruction
+        .accounts
+        .push(AccountMeta::new_readonly(session, false));
+    instruction
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_program::program_error::ProgramError;
+
+    /// very small data for easy testing
+    const DATA_SIZE: usize = 8;
+
+    /// Bytes for tests
+    const TEST_BYTES: [u8; DATA_SIZE] = [42; DATA_SIZE];
+
+    #[test]
+    fn serialize_initialize() {
+        let instruction = VaultInstruction::Initialize;
+        let expected = vec![0];
+        assert_eq!(borsh::to_vec(&instruction).unwrap(), expected);
+        assert_eq!(
+            VaultInstruction::try_from_slice(&expected).unwrap(),
+            instruction
+        );
+    }
+
+    #[test]
+    fn serialize_transfer_authority() {
+        let instruction = VaultInstruction::TransferAuthority;
+        let expected = vec![1];
+        assert_eq!(borsh::to_vec(&instruction).unwrap(), expected);
+        assert_eq!(
+            VaultInstruction::try_from_slice(&expected).unwrap(),
+            instruction
+        );
+    }
+
+    #[test]
+    fn serialize_close_account() {
+        let instruction = VaultInstruction::CloseAccount;
+        let expected = vec![2];
+        assert_eq!(borsh::to_vec(&instruction).unwrap(), expected);
+        assert_eq!(
+            VaultInstruction::try_from_slice(&expected).unwrap(),
+            instruction
+        );
+    }
+
+    #[test]
+    fn serialize_reopen() {
+        let instruction = VaultInstruction::Reopen;
+        let expected = vec![3];
+        assert_eq!(borsh::to_vec(&instruction).unwrap(), expected);
+        assert_eq!(
+            VaultInstruction::try_from_slice(&expected).unwrap(),
+            instruction
+        );
+    }
+
+    #[test]
+    fn serialize_propose_dart() {
+        let instruction = VaultInstruction::ProposeDart;
+        let expected = vec![4];
+        assert_eq!(borsh::to_vec(&instruction).unwrap(), expected);
+        assert_eq!(
+            VaultInstruction::try_from_slice(&expected).unwrap(),
+            instruction
+        );
+    }
+
+    #[test]
+    fn serialize_accept_dart() {
+        let instruction = VaultInstruction::AcceptDart;
+        let expected = vec![5];
+        assert_eq!(borsh::to_vec(&instruction).unwrap(), expected);
+        assert_eq!(
+            VaultInstruction::try_from_slice(&expected).unwrap(),
+            instruction
+        );
+    }
+
+    #[test]
+    fn serialize_initialize_dart_identity() {
+        let instruction = VaultInstruction::InitializeDartIdentity;
+        let expected = vec![6];
+        assert_eq!(borsh::to_vec(&instruction).unwrap(), expected);
+        assert_eq!(
+            VaultInstruction::try_from_slice(&expected).unwrap(),
+            instruction
+        );
+    }
+
+    #[test]
+    fn serialize_set_operational_key() {
+        let key = Pubkey::new_from_array([7; 32]);
+        let instruction = VaultInstruction::SetOperationalKey { slot: 2, key };
+        let mut expected = vec![7, 2];
+        expected.extend_from_slice(&key.to_bytes());
+        assert_eq!(borsh::to_vec(&instruction).unwrap(), expected);
+        assert_eq!(
+            VaultInstruction::try_from_slice(&expected).unwrap(),
+            instruction
+        );
+    }
+
+    #[test]
+    fn serialize_create_session() {
+        let key = Pubkey::new_from_array([7; 32]);
+        let instruction = VaultInstruction::CreateSession {
+            key,
+            expires_at_slot: 300,
+            scope: 1,
+        };
+        let mut expected = vec![8];
+        expected.extend_from_slice(&key.to_bytes());
+        expected.extend_from_slice(&300u64.to_le_bytes());
+        expected.push(1);
+        assert_eq!(borsh::to_vec(&instruction).unwrap(), expected);
+        assert_eq!(
+            VaultInstruction::try_from_slice(&expected).unwrap(),
+            instruction
+        );
+    }
+
+    #[test]
+    fn serialize_guardian_instructions() {
+        let cases = [
+            (VaultInstruction::AddGuardian { threshold: 2 }, vec![9, 2]),
+            (
+                VaultInstruction::RemoveGuardian { threshold: 1 },
+                vec![10, 1],
+            ),
+            (VaultInstruction::InitiateRecovery, vec![11]),
+            (VaultInstruction::ApproveRecovery, vec![12]),
+            (VaultInstruction::CancelRecovery, vec![13]),
+            (VaultInstruction::CompleteRecovery, vec![14]),
+        ];
+        for (instruction, expected) in cases {
+            assert_eq!(borsh::to_vec(&instruction).unwrap(), expected);
+            assert_eq!(
+                VaultInstruction::try_from_slice(&expected).unwrap(),
+                instruction
+            );
+        }
+    }
+
+    #[test]
+    fn serialize_inheritance_instructions() {
+        let mut set_beneficiary = vec![15];
+        set_beneficiary.extend_from_slice(&1_000u64.to_le_bytes());
+        let cases = [
+            (
+                VaultInstruction::SetBeneficiary {
+                    inactivity_slots: 1_000,
+                },
+                set_beneficiary,
+            ),
+            (VaultInstruction::CheckIn, vec![16]),
+            (VaultInstruction::ClaimInheritance, vec![17]),
+        ];
+        for (instruction, expected) in cases {
+            assert_eq!(borsh::to_vec(&instruction).unwrap(), expected);
+            assert_eq!(
+                VaultInstruction::try_from_slice(&expected).unwrap(),
+                instruction
+            );
+        }
+    }
+
+    #[test]
+    fn serialize_initialize_sized() {
+        let instruction = VaultInstruction::InitializeSized { data_len: 1024 };
+        let mut expected = vec![19];
+        expected.extend_from_slice(&1024u32.to_le_bytes());
+        assert_eq!(borsh::to_vec(&instruction).unwrap(), expected);
+        assert_eq!(
+            VaultInstruction::try_from_slice(&expected).unwrap(),
+            instruction
+        );
+    }
+
+    #[test]
+    fn serialize_grow_account() {
+        let instruction = VaultInstruction::GrowAccount { target_len: 40_960 };
+        let mut expected = vec![20];
+        expected.extend_from_slice(&40_960u32.to_le_bytes());
+        assert_eq!(borsh::to_vec(&instruction).unwrap(), expected);
+        assert_eq!(
+            VaultInstruction::try_from_slice(&expected).unwrap(),
+            instruction
+        );
+    }
+
+    #[test]
+    fn serialize_close_account_with_refund() {
+        let instruction = VaultInstruction::CloseAccountWithRefund {
+            refund_authority: true,
+        };
+        let expected = vec![21, 1];
+        assert_eq!(borsh::to_vec(&instruction).unwrap(), expected);
+        assert_eq!(
+            VaultInstruction::try_from_slice(&expected).unwrap(),
+            instruction
+        );
+    }
+
+    #[test]
+    fn serialize_record_attestation() {
+        let instruction = VaultInstruction::RecordAttestation { vaa_hash: [7; 32] };
+        let mut expected = vec![22];
+        expected.extend_from_slice(&[7; 32]);
+        assert_eq!(borsh::to_vec(&instruction).unwrap(), expected);
+        assert_eq!(
+            VaultInstruction::try_from_slice(&expected).unwrap(),
+            instruction
+        );
+    }
+
+    #[test]
+    fn serialize_swap_authorities() {
+        let instruction = VaultInstruction::SwapAuthorities;
+        let expected = vec![23];
+        assert_eq!(borsh::to_vec(&instruction).unwrap(), expected);
+        assert_eq!(
+            VaultInstruction::try_from_slice(&expected).unwrap(),
+            instruction
+        );
+    }
+
+    #[test]
+    fn serialize_escrowed_transfer() {
+        let instruction = VaultInstruction::ProposeTransfer { timeout_slots: 300 };
+        let mut expected = vec![24];
+        expected.extend_from_slice(&300u64.to_le_bytes());
+        assert_eq!(borsh::to_vec(&instruction).unwrap(), expected);
+        assert_eq!(
+            VaultInstruction::try_from_slice(&expected).unwrap(),
+            instruction
+        );
+        assert_eq!(
+            borsh::to_vec(&VaultInstruction::AcceptTransfer).unwrap(),
+            vec![25]
+        );
+        assert_eq!(
+            borsh::to_vec(&VaultInstruction::ExpireTransfer).unwrap(),
+            vec![26]
+        );
+    }
+
+    #[test]
+    fn serialize_set_oracle() {
+        let instruction = VaultInstruction::SetOracle {
+            max_staleness_slots: 25,
+            min_price: -1,
+        };
+        let mut expected = vec![27];
+        expected.extend_from_slice(&25u64.to_le_bytes());
+        expected.extend_from_slice(&(-1i64).to_le_bytes());
+        assert_eq!(borsh::to_vec(&instruction).unwrap(), expected);
+        assert_eq!(
+            VaultInstruction::try_from_slice(&expected).unwrap(),
+            instruction
+        );
+    }
+
+    #[test]
+    fn serialize_mint_receipt() {
+        let instruction = VaultInstruction::MintReceipt;
+        let expected = vec![28];
+        assert_eq!(borsh::to_vec(&instruction).unwrap(), expected);
+        assert_eq!(
+            VaultInstruction::try_from_slice(&expected).unwrap(),
+            instruction
+        );
+    }
+
+    #[test]
+    fn serialize_set_receipt_authority() {
+        let instruction = VaultInstruction::SetReceiptAuthority { enabled: true };
+        let expected = vec![29, 1];
+        assert_eq!(borsh::to_vec(&instruction).unwrap(), expected);
+        assert_eq!(
+            VaultInstruction::try_from_slice(&expected).unwrap(),
+            instruction
+        );
+    }
+
+    #[test]
+    fn serialize_initialize_transfer_hook() {
+        let instruction = VaultInstruction::InitializeTransferHook;
+        let expected = vec![30];
+        assert_eq!(borsh::to_vec(&instruction).unwrap(), expected);
+        assert_eq!(
+            VaultInstruction::try_from_slice(&expected).unwrap(),
+            instruction
+        );
+    }
+
+    #[test]
+    fn serialize_set_allowlist_entry() {
+        let holder = Pubkey::new_from_array([8; 32]);
+        let instruction = VaultInstruction::SetAllowlistEntry { slot: 3, holder };
+        let mut expected = vec![31, 3];
+        expected.extend_from_slice(&holder.to_bytes());
+        assert_eq!(borsh::to_vec(&instruction).unwrap(), expected);
+        assert_eq!(
+            VaultInstruction::try_from_slice(&expected).unwrap(),
+            instruction
+        );
+    }
+
+    #[test]
+    fn serialize_rekey() {
+        let instruction = VaultInstruction::Rekey;
+        let expected = vec![32];
+        assert_eq!(borsh::to_vec(&instruction).unwrap(), expected);
+        assert_eq!(
+            VaultInstruction::try_from_slice(&expected).unwrap(),
+            instruction
+        );
+    }
+
+    // One instruction per tag, in tag order.
+    fn every_variant() -> Vec<VaultInstruction> {
+        let key = Pubkey::new_from_array([5; 32]);
+        vec![
+            VaultInstruction::Initialize,
+            VaultInstruction::TransferAuthority,
+            VaultInstruction::CloseAccount,
+            VaultInstruction::Reopen,
+            VaultInstruction::ProposeDart,
+            VaultInstruction::AcceptDart,
+            VaultInstruction::InitializeDartIdentity,
+            VaultInstruction::SetOperationalKey { slot: 1, key },
+            VaultInstruction::CreateSession {
+                key,
+                expires_at_slot: 9,
+                scope: 1,
+            },
+            VaultInstruction::AddGuardian { threshold: 1 },
+            VaultInstruction::RemoveGuardian { threshold: 1 },
+            VaultInstruction::InitiateRecovery,
+            VaultInstruction::ApproveRecovery,
+            VaultInstruction::CancelRecovery,
+            VaultInstruction::CompleteRecovery,
+            VaultInstruction::SetBeneficiary {
+                inactivity_slots: 9,
+            },
+            VaultInstruction::CheckIn,
+            VaultInstruction::ClaimInheritance,
+            VaultInstruction::SetTag { tag: 2 },
+            VaultInstruction::InitializeSized { data_len: 512 },
+            VaultInstruction::GrowAccount { target_len: 1024 },
+            VaultInstruction::CloseAccountWithRefund {
+                refund_authority: true,
+            },
+            VaultInstruction::RecordAttestation { vaa_hash: [3; 32] },
+            VaultInstruction::SwapAuthorities,
+            VaultInstruction::ProposeTransfer { timeout_slots: 9 },
+            VaultInstruction::AcceptTransfer,
+            VaultInstruction::ExpireTransfer,
+            VaultInstruction::SetOracle {
+                max_staleness_slots: 9,
+                min_price: -1,
+            },
+            VaultInstruction::MintReceipt,
+            VaultInstruction::SetReceiptAuthority { enabled: true },
+            VaultInstruction::InitializeTransferHook,
+            VaultInstruction::SetAllowlistEntry {
+                slot: 1,
+                holder: key,
+            },
+            VaultInstruction::Rekey,
+            VaultInstruction::TransferAuthorityChecked {
+                expected_authority: key,
+            },
+            VaultInstruction::CloseAccountChecked {
+                expected_authority: key,
+                refund_authority: false,
+            },
+            VaultInstruction::SetDartQuorum { quorum: 2 },
+            VaultInstruction::InitializeConfig,
+            VaultInstruction::Decommission { successor: key },
+            VaultInstruction::MigrateToProgram,
+            VaultInstruction::SetApprovalRoot {
+                root: [7; 32],
+                expires_at_slot: 9,
+            },
+            VaultInstruction::TransferWithProof {
+                proof: vec![[7; 32]; 2],
+            },
+            VaultInstruction::SetNoteKey { note_key: [7; 32] },
+            VaultInstruction::SetNote {
+                encrypted_note: [7; ENCRYPTED_NOTE_LEN],
+            },
+            VaultInstruction::UpdateCommitments {
+                commitments: [[7; 32]; MAX_COMMITMENTS],
+            },
+            VaultInstruction::AddNote {
+                hash: [7; 32],
+                category: 7,
+            },
+            VaultInstruction::AddAttestation {
+                kind: 7,
+                hash: [7; 32],
+                expires_at_slot: 7,
+            },
+            VaultInstruction::RemoveAttestation {
+                kind: 7,
+                hash: [7; 32],
+            },
+            VaultInstruction::PledgeAsCollateral {
+                lender: Pubkey::new_from_array([7; 32]),
+            },
+            VaultInstruction::ReleaseCollateral,
+            VaultInstruction::SetPosition {
+                asset_id: [7; 32],
+                balance: 7,
+            },
+            VaultInstruction::ApplyCorporateAction {
+                action_type: CorporateActionType::SymbolChange { asset_id: [7; 32] },
+                ratio: 7,
+            },
+            VaultInstruction::Distribute {
+                id: 7,
+                asset_id: [7; 32],
+                amount_per_unit: 7,
+            },
+            VaultInstruction::Snapshot {
+                record_date_slot: 7,
+            },
+            VaultInstruction::DelegateVoting {
+                proxy: Pubkey::new_from_array([7; 32]),
+            },
+            VaultInstruction::SetTransferWindow {
+                lockup_until_slot: 7,
+                blackouts: [BlackoutRange {
+                    start_slot: 7,
+                    end_slot: 8,
+                }; MAX_BLACKOUTS],
+            },
+            VaultInstruction::SetDartTransferWindow {
+                lockup_until_slot: 7,
+                blackouts: [BlackoutRange::default(); MAX_BLACKOUTS],
+            },
+            VaultInstruction::SetRateLimit {
+                max_mutations_per_epoch: 7,
+            },
+            VaultInstruction::SetDartPolicy {
+                defaults: PolicyDefaults {
+                    tag: 7,
+                    oracle: Pubkey::new_from_array([7; 32]),
+                    oracle_max_staleness_slots: 7,
+                    oracle_min_price: 7,
+                    close_recipient: Pubkey::new_from_array([7; 32]),
+                    cosign: true,
+                    close_fee: 7,
+                    restrictions: 7,
+                },
+            },
+            VaultInstruction::CloneVault,
+            VaultInstruction::CreateAssetVault { asset_id: [7; 32] },
+            VaultInstruction::SyncVaultIndex,
+            VaultInstruction::SetCrankRules {
+                bounty_lamports: 7,
+                grace_slots: 7,
+            },
+            VaultInstruction::Crank,
+            VaultInstruction::SetFeeExemption {
+                slot: 7,
+                key: Pubkey::new_from_array([7; 32]),
+            },
+            VaultInstruction::SetFeeDestination {
+                destination: Pubkey::new_from_array([7; 32]),
+            },
+            VaultInstruction::CollectFees,
+            VaultInstruction::CreateAuditTrail { capacity: 7 },
+            VaultInstruction::InitializeStats,
+            VaultInstruction::SetCloseFees {
+                default_lamports: 7,
+                tiers: [CloseFeeTier {
+                    active: true,
+                    tag: 7,
+                    lamports: 7,
+                    min_age_slots: 7,
+                }; MAX_CLOSE_FEE_TIERS],
+            },
+            VaultInstruction::SetNotify {
+                notify: Pubkey::new_from_array([7; 32]),
+            },
+            VaultInstruction::CheckTransfer,
+        ]
+    }
+
+    #[test]
+    fn unpack_rejects_malformed_data() {
+        let variants = every_variant();
+        assert!(variants.len() <= crate::state::MAX_STATS_TAGS);
+        for (tag, instruction) in variants.iter().enumerate() {
+            let data = borsh::to_vec(instruction).unwrap();
+            assert_eq!(data[0] as usize, tag);
+            assert_eq!(instruction.tag() as usize, tag);
+            assert_eq!(VaultInstruction::unpack(&data).as_ref(), Ok(instruction));
+
+            for len in 0..data.len() {
+                assert_eq!(
+                    VaultInstruction::unpack(&data[..len]),
+                    Err(ProgramError::InvalidInstructionData),
+                    "{instruction:?} truncated to {len} bytes"
+                );
+            }
+            let mut trailing = data.clone();
+            trailing.push(0);
+            assert_eq!(
+                VaultInstruction::unpack(&trailing),
+                Err(ProgramError::InvalidInstructionData),
+                "{instruction:?} with a trailing byte"
+            );
+        }
+
+        // Unknown tags and out-of-range bools.
+        assert_eq!(
+            VaultInstruction::unpack(&[variants.len() as u8]),
+            Err(ProgramError::InvalidInstructionData)
+        );
+        assert_eq!(
+            VaultInstruction::unpack(&[29, 2]),
+            Err(ProgramError::InvalidInstructionData)
+        );
+    }
+
+    #[test]
+    fn unpack_versioned_data() {
+        for instruction in every_variant() {
+            let data = instruction.pack();
+            assert_eq!(data[..2], [FORMAT_PREFIX, FORMAT_VERSION]);
+            assert_eq!(data[2..], borsh::to_vec(&instruction).unwrap());
+            assert_eq!(VaultInstruction::unpack(&data), Ok(instruction));
+        }
+
+        let mut data = VaultInstruction::Initialize.pack();
+        data.push(0);
+        assert_eq!(
+            VaultInstruction::unpack(&data),
+            Err(ProgramError::InvalidInstructionData)
+        );
+        for data in [
+            vec![FORMAT_PREFIX],
+            vec![FORMAT_PREFIX, FORMAT_VERSION],
+            vec![FORMAT_PREFIX, 0, 0],
+            vec![FORMAT_PREFIX, SLOT_BOUNDED_FORMAT_VERSION, 0],
+            vec![FORMAT_PREFIX, SLOT_BOUNDED_FORMAT_VERSION + 1, 0],
+        ] {
+            assert_eq!(
+                VaultInstruction::unpack(&data),
+                Err(ProgramError::InvalidInstructionData),
+                "{data:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn account_indexes_match_builders() {
+        let [pda, dart, authority, other] = [1, 2, 3, 4].map(|n| Pubkey::new_from_array([n; 32]));
+        let key = |instruction: &Instruction, index: usize| instruction.accounts[index].pubkey;
+
+        let instruction = initialize(crate::id(), &pda, &dart, &authority);
+        assert_eq!(key(&instruction, INITIALIZE_PDA_INDEX), pda);
+        assert_eq!(key(&instruction, INITIALIZE_DART_INDEX), dart);
+        assert_eq!(key(&instruction, INITIALIZE_AUTHORITY_INDEX), authority);
+
+        let instruction = transfer_authority(crate::id(), &pda, &dart, &authority, &other);
+        assert_eq!(key(&instruction, TRANSFER_PDA_INDEX), pda);
+        assert_eq!(key(&instruction, TRANSFER_DART_INDEX), dart);
+        assert_eq!(key(&instruction, TRANSFER_AUTHORITY_INDEX), authority);
+        assert_eq!(key(&instruction, TRANSFER_NEW_AUTHORITY_INDEX), other);
+
+        let instruction = close_account_checked(crate::id(), &pda, &dart, &authority, false);
+        assert_eq!(key(&instruction, CLOSE_PDA_INDEX), pda);
+        assert_eq!(key(&instruction, CLOSE_DART_INDEX), dart);
+        assert_eq!(key(&instruction, CLOSE_AUTHORITY_INDEX), authority);
+
+        let instruction = swap_authorities(crate::id(), &pda, &other, &dart, &authority, &other);
+        assert_eq!(key(&instruction, SWAP_PDA_A_INDEX), pda);
+        assert_eq!(key(&instruction, SWAP_PDA_B_INDEX), other);
+        assert_eq!(key(&instruction, SWAP_DART_INDEX), dart);
+        assert_eq!(key(&instruction, SWAP_AUTHORITY_A_INDEX), authority);
+
+        let instruction = rekey(crate::id(), &pda, &other, &dart, &authority);
+        assert_eq!(key(&instruction, REKEY_PDA_INDEX), pda);
+        assert_eq!(key(&instruction, REKEY_NEW_PDA_INDEX), other);
+        assert_eq!(key(&instruction, REKEY_DART_INDEX), dart);
+        assert_eq!(key(&instruction, REKEY_AUTHORITY_INDEX), authority);
+
+        let instruction = set_dart_quorum(crate::id(), &pda, &authority, 1);
+        assert_eq!(key(&instruction, DART_IDENTITY_IDENTITY_INDEX), pda);
+        assert_eq!(key(&instruction, DART_IDENTITY_MASTER_INDEX), authority);
+
+        let instruction = initialize_config(crate::id(), &authority, &other);
+        assert_eq!(
+            key(&instruction, INITIALIZE_CONFIG_CONFIG_INDEX),
+            ProgramConfig::find_address(&crate::id()).0
+        );
+        assert_eq!(key(&instruction, INITIALIZE_CONFIG_ADMIN_INDEX), authority);
+        assert_eq!(key(&instruction, INITIALIZE_CONFIG_PAYER_INDEX), other);
+        assert_eq!(
+            key(&instruction, INITIALIZE_CONFIG_PROGRAM_DATA_INDEX),
+            program_data_address(&crate::id())
+        );
+
+        let instruction = decommission(crate::id(), &authority, other);
+        assert_eq!(
+            key(&instruction, DECOMMISSION_CONFIG_INDEX),
+            ProgramConfig::find_address(&crate::id()).0
+        );
+        assert_eq!(key(&instruction, DECOMMISSION_ADMIN_INDEX), authority);
+        // An instruction already taking the config is not passed it twice.
+        assert_eq!(with_program_config(instruction.clone()), instruction);
+
+        let instruction = migrate_to_program(crate::id(), &pda, &other, &dart, &authority, &other);
+        assert_eq!(key(&instruction, MIGRATE_TO_PROGRAM_PDA_INDEX), pda);
+        assert_eq!(key(&instruction, MIGRATE_TO_PROGRAM_NEW_PDA_INDEX), other);
+        assert_eq!(key(&instruction, MIGRATE_TO_PROGRAM_DART_INDEX), dart);
+        assert_eq!(
+            key(&instruction, MIGRATE_TO_PROGRAM_AUTHORITY_INDEX),
+            authority
+        );
+        assert_eq!(
+            key(&instruction, MIGRATE_TO_PROGRAM_CONFIG_INDEX),
+            ProgramConfig::find_address(&crate::id()).0
+        );
+        assert_eq!(key(&instruction, MIGRATE_TO_PROGRAM_SUCCESSOR_INDEX), other);
+
+        let approval = ApprovalRoot::find_address(&crate::id(), &dart).0;
+        let instruction = set_approval_root(crate::id(), &dart, &other, [7; 32], 9);
+        assert_eq!(
+            key(&instruction, SET_APPROVAL_ROOT_APPROVAL_INDEX),
+            approval
+        );
+        assert_eq!(key(&instruction, SET_APPROVAL_ROOT_DART_INDEX), dart);
+        assert_eq!(key(&instruction, SET_APPROVAL_ROOT_PAYER_INDEX), other);
+
+        let instruction = transfer_with_proof(crate::id(), &pda, &dart, &authority, &other, vec![]);
+        assert_eq!(key(&instruction, TRANSFER_WITH_PROOF_PDA_INDEX), pda);
+        assert_eq!(
+            key(&instruction, TRANSFER_WITH_PROOF_APPROVAL_INDEX),
+            approval
+        );
+        assert_eq!(
+            key(&instruction, TRANSFER_WITH_PROOF_AUTHORITY_INDEX),
+            authority
+        );
+        assert_eq!(
+            key(&instruction, TRANSFER_WITH_PROOF_NEW_AUTHORITY_INDEX),
+            other
+        );
+
+        let note = NoteRecord::find_address(&crate::id(), &pda).0;
+        let instruction = set_note_key(crate::id(), &pda, &authority, &other, [7; 32]);
+        assert_eq!(key(&instruction, SET_NOTE_KEY_PDA_INDEX), pda);
+        assert_eq!(key(&instruction, SET_NOTE_KEY_AUTHORITY_INDEX), authority);
+        assert_eq!(key(&instruction, SET_NOTE_KEY_NOTE_INDEX), note);
+        assert_eq!(key(&instruction, SET_NOTE_KEY_PAYER_INDEX), other);
+
+        let instruction = set_note(crate::id(), &pda, &dart, [7; ENCRYPTED_NOTE_LEN]);
+        assert_eq!(key(&instruction, SET_NOTE_PDA_INDEX), pda);
+        assert_eq!(key(&instruction, SET_NOTE_DART_INDEX), dart);
+        assert_eq!(key(&instruction, SET_NOTE_NOTE_INDEX), note);
+
+        let instruction =
+            update_commitments(crate::id(), &pda, &dart, &other, [[7; 32]; MAX_COMMITMENTS]);
+        assert_eq!(key(&instruction, UPDATE_COMMITMENTS_PDA_INDEX), pda);
+        assert_eq!(key(&instruction, UPDATE_COMMITMENTS_DART_INDEX), dart);
+        assert_eq!(
+            key(&instruction, UPDATE_COMMITMENTS_COMMITMENTS_INDEX),
+            CommitmentRecord::find_address(&crate::id(), &pda).0
+        );
+        assert_eq!(key(&instruction, UPDATE_COMMITMENTS_PAYER_INDEX), other);
+
+        let instruction = add_note(crate::id(), &pda, &dart, &other, [7; 32], 7);
+        assert_eq!(key(&instruction, ADD_NOTE_PDA_INDEX), pda);
+        assert_eq!(key(&instruction, ADD_NOTE_DART_INDEX), dart);
+        assert_eq!(
+            key(&instruction, ADD_NOTE_LOG_INDEX),
+            NotesLog::find_address(&crate::id(), &pda).0
+        );
+        assert_eq!(key(&instruction, ADD_NOTE_PAYER_INDEX), other);
+
+        let list = AttestationList::find_address(&crate::id(), &pda).0;
+        let instruction = add_attestation(crate::id(), &pda, &dart, &other, 7, [7; 32], 7);
+        assert_eq!(key(&instruction, ADD_ATTESTATION_PDA_INDEX), pda);
+        assert_eq!(key(&instruction, ADD_ATTESTATION_DART_INDEX), dart);
+        assert_eq!(key(&instruction, ADD_ATTESTATION_LIST_INDEX), list);
+        assert_eq!(key(&instruction, ADD_ATTESTATION_PAYER_INDEX), other);
+
+        let instruction = remove_attestation(crate::id(), &pda, &dart, 7, [7; 32]);
+        assert_eq!(key(&instruction, REMOVE_ATTESTATION_PDA_INDEX), pda);
+        assert_eq!(key(&instruction, REMOVE_ATTESTATION_DART_INDEX), dart);
+        assert_eq!(key(&instruction, REMOVE_ATTESTATION_LIST_INDEX), list);
+
+        let pledge = PledgeRecord::find_address(&crate::id(), &pda).0;
+        let instruction = pledge_as_collateral(crate::id(), &pda, &authority, &other, dart);
+        assert_eq!(key(&instruction, PLEDGE_PDA_INDEX), pda);
+        assert_eq!(key(&instruction, PLEDGE_AUTHORITY_INDEX), authority);
+        assert_eq!(key(&instruction, PLEDGE_PLEDGE_INDEX), pledge);
+        assert_eq!(key(&instruction, PLEDGE_PAYER_INDEX), other);
+
+        let instruction = release_collateral(crate::id(), &pda, &other);
+        assert_eq!(key(&instruction, RELEASE_COLLATERAL_PDA_INDEX), pda);
+        assert_eq!(key(&instruction, RELEASE_COLLATERAL_LENDER_INDEX), other);
+        assert_eq!(key(&instruction, RELEASE_COLLATERAL_PLEDGE_INDEX), pledge);
+
+        let position = PositionRecord::find_address(&crate::id(), &pda).0;
+        let instruction = set_position(crate::id(), &pda, &dart, &other, [7; 32], 7);
+        assert_eq!(key(&instruction, SET_POSITION_PDA_INDEX), pda);
+        assert_eq!(key(&instruction, SET_POSITION_DART_INDEX), dart);
+        assert_eq!(key(&instruction, SET_POSITION_POSITION_INDEX), position);
+        assert_eq!(key(&instruction, SET_POSITION_PAYER_INDEX), other);
+
+        let instruction =
+            apply_corporate_action(crate::id(), &dart, &[pda], CorporateActionType::Split, 2);
+        assert_eq!(key(&instruction, APPLY_CORPORATE_ACTION_DART_INDEX), dart);
+        assert_eq!(
+            key(&instruction, APPLY_CORPORATE_ACTION_FIRST_PDA_INDEX),
+            pda
+        );
+        assert_eq!(
+            key(&instruction, APPLY_CORPORATE_ACTION_FIRST_PDA_INDEX + 1),
+            position
+        );
+
+        let cursor = DistributionCursor::find_address(&crate::id(), &dart, 7).0;
+        let instruction = distribute(
+            crate::id(),
+            &dart,
+            &other,
+            7,
+            [7; 32],
+            7,
+            &[(pda, authority)],
+        );
+        assert_eq!(key(&instruction, DISTRIBUTE_DART_INDEX), dart);
+        assert_eq!(key(&instruction, DISTRIBUTE_CURSOR_INDEX), cursor);
+        assert_eq!(key(&instruction, DISTRIBUTE_SOURCE_INDEX), other);
+        assert_eq!(
+            key(&instruction, DISTRIBUTE_TOKEN_PROGRAM_INDEX),
+            spl_token::id()
+        );
+        assert_eq!(
+            key(&instruction, DISTRIBUTE_SYSTEM_PROGRAM_INDEX),
+            system_program::id()
+        );
+        assert_eq!(key(&instruction, DISTRIBUTE_FIRST_PDA_INDEX), pda);
+        assert_eq!(key(&instruction, DISTRIBUTE_FIRST_PDA_INDEX + 1), position);
+        assert_eq!(key(&instruction, DISTRIBUTE_FIRST_PDA_INDEX + 2), authority);
+
+        let snapshot_address = SnapshotRecord::find_address(&crate::id(), &pda, 7).0;
+        let instruction = snapshot(crate::id(), &pda, &dart, &other, 7);
+        assert_eq!(key(&instruction, SNAPSHOT_PDA_INDEX), pda);
+        assert_eq!(key(&instruction, SNAPSHOT_DART_INDEX), dart);
+        assert_eq!(key(&instruction, SNAPSHOT_POSITION_INDEX), position);
+        assert_eq!(key(&instruction, SNAPSHOT_SNAPSHOT_INDEX), snapshot_address);
+        assert_eq!(key(&instruction, SNAPSHOT_PAYER_INDEX), other);
+
+        let voting = VotingProxy::find_address(&crate::id(), &pda).0;
+        let instruction = delegate_voting(crate::id(), &pda, &authority, &other, dart);
+        assert_eq!(key(&instruction, DELEGATE_VOTING_PDA_INDEX), pda);
+        assert_eq!(
+            key(&instruction, DELEGATE_VOTING_AUTHORITY_INDEX),
+            authority
+        );
+        assert_eq!(key(&instruction, DELEGATE_VOTING_PROXY_INDEX), voting);
+        assert_eq!(key(&instruction, DELEGATE_VOTING_PAYER_INDEX), other);
+
+        let blackouts = [BlackoutRange::default(); MAX_BLACKOUTS];
+        let window = TransferWindow::find_address(&crate::id(), TRANSFER_WINDOW_VAULT, &pda).0;
+        let instruction = set_transfer_window(crate::id(), &pda, &dart, &other, 7, blackouts);
+        assert_eq!(key(&instruction, SET_TRANSFER_WINDOW_PDA_INDEX), pda);
+        assert_eq!(key(&instruction, SET_TRANSFER_WINDOW_DART_INDEX), dart);
+        assert_eq!(key(&instruction, SET_TRANSFER_WINDOW_WINDOW_INDEX), window);
+        assert_eq!(key(&instruction, SET_TRANSFER_WINDOW_PAYER_INDEX), other);
+
+        let window = TransferWindow::find_address(&crate::id(), TRANSFER_WINDOW_DART, &dart).0;
+        let instruction = set_dart_transfer_window(crate::id(), &dart, &other, 7, blackouts);
+        assert_eq!(
+            key(&instruction, SET_DART_TRANSFER_WINDOW_WINDOW_INDEX),
+            window
+        );
+        assert_eq!(key(&instruction, SET_DART_TRANSFER_WINDOW_DART_INDEX), dart);
+        assert_eq!(
+            key(&instruction, SET_DART_TRANSFER_WINDOW_PAYER_INDEX),
+            other
+        );
+
+        let rate_limit = RateLimit::find_address(&crate::id(), &pda).0;
+        let instruction = set_rate_limit(crate::id(), &pda, &dart, &other, 7);
+        assert_eq!(key(&instruction, SET_RATE_LIMIT_PDA_INDEX), pda);
+        assert_eq!(key(&instruction, SET_RATE_LIMIT_DART_INDEX), dart);
+        assert_eq!(
+            key(&instruction, SET_RATE_LIMIT_RATE_LIMIT_INDEX),
+            rate_limit
+        );
+        assert_eq!(key(&instruction, SET_RATE_LIMIT_PAYER_INDEX), other);
+
+        let policy = DartPolicy::find_address(&crate::id(), &dart).0;
+        let instruction = set_dart_policy(crate::id(), &dart, &other, PolicyDefaults::default());
+        assert_eq!(key(&instruction, SET_DART_POLICY_POLICY_INDEX), policy);
+        assert_eq!(key(&instruction, SET_DART_POLICY_DART_INDEX), dart);
+        assert_eq!(key(&instruction, SET_DART_POLICY_PAYER_INDEX), other);
+
+        let instruction = clone_vault(crate::id(), &pda, &other, &dart, &authority);
+        assert_eq!(key(&instruction, CLONE_VAULT_PDA_INDEX), pda);
+        assert_eq!(key(&instruction, CLONE_VAULT_NEW_PDA_INDEX), other);
+        assert_eq!(key(&instruction, CLONE_VAULT_DART_INDEX), dart);
+        assert_eq!(key(&instruction, CLONE_VAULT_AUTHORITY_INDEX), authority);
+
+        let asset_vault = find_asset_vault_address(&crate::id(), &dart, &authority, &[7; 32]).0;
+        let instruction = create_asset_vault(crate::id(), &dart, &authority, &other, [7; 32]);
+        assert_eq!(key(&instruction, CREATE_ASSET_VAULT_PDA_INDEX), asset_vault);
+        assert_eq!(key(&instruction, CREATE_ASSET_VAULT_DART_INDEX), dart);
+        assert_eq!(
+            key(&instruction, CREATE_ASSET_VAULT_AUTHORITY_INDEX),
+            authority
+        );
+        assert_eq!(key(&instruction, CREATE_ASSET_VAULT_PAYER_INDEX), other);
+
+        let index = VaultIndex::find_address(&crate::id(), &authority).0;
+        let instruction = sync_vault_index(crate::id(), &authority, &other, &[pda]);
+        assert_eq!(key(&instruction, SYNC_VAULT_INDEX_INDEX_INDEX), index);
+        assert_eq!(
+            key(&instruction, SYNC_VAULT_INDEX_AUTHORITY_INDEX),
+            authority
+        );
+        assert_eq!(key(&instruction, SYNC_VAULT_INDEX_PAYER_INDEX), other);
+
+        let config = ProgramConfig::find_address(&crate::id()).0;
+        let instruction = set_crank_rules(crate::id(), &authority, 7, 7);
+        assert_eq!(key(&instruction, SET_CRANK_RULES_CONFIG_INDEX), config);
+        assert_eq!(key(&instruction, SET_CRANK_RULES_ADMIN_INDEX), authority);
+
+        let instruction = crank(crate::id(), &other, &[(pda, authority)]);
+        assert_eq!(key(&instruction, CRANK_CONFIG_INDEX), config);
+        assert_eq!(key(&instruction, CRANK_CRANKER_INDEX), other);
+
+        let instruction = set_fee_exemption(crate::id(), &authority, &other, 7, dart);
+        assert_eq!(
+            key(&instruction, SET_FEE_EXEMPTION_FEE_CONFIG_INDEX),
+            FeeConfig::find_address(&crate::id()).0
+        );
+        assert_eq!(key(&instruction, SET_FEE_EXEMPTION_CONFIG_INDEX), config);
+        assert_eq!(key(&instruction, SET_FEE_EXEMPTION_ADMIN_INDEX), authority);
+        assert_eq!(key(&instruction, SET_FEE_EXEMPTION_PAYER_INDEX), other);
+
+        let instruction = set_fee_destination(crate::id(), &authority, &other, dart);
+        assert_eq!(
+            key(&instruction, SET_FEE_DESTINATION_FEE_CONFIG_INDEX),
+            FeeConfig::find_address(&crate::id()).0
+        );
+        assert_eq!(key(&instruction, SET_FEE_DESTINATION_CONFIG_INDEX), config);
+        assert_eq!(
+            key(&instruction, SET_FEE_DESTINATION_ADMIN_INDEX),
+            authority
+        );
+        assert_eq!(key(&instruction, SET_FEE_DESTINATION_PAYER_INDEX), other);
+
+        let instruction = collect_fees(crate::id(), &authority, &other);
+        assert_eq!(
+            key(&instruction, COLLECT_FEES_FEE_CONFIG_INDEX),
+            FeeConfig::find_address(&crate::id()).0
+        );
+        assert_eq!(key(&instruction, COLLECT_FEES_CONFIG_INDEX), config);
+        assert_eq!(key(&instruction, COLLECT_FEES_ADMIN_INDEX), authority);
+        assert_eq!(key(&instruction, COLLECT_FEES_DESTINATION_INDEX), other);
+
+        let instruction = create_audit_trail(crate::id(), &pda, &dart, &other, 7);
+        assert_eq!(key(&instruction, CREATE_AUDIT_TRAIL_PDA_INDEX), pda);
+        assert_eq!(key(&instruction, CREATE_AUDIT_TRAIL_DART_INDEX), dart);
+        assert_eq!(
+            key(&instruction, CREATE_AUDIT_TRAIL_TRAIL_INDEX),
+            AuditTrail::find_address(&crate::id(), &pda).0
+        );
+        assert_eq!(key(&instruction, CREATE_AUDIT_TRAIL_PAYER_INDEX), other);
+
+        let instruction = initialize_stats(crate::id(), &authority, &other);
+        assert_eq!(
+            key(&instruction, INITIALIZE_STATS_STATS_INDEX),
+            ProgramStats::find_address(&crate::id()).0
+        );
+        assert_eq!(key(&instruction, INITIALIZE_STATS_CONFIG_INDEX), config);
+        assert_eq!(key(&instruction, INITIALIZE_STATS_ADMIN_INDEX), authority);
+        assert_eq!(key(&instruction, INITIALIZE_STATS_PAYER_INDEX), other);
+
+        let tiers = [CloseFeeTier::default(); MAX_CLOSE_FEE_TIERS];
+        let instruction = set_close_fees(crate::id(), &authority, &other, 5_000, tiers);
+        let fee_config = FeeConfig::find_address(&crate::id()).0;
+        assert_eq!(
+            key(&instruction, SET_CLOSE_FEES_FEE_CONFIG_INDEX),
+            fee_config
+        );
+        assert_eq!(key(&instruction, SET_CLOSE_FEES_CONFIG_INDEX), config);
+        assert_eq!(key(&instruction, SET_CLOSE_FEES_ADMIN_INDEX), authority);
+        assert_eq!(key(&instruction, SET_CLOSE_FEES_PAYER_INDEX), other);
+
+        let instruction = close_account(crate::id(), &pda, &dart, &authority);
+        assert!(instruction
+            .accounts
+            .iter()
+            .any(|meta| meta.pubkey == fee_config && meta.is_writable));
+        assert_eq!(with_fee_config(instruction.clone()), instruction);
+
+        let notify = Pubkey::new_from_array([9; 32]);
+        let notify_record = NotifyRecord::find_address(&crate::id(), &pda).0;
+        let notification = Notification::find_address(&crate::id(), &notify).0;
+        let instruction = set_notify(crate::id(), &pda, &authority, &other, notify);
+        assert_eq!(key(&instruction, SET_NOTIFY_PDA_INDEX), pda);
+        assert_eq!(key(&instruction, SET_NOTIFY_AUTHORITY_INDEX), authority);
+        assert_eq!(
+            key(&instruction, SET_NOTIFY_NOTIFY_RECORD_INDEX),
+            notify_record
+        );
+        assert_eq!(
+            key(&instruction, SET_NOTIFY_NOTIFICATION_INDEX),
+            notification
+        );
+        assert_eq!(key(&instruction, SET_NOTIFY_PAYER_INDEX), other);
+
+        let instruction = with_notify(
+            close_account(crate::id(), &pda, &dart, &authority),
+            &pda,
+            &notify,
+        );
+        assert_eq!(
+            instruction
+                .accounts
+                .iter()
+                .filter(|meta| **meta == AccountMeta::new_readonly(notify_record, false))
+                .count(),
+            1
+        );
+        assert_eq!(
+            instruction.accounts.last(),
+            Some(&AccountMeta::new(notification, false))
+        );
+
+        let instruction = check_transfer(crate::id(), &pda, &dart, &authority, &other);
+        assert_eq!(key(&instruction, CHECK_TRANSFER_PDA_INDEX), pda);
+        assert_eq!(key(&instruction, CHECK_TRANSFER_AUTHORITY_INDEX), authority);
+        assert_eq!(key(&instruction, CHECK_TRANSFER_NEW_AUTHORITY_INDEX), other);
+        assert!(instruction
+            .accounts
+            .iter()
+            .all(|meta| !meta.is_writable && !meta.is_signer));
+    }
+
+    #[test]
+    fn dart_quorum_accounts() {
+        let pda = Pubkey::new_from_array([1; 32]);
+        let identity = Pubkey::new_from_array([2; 32]);
+        let instruction = with_dart_quorum(reopen(crate::id(), &pda, &identity), &identity);
+        assert_eq!(
+            instruction.accounts,
+            vec![
+                AccountMeta::new(pda, false),
+                AccountMeta::new(identity, false),
+                AccountMeta::new_readonly(ProgramConfig::find_address(&crate::id()).0, false),
+                AccountMeta::new_readonly(sysvar::instructions::id(), false),
+            ]
+        );
+        assert_eq!(
+            borsh::to_vec(&VaultInstruction::SetDartQuorum { quorum: 2 }).unwrap(),
+            [35, 2]
+        );
+    }
+
+    #[test]
+    fn slot_bounded_data() {
+        let pda = Pubkey::new_from_array([1; 32]);
+        let dart = Pubkey::new_from_array([2; 32]);
+        let instruction = with_valid_until_slot(reopen(crate::id(), &pda, &dart), 300);
+
+        let mut expected = vec![FORMAT_PREFIX, SLOT_BOUNDED_FORMAT_VERSION];
+        expected.extend_from_slice(&300u64.to_le_bytes());
+        expected.push(3);
+        assert_eq!(instruction.data, expected);
+        assert_eq!(
+            unpack_envelope(&instruction.data),
+            Ok((&[3][..], Some(300)))
+        );
+        assert_eq!(
+            VaultInstruction::unpack(&instruction.data),
+            Ok(VaultInstruction::Reopen)
+        );
+
+        // Re-bounding replaces the slot rather than nesting prefixes.
+        let instruction = with_valid_until_slot(instruction, 400);
+        assert_eq!(
+            unpack_envelope(&instruction.data),
+            Ok((&[3][..], Some(400)))
+        );
+        let data = VaultInstruction::Reopen.pack();
+        assert_eq!(unpack_envelope(&data), Ok((&[3][..], None)));
+
+        assert_eq!(
+            unpack_envelope(&expected[..9]),
+            Err(ProgramError::InvalidInstructionData)
+        );
+    }
+
+    #[test]
+    fn serialize_checked_mutations() {
+        let expected_authority = Pubkey::new_from_array([7; 32]);
+        let instruction = VaultInstruction::TransferAuthorityChecked { expected_authority };
+        let mut expected = vec![33];
+        expected.extend_from_slice(expected_authority.as_ref());
+        assert_eq!(borsh::to_vec(&instruction).unwrap(), expected);
+
+        let instruction = VaultInstruction::CloseAccountChecked {
+            expected_authority,
+            refund_authority: true,
+        };
+        expected[0] = 34;
+        expected.push(1);
+        assert_eq!(borsh::to_vec(&instruction).unwrap(), expected);
+    }
+
+    #[test]
+    fn transfer_hook_execute_data() {
+        let key = Pubkey::new_unique();
+        let instruction =
+            transfer_hook_execute(Pubkey::new_unique(), &key, &key, &key, &key, &key, 7);
+        assert!(transfer_hook::is_execute(&instruction.data));
+        assert_eq!(instruction.data[8..], 7u64.to_le_bytes());
+        assert!(instruction
+            .accounts
+            .iter()
+            .all(|meta| !meta.is_signer && !meta.is_writable));
+    }
+
+    #[test]
+    fn optional_accounts_keep_positions() {
+        let program_id = Pubkey::new_unique();
+        let (pda, dart, authority, payer) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        let instruction = with_rent_payer(initialize(program_id, &pda, &dart, &authority), &payer);
+        assert_eq!(instruction.accounts[3].pubkey, program_id);
+        assert_eq!(
+            instruction.accounts[4],
+            AccountMeta::new_readonly(payer, true)
+        );
+
+        let identity = Pubkey::new_unique();
+        let instruction = with_rent_payer(
+            with_dart_identity(initialize(program_id, &pda, &dart, &authority), &identity),
+            &payer,
+        );
+        assert_eq!(instruction.accounts[3].pubkey, identity);
+        assert_eq!(instruction.accounts[4].pubkey, payer);
+
+        let recipient = Pubkey::new_unique();
+        let instruction =
+            with_close_recipient(initialize(program_id, &pda, &dart, &authority), &recipient);
+        assert_eq!(instruction.accounts[3].pubkey, program_id);
+        assert_eq!(instruction.accounts[4].pubkey, program_id);
+        assert_eq!(
+            instruction.accounts[5],
+            AccountMeta::new_readonly(recipient, false)
+        );
+
+        let instruction = with_rent_payer(instruction, &payer);
+        assert_eq!(instruction.accounts.len(), 9);
+        assert_eq!(instruction.accounts[4].pubkey, payer);
+        assert_eq!(instruction.accounts[5].pubkey, recipient);
+
+        let instruction = with_dart_policy(initialize(program_id, &pda, &dart, &authority), &dart);
+        assert_eq!(instruction.accounts.len(), 9);
+        assert_eq!(instruction.accounts[5].pubkey, program_id);
+        assert_eq!(
+            instruction.accounts[6].pubkey,
+            DartPolicy::find_address(&program_id, &dart).0
+        );
+        // The vault index and the program config stay after the optional accounts.
+        assert_eq!(
+            instruction.accounts[7].pubkey,
+            VaultIndex::find_address(&program_id, &authority).0
+        );
+        assert_eq!(
+            instruction.accounts[8].pubkey,
+            ProgramConfig::find_address(&program_id).0
+        );
+    }
+
+    #[test]
+    fn builders_pass_program_config() {
+        let (pda, dart, authority) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        let (config, _) = ProgramConfig::find_address(&crate::id());
+        let instruction = transfer_authority(crate::id(), &pda, &dart, &authority, &pda);
+        assert_eq!(
+            instruction.accounts.last(),
+            Some(&AccountMeta::new_readonly(config, false))
+        );
+        assert!(VaultInstruction::try_from_slice(&instruction.data)
+            .unwrap()
+            .requires_program_config());
+
+        // Migrating away runs after decommissioning, with the config in its own place.
+        let instruction = migrate_to_program(crate::id(), &pda, &pda, &dart, &authority, &pda);
+        assert_eq!(
+            instruction
+                .accounts
+                .iter()
+                .filter(|meta| meta.pubkey == config)
+                .count(),
+            1
+        );
+        assert!(!VaultInstruction::MigrateToProgram.requires_program_config());
+    }
+
+    #[test]
+    fn builders_pass_vault_indexes() {
+        let (pda, dart, authority, new_authority) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        let program_id = crate::id();
+        let index = vault_index(&program_id, &authority);
+        let new_index = vault_index(&program_id, &new_authority);
+        for instruction in [
+            transfer_authority(program_id, &pda, &dart, &authority, &new_authority),
+            transfer_with_proof(program_id, &pda, &dart, &authority, &new_authority, vec![]),
+            accept_transfer(program_id, &pda, &dart, &authority, &new_authority),
+            swap_authorities(program_id, &pda, &dart, &dart, &authority, &new_authority),
+            complete_recovery(program_id, &pda, &dart, &authority, &new_authority),
+            claim_inheritance(program_id, &pda, &dart, &authority, &new_authority),
+        ] {
+            assert!(instruction.accounts.contains(&index));
+            assert!(instruction.accounts.contains(&new_index));
+        }
+        for instruction in [
+            close_account(program_id, &pda, &dart, &authority),
+            rekey(program_id, &pda, &new_authority, &dart, &authority),
+            clone_vault(program_id, &pda, &new_authority, &dart, &authority),
+        ] {
+            assert!(instruction.accounts.contains(&index));
+        }
+
+        // Vault creation takes the index past its optional accounts, which fill in
+        // around it.
+        let instruction = with_rent_payer(
+            with_dart_identity(
+                initialize(program_id, &pda, &dart, &authority),
+                &new_authority,
+            ),
+            &new_authority,
+        );
+        assert_eq!(
+            instruction.accounts[INITIALIZE_IDENTITY_INDEX].pubkey,
+            new_authority
+        );
+        assert_eq!(
+            instruction.accounts[INITIALIZE_RENT_PAYER_INDEX].pubkey,
+            new_authority
+        );
+        assert_eq!(instruction.accounts[INITIALIZE_VAULT_INDEX_INDEX], index);
+        let instruction = with_dart_identity(
+            create_asset_vault(program_id, &dart, &authority, &pda, [1; 32]),
+            &new_authority,
+        );
+        assert_eq!(
+            instruction.accounts[CREATE_ASSET_VAULT_IDENTITY_INDEX].pubkey,
+            new_authority
+        );
+        assert_eq!(
+            instruction.accounts[CREATE_ASSET_VAULT_VAULT_INDEX_INDEX],
+            index
+        );
+    }
+
+    #[test]
+    fn builders_pass_dart_transfer_window() {
+        let (pda, dart, authority, identity) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        let program_id = crate::id();
+        let window = transfer_window(&program_id, TRANSFER_WINDOW_DART, &dart);
+        for instruction in [
+            transfer_authority(program_id, &pda, &dart, &authority, &pda),
+            propose_transfer(program_id, &pda, &dart, &authority, &pda, 100),
+            accept_transfer(program_id, &pda, &dart, &identity, &authority),
+            swap_authorities(program_id, &pda, &identity, &dart, &authority, &identity),
+        ] {
+            assert!(VaultInstruction::try_from_slice(&instruction.data)
+                .unwrap()
+                .changes_authority());
+            assert!(instruction.accounts.contains(&window));
+            let windowed = with_transfer_windows(instruction.clone(), &pda, &dart);
+            assert_eq!(windowed.accounts.len(), instruction.accounts.len() + 1);
+        }
+
+        let instruction = with_dart_identity(
+            transfer_authority(program_id, &pda, &identity, &authority, &pda),
+            &identity,
+        );
+        assert!(instruction.accounts.contains(&transfer_window(
+            &program_id,
+            TRANSFER_WINDOW_DART,
+            &identity
+        )));
+        let instruction = with_dart_identity(set_tag(program_id, &pda, &identity, 7), &identity);
+        assert!(!instruction.accounts.contains(&transfer_window(
+            &program_id,
+            TRANSFER_WINDOW_DART,
+            &identity
+        )));
+        assert!(!VaultInstruction::SetTag { tag: 7 }.changes_authority());
+    }
+
+    #[test]
+    fn rate_limited_mutations() {
+        let (pda, dart) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let instruction = with_rate_limit(set_tag(crate::id(), &pda, &dart, 7), &pda);
+        assert!(VaultInstruction::try_from_slice(&instruction.data)
+            .unwrap()
+            .is_rate_limited());
+        assert_eq!(
+            instruction.accounts[3],
+            AccountMeta::new(RateLimit::find_address(&crate::id(), &pda).0, false)
+        );
+
+        // Whoever signs, every instruction acting on a vault counts.
+        for instruction in [
+            VaultInstruction::AcceptTransfer,
+            VaultInstruction::ExpireTransfer,
+            VaultInstruction::SwapAuthorities,
+            VaultInstruction::Rekey,
+            VaultInstruction::Reopen,
+            VaultInstruction::MigrateToProgram,
+            VaultInstruction::CheckIn,
+        ] {
+            assert!(instruction.is_rate_limited(), "{instruction:?}");
+        }
+
+        // The limit itself can always be lifted.
+        assert!(!VaultInstruction::SetRateLimit {
+            max_mutations_per_epoch: 0
+        }
+        .is_rate_limited());
+        assert!(!VaultInstruction::Initialize.is_rate_limited());
+        assert!(!VaultInstruction::CheckTransfer.is_rate_limited());
+        assert!(!VaultInstruction::Crank.is_rate_limited());
+    }
+
+    #[test]
+    fn serialize_set_tag() {
+        let instruction = VaultInstruction::SetTag { tag: 0x0102 };
+        let expected = vec![18, 0x02, 0x01];
+        assert_eq!(borsh::to_vec(&instruction).unwrap(), expected);
+        assert_eq!(
+            VaultInstruction::try_from_slice(&expected).unwrap(),
+            instruction
+        );
+    }
+
+    #[test]
+    fn wire_compatible_with_fixtures() {
+        // Recorded with borsh 0.10 before the borsh 1.x migration.
+        let fixtures: [(&[u8], VaultInstruction); 4] = [
+            (
+                include_bytes!("../tests/fixtures/instruction_initialize.bin"),
+                VaultInstruction::Initialize,
+            ),
+            (
+                include_bytes!("../tests/fixtures/instruction_transfer_authority.bin"),
+                VaultInstruction::TransferAuthority,
+            ),
+            (
+                include_bytes!("../tests/fixtures/instruction_close_account.bin"),
+                VaultInstruction::CloseAccount,
+            ),
+            (
+                include_bytes!("../tests/fixtures/instruction_reopen.bin"),
+                VaultInstruction::Reopen,
+            ),
+        ];
+        for (fixture, instruction) in fixtures {
+            assert_eq!(borsh::to_vec(&instruction).unwrap(), fixture);
+            assert_eq!(
+                VaultInstruction::try_from_slice(fixture).unwrap(),
+                instruction
+            );
+        }
     }
 
     #[test]
     fn deserialize_invalid_instruction() {
-        let mut expected = vec![12];
-        expected.append(&mut TEST_BYTES.try_to_vec().unwrap());
+        let mut expected = vec![255];
+        expected.append(&mut borsh::to_vec(&TEST_BYTES).unwrap());
         let err: ProgramError = VaultInstruction::try_from_slice(&expected)
             .unwrap_err()
             .into();
@@ -1,7 +1,64 @@
+/// Log an informational message. The `minimal-logs` feature compiles these out,
+/// leaving only the messages that explain a failure.
+#[cfg(not(feature = "state-only"))]
+macro_rules! info {
+    ($($arg:tt)*) => {{
+        #[cfg(not(feature = "minimal-logs"))]
+        ::solana_program::msg!($($arg)*);
+    }};
+}
+
+#[cfg(all(
+    feature = "state-only",
+    any(
+        feature = "anchor",
+        feature = "client",
+        feature = "geyser",
+        feature = "test-utils"
+    )
+))]
+compile_error!(
+    "`state-only` leaves out the instructions `anchor`, `client`, `geyser` and `test-utils` build on"
+);
+
+#[cfg(feature = "anchor")]
+pub mod anchor;
+#[cfg(not(feature = "state-only"))]
+pub mod approval;
+#[cfg(feature = "client")]
+pub mod client;
+#[cfg(not(feature = "state-only"))]
 mod entrypoint;
 pub mod error;
+pub mod event;
+#[cfg(feature = "geyser")]
+pub mod geyser;
+#[cfg(not(feature = "state-only"))]
+pub mod input;
+#[cfg(not(feature = "state-only"))]
 pub mod instruction;
+pub mod math;
+#[cfg(not(feature = "state-only"))]
+pub mod oracle;
+#[cfg(not(feature = "state-only"))]
 pub mod processor;
+#[cfg(feature = "proto")]
+pub mod proto;
+#[cfg(not(feature = "state-only"))]
+pub mod quorum;
+#[cfg(not(feature = "state-only"))]
+pub mod receipt;
 pub mod state;
+#[cfg(feature = "test-utils")]
+pub mod test_utils;
+#[cfg(not(feature = "state-only"))]
+pub mod transfer_hook;
+#[cfg(not(feature = "state-only"))]
+pub mod validation;
+
+/// Heap size used when built with the `custom-heap` feature. Every transaction that
+/// invokes the program must request it with `ComputeBudgetInstruction::request_heap_frame`.
+#[cfg(feature = "custom-heap")]
+pub const HEAP_LENGTH: usize = 256 * 1024;
 
 solana_program::declare_id!("DARTSo1anaVau1t1111111111111111111111111111");
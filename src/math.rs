@@ -0,0 +1,58 @@
+//! Checked arithmetic for lamport and balance updates.
+use {crate::error::VaultError, solana_program::program_error::ProgramError};
+
+/// `a + b`, or `VaultError::Overflow`.
+pub fn checked_add(a: u64, b: u64) -> Result<u64, ProgramError> {
+    a.checked_add(b).ok_or_else(|| VaultError::Overflow.into())
+}
+
+/// `a - b`, or `VaultError::Overflow` on underflow.
+pub fn checked_sub(a: u64, b: u64) -> Result<u64, ProgramError> {
+    a.checked_sub(b).ok_or_else(|| VaultError::Overflow.into())
+}
+
+/// `a * b`, or `VaultError::Overflow`.
+pub fn checked_mul(a: u64, b: u64) -> Result<u64, ProgramError> {
+    a.checked_mul(b).ok_or_else(|| VaultError::Overflow.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checked_ops() {
+        let overflow = Err(VaultError::Overflow.into());
+        assert_eq!(checked_add(1, 2), Ok(3));
+        assert_eq!(checked_add(u64::MAX, 1), overflow);
+        assert_eq!(checked_sub(3, 2), Ok(1));
+        assert_eq!(checked_sub(0, 1), overflow);
+        assert_eq!(checked_mul(3, 2), Ok(6));
+        assert_eq!(checked_mul(u64::MAX, 2), overflow);
+    }
+}
+
+#[cfg(kani)]
+mod verification {
+    use super::*;
+
+    #[kani::proof]
+    fn checked_ops_are_exact() {
+        let (a, b): (u64, u64) = (kani::any(), kani::any());
+        let wide = |result: Result<u64, ProgramError>| result.ok().map(u128::from);
+        let (a128, b128) = (u128::from(a), u128::from(b));
+
+        let sum = a128 + b128;
+        assert_eq!(
+            wide(checked_add(a, b)),
+            (sum <= u64::MAX.into()).then_some(sum)
+        );
+        let difference = a128.checked_sub(b128);
+        assert_eq!(wide(checked_sub(a, b)), difference);
+        let product = a128 * b128;
+        assert_eq!(
+            wide(checked_mul(a, b)),
+            (product <= u64::MAX.into()).then_some(product)
+        );
+    }
+}
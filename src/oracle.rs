@@ -0,0 +1,124 @@
+//! Price and market-status feeds gating authority transfers.
+use {
+    crate::{error::VaultError, math, state::VaultRecord},
+    solana_program::{
+        account_info::AccountInfo, clock::Clock, msg, program_error::ProgramError, pubkey::Pubkey,
+        sysvar::Sysvar,
+    },
+};
+
+/// Pyth price account magic number.
+const PYTH_MAGIC: u32 = 0xa1b2_c3d4;
+/// Pyth price account type.
+const PYTH_PRICE_ACCOUNT: u32 = 3;
+/// Offset of the aggregate price.
+const PYTH_AGG_PRICE_OFFSET: usize = 208;
+/// Offset of the aggregate status.
+const PYTH_AGG_STATUS_OFFSET: usize = 224;
+/// Offset of the slot the aggregate was published in.
+const PYTH_AGG_PUB_SLOT_OFFSET: usize = 232;
+/// Pyth price account length up to the end of the aggregate.
+const PYTH_MIN_LEN: usize = 240;
+/// Aggregate status of a market that is open.
+const PYTH_STATUS_TRADING: u32 = 1;
+
+/// Latest aggregate read from a feed account.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FeedReading {
+    /// Aggregate price, in the feed's exponent
+    pub price: i64,
+    /// Whether the market is open
+    pub trading: bool,
+    /// Slot the aggregate was published in
+    pub pub_slot: u64,
+}
+
+/// Read the aggregate of a Pyth price account.
+pub fn read_feed(data: &[u8]) -> Result<FeedReading, ProgramError> {
+    if data.len() < PYTH_MIN_LEN
+        || read_u32(data, 0) != PYTH_MAGIC
+        || read_u32(data, 8) != PYTH_PRICE_ACCOUNT
+    {
+        msg!("Oracle account is not a price feed");
+        return Err(VaultError::OracleUnavailable.into());
+    }
+    Ok(FeedReading {
+        price: read_u64(data, PYTH_AGG_PRICE_OFFSET) as i64,
+        trading: read_u32(data, PYTH_AGG_STATUS_OFFSET) == PYTH_STATUS_TRADING,
+        pub_slot: read_u64(data, PYTH_AGG_PUB_SLOT_OFFSET),
+    })
+}
+
+/// Check the record's oracle, if it has one, among `accounts`: the market must be
+/// open, the price fresh and at least the record's floor.
+pub fn validate_oracle(record: &VaultRecord, accounts: &[AccountInfo]) -> Result<(), ProgramError> {
+    if record.oracle == Pubkey::default() {
+        return Ok(());
+    }
+    let feed = accounts
+        .iter()
+        .find(|account| *account.key == record.oracle)
+        .ok_or_else(|| {
+            msg!("Missing oracle account {}", record.oracle);
+            ProgramError::from(VaultError::OracleUnavailable)
+        })?;
+    let reading = read_feed(&feed.data.borrow())?;
+    if Clock::get()?.slot > math::checked_add(reading.pub_slot, record.oracle_max_staleness_slots)?
+    {
+        msg!("Oracle last published at slot {}", reading.pub_slot);
+        return Err(VaultError::OracleStale.into());
+    }
+    if !reading.trading || reading.price < record.oracle_min_price {
+        msg!("Oracle does not permit the transfer");
+        return Err(VaultError::OracleRejected.into());
+    }
+    Ok(())
+}
+
+fn read_u32(data: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap())
+}
+
+fn read_u64(data: &[u8], offset: usize) -> u64 {
+    u64::from_le_bytes(data[offset..offset + 8].try_into().unwrap())
+}
+
+/// Pyth price account data with the given aggregate.
+#[cfg(any(test, feature = "test-utils"))]
+pub fn pyth_price_data(price: i64, trading: bool, pub_slot: u64) -> Vec<u8> {
+    let mut data = vec![0; PYTH_MIN_LEN];
+    data[0..4].copy_from_slice(&PYTH_MAGIC.to_le_bytes());
+    data[4..8].copy_from_slice(&2u32.to_le_bytes());
+    data[8..12].copy_from_slice(&PYTH_PRICE_ACCOUNT.to_le_bytes());
+    data[PYTH_AGG_PRICE_OFFSET..PYTH_AGG_PRICE_OFFSET + 8].copy_from_slice(&price.to_le_bytes());
+    let status = if trading { PYTH_STATUS_TRADING } else { 2 };
+    data[PYTH_AGG_STATUS_OFFSET..PYTH_AGG_STATUS_OFFSET + 4].copy_from_slice(&status.to_le_bytes());
+    data[PYTH_AGG_PUB_SLOT_OFFSET..PYTH_AGG_PUB_SLOT_OFFSET + 8]
+        .copy_from_slice(&pub_slot.to_le_bytes());
+    data
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read_pyth_aggregate() {
+        let data = pyth_price_data(-5, true, 42);
+        assert_eq!(
+            read_feed(&data).unwrap(),
+            FeedReading {
+                price: -5,
+                trading: true,
+                pub_slot: 42,
+            }
+        );
+        assert!(!read_feed(&pyth_price_data(5, false, 42)).unwrap().trading);
+
+        let unavailable = Err(VaultError::OracleUnavailable.into());
+        assert_eq!(read_feed(&data[..PYTH_MIN_LEN - 1]), unavailable);
+        let mut data = data;
+        data[0] = 0;
+        assert_eq!(read_feed(&data), unavailable);
+    }
+}
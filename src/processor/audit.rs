@@ -0,0 +1,71 @@
+use {
+    super::validation::{
+        create_pda_account, instruction_accounts, load_record, validate_active, validate_dart,
+    },
+    crate::state::{AuditTrail, AUDIT_TRAIL_SEED, MAX_AUDIT_ENTRIES},
+    solana_program::{
+        account_info::AccountInfo, entrypoint::ProgramResult, msg, program_error::ProgramError,
+        pubkey::Pubkey,
+    },
+};
+
+instruction_accounts! {
+    /// Accounts of `CreateAuditTrail`.
+    CreateAuditTrailAccounts { pda, dart, trail, payer, system_program; ..remaining }
+    // The DART may also pay for the audit trail.
+    unique: [pda, dart, trail];
+    unique: [pda, trail, payer];
+    writable: [pda, trail, payer];
+    readonly: [dart];
+    owned: [pda];
+    signers: [payer];
+}
+
+// Open the audit trail of a vault (DART only), sized for `capacity` entries, and flag the
+// vault so the processor requires the trail and records instructions in it.
+pub(super) fn create_audit_trail(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    capacity: u16,
+) -> ProgramResult {
+    let CreateAuditTrailAccounts {
+        pda,
+        dart,
+        trail,
+        payer,
+        system_program,
+        remaining,
+    } = CreateAuditTrailAccounts::parse(program_id, accounts)?;
+
+    let mut record = load_record(program_id, pda)?;
+    validate_active(&record)?;
+    validate_dart(program_id, dart, remaining, &record.dart)?;
+
+    if capacity == 0 || capacity > MAX_AUDIT_ENTRIES {
+        msg!("Audit trail capacity must be 1 to {}", MAX_AUDIT_ENTRIES);
+        return Err(ProgramError::InvalidArgument);
+    }
+    let (address, bump) = AuditTrail::find_address(program_id, pda.key);
+    if *trail.key != address {
+        msg!("Audit trail does not match the vault");
+        return Err(ProgramError::InvalidSeeds);
+    }
+    if trail.owner == program_id {
+        msg!("Audit trail already exists");
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+    create_pda_account(
+        program_id,
+        payer,
+        trail,
+        system_program,
+        AuditTrail::space(capacity),
+        &[AUDIT_TRAIL_SEED, pda.key.as_ref(), &[bump]],
+    )?;
+
+    // The entries after the header start zeroed, and are only read once recorded.
+    let header = borsh::to_vec(&AuditTrail::new(*pda.key, capacity, bump))?;
+    trail.data.borrow_mut()[..AuditTrail::LEN].copy_from_slice(&header);
+    record.audited = true;
+    record.pack_into(&mut pda.data.borrow_mut())
+}
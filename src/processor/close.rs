@@ -0,0 +1,386 @@
+use {
+    super::validation::{
+        close_recipient_key, find_account, find_fee_config, instruction_accounts, load_fee_config,
+        load_program_config, load_record, notify_change, sync_receipt_holder, update_vault_index,
+        validate_account_flags, validate_active, validate_dart, validate_expected_authority,
+        validate_not_pledged, validate_pledge, validate_signer, validate_vault_empty,
+    },
+    crate::{
+        error::VaultError,
+        event::{self, CloseReceipt, VaultClosed, VaultRekeyed},
+        instruction::{VaultInstruction, INITIALIZE_VAULT_INDEX_INDEX},
+        math, receipt,
+        state::{NotificationKind, VaultIndex, VaultRecord, VaultStatus},
+        validation::{require_owner, require_writable},
+    },
+    solana_program::{
+        account_info::AccountInfo,
+        clock::Clock,
+        entrypoint::ProgramResult,
+        instruction::{AccountMeta, Instruction},
+        msg,
+        program::{invoke, set_return_data},
+        program_error::ProgramError,
+        program_pack::IsInitialized,
+        pubkey::Pubkey,
+        rent::Rent,
+        sysvar::Sysvar,
+    },
+};
+
+instruction_accounts! {
+    /// Accounts of `CloseAccount`, `CloseAccountWithRefund` and `CloseAccountChecked`.
+    CloseAccountAccounts { pda, dart, authority; ..remaining }
+    unique: [pda, dart, authority];
+    writable: [pda, authority];
+    readonly: [dart];
+    owned: [pda];
+}
+
+instruction_accounts! {
+    /// Accounts of `Reopen`.
+    ReopenAccounts { pda, dart; ..remaining }
+    unique: [pda, dart];
+    writable: [pda];
+    readonly: [dart];
+    owned: [pda];
+}
+
+instruction_accounts! {
+    /// Accounts of `MigrateToProgram`.
+    MigrateToProgramAccounts { pda, new_pda, dart, authority, config_info, successor; ..remaining }
+    unique: [pda, new_pda, dart, authority, config_info, successor];
+    writable: [pda, new_pda];
+    readonly: [dart, authority, config_info, successor];
+    owned: [pda, config_info];
+}
+
+instruction_accounts! {
+    /// Accounts of `Rekey`.
+    RekeyAccounts { pda, new_pda, dart, authority; ..remaining }
+    unique: [pda, new_pda, dart, authority];
+    writable: [pda, new_pda];
+    readonly: [dart, authority];
+}
+
+// Close a vault record account, draining lamports to the close recipient, the rent
+// payer, or the authority.
+pub(super) fn close_account(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    refund_authority: bool,
+    expected_authority: Option<&Pubkey>,
+) -> ProgramResult {
+    let CloseAccountAccounts {
+        pda,
+        dart,
+        authority,
+        remaining,
+    } = CloseAccountAccounts::parse(program_id, accounts)?;
+
+    let mut record = load_record(program_id, pda)?;
+    if record.status == VaultStatus::Closed {
+        msg!("record already closed");
+        return Err(VaultError::AccountClosed.into());
+    }
+
+    validate_dart(program_id, dart, remaining, &record.dart)?;
+    // A receipt holder's vault stays indexed under the authority last written.
+    let indexed_authority = record.authority;
+    sync_receipt_holder(&mut record, remaining)?;
+    validate_expected_authority(&record, expected_authority)?;
+    validate_signer(authority, &record.authority, VaultError::IncorrectAuthority)?;
+    validate_pledge(program_id, pda.key, &record, remaining)?;
+    validate_vault_empty(program_id, pda.key, remaining)?;
+
+    // Burn the custody receipt when the authority passes the account holding it.
+    if record.receipt_mint != Pubkey::default() {
+        if let Some((holder, token)) = receipt::find_holder(remaining, &record.receipt_mint) {
+            if token.owner == record.authority {
+                let (Some(mint), Some(token_program)) = (
+                    find_account(remaining, &record.receipt_mint),
+                    find_account(remaining, &spl_token::id()),
+                ) else {
+                    msg!("Missing receipt mint or token program");
+                    return Err(ProgramError::NotEnoughAccountKeys);
+                };
+                receipt::burn(token_program, mint, holder, authority)?;
+            }
+        }
+        record.receipt_mint = Pubkey::default();
+        record.receipt_is_authority = false;
+    }
+
+    let recipient_key = close_recipient_key(&record, refund_authority);
+    let recipient = find_account(accounts, &recipient_key).ok_or_else(|| {
+        msg!("Missing close recipient account {}", recipient_key);
+        ProgramError::NotEnoughAccountKeys
+    })?;
+    validate_account_flags(&[recipient], &[])?;
+
+    let recipient_starting_lamports = recipient.lamports();
+    let pda_lamports = pda.lamports();
+
+    // The close fee, once the fee config exists, stays there until `CollectFees`. It
+    // never exceeds the vault's lamports, so a vault can always be closed.
+    let fee_info = find_fee_config(program_id, remaining)?;
+    let fee = match fee_info {
+        Some(fee_info) => {
+            require_writable(fee_info)?;
+            let fee_config = load_fee_config(program_id, fee_info)?;
+            fee_config
+                .close_fee(&record, Clock::get()?.slot)
+                .min(pda_lamports)
+        }
+        None => 0,
+    };
+    let payout = math::checked_sub(pda_lamports, fee)?;
+
+    **pda.lamports.borrow_mut() = 0;
+    **recipient.lamports.borrow_mut() = math::checked_add(recipient_starting_lamports, payout)?;
+    if let Some(fee_info) = fee_info {
+        let fee_starting_lamports = fee_info.lamports();
+        **fee_info.lamports.borrow_mut() = math::checked_add(fee_starting_lamports, fee)?;
+    }
+
+    // Tombstone the record in case the account is re-funded in this transaction.
+    record.status.transition_to(VaultStatus::Closed)?;
+
+    record.pack_into(&mut pda.data.borrow_mut())?;
+    update_vault_index(program_id, remaining, &indexed_authority, pda.key, false)?;
+    notify_change(
+        program_id,
+        remaining,
+        NotificationKind::Close,
+        pda.key,
+        &record.authority,
+        &Pubkey::default(),
+    )?;
+    event::emit(
+        program_id,
+        accounts,
+        &VaultClosed {
+            vault: *pda.key,
+            authority: record.authority,
+            recipient: recipient_key,
+            lamports: payout,
+        },
+    )?;
+
+    // Set the receipt last: the self-CPI of an event would clear earlier return data.
+    let receipt = CloseReceipt {
+        vault: *pda.key,
+        authority: record.authority,
+        lamports: payout,
+        slot: Clock::get()?.slot,
+    };
+    event::emit(program_id, accounts, &receipt)?;
+    set_return_data(&event::event_data(&receipt));
+    Ok(())
+}
+
+// Reopen a closed vault record that has been re-funded to rent exemption.
+pub(super) fn reopen(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let ReopenAccounts {
+        pda,
+        dart,
+        remaining,
+    } = ReopenAccounts::parse(program_id, accounts)?;
+
+    let mut record = load_record(program_id, pda)?;
+    if record.status != VaultStatus::Closed {
+        msg!("record not closed");
+        return Err(VaultError::AccountNotClosed.into());
+    }
+
+    validate_dart(program_id, dart, remaining, &record.dart)?;
+
+    if !Rent::get()?.is_exempt(pda.lamports(), pda.data_len()) {
+        msg!("vault account must be rent exempt to reopen");
+        return Err(ProgramError::AccountNotRentExempt);
+    }
+
+    record.status.transition_to(VaultStatus::Active)?;
+
+    record.pack_into(&mut pda.data.borrow_mut())
+}
+
+// Move a vault record to a new account (by DART and authority), paying the old
+// account's lamports out as a close would and wiping it.
+pub(super) fn rekey(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let RekeyAccounts {
+        pda,
+        new_pda,
+        dart,
+        authority,
+        remaining,
+    } = RekeyAccounts::parse(program_id, accounts)?;
+
+    let mut record = load_record(program_id, pda)?;
+    validate_active(&record)?;
+    validate_dart(program_id, dart, remaining, &record.dart)?;
+    let indexed_authority = record.authority;
+    sync_receipt_holder(&mut record, remaining)?;
+    validate_signer(authority, &record.authority, VaultError::IncorrectAuthority)?;
+    validate_not_pledged(&record)?;
+    // The position and token accounts stay keyed by the old address.
+    validate_vault_empty(program_id, pda.key, remaining)?;
+
+    require_owner(new_pda, program_id)?;
+    if new_pda.data_len() != pda.data_len() {
+        msg!(
+            "New vault account must be {} bytes, not {}",
+            pda.data_len(),
+            new_pda.data_len()
+        );
+        return Err(VaultError::InvalidDataLength.into());
+    }
+    if VaultRecord::from_account_data(&new_pda.data.borrow())?.is_initialized() {
+        msg!("New vault account already initialized");
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+    if !Rent::get()?.is_exempt(new_pda.lamports(), new_pda.data_len()) {
+        msg!("New vault account must be rent exempt");
+        return Err(ProgramError::AccountNotRentExempt);
+    }
+
+    let recipient_key = close_recipient_key(&record, false);
+    let recipient = find_account(accounts, &recipient_key).ok_or_else(|| {
+        msg!("Missing close recipient account {}", recipient_key);
+        ProgramError::NotEnoughAccountKeys
+    })?;
+    validate_account_flags(&[recipient], &[])?;
+
+    // Metadata past the header moves with the record.
+    new_pda
+        .data
+        .borrow_mut()
+        .copy_from_slice(&pda.data.borrow());
+    // The rent payer was refunded from the old account.
+    record.rent_payer = Pubkey::default();
+    // The audit trail, rate limit and transfer window stay keyed by the old address;
+    // the new vault would otherwise demand accounts it cannot have.
+    record.audited = false;
+    record.rate_limited = false;
+    record.transfer_windowed = false;
+    record.pack_into(&mut new_pda.data.borrow_mut())?;
+
+    let pda_lamports = pda.lamports();
+    let recipient_starting_lamports = recipient.lamports();
+    **pda.lamports.borrow_mut() = 0;
+    **recipient.lamports.borrow_mut() =
+        math::checked_add(recipient_starting_lamports, pda_lamports)?;
+    // Wipe the old record so it cannot be reopened alongside the new one.
+    pda.data.borrow_mut().fill(0);
+    update_vault_index(program_id, remaining, &indexed_authority, pda.key, false)?;
+    update_vault_index(program_id, remaining, &record.authority, new_pda.key, true)?;
+
+    event::emit(
+        program_id,
+        accounts,
+        &VaultRekeyed {
+            vault: *pda.key,
+            new_vault: *new_pda.key,
+            authority: record.authority,
+        },
+    )
+}
+
+// Move a vault record to the program's successor (by DART and authority): initialize
+// it there through the successor's `Initialize`, then pay the old account's lamports
+// out as a close would and wipe it.
+pub(super) fn migrate_to_program(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let MigrateToProgramAccounts {
+        pda,
+        new_pda,
+        dart,
+        authority,
+        config_info,
+        successor,
+        remaining,
+    } = MigrateToProgramAccounts::parse(program_id, accounts)?;
+
+    let config = load_program_config(program_id, config_info)?;
+    if !config.is_migrated() || *successor.key != config.successor {
+        msg!("{} is not the program's successor", successor.key);
+        return Err(VaultError::NotSuccessor.into());
+    }
+    if !successor.executable {
+        msg!("Successor {} is not executable", successor.key);
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let record = load_record(program_id, pda)?;
+    validate_active(&record)?;
+    validate_dart(program_id, dart, remaining, &record.dart)?;
+    validate_signer(authority, &record.authority, VaultError::IncorrectAuthority)?;
+    if record.receipt_mint != Pubkey::default() {
+        msg!("Burn the custody receipt before migrating");
+        return Err(VaultError::ReceiptAlreadyMinted.into());
+    }
+    validate_not_pledged(&record)?;
+    validate_vault_empty(program_id, pda.key, remaining)?;
+
+    let recipient_key = close_recipient_key(&record, false);
+    let recipient = find_account(accounts, &recipient_key).ok_or_else(|| {
+        msg!("Missing close recipient account {}", recipient_key);
+        ProgramError::NotEnoughAccountKeys
+    })?;
+    validate_account_flags(&[recipient], &[])?;
+
+    // The successor shares the `Initialize` interface; a record held through a DART
+    // identity passes the identity on for the successor to check, and the authority's
+    // vault index at the successor goes along for it to list the vault. The DART's
+    // signature is forwarded only when it signed: an identity approving through its
+    // quorum is passed unsigned, as claiming a signature it lacks would fail the invoke.
+    let mut cpi_accounts = vec![new_pda.clone(), dart.clone(), authority.clone()];
+    let mut metas = vec![
+        AccountMeta::new(*new_pda.key, false),
+        AccountMeta::new_readonly(*dart.key, dart.is_signer),
+        AccountMeta::new_readonly(*authority.key, false),
+    ];
+    if record.dart != *dart.key {
+        let identity =
+            find_account(remaining, &record.dart).ok_or(ProgramError::NotEnoughAccountKeys)?;
+        cpi_accounts.push(identity.clone());
+        metas.push(AccountMeta::new_readonly(*identity.key, false));
+    }
+    // The vault index goes past the optional accounts, which the successor's id stands in
+    // for.
+    metas.resize(
+        INITIALIZE_VAULT_INDEX_INDEX,
+        AccountMeta::new_readonly(*successor.key, false),
+    );
+    let (index_address, _) = VaultIndex::find_address(successor.key, authority.key);
+    let index = find_account(remaining, &index_address).ok_or_else(|| {
+        msg!("Missing successor vault index {}", index_address);
+        ProgramError::NotEnoughAccountKeys
+    })?;
+    cpi_accounts.push(index.clone());
+    metas.push(AccountMeta::new(*index.key, false));
+    cpi_accounts.push(successor.clone());
+    invoke(
+        &Instruction::new_with_borsh(*successor.key, &VaultInstruction::Initialize, metas),
+        &cpi_accounts,
+    )?;
+
+    let pda_lamports = pda.lamports();
+    let recipient_starting_lamports = recipient.lamports();
+    **pda.lamports.borrow_mut() = 0;
+    **recipient.lamports.borrow_mut() =
+        math::checked_add(recipient_starting_lamports, pda_lamports)?;
+    // Wipe the old record so it cannot be reopened alongside the migrated one.
+    pda.data.borrow_mut().fill(0);
+    update_vault_index(program_id, remaining, &record.authority, pda.key, false)?;
+
+    event::emit(
+        program_id,
+        accounts,
+        &VaultClosed {
+            vault: *pda.key,
+            authority: record.authority,
+            recipient: recipient_key,
+            lamports: pda_lamports,
+        },
+    )
+}
@@ -0,0 +1,62 @@
+use {
+    super::validation::{
+        create_pda_account, instruction_accounts, load_record, validate_active, validate_dart,
+        write_account,
+    },
+    crate::state::{CommitmentRecord, COMMITMENT_SEED, MAX_COMMITMENTS},
+    solana_program::{
+        account_info::AccountInfo, clock::Clock, entrypoint::ProgramResult, msg,
+        program_error::ProgramError, pubkey::Pubkey, sysvar::Sysvar,
+    },
+};
+
+instruction_accounts! {
+    /// Accounts of `UpdateCommitments`.
+    UpdateCommitmentsAccounts { pda, dart, commitments, payer, system_program; ..remaining }
+    // The DART may also pay for the commitment account.
+    unique: [pda, dart, commitments];
+    unique: [pda, commitments, payer];
+    writable: [commitments, payer];
+    readonly: [pda, dart];
+    owned: [pda];
+}
+
+// Replace the commitments to the vault's confidential metadata (DART only), creating
+// the commitment account if needed.
+pub(super) fn update_commitments(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    commitments: [[u8; 32]; MAX_COMMITMENTS],
+) -> ProgramResult {
+    let UpdateCommitmentsAccounts {
+        pda,
+        dart,
+        commitments: commitments_info,
+        payer,
+        system_program,
+        remaining,
+    } = UpdateCommitmentsAccounts::parse(program_id, accounts)?;
+
+    let record = load_record(program_id, pda)?;
+    validate_active(&record)?;
+    validate_dart(program_id, dart, remaining, &record.dart)?;
+
+    let (address, bump) = CommitmentRecord::find_address(program_id, pda.key);
+    if *commitments_info.key != address {
+        msg!("Commitment account does not match the vault");
+        return Err(ProgramError::InvalidSeeds);
+    }
+    if commitments_info.owner != program_id {
+        create_pda_account(
+            program_id,
+            payer,
+            commitments_info,
+            system_program,
+            CommitmentRecord::LEN,
+            &[COMMITMENT_SEED, pda.key.as_ref(), &[bump]],
+        )?;
+    }
+    let commitment_record = CommitmentRecord::new(*pda.key, commitments, Clock::get()?.slot, bump);
+
+    write_account(commitments_info, &commitment_record)
+}
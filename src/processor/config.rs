@@ -0,0 +1,385 @@
+use {
+    super::validation::{
+        create_pda_account, instruction_accounts, load_fee_config, load_program_config,
+        validate_signer, write_account,
+    },
+    crate::{
+        error::VaultError,
+        event::{self, FeesCollected},
+        math,
+        state::{
+            program_data_address, upgrade_authority, CloseFeeTier, FeeConfig, ProgramConfig,
+            ProgramStats, CONFIG_SEED, FEE_CONFIG_SEED, MAX_CLOSE_FEE_TIERS, MAX_FEE_EXEMPT,
+            STATS_SEED,
+        },
+    },
+    solana_program::{
+        account_info::AccountInfo, bpf_loader_upgradeable, entrypoint::ProgramResult, msg,
+        program_error::ProgramError, pubkey::Pubkey, rent::Rent, sysvar::Sysvar,
+    },
+};
+
+instruction_accounts! {
+    /// Accounts of `InitializeConfig`.
+    InitializeConfigAccounts { config_info, admin, payer, program_data, system_program }
+    // The upgrade authority may also pay for the config.
+    unique: [config_info, admin, program_data];
+    unique: [config_info, payer, program_data];
+    writable: [config_info, payer];
+    readonly: [admin, program_data];
+    signers: [payer];
+}
+
+instruction_accounts! {
+    /// Accounts of `Decommission`.
+    DecommissionAccounts { config_info, admin }
+    unique: [config_info, admin];
+    writable: [config_info];
+    readonly: [admin];
+    owned: [config_info];
+}
+
+instruction_accounts! {
+    /// Accounts of `SetCrankRules`.
+    SetCrankRulesAccounts { config_info, admin }
+    unique: [config_info, admin];
+    writable: [config_info];
+    readonly: [admin];
+    owned: [config_info];
+}
+
+instruction_accounts! {
+    /// Accounts of `SetFeeExemption`.
+    SetFeeExemptionAccounts { fee_config_info, config_info, admin, payer, system_program }
+    // The admin may also pay for the fee config.
+    unique: [fee_config_info, config_info, admin];
+    unique: [fee_config_info, config_info, payer];
+    writable: [fee_config_info, payer];
+    readonly: [config_info, admin];
+    owned: [config_info];
+    signers: [payer];
+}
+
+instruction_accounts! {
+    /// Accounts of `SetFeeDestination`.
+    SetFeeDestinationAccounts { fee_config_info, config_info, admin, payer, system_program }
+    // The admin may also pay for the fee config.
+    unique: [fee_config_info, config_info, admin];
+    unique: [fee_config_info, config_info, payer];
+    writable: [fee_config_info, payer];
+    readonly: [config_info, admin];
+    owned: [config_info];
+    signers: [payer];
+}
+
+instruction_accounts! {
+    /// Accounts of `SetCloseFees`.
+    SetCloseFeesAccounts { fee_config_info, config_info, admin, payer, system_program }
+    // The admin may also pay for the fee config.
+    unique: [fee_config_info, config_info, admin];
+    unique: [fee_config_info, config_info, payer];
+    writable: [fee_config_info, payer];
+    readonly: [config_info, admin];
+    owned: [config_info];
+    signers: [payer];
+}
+
+instruction_accounts! {
+    /// Accounts of `InitializeStats`.
+    InitializeStatsAccounts { stats_info, config_info, admin, payer, system_program }
+    // The admin may also pay for the stats.
+    unique: [stats_info, config_info, admin];
+    unique: [stats_info, config_info, payer];
+    writable: [stats_info, payer];
+    readonly: [config_info, admin];
+    owned: [config_info];
+    signers: [payer];
+}
+
+instruction_accounts! {
+    /// Accounts of `CollectFees`.
+    CollectFeesAccounts { fee_config_info, config_info, admin, destination }
+    // The admin may also be the fee destination.
+    unique: [fee_config_info, config_info, admin];
+    unique: [fee_config_info, config_info, destination];
+    writable: [fee_config_info, destination];
+    readonly: [config_info, admin];
+    owned: [fee_config_info, config_info];
+}
+
+// Create the program config, administered by the program's upgrade authority.
+pub(super) fn initialize_config(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let InitializeConfigAccounts {
+        config_info,
+        admin,
+        payer,
+        program_data,
+        system_program,
+    } = InitializeConfigAccounts::parse(program_id, accounts)?;
+
+    if *program_data.key != program_data_address(program_id)
+        || *program_data.owner != bpf_loader_upgradeable::id()
+    {
+        msg!("Account is not the program's ProgramData");
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let authority = upgrade_authority(&program_data.data.borrow()).ok_or_else(|| {
+        msg!("Program has no upgrade authority");
+        VaultError::NotUpgradeAuthority
+    })?;
+    validate_signer(admin, &authority, VaultError::NotUpgradeAuthority)?;
+
+    let (address, bump) = ProgramConfig::find_address(program_id);
+    if *config_info.key != address {
+        msg!("Program config address mismatch");
+        return Err(ProgramError::InvalidSeeds);
+    }
+    if !config_info.data_is_empty() {
+        msg!("Program config already initialized");
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+    create_pda_account(
+        program_id,
+        payer,
+        config_info,
+        system_program,
+        ProgramConfig::LEN,
+        &[CONFIG_SEED, &[bump]],
+    )?;
+    let config = ProgramConfig::new(*admin.key, bump);
+
+    write_account(config_info, &config)
+}
+
+// Record the program vaults migrate to (admin only).
+pub(super) fn decommission(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    successor: Pubkey,
+) -> ProgramResult {
+    let DecommissionAccounts { config_info, admin } =
+        DecommissionAccounts::parse(program_id, accounts)?;
+
+    let mut config = load_program_config(program_id, config_info)?;
+    validate_signer(admin, &config.admin, VaultError::IncorrectAuthority)?;
+    if successor == Pubkey::default() || successor == *program_id {
+        msg!("{} cannot succeed the program", successor);
+        return Err(ProgramError::InvalidArgument);
+    }
+    config.successor = successor;
+
+    write_account(config_info, &config)
+}
+
+// Set or clear a slot of the fee exemption list (admin only), creating the fee config
+// if needed.
+pub(super) fn set_fee_exemption(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    slot: u8,
+    key: Pubkey,
+) -> ProgramResult {
+    let SetFeeExemptionAccounts {
+        fee_config_info,
+        config_info,
+        admin,
+        payer,
+        system_program,
+    } = SetFeeExemptionAccounts::parse(program_id, accounts)?;
+    let mut fee_config = load_or_create_fee_config(
+        program_id,
+        fee_config_info,
+        config_info,
+        admin,
+        payer,
+        system_program,
+    )?;
+
+    let slot = usize::from(slot);
+    if slot >= MAX_FEE_EXEMPT {
+        msg!("fee exemption slot {} out of range", slot);
+        return Err(VaultError::InvalidFeeExemptionSlot.into());
+    }
+    fee_config.exempt[slot] = key;
+
+    write_account(fee_config_info, &fee_config)
+}
+
+// Set the account `CollectFees` pays to (admin only), creating the fee config if needed.
+pub(super) fn set_fee_destination(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    destination: Pubkey,
+) -> ProgramResult {
+    let SetFeeDestinationAccounts {
+        fee_config_info,
+        config_info,
+        admin,
+        payer,
+        system_program,
+    } = SetFeeDestinationAccounts::parse(program_id, accounts)?;
+    let mut fee_config = load_or_create_fee_config(
+        program_id,
+        fee_config_info,
+        config_info,
+        admin,
+        payer,
+        system_program,
+    )?;
+    fee_config.fee_destination = destination;
+
+    write_account(fee_config_info, &fee_config)
+}
+
+// Replace the close fee schedule (admin only), creating the fee config if needed.
+pub(super) fn set_close_fees(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    default_lamports: u64,
+    tiers: [CloseFeeTier; MAX_CLOSE_FEE_TIERS],
+) -> ProgramResult {
+    let SetCloseFeesAccounts {
+        fee_config_info,
+        config_info,
+        admin,
+        payer,
+        system_program,
+    } = SetCloseFeesAccounts::parse(program_id, accounts)?;
+    let mut fee_config = load_or_create_fee_config(
+        program_id,
+        fee_config_info,
+        config_info,
+        admin,
+        payer,
+        system_program,
+    )?;
+    fee_config.default_close_fee = default_lamports;
+    fee_config.close_fee_tiers = tiers;
+
+    write_account(fee_config_info, &fee_config)
+}
+
+// Check the admin, and load the fee config or create it.
+fn load_or_create_fee_config<'a>(
+    program_id: &Pubkey,
+    fee_config_info: &AccountInfo<'a>,
+    config_info: &AccountInfo,
+    admin: &AccountInfo,
+    payer: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+) -> Result<FeeConfig, ProgramError> {
+    let config = load_program_config(program_id, config_info)?;
+    validate_signer(admin, &config.admin, VaultError::IncorrectAuthority)?;
+
+    let (address, bump) = FeeConfig::find_address(program_id);
+    if *fee_config_info.key != address {
+        msg!("Fee config address mismatch");
+        return Err(ProgramError::InvalidSeeds);
+    }
+    if fee_config_info.owner == program_id {
+        return load_fee_config(program_id, fee_config_info);
+    }
+    create_pda_account(
+        program_id,
+        payer,
+        fee_config_info,
+        system_program,
+        FeeConfig::LEN,
+        &[FEE_CONFIG_SEED, &[bump]],
+    )?;
+    Ok(FeeConfig::new(bump))
+}
+
+// Create the program stats (admin only), counting the instructions passed them from
+// then on.
+pub(super) fn initialize_stats(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let InitializeStatsAccounts {
+        stats_info,
+        config_info,
+        admin,
+        payer,
+        system_program,
+    } = InitializeStatsAccounts::parse(program_id, accounts)?;
+
+    let config = load_program_config(program_id, config_info)?;
+    validate_signer(admin, &config.admin, VaultError::IncorrectAuthority)?;
+
+    let (address, bump) = ProgramStats::find_address(program_id);
+    if *stats_info.key != address {
+        msg!("Program stats address mismatch");
+        return Err(ProgramError::InvalidSeeds);
+    }
+    if stats_info.owner == program_id {
+        msg!("Program stats already initialized");
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+    create_pda_account(
+        program_id,
+        payer,
+        stats_info,
+        system_program,
+        ProgramStats::LEN,
+        &[STATS_SEED, &[bump]],
+    )?;
+
+    write_account(stats_info, &ProgramStats::new(bump))
+}
+
+// Pay the fees accumulated in the fee config, its lamports above rent exemption, to
+// the fee destination (admin only).
+pub(super) fn collect_fees(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let CollectFeesAccounts {
+        fee_config_info,
+        config_info,
+        admin,
+        destination,
+    } = CollectFeesAccounts::parse(program_id, accounts)?;
+
+    let config = load_program_config(program_id, config_info)?;
+    validate_signer(admin, &config.admin, VaultError::IncorrectAuthority)?;
+    let fee_config = load_fee_config(program_id, fee_config_info)?;
+    if fee_config.fee_destination == Pubkey::default() {
+        msg!("No fee destination set");
+        return Err(ProgramError::InvalidArgument);
+    }
+    if *destination.key != fee_config.fee_destination {
+        msg!("Fees go to {}", fee_config.fee_destination);
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    let rent_exempt = Rent::get()?.minimum_balance(fee_config_info.data_len());
+    let fee_lamports = fee_config_info.lamports();
+    let collected = fee_lamports.saturating_sub(rent_exempt);
+    let destination_starting_lamports = destination.lamports();
+    **fee_config_info.lamports.borrow_mut() = math::checked_sub(fee_lamports, collected)?;
+    **destination.lamports.borrow_mut() =
+        math::checked_add(destination_starting_lamports, collected)?;
+
+    event::emit(
+        program_id,
+        accounts,
+        &FeesCollected {
+            fee_config: *fee_config_info.key,
+            destination: *destination.key,
+            lamports: collected,
+        },
+    )
+}
+
+// Set the bounty and grace period of `Crank` (admin only).
+pub(super) fn set_crank_rules(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    bounty_lamports: u64,
+    grace_slots: u64,
+) -> ProgramResult {
+    let SetCrankRulesAccounts { config_info, admin } =
+        SetCrankRulesAccounts::parse(program_id, accounts)?;
+
+    let mut config = load_program_config(program_id, config_info)?;
+    validate_signer(admin, &config.admin, VaultError::IncorrectAuthority)?;
+    config.crank_bounty = bounty_lamports;
+    config.crank_grace_slots = grace_slots;
+
+    write_account(config_info, &config)
+}
@@ -0,0 +1,98 @@
+use {
+    super::validation::{instruction_accounts, load_program_config, validate_account_flags},
+    crate::{
+        error::VaultError,
+        math,
+        state::{ApprovalRoot, SessionRecord},
+        validation::{require_initialized, require_owner},
+    },
+    borsh::BorshDeserialize,
+    solana_program::{
+        account_info::AccountInfo, clock::Clock, entrypoint::ProgramResult, msg,
+        program_error::ProgramError, pubkey::Pubkey, sysvar::Sysvar,
+    },
+    spl_discriminator::SplDiscriminate,
+};
+
+instruction_accounts! {
+    /// Accounts of `Crank`.
+    CrankAccounts { config_info, cranker; ..remaining }
+    unique: [config_info, cranker];
+    writable: [cranker];
+    readonly: [config_info];
+    owned: [config_info];
+    signers: [cranker];
+}
+
+// Sweep expired accounts past the config's grace period (by anyone): pay each one's
+// lamports to whoever created it, less the config's bounty to the cranker. The bounty
+// comes out of the reclaimed lamports, capped at a share of them, so a cranker sweeping
+// throwaway accounts it created only recovers part of their rent.
+pub(super) fn crank(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let CrankAccounts {
+        config_info,
+        cranker,
+        remaining,
+    } = CrankAccounts::parse(program_id, accounts)?;
+
+    let config = load_program_config(program_id, config_info)?;
+    if remaining.is_empty() || remaining.len() % 2 != 0 {
+        msg!("Pass each account to sweep followed by its rent recipient");
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+
+    let slot = Clock::get()?.slot;
+    for pair in remaining.chunks(2) {
+        let (account, recipient) = (&pair[0], &pair[1]);
+        validate_account_flags(&[account, recipient], &[])?;
+        require_owner(account, program_id)?;
+        if account.key == recipient.key || account.key == config_info.key {
+            msg!("Account {} cannot be swept into itself", account.key);
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        let (expires_at_slot, creator) = sweep_terms(account)?;
+        if !config.is_sweepable(expires_at_slot, slot) {
+            msg!("Account {} is within its grace period", account.key);
+            return Err(VaultError::NotSweepable.into());
+        }
+        if *recipient.key != creator {
+            msg!("Rent of {} goes to {}", account.key, creator);
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        let account_lamports = account.lamports();
+        let bounty = config.crank_bounty_of(account_lamports);
+        **account.lamports.borrow_mut() = 0;
+        account.data.borrow_mut().fill(0);
+
+        let recipient_starting_lamports = recipient.lamports();
+        **recipient.lamports.borrow_mut() = math::checked_add(
+            recipient_starting_lamports,
+            math::checked_sub(account_lamports, bounty)?,
+        )?;
+        let cranker_starting_lamports = cranker.lamports();
+        **cranker.lamports.borrow_mut() = math::checked_add(cranker_starting_lamports, bounty)?;
+    }
+    Ok(())
+}
+
+// Expiry slot and creator of an account the crank sweeps: a session, refunded to the
+// authority that created it, or an approval root, refunded to its DART. Closed vaults
+// are not swept: a close drains them for the runtime to purge, and one re-funded in
+// the same transaction keeps its tombstone for `Reopen`.
+fn sweep_terms(account: &AccountInfo) -> Result<(u64, Pubkey), ProgramError> {
+    let data = account.data.borrow();
+    if data.starts_with(SessionRecord::SPL_DISCRIMINATOR_SLICE) {
+        let session = SessionRecord::try_from_slice(&data)?;
+        require_initialized(account, &session)?;
+        return Ok((session.expires_at_slot, session.authority));
+    }
+    if data.starts_with(ApprovalRoot::SPL_DISCRIMINATOR_SLICE) {
+        let approval = ApprovalRoot::try_from_slice(&data)?;
+        require_initialized(account, &approval)?;
+        return Ok((approval.expires_at_slot, approval.dart));
+    }
+    msg!("Account {} is not one the crank sweeps", account.key);
+    Err(VaultError::NotSweepable.into())
+}
@@ -0,0 +1,281 @@
+use {
+    super::validation::{
+        create_pda_account, instruction_accounts, load_dart_identity, load_record, validate_active,
+        validate_dart, validate_signer, validate_unique, write_account,
+    },
+    crate::{
+        error::VaultError,
+        state::{
+            restriction, ApprovalRoot, DartIdentity, DartPolicy, PolicyDefaults,
+            APPROVAL_ROOT_SEED, DART_POLICY_SEED, MAX_OPERATIONAL_KEYS,
+        },
+    },
+    borsh::BorshDeserialize,
+    solana_program::{
+        account_info::AccountInfo, entrypoint::ProgramResult, msg, program_error::ProgramError,
+        program_pack::IsInitialized, pubkey::Pubkey,
+    },
+};
+
+instruction_accounts! {
+    /// Accounts of `ProposeDart`.
+    ProposeDartAccounts { pda, dart, new_dart; ..remaining }
+    unique: [pda, dart];
+    writable: [pda];
+    readonly: [dart, new_dart];
+    owned: [pda];
+}
+
+instruction_accounts! {
+    /// Accounts of `AcceptDart`.
+    AcceptDartAccounts { pda, new_dart; ..remaining }
+    unique: [pda, new_dart];
+    writable: [pda];
+    readonly: [new_dart];
+    owned: [pda];
+}
+
+instruction_accounts! {
+    /// Accounts of `InitializeDartIdentity`.
+    InitializeDartIdentityAccounts { identity, master }
+    unique: [identity, master];
+    writable: [identity];
+    readonly: [master];
+    owned: [identity];
+    signers: [master];
+}
+
+instruction_accounts! {
+    /// Accounts of `SetOperationalKey`.
+    SetOperationalKeyAccounts { identity, master }
+    unique: [identity, master];
+    writable: [identity];
+    readonly: [master];
+}
+
+instruction_accounts! {
+    /// Accounts of `SetApprovalRoot`.
+    SetApprovalRootAccounts { approval, dart, payer, system_program }
+    // The DART may also pay for the approval root account.
+    unique: [approval, dart];
+    unique: [approval, payer];
+    writable: [approval, payer];
+    readonly: [dart];
+    signers: [dart, payer];
+}
+
+instruction_accounts! {
+    /// Accounts of `SetDartPolicy`.
+    SetDartPolicyAccounts { policy, dart, payer, system_program }
+    // The DART may also pay for the policy account.
+    unique: [policy, dart];
+    unique: [policy, payer];
+    writable: [policy, payer];
+    readonly: [dart];
+    signers: [dart, payer];
+}
+
+instruction_accounts! {
+    /// Accounts of `SetDartQuorum`.
+    SetDartQuorumAccounts { identity, master }
+    unique: [identity, master];
+    writable: [identity];
+    readonly: [master];
+}
+
+// Propose a successor DART; the current DART keeps control until it is accepted.
+pub(super) fn propose_dart(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let ProposeDartAccounts {
+        pda,
+        dart,
+        new_dart,
+        remaining,
+    } = ProposeDartAccounts::parse(program_id, accounts)?;
+
+    let mut record = load_record(program_id, pda)?;
+    validate_active(&record)?;
+
+    validate_dart(program_id, dart, remaining, &record.dart)?;
+
+    if *new_dart.key == record.dart {
+        msg!("New DART matches current DART");
+        return Err(VaultError::DartUnchanged.into());
+    }
+    if *new_dart.key == Pubkey::default() {
+        msg!("New DART must not be the default pubkey");
+        return Err(VaultError::InvalidDart.into());
+    }
+    if *new_dart.key == record.authority {
+        msg!("New DART must not be the authority");
+        return Err(VaultError::AuthorityIsDart.into());
+    }
+    validate_unique(&[pda, new_dart])?;
+
+    record.pending_dart = *new_dart.key;
+
+    record.pack_into(&mut pda.data.borrow_mut())
+}
+
+// Complete a DART succession, signed by the proposed DART.
+pub(super) fn accept_dart(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let AcceptDartAccounts {
+        pda,
+        new_dart,
+        remaining,
+    } = AcceptDartAccounts::parse(program_id, accounts)?;
+
+    let mut record = load_record(program_id, pda)?;
+    validate_active(&record)?;
+
+    if record.pending_dart == Pubkey::default() {
+        msg!("no pending DART");
+        return Err(VaultError::NoPendingDart.into());
+    }
+    validate_dart(program_id, new_dart, remaining, &record.pending_dart)?;
+
+    // The authority may have been transferred to the proposed DART since the proposal.
+    if record.pending_dart == record.authority {
+        msg!("New DART must not be the authority");
+        return Err(VaultError::AuthorityIsDart.into());
+    }
+
+    record.dart = record.pending_dart;
+    record.pending_dart = Pubkey::default();
+
+    record.pack_into(&mut pda.data.borrow_mut())
+}
+
+// Initialize a DART identity controlled by its master key.
+pub(super) fn initialize_dart_identity(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let InitializeDartIdentityAccounts { identity, master } =
+        InitializeDartIdentityAccounts::parse(program_id, accounts)?;
+
+    let existing = DartIdentity::try_from_slice(&identity.data.borrow())?;
+    if existing.is_initialized() {
+        msg!("DART identity already initialized");
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+
+    let record = DartIdentity::new(*master.key);
+
+    write_account(identity, &record)
+}
+
+// Rotate an operational key of a DART identity (master key only).
+pub(super) fn set_operational_key(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    slot: u8,
+    key: Pubkey,
+) -> ProgramResult {
+    let SetOperationalKeyAccounts { identity, master } =
+        SetOperationalKeyAccounts::parse(program_id, accounts)?;
+
+    let mut record = load_dart_identity(program_id, identity)?;
+    validate_signer(master, &record.master, VaultError::IncorrectMasterKey)?;
+
+    let slot = usize::from(slot);
+    if slot >= MAX_OPERATIONAL_KEYS {
+        msg!("operational key slot {} out of range", slot);
+        return Err(VaultError::InvalidOperationalKeySlot.into());
+    }
+    record.operational_keys[slot] = key;
+
+    write_account(identity, &record)
+}
+
+// Set the operational key quorum of a DART identity (master key only).
+pub(super) fn set_dart_quorum(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    quorum: u8,
+) -> ProgramResult {
+    let SetDartQuorumAccounts { identity, master } =
+        SetDartQuorumAccounts::parse(program_id, accounts)?;
+
+    let mut record = load_dart_identity(program_id, identity)?;
+    validate_signer(master, &record.master, VaultError::IncorrectMasterKey)?;
+
+    if usize::from(quorum) > MAX_OPERATIONAL_KEYS {
+        msg!("quorum {} exceeds the operational keys", quorum);
+        return Err(VaultError::InvalidQuorum.into());
+    }
+    record.quorum = quorum;
+
+    write_account(identity, &record)
+}
+
+// Set the Merkle root of transfers the DART approves in bulk, creating its approval
+// root account if needed.
+pub(super) fn set_approval_root(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    root: [u8; 32],
+    expires_at_slot: u64,
+) -> ProgramResult {
+    let SetApprovalRootAccounts {
+        approval,
+        dart,
+        payer,
+        system_program,
+    } = SetApprovalRootAccounts::parse(program_id, accounts)?;
+
+    let (address, bump) = ApprovalRoot::find_address(program_id, dart.key);
+    if *approval.key != address {
+        msg!("Approval root account does not match the DART");
+        return Err(ProgramError::InvalidSeeds);
+    }
+    if approval.owner != program_id {
+        create_pda_account(
+            program_id,
+            payer,
+            approval,
+            system_program,
+            ApprovalRoot::LEN,
+            &[APPROVAL_ROOT_SEED, dart.key.as_ref(), &[bump]],
+        )?;
+    }
+    let record = ApprovalRoot::new(*dart.key, root, expires_at_slot, bump);
+
+    write_account(approval, &record)
+}
+
+// Set the defaults the DART's new vaults take, creating its policy account if needed.
+pub(super) fn set_dart_policy(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    defaults: PolicyDefaults,
+) -> ProgramResult {
+    let SetDartPolicyAccounts {
+        policy,
+        dart,
+        payer,
+        system_program,
+    } = SetDartPolicyAccounts::parse(program_id, accounts)?;
+
+    if defaults.restrictions & !restriction::ALL != 0 {
+        msg!("Unknown restriction bits {:#04x}", defaults.restrictions);
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    let (address, bump) = DartPolicy::find_address(program_id, dart.key);
+    if *policy.key != address {
+        msg!("Policy account does not match the DART");
+        return Err(ProgramError::InvalidSeeds);
+    }
+    if policy.owner != program_id {
+        create_pda_account(
+            program_id,
+            payer,
+            policy,
+            system_program,
+            DartPolicy::LEN,
+            &[DART_POLICY_SEED, dart.key.as_ref(), &[bump]],
+        )?;
+    }
+
+    write_account(policy, &DartPolicy::new(*dart.key, defaults, bump))
+}
@@ -0,0 +1,131 @@
+use {
+    super::validation::{
+        batch_accounts, create_pda_account, instruction_accounts, load_position, load_record,
+        validate_account_flags, validate_dart, validate_program, write_account,
+    },
+    crate::{
+        math,
+        state::{DistributionCursor, DISTRIBUTION_SEED},
+        validation::{require_initialized, require_owner},
+    },
+    borsh::BorshDeserialize,
+    solana_program::{
+        account_info::AccountInfo, entrypoint::ProgramResult, msg, program::invoke,
+        program_error::ProgramError, program_pack::Pack, pubkey::Pubkey,
+    },
+    spl_token::state::Account as TokenAccount,
+};
+
+instruction_accounts! {
+    /// Accounts of `Distribute`.
+    DistributeAccounts { dart, cursor, source, token_program, system_program; ..remaining }
+    unique: [dart, cursor, source];
+    writable: [dart, cursor, source];
+    readonly: [token_program];
+}
+
+// Pay a batch of vaults their share of distribution `id`, `amount_per_unit` tokens per
+// unit of position balance, from the DART's token account to the authorities' token
+// accounts. The DART pays for the cursor on the first batch. Vaults are passed as
+// (vault, position, destination) triples in ascending vault order after the cursor.
+pub(super) fn distribute(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    id: u64,
+    asset_id: [u8; 32],
+    amount_per_unit: u64,
+) -> ProgramResult {
+    let DistributeAccounts {
+        dart,
+        cursor: cursor_info,
+        source,
+        token_program,
+        system_program,
+        remaining,
+    } = DistributeAccounts::parse(program_id, accounts)?;
+
+    validate_program(token_program, &spl_token::id())?;
+    require_owner(source, &spl_token::id())?;
+    let mint = TokenAccount::unpack(&source.data.borrow())?.mint;
+
+    let (address, bump) = DistributionCursor::find_address(program_id, dart.key, id);
+    if *cursor_info.key != address {
+        msg!("Distribution cursor does not match the DART and id");
+        return Err(ProgramError::InvalidSeeds);
+    }
+    let mut cursor = if cursor_info.owner == program_id {
+        let cursor = DistributionCursor::try_from_slice(&cursor_info.data.borrow())?;
+        require_initialized(cursor_info, &cursor)?;
+        if cursor.asset_id != asset_id
+            || cursor.mint != mint
+            || cursor.amount_per_unit != amount_per_unit
+        {
+            msg!("Distribution terms differ from its first batch");
+            return Err(ProgramError::InvalidArgument);
+        }
+        cursor
+    } else {
+        create_pda_account(
+            program_id,
+            dart,
+            cursor_info,
+            system_program,
+            DistributionCursor::LEN,
+            &[
+                DISTRIBUTION_SEED,
+                dart.key.as_ref(),
+                &id.to_le_bytes(),
+                &[bump],
+            ],
+        )?;
+        DistributionCursor::new(*dart.key, id, asset_id, mint, amount_per_unit, bump)
+    };
+
+    // A trailing account left over is the DART identity.
+    let triples = batch_accounts(program_id, remaining).chunks_exact(3);
+    if triples.len() == 0 {
+        msg!("No vaults to pay");
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+    for triple in triples {
+        let (pda, position_info, destination) = (&triple[0], &triple[1], &triple[2]);
+        let record = load_record(program_id, pda)?;
+        validate_dart(program_id, dart, remaining, &record.dart)?;
+        let position = load_position(program_id, pda.key, position_info)?;
+        if position.asset_id != asset_id {
+            msg!("Vault {} holds another asset", pda.key);
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        validate_account_flags(&[destination], &[])?;
+        require_owner(destination, &spl_token::id())?;
+        let token = TokenAccount::unpack(&destination.data.borrow())?;
+        if token.owner != record.authority || token.mint != mint {
+            msg!("Token account {} is not the authority's", destination.key);
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        let amount = math::checked_mul(position.balance, amount_per_unit)?;
+        cursor.advance(*pda.key, amount)?;
+        if amount > 0 {
+            invoke(
+                &spl_token::instruction::transfer(
+                    token_program.key,
+                    source.key,
+                    destination.key,
+                    dart.key,
+                    &[],
+                    amount,
+                )?,
+                &[
+                    source.clone(),
+                    destination.clone(),
+                    dart.clone(),
+                    token_program.clone(),
+                ],
+            )?;
+        }
+    }
+
+    write_account(cursor_info, &cursor)
+}
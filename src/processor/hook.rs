@@ -0,0 +1,195 @@
+use {
+    super::validation::{
+        create_pda_account, instruction_accounts, load_record, load_transfer_hook_config,
+        validate_active, validate_dart, validate_signer, write_account,
+    },
+    crate::{
+        error::VaultError,
+        state::{TransferHookConfig, VaultStatus, MAX_ALLOWLIST, TRANSFER_HOOK_SEED},
+        transfer_hook::{self, EXTRA_ACCOUNT_METAS_SEED, TOKEN_2022_PROGRAM_ID},
+    },
+    borsh::BorshDeserialize,
+    solana_program::{
+        account_info::AccountInfo, entrypoint::ProgramResult, msg, program_error::ProgramError,
+        pubkey::Pubkey,
+    },
+};
+
+instruction_accounts! {
+    /// Accounts of `InitializeTransferHook`.
+    InitializeTransferHookAccounts { pda, dart, mint_authority, payer, mint, extra_account_metas, config_info, system_program; ..remaining }
+    // The mint authority may also pay for the accounts.
+    unique: [pda, dart, mint_authority, mint, extra_account_metas, config_info];
+    unique: [pda, payer, mint, extra_account_metas, config_info];
+    writable: [payer, extra_account_metas, config_info];
+    readonly: [pda, dart, mint_authority, mint];
+    signers: [payer];
+}
+
+instruction_accounts! {
+    /// Accounts of `SetAllowlistEntry`.
+    SetAllowlistEntryAccounts { pda, dart, config_info; ..remaining }
+    unique: [pda, dart, config_info];
+    writable: [config_info];
+    readonly: [pda, dart];
+}
+
+instruction_accounts! {
+    /// Accounts of the transfer-hook `Execute`.
+    TransferHookExecuteAccounts { _source, mint, destination, _owner, extra_account_metas, config_info, pda }
+    writable: [];
+    readonly: [];
+}
+
+// Link a Token-2022 mint to the vault and publish the accounts its transfer hook
+// needs (by DART and mint authority).
+pub(super) fn initialize_transfer_hook(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let InitializeTransferHookAccounts {
+        pda,
+        dart,
+        mint_authority,
+        payer,
+        mint,
+        extra_account_metas,
+        config_info,
+        system_program,
+        remaining,
+    } = InitializeTransferHookAccounts::parse(program_id, accounts)?;
+
+    let record = load_record(program_id, pda)?;
+    validate_active(&record)?;
+    validate_dart(program_id, dart, remaining, &record.dart)?;
+
+    if *mint.owner != TOKEN_2022_PROGRAM_ID {
+        msg!("Mint is not a Token-2022 mint");
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    let expected = transfer_hook::mint_authority(&mint.data.borrow()).ok_or_else(|| {
+        msg!("Mint has no mint authority");
+        ProgramError::InvalidAccountData
+    })?;
+    validate_signer(mint_authority, &expected, VaultError::IncorrectAuthority)?;
+
+    let (metas_address, metas_bump) =
+        transfer_hook::find_extra_account_metas_address(program_id, mint.key);
+    let (config_address, config_bump) = TransferHookConfig::find_address(program_id, mint.key);
+    if *extra_account_metas.key != metas_address || *config_info.key != config_address {
+        msg!("Transfer hook accounts do not match the mint");
+        return Err(ProgramError::InvalidSeeds);
+    }
+    if !extra_account_metas.data_is_empty() || !config_info.data_is_empty() {
+        msg!("Transfer hook already initialized");
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+
+    let metas = transfer_hook::pack_extra_account_metas(&[config_address, *pda.key]);
+    create_pda_account(
+        program_id,
+        payer,
+        extra_account_metas,
+        system_program,
+        metas.len(),
+        &[EXTRA_ACCOUNT_METAS_SEED, mint.key.as_ref(), &[metas_bump]],
+    )?;
+    extra_account_metas
+        .data
+        .borrow_mut()
+        .copy_from_slice(&metas);
+
+    create_pda_account(
+        program_id,
+        payer,
+        config_info,
+        system_program,
+        TransferHookConfig::LEN,
+        &[TRANSFER_HOOK_SEED, mint.key.as_ref(), &[config_bump]],
+    )?;
+    let config = TransferHookConfig::new(*mint.key, *pda.key, config_bump);
+
+    write_account(config_info, &config)
+}
+
+// Set or clear an allowlist slot of a transfer hook config (DART only).
+pub(super) fn set_allowlist_entry(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    slot: u8,
+    holder: Pubkey,
+) -> ProgramResult {
+    let SetAllowlistEntryAccounts {
+        pda,
+        dart,
+        config_info,
+        remaining,
+    } = SetAllowlistEntryAccounts::parse(program_id, accounts)?;
+
+    let record = load_record(program_id, pda)?;
+    validate_dart(program_id, dart, remaining, &record.dart)?;
+
+    // The config records its mint, which its address is then checked against.
+    let mint = match TransferHookConfig::try_from_slice(&config_info.data.borrow()) {
+        Ok(config) => config.mint,
+        Err(_) => Pubkey::default(),
+    };
+    let mut config = load_transfer_hook_config(program_id, &mint, config_info)?;
+    if config.vault != *pda.key {
+        msg!("Transfer hook config is linked to another vault");
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let slot = usize::from(slot);
+    if slot >= MAX_ALLOWLIST {
+        msg!("allowlist slot {} out of range", slot);
+        return Err(VaultError::InvalidAllowlistSlot.into());
+    }
+    config.allowlist[slot] = holder;
+
+    write_account(config_info, &config)
+}
+
+// Token-2022 transfer hook: refuse the transfer while the linked vault is frozen,
+// seized or closed, or when the destination owner is off the allowlist. Nothing is
+// written, so a direct call outside a transfer is harmless.
+pub(super) fn transfer_hook_execute(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let TransferHookExecuteAccounts {
+        mint,
+        destination,
+        extra_account_metas,
+        config_info,
+        pda,
+        ..
+    } = TransferHookExecuteAccounts::parse(program_id, accounts)?;
+
+    let (metas_address, _) = transfer_hook::find_extra_account_metas_address(program_id, mint.key);
+    if *extra_account_metas.key != metas_address {
+        msg!("Extra account metas do not match the mint");
+        return Err(ProgramError::InvalidSeeds);
+    }
+    let config = load_transfer_hook_config(program_id, mint.key, config_info)?;
+    if config.vault != *pda.key {
+        msg!("Vault does not match the transfer hook config");
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let record = load_record(program_id, pda)?;
+    if matches!(
+        record.status,
+        VaultStatus::Frozen | VaultStatus::Seized | VaultStatus::Closed
+    ) {
+        msg!("vault status {:?} does not permit transfers", record.status);
+        return Err(VaultError::TransfersRestricted.into());
+    }
+    let holder = transfer_hook::token_account_owner(&destination.data.borrow())
+        .ok_or(ProgramError::InvalidAccountData)?;
+    if !config.is_allowed(&holder) {
+        msg!("{} is not on the allowlist", holder);
+        return Err(VaultError::HolderNotAllowed.into());
+    }
+    Ok(())
+}
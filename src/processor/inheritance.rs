@@ -0,0 +1,196 @@
+use {
+    super::validation::{
+        create_pda_account, instruction_accounts, load_inheritance, load_record, move_vault_index,
+        notify_change, sync_receipt_holder, validate_active, validate_dart, validate_pledge,
+        validate_signer, validate_transfer_window, write_account,
+    },
+    crate::{
+        error::VaultError,
+        event::{self, AuthorityTransferred},
+        state::{InheritanceRecord, NotificationKind, INHERITANCE_SEED},
+    },
+    solana_program::{
+        account_info::AccountInfo, clock::Clock, entrypoint::ProgramResult, msg,
+        program_error::ProgramError, pubkey::Pubkey, sysvar::Sysvar,
+    },
+};
+
+instruction_accounts! {
+    /// Accounts of `SetBeneficiary`.
+    SetBeneficiaryAccounts { pda, authority, inheritance_info, beneficiary, payer, system_program; ..remaining }
+    // The authority may also pay for the inheritance account.
+    unique: [pda, authority, inheritance_info, beneficiary];
+    unique: [pda, inheritance_info, payer];
+    writable: [inheritance_info, payer];
+    readonly: [pda, authority, beneficiary];
+}
+
+instruction_accounts! {
+    /// Accounts of `CheckIn`.
+    CheckInAccounts { pda, authority, inheritance_info; ..remaining }
+    unique: [pda, authority, inheritance_info];
+    writable: [inheritance_info];
+    readonly: [pda, authority];
+}
+
+instruction_accounts! {
+    /// Accounts of `ClaimInheritance`.
+    ClaimInheritanceAccounts { pda, dart, inheritance_info, beneficiary; ..remaining }
+    unique: [pda, dart, inheritance_info, beneficiary];
+    writable: [pda, inheritance_info];
+    readonly: [dart, beneficiary];
+}
+
+// Designate (or revoke) the beneficiary, creating the inheritance account if needed.
+pub(super) fn set_beneficiary(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    inactivity_slots: u64,
+) -> ProgramResult {
+    let SetBeneficiaryAccounts {
+        pda,
+        authority,
+        inheritance_info,
+        beneficiary,
+        payer,
+        system_program,
+        remaining,
+    } = SetBeneficiaryAccounts::parse(program_id, accounts)?;
+
+    let mut record = load_record(program_id, pda)?;
+    validate_active(&record)?;
+    sync_receipt_holder(&mut record, remaining)?;
+    validate_signer(authority, &record.authority, VaultError::IncorrectAuthority)?;
+
+    let revoke = *beneficiary.key == Pubkey::default();
+    if *beneficiary.key == record.dart {
+        msg!("Beneficiary must not be the DART");
+        return Err(VaultError::InvalidBeneficiary.into());
+    }
+    if !revoke && inactivity_slots == 0 {
+        msg!("Inactivity period must be non-zero");
+        return Err(VaultError::InvalidInactivityPeriod.into());
+    }
+
+    let (address, bump) = InheritanceRecord::find_address(program_id, pda.key);
+    if *inheritance_info.key != address {
+        msg!("Inheritance account does not match the vault");
+        return Err(ProgramError::InvalidSeeds);
+    }
+    let mut inheritance = if inheritance_info.owner == program_id {
+        load_inheritance(program_id, pda.key, inheritance_info)?
+    } else {
+        create_pda_account(
+            program_id,
+            payer,
+            inheritance_info,
+            system_program,
+            InheritanceRecord::LEN,
+            &[INHERITANCE_SEED, pda.key.as_ref(), &[bump]],
+        )?;
+        InheritanceRecord::new(*pda.key, bump)
+    };
+
+    inheritance.authority = record.authority;
+    inheritance.beneficiary = *beneficiary.key;
+    inheritance.inactivity_slots = if revoke { 0 } else { inactivity_slots };
+    inheritance.last_activity_slot = Clock::get()?.slot;
+
+    write_account(inheritance_info, &inheritance)
+}
+
+// Restart the inactivity period.
+pub(super) fn check_in(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let CheckInAccounts {
+        pda,
+        authority,
+        inheritance_info,
+        remaining,
+    } = CheckInAccounts::parse(program_id, accounts)?;
+
+    let mut record = load_record(program_id, pda)?;
+    sync_receipt_holder(&mut record, remaining)?;
+    validate_signer(authority, &record.authority, VaultError::IncorrectAuthority)?;
+
+    let mut inheritance = load_inheritance(program_id, pda.key, inheritance_info)?;
+    if inheritance.authority != record.authority {
+        msg!("Beneficiary was designated by a previous authority");
+        return Err(VaultError::NoBeneficiary.into());
+    }
+    inheritance.last_activity_slot = Clock::get()?.slot;
+
+    write_account(inheritance_info, &inheritance)
+}
+
+// Hand the authority role to the beneficiary after the inactivity period.
+pub(super) fn claim_inheritance(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let ClaimInheritanceAccounts {
+        pda,
+        dart,
+        inheritance_info,
+        beneficiary,
+        remaining,
+    } = ClaimInheritanceAccounts::parse(program_id, accounts)?;
+
+    let mut record = load_record(program_id, pda)?;
+    validate_active(&record)?;
+    validate_dart(program_id, dart, remaining, &record.dart)?;
+
+    let mut inheritance = load_inheritance(program_id, pda.key, inheritance_info)?;
+    if inheritance.beneficiary == Pubkey::default() || inheritance.authority != record.authority {
+        msg!("No beneficiary designated by the current authority");
+        return Err(VaultError::NoBeneficiary.into());
+    }
+    validate_signer(
+        beneficiary,
+        &inheritance.beneficiary,
+        VaultError::IncorrectBeneficiary,
+    )?;
+    if *beneficiary.key == record.dart {
+        msg!("New authority must not be the DART");
+        return Err(VaultError::AuthorityIsDart.into());
+    }
+
+    let claimable_at = inheritance.claimable_at_slot()?;
+    if Clock::get()?.slot < claimable_at {
+        msg!("Inheritance claimable at slot {}", claimable_at);
+        return Err(VaultError::InactivityPeriodActive.into());
+    }
+    validate_pledge(program_id, pda.key, &record, remaining)?;
+    validate_transfer_window(program_id, pda.key, &record, remaining)?;
+
+    let previous_authority = record.authority;
+    record.authority = *beneficiary.key;
+    // The beneficiary holds the vault directly, whoever has the receipt.
+    record.receipt_is_authority = false;
+    inheritance.authority = record.authority;
+    inheritance.beneficiary = Pubkey::default();
+    inheritance.inactivity_slots = 0;
+
+    record.pack_into(&mut pda.data.borrow_mut())?;
+    write_account(inheritance_info, &inheritance)?;
+    notify_change(
+        program_id,
+        remaining,
+        NotificationKind::Transfer,
+        pda.key,
+        &previous_authority,
+        &record.authority,
+    )?;
+    move_vault_index(
+        program_id,
+        remaining,
+        pda.key,
+        &previous_authority,
+        &record.authority,
+    )?;
+    event::emit(
+        program_id,
+        accounts,
+        &AuthorityTransferred {
+            vault: *pda.key,
+            previous_authority,
+            authority: record.authority,
+        },
+    )
+}
@@ -0,0 +1,345 @@
+use {
+    super::validation::{
+        create_pda_account, find_account, instruction_accounts, load_dart_policy, load_record,
+        update_vault_index, validate_active, validate_dart, validate_program, validate_role_key,
+        validate_unique,
+    },
+    crate::{
+        error::VaultError,
+        state::{find_asset_vault_address, PolicyDefaults, VaultRecord, ASSET_VAULT_SEED},
+        validation::{require_owner, require_signer},
+    },
+    solana_program::{
+        account_info::AccountInfo,
+        clock::Clock,
+        entrypoint::ProgramResult,
+        entrypoint::MAX_PERMITTED_DATA_INCREASE,
+        msg,
+        program::invoke,
+        program_error::ProgramError,
+        program_pack::IsInitialized,
+        pubkey::Pubkey,
+        rent::Rent,
+        system_instruction::{self, MAX_PERMITTED_DATA_LENGTH},
+        system_program,
+        sysvar::Sysvar,
+    },
+};
+
+instruction_accounts! {
+    /// Accounts of `Initialize` and `InitializeSized`.
+    InitializeAccounts {
+        pda, dart, authority; optional: identity, rent_payer, close_recipient, policy
+    }
+    unique: [pda, dart, authority];
+    writable: [pda];
+    readonly: [dart, authority];
+}
+
+instruction_accounts! {
+    /// Accounts of `CreateAssetVault`.
+    CreateAssetVaultAccounts {
+        pda, dart, authority, payer, system_program; optional: identity, policy
+    }
+    // The DART or the authority may also pay for the vault account.
+    unique: [pda, dart, authority];
+    unique: [pda, payer];
+    writable: [pda, payer];
+    readonly: [dart, authority];
+    signers: [payer];
+}
+
+instruction_accounts! {
+    /// Accounts of `CloneVault`.
+    CloneVaultAccounts { pda, new_pda, dart, authority; ..remaining }
+    unique: [pda, new_pda, dart, authority];
+    writable: [new_pda];
+    readonly: [pda, dart, authority];
+}
+
+instruction_accounts! {
+    /// Accounts of `GrowAccount`.
+    GrowAccountAccounts { pda, dart, payer, system_program; ..remaining }
+    unique: [pda, dart, payer];
+    writable: [pda, payer];
+    readonly: [dart];
+}
+
+// Initialize a vault record (by DART on behalf of a given authority).
+pub(super) fn process_initialize(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data_len: Option<u32>,
+) -> ProgramResult {
+    let InitializeAccounts {
+        pda,
+        dart,
+        authority,
+        identity,
+        rent_payer,
+        close_recipient,
+        policy,
+    } = InitializeAccounts::parse(program_id, accounts)?;
+
+    if let Some(rent_payer) = rent_payer {
+        validate_unique(&[pda, rent_payer])?;
+        require_signer(rent_payer)?;
+    }
+
+    // Check that the owner of the pda is the program.
+    require_owner(pda, program_id)?;
+
+    let record_dart = validate_new_record(program_id, dart, identity, authority)?;
+
+    // Clients built before version 4 and version 6 records size accounts for versions 3
+    // and 5.
+    let legacy_version = match pda.data_len() {
+        VaultRecord::LEN_V3 => Some(VaultRecord::DISCRIMINATOR_VERSION),
+        VaultRecord::LEN_V5 => Some(VaultRecord::CHECKSUM_VERSION),
+        _ => None,
+    }
+    .filter(|_| data_len.is_none());
+
+    // The header stays at fixed offsets; any space past it is metadata capacity.
+    let len = data_len.map_or(VaultRecord::LEN, |data_len| data_len as usize);
+    if legacy_version.is_none()
+        && (len < VaultRecord::LEN
+            || len as u64 > MAX_PERMITTED_DATA_LENGTH
+            || pda.data_len() != len)
+    {
+        msg!(
+            "Data length {} invalid for a {} byte account",
+            len,
+            pda.data_len()
+        );
+        return Err(VaultError::InvalidDataLength.into());
+    }
+
+    let record = VaultRecord::from_account_data(&pda.data.borrow())?;
+    if record.is_initialized() {
+        msg!("Vault record account already initialized");
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+
+    let mut record = VaultRecord::new(*authority.key, record_dart);
+    if let Some(version) = legacy_version {
+        record.version = version;
+    } else {
+        record.data_len = len as u32;
+        record.created_slot = Clock::get()?.slot;
+    }
+    if let Some(rent_payer) = rent_payer {
+        record.rent_payer = *rent_payer.key;
+    }
+    if let Some(policy) = policy {
+        load_dart_policy(program_id, &record_dart, policy)?
+            .defaults
+            .apply(&mut record);
+    }
+    if let Some(close_recipient) = close_recipient {
+        if *close_recipient.key == Pubkey::default() || close_recipient.key == pda.key {
+            msg!("Close recipient must not be the default pubkey or the vault");
+            return Err(VaultError::InvalidCloseRecipient.into());
+        }
+        record.close_recipient = *close_recipient.key;
+    }
+
+    record.pack_into(&mut pda.data.borrow_mut())?;
+    update_vault_index(program_id, accounts, authority.key, pda.key, true)
+}
+
+// Check the DART and authority of a new record, returning the DART it names: the
+// signer, or the DART identity the signer is an operational key of.
+fn validate_new_record(
+    program_id: &Pubkey,
+    dart: &AccountInfo,
+    identity: Option<&AccountInfo>,
+    authority: &AccountInfo,
+) -> Result<Pubkey, ProgramError> {
+    let record_dart = match identity {
+        Some(identity) => {
+            validate_dart(
+                program_id,
+                dart,
+                std::slice::from_ref(identity),
+                identity.key,
+            )?;
+            *identity.key
+        }
+        None => {
+            require_signer(dart)?;
+            *dart.key
+        }
+    };
+    validate_role_key(program_id, &record_dart)?;
+    validate_role_key(program_id, authority.key)?;
+    if *authority.key == record_dart {
+        msg!("Authority must not be the DART");
+        return Err(VaultError::AuthorityIsDart.into());
+    }
+    Ok(record_dart)
+}
+
+// Create and initialize the vault of an authority for one asset at its derived
+// address (by DART on behalf of the authority).
+pub(super) fn create_asset_vault(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    asset_id: &[u8; 32],
+) -> ProgramResult {
+    let CreateAssetVaultAccounts {
+        pda,
+        dart,
+        authority,
+        payer,
+        system_program,
+        identity,
+        policy,
+    } = CreateAssetVaultAccounts::parse(program_id, accounts)?;
+
+    let record_dart = validate_new_record(program_id, dart, identity, authority)?;
+
+    let (address, bump) =
+        find_asset_vault_address(program_id, &record_dart, authority.key, asset_id);
+    if *pda.key != address {
+        msg!("Vault account does not match the DART, authority and asset");
+        return Err(ProgramError::InvalidSeeds);
+    }
+    if pda.owner == program_id {
+        msg!("Vault record account already initialized");
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+    create_pda_account(
+        program_id,
+        payer,
+        pda,
+        system_program,
+        VaultRecord::LEN,
+        &[
+            ASSET_VAULT_SEED,
+            record_dart.as_ref(),
+            authority.key.as_ref(),
+            asset_id,
+            &[bump],
+        ],
+    )?;
+
+    let mut record = VaultRecord::new(*authority.key, record_dart);
+    record.data_len = VaultRecord::LEN as u32;
+    record.created_slot = Clock::get()?.slot;
+    if payer.key != authority.key {
+        record.rent_payer = *payer.key;
+    }
+    if let Some(policy) = policy {
+        load_dart_policy(program_id, &record_dart, policy)?
+            .defaults
+            .apply(&mut record);
+    }
+
+    record.pack_into(&mut pda.data.borrow_mut())?;
+    update_vault_index(program_id, accounts, authority.key, pda.key, true)
+}
+
+// Initialize a vault record for a new authority with the settings and metadata of
+// an existing one (by its DART).
+pub(super) fn clone_vault(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let CloneVaultAccounts {
+        pda,
+        new_pda,
+        dart,
+        authority,
+        remaining,
+    } = CloneVaultAccounts::parse(program_id, accounts)?;
+
+    let source = load_record(program_id, pda)?;
+    validate_active(&source)?;
+    validate_dart(program_id, dart, remaining, &source.dart)?;
+    validate_role_key(program_id, authority.key)?;
+    if *authority.key == source.dart {
+        msg!("Authority must not be the DART");
+        return Err(VaultError::AuthorityIsDart.into());
+    }
+
+    require_owner(new_pda, program_id)?;
+    let source_data = pda.data.borrow();
+    let metadata = &source_data[source.header_len()..];
+    let len = VaultRecord::LEN + metadata.len();
+    if new_pda.data_len() != len {
+        msg!(
+            "New vault account must be {} bytes, not {}",
+            len,
+            new_pda.data_len()
+        );
+        return Err(VaultError::InvalidDataLength.into());
+    }
+    if VaultRecord::from_account_data(&new_pda.data.borrow())?.is_initialized() {
+        msg!("New vault account already initialized");
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+
+    let mut record = VaultRecord::new(*authority.key, source.dart);
+    record.data_len = len as u32;
+    record.created_slot = Clock::get()?.slot;
+    // The close recipient pays out of the new vault too, so it carries over only when
+    // the DART passes it.
+    let mut defaults = PolicyDefaults::of(&source);
+    if find_account(remaining, &source.close_recipient).is_none() {
+        defaults.close_recipient = Pubkey::default();
+    }
+    defaults.apply(&mut record);
+
+    new_pda.data.borrow_mut()[VaultRecord::LEN..].copy_from_slice(metadata);
+    record.pack_into(&mut new_pda.data.borrow_mut())?;
+    update_vault_index(program_id, remaining, authority.key, new_pda.key, true)
+}
+
+// Grow the vault account one realloc step toward `target_len` (DART only).
+pub(super) fn grow_account(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    target_len: u32,
+) -> ProgramResult {
+    let GrowAccountAccounts {
+        pda,
+        dart,
+        payer,
+        system_program,
+        remaining,
+    } = GrowAccountAccounts::parse(program_id, accounts)?;
+    validate_program(system_program, &system_program::id())?;
+
+    let mut record = load_record(program_id, pda)?;
+    validate_active(&record)?;
+    validate_dart(program_id, dart, remaining, &record.dart)?;
+
+    let current_len = pda.data_len();
+    let target = target_len as usize;
+    if target < current_len || target as u64 > MAX_PERMITTED_DATA_LENGTH {
+        msg!(
+            "Target length {} invalid for a {} byte account",
+            target,
+            current_len
+        );
+        return Err(VaultError::InvalidDataLength.into());
+    }
+    if target == current_len {
+        info!("Account already {} bytes", current_len);
+        return Ok(());
+    }
+
+    let new_len = target.min(current_len + MAX_PERMITTED_DATA_INCREASE);
+    let rent = Rent::get()?.minimum_balance(new_len);
+    let shortfall = rent.saturating_sub(pda.lamports());
+    if shortfall > 0 {
+        invoke(
+            &system_instruction::transfer(payer.key, pda.key, shortfall),
+            &[payer.clone(), pda.clone(), system_program.clone()],
+        )?;
+    }
+    pda.realloc(new_len, true)?;
+
+    record.data_len = new_len as u32;
+    record.target_len = target_len;
+
+    record.pack_into(&mut pda.data.borrow_mut())
+}
@@ -0,0 +1,224 @@
+use {
+    super::validation::{
+        create_pda_account, instruction_accounts, load_record, validate_active, validate_dart,
+        validate_unique, write_account,
+    },
+    crate::{
+        oracle,
+        state::{Attestation, AttestationList, ATTESTATIONS_SEED},
+        validation::require_initialized,
+    },
+    borsh::BorshDeserialize,
+    solana_program::{
+        account_info::AccountInfo, clock::Clock, entrypoint::ProgramResult, msg,
+        program_error::ProgramError, pubkey::Pubkey, sysvar::Sysvar,
+    },
+};
+
+instruction_accounts! {
+    /// Accounts of `SetTag`.
+    SetTagAccounts { pda, dart; ..remaining }
+    unique: [pda, dart];
+    writable: [pda];
+    readonly: [dart];
+}
+
+instruction_accounts! {
+    /// Accounts of `RecordAttestation`.
+    RecordAttestationAccounts { pda, dart; ..remaining }
+    unique: [pda, dart];
+    writable: [pda];
+    readonly: [dart];
+}
+
+instruction_accounts! {
+    /// Accounts of `AddAttestation`.
+    AddAttestationAccounts { pda, dart, list, payer, system_program; ..remaining }
+    // The DART may also pay for the attestation list.
+    unique: [pda, dart, list];
+    unique: [pda, list, payer];
+    writable: [list, payer];
+    readonly: [pda, dart];
+    owned: [pda];
+}
+
+instruction_accounts! {
+    /// Accounts of `RemoveAttestation`.
+    RemoveAttestationAccounts { pda, dart, list; ..remaining }
+    unique: [pda, dart, list];
+    writable: [list];
+    readonly: [pda, dart];
+    owned: [pda, list];
+}
+
+instruction_accounts! {
+    /// Accounts of `SetOracle`.
+    SetOracleAccounts { pda, dart; optional: feed; ..remaining }
+    unique: [pda, dart];
+    writable: [pda];
+    readonly: [dart];
+}
+
+// Set the record's category tag (DART only).
+pub(super) fn set_tag(program_id: &Pubkey, accounts: &[AccountInfo], tag: u16) -> ProgramResult {
+    let SetTagAccounts {
+        pda,
+        dart,
+        remaining,
+    } = SetTagAccounts::parse(program_id, accounts)?;
+
+    let mut record = load_record(program_id, pda)?;
+    validate_active(&record)?;
+    validate_dart(program_id, dart, remaining, &record.dart)?;
+
+    record.tag = tag;
+
+    record.pack_into(&mut pda.data.borrow_mut())
+}
+
+// Anchor a Wormhole VAA digest to a vault record (DART only).
+pub(super) fn record_attestation(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    vaa_hash: [u8; 32],
+) -> ProgramResult {
+    let RecordAttestationAccounts {
+        pda,
+        dart,
+        remaining,
+    } = RecordAttestationAccounts::parse(program_id, accounts)?;
+
+    let mut record = load_record(program_id, pda)?;
+    validate_active(&record)?;
+    validate_dart(program_id, dart, remaining, &record.dart)?;
+
+    record.attestation = vaa_hash;
+
+    record.pack_into(&mut pda.data.borrow_mut())
+}
+
+// Gate authority changes of a vault record on a price feed (DART only).
+pub(super) fn set_oracle(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    max_staleness_slots: u64,
+    min_price: i64,
+) -> ProgramResult {
+    let SetOracleAccounts {
+        pda,
+        dart,
+        feed,
+        remaining,
+    } = SetOracleAccounts::parse(program_id, accounts)?;
+
+    let mut record = load_record(program_id, pda)?;
+    validate_active(&record)?;
+    validate_dart(program_id, dart, remaining, &record.dart)?;
+
+    match feed {
+        Some(feed) => {
+            validate_unique(&[pda, dart, feed])?;
+            oracle::read_feed(&feed.data.borrow())?;
+            record.oracle = *feed.key;
+            record.oracle_max_staleness_slots = max_staleness_slots;
+            record.oracle_min_price = min_price;
+        }
+        None => {
+            record.oracle = Pubkey::default();
+            record.oracle_max_staleness_slots = 0;
+            record.oracle_min_price = 0;
+        }
+    }
+
+    record.pack_into(&mut pda.data.borrow_mut())
+}
+
+// Attach an attestation to a vault record (DART only), creating its attestation list if
+// needed.
+pub(super) fn add_attestation(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    kind: u16,
+    hash: [u8; 32],
+    expires_at_slot: u64,
+) -> ProgramResult {
+    let AddAttestationAccounts {
+        pda,
+        dart,
+        list,
+        payer,
+        system_program,
+        remaining,
+    } = AddAttestationAccounts::parse(program_id, accounts)?;
+
+    let record = load_record(program_id, pda)?;
+    validate_active(&record)?;
+    validate_dart(program_id, dart, remaining, &record.dart)?;
+
+    let slot = Clock::get()?.slot;
+    let attestation = Attestation {
+        kind,
+        hash,
+        expires_at_slot,
+    };
+    if !attestation.is_live(slot) {
+        msg!("Attestation is empty or already expired");
+        return Err(ProgramError::InvalidArgument);
+    }
+    let (address, bump) = AttestationList::find_address(program_id, pda.key);
+    if *list.key != address {
+        msg!("Attestation list does not match the vault");
+        return Err(ProgramError::InvalidSeeds);
+    }
+    let mut attestations = if list.owner == program_id {
+        load_attestation_list(list)?
+    } else {
+        create_pda_account(
+            program_id,
+            payer,
+            list,
+            system_program,
+            AttestationList::LEN,
+            &[ATTESTATIONS_SEED, pda.key.as_ref(), &[bump]],
+        )?;
+        AttestationList::new(*pda.key, bump)
+    };
+    attestations.add(attestation, slot)?;
+
+    write_account(list, &attestations)
+}
+
+// Withdraw an attestation of a vault record (DART only).
+pub(super) fn remove_attestation(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    kind: u16,
+    hash: [u8; 32],
+) -> ProgramResult {
+    let RemoveAttestationAccounts {
+        pda,
+        dart,
+        list,
+        remaining,
+    } = RemoveAttestationAccounts::parse(program_id, accounts)?;
+
+    let record = load_record(program_id, pda)?;
+    validate_active(&record)?;
+    validate_dart(program_id, dart, remaining, &record.dart)?;
+
+    let mut attestations = load_attestation_list(list)?;
+    if attestations.vault != *pda.key {
+        msg!("Attestation list does not match the vault");
+        return Err(ProgramError::InvalidSeeds);
+    }
+    attestations.remove(kind, &hash)?;
+
+    write_account(list, &attestations)
+}
+
+// Deserialize an attestation list, checking its discriminator and version.
+fn load_attestation_list(list: &AccountInfo) -> Result<AttestationList, ProgramError> {
+    let attestations = AttestationList::try_from_slice(&list.data.borrow())?;
+    require_initialized(list, &attestations)?;
+    Ok(attestations)
+}
@@ -0,0 +1,782 @@
+//! Instruction processing. `Processor` decodes an instruction and dispatches it to
+//! the handler module for its group; the checks and loaders the handlers share live
+//! in `validation`.
+use {
+    self::validation::{
+        consume_rate_limit, count_invocation, find_audit_trail, find_program_stats, record_audit,
+        validate_cosign, validate_not_migrated, validate_valid_until_slot,
+    },
+    crate::{
+        instruction::{self, VaultInstruction},
+        transfer_hook,
+    },
+    solana_program::{account_info::AccountInfo, entrypoint::ProgramResult, pubkey::Pubkey},
+};
+
+mod audit;
+mod close;
+mod commitment;
+mod config;
+mod crank;
+mod dart;
+mod distribution;
+mod hook;
+mod inheritance;
+mod initialize;
+mod metadata;
+mod note;
+mod notify;
+mod pledge;
+mod position;
+mod rate_limit;
+mod receipt;
+mod recovery;
+mod session;
+mod transfer;
+mod validation;
+mod vault_index;
+mod voting;
+mod window;
+
+/// Instruction processor
+pub struct Processor {}
+
+impl Processor {
+    pub fn process_instruction(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        input: &[u8],
+    ) -> ProgramResult {
+        #[cfg(feature = "emit-cpi")]
+        if input.starts_with(&crate::event::EVENT_IX_TAG_LE) {
+            return crate::event::process_event(program_id, accounts);
+        }
+        if transfer_hook::is_execute(input) {
+            info!("TransferHookInstruction::Execute");
+            return hook::transfer_hook_execute(program_id, accounts);
+        }
+        let (input, valid_until_slot) = instruction::unpack_envelope(input)?;
+        if let Some(valid_until_slot) = valid_until_slot {
+            validate_valid_until_slot(valid_until_slot)?;
+        }
+        #[cfg(feature = "anchor")]
+        let instruction = crate::anchor::unpack_instruction(input)?;
+        #[cfg(not(feature = "anchor"))]
+        let instruction = VaultInstruction::unpack(input)?;
+        if instruction.requires_program_config() {
+            validate_not_migrated(program_id, accounts)?;
+        }
+        let mut trail = None;
+        if instruction.is_rate_limited() {
+            consume_rate_limit(program_id, accounts)?;
+            if let Some(pda) = accounts.first() {
+                validate_cosign(program_id, pda, accounts)?;
+                trail = find_audit_trail(program_id, pda, accounts)?;
+            }
+        }
+        // The handlers take the instruction by value; keep its tag for the audit trail
+        // and stats.
+        let stats = find_program_stats(program_id, accounts);
+        let tag = instruction.tag();
+        match instruction {
+            VaultInstruction::Initialize => {
+                info!("VaultInstruction::Initialize");
+                initialize::process_initialize(program_id, accounts, None)
+            }
+            VaultInstruction::TransferAuthority => {
+                info!("VaultInstruction::TransferAuthority");
+                transfer::transfer_authority(program_id, accounts, None)
+            }
+            VaultInstruction::CloseAccount => {
+                info!("VaultInstruction::CloseAccount");
+                close::close_account(program_id, accounts, false, None)
+            }
+            VaultInstruction::Reopen => {
+                info!("VaultInstruction::Reopen");
+                close::reopen(program_id, accounts)
+            }
+            VaultInstruction::ProposeDart => {
+                info!("VaultInstruction::ProposeDart");
+                dart::propose_dart(program_id, accounts)
+            }
+            VaultInstruction::AcceptDart => {
+                info!("VaultInstruction::AcceptDart");
+                dart::accept_dart(program_id, accounts)
+            }
+            VaultInstruction::InitializeDartIdentity => {
+                info!("VaultInstruction::InitializeDartIdentity");
+                dart::initialize_dart_identity(program_id, accounts)
+            }
+            VaultInstruction::SetOperationalKey { slot, key } => {
+                info!("VaultInstruction::SetOperationalKey");
+                dart::set_operational_key(program_id, accounts, slot, key)
+            }
+            VaultInstruction::SetDartQuorum { quorum } => {
+                info!("VaultInstruction::SetDartQuorum");
+                dart::set_dart_quorum(program_id, accounts, quorum)
+            }
+            VaultInstruction::InitializeConfig => {
+                info!("VaultInstruction::InitializeConfig");
+                config::initialize_config(program_id, accounts)
+            }
+            VaultInstruction::Decommission { successor } => {
+                info!("VaultInstruction::Decommission");
+                config::decommission(program_id, accounts, successor)
+            }
+            VaultInstruction::CreateSession {
+                key,
+                expires_at_slot,
+                scope,
+            } => {
+                info!("VaultInstruction::CreateSession");
+                session::create_session(program_id, accounts, key, expires_at_slot, scope)
+            }
+            VaultInstruction::AddGuardian { threshold } => {
+                info!("VaultInstruction::AddGuardian");
+                recovery::add_guardian(program_id, accounts, threshold)
+            }
+            VaultInstruction::RemoveGuardian { threshold } => {
+                info!("VaultInstruction::RemoveGuardian");
+                recovery::remove_guardian(program_id, accounts, threshold)
+            }
+            VaultInstruction::InitiateRecovery => {
+                info!("VaultInstruction::InitiateRecovery");
+                recovery::initiate_recovery(program_id, accounts)
+            }
+            VaultInstruction::ApproveRecovery => {
+                info!("VaultInstruction::ApproveRecovery");
+                recovery::approve_recovery(program_id, accounts)
+            }
+            VaultInstruction::CancelRecovery => {
+                info!("VaultInstruction::CancelRecovery");
+                recovery::cancel_recovery(program_id, accounts)
+            }
+            VaultInstruction::CompleteRecovery => {
+                info!("VaultInstruction::CompleteRecovery");
+                recovery::complete_recovery(program_id, accounts)
+            }
+            VaultInstruction::SetBeneficiary { inactivity_slots } => {
+                info!("VaultInstruction::SetBeneficiary");
+                inheritance::set_beneficiary(program_id, accounts, inactivity_slots)
+            }
+            VaultInstruction::CheckIn => {
+                info!("VaultInstruction::CheckIn");
+                inheritance::check_in(program_id, accounts)
+            }
+            VaultInstruction::ClaimInheritance => {
+                info!("VaultInstruction::ClaimInheritance");
+                inheritance::claim_inheritance(program_id, accounts)
+            }
+            VaultInstruction::SetTag { tag } => {
+                info!("VaultInstruction::SetTag");
+                metadata::set_tag(program_id, accounts, tag)
+            }
+            VaultInstruction::InitializeSized { data_len } => {
+                info!("VaultInstruction::InitializeSized");
+                initialize::process_initialize(program_id, accounts, Some(data_len))
+            }
+            VaultInstruction::GrowAccount { target_len } => {
+                info!("VaultInstruction::GrowAccount");
+                initialize::grow_account(program_id, accounts, target_len)
+            }
+            VaultInstruction::CloseAccountWithRefund { refund_authority } => {
+                info!("VaultInstruction::CloseAccountWithRefund");
+                close::close_account(program_id, accounts, refund_authority, None)
+            }
+            VaultInstruction::RecordAttestation { vaa_hash } => {
+                info!("VaultInstruction::RecordAttestation");
+                metadata::record_attestation(program_id, accounts, vaa_hash)
+            }
+            VaultInstruction::SwapAuthorities => {
+                info!("VaultInstruction::SwapAuthorities");
+                transfer::swap_authorities(program_id, accounts)
+            }
+            VaultInstruction::ProposeTransfer { timeout_slots } => {
+                info!("VaultInstruction::ProposeTransfer");
+                transfer::propose_transfer(program_id, accounts, timeout_slots)
+            }
+            VaultInstruction::AcceptTransfer => {
+                info!("VaultInstruction::AcceptTransfer");
+                transfer::accept_transfer(program_id, accounts)
+            }
+            VaultInstruction::ExpireTransfer => {
+                info!("VaultInstruction::ExpireTransfer");
+                transfer::expire_transfer(program_id, accounts)
+            }
+            VaultInstruction::SetOracle {
+                max_staleness_slots,
+                min_price,
+            } => {
+                info!("VaultInstruction::SetOracle");
+                metadata::set_oracle(program_id, accounts, max_staleness_slots, min_price)
+            }
+            VaultInstruction::MintReceipt => {
+                info!("VaultInstruction::MintReceipt");
+                receipt::mint_receipt(program_id, accounts)
+            }
+            VaultInstruction::SetReceiptAuthority { enabled } => {
+                info!("VaultInstruction::SetReceiptAuthority");
+                receipt::set_receipt_authority(program_id, accounts, enabled)
+            }
+            VaultInstruction::InitializeTransferHook => {
+                info!("VaultInstruction::InitializeTransferHook");
+                hook::initialize_transfer_hook(program_id, accounts)
+            }
+            VaultInstruction::SetAllowlistEntry { slot, holder } => {
+                info!("VaultInstruction::SetAllowlistEntry");
+                hook::set_allowlist_entry(program_id, accounts, slot, holder)
+            }
+            VaultInstruction::MigrateToProgram => {
+                info!("VaultInstruction::MigrateToProgram");
+                close::migrate_to_program(program_id, accounts)
+            }
+            VaultInstruction::SetApprovalRoot {
+                root,
+                expires_at_slot,
+            } => {
+                info!("VaultInstruction::SetApprovalRoot");
+                dart::set_approval_root(program_id, accounts, root, expires_at_slot)
+            }
+            VaultInstruction::TransferWithProof { proof } => {
+                info!("VaultInstruction::TransferWithProof");
+                transfer::transfer_with_proof(program_id, accounts, &proof)
+            }
+            VaultInstruction::SetNoteKey { note_key } => {
+                info!("VaultInstruction::SetNoteKey");
+                note::set_note_key(program_id, accounts, note_key)
+            }
+            VaultInstruction::SetNote { encrypted_note } => {
+                info!("VaultInstruction::SetNote");
+                note::set_note(program_id, accounts, encrypted_note)
+            }
+            VaultInstruction::UpdateCommitments { commitments } => {
+                info!("VaultInstruction::UpdateCommitments");
+                commitment::update_commitments(program_id, accounts, commitments)
+            }
+            VaultInstruction::AddNote { hash, category } => {
+                info!("VaultInstruction::AddNote");
+                note::add_note(program_id, accounts, hash, category)
+            }
+            VaultInstruction::AddAttestation {
+                kind,
+                hash,
+                expires_at_slot,
+            } => {
+                info!("VaultInstruction::AddAttestation");
+                metadata::add_attestation(program_id, accounts, kind, hash, expires_at_slot)
+            }
+            VaultInstruction::RemoveAttestation { kind, hash } => {
+                info!("VaultInstruction::RemoveAttestation");
+                metadata::remove_attestation(program_id, accounts, kind, hash)
+            }
+            VaultInstruction::PledgeAsCollateral { lender } => {
+                info!("VaultInstruction::PledgeAsCollateral");
+                pledge::pledge_as_collateral(program_id, accounts, lender)
+            }
+            VaultInstruction::ReleaseCollateral => {
+                info!("VaultInstruction::ReleaseCollateral");
+                pledge::release_collateral(program_id, accounts)
+            }
+            VaultInstruction::SetPosition { asset_id, balance } => {
+                info!("VaultInstruction::SetPosition");
+                position::set_position(program_id, accounts, asset_id, balance)
+            }
+            VaultInstruction::ApplyCorporateAction { action_type, ratio } => {
+                info!("VaultInstruction::ApplyCorporateAction");
+                position::apply_corporate_action(program_id, accounts, action_type, ratio)
+            }
+            VaultInstruction::Distribute {
+                id,
+                asset_id,
+                amount_per_unit,
+            } => {
+                info!("VaultInstruction::Distribute");
+                distribution::distribute(program_id, accounts, id, asset_id, amount_per_unit)
+            }
+            VaultInstruction::Snapshot { record_date_slot } => {
+                info!("VaultInstruction::Snapshot");
+                position::snapshot(program_id, accounts, record_date_slot)
+            }
+            VaultInstruction::DelegateVoting { proxy } => {
+                info!("VaultInstruction::DelegateVoting");
+                voting::delegate_voting(program_id, accounts, proxy)
+            }
+            VaultInstruction::SetTransferWindow {
+                lockup_until_slot,
+                blackouts,
+            } => {
+                info!("VaultInstruction::SetTransferWindow");
+                window::set_transfer_window(program_id, accounts, lockup_until_slot, blackouts)
+            }
+            VaultInstruction::SetDartTransferWindow {
+                lockup_until_slot,
+                blackouts,
+            } => {
+                info!("VaultInstruction::SetDartTransferWindow");
+                window::set_dart_transfer_window(program_id, accounts, lockup_until_slot, blackouts)
+            }
+            VaultInstruction::SetRateLimit {
+                max_mutations_per_epoch,
+            } => {
+                info!("VaultInstruction::SetRateLimit");
+                rate_limit::set_rate_limit(program_id, accounts, max_mutations_per_epoch)
+            }
+            VaultInstruction::SetDartPolicy { defaults } => {
+                info!("VaultInstruction::SetDartPolicy");
+                dart::set_dart_policy(program_id, accounts, defaults)
+            }
+            VaultInstruction::CloneVault => {
+                info!("VaultInstruction::CloneVault");
+                initialize::clone_vault(program_id, accounts)
+            }
+            VaultInstruction::CreateAssetVault { asset_id } => {
+                info!("VaultInstruction::CreateAssetVault");
+                initialize::create_asset_vault(program_id, accounts, &asset_id)
+            }
+            VaultInstruction::SyncVaultIndex => {
+                info!("VaultInstruction::SyncVaultIndex");
+                vault_index::sync_vault_index(program_id, accounts)
+            }
+            VaultInstruction::SetCrankRules {
+                bounty_lamports,
+                grace_slots,
+            } => {
+                info!("VaultInstruction::SetCrankRules");
+                config::set_crank_rules(program_id, accounts, bounty_lamports, grace_slots)
+            }
+            VaultInstruction::Crank => {
+                info!("VaultInstruction::Crank");
+                crank::crank(program_id, accounts)
+            }
+            VaultInstruction::SetFeeExemption { slot, key } => {
+                info!("VaultInstruction::SetFeeExemption");
+                config::set_fee_exemption(program_id, accounts, slot, key)
+            }
+            VaultInstruction::SetFeeDestination { destination } => {
+                info!("VaultInstruction::SetFeeDestination");
+                config::set_fee_destination(program_id, accounts, destination)
+            }
+            VaultInstruction::CollectFees => {
+                info!("VaultInstruction::CollectFees");
+                config::collect_fees(program_id, accounts)
+            }
+            VaultInstruction::Rekey => {
+                info!("VaultInstruction::Rekey");
+                close::rekey(program_id, accounts)
+            }
+            VaultInstruction::TransferAuthorityChecked { expected_authority } => {
+                info!("VaultInstruction::TransferAuthorityChecked");
+                transfer::transfer_authority(program_id, accounts, Some(&expected_authority))
+            }
+            VaultInstruction::CloseAccountChecked {
+                expected_authority,
+                refund_authority,
+            } => {
+                info!("VaultInstruction::CloseAccountChecked");
+                close::close_account(
+                    program_id,
+                    accounts,
+                    refund_authority,
+                    Some(&expected_authority),
+                )
+            }
+            VaultInstruction::CreateAuditTrail { capacity } => {
+                info!("VaultInstruction::CreateAuditTrail");
+                audit::create_audit_trail(program_id, accounts, capacity)
+            }
+            VaultInstruction::InitializeStats => {
+                info!("VaultInstruction::InitializeStats");
+                config::initialize_stats(program_id, accounts)
+            }
+            VaultInstruction::SetCloseFees {
+                default_lamports,
+                tiers,
+            } => {
+                info!("VaultInstruction::SetCloseFees");
+                config::set_close_fees(program_id, accounts, default_lamports, tiers)
+            }
+            VaultInstruction::SetNotify { notify } => {
+                info!("VaultInstruction::SetNotify");
+                notify::set_notify(program_id, accounts, notify)
+            }
+            VaultInstruction::CheckTransfer => {
+                info!("VaultInstruction::CheckTransfer");
+                transfer::check_transfer(program_id, accounts)
+            }
+        }?;
+        if let Some(trail) = trail {
+            record_audit(trail, accounts, tag)?;
+        }
+        if let Some(stats) = stats {
+            count_invocation(stats, tag)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    //! Property tests of the processor, run in process on generated account states:
+    //! arbitrary record bytes, signer combinations and instruction sequences. Like
+    //! `tests/invariants.rs`, cases come from a seeded xorshift generator rather than a
+    //! property testing dependency. Runs use a fixed seed, so they are reproducible; set
+    //! `VAULT_PROPTEST_SEED` to explore others.
+    use {
+        super::*,
+        crate::state::{FeeConfig, ProgramConfig, VaultRecord, VaultStatus},
+        solana_program::{
+            clock::Clock, entrypoint::SUCCESS, instruction::Instruction,
+            program_pack::IsInitialized, program_stubs,
+        },
+        std::sync::Once,
+    };
+
+    const CASES: usize = 256;
+    const STEPS: usize = 32;
+    // Seed of every run unless `VAULT_PROPTEST_SEED` replaces it.
+    const DEFAULT_SEED: u64 = 0x5eed_5eed_5eed_5eed;
+
+    // xorshift64*, as in `tests/invariants.rs`.
+    struct Rng(u64);
+
+    impl Rng {
+        fn from_env() -> Self {
+            let seed = std::env::var("VAULT_PROPTEST_SEED")
+                .ok()
+                .and_then(|seed| seed.parse().ok())
+                .unwrap_or(DEFAULT_SEED)
+                | 1;
+            Self(seed)
+        }
+
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 >> 12;
+            self.0 ^= self.0 << 25;
+            self.0 ^= self.0 >> 27;
+            self.0.wrapping_mul(0x2545_f491_4f6c_dd1d)
+        }
+
+        fn below(&mut self, n: usize) -> usize {
+            (self.next() % n as u64) as usize
+        }
+
+        fn flip(&mut self) -> bool {
+            self.next() & 1 == 1
+        }
+
+        fn bytes(&mut self, len: usize) -> Vec<u8> {
+            (0..len).map(|_| self.next() as u8).collect()
+        }
+    }
+
+    // Serves the clock sysvar, read on close for its receipt, in process.
+    struct ClockStub;
+
+    impl program_stubs::SyscallStubs for ClockStub {
+        fn sol_get_clock_sysvar(&self, var_addr: *mut u8) -> u64 {
+            // SAFETY: `Clock::get` passes a pointer to a `Clock` it owns.
+            unsafe { *(var_addr as *mut Clock) = Clock::default() };
+            SUCCESS
+        }
+    }
+
+    #[derive(Clone, Debug)]
+    struct Account {
+        key: Pubkey,
+        owner: Pubkey,
+        lamports: u64,
+        data: Vec<u8>,
+    }
+
+    // Accounts of one vault: the record, its DART, an impostor, candidate authorities and
+    // the uncreated program and fee configs.
+    #[derive(Clone, Debug)]
+    struct World {
+        accounts: Vec<Account>,
+    }
+
+    const PDA: usize = 0;
+    const DART: usize = 1;
+    const IMPOSTOR: usize = 2;
+    const AUTHORITIES: std::ops::Range<usize> = 3..6;
+    const CONFIG: usize = AUTHORITIES.end;
+    const FEE_CONFIG: usize = CONFIG + 1;
+
+    impl World {
+        fn new(rng: &mut Rng, pda_data: Vec<u8>) -> Self {
+            static STUBS: Once = Once::new();
+            STUBS.call_once(|| {
+                program_stubs::set_syscall_stubs(Box::new(ClockStub));
+            });
+            let accounts = (0..=FEE_CONFIG)
+                .map(|index| Account {
+                    key: match index {
+                        CONFIG => ProgramConfig::find_address(&crate::id()).0,
+                        FEE_CONFIG => FeeConfig::find_address(&crate::id()).0,
+                        _ => Pubkey::new_unique(),
+                    },
+                    owner: if index == PDA {
+                        crate::id()
+                    } else {
+                        Pubkey::default()
+                    },
+                    lamports: rng.next() % 1_000_000_000,
+                    data: if index == PDA {
+                        pda_data.clone()
+                    } else {
+                        vec![]
+                    },
+                })
+                .collect();
+            Self { accounts }
+        }
+
+        fn key(&self, index: usize) -> Pubkey {
+            self.accounts[index].key
+        }
+
+        fn record(&self) -> Option<VaultRecord> {
+            VaultRecord::from_account_data(&self.accounts[PDA].data)
+                .ok()
+                .filter(IsInitialized::is_initialized)
+        }
+
+        fn lamports(&self) -> u128 {
+            self.accounts
+                .iter()
+                .map(|account| u128::from(account.lamports))
+                .sum()
+        }
+
+        // Run `instruction` as the runtime would: with the signer and writable flags of
+        // its metas, and rolled back if it fails. Metas the world does not hold, like the
+        // DART's transfer window, name accounts nobody created.
+        fn process(&mut self, instruction: &Instruction) -> ProgramResult {
+            for meta in &instruction.accounts {
+                if self
+                    .accounts
+                    .iter()
+                    .all(|account| account.key != meta.pubkey)
+                {
+                    self.accounts.push(Account {
+                        key: meta.pubkey,
+                        owner: Pubkey::default(),
+                        lamports: 0,
+                        data: vec![],
+                    });
+                }
+            }
+            let snapshot = self.clone();
+            let result = {
+                let infos: Vec<AccountInfo> = self
+                    .accounts
+                    .iter_mut()
+                    .map(|account| {
+                        AccountInfo::new(
+                            &account.key,
+                            false,
+                            false,
+                            &mut account.lamports,
+                            &mut account.data,
+                            &account.owner,
+                            false,
+                            0,
+                        )
+                    })
+                    .collect();
+                let accounts: Vec<AccountInfo> = instruction
+                    .accounts
+                    .iter()
+                    .map(|meta| {
+                        let mut info = infos
+                            .iter()
+                            .find(|info| *info.key == meta.pubkey)
+                            .expect("instruction account outside the world")
+                            .clone();
+                        info.is_signer = meta.is_signer;
+                        info.is_writable = meta.is_writable;
+                        info
+                    })
+                    .collect();
+                Processor::process_instruction(&crate::id(), &accounts, &instruction.data)
+            };
+            if result.is_err() {
+                *self = snapshot;
+            }
+            result
+        }
+    }
+
+    // Drop the signature of `index` from `instruction`, or replace it by the impostor,
+    // at random.
+    fn tamper(rng: &mut Rng, world: &World, instruction: &mut Instruction, index: usize) {
+        let key = world.key(index);
+        let (signed, replaced) = (rng.below(4) != 0, rng.below(4) == 0);
+        for meta in instruction
+            .accounts
+            .iter_mut()
+            .filter(|meta| meta.pubkey == key)
+        {
+            meta.is_signer &= signed;
+            if replaced {
+                meta.pubkey = world.key(IMPOSTOR);
+            }
+        }
+    }
+
+    // Whether `key` signs `instruction`.
+    fn signs(instruction: &Instruction, key: &Pubkey) -> bool {
+        instruction
+            .accounts
+            .iter()
+            .any(|meta| meta.pubkey == *key && meta.is_signer)
+    }
+
+    #[test]
+    fn arbitrary_record_bytes_only_succeed_when_initialized() {
+        let mut rng = Rng::from_env();
+        for case in 0..CASES {
+            // Random bytes of random length, or a valid record with random corruption.
+            let mut data = if rng.flip() {
+                let len = rng.below(VaultRecord::LEN + 64);
+                rng.bytes(len)
+            } else {
+                let mut data = vec![0; VaultRecord::LEN];
+                let record = VaultRecord::new(Pubkey::new_unique(), Pubkey::new_unique());
+                record.pack_into(&mut data).unwrap();
+                data
+            };
+            for _ in 0..rng.below(3) {
+                if !data.is_empty() {
+                    let index = rng.below(data.len());
+                    data[index] = rng.next() as u8;
+                }
+            }
+            let mut world = World::new(&mut rng, data);
+            let before = world.record();
+
+            // Whatever the bytes hold, take the DART and authority from them when they
+            // decode, so the signatures are not what fails.
+            let (dart, authority) = match &before {
+                Some(record) => (record.dart, record.authority),
+                None => (world.key(DART), world.key(AUTHORITIES.start)),
+            };
+            world.accounts[DART].key = dart;
+            world.accounts[AUTHORITIES.start].key = authority;
+            let pda = world.key(PDA);
+            let instruction = match rng.below(3) {
+                0 => instruction::transfer_authority(
+                    crate::id(),
+                    &pda,
+                    &dart,
+                    &authority,
+                    &world.key(AUTHORITIES.start + 1),
+                ),
+                1 => instruction::close_account(crate::id(), &pda, &dart, &authority),
+                _ => instruction::initialize(
+                    crate::id(),
+                    &pda,
+                    &dart,
+                    &world.key(AUTHORITIES.start + 1),
+                ),
+            };
+            let result = world.process(&instruction);
+            let trace = format!("case {case}: {:?} -> {result:?}", instruction.data);
+
+            let initializes = matches!(
+                VaultInstruction::unpack(&instruction.data),
+                Ok(VaultInstruction::Initialize)
+            );
+            if result.is_ok() {
+                assert_eq!(before.is_some(), !initializes, "{trace}");
+                if let Some(before) = before {
+                    assert_eq!(before.status, VaultStatus::Active, "{trace}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn authority_only_changes_with_both_signatures() {
+        let mut rng = Rng::from_env();
+        for case in 0..CASES {
+            let mut world = World::new(&mut rng, vec![0; VaultRecord::LEN]);
+            let pda = world.key(PDA);
+            let (dart, authority) = (world.key(DART), world.key(AUTHORITIES.start));
+            let initialize = instruction::initialize(crate::id(), &pda, &dart, &authority);
+            world.process(&initialize).unwrap();
+
+            let mut transfer = instruction::transfer_authority(
+                crate::id(),
+                &pda,
+                &dart,
+                &authority,
+                &world.key(AUTHORITIES.start + 1),
+            );
+            tamper(&mut rng, &world, &mut transfer, DART);
+            tamper(&mut rng, &world, &mut transfer, AUTHORITIES.start);
+            let both = signs(&transfer, &dart) && signs(&transfer, &authority);
+            let result = world.process(&transfer);
+
+            let changed = world.record().unwrap().authority != authority;
+            assert_eq!(changed, both, "case {case}: {transfer:?} -> {result:?}");
+        }
+    }
+
+    #[test]
+    fn sequences_conserve_lamports_and_keep_closed_vaults_closed() {
+        let mut rng = Rng::from_env();
+        for case in 0..CASES / 8 {
+            let mut world = World::new(&mut rng, vec![0; VaultRecord::LEN]);
+            let pda = world.key(PDA);
+            let lamports = world.lamports();
+            let mut closed = false;
+
+            for step in 0..STEPS {
+                let before = world.record();
+                let authority = before
+                    .as_ref()
+                    .map_or(world.key(AUTHORITIES.start), |record| record.authority);
+                let candidate = world.key(AUTHORITIES.start + rng.below(AUTHORITIES.len()));
+                let dart = world.key(DART);
+                let mut instruction = match rng.below(4) {
+                    0 => instruction::initialize(crate::id(), &pda, &dart, &candidate),
+                    1 => instruction::close_account(crate::id(), &pda, &dart, &authority),
+                    _ => instruction::transfer_authority(
+                        crate::id(),
+                        &pda,
+                        &dart,
+                        &authority,
+                        &candidate,
+                    ),
+                };
+                tamper(&mut rng, &world, &mut instruction, DART);
+                if let Some(index) = world.accounts.iter().position(|a| a.key == authority) {
+                    tamper(&mut rng, &world, &mut instruction, index);
+                }
+                let result = world.process(&instruction);
+                let trace = format!("case {case} step {step}: {instruction:?} -> {result:?}");
+
+                assert_eq!(
+                    world.lamports(),
+                    lamports,
+                    "lamports not conserved, {trace}"
+                );
+                if closed {
+                    assert!(result.is_err(), "closed vault accepted, {trace}");
+                    continue;
+                }
+                let (Some(before), Some(after)) = (before, world.record()) else {
+                    continue;
+                };
+                let both = signs(&instruction, &before.dart) && signs(&instruction, &authority);
+                if after.status == VaultStatus::Closed {
+                    assert!(both, "closed without both signatures, {trace}");
+                    closed = true;
+                } else if after.authority != before.authority {
+                    assert!(both, "authority changed without both signatures, {trace}");
+                }
+            }
+        }
+    }
+}
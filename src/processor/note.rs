@@ -0,0 +1,183 @@
+use {
+    super::validation::{
+        create_pda_account, instruction_accounts, load_record, validate_active, validate_dart,
+        validate_signer, write_account,
+    },
+    crate::{
+        error::VaultError,
+        event::{self, NoteAdded},
+        state::{LogNote, NoteRecord, NotesLog, ENCRYPTED_NOTE_LEN, NOTES_LOG_SEED, NOTE_SEED},
+        validation::require_initialized,
+    },
+    borsh::BorshDeserialize,
+    solana_program::{
+        account_info::AccountInfo, clock::Clock, entrypoint::ProgramResult, msg,
+        program_error::ProgramError, pubkey::Pubkey, sysvar::Sysvar,
+    },
+};
+
+instruction_accounts! {
+    /// Accounts of `SetNoteKey`.
+    SetNoteKeyAccounts { pda, authority, note, payer, system_program }
+    // The authority may also pay for the note account.
+    unique: [pda, authority, note];
+    unique: [pda, note, payer];
+    writable: [note, payer];
+    readonly: [pda, authority];
+    owned: [pda];
+}
+
+instruction_accounts! {
+    /// Accounts of `SetNote`.
+    SetNoteAccounts { pda, dart, note; ..remaining }
+    unique: [pda, dart, note];
+    writable: [note];
+    readonly: [pda, dart];
+    owned: [pda];
+}
+
+instruction_accounts! {
+    /// Accounts of `AddNote`.
+    AddNoteAccounts { pda, dart, log, payer, system_program; ..remaining }
+    // The DART may also pay for the notes log.
+    unique: [pda, dart, log];
+    unique: [pda, log, payer];
+    writable: [log, payer];
+    readonly: [pda, dart];
+    owned: [pda];
+}
+
+// Set the X25519 key the vault's note is sealed to (authority only), clearing the note.
+pub(super) fn set_note_key(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    note_key: [u8; 32],
+) -> ProgramResult {
+    let SetNoteKeyAccounts {
+        pda,
+        authority,
+        note,
+        payer,
+        system_program,
+    } = SetNoteKeyAccounts::parse(program_id, accounts)?;
+
+    let record = load_record(program_id, pda)?;
+    validate_active(&record)?;
+    validate_signer(authority, &record.authority, VaultError::IncorrectAuthority)?;
+
+    let (address, bump) = NoteRecord::find_address(program_id, pda.key);
+    if *note.key != address {
+        msg!("Note account does not match the vault");
+        return Err(ProgramError::InvalidSeeds);
+    }
+    if note.owner != program_id {
+        create_pda_account(
+            program_id,
+            payer,
+            note,
+            system_program,
+            NoteRecord::LEN,
+            &[NOTE_SEED, pda.key.as_ref(), &[bump]],
+        )?;
+    }
+    let note_record = NoteRecord::new(*pda.key, record.authority, note_key, bump);
+
+    write_account(note, &note_record)
+}
+
+// Replace the vault's sealed note (DART only).
+pub(super) fn set_note(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    encrypted_note: [u8; ENCRYPTED_NOTE_LEN],
+) -> ProgramResult {
+    let SetNoteAccounts {
+        pda,
+        dart,
+        note,
+        remaining,
+    } = SetNoteAccounts::parse(program_id, accounts)?;
+
+    let record = load_record(program_id, pda)?;
+    validate_active(&record)?;
+    validate_dart(program_id, dart, remaining, &record.dart)?;
+
+    if note.owner != program_id {
+        msg!("No note key set for the vault");
+        return Err(VaultError::NoteKeyNotSet.into());
+    }
+    let mut note_record = NoteRecord::try_from_slice(&note.data.borrow())?;
+    require_initialized(note, &note_record)?;
+    if note_record.vault != *pda.key {
+        msg!("Note account does not match the vault");
+        return Err(ProgramError::InvalidSeeds);
+    }
+    // A key set by a previous authority would seal the note to the wrong holder.
+    if note_record.authority != record.authority {
+        msg!("Note key was set by a previous authority");
+        return Err(VaultError::NoteKeyNotSet.into());
+    }
+    note_record.encrypted_note = encrypted_note;
+
+    write_account(note, &note_record)
+}
+
+// Append a corporate action note to the vault's notes log (DART only), creating the log
+// if needed and evicting its oldest note when full.
+pub(super) fn add_note(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    hash: [u8; 32],
+    category: u16,
+) -> ProgramResult {
+    let AddNoteAccounts {
+        pda,
+        dart,
+        log,
+        payer,
+        system_program,
+        remaining,
+    } = AddNoteAccounts::parse(program_id, accounts)?;
+
+    let record = load_record(program_id, pda)?;
+    validate_active(&record)?;
+    validate_dart(program_id, dart, remaining, &record.dart)?;
+
+    let (address, bump) = NotesLog::find_address(program_id, pda.key);
+    if *log.key != address {
+        msg!("Notes log does not match the vault");
+        return Err(ProgramError::InvalidSeeds);
+    }
+    let mut notes_log = if log.owner == program_id {
+        let notes_log = NotesLog::try_from_slice(&log.data.borrow())?;
+        require_initialized(log, &notes_log)?;
+        notes_log
+    } else {
+        create_pda_account(
+            program_id,
+            payer,
+            log,
+            system_program,
+            NotesLog::LEN,
+            &[NOTES_LOG_SEED, pda.key.as_ref(), &[bump]],
+        )?;
+        NotesLog::new(*pda.key, bump)
+    };
+    let (sequence, _) = notes_log.push(LogNote {
+        hash,
+        category,
+        slot: Clock::get()?.slot,
+    })?;
+
+    write_account(log, &notes_log)?;
+    event::emit(
+        program_id,
+        accounts,
+        &NoteAdded {
+            vault: *pda.key,
+            sequence,
+            category,
+            hash,
+        },
+    )
+}
@@ -0,0 +1,85 @@
+use {
+    super::validation::{
+        create_pda_account, instruction_accounts, load_record, validate_active, validate_signer,
+        write_account,
+    },
+    crate::{
+        error::VaultError,
+        state::{Notification, NotifyRecord, NOTIFICATION_SEED, NOTIFY_SEED},
+    },
+    solana_program::{
+        account_info::AccountInfo, entrypoint::ProgramResult, msg, program_error::ProgramError,
+        pubkey::Pubkey,
+    },
+};
+
+instruction_accounts! {
+    /// Accounts of `SetNotify`.
+    SetNotifyAccounts { pda, authority, notify_info, notification, payer, system_program }
+    // The authority may also pay for the accounts.
+    unique: [pda, authority, notify_info, notification];
+    unique: [pda, notify_info, notification, payer];
+    writable: [notify_info, notification, payer];
+    readonly: [pda, authority];
+    owned: [pda];
+}
+
+// Register the key notified of the vault's authority changes and close (authority
+// only), creating the notify record and the key's notification account if needed.
+pub(super) fn set_notify(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    notify: Pubkey,
+) -> ProgramResult {
+    let SetNotifyAccounts {
+        pda,
+        authority,
+        notify_info,
+        notification,
+        payer,
+        system_program,
+    } = SetNotifyAccounts::parse(program_id, accounts)?;
+
+    let record = load_record(program_id, pda)?;
+    validate_active(&record)?;
+    validate_signer(authority, &record.authority, VaultError::IncorrectAuthority)?;
+
+    let (address, bump) = NotifyRecord::find_address(program_id, pda.key);
+    if *notify_info.key != address {
+        msg!("Notify record does not match the vault");
+        return Err(ProgramError::InvalidSeeds);
+    }
+    if notify_info.owner != program_id {
+        create_pda_account(
+            program_id,
+            payer,
+            notify_info,
+            system_program,
+            NotifyRecord::LEN,
+            &[NOTIFY_SEED, pda.key.as_ref(), &[bump]],
+        )?;
+    }
+
+    let (address, notification_bump) = Notification::find_address(program_id, &notify);
+    if *notification.key != address {
+        msg!("Notification account does not match the notify key");
+        return Err(ProgramError::InvalidSeeds);
+    }
+    // Other vaults may already notify the key; clearing creates nothing.
+    if notify != Pubkey::default() && notification.owner != program_id {
+        create_pda_account(
+            program_id,
+            payer,
+            notification,
+            system_program,
+            Notification::LEN,
+            &[NOTIFICATION_SEED, notify.as_ref(), &[notification_bump]],
+        )?;
+        write_account(notification, &Notification::new(notify, notification_bump))?;
+    }
+
+    write_account(
+        notify_info,
+        &NotifyRecord::new(*pda.key, record.authority, notify, bump),
+    )
+}
@@ -0,0 +1,115 @@
+use {
+    super::validation::{
+        create_pda_account, instruction_accounts, load_pledge, load_record, validate_active,
+        validate_signer, write_account,
+    },
+    crate::{
+        error::VaultError,
+        state::{PledgeRecord, PLEDGE_SEED},
+    },
+    solana_program::{
+        account_info::AccountInfo, entrypoint::ProgramResult, msg, program_error::ProgramError,
+        pubkey::Pubkey,
+    },
+};
+
+instruction_accounts! {
+    /// Accounts of `PledgeAsCollateral`.
+    PledgeAsCollateralAccounts { pda, authority, pledge, payer, system_program }
+    // The authority may also pay for the pledge account.
+    unique: [pda, authority, pledge];
+    unique: [pda, pledge, payer];
+    writable: [pda, pledge, payer];
+    readonly: [authority];
+    owned: [pda];
+    signers: [payer];
+}
+
+instruction_accounts! {
+    /// Accounts of `ReleaseCollateral`.
+    ReleaseCollateralAccounts { pda, lender, pledge }
+    unique: [pda, lender, pledge];
+    writable: [pda, pledge];
+    readonly: [lender];
+    owned: [pda, pledge];
+}
+
+// Pledge the vault as collateral to `lender`, creating the pledge account if needed.
+pub(super) fn pledge_as_collateral(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    lender: Pubkey,
+) -> ProgramResult {
+    let PledgeAsCollateralAccounts {
+        pda,
+        authority,
+        pledge: pledge_info,
+        payer,
+        system_program,
+    } = PledgeAsCollateralAccounts::parse(program_id, accounts)?;
+
+    let mut record = load_record(program_id, pda)?;
+    validate_active(&record)?;
+    validate_signer(authority, &record.authority, VaultError::IncorrectAuthority)?;
+
+    if record.pledged {
+        msg!("Vault already pledged");
+        return Err(VaultError::VaultPledged.into());
+    }
+    // A receipt could carry the vault to its next holder without the lender.
+    if record.receipt_mint != Pubkey::default() {
+        msg!("Burn the custody receipt before pledging");
+        return Err(VaultError::ReceiptAlreadyMinted.into());
+    }
+    if lender == Pubkey::default() || lender == record.authority {
+        msg!("Lender must be a third party");
+        return Err(ProgramError::InvalidArgument);
+    }
+    if lender == record.dart {
+        msg!("Lender must not be the DART");
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    let (address, bump) = PledgeRecord::find_address(program_id, pda.key);
+    if *pledge_info.key != address {
+        msg!("Pledge account does not match the vault");
+        return Err(ProgramError::InvalidSeeds);
+    }
+    if pledge_info.owner != program_id {
+        create_pda_account(
+            program_id,
+            payer,
+            pledge_info,
+            system_program,
+            PledgeRecord::LEN,
+            &[PLEDGE_SEED, pda.key.as_ref(), &[bump]],
+        )?;
+    }
+    record.pledged = true;
+
+    record.pack_into(&mut pda.data.borrow_mut())?;
+    write_account(pledge_info, &PledgeRecord::new(*pda.key, lender, bump))
+}
+
+// Release the vault from its pledge, signed by the lender.
+pub(super) fn release_collateral(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let ReleaseCollateralAccounts {
+        pda,
+        lender,
+        pledge: pledge_info,
+    } = ReleaseCollateralAccounts::parse(program_id, accounts)?;
+
+    let mut record = load_record(program_id, pda)?;
+    if !record.pledged {
+        msg!("Vault not pledged");
+        return Err(VaultError::NotPledged.into());
+    }
+    let mut pledge = load_pledge(program_id, pda.key, pledge_info)?;
+    validate_signer(lender, &pledge.lender, VaultError::IncorrectAuthority)?;
+
+    record.pledged = false;
+    pledge.lender = Pubkey::default();
+
+    record.pack_into(&mut pda.data.borrow_mut())?;
+    write_account(pledge_info, &pledge)
+}
@@ -0,0 +1,198 @@
+use {
+    super::validation::{
+        batch_accounts, consume_vault_rate_limit, create_pda_account, find_audit_trail,
+        instruction_accounts, load_position, load_record, record_audit, validate_account_flags,
+        validate_active, validate_dart, validate_unique, write_account,
+    },
+    crate::{
+        event::{self, CorporateActionApplied},
+        instruction::VaultInstruction,
+        state::{
+            CorporateActionType, PositionRecord, SnapshotRecord, POSITION_SEED, SNAPSHOT_SEED,
+        },
+    },
+    solana_program::{
+        account_info::AccountInfo, clock::Clock, entrypoint::ProgramResult, msg,
+        program_error::ProgramError, pubkey::Pubkey, sysvar::Sysvar,
+    },
+};
+
+instruction_accounts! {
+    /// Accounts of `SetPosition`.
+    SetPositionAccounts { pda, dart, position, payer, system_program; ..remaining }
+    // The DART may also pay for the position account.
+    unique: [pda, dart, position];
+    unique: [pda, position, payer];
+    writable: [position, payer];
+    readonly: [pda, dart];
+    owned: [pda];
+}
+
+instruction_accounts! {
+    /// Accounts of `Snapshot`.
+    SnapshotAccounts { pda, dart, position, snapshot, payer, system_program; ..remaining }
+    // The DART may also pay for the snapshot.
+    unique: [pda, dart, position, snapshot];
+    unique: [pda, position, snapshot, payer];
+    writable: [snapshot, payer];
+    readonly: [pda, dart, position];
+    owned: [pda];
+}
+
+instruction_accounts! {
+    /// Accounts of `ApplyCorporateAction`.
+    ApplyCorporateActionAccounts { dart; ..remaining }
+    writable: [];
+    readonly: [dart];
+}
+
+// Book the security the vault holds and its balance (DART only), creating the position
+// account if needed.
+pub(super) fn set_position(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    asset_id: [u8; 32],
+    balance: u64,
+) -> ProgramResult {
+    let SetPositionAccounts {
+        pda,
+        dart,
+        position,
+        payer,
+        system_program,
+        remaining,
+    } = SetPositionAccounts::parse(program_id, accounts)?;
+
+    let record = load_record(program_id, pda)?;
+    validate_active(&record)?;
+    validate_dart(program_id, dart, remaining, &record.dart)?;
+
+    let (address, bump) = PositionRecord::find_address(program_id, pda.key);
+    if *position.key != address {
+        msg!("Position account does not match the vault");
+        return Err(ProgramError::InvalidSeeds);
+    }
+    if position.owner != program_id {
+        create_pda_account(
+            program_id,
+            payer,
+            position,
+            system_program,
+            PositionRecord::LEN,
+            &[POSITION_SEED, pda.key.as_ref(), &[bump]],
+        )?;
+    }
+    let position_record =
+        PositionRecord::new(*pda.key, asset_id, balance, Clock::get()?.slot, bump);
+
+    write_account(position, &position_record)
+}
+
+// Copy the vault's authority and position into its snapshot for `record_date_slot`
+// (DART only), once the record date is reached. Snapshots are never rewritten.
+pub(super) fn snapshot(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    record_date_slot: u64,
+) -> ProgramResult {
+    let SnapshotAccounts {
+        pda,
+        dart,
+        position: position_info,
+        snapshot: snapshot_info,
+        payer,
+        system_program,
+        remaining,
+    } = SnapshotAccounts::parse(program_id, accounts)?;
+
+    let record = load_record(program_id, pda)?;
+    validate_dart(program_id, dart, remaining, &record.dart)?;
+    let position = load_position(program_id, pda.key, position_info)?;
+
+    let slot = Clock::get()?.slot;
+    if slot < record_date_slot {
+        msg!("Record date slot {} not reached", record_date_slot);
+        return Err(ProgramError::InvalidArgument);
+    }
+    let (address, bump) = SnapshotRecord::find_address(program_id, pda.key, record_date_slot);
+    if *snapshot_info.key != address {
+        msg!("Snapshot account does not match the vault and record date");
+        return Err(ProgramError::InvalidSeeds);
+    }
+    if snapshot_info.owner == program_id {
+        msg!("Snapshot already taken for slot {}", record_date_slot);
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+    create_pda_account(
+        program_id,
+        payer,
+        snapshot_info,
+        system_program,
+        SnapshotRecord::LEN,
+        &[
+            SNAPSHOT_SEED,
+            pda.key.as_ref(),
+            &record_date_slot.to_le_bytes(),
+            &[bump],
+        ],
+    )?;
+    let snapshot = SnapshotRecord::new(*pda.key, record_date_slot, slot, &record, &position, bump);
+
+    write_account(snapshot_info, &snapshot)
+}
+
+// Apply a corporate action to the positions of a batch of vaults under the DART, passed
+// as (vault, position) pairs, emitting `CorporateActionApplied` for each.
+pub(super) fn apply_corporate_action(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    action_type: CorporateActionType,
+    ratio: u64,
+) -> ProgramResult {
+    let ApplyCorporateActionAccounts { dart, remaining } =
+        ApplyCorporateActionAccounts::parse(program_id, accounts)?;
+
+    // A trailing odd account is the DART identity.
+    let pairs = batch_accounts(program_id, remaining).chunks_exact(2);
+    if pairs.len() == 0 {
+        msg!("No vaults to apply the corporate action to");
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+    // A vault passed twice would have the action applied twice.
+    validate_unique(&pairs.clone().map(|pair| &pair[0]).collect::<Vec<_>>())?;
+
+    let action = VaultInstruction::ApplyCorporateAction { action_type, ratio };
+    let slot = Clock::get()?.slot;
+    for pair in pairs {
+        let (pda, position_info) = (&pair[0], &pair[1]);
+        let record = load_record(program_id, pda)?;
+        validate_dart(program_id, dart, remaining, &record.dart)?;
+        validate_account_flags(&[position_info], &[])?;
+        consume_vault_rate_limit(program_id, pda, remaining)?;
+        let trail = find_audit_trail(program_id, pda, remaining)?;
+
+        let mut position = load_position(program_id, pda.key, position_info)?;
+        let (previous_asset_id, previous_balance) = (position.asset_id, position.balance);
+        position.apply(&action_type, ratio)?;
+        position.updated_slot = slot;
+
+        write_account(position_info, &position)?;
+        if let Some(trail) = trail {
+            record_audit(trail, accounts, action.tag())?;
+        }
+        event::emit(
+            program_id,
+            accounts,
+            &CorporateActionApplied {
+                vault: *pda.key,
+                action_type,
+                ratio,
+                previous_asset_id,
+                asset_id: position.asset_id,
+                previous_balance,
+                balance: position.balance,
+            },
+        )?;
+    }
+    Ok(())
+}
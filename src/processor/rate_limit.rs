@@ -0,0 +1,68 @@
+use {
+    super::validation::{
+        create_pda_account, instruction_accounts, load_rate_limit, load_record, validate_active,
+        validate_dart, write_account,
+    },
+    crate::state::{RateLimit, RATE_LIMIT_SEED},
+    solana_program::{
+        account_info::AccountInfo, entrypoint::ProgramResult, msg, program_error::ProgramError,
+        pubkey::Pubkey,
+    },
+};
+
+instruction_accounts! {
+    /// Accounts of `SetRateLimit`.
+    SetRateLimitAccounts { pda, dart, rate_limit, payer, system_program; ..remaining }
+    // The DART may also pay for the rate limit account.
+    unique: [pda, dart, rate_limit];
+    unique: [pda, rate_limit, payer];
+    writable: [pda, rate_limit, payer];
+    readonly: [dart];
+    owned: [pda];
+    signers: [payer];
+}
+
+// Limit the DART-signed mutations of a vault per epoch (DART only), creating the rate
+// limit account if needed. The count of the current epoch carries over a new limit.
+pub(super) fn set_rate_limit(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    max_mutations_per_epoch: u32,
+) -> ProgramResult {
+    let SetRateLimitAccounts {
+        pda,
+        dart,
+        rate_limit: rate_limit_info,
+        payer,
+        system_program,
+        remaining,
+    } = SetRateLimitAccounts::parse(program_id, accounts)?;
+
+    let mut record = load_record(program_id, pda)?;
+    validate_active(&record)?;
+    validate_dart(program_id, dart, remaining, &record.dart)?;
+
+    let (address, bump) = RateLimit::find_address(program_id, pda.key);
+    if *rate_limit_info.key != address {
+        msg!("Rate limit account does not match the vault");
+        return Err(ProgramError::InvalidSeeds);
+    }
+    let mut rate_limit = if rate_limit_info.owner == program_id {
+        load_rate_limit(program_id, pda.key, rate_limit_info)?
+    } else {
+        create_pda_account(
+            program_id,
+            payer,
+            rate_limit_info,
+            system_program,
+            RateLimit::LEN,
+            &[RATE_LIMIT_SEED, pda.key.as_ref(), &[bump]],
+        )?;
+        RateLimit::new(*pda.key, max_mutations_per_epoch, bump)
+    };
+    rate_limit.max_per_epoch = max_mutations_per_epoch;
+    record.rate_limited = max_mutations_per_epoch > 0;
+
+    record.pack_into(&mut pda.data.borrow_mut())?;
+    write_account(rate_limit_info, &rate_limit)
+}
@@ -0,0 +1,182 @@
+use {
+    super::validation::{
+        create_pda_account, instruction_accounts, load_record, move_vault_index,
+        sync_receipt_holder, validate_active, validate_dart, validate_not_pledged,
+        validate_program, validate_signer, validate_unrestricted,
+    },
+    crate::{
+        error::VaultError,
+        receipt::{self, RECEIPT_ACCOUNT_SEED, RECEIPT_SEED},
+        state::restriction,
+    },
+    solana_program::{
+        account_info::AccountInfo,
+        entrypoint::ProgramResult,
+        msg,
+        program::{invoke, invoke_signed},
+        program_error::ProgramError,
+        program_pack::Pack,
+        pubkey::Pubkey,
+    },
+    spl_token::state::{Account as TokenAccount, Mint},
+};
+
+instruction_accounts! {
+    /// Accounts of `MintReceipt`.
+    MintReceiptAccounts { pda, dart, payer, mint, token_account, token_program, system_program; ..remaining }
+    unique: [pda, dart, payer, mint, token_account];
+    writable: [pda, payer, mint, token_account];
+    readonly: [dart];
+    signers: [payer];
+}
+
+instruction_accounts! {
+    /// Accounts of `SetReceiptAuthority`.
+    SetReceiptAuthorityAccounts { pda, dart, authority; ..remaining }
+    unique: [pda, dart, authority];
+    writable: [pda];
+    readonly: [dart, authority];
+}
+
+// Mint a custody receipt token to the vault authority (DART only).
+pub(super) fn mint_receipt(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let MintReceiptAccounts {
+        pda,
+        dart,
+        payer,
+        mint,
+        token_account,
+        token_program,
+        system_program,
+        remaining,
+    } = MintReceiptAccounts::parse(program_id, accounts)?;
+
+    validate_program(token_program, &spl_token::id())?;
+
+    let mut record = load_record(program_id, pda)?;
+    validate_active(&record)?;
+    validate_dart(program_id, dart, remaining, &record.dart)?;
+
+    if record.receipt_mint != Pubkey::default() {
+        msg!("Receipt already minted");
+        return Err(VaultError::ReceiptAlreadyMinted.into());
+    }
+    validate_not_pledged(&record)?;
+    validate_unrestricted(&record, restriction::NO_RECEIPT)?;
+
+    let (mint_address, mint_bump) = receipt::find_mint_address(program_id, pda.key);
+    let (account_address, account_bump) = receipt::find_token_account_address(program_id, pda.key);
+    if *mint.key != mint_address || *token_account.key != account_address {
+        msg!("Receipt accounts do not match the vault");
+        return Err(ProgramError::InvalidSeeds);
+    }
+    let mint_seeds: &[&[u8]] = &[RECEIPT_SEED, pda.key.as_ref(), &[mint_bump]];
+
+    // A reopened vault reuses the mint of its earlier receipt.
+    if *mint.owner != spl_token::id() {
+        create_pda_account(
+            token_program.key,
+            payer,
+            mint,
+            system_program,
+            Mint::LEN,
+            mint_seeds,
+        )?;
+        invoke(
+            &spl_token::instruction::initialize_mint2(
+                token_program.key,
+                mint.key,
+                mint.key,
+                None,
+                0,
+            )?,
+            std::slice::from_ref(mint),
+        )?;
+    }
+    if *token_account.owner != spl_token::id() {
+        create_pda_account(
+            token_program.key,
+            payer,
+            token_account,
+            system_program,
+            TokenAccount::LEN,
+            &[RECEIPT_ACCOUNT_SEED, pda.key.as_ref(), &[account_bump]],
+        )?;
+        invoke(
+            &spl_token::instruction::initialize_account3(
+                token_program.key,
+                token_account.key,
+                mint.key,
+                &record.authority,
+            )?,
+            &[token_account.clone(), mint.clone()],
+        )?;
+    } else if TokenAccount::unpack(&token_account.data.borrow())?.owner != record.authority {
+        msg!("Receipt token account belongs to a former authority");
+        return Err(ProgramError::InvalidAccountData);
+    }
+    invoke_signed(
+        &spl_token::instruction::mint_to(
+            token_program.key,
+            mint.key,
+            token_account.key,
+            mint.key,
+            &[],
+            1,
+        )?,
+        &[mint.clone(), token_account.clone(), token_program.clone()],
+        &[mint_seeds],
+    )?;
+
+    record.receipt_mint = *mint.key;
+
+    record.pack_into(&mut pda.data.borrow_mut())
+}
+
+// Make the receipt holder the vault authority, or pin the authority to the
+// current holder (by DART and authority).
+pub(super) fn set_receipt_authority(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    enabled: bool,
+) -> ProgramResult {
+    let SetReceiptAuthorityAccounts {
+        pda,
+        dart,
+        authority,
+        remaining,
+    } = SetReceiptAuthorityAccounts::parse(program_id, accounts)?;
+
+    let mut record = load_record(program_id, pda)?;
+    validate_active(&record)?;
+    validate_dart(program_id, dart, remaining, &record.dart)?;
+    let indexed_authority = record.authority;
+    sync_receipt_holder(&mut record, remaining)?;
+    validate_signer(authority, &record.authority, VaultError::IncorrectAuthority)?;
+
+    if enabled && !record.receipt_is_authority {
+        if record.receipt_mint == Pubkey::default() {
+            msg!("Vault has no receipt");
+            return Err(VaultError::ReceiptNotFound.into());
+        }
+        // The authority must hold the receipt, so enabling hands the vault to nobody.
+        match receipt::find_holder(remaining, &record.receipt_mint) {
+            Some((_, token)) if token.owner == record.authority => {}
+            _ => {
+                msg!("Authority must pass the token account holding the receipt");
+                return Err(VaultError::ReceiptNotFound.into());
+            }
+        }
+    }
+    record.receipt_is_authority = enabled;
+
+    record.pack_into(&mut pda.data.borrow_mut())?;
+    // The holder the receipt passed to is written as the authority.
+    move_vault_index(
+        program_id,
+        remaining,
+        pda.key,
+        &indexed_authority,
+        &record.authority,
+    )
+}
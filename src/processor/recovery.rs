@@ -0,0 +1,331 @@
+use {
+    super::validation::{
+        create_pda_account, instruction_accounts, load_record, load_recovery, move_vault_index,
+        notify_change, sync_receipt_holder, validate_active, validate_dart, validate_pledge,
+        validate_signer, validate_transfer_window, write_account,
+    },
+    crate::{
+        error::VaultError,
+        event::{self, AuthorityTransferred},
+        math,
+        state::{NotificationKind, RecoveryRecord, RECOVERY_CHALLENGE_SLOTS, RECOVERY_SEED},
+        validation::require_signer,
+    },
+    solana_program::{
+        account_info::AccountInfo, clock::Clock, entrypoint::ProgramResult, msg,
+        program_error::ProgramError, pubkey::Pubkey, sysvar::Sysvar,
+    },
+};
+
+instruction_accounts! {
+    /// Accounts of `AddGuardian`.
+    AddGuardianAccounts { pda, authority, recovery_info, guardian, payer, system_program; ..remaining }
+    // The authority may also pay for the recovery account.
+    unique: [pda, authority, recovery_info, guardian];
+    unique: [pda, recovery_info, payer];
+    writable: [recovery_info, payer];
+    readonly: [pda, authority, guardian];
+}
+
+instruction_accounts! {
+    /// Accounts of `RemoveGuardian`.
+    RemoveGuardianAccounts { pda, authority, recovery_info, guardian; ..remaining }
+    unique: [pda, authority, recovery_info, guardian];
+    writable: [recovery_info];
+    readonly: [pda, authority, guardian];
+}
+
+instruction_accounts! {
+    /// Accounts of `InitiateRecovery`.
+    InitiateRecoveryAccounts { pda, dart, recovery_info, new_authority; ..remaining }
+    unique: [pda, dart, recovery_info, new_authority];
+    writable: [recovery_info];
+    readonly: [pda, dart, new_authority];
+}
+
+instruction_accounts! {
+    /// Accounts of `ApproveRecovery`.
+    ApproveRecoveryAccounts { pda, recovery_info, guardian }
+    unique: [pda, recovery_info, guardian];
+    writable: [recovery_info];
+    readonly: [pda, guardian];
+}
+
+instruction_accounts! {
+    /// Accounts of `CancelRecovery`.
+    CancelRecoveryAccounts { pda, authority, recovery_info; ..remaining }
+    unique: [pda, authority, recovery_info];
+    writable: [recovery_info];
+    readonly: [pda, authority];
+}
+
+instruction_accounts! {
+    /// Accounts of `CompleteRecovery`.
+    CompleteRecoveryAccounts { pda, dart, recovery_info; ..remaining }
+    unique: [pda, dart, recovery_info];
+    writable: [pda, recovery_info];
+    readonly: [dart];
+}
+
+// Add a guardian, creating the vault's recovery account if needed.
+pub(super) fn add_guardian(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    threshold: u8,
+) -> ProgramResult {
+    let AddGuardianAccounts {
+        pda,
+        authority,
+        recovery_info,
+        guardian,
+        payer,
+        system_program,
+        remaining,
+    } = AddGuardianAccounts::parse(program_id, accounts)?;
+
+    let mut record = load_record(program_id, pda)?;
+    validate_active(&record)?;
+    sync_receipt_holder(&mut record, remaining)?;
+    validate_signer(authority, &record.authority, VaultError::IncorrectAuthority)?;
+
+    if *guardian.key == Pubkey::default() || *guardian.key == record.dart {
+        msg!("Guardian must not be the default pubkey or DART");
+        return Err(VaultError::InvalidGuardian.into());
+    }
+
+    let (address, bump) = RecoveryRecord::find_address(program_id, pda.key);
+    if *recovery_info.key != address {
+        msg!("Recovery account does not match the vault");
+        return Err(ProgramError::InvalidSeeds);
+    }
+    let mut recovery = if recovery_info.owner == program_id {
+        load_recovery(program_id, pda.key, recovery_info)?
+    } else {
+        create_pda_account(
+            program_id,
+            payer,
+            recovery_info,
+            system_program,
+            RecoveryRecord::LEN,
+            &[RECOVERY_SEED, pda.key.as_ref(), &[bump]],
+        )?;
+        RecoveryRecord::new(*pda.key, bump)
+    };
+
+    if recovery.guardian_index(guardian.key).is_some() {
+        msg!("Already a guardian");
+        return Err(VaultError::InvalidGuardian.into());
+    }
+    let slot = recovery
+        .guardians
+        .iter()
+        .position(|key| *key == Pubkey::default())
+        .ok_or(VaultError::TooManyGuardians)?;
+    recovery.guardians[slot] = *guardian.key;
+
+    if !recovery.is_valid_threshold(threshold) {
+        msg!("Threshold {} invalid for the guardian set", threshold);
+        return Err(VaultError::InvalidThreshold.into());
+    }
+    recovery.threshold = threshold;
+    // Approvals were given to the previous guardian set.
+    recovery.clear_pending();
+
+    write_account(recovery_info, &recovery)
+}
+
+// Remove a guardian and reset the threshold.
+pub(super) fn remove_guardian(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    threshold: u8,
+) -> ProgramResult {
+    let RemoveGuardianAccounts {
+        pda,
+        authority,
+        recovery_info,
+        guardian,
+        remaining,
+    } = RemoveGuardianAccounts::parse(program_id, accounts)?;
+
+    let mut record = load_record(program_id, pda)?;
+    validate_active(&record)?;
+    sync_receipt_holder(&mut record, remaining)?;
+    validate_signer(authority, &record.authority, VaultError::IncorrectAuthority)?;
+
+    let mut recovery = load_recovery(program_id, pda.key, recovery_info)?;
+    let slot = recovery
+        .guardian_index(guardian.key)
+        .ok_or(VaultError::GuardianNotFound)?;
+    recovery.guardians[slot] = Pubkey::default();
+
+    if !recovery.is_valid_threshold(threshold) {
+        msg!("Threshold {} invalid for the guardian set", threshold);
+        return Err(VaultError::InvalidThreshold.into());
+    }
+    recovery.threshold = threshold;
+    // Approvals were given to the previous guardian set.
+    recovery.clear_pending();
+
+    write_account(recovery_info, &recovery)
+}
+
+// Start a recovery to a new authority, opening the challenge period.
+pub(super) fn initiate_recovery(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let InitiateRecoveryAccounts {
+        pda,
+        dart,
+        recovery_info,
+        new_authority,
+        remaining,
+    } = InitiateRecoveryAccounts::parse(program_id, accounts)?;
+
+    let record = load_record(program_id, pda)?;
+    validate_active(&record)?;
+    validate_dart(program_id, dart, remaining, &record.dart)?;
+
+    let mut recovery = load_recovery(program_id, pda.key, recovery_info)?;
+    if recovery.threshold == 0 {
+        msg!("No guardians configured");
+        return Err(VaultError::InvalidThreshold.into());
+    }
+    if recovery.pending_authority != Pubkey::default() {
+        msg!("Recovery already pending");
+        return Err(VaultError::RecoveryPending.into());
+    }
+
+    if *new_authority.key == record.authority {
+        msg!("New authority matches current authority");
+        return Err(VaultError::AuthorityUnchanged.into());
+    }
+    if *new_authority.key == Pubkey::default() {
+        msg!("New authority must not be the default pubkey");
+        return Err(VaultError::InvalidAuthority.into());
+    }
+    if *new_authority.key == record.dart {
+        msg!("New authority must not be the DART");
+        return Err(VaultError::AuthorityIsDart.into());
+    }
+
+    recovery.pending_authority = *new_authority.key;
+    recovery.initiated_at_slot = Clock::get()?.slot;
+    recovery.approvals = 0;
+
+    write_account(recovery_info, &recovery)
+}
+
+// Record a guardian's approval of the pending recovery.
+pub(super) fn approve_recovery(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let ApproveRecoveryAccounts {
+        pda,
+        recovery_info,
+        guardian,
+    } = ApproveRecoveryAccounts::parse(program_id, accounts)?;
+
+    let record = load_record(program_id, pda)?;
+    validate_active(&record)?;
+
+    let mut recovery = load_recovery(program_id, pda.key, recovery_info)?;
+    if recovery.pending_authority == Pubkey::default() {
+        msg!("No recovery pending");
+        return Err(VaultError::RecoveryNotPending.into());
+    }
+    let slot = recovery
+        .guardian_index(guardian.key)
+        .ok_or(VaultError::GuardianNotFound)?;
+    require_signer(guardian)?;
+    recovery.approvals |= 1 << slot;
+
+    write_account(recovery_info, &recovery)
+}
+
+// Cancel the pending recovery during its challenge period (current authority only).
+pub(super) fn cancel_recovery(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let CancelRecoveryAccounts {
+        pda,
+        authority,
+        recovery_info,
+        remaining,
+    } = CancelRecoveryAccounts::parse(program_id, accounts)?;
+
+    let mut record = load_record(program_id, pda)?;
+    sync_receipt_holder(&mut record, remaining)?;
+    validate_signer(authority, &record.authority, VaultError::IncorrectAuthority)?;
+
+    let mut recovery = load_recovery(program_id, pda.key, recovery_info)?;
+    if recovery.pending_authority == Pubkey::default() {
+        msg!("No recovery pending");
+        return Err(VaultError::RecoveryNotPending.into());
+    }
+    recovery.clear_pending();
+
+    write_account(recovery_info, &recovery)
+}
+
+// Rotate the authority once guardians approved and the challenge period passed.
+pub(super) fn complete_recovery(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let CompleteRecoveryAccounts {
+        pda,
+        dart,
+        recovery_info,
+        remaining,
+    } = CompleteRecoveryAccounts::parse(program_id, accounts)?;
+
+    let mut record = load_record(program_id, pda)?;
+    validate_active(&record)?;
+    validate_dart(program_id, dart, remaining, &record.dart)?;
+
+    let mut recovery = load_recovery(program_id, pda.key, recovery_info)?;
+    if recovery.pending_authority == Pubkey::default() {
+        msg!("No recovery pending");
+        return Err(VaultError::RecoveryNotPending.into());
+    }
+    if recovery.approval_count() < u32::from(recovery.threshold) {
+        msg!(
+            "{} of {} guardian approvals",
+            recovery.approval_count(),
+            recovery.threshold
+        );
+        return Err(VaultError::RecoveryThresholdNotMet.into());
+    }
+    let unlocked_at = math::checked_add(recovery.initiated_at_slot, RECOVERY_CHALLENGE_SLOTS)?;
+    if Clock::get()?.slot < unlocked_at {
+        msg!("Recovery unlocks at slot {}", unlocked_at);
+        return Err(VaultError::ChallengePeriodActive.into());
+    }
+    validate_pledge(program_id, pda.key, &record, remaining)?;
+    validate_transfer_window(program_id, pda.key, &record, remaining)?;
+
+    let previous_authority = record.authority;
+    record.authority = recovery.pending_authority;
+    // The recovered authority holds the vault directly, whoever has the receipt.
+    record.receipt_is_authority = false;
+    recovery.clear_pending();
+
+    record.pack_into(&mut pda.data.borrow_mut())?;
+    write_account(recovery_info, &recovery)?;
+    notify_change(
+        program_id,
+        remaining,
+        NotificationKind::Transfer,
+        pda.key,
+        &previous_authority,
+        &record.authority,
+    )?;
+    move_vault_index(
+        program_id,
+        remaining,
+        pda.key,
+        &previous_authority,
+        &record.authority,
+    )?;
+    event::emit(
+        program_id,
+        accounts,
+        &AuthorityTransferred {
+            vault: *pda.key,
+            previous_authority,
+            authority: record.authority,
+        },
+    )
+}
@@ -0,0 +1,87 @@
+use {
+    super::validation::{
+        create_pda_account, instruction_accounts, load_record, sync_receipt_holder,
+        validate_active, validate_signer, validate_unrestricted, write_account,
+    },
+    crate::{
+        error::VaultError,
+        state::{restriction, session_scope, SessionRecord, SESSION_SEED},
+    },
+    solana_program::{
+        account_info::AccountInfo, entrypoint::ProgramResult, msg, program_error::ProgramError,
+        pubkey::Pubkey,
+    },
+};
+
+instruction_accounts! {
+    /// Accounts of `CreateSession`.
+    CreateSessionAccounts { pda, authority, session, payer, system_program; ..remaining }
+    // The authority may also pay for the session account.
+    unique: [pda, authority, session];
+    unique: [pda, session, payer];
+    writable: [session, payer];
+    readonly: [pda, authority];
+    owned: [pda];
+}
+
+// Create or replace an authority session key for a vault.
+pub(super) fn create_session(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    key: Pubkey,
+    expires_at_slot: u64,
+    scope: u8,
+) -> ProgramResult {
+    let CreateSessionAccounts {
+        pda,
+        authority,
+        session,
+        payer,
+        system_program,
+        remaining,
+    } = CreateSessionAccounts::parse(program_id, accounts)?;
+
+    let mut record = load_record(program_id, pda)?;
+    validate_active(&record)?;
+
+    sync_receipt_holder(&mut record, remaining)?;
+    validate_signer(authority, &record.authority, VaultError::IncorrectAuthority)?;
+    validate_unrestricted(&record, restriction::NO_SESSIONS)?;
+
+    if key == Pubkey::default() || key == record.authority || key == record.dart {
+        msg!("Session key must not be the default pubkey, authority or DART");
+        return Err(VaultError::InvalidSessionKey.into());
+    }
+    if scope & !session_scope::ALL != 0 {
+        msg!("Unknown session scope bits {:#04x}", scope);
+        return Err(VaultError::SessionScopeNotAllowed.into());
+    }
+
+    let (address, bump) = SessionRecord::find_address(program_id, pda.key, &key);
+    if *session.key != address {
+        msg!("Session account does not match the session key");
+        return Err(ProgramError::InvalidSeeds);
+    }
+
+    if session.owner != program_id {
+        create_pda_account(
+            program_id,
+            payer,
+            session,
+            system_program,
+            SessionRecord::LEN,
+            &[SESSION_SEED, pda.key.as_ref(), key.as_ref(), &[bump]],
+        )?;
+    }
+
+    let session_record = SessionRecord::new(
+        *pda.key,
+        record.authority,
+        key,
+        expires_at_slot,
+        scope,
+        bump,
+    );
+
+    write_account(session, &session_record)
+}
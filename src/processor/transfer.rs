@@ -0,0 +1,544 @@
+use {
+    super::validation::{
+        consume_vault_rate_limit, find_audit_trail, instruction_accounts, load_approval_root,
+        load_record, move_vault_index, notify_change, record_audit, validate_active,
+        validate_authority, validate_dart, validate_expected_authority,
+        validate_not_receipt_holder_mode, validate_pledge, validate_signer,
+        validate_transfer_window, validate_unique,
+    },
+    crate::{
+        approval,
+        error::VaultError,
+        event::{self, AuthorityTransferred, TransferCheck},
+        instruction::VaultInstruction,
+        math, oracle,
+        state::{session_scope, NotificationKind, VaultRecord, VaultStatus},
+    },
+    solana_program::{
+        account_info::AccountInfo, clock::Clock, entrypoint::ProgramResult, msg,
+        program::set_return_data, pubkey::Pubkey, sysvar::Sysvar,
+    },
+};
+
+instruction_accounts! {
+    /// Accounts of `TransferAuthority` and `TransferAuthorityChecked`.
+    TransferAuthorityAccounts { pda, dart, authority, new_authority; ..remaining }
+    unique: [pda, dart, authority];
+    writable: [pda];
+    readonly: [dart, authority, new_authority];
+    owned: [pda];
+}
+
+instruction_accounts! {
+    /// Accounts of `TransferWithProof`.
+    TransferWithProofAccounts { pda, approval, authority, new_authority; ..remaining }
+    unique: [pda, approval, authority];
+    writable: [pda];
+    readonly: [approval, authority, new_authority];
+    owned: [pda, approval];
+}
+
+instruction_accounts! {
+    /// Accounts of `ProposeTransfer`.
+    ProposeTransferAccounts { pda, dart, authority, new_authority; ..remaining }
+    unique: [pda, dart, authority];
+    writable: [pda];
+    readonly: [dart, authority, new_authority];
+}
+
+instruction_accounts! {
+    /// Accounts of `AcceptTransfer`.
+    AcceptTransferAccounts { pda, new_authority; ..remaining }
+    unique: [pda, new_authority];
+    writable: [pda];
+    readonly: [new_authority];
+}
+
+instruction_accounts! {
+    /// Accounts of `ExpireTransfer`.
+    ExpireTransferAccounts { pda }
+    writable: [pda];
+    readonly: [];
+}
+
+instruction_accounts! {
+    /// Accounts of `CheckTransfer`.
+    CheckTransferAccounts { pda, authority, new_authority; ..remaining }
+    unique: [pda, authority];
+    writable: [];
+    readonly: [pda, authority, new_authority];
+}
+
+instruction_accounts! {
+    /// Accounts of `SwapAuthorities`.
+    SwapAuthoritiesAccounts { pda_a, pda_b, dart, authority_a, authority_b; ..remaining }
+    unique: [pda_a, pda_b, dart, authority_a, authority_b];
+    writable: [pda_a, pda_b];
+    readonly: [dart, authority_a, authority_b];
+}
+
+// Transfer ownership of a vault record
+pub(super) fn transfer_authority(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    expected_authority: Option<&Pubkey>,
+) -> ProgramResult {
+    let TransferAuthorityAccounts {
+        pda,
+        dart,
+        authority,
+        new_authority,
+        remaining,
+    } = TransferAuthorityAccounts::parse(program_id, accounts)?;
+
+    let mut record = load_record(program_id, pda)?;
+    validate_active(&record)?;
+    validate_expected_authority(&record, expected_authority)?;
+
+    validate_not_receipt_holder_mode(&record)?;
+    validate_dart(program_id, dart, remaining, &record.dart)?;
+    validate_authority(
+        program_id,
+        pda,
+        authority,
+        remaining,
+        &record.authority,
+        session_scope::TRANSFER_AUTHORITY,
+    )?;
+    validate_transfer_rules(program_id, pda, &record, dart.key, new_authority, remaining)?;
+
+    let previous_authority = record.authority;
+    record.authority = *new_authority.key;
+
+    record.pack_into(&mut pda.data.borrow_mut())?;
+    notify_change(
+        program_id,
+        remaining,
+        NotificationKind::Transfer,
+        pda.key,
+        &previous_authority,
+        &record.authority,
+    )?;
+    move_vault_index(
+        program_id,
+        remaining,
+        pda.key,
+        &previous_authority,
+        &record.authority,
+    )?;
+    event::emit(
+        program_id,
+        accounts,
+        &AuthorityTransferred {
+            vault: *pda.key,
+            previous_authority,
+            authority: record.authority,
+        },
+    )
+}
+
+// Check a `TransferAuthority` of the vault to `new_authority` by `authority` without
+// signatures or writes, setting the outcome as return data.
+pub(super) fn check_transfer(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let CheckTransferAccounts {
+        pda,
+        authority,
+        new_authority,
+        remaining,
+    } = CheckTransferAccounts::parse(program_id, accounts)?;
+
+    let result = load_record(program_id, pda).and_then(|record| {
+        validate_active(&record)?;
+        validate_not_receipt_holder_mode(&record)?;
+        if *authority.key != record.authority {
+            msg!("Incorrect authority provided");
+            return Err(VaultError::IncorrectAuthority.into());
+        }
+        validate_transfer_rules(
+            program_id,
+            pda,
+            &record,
+            &record.dart,
+            new_authority,
+            remaining,
+        )
+    });
+    let error = match result {
+        Ok(()) => {
+            msg!("Transfer would pass");
+            0
+        }
+        Err(error) => {
+            msg!("Transfer would fail: {}", error);
+            error.into()
+        }
+    };
+    set_return_data(&event::event_data(&TransferCheck {
+        vault: *pda.key,
+        authority: *authority.key,
+        new_authority: *new_authority.key,
+        error,
+    }));
+    Ok(())
+}
+
+// The checks of a transfer to `new_authority` past its signatures, shared with
+// `CheckTransfer`: the new authority, the price feed, pledges and transfer windows.
+// `dart` is the signing DART key, possibly an operational key of its identity.
+fn validate_transfer_rules<'a, 'b>(
+    program_id: &Pubkey,
+    pda: &'b AccountInfo<'a>,
+    record: &VaultRecord,
+    dart: &Pubkey,
+    new_authority: &'b AccountInfo<'a>,
+    remaining: &[AccountInfo],
+) -> ProgramResult {
+    if *new_authority.key == record.authority {
+        msg!("New authority matches current authority");
+        return Err(VaultError::AuthorityUnchanged.into());
+    }
+    if *new_authority.key == Pubkey::default() {
+        msg!("New authority must not be the default pubkey");
+        return Err(VaultError::InvalidAuthority.into());
+    }
+    if new_authority.key == dart || *new_authority.key == record.dart {
+        msg!("New authority must not be the DART");
+        return Err(VaultError::AuthorityIsDart.into());
+    }
+    validate_unique(&[pda, new_authority])?;
+    oracle::validate_oracle(record, remaining)?;
+    validate_pledge(program_id, pda.key, record, remaining)?;
+    validate_transfer_window(program_id, pda.key, record, remaining)
+}
+
+// Transfer ownership of a vault record, approved by a Merkle proof against the DART's
+// approval root rather than a DART signature.
+pub(super) fn transfer_with_proof(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    proof: &[[u8; 32]],
+) -> ProgramResult {
+    let TransferWithProofAccounts {
+        pda,
+        approval,
+        authority,
+        new_authority,
+        remaining,
+    } = TransferWithProofAccounts::parse(program_id, accounts)?;
+
+    let mut record = load_record(program_id, pda)?;
+    validate_active(&record)?;
+    validate_not_receipt_holder_mode(&record)?;
+
+    let approval_root = load_approval_root(program_id, approval)?;
+    if approval_root.dart != record.dart {
+        msg!("Approval root is not the record DART's");
+        return Err(VaultError::IncorrectDart.into());
+    }
+    if !approval_root.is_live(Clock::get()?.slot) {
+        msg!(
+            "Approval root revoked or expired at slot {}",
+            approval_root.expires_at_slot
+        );
+        return Err(VaultError::InstructionExpired.into());
+    }
+    let leaf = approval::transfer_leaf(
+        program_id,
+        pda.key,
+        record.approval_nonce,
+        &record.authority,
+        new_authority.key,
+    );
+    if proof.len() > approval::MAX_PROOF_LEN
+        || !approval::verify_proof(&leaf, proof, &approval_root.root)
+    {
+        msg!("Transfer to {} not approved by the DART", new_authority.key);
+        return Err(VaultError::InvalidProof.into());
+    }
+
+    validate_authority(
+        program_id,
+        pda,
+        authority,
+        remaining,
+        &record.authority,
+        session_scope::TRANSFER_AUTHORITY,
+    )?;
+    if *new_authority.key == record.authority {
+        msg!("New authority matches current authority");
+        return Err(VaultError::AuthorityUnchanged.into());
+    }
+    if *new_authority.key == Pubkey::default() {
+        msg!("New authority must not be the default pubkey");
+        return Err(VaultError::InvalidAuthority.into());
+    }
+    if *new_authority.key == record.dart {
+        msg!("New authority must not be the DART");
+        return Err(VaultError::AuthorityIsDart.into());
+    }
+    validate_unique(&[pda, new_authority])?;
+    oracle::validate_oracle(&record, remaining)?;
+    validate_pledge(program_id, pda.key, &record, remaining)?;
+    validate_transfer_window(program_id, pda.key, &record, remaining)?;
+
+    let previous_authority = record.authority;
+    record.authority = *new_authority.key;
+    record.approval_nonce = math::checked_add(record.approval_nonce, 1)?;
+
+    record.pack_into(&mut pda.data.borrow_mut())?;
+    notify_change(
+        program_id,
+        remaining,
+        NotificationKind::Transfer,
+        pda.key,
+        &previous_authority,
+        &record.authority,
+    )?;
+    move_vault_index(
+        program_id,
+        remaining,
+        pda.key,
+        &previous_authority,
+        &record.authority,
+    )?;
+    event::emit(
+        program_id,
+        accounts,
+        &AuthorityTransferred {
+            vault: *pda.key,
+            previous_authority,
+            authority: record.authority,
+        },
+    )
+}
+
+// Propose an escrowed transfer of a vault record, accepted by the new authority.
+pub(super) fn propose_transfer(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    timeout_slots: u64,
+) -> ProgramResult {
+    let ProposeTransferAccounts {
+        pda,
+        dart,
+        authority,
+        new_authority,
+        remaining,
+    } = ProposeTransferAccounts::parse(program_id, accounts)?;
+
+    let mut record = load_record(program_id, pda)?;
+    validate_active(&record)?;
+
+    validate_not_receipt_holder_mode(&record)?;
+    validate_dart(program_id, dart, remaining, &record.dart)?;
+    validate_authority(
+        program_id,
+        pda,
+        authority,
+        remaining,
+        &record.authority,
+        session_scope::TRANSFER_AUTHORITY,
+    )?;
+
+    if *new_authority.key == record.authority {
+        msg!("New authority matches current authority");
+        return Err(VaultError::AuthorityUnchanged.into());
+    }
+    if *new_authority.key == Pubkey::default() {
+        msg!("New authority must not be the default pubkey");
+        return Err(VaultError::InvalidAuthority.into());
+    }
+    if new_authority.key == dart.key || *new_authority.key == record.dart {
+        msg!("New authority must not be the DART");
+        return Err(VaultError::AuthorityIsDart.into());
+    }
+    validate_unique(&[pda, new_authority])?;
+    if timeout_slots == 0 {
+        msg!("Transfer timeout must be at least one slot");
+        return Err(VaultError::InvalidTransferTimeout.into());
+    }
+    validate_pledge(program_id, pda.key, &record, remaining)?;
+    validate_transfer_window(program_id, pda.key, &record, remaining)?;
+
+    record.status.transition_to(VaultStatus::PendingTransfer)?;
+    record.pending_authority = *new_authority.key;
+    record.transfer_expires_at_slot = math::checked_add(Clock::get()?.slot, timeout_slots)?;
+
+    record.pack_into(&mut pda.data.borrow_mut())
+}
+
+// Complete an escrowed transfer, signed by the proposed authority before it expires.
+pub(super) fn accept_transfer(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let AcceptTransferAccounts {
+        pda,
+        new_authority,
+        remaining,
+    } = AcceptTransferAccounts::parse(program_id, accounts)?;
+
+    let mut record = load_record(program_id, pda)?;
+    if record.status != VaultStatus::PendingTransfer {
+        msg!("no pending transfer");
+        return Err(VaultError::NoPendingTransfer.into());
+    }
+    validate_signer(
+        new_authority,
+        &record.pending_authority,
+        VaultError::IncorrectAuthority,
+    )?;
+    if Clock::get()?.slot > record.transfer_expires_at_slot {
+        msg!(
+            "transfer expired at slot {}",
+            record.transfer_expires_at_slot
+        );
+        return Err(VaultError::TransferExpired.into());
+    }
+    // The DART may have been succeeded by the proposed authority since the proposal.
+    if record.pending_authority == record.dart {
+        msg!("New authority must not be the DART");
+        return Err(VaultError::AuthorityIsDart.into());
+    }
+    oracle::validate_oracle(&record, remaining)?;
+    validate_transfer_window(program_id, pda.key, &record, remaining)?;
+
+    record.status.transition_to(VaultStatus::Active)?;
+    let previous_authority = record.authority;
+    record.authority = record.pending_authority;
+    record.pending_authority = Pubkey::default();
+    record.transfer_expires_at_slot = 0;
+
+    record.pack_into(&mut pda.data.borrow_mut())?;
+    notify_change(
+        program_id,
+        remaining,
+        NotificationKind::Transfer,
+        pda.key,
+        &previous_authority,
+        &record.authority,
+    )?;
+    move_vault_index(
+        program_id,
+        remaining,
+        pda.key,
+        &previous_authority,
+        &record.authority,
+    )?;
+    event::emit(
+        program_id,
+        accounts,
+        &AuthorityTransferred {
+            vault: *pda.key,
+            previous_authority,
+            authority: record.authority,
+        },
+    )
+}
+
+// Drop an expired escrowed transfer, leaving the original authority in place.
+pub(super) fn expire_transfer(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let ExpireTransferAccounts { pda } = ExpireTransferAccounts::parse(program_id, accounts)?;
+
+    let mut record = load_record(program_id, pda)?;
+    if record.status != VaultStatus::PendingTransfer {
+        msg!("no pending transfer");
+        return Err(VaultError::NoPendingTransfer.into());
+    }
+    if Clock::get()?.slot <= record.transfer_expires_at_slot {
+        msg!(
+            "transfer may be accepted until slot {}",
+            record.transfer_expires_at_slot
+        );
+        return Err(VaultError::TransferNotExpired.into());
+    }
+
+    record.status.transition_to(VaultStatus::Active)?;
+    record.pending_authority = Pubkey::default();
+    record.transfer_expires_at_slot = 0;
+
+    record.pack_into(&mut pda.data.borrow_mut())
+}
+
+// Exchange the authorities of two vault records under the same DART.
+pub(super) fn swap_authorities(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let SwapAuthoritiesAccounts {
+        pda_a,
+        pda_b,
+        dart,
+        authority_a,
+        authority_b,
+        remaining,
+    } = SwapAuthoritiesAccounts::parse(program_id, accounts)?;
+
+    let mut record_a = load_record(program_id, pda_a)?;
+    let mut record_b = load_record(program_id, pda_b)?;
+    validate_active(&record_a)?;
+    validate_active(&record_b)?;
+    validate_not_receipt_holder_mode(&record_a)?;
+    validate_not_receipt_holder_mode(&record_b)?;
+
+    if record_a.dart != record_b.dart {
+        msg!("Vault records have different DARTs");
+        return Err(VaultError::IncorrectDart.into());
+    }
+    validate_dart(program_id, dart, remaining, &record_a.dart)?;
+    for (pda, authority, record) in [
+        (pda_a, authority_a, &record_a),
+        (pda_b, authority_b, &record_b),
+    ] {
+        validate_authority(
+            program_id,
+            pda,
+            authority,
+            remaining,
+            &record.authority,
+            session_scope::TRANSFER_AUTHORITY,
+        )?;
+    }
+    if record_a.authority == record_b.authority {
+        msg!("Vault records have the same authority");
+        return Err(VaultError::AuthorityUnchanged.into());
+    }
+    oracle::validate_oracle(&record_a, remaining)?;
+    oracle::validate_oracle(&record_b, remaining)?;
+    validate_pledge(program_id, pda_a.key, &record_a, remaining)?;
+    validate_pledge(program_id, pda_b.key, &record_b, remaining)?;
+    validate_transfer_window(program_id, pda_a.key, &record_a, remaining)?;
+    validate_transfer_window(program_id, pda_b.key, &record_b, remaining)?;
+    // The processor counts and records the vault at account 0; this is the other one.
+    consume_vault_rate_limit(program_id, pda_b, remaining)?;
+    let trail_b = find_audit_trail(program_id, pda_b, remaining)?;
+
+    std::mem::swap(&mut record_a.authority, &mut record_b.authority);
+
+    record_a.pack_into(&mut pda_a.data.borrow_mut())?;
+    record_b.pack_into(&mut pda_b.data.borrow_mut())?;
+    for (pda, record, previous) in [(pda_a, &record_a, &record_b), (pda_b, &record_b, &record_a)] {
+        notify_change(
+            program_id,
+            remaining,
+            NotificationKind::Transfer,
+            pda.key,
+            &previous.authority,
+            &record.authority,
+        )?;
+        move_vault_index(
+            program_id,
+            remaining,
+            pda.key,
+            &previous.authority,
+            &record.authority,
+        )?;
+        event::emit(
+            program_id,
+            accounts,
+            &AuthorityTransferred {
+                vault: *pda.key,
+                previous_authority: previous.authority,
+                authority: record.authority,
+            },
+        )?;
+    }
+    match trail_b {
+        Some(trail) => record_audit(trail, accounts, VaultInstruction::SwapAuthorities.tag()),
+        None => Ok(()),
+    }
+}
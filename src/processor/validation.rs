@@ -0,0 +1,1072 @@
+use {
+    crate::{
+        approval,
+        error::VaultError,
+        math, quorum, receipt,
+        state::{
+            find_asset_token_address, ApprovalRoot, AuditEntry, AuditTrail, DartIdentity,
+            DartPolicy, FeeConfig, InheritanceRecord, Notification, NotificationKind, NotifyRecord,
+            PledgeRecord, PositionRecord, ProgramConfig, ProgramStats, RateLimit, RecoveryRecord,
+            SessionRecord, TransferHookConfig, TransferWindow, VaultIndex, VaultRecord,
+            VaultStatus, TRANSFER_WINDOW_DART, TRANSFER_WINDOW_VAULT,
+        },
+        validation::{require_initialized, require_owner, require_signer, require_writable},
+    },
+    borsh::{BorshDeserialize, BorshSerialize},
+    solana_program::{
+        account_info::AccountInfo,
+        clock::Clock,
+        ed25519_program,
+        entrypoint::ProgramResult,
+        msg,
+        program::invoke_signed,
+        program_error::ProgramError,
+        program_pack::{IsInitialized, Pack},
+        pubkey::Pubkey,
+        rent::Rent,
+        system_instruction, system_program,
+        sysvar::{
+            self,
+            instructions::{load_current_index_checked, load_instruction_at_checked},
+            Sysvar,
+        },
+    },
+    spl_discriminator::SplDiscriminate,
+    spl_token::state::Account as TokenAccount,
+};
+
+pub(super) fn validate_signer(
+    account: &AccountInfo,
+    key: &Pubkey,
+    error: VaultError,
+) -> ProgramResult {
+    if key != account.key {
+        msg!("Account key mismatch");
+        return Err(error.into());
+    }
+    require_signer(account)?;
+    Ok(())
+}
+
+// Check that `account` is the program `expected` the instruction invokes.
+pub(super) fn validate_program(account: &AccountInfo, expected: &Pubkey) -> ProgramResult {
+    if account.key != expected {
+        msg!("Incorrect program {}, expected {}", account.key, expected);
+        return Err(VaultError::IncorrectProgram.into());
+    }
+    Ok(())
+}
+
+pub(super) fn validate_unique(accounts: &[&AccountInfo]) -> ProgramResult {
+    for (i, account) in accounts.iter().enumerate() {
+        if accounts[i + 1..]
+            .iter()
+            .any(|other| other.key == account.key)
+        {
+            msg!("Account {} provided for more than one role", account.key);
+            return Err(VaultError::DuplicateAccount.into());
+        }
+    }
+    Ok(())
+}
+
+pub(super) fn validate_account_flags<'a, 'b>(
+    writable: &[&'b AccountInfo<'a>],
+    readonly: &[&'b AccountInfo<'a>],
+) -> ProgramResult {
+    for account in writable {
+        require_writable(account)?;
+    }
+    for account in writable.iter().chain(readonly) {
+        if account.executable {
+            msg!("Account {} must not be executable", account.key);
+            return Err(VaultError::ExecutableAccount.into());
+        }
+    }
+    Ok(())
+}
+
+pub(super) fn validate_active(record: &VaultRecord) -> ProgramResult {
+    match record.status {
+        VaultStatus::Active => Ok(()),
+        VaultStatus::Closed => {
+            msg!("vault account closed");
+            Err(VaultError::AccountClosed.into())
+        }
+        status => {
+            msg!("vault status {:?} does not permit this instruction", status);
+            Err(VaultError::VaultNotActive.into())
+        }
+    }
+}
+
+// Roles held by the default pubkey, the system program or this program can never sign,
+// so a record naming one would be unusable.
+pub(super) fn validate_role_key(program_id: &Pubkey, key: &Pubkey) -> ProgramResult {
+    if *key == Pubkey::default() || *key == system_program::id() || key == program_id {
+        msg!("{} cannot hold a vault role", key);
+        return Err(VaultError::InvalidRoleKey.into());
+    }
+    Ok(())
+}
+
+pub(super) fn load_dart_identity(
+    program_id: &Pubkey,
+    account: &AccountInfo,
+) -> Result<DartIdentity, ProgramError> {
+    require_owner(account, program_id)?;
+    let identity = DartIdentity::try_from_slice(&account.data.borrow())?;
+    require_initialized(account, &identity)?;
+    Ok(identity)
+}
+
+pub(super) fn load_program_config(
+    program_id: &Pubkey,
+    account: &AccountInfo,
+) -> Result<ProgramConfig, ProgramError> {
+    if *account.key != ProgramConfig::find_address(program_id).0 {
+        msg!("Account is not the program config");
+        return Err(ProgramError::InvalidSeeds);
+    }
+    require_owner(account, program_id)?;
+    let config = ProgramConfig::try_from_slice(&account.data.borrow())?;
+    require_initialized(account, &config)?;
+    Ok(config)
+}
+
+pub(super) fn load_fee_config(
+    program_id: &Pubkey,
+    account: &AccountInfo,
+) -> Result<FeeConfig, ProgramError> {
+    if *account.key != FeeConfig::find_address(program_id).0 {
+        msg!("Account is not the fee config");
+        return Err(ProgramError::InvalidSeeds);
+    }
+    require_owner(account, program_id)?;
+    let config = FeeConfig::try_from_slice(&account.data.borrow())?;
+    require_initialized(account, &config)?;
+    Ok(config)
+}
+
+pub(super) fn load_approval_root(
+    program_id: &Pubkey,
+    account: &AccountInfo,
+) -> Result<ApprovalRoot, ProgramError> {
+    require_owner(account, program_id)?;
+    let approval = ApprovalRoot::try_from_slice(&account.data.borrow())?;
+    require_initialized(account, &approval)?;
+    if *account.key != ApprovalRoot::find_address(program_id, &approval.dart).0 {
+        msg!("Account is not the approval root of its DART");
+        return Err(ProgramError::InvalidSeeds);
+    }
+    Ok(approval)
+}
+
+// Serialize `value` over the whole of `account`'s data, failing with `InvalidDataLength`
+// rather than leaving stale bytes behind a shorter encoding, or truncating a longer one.
+pub(super) fn write_account<T: BorshSerialize>(account: &AccountInfo, value: &T) -> ProgramResult {
+    let bytes = borsh::to_vec(value)?;
+    let mut data = account.data.borrow_mut();
+    if data.len() != bytes.len() {
+        msg!(
+            "Account {} is {} bytes, expected {}",
+            account.key,
+            data.len(),
+            bytes.len()
+        );
+        return Err(VaultError::InvalidDataLength.into());
+    }
+    data.copy_from_slice(&bytes);
+    Ok(())
+}
+
+// Fail once the program is decommissioned. The program config must be among
+// `accounts` whether or not it exists yet, so a caller cannot skip the check by leaving
+// it out, and its absence fails with `NotEnoughAccountKeys`; before `InitializeConfig`
+// its address holds no program account and nothing is migrated.
+pub(super) fn validate_not_migrated(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let (address, _) = ProgramConfig::find_address(program_id);
+    let Some(account) = find_account(accounts, &address) else {
+        msg!("Missing program config {}", address);
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    if account.owner != program_id {
+        return Ok(());
+    }
+    let config = ProgramConfig::try_from_slice(&account.data.borrow())?;
+    if config.is_migrated() {
+        msg!("Program migrated to {}", config.successor);
+        return Err(VaultError::ProgramMigrated.into());
+    }
+    Ok(())
+}
+
+// The batch of a batched instruction among `remaining`: the accounts before the program
+// config, which the builders append after the batch and before any DART identity or
+// instructions sysvar.
+pub(super) fn batch_accounts<'a, 'b>(
+    program_id: &Pubkey,
+    remaining: &'b [AccountInfo<'a>],
+) -> &'b [AccountInfo<'a>] {
+    let (address, _) = ProgramConfig::find_address(program_id);
+    remaining
+        .iter()
+        .position(|account| *account.key == address)
+        .map_or(remaining, |position| &remaining[..position])
+}
+
+// Notify the registered key of a change of `vault` by `previous_authority`, when the
+// vault has one and it applies: record it in the key's notification account, which must
+// then be passed, writable. The vault's notify record address must be passed either way,
+// so a caller cannot skip the notification by leaving the record out.
+pub(super) fn notify_change(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    kind: NotificationKind,
+    vault: &Pubkey,
+    previous_authority: &Pubkey,
+    authority: &Pubkey,
+) -> ProgramResult {
+    let (address, _) = NotifyRecord::find_address(program_id, vault);
+    let Some(account) = find_account(accounts, &address) else {
+        msg!("Missing notify record {}", address);
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    if account.owner != program_id {
+        return Ok(());
+    }
+    let registration = NotifyRecord::try_from_slice(&account.data.borrow())?;
+    if registration.vault != *vault || !registration.applies_to(previous_authority) {
+        return Ok(());
+    }
+    let address = Notification::find_address(program_id, &registration.notify).0;
+    let notification_info = find_account(accounts, &address).ok_or_else(|| {
+        msg!("Missing notification account {}", address);
+        ProgramError::NotEnoughAccountKeys
+    })?;
+    require_writable(notification_info)?;
+    require_owner(notification_info, program_id)?;
+    let mut notification = Notification::try_from_slice(&notification_info.data.borrow())?;
+    require_initialized(notification_info, &notification)?;
+    notification.record(
+        kind,
+        *vault,
+        *previous_authority,
+        *authority,
+        Clock::get()?.slot,
+    );
+    write_account(notification_info, &notification)
+}
+
+// The fee config among `accounts`, or `None` before the fee setters create it. Its
+// address must be passed either way, so a caller cannot skip the fee by leaving it out.
+pub(super) fn find_fee_config<'a, 'b>(
+    program_id: &Pubkey,
+    accounts: &'b [AccountInfo<'a>],
+) -> Result<Option<&'b AccountInfo<'a>>, ProgramError> {
+    let (address, _) = FeeConfig::find_address(program_id);
+    let Some(account) = find_account(accounts, &address) else {
+        msg!("Missing fee config {}", address);
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    Ok((account.owner == program_id).then_some(account))
+}
+
+// The program stats, when among `accounts`. Only `InitializeStats` creates
+// program-owned accounts with the stats' discriminator, at the stats address, so the
+// discriminator alone identifies it.
+pub(super) fn find_program_stats<'a, 'b>(
+    program_id: &Pubkey,
+    accounts: &'b [AccountInfo<'a>],
+) -> Option<&'b AccountInfo<'a>> {
+    accounts.iter().find(|account| {
+        account.owner == program_id
+            && account
+                .data
+                .borrow()
+                .starts_with(ProgramStats::SPL_DISCRIMINATOR_SLICE)
+    })
+}
+
+// Count a successful invocation of instruction `tag` in the program stats.
+pub(super) fn count_invocation(stats: &AccountInfo, tag: u8) -> ProgramResult {
+    require_writable(stats)?;
+    ProgramStats::count(&mut stats.data.borrow_mut(), tag)
+}
+
+// Keep the vault index of `authority` current: list `vault` when `listed`, unlist it
+// otherwise. Nothing happens before `SyncVaultIndex` creates the index, but its address
+// must be passed either way, so a caller cannot leave the index stale by leaving it out.
+pub(super) fn update_vault_index(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    authority: &Pubkey,
+    vault: &Pubkey,
+    listed: bool,
+) -> ProgramResult {
+    let (address, _) = VaultIndex::find_address(program_id, authority);
+    let Some(account) = find_account(accounts, &address) else {
+        msg!("Missing vault index {}", address);
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    if account.owner != program_id {
+        return Ok(());
+    }
+    require_writable(account)?;
+    let mut index = load_vault_index(program_id, authority, account)?;
+    if listed {
+        index.insert(*vault)?;
+    } else {
+        index.remove(vault);
+    }
+    write_account(account, &index)
+}
+
+// Move `vault` from the vault index of `authority` to that of `new_authority` on a
+// change of its authority. Both index addresses must be passed, unless the authority
+// stays the same.
+pub(super) fn move_vault_index(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    vault: &Pubkey,
+    authority: &Pubkey,
+    new_authority: &Pubkey,
+) -> ProgramResult {
+    if authority == new_authority {
+        return Ok(());
+    }
+    update_vault_index(program_id, accounts, authority, vault, false)?;
+    update_vault_index(program_id, accounts, new_authority, vault, true)
+}
+
+// Optional positional account: omitted, or the program id in its place, when absent.
+// The program config `config`, which may come anywhere after the fixed accounts, is
+// skipped rather than taken for one.
+pub(super) fn next_optional_account<'a, 'b>(
+    program_id: &Pubkey,
+    config: &Pubkey,
+    iter: &mut std::slice::Iter<'b, AccountInfo<'a>>,
+) -> Option<&'b AccountInfo<'a>> {
+    iter.find(|account| account.key != config)
+        .filter(|account| account.key != program_id)
+}
+
+pub(super) fn find_account<'a, 'b>(
+    accounts: &'b [AccountInfo<'a>],
+    key: &Pubkey,
+) -> Option<&'b AccountInfo<'a>> {
+    accounts.iter().find(|account| account.key == key)
+}
+
+// Check the DART role: either `expected` itself signed, or `expected` is a DART identity
+// passed among `remaining` and `dart` is one of its current operational keys, or the
+// identity is passed unsigned as `dart` and approves through its quorum.
+pub(super) fn validate_dart(
+    program_id: &Pubkey,
+    dart: &AccountInfo,
+    remaining: &[AccountInfo],
+    expected: &Pubkey,
+) -> ProgramResult {
+    if dart.key == expected && !dart.is_signer && dart.owner == program_id {
+        return validate_dart_quorum(program_id, dart, remaining);
+    }
+    let identity = match find_account(remaining, expected) {
+        Some(identity) if dart.key != expected => identity,
+        _ => return validate_signer(dart, expected, VaultError::IncorrectDart),
+    };
+    if !load_dart_identity(program_id, identity)?.is_operational(dart.key) {
+        msg!("Not an operational key of the DART identity");
+        return Err(VaultError::IncorrectDart.into());
+    }
+    require_signer(dart)?;
+    Ok(())
+}
+
+// A DART identity approves the current instruction when enough of its operational keys
+// signed its approval message in Ed25519 program instructions of the transaction. The
+// approval consumes the identity's nonce.
+pub(super) fn validate_dart_quorum(
+    program_id: &Pubkey,
+    identity: &AccountInfo,
+    remaining: &[AccountInfo],
+) -> ProgramResult {
+    let mut record = load_dart_identity(program_id, identity)?;
+    if record.quorum == 0 {
+        msg!("Missing required signature");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    validate_account_flags(&[identity], &[])?;
+
+    let instructions = find_account(remaining, &sysvar::instructions::id()).ok_or_else(|| {
+        msg!("Quorum approval requires the instructions sysvar");
+        VaultError::IncorrectSysvar
+    })?;
+    let current = load_instruction_at_checked(
+        usize::from(load_current_index_checked(instructions)?),
+        instructions,
+    )?;
+    if current.program_id != *program_id {
+        msg!("Quorum approval requires a top-level vault instruction");
+        return Err(VaultError::QuorumNotMet.into());
+    }
+    let message = approval::message(&current, record.approval_nonce);
+
+    let mut approvals: Vec<Pubkey> = Vec::new();
+    let mut index = 0;
+    while let Ok(instruction) = load_instruction_at_checked(index, instructions) {
+        if instruction.program_id == ed25519_program::id() {
+            for key in quorum::signers_of(&instruction.data, &message) {
+                if record.is_operational(&key) && !approvals.contains(&key) {
+                    approvals.push(key);
+                }
+            }
+        }
+        index += 1;
+    }
+    if approvals.len() < usize::from(record.quorum) {
+        msg!(
+            "{} of {} operational keys approved",
+            approvals.len(),
+            record.quorum
+        );
+        return Err(VaultError::QuorumNotMet.into());
+    }
+
+    record.approval_nonce = math::checked_add(record.approval_nonce, 1)?;
+    write_account(identity, &record)
+}
+
+pub(super) fn load_record(
+    program_id: &Pubkey,
+    pda: &AccountInfo,
+) -> Result<VaultRecord, ProgramError> {
+    require_owner(pda, program_id)?;
+    let record = VaultRecord::from_account_data(&pda.data.borrow())?;
+    require_initialized(pda, &record)?;
+    Ok(record)
+}
+
+pub(super) fn load_recovery(
+    program_id: &Pubkey,
+    vault: &Pubkey,
+    account: &AccountInfo,
+) -> Result<RecoveryRecord, ProgramError> {
+    if *account.key != RecoveryRecord::find_address(program_id, vault).0 {
+        msg!("Recovery account does not match the vault");
+        return Err(ProgramError::InvalidSeeds);
+    }
+    require_owner(account, program_id)?;
+    let recovery = RecoveryRecord::try_from_slice(&account.data.borrow())?;
+    require_initialized(account, &recovery)?;
+    Ok(recovery)
+}
+
+pub(super) fn load_inheritance(
+    program_id: &Pubkey,
+    vault: &Pubkey,
+    account: &AccountInfo,
+) -> Result<InheritanceRecord, ProgramError> {
+    if *account.key != InheritanceRecord::find_address(program_id, vault).0 {
+        msg!("Inheritance account does not match the vault");
+        return Err(ProgramError::InvalidSeeds);
+    }
+    require_owner(account, program_id)?;
+    let inheritance = InheritanceRecord::try_from_slice(&account.data.borrow())?;
+    require_initialized(account, &inheritance)?;
+    Ok(inheritance)
+}
+
+pub(super) fn load_transfer_hook_config(
+    program_id: &Pubkey,
+    mint: &Pubkey,
+    account: &AccountInfo,
+) -> Result<TransferHookConfig, ProgramError> {
+    if *account.key != TransferHookConfig::find_address(program_id, mint).0 {
+        msg!("Transfer hook config does not match the mint");
+        return Err(ProgramError::InvalidSeeds);
+    }
+    require_owner(account, program_id)?;
+    let config = TransferHookConfig::try_from_slice(&account.data.borrow())?;
+    require_initialized(account, &config)?;
+    Ok(config)
+}
+
+// In receipt-holder mode the authority is the owner of the token account holding the
+// vault's receipt, passed among `remaining`; bring the record's authority up to date.
+pub(super) fn sync_receipt_holder(
+    record: &mut VaultRecord,
+    remaining: &[AccountInfo],
+) -> ProgramResult {
+    if !record.receipt_is_authority {
+        return Ok(());
+    }
+    let (_, token) = receipt::find_holder(remaining, &record.receipt_mint).ok_or_else(|| {
+        msg!(
+            "Missing token account holding receipt {}",
+            record.receipt_mint
+        );
+        VaultError::ReceiptNotFound
+    })?;
+    record.authority = token.owner;
+    Ok(())
+}
+
+pub(super) fn load_pledge(
+    program_id: &Pubkey,
+    vault: &Pubkey,
+    account: &AccountInfo,
+) -> Result<PledgeRecord, ProgramError> {
+    let (address, _) = PledgeRecord::find_address(program_id, vault);
+    if *account.key != address {
+        msg!("Pledge account does not match the vault");
+        return Err(ProgramError::InvalidSeeds);
+    }
+    require_owner(account, program_id)?;
+    let pledge = PledgeRecord::try_from_slice(&account.data.borrow())?;
+    require_initialized(account, &pledge)?;
+    Ok(pledge)
+}
+
+pub(super) fn load_transfer_window(
+    program_id: &Pubkey,
+    kind: &[u8],
+    scope: &Pubkey,
+    account: &AccountInfo,
+) -> Result<TransferWindow, ProgramError> {
+    let (address, _) = TransferWindow::find_address(program_id, kind, scope);
+    if *account.key != address {
+        msg!("Transfer window does not match {}", scope);
+        return Err(ProgramError::InvalidSeeds);
+    }
+    require_owner(account, program_id)?;
+    let window = TransferWindow::try_from_slice(&account.data.borrow())?;
+    require_initialized(account, &window)?;
+    Ok(window)
+}
+
+pub(super) fn load_dart_policy(
+    program_id: &Pubkey,
+    dart: &Pubkey,
+    account: &AccountInfo,
+) -> Result<DartPolicy, ProgramError> {
+    let (address, _) = DartPolicy::find_address(program_id, dart);
+    if *account.key != address {
+        msg!("Policy account does not match the DART");
+        return Err(ProgramError::InvalidSeeds);
+    }
+    require_owner(account, program_id)?;
+    let policy = DartPolicy::try_from_slice(&account.data.borrow())?;
+    require_initialized(account, &policy)?;
+    Ok(policy)
+}
+
+pub(super) fn load_rate_limit(
+    program_id: &Pubkey,
+    vault: &Pubkey,
+    account: &AccountInfo,
+) -> Result<RateLimit, ProgramError> {
+    let (address, _) = RateLimit::find_address(program_id, vault);
+    if *account.key != address {
+        msg!("Rate limit account does not match the vault");
+        return Err(ProgramError::InvalidSeeds);
+    }
+    require_owner(account, program_id)?;
+    let rate_limit = RateLimit::try_from_slice(&account.data.borrow())?;
+    require_initialized(account, &rate_limit)?;
+    Ok(rate_limit)
+}
+
+pub(super) fn load_vault_index(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    account: &AccountInfo,
+) -> Result<VaultIndex, ProgramError> {
+    let (address, _) = VaultIndex::find_address(program_id, authority);
+    if *account.key != address {
+        msg!("Vault index does not match the authority");
+        return Err(ProgramError::InvalidSeeds);
+    }
+    require_owner(account, program_id)?;
+    let index = VaultIndex::try_from_slice(&account.data.borrow())?;
+    require_initialized(account, &index)?;
+    Ok(index)
+}
+
+pub(super) fn load_position(
+    program_id: &Pubkey,
+    vault: &Pubkey,
+    account: &AccountInfo,
+) -> Result<PositionRecord, ProgramError> {
+    let (address, _) = PositionRecord::find_address(program_id, vault);
+    if *account.key != address {
+        msg!("Position account does not match the vault");
+        return Err(ProgramError::InvalidSeeds);
+    }
+    require_owner(account, program_id)?;
+    let position = PositionRecord::try_from_slice(&account.data.borrow())?;
+    require_initialized(account, &position)?;
+    Ok(position)
+}
+
+// A vault is only closed or moved empty. Its position account must be passed among
+// `remaining` whether or not it exists yet; once it does, so must the vault's associated
+// token account for the position's asset. Both, and any other position or token
+// account of `vault` passed, must hold nothing.
+pub(super) fn validate_vault_empty(
+    program_id: &Pubkey,
+    vault: &Pubkey,
+    remaining: &[AccountInfo],
+) -> ProgramResult {
+    let (address, _) = PositionRecord::find_address(program_id, vault);
+    let Some(position_info) = find_account(remaining, &address) else {
+        msg!("Missing position account {} of the vault", address);
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    if position_info.owner == program_id {
+        let position = load_position(program_id, vault, position_info)?;
+        let (address, _) = find_asset_token_address(vault, &position.asset_id);
+        if find_account(remaining, &address).is_none() {
+            msg!("Missing token account {} of the vault's asset", address);
+            return Err(ProgramError::NotEnoughAccountKeys);
+        }
+    }
+    for account in remaining {
+        let data = account.data.borrow();
+        let held = if account.owner == program_id
+            && data.starts_with(PositionRecord::SPL_DISCRIMINATOR_SLICE)
+        {
+            let position = PositionRecord::try_from_slice(&data)?;
+            position.vault == *vault && position.balance != 0
+        } else if *account.owner == spl_token::id() && data.len() == TokenAccount::LEN {
+            let token = TokenAccount::unpack(&data)?;
+            token.owner == *vault && token.amount != 0
+        } else {
+            false
+        };
+        if held {
+            msg!("Account {} still holds assets of the vault", account.key);
+            return Err(VaultError::VaultNotEmpty.into());
+        }
+    }
+    Ok(())
+}
+
+// A vault pledged as collateral only changes hands with the lender's signature: its
+// pledge account and the signing lender must be passed among `remaining`.
+pub(super) fn validate_pledge(
+    program_id: &Pubkey,
+    vault: &Pubkey,
+    record: &VaultRecord,
+    remaining: &[AccountInfo],
+) -> ProgramResult {
+    if !record.pledged {
+        return Ok(());
+    }
+    let (address, _) = PledgeRecord::find_address(program_id, vault);
+    let pledge = find_account(remaining, &address)
+        .map(|account| load_pledge(program_id, vault, account))
+        .transpose()?;
+    match pledge.and_then(|pledge| find_account(remaining, &pledge.lender)) {
+        Some(lender) if lender.is_signer => Ok(()),
+        _ => {
+            msg!("Vault pledged as collateral; the lender must sign");
+            Err(VaultError::VaultPledged.into())
+        }
+    }
+}
+
+// The authority only changes inside the vault's transfer window, which must be passed
+// among `remaining` while the record is flagged, and inside its DART's window. The DART
+// window must be passed whether or not it exists yet, so a caller cannot skip it by
+// leaving it out; before `SetTransferWindow` creates it, its address holds no program
+// account and nothing is checked. Every instruction changing the authority calls this.
+pub(super) fn validate_transfer_window(
+    program_id: &Pubkey,
+    vault: &Pubkey,
+    record: &VaultRecord,
+    remaining: &[AccountInfo],
+) -> ProgramResult {
+    let (vault_window, _) = TransferWindow::find_address(program_id, TRANSFER_WINDOW_VAULT, vault);
+    let (dart_window, _) =
+        TransferWindow::find_address(program_id, TRANSFER_WINDOW_DART, &record.dart);
+    let vault_window = find_account(remaining, &vault_window);
+    if record.transfer_windowed && vault_window.is_none() {
+        msg!("Vault has a transfer window; pass its account");
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+    let Some(dart_window) = find_account(remaining, &dart_window) else {
+        msg!("Missing transfer window {} of the DART", dart_window);
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    let windows = [
+        (TRANSFER_WINDOW_VAULT, vault, vault_window),
+        (TRANSFER_WINDOW_DART, &record.dart, Some(dart_window)),
+    ];
+    for (kind, scope, account) in windows {
+        let Some(account) = account.filter(|account| account.owner == program_id) else {
+            continue;
+        };
+        let window = load_transfer_window(program_id, kind, scope, account)?;
+        if !window.is_open(Clock::get()?.slot) {
+            msg!("Transfer window of {} is closed", scope);
+            return Err(VaultError::TransferWindowClosed.into());
+        }
+    }
+    Ok(())
+}
+
+// Instructions that would move the vault beyond the pledge account's reach are refused
+// until the collateral is released.
+pub(super) fn validate_not_pledged(record: &VaultRecord) -> ProgramResult {
+    if record.pledged {
+        msg!("Release the collateral first");
+        return Err(VaultError::VaultPledged.into());
+    }
+    Ok(())
+}
+
+// The DART policy may bar a vault from some instructions, by `restriction` bit.
+pub(super) fn validate_unrestricted(record: &VaultRecord, restriction: u8) -> ProgramResult {
+    if record.restrictions & restriction != 0 {
+        msg!("Vault policy restricts this instruction");
+        return Err(VaultError::VaultRestricted.into());
+    }
+    Ok(())
+}
+
+// Authority transfers are token transfers in receipt-holder mode.
+pub(super) fn validate_not_receipt_holder_mode(record: &VaultRecord) -> ProgramResult {
+    if record.receipt_is_authority {
+        msg!("Vault authority follows its receipt token");
+        return Err(VaultError::AuthorityFollowsReceipt.into());
+    }
+    Ok(())
+}
+
+// Count an instruction acting on the vault record at account 0 against its rate limit.
+pub(super) fn consume_rate_limit(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    match accounts.split_first() {
+        Some((pda, rest)) => consume_vault_rate_limit(program_id, pda, rest),
+        None => Ok(()),
+    }
+}
+
+// Count an instruction acting on `pda` against its rate limit, whose account must then
+// be among `accounts`, writable. Records that fail to load are left for the
+// instruction's handler to reject.
+pub(super) fn consume_vault_rate_limit(
+    program_id: &Pubkey,
+    pda: &AccountInfo,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    if pda.owner != program_id {
+        return Ok(());
+    }
+    match VaultRecord::from_account_data(&pda.data.borrow()) {
+        Ok(record) if record.rate_limited => {}
+        _ => return Ok(()),
+    }
+    let (address, _) = RateLimit::find_address(program_id, pda.key);
+    let Some(account) = find_account(accounts, &address) else {
+        msg!("Vault is rate limited; pass its rate limit account");
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    require_writable(account)?;
+    let mut rate_limit = load_rate_limit(program_id, pda.key, account)?;
+    if let Err(error) = rate_limit.consume(Clock::get()?.epoch) {
+        msg!("Vault took {} mutations this epoch", rate_limit.count);
+        return Err(error.into());
+    }
+    write_account(account, &rate_limit)
+}
+
+// The audit trail of `pda` among `accounts`, writable, when the vault has one; looked up
+// before the instruction runs, so a vault it closes is still recorded. Records that fail
+// to load are left for the instruction's handler to reject.
+pub(super) fn find_audit_trail<'a, 'b>(
+    program_id: &Pubkey,
+    pda: &AccountInfo,
+    accounts: &'b [AccountInfo<'a>],
+) -> Result<Option<&'b AccountInfo<'a>>, ProgramError> {
+    if pda.owner != program_id {
+        return Ok(None);
+    }
+    match VaultRecord::from_account_data(&pda.data.borrow()) {
+        Ok(record) if record.audited => {}
+        _ => return Ok(None),
+    }
+    let (address, _) = AuditTrail::find_address(program_id, pda.key);
+    let Some(account) = find_account(accounts, &address) else {
+        msg!("Vault has an audit trail; pass its account");
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    require_writable(account)?;
+    require_owner(account, program_id)?;
+    Ok(Some(account))
+}
+
+// A vault whose DART policy set `cosign` takes the DART's signature on every instruction
+// acting on it: the DART's own, or an operational key's with its identity among
+// `accounts`. Records that fail to load are left for the instruction's handler to reject.
+pub(super) fn validate_cosign(
+    program_id: &Pubkey,
+    pda: &AccountInfo,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    if pda.owner != program_id {
+        return Ok(());
+    }
+    let dart = match VaultRecord::from_account_data(&pda.data.borrow()) {
+        Ok(record) if record.cosign => record.dart,
+        _ => return Ok(()),
+    };
+    let mut signers = accounts.iter().filter(|account| account.is_signer);
+    if signers.clone().any(|account| *account.key == dart) {
+        return Ok(());
+    }
+    if let Some(identity) = find_account(accounts, &dart) {
+        if identity.owner == program_id {
+            let identity = load_dart_identity(program_id, identity)?;
+            if signers.any(|account| identity.is_operational(account.key)) {
+                return Ok(());
+            }
+        }
+    }
+    msg!("Vault requires the DART's co-signature");
+    Err(ProgramError::MissingRequiredSignature)
+}
+
+// Record `action` in an audit trail found by `find_audit_trail`, with the first signer
+// among `accounts` as the actor.
+pub(super) fn record_audit(
+    trail: &AccountInfo,
+    accounts: &[AccountInfo],
+    action: u8,
+) -> ProgramResult {
+    let mut header = AuditTrail::from_account_data(&trail.data.borrow())?;
+    let actor = accounts
+        .iter()
+        .find(|account| account.is_signer)
+        .map_or_else(Pubkey::default, |account| *account.key);
+    let entry = AuditEntry {
+        slot: Clock::get()?.slot,
+        action,
+        actor,
+    };
+    header.push(&mut trail.data.borrow_mut(), &entry)?;
+    Ok(())
+}
+
+// Pre-signed instructions bounded to a slot may not run after it.
+pub(super) fn validate_valid_until_slot(valid_until_slot: u64) -> ProgramResult {
+    if Clock::get()?.slot > valid_until_slot {
+        msg!("Instruction expired at slot {}", valid_until_slot);
+        return Err(VaultError::InstructionExpired.into());
+    }
+    Ok(())
+}
+
+// Optimistic concurrency: refuse to act when the authority changed after the
+// transaction was built.
+pub(super) fn validate_expected_authority(
+    record: &VaultRecord,
+    expected_authority: Option<&Pubkey>,
+) -> ProgramResult {
+    match expected_authority {
+        Some(expected) if *expected != record.authority => {
+            msg!("Record authority changed since the transaction was built");
+            Err(VaultError::StaleState.into())
+        }
+        _ => Ok(()),
+    }
+}
+
+// A close recipient set at initialize is always paid. Otherwise rent returns to
+// whoever funded it, unless the signers choose the authority.
+pub(super) fn close_recipient_key(record: &VaultRecord, refund_authority: bool) -> Pubkey {
+    if record.close_recipient != Pubkey::default() {
+        record.close_recipient
+    } else if refund_authority || record.rent_payer == Pubkey::default() {
+        record.authority
+    } else {
+        record.rent_payer
+    }
+}
+
+// Create a PDA of `space` bytes owned by `owner`, rent-exempt and funded by `payer`.
+pub(super) fn create_pda_account<'a>(
+    owner: &Pubkey,
+    payer: &AccountInfo<'a>,
+    account: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    space: usize,
+    seeds: &[&[u8]],
+) -> ProgramResult {
+    validate_program(system_program, &system_program::id())?;
+    invoke_signed(
+        &system_instruction::create_account(
+            payer.key,
+            account.key,
+            Rent::get()?.minimum_balance(space),
+            space as u64,
+            owner,
+        ),
+        &[payer.clone(), account.clone(), system_program.clone()],
+        &[seeds],
+    )
+}
+
+// Check the authority role: either `expected` itself signed, or `authority` is a session
+// key of `vault` created by `expected`, allowed `scope`, unexpired, and whose session
+// account is passed among `remaining`.
+pub(super) fn validate_authority(
+    program_id: &Pubkey,
+    vault: &AccountInfo,
+    authority: &AccountInfo,
+    remaining: &[AccountInfo],
+    expected: &Pubkey,
+    scope: u8,
+) -> ProgramResult {
+    let (address, _) = SessionRecord::find_address(program_id, vault.key, authority.key);
+    let session = match find_account(remaining, &address) {
+        Some(session) if authority.key != expected => session,
+        _ => return validate_signer(authority, expected, VaultError::IncorrectAuthority),
+    };
+    require_owner(session, program_id)?;
+    let session = SessionRecord::try_from_slice(&session.data.borrow())?;
+    if !session.is_initialized() || session.authority != *expected {
+        msg!("Session not issued by the current authority");
+        return Err(VaultError::IncorrectAuthority.into());
+    }
+    if session.scope & scope != scope {
+        msg!("Session scope does not permit this instruction");
+        return Err(VaultError::SessionScopeNotAllowed.into());
+    }
+    if Clock::get()?.slot > session.expires_at_slot {
+        msg!("Session expired at slot {}", session.expires_at_slot);
+        return Err(VaultError::SessionExpired.into());
+    }
+    require_signer(authority)?;
+    Ok(())
+}
+
+pub(super) fn validate_owned(program_id: &Pubkey, accounts: &[&AccountInfo]) -> ProgramResult {
+    accounts
+        .iter()
+        .try_for_each(|account| require_owner(account, program_id))
+}
+
+pub(super) fn validate_signers(accounts: &[&AccountInfo]) -> ProgramResult {
+    accounts
+        .iter()
+        .try_for_each(|account| require_signer(account))
+}
+
+// Declare the typed accounts of an instruction. `parse` takes the fixed accounts in
+// order, then any optional positional accounts past the program config, and leaves the
+// rest in `remaining`; it checks the listed groups are distinct, the writable and
+// readonly flags, and which accounts must be owned by the program or sign.
+macro_rules! instruction_accounts {
+    (
+        $(#[$meta:meta])*
+        $name:ident {
+            $($field:ident),+
+            $(; optional: $($optional:ident),+)?
+            $(; ..$remaining:ident)? $(;)?
+        }
+        $(unique: [$($unique:ident),+];)*
+        writable: [$($writable:ident),*];
+        readonly: [$($readonly:ident),*];
+        $(owned: [$($owned:ident),+];)?
+        $(signers: [$($signer:ident),+];)?
+    ) => {
+        $(#[$meta])*
+        struct $name<'a, 'b> {
+            $($field: &'b ::solana_program::account_info::AccountInfo<'a>,)+
+            $($($optional: Option<&'b ::solana_program::account_info::AccountInfo<'a>>,)+)?
+            $($remaining: &'b [::solana_program::account_info::AccountInfo<'a>],)?
+        }
+
+        impl<'a, 'b> $name<'a, 'b> {
+            fn parse(
+                program_id: &::solana_program::pubkey::Pubkey,
+                accounts: &'b [::solana_program::account_info::AccountInfo<'a>],
+            ) -> Result<Self, ::solana_program::program_error::ProgramError> {
+                let iter = &mut accounts.iter();
+                $(let $field = ::solana_program::account_info::next_account_info(iter)?;)+
+                $(
+                    let (config, _) = $crate::state::ProgramConfig::find_address(program_id);
+                    $(let $optional = $crate::processor::validation::next_optional_account(program_id, &config, iter);)+
+                )?
+                $($crate::processor::validation::validate_unique(&[$($unique),+])?;)*
+                $crate::processor::validation::validate_account_flags(&[$($writable),*], &[$($readonly),*])?;
+                $crate::processor::validation::validate_owned(program_id, &[$($($owned),+)?])?;
+                $crate::processor::validation::validate_signers(&[$($($signer),+)?])?;
+                Ok(Self {
+                    $($field,)+
+                    $($($optional,)+)?
+                    $($remaining: iter.as_slice(),)?
+                })
+            }
+        }
+    };
+}
+
+pub(super) use instruction_accounts;
+
+#[cfg(kani)]
+mod verification {
+    use super::*;
+
+    #[kani::proof]
+    fn validate_signer_requires_matching_signer() {
+        let key = Pubkey::new_from_array(kani::any());
+        let expected = Pubkey::new_from_array(kani::any());
+        let owner = Pubkey::new_from_array(kani::any());
+        let is_signer: bool = kani::any();
+        let mut lamports = 0;
+        let mut data: [u8; 0] = [];
+        let account = AccountInfo::new(
+            &key,
+            is_signer,
+            kani::any(),
+            &mut lamports,
+            &mut data,
+            &owner,
+            kani::any(),
+            0,
+        );
+
+        let result = validate_signer(&account, &expected, VaultError::IncorrectAuthority);
+        assert_eq!(result.is_ok(), key == expected && is_signer);
+        if key != expected {
+            assert_eq!(result, Err(VaultError::IncorrectAuthority.into()));
+        }
+    }
+
+    #[kani::proof]
+    fn validate_role_key_rejects_unusable_keys() {
+        let program_id = Pubkey::new_from_array(kani::any());
+        let key = Pubkey::new_from_array(kani::any());
+        assert_eq!(
+            validate_role_key(&program_id, &key).is_ok(),
+            key != Pubkey::default() && key != system_program::id() && key != program_id
+        );
+    }
+
+    #[kani::proof]
+    fn validate_active_only_accepts_active() {
+        let mut record = VaultRecord::new(
+            Pubkey::new_from_array(kani::any()),
+            Pubkey::new_from_array(kani::any()),
+        );
+        record.status = kani::any();
+        assert_eq!(
+            validate_active(&record).is_ok(),
+            record.status == VaultStatus::Active
+        );
+    }
+}
@@ -0,0 +1,69 @@
+use {
+    super::validation::{
+        create_pda_account, instruction_accounts, load_vault_index, write_account,
+    },
+    crate::state::{VaultIndex, VaultRecord, VaultStatus, VAULT_INDEX_SEED},
+    solana_program::{
+        account_info::AccountInfo, entrypoint::ProgramResult, msg, program_error::ProgramError,
+        program_pack::IsInitialized, pubkey::Pubkey,
+    },
+};
+
+instruction_accounts! {
+    /// Accounts of `SyncVaultIndex`.
+    SyncVaultIndexAccounts { index, authority, payer, system_program; ..remaining }
+    // The authority may also pay for its index.
+    unique: [index, authority];
+    unique: [index, payer];
+    writable: [index, payer];
+    readonly: [authority];
+    signers: [payer];
+}
+
+// Bring the vault index of an authority up to date with the given vaults (by anyone),
+// creating it if needed: active vaults of the authority are listed, any other account
+// is unlisted.
+pub(super) fn sync_vault_index(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let SyncVaultIndexAccounts {
+        index: index_info,
+        authority,
+        payer,
+        system_program,
+        remaining,
+    } = SyncVaultIndexAccounts::parse(program_id, accounts)?;
+
+    let (address, bump) = VaultIndex::find_address(program_id, authority.key);
+    if *index_info.key != address {
+        msg!("Vault index does not match the authority");
+        return Err(ProgramError::InvalidSeeds);
+    }
+    let mut index = if index_info.owner == program_id {
+        load_vault_index(program_id, authority.key, index_info)?
+    } else {
+        create_pda_account(
+            program_id,
+            payer,
+            index_info,
+            system_program,
+            VaultIndex::LEN,
+            &[VAULT_INDEX_SEED, authority.key.as_ref(), &[bump]],
+        )?;
+        VaultIndex::new(*authority.key, bump)
+    };
+
+    for vault in remaining {
+        let held = vault.owner == program_id
+            && VaultRecord::from_account_data(&vault.data.borrow()).is_ok_and(|record| {
+                record.is_initialized()
+                    && record.status == VaultStatus::Active
+                    && record.authority == *authority.key
+            });
+        if held {
+            index.insert(*vault.key)?;
+        } else {
+            index.remove(vault.key);
+        }
+    }
+
+    write_account(index_info, &index)
+}
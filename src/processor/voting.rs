@@ -0,0 +1,82 @@
+use {
+    super::validation::{
+        create_pda_account, instruction_accounts, load_record, sync_receipt_holder,
+        validate_active, validate_signer, write_account,
+    },
+    crate::{
+        error::VaultError,
+        event::{self, VotingDelegated},
+        state::{VotingProxy, VOTING_PROXY_SEED},
+    },
+    solana_program::{
+        account_info::AccountInfo, entrypoint::ProgramResult, msg, program_error::ProgramError,
+        pubkey::Pubkey,
+    },
+};
+
+instruction_accounts! {
+    /// Accounts of `DelegateVoting`.
+    DelegateVotingAccounts { pda, authority, voting, payer, system_program; ..remaining }
+    // The authority may also pay for the voting proxy account.
+    unique: [pda, authority, voting];
+    unique: [pda, voting, payer];
+    writable: [voting, payer];
+    readonly: [pda, authority];
+    owned: [pda];
+}
+
+// Delegate (or revoke, with the default pubkey) the vault's votes to `proxy`, creating
+// the voting proxy account if needed.
+pub(super) fn delegate_voting(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    proxy: Pubkey,
+) -> ProgramResult {
+    let DelegateVotingAccounts {
+        pda,
+        authority,
+        voting,
+        payer,
+        system_program,
+        remaining,
+    } = DelegateVotingAccounts::parse(program_id, accounts)?;
+
+    let mut record = load_record(program_id, pda)?;
+    validate_active(&record)?;
+    sync_receipt_holder(&mut record, remaining)?;
+    validate_signer(authority, &record.authority, VaultError::IncorrectAuthority)?;
+
+    if proxy == record.authority {
+        msg!("Proxy must not be the authority");
+        return Err(ProgramError::InvalidArgument);
+    }
+    let (address, bump) = VotingProxy::find_address(program_id, pda.key);
+    if *voting.key != address {
+        msg!("Voting proxy account does not match the vault");
+        return Err(ProgramError::InvalidSeeds);
+    }
+    if voting.owner != program_id {
+        create_pda_account(
+            program_id,
+            payer,
+            voting,
+            system_program,
+            VotingProxy::LEN,
+            &[VOTING_PROXY_SEED, pda.key.as_ref(), &[bump]],
+        )?;
+    }
+
+    write_account(
+        voting,
+        &VotingProxy::new(*pda.key, record.authority, proxy, bump),
+    )?;
+    event::emit(
+        program_id,
+        accounts,
+        &VotingDelegated {
+            vault: *pda.key,
+            authority: record.authority,
+            proxy,
+        },
+    )
+}
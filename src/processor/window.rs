@@ -0,0 +1,144 @@
+use {
+    super::validation::{
+        create_pda_account, instruction_accounts, load_record, validate_active, validate_dart,
+        write_account,
+    },
+    crate::state::{
+        BlackoutRange, TransferWindow, MAX_BLACKOUTS, TRANSFER_WINDOW_DART, TRANSFER_WINDOW_SEED,
+        TRANSFER_WINDOW_VAULT,
+    },
+    solana_program::{
+        account_info::AccountInfo, entrypoint::ProgramResult, msg, program_error::ProgramError,
+        pubkey::Pubkey,
+    },
+};
+
+instruction_accounts! {
+    /// Accounts of `SetTransferWindow`.
+    SetTransferWindowAccounts { pda, dart, window, payer, system_program; ..remaining }
+    // The DART may also pay for the transfer window.
+    unique: [pda, dart, window];
+    unique: [pda, window, payer];
+    writable: [pda, window, payer];
+    readonly: [dart];
+    owned: [pda];
+    signers: [payer];
+}
+
+instruction_accounts! {
+    /// Accounts of `SetDartTransferWindow`.
+    SetDartTransferWindowAccounts { window, dart, payer, system_program; ..remaining }
+    // The DART may also pay for the transfer window.
+    unique: [window, dart];
+    unique: [window, payer];
+    writable: [window, payer];
+    readonly: [dart];
+    signers: [payer];
+}
+
+// Set the transfer window of a vault (DART only), creating the window account if needed,
+// and flag the record so transfers must pass the window while it restricts them.
+pub(super) fn set_transfer_window(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    lockup_until_slot: u64,
+    blackouts: [BlackoutRange; MAX_BLACKOUTS],
+) -> ProgramResult {
+    let SetTransferWindowAccounts {
+        pda,
+        dart,
+        window,
+        payer,
+        system_program,
+        remaining,
+    } = SetTransferWindowAccounts::parse(program_id, accounts)?;
+
+    let mut record = load_record(program_id, pda)?;
+    validate_active(&record)?;
+    validate_dart(program_id, dart, remaining, &record.dart)?;
+
+    let bump = create_transfer_window(
+        program_id,
+        TRANSFER_WINDOW_VAULT,
+        pda.key,
+        window,
+        payer,
+        system_program,
+    )?;
+    let transfer_window = TransferWindow::new(*pda.key, lockup_until_slot, blackouts, bump);
+    write_account(window, &transfer_window)?;
+    record.transfer_windowed = transfer_window.is_restricted();
+
+    record.pack_into(&mut pda.data.borrow_mut())
+}
+
+// Set the transfer window of all the DART's vaults, creating the window account if
+// needed.
+pub(super) fn set_dart_transfer_window(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    lockup_until_slot: u64,
+    blackouts: [BlackoutRange; MAX_BLACKOUTS],
+) -> ProgramResult {
+    let SetDartTransferWindowAccounts {
+        window,
+        dart,
+        payer,
+        system_program,
+        remaining,
+    } = SetDartTransferWindowAccounts::parse(program_id, accounts)?;
+
+    // The window is account 1's, or that of a DART identity among `remaining` whose
+    // operational key account 1 is.
+    let scope = remaining
+        .iter()
+        .map(|account| account.key)
+        .find(|key| {
+            *key != dart.key
+                && TransferWindow::find_address(program_id, TRANSFER_WINDOW_DART, key).0
+                    == *window.key
+        })
+        .unwrap_or(dart.key);
+    validate_dart(program_id, dart, remaining, scope)?;
+
+    let bump = create_transfer_window(
+        program_id,
+        TRANSFER_WINDOW_DART,
+        scope,
+        window,
+        payer,
+        system_program,
+    )?;
+    write_account(
+        window,
+        &TransferWindow::new(*scope, lockup_until_slot, blackouts, bump),
+    )
+}
+
+// Check the transfer window account of `scope`, creating it if needed, and return its
+// bump.
+fn create_transfer_window<'a>(
+    program_id: &Pubkey,
+    kind: &[u8],
+    scope: &Pubkey,
+    window: &AccountInfo<'a>,
+    payer: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+) -> Result<u8, ProgramError> {
+    let (address, bump) = TransferWindow::find_address(program_id, kind, scope);
+    if *window.key != address {
+        msg!("Transfer window does not match {}", scope);
+        return Err(ProgramError::InvalidSeeds);
+    }
+    if window.owner != program_id {
+        create_pda_account(
+            program_id,
+            payer,
+            window,
+            system_program,
+            TransferWindow::LEN,
+            &[TRANSFER_WINDOW_SEED, kind, scope.as_ref(), &[bump]],
+        )?;
+    }
+    Ok(bump)
+}
@@ -0,0 +1,819 @@
+//! Protobuf messages mirroring `proto/vault.proto`, for off-chain indexers: the vault
+//! record and one message per event of `event`.
+use {
+    crate::{event, state},
+    solana_program::{program_error::ProgramError, pubkey::Pubkey},
+};
+
+/// Protobuf encoding of a `VaultStatus`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, prost::Enumeration)]
+#[repr(i32)]
+pub enum VaultStatus {
+    Active = 0,
+    Frozen = 1,
+    PendingTransfer = 2,
+    Seized = 3,
+    Closed = 4,
+}
+
+impl From<state::VaultStatus> for VaultStatus {
+    fn from(status: state::VaultStatus) -> Self {
+        match status {
+            state::VaultStatus::Active => Self::Active,
+            state::VaultStatus::Frozen => Self::Frozen,
+            state::VaultStatus::PendingTransfer => Self::PendingTransfer,
+            state::VaultStatus::Seized => Self::Seized,
+            state::VaultStatus::Closed => Self::Closed,
+        }
+    }
+}
+
+impl From<VaultStatus> for state::VaultStatus {
+    fn from(status: VaultStatus) -> Self {
+        match status {
+            VaultStatus::Active => Self::Active,
+            VaultStatus::Frozen => Self::Frozen,
+            VaultStatus::PendingTransfer => Self::PendingTransfer,
+            VaultStatus::Seized => Self::Seized,
+            VaultStatus::Closed => Self::Closed,
+        }
+    }
+}
+
+/// Protobuf encoding of a `VaultRecord`.
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct VaultRecord {
+    /// Struct version, allows for upgrades to the program
+    #[prost(uint32, tag = "1")]
+    pub version: u32,
+
+    /// The account owner
+    #[prost(bytes = "vec", tag = "2")]
+    pub authority: Vec<u8>,
+
+    /// The securities intermediary
+    #[prost(bytes = "vec", tag = "3")]
+    pub dart: Vec<u8>,
+
+    /// Lifecycle status
+    #[prost(enumeration = "VaultStatus", tag = "4")]
+    pub status: i32,
+
+    /// Proposed successor DART, all zeros when none is pending
+    #[prost(bytes = "vec", tag = "5")]
+    pub pending_dart: Vec<u8>,
+
+    /// Category tag, zero when untagged
+    #[prost(uint32, tag = "6")]
+    pub tag: u32,
+
+    /// Account refunded on close, all zeros when the authority is
+    #[prost(bytes = "vec", tag = "7")]
+    pub rent_payer: Vec<u8>,
+
+    /// Account paid on close whoever signs, all zeros when unset
+    #[prost(bytes = "vec", tag = "8")]
+    pub close_recipient: Vec<u8>,
+
+    /// Digest of the last attested Wormhole VAA, all zeros when none
+    #[prost(bytes = "vec", tag = "9")]
+    pub attestation: Vec<u8>,
+
+    /// Authority proposed by an escrowed transfer, all zeros when none is pending
+    #[prost(bytes = "vec", tag = "10")]
+    pub pending_authority: Vec<u8>,
+
+    /// Last slot the pending transfer may be accepted in
+    #[prost(uint64, tag = "11")]
+    pub transfer_expires_at_slot: u64,
+
+    /// Price feed gating authority changes, all zeros when transfers are not gated
+    #[prost(bytes = "vec", tag = "12")]
+    pub oracle: Vec<u8>,
+
+    /// Slots the oracle may go without publishing
+    #[prost(uint64, tag = "13")]
+    pub oracle_max_staleness_slots: u64,
+
+    /// Lowest oracle price at which transfers are allowed
+    #[prost(int64, tag = "14")]
+    pub oracle_min_price: i64,
+
+    /// Mint of the outstanding custody receipt, all zeros when none
+    #[prost(bytes = "vec", tag = "15")]
+    pub receipt_mint: Vec<u8>,
+
+    /// Whether the receipt holder is the authority
+    #[prost(bool, tag = "16")]
+    pub receipt_is_authority: bool,
+
+    /// Whether the vault is pledged as collateral
+    #[prost(bool, tag = "17")]
+    pub pledged: bool,
+
+    /// Whether the vault has its own transfer window
+    #[prost(bool, tag = "18")]
+    pub transfer_windowed: bool,
+
+    /// Whether the vault has a rate limit
+    #[prost(bool, tag = "19")]
+    pub rate_limited: bool,
+
+    /// Slot the vault was created in, zero when created before version 6
+    #[prost(uint64, tag = "20")]
+    pub created_slot: u64,
+    /// Whether the vault has an audit trail
+    #[prost(bool, tag = "21")]
+    pub audited: bool,
+    /// Transfers redeemed with a Merkle proof
+    #[prost(uint64, tag = "22")]
+    pub approval_nonce: u64,
+    /// Whether instructions acting on the vault need the DART's signature
+    #[prost(bool, tag = "23")]
+    pub cosign: bool,
+    /// Close fee charged in place of the fee config's default, zero for the default
+    #[prost(uint64, tag = "24")]
+    pub close_fee: u64,
+    /// Bits of the instructions the DART policy bars the vault from
+    #[prost(uint32, tag = "25")]
+    pub restrictions: u32,
+}
+
+impl From<&state::VaultRecord> for VaultRecord {
+    fn from(record: &state::VaultRecord) -> Self {
+        Self {
+            version: record.version.into(),
+            authority: record.authority.to_bytes().to_vec(),
+            dart: record.dart.to_bytes().to_vec(),
+            status: VaultStatus::from(record.status).into(),
+            pending_dart: record.pending_dart.to_bytes().to_vec(),
+            tag: record.tag.into(),
+            rent_payer: record.rent_payer.to_bytes().to_vec(),
+            close_recipient: record.close_recipient.to_bytes().to_vec(),
+            attestation: record.attestation.to_vec(),
+            pending_authority: record.pending_authority.to_bytes().to_vec(),
+            transfer_expires_at_slot: record.transfer_expires_at_slot,
+            oracle: record.oracle.to_bytes().to_vec(),
+            oracle_max_staleness_slots: record.oracle_max_staleness_slots,
+            oracle_min_price: record.oracle_min_price,
+            receipt_mint: record.receipt_mint.to_bytes().to_vec(),
+            receipt_is_authority: record.receipt_is_authority,
+            pledged: record.pledged,
+            transfer_windowed: record.transfer_windowed,
+            rate_limited: record.rate_limited,
+            created_slot: record.created_slot,
+            audited: record.audited,
+            approval_nonce: record.approval_nonce,
+            cosign: record.cosign,
+            close_fee: record.close_fee,
+            restrictions: record.restrictions.into(),
+        }
+    }
+}
+
+impl From<state::VaultRecord> for VaultRecord {
+    fn from(record: state::VaultRecord) -> Self {
+        Self::from(&record)
+    }
+}
+
+impl TryFrom<VaultRecord> for state::VaultRecord {
+    type Error = ProgramError;
+
+    fn try_from(message: VaultRecord) -> Result<Self, Self::Error> {
+        let mut record = Self::new(
+            pubkey_from_bytes(&message.authority)?,
+            pubkey_from_bytes(&message.dart)?,
+        );
+        record.version =
+            u8::try_from(message.version).map_err(|_| ProgramError::InvalidAccountData)?;
+        record.status = VaultStatus::try_from(message.status)
+            .map_err(|_| ProgramError::InvalidAccountData)?
+            .into();
+        record.pending_dart = pubkey_from_bytes(&message.pending_dart)?;
+        record.tag = u16::try_from(message.tag).map_err(|_| ProgramError::InvalidAccountData)?;
+        record.rent_payer = pubkey_from_bytes(&message.rent_payer)?;
+        record.close_recipient = pubkey_from_bytes(&message.close_recipient)?;
+        record.attestation = message
+            .attestation
+            .as_slice()
+            .try_into()
+            .map_err(|_| ProgramError::InvalidAccountData)?;
+        record.pending_authority = pubkey_from_bytes(&message.pending_authority)?;
+        record.transfer_expires_at_slot = message.transfer_expires_at_slot;
+        record.oracle = pubkey_from_bytes(&message.oracle)?;
+        record.oracle_max_staleness_slots = message.oracle_max_staleness_slots;
+        record.oracle_min_price = message.oracle_min_price;
+        record.receipt_mint = pubkey_from_bytes(&message.receipt_mint)?;
+        record.receipt_is_authority = message.receipt_is_authority;
+        record.pledged = message.pledged;
+        record.transfer_windowed = message.transfer_windowed;
+        record.rate_limited = message.rate_limited;
+        record.created_slot = message.created_slot;
+        record.audited = message.audited;
+        record.approval_nonce = message.approval_nonce;
+        record.cosign = message.cosign;
+        record.close_fee = message.close_fee;
+        record.restrictions =
+            u8::try_from(message.restrictions).map_err(|_| ProgramError::InvalidAccountData)?;
+        Ok(record)
+    }
+}
+
+/// Protobuf encoding of a `CorporateActionType`; the asset id of a symbol change is
+/// carried beside it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, prost::Enumeration)]
+#[repr(i32)]
+pub enum CorporateActionType {
+    Split = 0,
+    ReverseSplit = 1,
+    SymbolChange = 2,
+}
+
+/// Protobuf encoding of an `AuthorityTransferred` event.
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct AuthorityTransferred {
+    /// The vault record
+    #[prost(bytes = "vec", tag = "1")]
+    pub vault: Vec<u8>,
+    /// The authority before the change
+    #[prost(bytes = "vec", tag = "2")]
+    pub previous_authority: Vec<u8>,
+    /// The authority after the change
+    #[prost(bytes = "vec", tag = "3")]
+    pub authority: Vec<u8>,
+}
+
+impl From<&event::AuthorityTransferred> for AuthorityTransferred {
+    fn from(event: &event::AuthorityTransferred) -> Self {
+        Self {
+            vault: event.vault.to_bytes().to_vec(),
+            previous_authority: event.previous_authority.to_bytes().to_vec(),
+            authority: event.authority.to_bytes().to_vec(),
+        }
+    }
+}
+
+impl TryFrom<AuthorityTransferred> for event::AuthorityTransferred {
+    type Error = ProgramError;
+
+    fn try_from(message: AuthorityTransferred) -> Result<Self, Self::Error> {
+        Ok(Self {
+            vault: pubkey_from_bytes(&message.vault)?,
+            previous_authority: pubkey_from_bytes(&message.previous_authority)?,
+            authority: pubkey_from_bytes(&message.authority)?,
+        })
+    }
+}
+
+/// Protobuf encoding of a `VaultClosed` event.
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct VaultClosed {
+    /// The vault record
+    #[prost(bytes = "vec", tag = "1")]
+    pub vault: Vec<u8>,
+    /// The authority at close
+    #[prost(bytes = "vec", tag = "2")]
+    pub authority: Vec<u8>,
+    /// The account paid the vault's lamports
+    #[prost(bytes = "vec", tag = "3")]
+    pub recipient: Vec<u8>,
+    /// Lamports paid out, net of any close fee
+    #[prost(uint64, tag = "4")]
+    pub lamports: u64,
+}
+
+impl From<&event::VaultClosed> for VaultClosed {
+    fn from(event: &event::VaultClosed) -> Self {
+        Self {
+            vault: event.vault.to_bytes().to_vec(),
+            authority: event.authority.to_bytes().to_vec(),
+            recipient: event.recipient.to_bytes().to_vec(),
+            lamports: event.lamports,
+        }
+    }
+}
+
+impl TryFrom<VaultClosed> for event::VaultClosed {
+    type Error = ProgramError;
+
+    fn try_from(message: VaultClosed) -> Result<Self, Self::Error> {
+        Ok(Self {
+            vault: pubkey_from_bytes(&message.vault)?,
+            authority: pubkey_from_bytes(&message.authority)?,
+            recipient: pubkey_from_bytes(&message.recipient)?,
+            lamports: message.lamports,
+        })
+    }
+}
+
+/// Protobuf encoding of a `VaultRekeyed` event.
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct VaultRekeyed {
+    /// The closed vault record account
+    #[prost(bytes = "vec", tag = "1")]
+    pub vault: Vec<u8>,
+    /// The account now holding the record
+    #[prost(bytes = "vec", tag = "2")]
+    pub new_vault: Vec<u8>,
+    /// The authority at the move
+    #[prost(bytes = "vec", tag = "3")]
+    pub authority: Vec<u8>,
+}
+
+impl From<&event::VaultRekeyed> for VaultRekeyed {
+    fn from(event: &event::VaultRekeyed) -> Self {
+        Self {
+            vault: event.vault.to_bytes().to_vec(),
+            new_vault: event.new_vault.to_bytes().to_vec(),
+            authority: event.authority.to_bytes().to_vec(),
+        }
+    }
+}
+
+impl TryFrom<VaultRekeyed> for event::VaultRekeyed {
+    type Error = ProgramError;
+
+    fn try_from(message: VaultRekeyed) -> Result<Self, Self::Error> {
+        Ok(Self {
+            vault: pubkey_from_bytes(&message.vault)?,
+            new_vault: pubkey_from_bytes(&message.new_vault)?,
+            authority: pubkey_from_bytes(&message.authority)?,
+        })
+    }
+}
+
+/// Protobuf encoding of a `NoteAdded` event.
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct NoteAdded {
+    /// The vault record
+    #[prost(bytes = "vec", tag = "1")]
+    pub vault: Vec<u8>,
+    /// Sequence number of the note in the vault's notes log
+    #[prost(uint64, tag = "2")]
+    pub sequence: u64,
+    /// Kind of corporate action
+    #[prost(uint32, tag = "3")]
+    pub category: u32,
+    /// Hash of the annotation document
+    #[prost(bytes = "vec", tag = "4")]
+    pub hash: Vec<u8>,
+}
+
+impl From<&event::NoteAdded> for NoteAdded {
+    fn from(event: &event::NoteAdded) -> Self {
+        Self {
+            vault: event.vault.to_bytes().to_vec(),
+            sequence: event.sequence,
+            category: event.category.into(),
+            hash: event.hash.to_vec(),
+        }
+    }
+}
+
+impl TryFrom<NoteAdded> for event::NoteAdded {
+    type Error = ProgramError;
+
+    fn try_from(message: NoteAdded) -> Result<Self, Self::Error> {
+        Ok(Self {
+            vault: pubkey_from_bytes(&message.vault)?,
+            sequence: message.sequence,
+            category: u16::try_from(message.category)
+                .map_err(|_| ProgramError::InvalidAccountData)?,
+            hash: hash_from_bytes(&message.hash)?,
+        })
+    }
+}
+
+/// Protobuf encoding of a `CorporateActionApplied` event.
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct CorporateActionApplied {
+    /// The vault record
+    #[prost(bytes = "vec", tag = "1")]
+    pub vault: Vec<u8>,
+    /// The corporate action
+    #[prost(enumeration = "CorporateActionType", tag = "2")]
+    pub action_type: i32,
+    /// New asset id of a symbol change, empty for other actions
+    #[prost(bytes = "vec", tag = "3")]
+    pub action_asset_id: Vec<u8>,
+    /// Ratio of the split or reverse split
+    #[prost(uint64, tag = "4")]
+    pub ratio: u64,
+    /// Asset id before the action
+    #[prost(bytes = "vec", tag = "5")]
+    pub previous_asset_id: Vec<u8>,
+    /// Asset id after the action
+    #[prost(bytes = "vec", tag = "6")]
+    pub asset_id: Vec<u8>,
+    /// Balance before the action
+    #[prost(uint64, tag = "7")]
+    pub previous_balance: u64,
+    /// Balance after the action
+    #[prost(uint64, tag = "8")]
+    pub balance: u64,
+}
+
+impl From<&event::CorporateActionApplied> for CorporateActionApplied {
+    fn from(event: &event::CorporateActionApplied) -> Self {
+        let (action_type, action_asset_id) = match event.action_type {
+            state::CorporateActionType::Split => (CorporateActionType::Split, vec![]),
+            state::CorporateActionType::ReverseSplit => (CorporateActionType::ReverseSplit, vec![]),
+            state::CorporateActionType::SymbolChange { asset_id } => {
+                (CorporateActionType::SymbolChange, asset_id.to_vec())
+            }
+        };
+        Self {
+            vault: event.vault.to_bytes().to_vec(),
+            action_type: action_type.into(),
+            action_asset_id,
+            ratio: event.ratio,
+            previous_asset_id: event.previous_asset_id.to_vec(),
+            asset_id: event.asset_id.to_vec(),
+            previous_balance: event.previous_balance,
+            balance: event.balance,
+        }
+    }
+}
+
+impl TryFrom<CorporateActionApplied> for event::CorporateActionApplied {
+    type Error = ProgramError;
+
+    fn try_from(message: CorporateActionApplied) -> Result<Self, Self::Error> {
+        let action_type = match CorporateActionType::try_from(message.action_type)
+            .map_err(|_| ProgramError::InvalidAccountData)?
+        {
+            CorporateActionType::Split if message.action_asset_id.is_empty() => {
+                state::CorporateActionType::Split
+            }
+            CorporateActionType::ReverseSplit if message.action_asset_id.is_empty() => {
+                state::CorporateActionType::ReverseSplit
+            }
+            CorporateActionType::SymbolChange => state::CorporateActionType::SymbolChange {
+                asset_id: hash_from_bytes(&message.action_asset_id)?,
+            },
+            _ => return Err(ProgramError::InvalidAccountData),
+        };
+        Ok(Self {
+            vault: pubkey_from_bytes(&message.vault)?,
+            action_type,
+            ratio: message.ratio,
+            previous_asset_id: hash_from_bytes(&message.previous_asset_id)?,
+            asset_id: hash_from_bytes(&message.asset_id)?,
+            previous_balance: message.previous_balance,
+            balance: message.balance,
+        })
+    }
+}
+
+/// Protobuf encoding of a `VotingDelegated` event.
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct VotingDelegated {
+    /// The vault record
+    #[prost(bytes = "vec", tag = "1")]
+    pub vault: Vec<u8>,
+    /// The delegating authority
+    #[prost(bytes = "vec", tag = "2")]
+    pub authority: Vec<u8>,
+    /// The proxy, all zeros when the delegation was revoked
+    #[prost(bytes = "vec", tag = "3")]
+    pub proxy: Vec<u8>,
+}
+
+impl From<&event::VotingDelegated> for VotingDelegated {
+    fn from(event: &event::VotingDelegated) -> Self {
+        Self {
+            vault: event.vault.to_bytes().to_vec(),
+            authority: event.authority.to_bytes().to_vec(),
+            proxy: event.proxy.to_bytes().to_vec(),
+        }
+    }
+}
+
+impl TryFrom<VotingDelegated> for event::VotingDelegated {
+    type Error = ProgramError;
+
+    fn try_from(message: VotingDelegated) -> Result<Self, Self::Error> {
+        Ok(Self {
+            vault: pubkey_from_bytes(&message.vault)?,
+            authority: pubkey_from_bytes(&message.authority)?,
+            proxy: pubkey_from_bytes(&message.proxy)?,
+        })
+    }
+}
+
+/// Protobuf encoding of a `CloseReceipt`.
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct CloseReceipt {
+    /// The vault record
+    #[prost(bytes = "vec", tag = "1")]
+    pub vault: Vec<u8>,
+    /// The authority at close
+    #[prost(bytes = "vec", tag = "2")]
+    pub authority: Vec<u8>,
+    /// Lamports returned from the vault account, net of any close fee
+    #[prost(uint64, tag = "3")]
+    pub lamports: u64,
+    /// Slot of the close
+    #[prost(uint64, tag = "4")]
+    pub slot: u64,
+}
+
+impl From<&event::CloseReceipt> for CloseReceipt {
+    fn from(event: &event::CloseReceipt) -> Self {
+        Self {
+            vault: event.vault.to_bytes().to_vec(),
+            authority: event.authority.to_bytes().to_vec(),
+            lamports: event.lamports,
+            slot: event.slot,
+        }
+    }
+}
+
+impl TryFrom<CloseReceipt> for event::CloseReceipt {
+    type Error = ProgramError;
+
+    fn try_from(message: CloseReceipt) -> Result<Self, Self::Error> {
+        Ok(Self {
+            vault: pubkey_from_bytes(&message.vault)?,
+            authority: pubkey_from_bytes(&message.authority)?,
+            lamports: message.lamports,
+            slot: message.slot,
+        })
+    }
+}
+
+/// Protobuf encoding of a `TransferCheck`.
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct TransferCheck {
+    /// The vault record
+    #[prost(bytes = "vec", tag = "1")]
+    pub vault: Vec<u8>,
+    /// The authority checked
+    #[prost(bytes = "vec", tag = "2")]
+    pub authority: Vec<u8>,
+    /// The new authority checked
+    #[prost(bytes = "vec", tag = "3")]
+    pub new_authority: Vec<u8>,
+    /// Zero when the transfer would pass, else the `u64` code of the `ProgramError` it
+    /// would fail with
+    #[prost(uint64, tag = "4")]
+    pub error: u64,
+}
+
+impl From<&event::TransferCheck> for TransferCheck {
+    fn from(event: &event::TransferCheck) -> Self {
+        Self {
+            vault: event.vault.to_bytes().to_vec(),
+            authority: event.authority.to_bytes().to_vec(),
+            new_authority: event.new_authority.to_bytes().to_vec(),
+            error: event.error,
+        }
+    }
+}
+
+impl TryFrom<TransferCheck> for event::TransferCheck {
+    type Error = ProgramError;
+
+    fn try_from(message: TransferCheck) -> Result<Self, Self::Error> {
+        Ok(Self {
+            vault: pubkey_from_bytes(&message.vault)?,
+            authority: pubkey_from_bytes(&message.authority)?,
+            new_authority: pubkey_from_bytes(&message.new_authority)?,
+            error: message.error,
+        })
+    }
+}
+
+/// Protobuf encoding of a `FeesCollected` event.
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct FeesCollected {
+    /// The fee config the fees accumulated in
+    #[prost(bytes = "vec", tag = "1")]
+    pub fee_config: Vec<u8>,
+    /// The fee destination paid
+    #[prost(bytes = "vec", tag = "2")]
+    pub destination: Vec<u8>,
+    /// Lamports paid out
+    #[prost(uint64, tag = "3")]
+    pub lamports: u64,
+}
+
+impl From<&event::FeesCollected> for FeesCollected {
+    fn from(event: &event::FeesCollected) -> Self {
+        Self {
+            fee_config: event.fee_config.to_bytes().to_vec(),
+            destination: event.destination.to_bytes().to_vec(),
+            lamports: event.lamports,
+        }
+    }
+}
+
+impl TryFrom<FeesCollected> for event::FeesCollected {
+    type Error = ProgramError;
+
+    fn try_from(message: FeesCollected) -> Result<Self, Self::Error> {
+        Ok(Self {
+            fee_config: pubkey_from_bytes(&message.fee_config)?,
+            destination: pubkey_from_bytes(&message.destination)?,
+            lamports: message.lamports,
+        })
+    }
+}
+
+// `From` an event by value, as `VaultRecord` converts either way.
+macro_rules! from_event {
+    ($($event:ident),* $(,)?) => {
+        $(
+            impl From<event::$event> for $event {
+                fn from(event: event::$event) -> Self {
+                    Self::from(&event)
+                }
+            }
+        )*
+    };
+}
+
+from_event!(
+    AuthorityTransferred,
+    VaultClosed,
+    VaultRekeyed,
+    NoteAdded,
+    CorporateActionApplied,
+    VotingDelegated,
+    CloseReceipt,
+    TransferCheck,
+    FeesCollected,
+);
+
+fn pubkey_from_bytes(bytes: &[u8]) -> Result<Pubkey, ProgramError> {
+    Pubkey::try_from(bytes).map_err(|_| ProgramError::InvalidAccountData)
+}
+
+fn hash_from_bytes(bytes: &[u8]) -> Result<[u8; 32], ProgramError> {
+    bytes
+        .try_into()
+        .map_err(|_| ProgramError::InvalidAccountData)
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::state::tests::{AUTH_PUBKEY, DART_PUBKEY, TEST_RECORD_DATA},
+        prost::Message,
+    };
+
+    #[test]
+    fn record_round_trip() {
+        let message = VaultRecord::from(&TEST_RECORD_DATA);
+        assert_eq!(message.authority, AUTH_PUBKEY.to_bytes());
+        assert_eq!(message.dart, DART_PUBKEY.to_bytes());
+
+        let decoded = VaultRecord::decode(message.encode_to_vec().as_slice()).unwrap();
+        assert_eq!(
+            state::VaultRecord::try_from(decoded).unwrap(),
+            TEST_RECORD_DATA
+        );
+    }
+
+    #[test]
+    fn record_invalid_status() {
+        let mut message = VaultRecord::from(&TEST_RECORD_DATA);
+        message.status = 42;
+        assert_eq!(
+            state::VaultRecord::try_from(message).unwrap_err(),
+            ProgramError::InvalidAccountData
+        );
+    }
+
+    #[test]
+    fn record_invalid_pubkey() {
+        let mut message = VaultRecord::from(&TEST_RECORD_DATA);
+        message.dart.truncate(31);
+        assert_eq!(
+            state::VaultRecord::try_from(message).unwrap_err(),
+            ProgramError::InvalidAccountData
+        );
+    }
+
+    // Encode `event` as its protobuf message `M` and decode it back.
+    fn round_trip<E, M>(event: &E) -> E
+    where
+        M: Message + Default + for<'a> From<&'a E>,
+        E: TryFrom<M, Error = ProgramError>,
+    {
+        let message = M::decode(M::from(event).encode_to_vec().as_slice()).unwrap();
+        E::try_from(message).unwrap()
+    }
+
+    #[test]
+    fn events_round_trip() {
+        let vault = Pubkey::new_from_array([1; 32]);
+        let authority = Pubkey::new_from_array([2; 32]);
+        let other = Pubkey::new_from_array([3; 32]);
+
+        let event = event::AuthorityTransferred {
+            vault,
+            previous_authority: authority,
+            authority: other,
+        };
+        assert_eq!(round_trip::<_, AuthorityTransferred>(&event), event);
+
+        let event = event::VaultClosed {
+            vault,
+            authority,
+            recipient: other,
+            lamports: 42,
+        };
+        assert_eq!(round_trip::<_, VaultClosed>(&event), event);
+
+        let event = event::VaultRekeyed {
+            vault,
+            new_vault: other,
+            authority,
+        };
+        assert_eq!(round_trip::<_, VaultRekeyed>(&event), event);
+
+        let event = event::NoteAdded {
+            vault,
+            sequence: 7,
+            category: 3,
+            hash: [4; 32],
+        };
+        assert_eq!(round_trip::<_, NoteAdded>(&event), event);
+
+        for action_type in [
+            state::CorporateActionType::Split,
+            state::CorporateActionType::ReverseSplit,
+            state::CorporateActionType::SymbolChange { asset_id: [5; 32] },
+        ] {
+            let event = event::CorporateActionApplied {
+                vault,
+                action_type,
+                ratio: 2,
+                previous_asset_id: [6; 32],
+                asset_id: [5; 32],
+                previous_balance: 100,
+                balance: 200,
+            };
+            assert_eq!(round_trip::<_, CorporateActionApplied>(&event), event);
+        }
+
+        let event = event::VotingDelegated {
+            vault,
+            authority,
+            proxy: other,
+        };
+        assert_eq!(round_trip::<_, VotingDelegated>(&event), event);
+
+        let event = event::CloseReceipt {
+            vault,
+            authority,
+            lamports: 42,
+            slot: 9,
+        };
+        assert_eq!(round_trip::<_, CloseReceipt>(&event), event);
+
+        let event = event::TransferCheck {
+            vault,
+            authority,
+            new_authority: other,
+            error: 12,
+        };
+        assert_eq!(round_trip::<_, TransferCheck>(&event), event);
+
+        let event = event::FeesCollected {
+            fee_config: vault,
+            destination: other,
+            lamports: 42,
+        };
+        assert_eq!(round_trip::<_, FeesCollected>(&event), event);
+    }
+
+    #[test]
+    fn corporate_action_invalid_asset_id() {
+        let event = event::CorporateActionApplied {
+            vault: Pubkey::new_from_array([1; 32]),
+            action_type: state::CorporateActionType::Split,
+            ratio: 2,
+            previous_asset_id: [6; 32],
+            asset_id: [6; 32],
+            previous_balance: 100,
+            balance: 200,
+        };
+        let mut message = CorporateActionApplied::from(&event);
+        message.action_asset_id = vec![5; 32];
+        assert_eq!(
+            event::CorporateActionApplied::try_from(message).unwrap_err(),
+            ProgramError::InvalidAccountData
+        );
+
+        let mut message = CorporateActionApplied::from(&event);
+        message.action_type = CorporateActionType::SymbolChange.into();
+        assert_eq!(
+            event::CorporateActionApplied::try_from(message).unwrap_err(),
+            ProgramError::InvalidAccountData
+        );
+    }
+}
@@ -0,0 +1,133 @@
+//! DART approval by a quorum of detached operational key signatures, verified by the
+//! Ed25519 program in the same transaction.
+//!
+//! A DART identity with a non-zero `quorum` may stand in the DART role unsigned: the
+//! identity itself is passed writable in place of the DART signer, along with the
+//! instructions sysvar, and at least `quorum` of its operational keys sign the
+//! `approval::message` of the instruction in an Ed25519 program instruction. Each
+//! approval consumes the identity's `approval_nonce`, so signatures cannot be
+//! replayed.
+use solana_program::{
+    ed25519_program,
+    instruction::Instruction,
+    pubkey::{Pubkey, PUBKEY_BYTES},
+};
+
+/// Ed25519 signature length.
+pub const SIGNATURE_LEN: usize = 64;
+
+/// Ed25519 instruction header: signature count and padding.
+const HEADER_LEN: usize = 2;
+
+/// Packed `Ed25519SignatureOffsets`: seven `u16` fields.
+const OFFSETS_LEN: usize = 14;
+
+/// Instruction index referring to the Ed25519 instruction itself.
+const CURRENT_INSTRUCTION: u16 = u16::MAX;
+
+/// Create an Ed25519 program instruction verifying detached `signatures` of
+/// `message` by their public keys.
+pub fn verify_signatures(
+    signatures: &[(Pubkey, [u8; SIGNATURE_LEN])],
+    message: &[u8],
+) -> Instruction {
+    let entries_start = HEADER_LEN + OFFSETS_LEN * signatures.len();
+    let message_offset = entries_start + (PUBKEY_BYTES + SIGNATURE_LEN) * signatures.len();
+
+    let mut data = Vec::with_capacity(message_offset + message.len());
+    data.extend_from_slice(&[signatures.len() as u8, 0]);
+    for index in 0..signatures.len() {
+        let public_key_offset = entries_start + (PUBKEY_BYTES + SIGNATURE_LEN) * index;
+        for field in [
+            public_key_offset + PUBKEY_BYTES,
+            CURRENT_INSTRUCTION.into(),
+            public_key_offset,
+            CURRENT_INSTRUCTION.into(),
+            message_offset,
+            message.len(),
+            CURRENT_INSTRUCTION.into(),
+        ] {
+            data.extend_from_slice(&(field as u16).to_le_bytes());
+        }
+    }
+    for (key, signature) in signatures {
+        data.extend_from_slice(key.as_ref());
+        data.extend_from_slice(signature);
+    }
+    data.extend_from_slice(message);
+
+    Instruction {
+        program_id: ed25519_program::id(),
+        accounts: vec![],
+        data,
+    }
+}
+
+/// Keys of the Ed25519 instruction `data` whose signatures cover `message`. Only
+/// entries whose key, signature and message all lie within the instruction are
+/// counted; the Ed25519 program has already failed the transaction if any signature
+/// is invalid.
+pub fn signers_of(data: &[u8], message: &[u8]) -> Vec<Pubkey> {
+    let count = data.first().copied().unwrap_or_default();
+    (0..usize::from(count))
+        .filter_map(|index| {
+            let start = HEADER_LEN + OFFSETS_LEN * index;
+            let offsets = data.get(start..start + OFFSETS_LEN)?;
+            let field = |n: usize| u16::from_le_bytes([offsets[2 * n], offsets[2 * n + 1]]);
+            if field(1) != CURRENT_INSTRUCTION
+                || field(3) != CURRENT_INSTRUCTION
+                || field(6) != CURRENT_INSTRUCTION
+            {
+                return None;
+            }
+            let (key, message_start) = (usize::from(field(2)), usize::from(field(4)));
+            let signed = data.get(message_start..message_start + usize::from(field(5)))?;
+            let key = data.get(key..key + PUBKEY_BYTES)?;
+            if signed != message {
+                return None;
+            }
+            Some(Pubkey::new_from_array(key.try_into().ok()?))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, crate::approval::MESSAGE_LEN};
+
+    #[test]
+    fn verify_signatures_round_trip() {
+        let keys = [
+            Pubkey::new_from_array([4; 32]),
+            Pubkey::new_from_array([5; 32]),
+        ];
+        let message = [9; MESSAGE_LEN];
+        let instruction = verify_signatures(
+            &[(keys[0], [6; SIGNATURE_LEN]), (keys[1], [7; SIGNATURE_LEN])],
+            &message,
+        );
+        assert_eq!(instruction.program_id, ed25519_program::id());
+        assert_eq!(instruction.data[..2], [2, 0]);
+        // First entry: signature after its key, both after the offsets.
+        assert_eq!(instruction.data[2..4], 62u16.to_le_bytes());
+        assert_eq!(instruction.data[6..8], 30u16.to_le_bytes());
+        assert_eq!(instruction.data[30..62], keys[0].to_bytes());
+        assert_eq!(instruction.data[62..126], [6; SIGNATURE_LEN]);
+
+        assert_eq!(signers_of(&instruction.data, &message), keys);
+        assert!(signers_of(&instruction.data, &[0; MESSAGE_LEN]).is_empty());
+        assert!(signers_of(&instruction.data[..20], &message).is_empty());
+    }
+
+    #[test]
+    fn signers_of_ignores_other_instructions() {
+        let key = Pubkey::new_from_array([4; 32]);
+        let message = [9; MESSAGE_LEN];
+        let mut data = verify_signatures(&[(key, [6; SIGNATURE_LEN])], &message).data;
+        assert_eq!(signers_of(&data, &message), [key]);
+
+        // A message read from another instruction does not count.
+        data[14..16].copy_from_slice(&0u16.to_le_bytes());
+        assert!(signers_of(&data, &message).is_empty());
+    }
+}
@@ -0,0 +1,70 @@
+//! Custody receipt tokens: a single SPL token per vault, minted by the program so
+//! wallets show who owns the vault.
+use {
+    solana_program::{
+        account_info::AccountInfo, program::invoke, program_error::ProgramError,
+        program_pack::Pack, pubkey::Pubkey,
+    },
+    spl_token::state::Account as TokenAccount,
+};
+
+/// Seed prefix of receipt mints: `[RECEIPT_SEED, vault]`. The mint is its own mint
+/// authority, since vault accounts need not be program addresses.
+pub const RECEIPT_SEED: &[u8] = b"receipt";
+
+/// Seed prefix of the token account a receipt is minted to:
+/// `[RECEIPT_ACCOUNT_SEED, vault]`.
+pub const RECEIPT_ACCOUNT_SEED: &[u8] = b"receipt_account";
+
+/// Find the receipt mint address of `vault`.
+pub fn find_mint_address(program_id: &Pubkey, vault: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[RECEIPT_SEED, vault.as_ref()], program_id)
+}
+
+/// Find the address of the token account a receipt of `vault` is minted to.
+pub fn find_token_account_address(program_id: &Pubkey, vault: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[RECEIPT_ACCOUNT_SEED, vault.as_ref()], program_id)
+}
+
+/// Find a token account holding the receipt of `mint` among `accounts`.
+pub fn find_holder<'a, 'b>(
+    accounts: &'b [AccountInfo<'a>],
+    mint: &Pubkey,
+) -> Option<(&'b AccountInfo<'a>, TokenAccount)> {
+    accounts
+        .iter()
+        .filter(|account| *account.owner == spl_token::id())
+        .find_map(|account| {
+            let token = TokenAccount::unpack(&account.data.borrow()).ok()?;
+            (token.mint == *mint && token.amount == 1).then_some((account, token))
+        })
+}
+
+/// Burn the receipt held in `holder` and close the emptied token account, signed by
+/// its `owner`, returning the rent to `owner`.
+pub fn burn<'a>(
+    token_program: &AccountInfo<'a>,
+    mint: &AccountInfo<'a>,
+    holder: &AccountInfo<'a>,
+    owner: &AccountInfo<'a>,
+) -> Result<(), ProgramError> {
+    invoke(
+        &spl_token::instruction::burn(token_program.key, holder.key, mint.key, owner.key, &[], 1)?,
+        &[
+            holder.clone(),
+            mint.clone(),
+            owner.clone(),
+            token_program.clone(),
+        ],
+    )?;
+    invoke(
+        &spl_token::instruction::close_account(
+            token_program.key,
+            holder.key,
+            owner.key,
+            owner.key,
+            &[],
+        )?,
+        &[holder.clone(), owner.clone(), token_program.clone()],
+    )
+}
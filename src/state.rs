@@ -1,11 +1,293 @@
 use {
+    crate::{error::VaultError, math},
     borsh::{BorshDeserialize, BorshSchema, BorshSerialize},
-    solana_program::{program_pack::IsInitialized, pubkey::Pubkey},
+    solana_program::{
+        bpf_loader_upgradeable, hash::hashv, keccak, msg, program_error::ProgramError,
+        program_pack::IsInitialized, pubkey::Pubkey,
+    },
+    spl_discriminator::{ArrayDiscriminator, SplDiscriminate},
+    std::mem::size_of,
 };
 
+/// Bytes held back at the end of the record for future fields.
+const RESERVED_LEN: usize = 13;
+
+/// Bytes held back at the end of a DART identity for future fields.
+const IDENTITY_RESERVED_LEN: usize = 55;
+
+/// Operational key slots in a DART identity.
+pub const MAX_OPERATIONAL_KEYS: usize = 4;
+
+/// Bytes held back at the end of a session record for future fields.
+const SESSION_RESERVED_LEN: usize = 32;
+
+/// Seed prefix of session accounts: `[SESSION_SEED, vault, session key]`.
+pub const SESSION_SEED: &[u8] = b"session";
+
+/// Guardian slots in a recovery record.
+pub const MAX_GUARDIANS: usize = 5;
+
+/// Bytes held back at the end of a recovery record for future fields.
+const RECOVERY_RESERVED_LEN: usize = 32;
+
+/// Seed prefix of recovery accounts: `[RECOVERY_SEED, vault]`.
+pub const RECOVERY_SEED: &[u8] = b"recovery";
+
+/// Slots a recovery must wait after initiation, during which the current authority
+/// may cancel it (about a day at 400ms slots).
+pub const RECOVERY_CHALLENGE_SLOTS: u64 = 216_000;
+
+/// Bytes held back at the end of an inheritance record for future fields.
+const INHERITANCE_RESERVED_LEN: usize = 32;
+
+/// Seed prefix of inheritance accounts: `[INHERITANCE_SEED, vault]`.
+pub const INHERITANCE_SEED: &[u8] = b"inheritance";
+
+/// Allowlist slots in a transfer hook config.
+pub const MAX_ALLOWLIST: usize = 8;
+
+/// Bytes held back at the end of a transfer hook config for future fields.
+const TRANSFER_HOOK_RESERVED_LEN: usize = 32;
+
+/// Seed prefix of transfer hook configs: `[TRANSFER_HOOK_SEED, mint]`.
+pub const TRANSFER_HOOK_SEED: &[u8] = b"transfer_hook";
+
+/// Bytes held back at the end of the program config for future fields.
+const CONFIG_RESERVED_LEN: usize = 16;
+
+/// Seed of the program config: `[CONFIG_SEED]`.
+pub const CONFIG_SEED: &[u8] = b"config";
+
+/// Largest share, in basis points, of a swept account's lamports `Crank` pays the
+/// cranker, so sweeping accounts one created oneself never returns more than their rent.
+pub const MAX_CRANK_BOUNTY_BPS: u64 = 1_000;
+
+/// Bytes held back at the end of the fee config for future fields.
+const FEE_CONFIG_RESERVED_LEN: usize = 12;
+
+/// Key slots in the fee exemption list.
+pub const MAX_FEE_EXEMPT: usize = 16;
+
+/// Close fee tiers in the fee config.
+pub const MAX_CLOSE_FEE_TIERS: usize = 4;
+
+/// Seed of the fee config: `[FEE_CONFIG_SEED]`.
+pub const FEE_CONFIG_SEED: &[u8] = b"fee_config";
+
+/// Bytes held back in an audit trail header for future fields.
+const AUDIT_TRAIL_RESERVED_LEN: usize = 32;
+
+/// Most entries an audit trail holds, bounding its account at about 10 KiB.
+pub const MAX_AUDIT_ENTRIES: u16 = 256;
+
+/// Seed prefix of audit trails: `[AUDIT_TRAIL_SEED, vault]`.
+pub const AUDIT_TRAIL_SEED: &[u8] = b"audit_trail";
+
+/// Bytes held back at the end of the program stats for future fields.
+const STATS_RESERVED_LEN: usize = 32;
+
+/// Instruction tags the program stats count, room for instructions yet to come.
+pub const MAX_STATS_TAGS: usize = 128;
+
+/// Seed of the program stats: `[STATS_SEED]`.
+pub const STATS_SEED: &[u8] = b"stats";
+
+/// Bytes held back at the end of an approval root for future fields.
+const APPROVAL_ROOT_RESERVED_LEN: usize = 32;
+
+/// Seed prefix of DART approval roots: `[APPROVAL_ROOT_SEED, dart]`.
+pub const APPROVAL_ROOT_SEED: &[u8] = b"approval_root";
+
+/// Bytes held back at the end of a note record for future fields.
+const NOTE_RESERVED_LEN: usize = 32;
+
+/// Capacity of an encrypted note, including the sealing overhead (e.g. ephemeral key,
+/// nonce and tag).
+pub const ENCRYPTED_NOTE_LEN: usize = 128;
+
+/// Seed prefix of note accounts: `[NOTE_SEED, vault]`.
+pub const NOTE_SEED: &[u8] = b"note";
+
+/// Bytes held back at the end of a notify record for future fields.
+const NOTIFY_RESERVED_LEN: usize = 16;
+
+/// Seed prefix of notify records: `[NOTIFY_SEED, vault]`.
+pub const NOTIFY_SEED: &[u8] = b"notify";
+
+/// Bytes held back at the end of a notification for future fields.
+const NOTIFICATION_RESERVED_LEN: usize = 16;
+
+/// Seed prefix of notifications: `[NOTIFICATION_SEED, notify]`.
+pub const NOTIFICATION_SEED: &[u8] = b"notification";
+
+/// Bytes held back at the end of a commitment record for future fields.
+const COMMITMENT_RESERVED_LEN: usize = 32;
+
+/// Commitment slots in a commitment record.
+pub const MAX_COMMITMENTS: usize = 4;
+
+/// Seed prefix of commitment accounts: `[COMMITMENT_SEED, vault]`.
+pub const COMMITMENT_SEED: &[u8] = b"commitments";
+
+/// Bytes held back at the end of a notes log for future fields.
+const NOTES_LOG_RESERVED_LEN: usize = 32;
+
+/// Entries a notes log keeps before evicting the oldest.
+pub const MAX_LOG_NOTES: usize = 8;
+
+/// Seed prefix of notes log accounts: `[NOTES_LOG_SEED, vault]`.
+pub const NOTES_LOG_SEED: &[u8] = b"notes_log";
+
+/// Bytes held back at the end of an attestation list for future fields.
+const ATTESTATIONS_RESERVED_LEN: usize = 32;
+
+/// Attestations an attestation list holds.
+pub const MAX_ATTESTATIONS: usize = 8;
+
+/// Seed prefix of attestation list accounts: `[ATTESTATIONS_SEED, vault]`.
+pub const ATTESTATIONS_SEED: &[u8] = b"attestations";
+
+/// Bytes held back at the end of a pledge record for future fields.
+const PLEDGE_RESERVED_LEN: usize = 32;
+
+/// Seed prefix of pledge accounts: `[PLEDGE_SEED, vault]`.
+pub const PLEDGE_SEED: &[u8] = b"pledge";
+
+/// Bytes held back at the end of a position record for future fields.
+const POSITION_RESERVED_LEN: usize = 32;
+
+/// Seed prefix of position accounts: `[POSITION_SEED, vault]`.
+pub const POSITION_SEED: &[u8] = b"position";
+
+/// Bytes held back at the end of a distribution cursor for future fields.
+const DISTRIBUTION_RESERVED_LEN: usize = 32;
+
+/// Seed prefix of distribution cursors: `[DISTRIBUTION_SEED, dart, id]`, the id little
+/// endian.
+pub const DISTRIBUTION_SEED: &[u8] = b"distribution";
+
+/// Bytes held back at the end of a snapshot for future fields.
+const SNAPSHOT_RESERVED_LEN: usize = 32;
+
+/// Seed prefix of snapshots: `[SNAPSHOT_SEED, vault, record_date_slot]`, the slot little
+/// endian.
+pub const SNAPSHOT_SEED: &[u8] = b"snapshot";
+
+/// Bytes held back at the end of a voting proxy for future fields.
+const VOTING_PROXY_RESERVED_LEN: usize = 32;
+
+/// Seed prefix of voting proxy accounts: `[VOTING_PROXY_SEED, vault]`.
+pub const VOTING_PROXY_SEED: &[u8] = b"voting_proxy";
+
+/// Bytes held back at the end of a transfer window for future fields.
+const TRANSFER_WINDOW_RESERVED_LEN: usize = 32;
+
+/// Blackout ranges a transfer window holds.
+pub const MAX_BLACKOUTS: usize = 4;
+
+/// Seed prefix of transfer windows: `[TRANSFER_WINDOW_SEED, kind, scope]`, the scope
+/// being a vault or, for the window of all its vaults, a DART, as `kind` tells.
+pub const TRANSFER_WINDOW_SEED: &[u8] = b"transfer_window";
+/// Kind seed of the transfer window of a vault
+pub const TRANSFER_WINDOW_VAULT: &[u8] = b"vault";
+/// Kind seed of the transfer window of all a DART's vaults
+pub const TRANSFER_WINDOW_DART: &[u8] = b"dart";
+
+/// Bytes held back at the end of a rate limit for future fields.
+const RATE_LIMIT_RESERVED_LEN: usize = 32;
+
+/// Seed prefix of rate limit accounts: `[RATE_LIMIT_SEED, vault]`.
+pub const RATE_LIMIT_SEED: &[u8] = b"rate_limit";
+
+/// Bytes held back at the end of a DART policy for future fields.
+const DART_POLICY_RESERVED_LEN: usize = 22;
+
+/// Seed prefix of DART policies: `[DART_POLICY_SEED, dart]`.
+pub const DART_POLICY_SEED: &[u8] = b"dart_policy";
+
+/// Bytes held back at the end of a vault index for future fields.
+const VAULT_INDEX_RESERVED_LEN: usize = 32;
+
+/// Vault slots in an authority's vault index.
+pub const MAX_INDEXED_VAULTS: usize = 32;
+
+/// Seed prefix of vault indexes: `[VAULT_INDEX_SEED, authority]`.
+pub const VAULT_INDEX_SEED: &[u8] = b"vault_index";
+
+/// Seed prefix of vaults created by `CreateAssetVault`:
+/// `[ASSET_VAULT_SEED, dart, authority, asset id]`.
+pub const ASSET_VAULT_SEED: &[u8] = b"asset_vault";
+
+/// Bits of `SessionRecord::scope`: the instructions a session key may sign in place of
+/// the authority.
+pub mod session_scope {
+    /// `TransferAuthority`
+    pub const TRANSFER_AUTHORITY: u8 = 1 << 0;
+    /// Every defined scope bit
+    pub const ALL: u8 = TRANSFER_AUTHORITY;
+}
+
+/// Bits of `VaultRecord::restrictions`: the instructions the DART bars the vault from.
+pub mod restriction {
+    /// `MintReceipt`
+    pub const NO_RECEIPT: u8 = 1 << 0;
+    /// `CreateSession`
+    pub const NO_SESSIONS: u8 = 1 << 1;
+    /// Every defined restriction bit
+    pub const ALL: u8 = NO_RECEIPT | NO_SESSIONS;
+}
+
+/// Lifecycle status of a vault record.
+#[derive(
+    Clone, Copy, Debug, Default, BorshSerialize, BorshDeserialize, BorshSchema, PartialEq, Eq,
+)]
+pub enum VaultStatus {
+    /// Open for normal operation
+    #[default]
+    Active,
+    /// Authority changes are blocked until thawed
+    Frozen,
+    /// An authority transfer is awaiting acceptance
+    PendingTransfer,
+    /// Held under the control of the securities intermediary
+    Seized,
+    /// Lamports drained; only a reopen is permitted
+    Closed,
+}
+
+impl VaultStatus {
+    /// Whether a record may move from this status to `next`.
+    pub const fn can_transition_to(self, next: VaultStatus) -> bool {
+        use VaultStatus::*;
+        matches!(
+            (self, next),
+            (Active, Frozen | PendingTransfer | Seized | Closed)
+                | (Frozen, Active | Seized)
+                | (PendingTransfer, Active | Frozen | Seized)
+                | (Seized, Active | Closed)
+                | (Closed, Active)
+        )
+    }
+
+    /// Move to `next`, failing if the transition is not allowed.
+    pub fn transition_to(&mut self, next: VaultStatus) -> Result<(), VaultError> {
+        if !self.can_transition_to(next) {
+            return Err(VaultError::InvalidStatusTransition);
+        }
+        *self = next;
+        Ok(())
+    }
+}
+
 /// Struct providing metadata (and could be extended to support data).
-#[derive(Clone, Debug, BorshSerialize, BorshDeserialize, BorshSchema, PartialEq)]
+#[derive(
+    Clone, Debug, BorshSerialize, BorshDeserialize, BorshSchema, PartialEq, SplDiscriminate,
+)]
+#[discriminator_hash_input("account:VaultRecord")]
 pub struct VaultRecord {
+    /// Account type discriminator, `SPL_DISCRIMINATOR` once initialized
+    discriminator: [u8; ArrayDiscriminator::LENGTH],
+
     /// Struct version, allows for upgrades to the program
     pub version: u8,
 
@@ -14,57 +296,4531 @@ pub struct VaultRecord {
 
     /// The securities intermediary
     pub dart: Pubkey,
+
+    /// Lifecycle status; closed records stay closed if the account is revived
+    pub status: VaultStatus,
+
+    /// DART proposed by `ProposeDart`, or the default pubkey when none is pending
+    pub pending_dart: Pubkey,
+
+    /// Category set by the DART (e.g. retail vs institutional); zero when untagged
+    pub tag: u16,
+
+    /// Account data length chosen at initialize; zero on records that predate it,
+    /// which are exactly `header_len` bytes
+    pub data_len: u32,
+
+    /// Length `GrowAccount` is growing the account toward; zero when never grown
+    pub target_len: u32,
+
+    /// Funder of the account rent, refunded on close; the default pubkey refunds
+    /// the authority. Version 4 and later.
+    pub rent_payer: Pubkey,
+
+    /// Account paid on close whoever signs, overriding `rent_payer`; the default
+    /// pubkey when unset. Version 4 and later.
+    pub close_recipient: Pubkey,
+
+    /// Digest of the Wormhole VAA last attested by the DART (see `vaa_digest`),
+    /// anchoring a custody event on another chain; zero when none. Version 4 and later.
+    pub attestation: [u8; 32],
+
+    /// Authority proposed by `ProposeTransfer` while the status is `PendingTransfer`,
+    /// or the default pubkey. Version 4 and later.
+    pub pending_authority: Pubkey,
+
+    /// Last slot `AcceptTransfer` may complete the pending transfer in. Version 4
+    /// and later.
+    pub transfer_expires_at_slot: u64,
+
+    /// Price feed checked before the authority changes, or the default pubkey when
+    /// transfers are not gated. Version 4 and later.
+    pub oracle: Pubkey,
+
+    /// Slots the oracle may go without publishing before transfers are refused
+    pub oracle_max_staleness_slots: u64,
+
+    /// Lowest oracle price, in the feed's exponent, at which transfers are allowed
+    pub oracle_min_price: i64,
+
+    /// Mint of the vault's custody receipt token while one is outstanding, or the
+    /// default pubkey. Version 4 and later.
+    pub receipt_mint: Pubkey,
+
+    /// Whether the authority is whoever holds the receipt token, so ownership moves
+    /// by token transfer; `authority` is then the holder last seen. Version 4 and later.
+    pub receipt_is_authority: bool,
+
+    /// Checksum of the rest of the header, written by `pack_into` and verified by
+    /// `from_account_data`. Version 5 and later.
+    pub checksum: u32,
+
+    /// Whether the vault is pledged as collateral, so the authority only changes with
+    /// the lender's signature (see `PledgeRecord`). Carved from the reserved bytes after
+    /// `checksum`, so records written before it still verify. Version 4 and later.
+    pub pledged: bool,
+
+    /// Whether the vault has its own transfer window, so `TransferAuthority` must be
+    /// passed its `TransferWindow` account. Carved from the reserved bytes after
+    /// `checksum`. Version 4 and later.
+    pub transfer_windowed: bool,
+
+    /// Whether the vault has a rate limit, so mutations must be passed its `RateLimit`
+    /// account. Carved from the last reserved byte. Version 4 and later.
+    pub rate_limited: bool,
+
+    /// Slot the vault was created in, which close fee tiers price its age by; zero on
+    /// records created before version 6.
+    pub created_slot: u64,
+
+    /// Whether the vault has an audit trail, so instructions acting on it must be passed
+    /// its `AuditTrail` account. Carved from the reserved bytes after `created_slot`.
+    /// Version 6 and later.
+    pub audited: bool,
+
+    /// Transfers redeemed with `TransferWithProof`, bound into their Merkle leaves so
+    /// each approval is redeemed once (see `approval::transfer_leaf`). Version 6 and
+    /// later.
+    pub approval_nonce: u64,
+
+    /// Whether instructions acting on the vault need the DART's signature, even those
+    /// the authority alone may sign otherwise. Version 6 and later.
+    pub cosign: bool,
+
+    /// Lamports charged on close when no close fee tier matches the vault, in place of
+    /// the fee config's default; zero keeps the default. Version 6 and later.
+    pub close_fee: u64,
+
+    /// Bits of `restriction` barring the vault from instructions. Version 6 and later.
+    pub restrictions: u8,
+
+    /// Zeroed padding; new fields are carved from here to avoid resizing accounts
+    reserved: [u8; RESERVED_LEN],
 }
 
 impl VaultRecord {
     /// Version to fill in on new created accounts
-    pub const CURRENT_VERSION: u8 = 1;
+    pub const CURRENT_VERSION: u8 = 6;
+    /// Oldest version still accepted, stored in the `LEN_V1` layout
+    pub const MIN_VERSION: u8 = 1;
+    /// Oldest version starting with the discriminator, stored in the shorter `LEN_V3`
+    /// layout
+    pub const DISCRIMINATOR_VERSION: u8 = 3;
+    /// Oldest version stored in the `LEN_V5` layout; upgraded to version 5 when
+    /// written
+    pub const FULL_LAYOUT_VERSION: u8 = 4;
+    /// Oldest version carrying a checksum
+    pub const CHECKSUM_VERSION: u8 = 5;
+    /// Oldest version stored in the full `LEN` layout, carrying the creation slot
+    pub const CREATED_SLOT_VERSION: u8 = 6;
     /// Packed vault record space
-    pub const LEN: usize = 65; // 1 + 32 + 32
+    pub const LEN: usize = ArrayDiscriminator::LENGTH
+        + size_of::<u8>()
+        + size_of::<Pubkey>()
+        + size_of::<Pubkey>()
+        + size_of::<VaultStatus>()
+        + size_of::<Pubkey>()
+        + size_of::<u16>()
+        + size_of::<u32>()
+        + size_of::<u32>()
+        + size_of::<Pubkey>()
+        + size_of::<Pubkey>()
+        + size_of::<[u8; 32]>()
+        + size_of::<Pubkey>()
+        + size_of::<u64>()
+        + size_of::<Pubkey>()
+        + size_of::<u64>()
+        + size_of::<i64>()
+        + size_of::<Pubkey>()
+        + size_of::<bool>()
+        + size_of::<u32>()
+        + size_of::<bool>()
+        + size_of::<bool>()
+        + size_of::<bool>()
+        + Self::V6_FIELDS_LEN
+        + RESERVED_LEN;
+    // Space of the fields version 6 appended to the version 5 layout, from
+    // `created_slot` to `restrictions`.
+    const V6_FIELDS_LEN: usize = size_of::<u64>()
+        + size_of::<bool>()
+        + size_of::<u64>()
+        + size_of::<bool>()
+        + size_of::<u64>()
+        + size_of::<u8>();
+    /// Packed space of a version 1 record: the version, authority and DART, without the
+    /// discriminator. Accounts sized for the closed flag once added without a version
+    /// bump carry it in one more byte, read as the `Closed` status.
+    pub const LEN_V1: usize = 65;
+    /// Packed space of a version 2 record: the version 3 layout without the
+    /// discriminator
+    pub const LEN_V2: usize = 130;
+    /// Packed space of a version 3 record: the current layout cut short after 22 bytes
+    /// of `rent_payer`, with every field past `target_len` zero
+    pub const LEN_V3: usize = 138;
+    /// Packed space of a version 4 or 5 record: the current layout cut short before
+    /// `created_slot`
+    pub const LEN_V5: usize = Self::LEN - Self::V6_FIELDS_LEN - RESERVED_LEN;
+    /// Byte offset of `authority`, for `getProgramAccounts` memcmp filters
+    pub const AUTHORITY_OFFSET: usize = ArrayDiscriminator::LENGTH + size_of::<u8>();
+    /// Byte offset of `dart`, for `getProgramAccounts` memcmp filters
+    pub const DART_OFFSET: usize =
+        ArrayDiscriminator::LENGTH + size_of::<u8>() + size_of::<Pubkey>();
+    /// Byte offset of `tag`, for `getProgramAccounts` memcmp filters
+    pub const TAG_OFFSET: usize = ArrayDiscriminator::LENGTH
+        + size_of::<u8>()
+        + size_of::<Pubkey>()
+        + size_of::<Pubkey>()
+        + size_of::<VaultStatus>()
+        + size_of::<Pubkey>();
+    /// Byte offset of `checksum`
+    const CHECKSUM_OFFSET: usize = Self::LEN_V5 - 3 * size_of::<bool>() - size_of::<u32>();
+
+    /// Create a current-version record for the given authority and DART.
+    pub fn new(authority: Pubkey, dart: Pubkey) -> Self {
+        let mut discriminator = [0; ArrayDiscriminator::LENGTH];
+        discriminator.copy_from_slice(Self::SPL_DISCRIMINATOR_SLICE);
+        Self {
+            discriminator,
+            version: Self::CURRENT_VERSION,
+            authority,
+            dart,
+            status: VaultStatus::Active,
+            pending_dart: Pubkey::default(),
+            tag: 0,
+            data_len: 0,
+            target_len: 0,
+            rent_payer: Pubkey::default(),
+            close_recipient: Pubkey::default(),
+            attestation: [0; 32],
+            pending_authority: Pubkey::default(),
+            transfer_expires_at_slot: 0,
+            oracle: Pubkey::default(),
+            oracle_max_staleness_slots: 0,
+            oracle_min_price: 0,
+            receipt_mint: Pubkey::default(),
+            receipt_is_authority: false,
+            checksum: 0,
+            pledged: false,
+            transfer_windowed: false,
+            rate_limited: false,
+            created_slot: 0,
+            audited: false,
+            approval_nonce: 0,
+            cosign: false,
+            close_fee: 0,
+            restrictions: 0,
+            reserved: [0; RESERVED_LEN],
+        }
+    }
+
+    /// Deserialize the fixed header from account data of any length, reading records
+    /// before version 4 from their shorter layouts and verifying the checksum of
+    /// initialized records that carry one.
+    pub fn from_account_data(data: &[u8]) -> Result<Self, ProgramError> {
+        if let Some(record) = Self::from_legacy_data(data)? {
+            return Ok(record);
+        }
+        let len = match data.get(ArrayDiscriminator::LENGTH) {
+            Some(version) if *version < Self::FULL_LAYOUT_VERSION => Self::LEN_V3,
+            Some(version) if *version < Self::CREATED_SLOT_VERSION => Self::LEN_V5,
+            _ => Self::LEN,
+        };
+        let mut header = [0; Self::LEN];
+        header[..len].copy_from_slice(data.get(..len).ok_or(ProgramError::AccountDataTooSmall)?);
+        let record = Self::try_from_slice(&header)?;
+        if record.discriminator == Self::SPL_DISCRIMINATOR_SLICE
+            && record.version >= Self::CHECKSUM_VERSION
+            && record.checksum != Self::checksum_of(&header[..len])
+        {
+            msg!("Vault record checksum mismatch");
+            return Err(VaultError::ChecksumMismatch.into());
+        }
+        Ok(record)
+    }
+
+    // Version 1 and 2 records predate the discriminator and start with the version byte,
+    // in accounts of exactly their layout's length (a version 1 record's plus the closed
+    // flag's); anything else, such as another account type whose discriminator starts
+    // with the same byte, is left to the current layout. The discriminator is filled in
+    // so the record reads as initialized.
+    fn from_legacy_data(data: &[u8]) -> Result<Option<Self>, ProgramError> {
+        let len = match (data.first(), data.len()) {
+            _ if data.starts_with(Self::SPL_DISCRIMINATOR_SLICE) => return Ok(None),
+            (Some(1), len) if len == Self::LEN_V1 || len == Self::LEN_V1 + 1 => Self::LEN_V1,
+            (Some(2), Self::LEN_V2) => Self::LEN_V2,
+            _ => return Ok(None),
+        };
+        let mut header = [0; Self::LEN];
+        header[..ArrayDiscriminator::LENGTH].copy_from_slice(Self::SPL_DISCRIMINATOR_SLICE);
+        header[ArrayDiscriminator::LENGTH..ArrayDiscriminator::LENGTH + len]
+            .copy_from_slice(&data[..len]);
+        let mut record = Self::try_from_slice(&header)?;
+        if len == Self::LEN_V1 && data.get(len).is_some_and(|closed| *closed != 0) {
+            record.status = VaultStatus::Closed;
+        }
+        Ok(Some(record))
+    }
+
+    /// Serialize the header into the start of the account data, in the layout of the
+    /// record's version, with a fresh checksum.
+    pub fn pack_into(&self, data: &mut [u8]) -> Result<(), ProgramError> {
+        let mut bytes = borsh::to_vec(self)?;
+        if self.version < Self::DISCRIMINATOR_VERSION {
+            return self.pack_legacy_into(&bytes[ArrayDiscriminator::LENGTH..], data);
+        }
+        if self.version >= Self::FULL_LAYOUT_VERSION {
+            bytes[ArrayDiscriminator::LENGTH] = self.version.max(Self::CHECKSUM_VERSION);
+            let checksum = Self::checksum_of(&bytes[..self.header_len()]);
+            bytes[Self::CHECKSUM_OFFSET..Self::CHECKSUM_OFFSET + size_of::<u32>()]
+                .copy_from_slice(&checksum.to_le_bytes());
+        }
+        let (header, rest) = bytes.split_at(self.header_len());
+        if rest.iter().any(|byte| *byte != 0) {
+            msg!("Field not supported by version {} records", self.version);
+            return Err(ProgramError::InvalidAccountData);
+        }
+        data.get_mut(..header.len())
+            .ok_or(ProgramError::AccountDataTooSmall)?
+            .copy_from_slice(header);
+        Ok(())
+    }
+
+    // Write a version 1 or 2 record, `bytes` being its current layout without the
+    // discriminator. A version 1 record keeps its status in the closed flag when the
+    // account has room for it; one closed without room gets a zero version instead, so
+    // it no longer loads.
+    fn pack_legacy_into(&self, bytes: &[u8], data: &mut [u8]) -> Result<(), ProgramError> {
+        let (header, mut rest) = bytes.split_at(self.header_len());
+        let mut closed = None;
+        if self.version == 1 {
+            closed = match self.status {
+                VaultStatus::Active => Some(0),
+                VaultStatus::Closed => Some(1),
+                _ => None,
+            };
+            rest = &rest[size_of::<VaultStatus>()..];
+        }
+        if rest.iter().any(|byte| *byte != 0) || (self.version == 1 && closed.is_none()) {
+            msg!("Field not supported by version {} records", self.version);
+            return Err(ProgramError::InvalidAccountData);
+        }
+        data.get_mut(..header.len())
+            .ok_or(ProgramError::AccountDataTooSmall)?
+            .copy_from_slice(header);
+        match (closed, data.get_mut(header.len())) {
+            (Some(closed), Some(flag)) => *flag = closed,
+            (Some(1), None) => data[0] = 0,
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Length of the fixed header; any account data past it is metadata.
+    pub fn header_len(&self) -> usize {
+        match self.version {
+            1 => Self::LEN_V1,
+            2 => Self::LEN_V2,
+            version if version < Self::FULL_LAYOUT_VERSION => Self::LEN_V3,
+            version if version < Self::CREATED_SLOT_VERSION => Self::LEN_V5,
+            _ => Self::LEN,
+        }
+    }
+
+    /// Account data length of the record, header included.
+    pub fn account_len(&self) -> usize {
+        match self.data_len {
+            0 => self.header_len(),
+            data_len => data_len as usize,
+        }
+    }
+
+    // First four bytes, little endian, of the SHA-256 of a packed header without its
+    // checksum.
+    fn checksum_of(header: &[u8]) -> u32 {
+        let digest = hashv(&[
+            &header[..Self::CHECKSUM_OFFSET],
+            &header[Self::CHECKSUM_OFFSET + size_of::<u32>()..],
+        ]);
+        u32::from_le_bytes(digest.to_bytes()[..4].try_into().unwrap())
+    }
+
+    /// Memcmp filter `(offset, bytes)` matching vault record accounts, to pair with
+    /// other filters so accounts of other types are skipped.
+    pub fn discriminator_filter() -> (usize, &'static [u8]) {
+        (0, Self::SPL_DISCRIMINATOR_SLICE)
+    }
+
+    /// Memcmp filter `(offset, bytes)` matching records whose authority is `authority`.
+    pub fn authority_filter(authority: &Pubkey) -> (usize, [u8; 32]) {
+        (Self::AUTHORITY_OFFSET, authority.to_bytes())
+    }
+
+    /// Memcmp filter `(offset, bytes)` matching records whose DART is `dart`.
+    pub fn dart_filter(dart: &Pubkey) -> (usize, [u8; 32]) {
+        (Self::DART_OFFSET, dart.to_bytes())
+    }
+
+    /// Memcmp filter `(offset, bytes)` matching records with `tag`.
+    pub fn tag_filter(tag: u16) -> (usize, [u8; 2]) {
+        (Self::TAG_OFFSET, tag.to_le_bytes())
+    }
+}
+
+// Adding a field to `VaultRecord` breaks this pattern, forcing `LEN` to be revisited.
+const _: fn(&VaultRecord) = |record| {
+    let VaultRecord {
+        discriminator: _,
+        version: _,
+        authority: _,
+        dart: _,
+        status: _,
+        pending_dart: _,
+        tag: _,
+        data_len: _,
+        target_len: _,
+        rent_payer: _,
+        close_recipient: _,
+        attestation: _,
+        pending_authority: _,
+        transfer_expires_at_slot: _,
+        oracle: _,
+        oracle_max_staleness_slots: _,
+        oracle_min_price: _,
+        receipt_mint: _,
+        receipt_is_authority: _,
+        checksum: _,
+        pledged: _,
+        transfer_windowed: _,
+        rate_limited: _,
+        created_slot: _,
+        audited: _,
+        approval_nonce: _,
+        cosign: _,
+        close_fee: _,
+        restrictions: _,
+        reserved: _,
+    } = record;
+};
+
+/// Digest identifying a Wormhole VAA, `keccak256(keccak256(body))`, for
+/// `VaultRecord::attestation`. The body follows the version, guardian set index and
+/// guardian signatures.
+pub fn vaa_digest(vaa: &[u8]) -> Result<[u8; 32], ProgramError> {
+    const SIGNATURES_OFFSET: usize = size_of::<u8>() + size_of::<u32>() + size_of::<u8>();
+    const SIGNATURE_LEN: usize = 66;
+    let signatures = *vaa
+        .get(SIGNATURES_OFFSET - 1)
+        .ok_or(ProgramError::InvalidInstructionData)?;
+    let body = vaa
+        .get(SIGNATURES_OFFSET + usize::from(signatures) * SIGNATURE_LEN..)
+        .ok_or(ProgramError::InvalidInstructionData)?;
+    Ok(keccak::hash(&keccak::hash(body).to_bytes()).to_bytes())
+}
+
+/// Find the address of the vault `CreateAssetVault` creates for `authority` holding
+/// `asset_id` through `dart`. The address keeps the seeds it was created with when the
+/// authority later changes.
+pub fn find_asset_vault_address(
+    program_id: &Pubkey,
+    dart: &Pubkey,
+    authority: &Pubkey,
+    asset_id: &[u8; 32],
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            ASSET_VAULT_SEED,
+            dart.as_ref(),
+            authority.as_ref(),
+            asset_id,
+        ],
+        program_id,
+    )
+}
+
+/// The Associated Token Account program, which derives the token account a wallet holds
+/// of a mint.
+pub const ASSOCIATED_TOKEN_PROGRAM_ID: Pubkey =
+    solana_program::pubkey!("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL");
+
+/// Find the associated SPL Token account of `vault` for the mint `asset_id`, the one a
+/// vault holding its position's asset in custody uses.
+pub fn find_asset_token_address(vault: &Pubkey, asset_id: &[u8; 32]) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[vault.as_ref(), spl_token::id().as_ref(), asset_id],
+        &ASSOCIATED_TOKEN_PROGRAM_ID,
+    )
 }
 
 impl IsInitialized for VaultRecord {
     /// Is initialized
     fn is_initialized(&self) -> bool {
-        self.version == Self::CURRENT_VERSION
+        self.discriminator == Self::SPL_DISCRIMINATOR_SLICE
+            && (Self::MIN_VERSION..=Self::CURRENT_VERSION).contains(&self.version)
     }
 }
 
-#[cfg(test)]
-pub mod tests {
-    use super::*;
-    use solana_program::program_error::ProgramError;
+/// A DART identity: a cold master key that rotates the operational keys used for
+/// day-to-day instructions. A vault record whose `dart` is an identity address accepts
+/// a signature from any of its current operational keys.
+#[derive(
+    Clone, Debug, BorshSerialize, BorshDeserialize, BorshSchema, PartialEq, SplDiscriminate,
+)]
+#[discriminator_hash_input("account:DartIdentity")]
+pub struct DartIdentity {
+    /// Account type discriminator, `SPL_DISCRIMINATOR` once initialized
+    discriminator: [u8; ArrayDiscriminator::LENGTH],
 
-    /// Version for tests
-    pub const TEST_VERSION: u8 = 1;
-    /// Authority pubkey
-    pub const AUTH_PUBKEY: Pubkey = Pubkey::new_from_array([99; 32]);
-    /// DART pubkey
-    pub const DART_PUBKEY: Pubkey = Pubkey::new_from_array([66; 32]);
-    /// VaultRecord for tests
-    pub const TEST_RECORD_DATA: VaultRecord = VaultRecord {
-        version: TEST_VERSION,
-        authority: AUTH_PUBKEY,
-        dart: DART_PUBKEY,
-    };
+    /// Struct version, allows for upgrades to the program
+    pub version: u8,
 
-    #[test]
-    fn serialize_data() {
-        let mut expected = vec![TEST_VERSION];
-        expected.extend_from_slice(&AUTH_PUBKEY.to_bytes());
-        expected.extend_from_slice(&DART_PUBKEY.to_bytes());
-        assert_eq!(TEST_RECORD_DATA.try_to_vec().unwrap(), expected);
-        assert_eq!(
-            VaultRecord::try_from_slice(&expected).unwrap(),
-            TEST_RECORD_DATA
-        );
+    /// Key reserved for rotating operational keys
+    pub master: Pubkey,
+
+    /// Keys accepted as the DART signature; empty slots hold the default pubkey
+    pub operational_keys: [Pubkey; MAX_OPERATIONAL_KEYS],
+
+    /// Operational key signatures approving as the DART through the Ed25519 program
+    /// (see `quorum`); zero disables quorum approval
+    pub quorum: u8,
+
+    /// Quorum approvals consumed, bound into each approval message
+    pub approval_nonce: u64,
+
+    /// Zeroed padding; new fields are carved from here to avoid resizing accounts
+    reserved: [u8; IDENTITY_RESERVED_LEN],
+}
+
+impl DartIdentity {
+    /// Version to fill in on new created accounts
+    pub const CURRENT_VERSION: u8 = 1;
+    /// Packed DART identity space
+    pub const LEN: usize = ArrayDiscriminator::LENGTH
+        + size_of::<u8>()
+        + size_of::<Pubkey>()
+        + size_of::<Pubkey>() * MAX_OPERATIONAL_KEYS
+        + size_of::<u8>()
+        + size_of::<u64>()
+        + IDENTITY_RESERVED_LEN;
+
+    /// Create a current-version identity with no operational keys.
+    pub fn new(master: Pubkey) -> Self {
+        let mut discriminator = [0; ArrayDiscriminator::LENGTH];
+        discriminator.copy_from_slice(Self::SPL_DISCRIMINATOR_SLICE);
+        Self {
+            discriminator,
+            version: Self::CURRENT_VERSION,
+            master,
+            operational_keys: [Pubkey::default(); MAX_OPERATIONAL_KEYS],
+            quorum: 0,
+            approval_nonce: 0,
+            reserved: [0; IDENTITY_RESERVED_LEN],
+        }
     }
 
-    #[test]
-    fn deserialize_invalid_slice() {
-        let mut expected = vec![TEST_VERSION];
-        expected.extend_from_slice(&AUTH_PUBKEY.to_bytes());
-        let err: ProgramError = VaultRecord::try_from_slice(&expected).unwrap_err().into();
-        assert!(matches!(err, ProgramError::BorshIoError(_)));
+    /// Whether `key` is a current operational key.
+    pub fn is_operational(&self, key: &Pubkey) -> bool {
+        *key != Pubkey::default() && self.operational_keys.contains(key)
+    }
+}
+
+// Adding a field to `DartIdentity` breaks this pattern, forcing `LEN` to be revisited.
+const _: fn(&DartIdentity) = |identity| {
+    let DartIdentity {
+        discriminator: _,
+        version: _,
+        master: _,
+        operational_keys: _,
+        quorum: _,
+        approval_nonce: _,
+        reserved: _,
+    } = identity;
+};
+
+impl IsInitialized for DartIdentity {
+    /// Is initialized
+    fn is_initialized(&self) -> bool {
+        self.discriminator == Self::SPL_DISCRIMINATOR_SLICE && self.version == Self::CURRENT_VERSION
+    }
+}
+
+/// A temporary key allowed to sign for a vault authority within a limited scope, held
+/// in a PDA derived from the vault and session key. The session lapses when it expires
+/// or when the vault authority changes.
+#[derive(
+    Clone, Debug, BorshSerialize, BorshDeserialize, BorshSchema, PartialEq, SplDiscriminate,
+)]
+#[discriminator_hash_input("account:SessionRecord")]
+pub struct SessionRecord {
+    /// Account type discriminator, `SPL_DISCRIMINATOR` once initialized
+    discriminator: [u8; ArrayDiscriminator::LENGTH],
+
+    /// Struct version, allows for upgrades to the program
+    pub version: u8,
+
+    /// The vault record the session applies to
+    pub vault: Pubkey,
+
+    /// The authority that created the session
+    pub authority: Pubkey,
+
+    /// The session key
+    pub key: Pubkey,
+
+    /// Last slot at which the session key is accepted
+    pub expires_at_slot: u64,
+
+    /// `session_scope` bits
+    pub scope: u8,
+
+    /// PDA bump seed
+    pub bump: u8,
+
+    /// Zeroed padding; new fields are carved from here to avoid resizing accounts
+    reserved: [u8; SESSION_RESERVED_LEN],
+}
+
+impl SessionRecord {
+    /// Version to fill in on new created accounts
+    pub const CURRENT_VERSION: u8 = 1;
+    /// Packed session record space
+    pub const LEN: usize = ArrayDiscriminator::LENGTH
+        + size_of::<u8>()
+        + size_of::<Pubkey>()
+        + size_of::<Pubkey>()
+        + size_of::<Pubkey>()
+        + size_of::<u64>()
+        + size_of::<u8>()
+        + size_of::<u8>()
+        + SESSION_RESERVED_LEN;
+
+    /// Create a current-version session record.
+    pub fn new(
+        vault: Pubkey,
+        authority: Pubkey,
+        key: Pubkey,
+        expires_at_slot: u64,
+        scope: u8,
+        bump: u8,
+    ) -> Self {
+        let mut discriminator = [0; ArrayDiscriminator::LENGTH];
+        discriminator.copy_from_slice(Self::SPL_DISCRIMINATOR_SLICE);
+        Self {
+            discriminator,
+            version: Self::CURRENT_VERSION,
+            vault,
+            authority,
+            key,
+            expires_at_slot,
+            scope,
+            bump,
+            reserved: [0; SESSION_RESERVED_LEN],
+        }
+    }
+
+    /// Address and bump of the session account for `key` on `vault`.
+    pub fn find_address(program_id: &Pubkey, vault: &Pubkey, key: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[SESSION_SEED, vault.as_ref(), key.as_ref()], program_id)
+    }
+}
+
+// Adding a field to `SessionRecord` breaks this pattern, forcing `LEN` to be revisited.
+const _: fn(&SessionRecord) = |session| {
+    let SessionRecord {
+        discriminator: _,
+        version: _,
+        vault: _,
+        authority: _,
+        key: _,
+        expires_at_slot: _,
+        scope: _,
+        bump: _,
+        reserved: _,
+    } = session;
+};
+
+impl IsInitialized for SessionRecord {
+    /// Is initialized
+    fn is_initialized(&self) -> bool {
+        self.discriminator == Self::SPL_DISCRIMINATOR_SLICE && self.version == Self::CURRENT_VERSION
+    }
+}
+
+/// Guardians of a vault and its pending recovery, held in a PDA derived from the vault.
+/// A threshold of guardians plus the DART can rotate a lost authority once the challenge
+/// period has passed.
+#[derive(
+    Clone, Debug, BorshSerialize, BorshDeserialize, BorshSchema, PartialEq, SplDiscriminate,
+)]
+#[discriminator_hash_input("account:RecoveryRecord")]
+pub struct RecoveryRecord {
+    /// Account type discriminator, `SPL_DISCRIMINATOR` once initialized
+    discriminator: [u8; ArrayDiscriminator::LENGTH],
+
+    /// Struct version, allows for upgrades to the program
+    pub version: u8,
+
+    /// The vault record the guardians protect
+    pub vault: Pubkey,
+
+    /// Guardian keys; empty slots hold the default pubkey
+    pub guardians: [Pubkey; MAX_GUARDIANS],
+
+    /// Guardian approvals needed to complete a recovery
+    pub threshold: u8,
+
+    /// Authority proposed by the DART, or the default pubkey when no recovery is pending
+    pub pending_authority: Pubkey,
+
+    /// Slot at which the pending recovery was initiated
+    pub initiated_at_slot: u64,
+
+    /// Bitmask of guardian slots that approved the pending recovery
+    pub approvals: u8,
+
+    /// PDA bump seed
+    pub bump: u8,
+
+    /// Zeroed padding; new fields are carved from here to avoid resizing accounts
+    reserved: [u8; RECOVERY_RESERVED_LEN],
+}
+
+impl RecoveryRecord {
+    /// Version to fill in on new created accounts
+    pub const CURRENT_VERSION: u8 = 1;
+    /// Packed recovery record space
+    pub const LEN: usize = ArrayDiscriminator::LENGTH
+        + size_of::<u8>()
+        + size_of::<Pubkey>()
+        + size_of::<Pubkey>() * MAX_GUARDIANS
+        + size_of::<u8>()
+        + size_of::<Pubkey>()
+        + size_of::<u64>()
+        + size_of::<u8>()
+        + size_of::<u8>()
+        + RECOVERY_RESERVED_LEN;
+
+    /// Create a current-version recovery record with no guardians.
+    pub fn new(vault: Pubkey, bump: u8) -> Self {
+        let mut discriminator = [0; ArrayDiscriminator::LENGTH];
+        discriminator.copy_from_slice(Self::SPL_DISCRIMINATOR_SLICE);
+        Self {
+            discriminator,
+            version: Self::CURRENT_VERSION,
+            vault,
+            guardians: [Pubkey::default(); MAX_GUARDIANS],
+            threshold: 0,
+            pending_authority: Pubkey::default(),
+            initiated_at_slot: 0,
+            approvals: 0,
+            bump,
+            reserved: [0; RECOVERY_RESERVED_LEN],
+        }
+    }
+
+    /// Address and bump of the recovery account for `vault`.
+    pub fn find_address(program_id: &Pubkey, vault: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[RECOVERY_SEED, vault.as_ref()], program_id)
+    }
+
+    /// Slot of `guardian`, if it is one.
+    pub fn guardian_index(&self, guardian: &Pubkey) -> Option<usize> {
+        if *guardian == Pubkey::default() {
+            return None;
+        }
+        self.guardians.iter().position(|key| key == guardian)
+    }
+
+    /// Number of guardians.
+    pub fn guardian_count(&self) -> usize {
+        self.guardians
+            .iter()
+            .filter(|key| **key != Pubkey::default())
+            .count()
+    }
+
+    /// Whether `threshold` is usable with the current guardians.
+    pub fn is_valid_threshold(&self, threshold: u8) -> bool {
+        let count = self.guardian_count();
+        usize::from(threshold) <= count && (threshold > 0 || count == 0)
+    }
+
+    /// Number of guardians that approved the pending recovery.
+    pub fn approval_count(&self) -> u32 {
+        self.approvals.count_ones()
+    }
+
+    /// Drop any pending recovery and its approvals.
+    pub fn clear_pending(&mut self) {
+        self.pending_authority = Pubkey::default();
+        self.initiated_at_slot = 0;
+        self.approvals = 0;
+    }
+}
+
+// Adding a field to `RecoveryRecord` breaks this pattern, forcing `LEN` to be revisited.
+const _: fn(&RecoveryRecord) = |recovery| {
+    let RecoveryRecord {
+        discriminator: _,
+        version: _,
+        vault: _,
+        guardians: _,
+        threshold: _,
+        pending_authority: _,
+        initiated_at_slot: _,
+        approvals: _,
+        bump: _,
+        reserved: _,
+    } = recovery;
+};
+
+impl IsInitialized for RecoveryRecord {
+    /// Is initialized
+    fn is_initialized(&self) -> bool {
+        self.discriminator == Self::SPL_DISCRIMINATOR_SLICE && self.version == Self::CURRENT_VERSION
+    }
+}
+
+/// Beneficiary of a vault, held in a PDA derived from the vault. Once the authority
+/// has been inactive for `inactivity_slots`, the beneficiary can claim the authority
+/// role with the DART. The designation lapses if the authority changes.
+#[derive(
+    Clone, Debug, BorshSerialize, BorshDeserialize, BorshSchema, PartialEq, SplDiscriminate,
+)]
+#[discriminator_hash_input("account:InheritanceRecord")]
+pub struct InheritanceRecord {
+    /// Account type discriminator, `SPL_DISCRIMINATOR` once initialized
+    discriminator: [u8; ArrayDiscriminator::LENGTH],
+
+    /// Struct version, allows for upgrades to the program
+    pub version: u8,
+
+    /// The vault record the beneficiary inherits
+    pub vault: Pubkey,
+
+    /// The authority that designated the beneficiary
+    pub authority: Pubkey,
+
+    /// The beneficiary, or the default pubkey when none is designated
+    pub beneficiary: Pubkey,
+
+    /// Slots without activity after which the beneficiary may claim
+    pub inactivity_slots: u64,
+
+    /// Slot of the authority's last `SetBeneficiary` or `CheckIn`
+    pub last_activity_slot: u64,
+
+    /// PDA bump seed
+    pub bump: u8,
+
+    /// Zeroed padding; new fields are carved from here to avoid resizing accounts
+    reserved: [u8; INHERITANCE_RESERVED_LEN],
+}
+
+impl InheritanceRecord {
+    /// Version to fill in on new created accounts
+    pub const CURRENT_VERSION: u8 = 1;
+    /// Packed inheritance record space
+    pub const LEN: usize = ArrayDiscriminator::LENGTH
+        + size_of::<u8>()
+        + size_of::<Pubkey>()
+        + size_of::<Pubkey>()
+        + size_of::<Pubkey>()
+        + size_of::<u64>()
+        + size_of::<u64>()
+        + size_of::<u8>()
+        + INHERITANCE_RESERVED_LEN;
+
+    /// Create a current-version inheritance record with no beneficiary.
+    pub fn new(vault: Pubkey, bump: u8) -> Self {
+        let mut discriminator = [0; ArrayDiscriminator::LENGTH];
+        discriminator.copy_from_slice(Self::SPL_DISCRIMINATOR_SLICE);
+        Self {
+            discriminator,
+            version: Self::CURRENT_VERSION,
+            vault,
+            authority: Pubkey::default(),
+            beneficiary: Pubkey::default(),
+            inactivity_slots: 0,
+            last_activity_slot: 0,
+            bump,
+            reserved: [0; INHERITANCE_RESERVED_LEN],
+        }
+    }
+
+    /// Address and bump of the inheritance account for `vault`.
+    pub fn find_address(program_id: &Pubkey, vault: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[INHERITANCE_SEED, vault.as_ref()], program_id)
+    }
+
+    /// First slot at which the beneficiary may claim.
+    pub fn claimable_at_slot(&self) -> Result<u64, ProgramError> {
+        math::checked_add(self.last_activity_slot, self.inactivity_slots)
+    }
+}
+
+// Adding a field to `InheritanceRecord` breaks this pattern, forcing `LEN` to be revisited.
+const _: fn(&InheritanceRecord) = |inheritance| {
+    let InheritanceRecord {
+        discriminator: _,
+        version: _,
+        vault: _,
+        authority: _,
+        beneficiary: _,
+        inactivity_slots: _,
+        last_activity_slot: _,
+        bump: _,
+        reserved: _,
+    } = inheritance;
+};
+
+impl IsInitialized for InheritanceRecord {
+    /// Is initialized
+    fn is_initialized(&self) -> bool {
+        self.discriminator == Self::SPL_DISCRIMINATOR_SLICE && self.version == Self::CURRENT_VERSION
+    }
+}
+
+/// Compliance settings of a Token-2022 mint whose transfer hook is this program, held
+/// in a PDA derived from the mint. Transfers are refused while the linked vault is
+/// frozen, seized or closed, and, once any allowlist slot is set, to token accounts
+/// whose owner is not listed.
+#[derive(
+    Clone, Debug, BorshSerialize, BorshDeserialize, BorshSchema, PartialEq, SplDiscriminate,
+)]
+#[discriminator_hash_input("account:TransferHookConfig")]
+pub struct TransferHookConfig {
+    /// Account type discriminator, `SPL_DISCRIMINATOR` once initialized
+    discriminator: [u8; ArrayDiscriminator::LENGTH],
+
+    /// Struct version, allows for upgrades to the program
+    pub version: u8,
+
+    /// The Token-2022 mint whose transfers are checked
+    pub mint: Pubkey,
+
+    /// The vault record whose status gates transfers
+    pub vault: Pubkey,
+
+    /// Owners that may receive the token; unused slots hold the default pubkey
+    pub allowlist: [Pubkey; MAX_ALLOWLIST],
+
+    /// PDA bump seed
+    pub bump: u8,
+
+    /// Zeroed padding; new fields are carved from here to avoid resizing accounts
+    reserved: [u8; TRANSFER_HOOK_RESERVED_LEN],
+}
+
+impl TransferHookConfig {
+    /// Version to fill in on new created accounts
+    pub const CURRENT_VERSION: u8 = 1;
+    /// Packed transfer hook config space
+    pub const LEN: usize = ArrayDiscriminator::LENGTH
+        + size_of::<u8>()
+        + size_of::<Pubkey>()
+        + size_of::<Pubkey>()
+        + size_of::<Pubkey>() * MAX_ALLOWLIST
+        + size_of::<u8>()
+        + TRANSFER_HOOK_RESERVED_LEN;
+
+    /// Create a current-version transfer hook config with an empty allowlist.
+    pub fn new(mint: Pubkey, vault: Pubkey, bump: u8) -> Self {
+        let mut discriminator = [0; ArrayDiscriminator::LENGTH];
+        discriminator.copy_from_slice(Self::SPL_DISCRIMINATOR_SLICE);
+        Self {
+            discriminator,
+            version: Self::CURRENT_VERSION,
+            mint,
+            vault,
+            allowlist: [Pubkey::default(); MAX_ALLOWLIST],
+            bump,
+            reserved: [0; TRANSFER_HOOK_RESERVED_LEN],
+        }
+    }
+
+    /// Address and bump of the transfer hook config for `mint`.
+    pub fn find_address(program_id: &Pubkey, mint: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[TRANSFER_HOOK_SEED, mint.as_ref()], program_id)
+    }
+
+    /// Whether `owner` may receive the token: anyone while the allowlist is empty.
+    pub fn is_allowed(&self, owner: &Pubkey) -> bool {
+        let mut listed = self
+            .allowlist
+            .iter()
+            .filter(|entry| **entry != Pubkey::default())
+            .peekable();
+        listed.peek().is_none() || listed.any(|entry| entry == owner)
+    }
+}
+
+// Adding a field to `TransferHookConfig` breaks this pattern, forcing `LEN` to be revisited.
+const _: fn(&TransferHookConfig) = |config| {
+    let TransferHookConfig {
+        discriminator: _,
+        version: _,
+        mint: _,
+        vault: _,
+        allowlist: _,
+        bump: _,
+        reserved: _,
+    } = config;
+};
+
+impl IsInitialized for TransferHookConfig {
+    /// Is initialized
+    fn is_initialized(&self) -> bool {
+        self.discriminator == Self::SPL_DISCRIMINATOR_SLICE && self.version == Self::CURRENT_VERSION
+    }
+}
+
+/// Program-wide settings, held in a single PDA. Its admin is the program's upgrade
+/// authority when the config is initialized.
+#[derive(
+    Clone, Debug, BorshSerialize, BorshDeserialize, BorshSchema, PartialEq, SplDiscriminate,
+)]
+#[discriminator_hash_input("account:ProgramConfig")]
+pub struct ProgramConfig {
+    /// Account type discriminator, `SPL_DISCRIMINATOR` once initialized
+    discriminator: [u8; ArrayDiscriminator::LENGTH],
+
+    /// Struct version, allows for upgrades to the program
+    pub version: u8,
+
+    /// Key allowed to change the config
+    pub admin: Pubkey,
+
+    /// PDA bump seed
+    pub bump: u8,
+
+    /// Program the vault moved to once decommissioned; the default pubkey until then
+    pub successor: Pubkey,
+
+    /// Lamports `Crank` pays per swept account out of its reclaimed lamports, capped at
+    /// `MAX_CRANK_BOUNTY_BPS` of them; zero pays nothing
+    pub crank_bounty: u64,
+
+    /// Slots past expiry before `Crank` may sweep an account
+    pub crank_grace_slots: u64,
+
+    /// Zeroed padding; new fields are carved from here to avoid resizing accounts
+    reserved: [u8; CONFIG_RESERVED_LEN],
+}
+
+impl ProgramConfig {
+    /// Version to fill in on new created accounts
+    pub const CURRENT_VERSION: u8 = 1;
+    /// Packed program config space
+    pub const LEN: usize = ArrayDiscriminator::LENGTH
+        + size_of::<u8>()
+        + size_of::<Pubkey>()
+        + size_of::<u8>()
+        + size_of::<Pubkey>()
+        + size_of::<u64>()
+        + size_of::<u64>()
+        + CONFIG_RESERVED_LEN;
+
+    /// Create a current-version config administered by `admin`.
+    pub fn new(admin: Pubkey, bump: u8) -> Self {
+        let mut discriminator = [0; ArrayDiscriminator::LENGTH];
+        discriminator.copy_from_slice(Self::SPL_DISCRIMINATOR_SLICE);
+        Self {
+            discriminator,
+            version: Self::CURRENT_VERSION,
+            admin,
+            bump,
+            successor: Pubkey::default(),
+            crank_bounty: 0,
+            crank_grace_slots: 0,
+            reserved: [0; CONFIG_RESERVED_LEN],
+        }
+    }
+
+    /// Whether the program was decommissioned in favor of `successor`.
+    pub fn is_migrated(&self) -> bool {
+        self.successor != Pubkey::default()
+    }
+
+    /// Whether `Crank` may sweep, at `slot`, an account that expired after
+    /// `expires_at_slot`.
+    pub fn is_sweepable(&self, expires_at_slot: u64, slot: u64) -> bool {
+        slot > expires_at_slot.saturating_add(self.crank_grace_slots)
+    }
+
+    /// Bounty `Crank` pays out of the `lamports` it reclaims from a swept account.
+    pub fn crank_bounty_of(&self, lamports: u64) -> u64 {
+        let cap = u128::from(lamports) * u128::from(MAX_CRANK_BOUNTY_BPS) / 10_000;
+        // The cap is below `lamports`.
+        self.crank_bounty.min(cap as u64)
+    }
+
+    /// Address and bump of the program config.
+    pub fn find_address(program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[CONFIG_SEED], program_id)
+    }
+}
+
+// Adding a field to `ProgramConfig` breaks this pattern, forcing `LEN` to be revisited.
+const _: fn(&ProgramConfig) = |config| {
+    let ProgramConfig {
+        discriminator: _,
+        version: _,
+        admin: _,
+        bump: _,
+        successor: _,
+        crank_bounty: _,
+        crank_grace_slots: _,
+        reserved: _,
+    } = config;
+};
+
+impl IsInitialized for ProgramConfig {
+    /// Is initialized
+    fn is_initialized(&self) -> bool {
+        self.discriminator == Self::SPL_DISCRIMINATOR_SLICE && self.version == Self::CURRENT_VERSION
+    }
+}
+
+/// Program-wide fee settings, kept by the program config's admin beside the config.
+#[derive(
+    Clone, Debug, BorshSerialize, BorshDeserialize, BorshSchema, PartialEq, SplDiscriminate,
+)]
+#[discriminator_hash_input("account:FeeConfig")]
+pub struct FeeConfig {
+    /// Account type discriminator, `SPL_DISCRIMINATOR` once initialized
+    discriminator: [u8; ArrayDiscriminator::LENGTH],
+
+    /// Struct version, allows for upgrades to the program
+    pub version: u8,
+
+    /// DARTs and authorities never charged fees, e.g. internal and treasury accounts;
+    /// unused slots hold the default pubkey
+    pub exempt: [Pubkey; MAX_FEE_EXEMPT],
+
+    /// PDA bump seed
+    pub bump: u8,
+
+    /// Account `CollectFees` pays the collected fees to; the default pubkey until the
+    /// admin sets one
+    pub fee_destination: Pubkey,
+
+    /// Lamports charged on close for vaults matching no close fee tier
+    pub default_close_fee: u64,
+
+    /// Close fees by vault tag and age, the first active tier matching the vault
+    /// applying
+    pub close_fee_tiers: [CloseFeeTier; MAX_CLOSE_FEE_TIERS],
+
+    /// Zeroed padding; new fields are carved from here to avoid resizing accounts
+    reserved: [u8; FEE_CONFIG_RESERVED_LEN],
+}
+
+impl FeeConfig {
+    /// Version to fill in on new created accounts
+    pub const CURRENT_VERSION: u8 = 1;
+    /// Packed fee config space
+    pub const LEN: usize = ArrayDiscriminator::LENGTH
+        + size_of::<u8>()
+        + size_of::<Pubkey>() * MAX_FEE_EXEMPT
+        + size_of::<u8>()
+        + size_of::<Pubkey>()
+        + size_of::<u64>()
+        + CloseFeeTier::LEN * MAX_CLOSE_FEE_TIERS
+        + FEE_CONFIG_RESERVED_LEN;
+
+    /// Create a current-version fee config exempting no one, with no fee destination
+    /// and no close fee.
+    pub fn new(bump: u8) -> Self {
+        let mut discriminator = [0; ArrayDiscriminator::LENGTH];
+        discriminator.copy_from_slice(Self::SPL_DISCRIMINATOR_SLICE);
+        Self {
+            discriminator,
+            version: Self::CURRENT_VERSION,
+            exempt: [Pubkey::default(); MAX_FEE_EXEMPT],
+            bump,
+            fee_destination: Pubkey::default(),
+            default_close_fee: 0,
+            close_fee_tiers: [CloseFeeTier::default(); MAX_CLOSE_FEE_TIERS],
+            reserved: [0; FEE_CONFIG_RESERVED_LEN],
+        }
+    }
+
+    /// Address and bump of the fee config.
+    pub fn find_address(program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[FEE_CONFIG_SEED], program_id)
+    }
+
+    /// Whether fees on `record` are waived, its DART or its authority being listed.
+    pub fn is_exempt(&self, record: &VaultRecord) -> bool {
+        self.exempt
+            .iter()
+            .filter(|key| **key != Pubkey::default())
+            .any(|key| *key == record.dart || *key == record.authority)
+    }
+
+    /// Lamports charged to close `record` in `slot`: nothing when exempt, else the fee
+    /// of the first active tier matching its tag and age, or the record's own close fee
+    /// when set, or the default close fee.
+    pub fn close_fee(&self, record: &VaultRecord, slot: u64) -> u64 {
+        if self.is_exempt(record) {
+            return 0;
+        }
+        let default_close_fee = match record.close_fee {
+            0 => self.default_close_fee,
+            close_fee => close_fee,
+        };
+        let age = slot.saturating_sub(record.created_slot);
+        self.close_fee_tiers
+            .iter()
+            .find(|tier| tier.active && tier.tag == record.tag && age >= tier.min_age_slots)
+            .map_or(default_close_fee, |tier| tier.lamports)
+    }
+}
+
+// Adding a field to `FeeConfig` breaks this pattern, forcing `LEN` to be revisited.
+const _: fn(&FeeConfig) = |config| {
+    let FeeConfig {
+        discriminator: _,
+        version: _,
+        exempt: _,
+        bump: _,
+        fee_destination: _,
+        default_close_fee: _,
+        close_fee_tiers: _,
+        reserved: _,
+    } = config;
+};
+
+/// A close fee for the vaults of one tag, from a minimum age on. List a tag's tiers
+/// oldest first, as the first match applies.
+#[derive(
+    Clone, Copy, Debug, Default, BorshSerialize, BorshDeserialize, BorshSchema, PartialEq, Eq,
+)]
+pub struct CloseFeeTier {
+    /// Whether the tier applies; inactive tiers are skipped
+    pub active: bool,
+    /// Vault tag the tier applies to
+    pub tag: u16,
+    /// Lamports charged on close, possibly none
+    pub lamports: u64,
+    /// Slots since `VaultRecord::created_slot` the vault must be closed after, zero for
+    /// any age
+    pub min_age_slots: u64,
+}
+
+impl CloseFeeTier {
+    /// Packed tier space
+    pub const LEN: usize =
+        size_of::<bool>() + size_of::<u16>() + size_of::<u64>() + size_of::<u64>();
+}
+
+impl IsInitialized for FeeConfig {
+    /// Is initialized
+    fn is_initialized(&self) -> bool {
+        self.discriminator == Self::SPL_DISCRIMINATOR_SLICE && self.version == Self::CURRENT_VERSION
+    }
+}
+
+/// Program-wide invocation counters by instruction tag, kept beside the program config.
+/// Only successful instructions passed the stats account are counted: a failed one
+/// rolls back its writes, the counter's included.
+#[derive(
+    Clone, Debug, BorshSerialize, BorshDeserialize, BorshSchema, PartialEq, SplDiscriminate,
+)]
+#[discriminator_hash_input("account:ProgramStats")]
+pub struct ProgramStats {
+    /// Account type discriminator, `SPL_DISCRIMINATOR` once initialized
+    discriminator: [u8; ArrayDiscriminator::LENGTH],
+
+    /// Struct version, allows for upgrades to the program
+    pub version: u8,
+
+    /// PDA bump seed
+    pub bump: u8,
+
+    /// Successful invocations, indexed by native instruction tag
+    pub invocations: [u64; MAX_STATS_TAGS],
+
+    /// Zeroed padding; new fields are carved from here to avoid resizing accounts
+    reserved: [u8; STATS_RESERVED_LEN],
+}
+
+impl ProgramStats {
+    /// Version to fill in on new created accounts
+    pub const CURRENT_VERSION: u8 = 1;
+    /// Packed program stats space
+    pub const LEN: usize = ArrayDiscriminator::LENGTH
+        + size_of::<u8>()
+        + size_of::<u8>()
+        + size_of::<u64>() * MAX_STATS_TAGS
+        + STATS_RESERVED_LEN;
+    // Offset of `invocations` in the packed stats.
+    const INVOCATIONS_OFFSET: usize = ArrayDiscriminator::LENGTH + 2 * size_of::<u8>();
+
+    /// Create current-version stats with nothing counted.
+    pub fn new(bump: u8) -> Self {
+        let mut discriminator = [0; ArrayDiscriminator::LENGTH];
+        discriminator.copy_from_slice(Self::SPL_DISCRIMINATOR_SLICE);
+        Self {
+            discriminator,
+            version: Self::CURRENT_VERSION,
+            bump,
+            invocations: [0; MAX_STATS_TAGS],
+            reserved: [0; STATS_RESERVED_LEN],
+        }
+    }
+
+    /// Address and bump of the program stats.
+    pub fn find_address(program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[STATS_SEED], program_id)
+    }
+
+    /// Count an invocation of `tag` in the packed stats `data`, touching only its
+    /// counter rather than deserializing the account. Counters saturate.
+    pub fn count(data: &mut [u8], tag: u8) -> Result<(), ProgramError> {
+        let offset = Self::INVOCATIONS_OFFSET + size_of::<u64>() * usize::from(tag);
+        if data.len() != Self::LEN || usize::from(tag) >= MAX_STATS_TAGS {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let counter = &mut data[offset..offset + size_of::<u64>()];
+        // The slice is exactly eight bytes.
+        let count = u64::from_le_bytes((&*counter).try_into().unwrap());
+        counter.copy_from_slice(&count.saturating_add(1).to_le_bytes());
+        Ok(())
+    }
+}
+
+// Adding a field to `ProgramStats` breaks this pattern, forcing `LEN` and
+// `INVOCATIONS_OFFSET` to be revisited.
+const _: fn(&ProgramStats) = |stats| {
+    let ProgramStats {
+        discriminator: _,
+        version: _,
+        bump: _,
+        invocations: _,
+        reserved: _,
+    } = stats;
+};
+
+impl IsInitialized for ProgramStats {
+    /// Is initialized
+    fn is_initialized(&self) -> bool {
+        self.discriminator == Self::SPL_DISCRIMINATOR_SLICE && self.version == Self::CURRENT_VERSION
+    }
+}
+
+/// Merkle root of the transfers a DART approved in bulk, redeemed one by one with
+/// `TransferWithProof` (see `approval::transfer_leaf`).
+#[derive(
+    Clone, Debug, BorshSerialize, BorshDeserialize, BorshSchema, PartialEq, SplDiscriminate,
+)]
+#[discriminator_hash_input("account:ApprovalRoot")]
+pub struct ApprovalRoot {
+    /// Account type discriminator, `SPL_DISCRIMINATOR` once initialized
+    discriminator: [u8; ArrayDiscriminator::LENGTH],
+
+    /// Struct version, allows for upgrades to the program
+    pub version: u8,
+
+    /// The DART that approved the transfers
+    pub dart: Pubkey,
+
+    /// Merkle root of the approved transfers; zero when revoked
+    pub root: [u8; 32],
+
+    /// Last slot the approvals may be redeemed in
+    pub expires_at_slot: u64,
+
+    /// PDA bump seed
+    pub bump: u8,
+
+    /// Zeroed padding; new fields are carved from here to avoid resizing accounts
+    reserved: [u8; APPROVAL_ROOT_RESERVED_LEN],
+}
+
+impl ApprovalRoot {
+    /// Version to fill in on new created accounts
+    pub const CURRENT_VERSION: u8 = 1;
+    /// Packed approval root space
+    pub const LEN: usize = ArrayDiscriminator::LENGTH
+        + size_of::<u8>()
+        + size_of::<Pubkey>()
+        + 32
+        + size_of::<u64>()
+        + size_of::<u8>()
+        + APPROVAL_ROOT_RESERVED_LEN;
+
+    /// Create a current-version approval root of `dart`.
+    pub fn new(dart: Pubkey, root: [u8; 32], expires_at_slot: u64, bump: u8) -> Self {
+        let mut discriminator = [0; ArrayDiscriminator::LENGTH];
+        discriminator.copy_from_slice(Self::SPL_DISCRIMINATOR_SLICE);
+        Self {
+            discriminator,
+            version: Self::CURRENT_VERSION,
+            dart,
+            root,
+            expires_at_slot,
+            bump,
+            reserved: [0; APPROVAL_ROOT_RESERVED_LEN],
+        }
+    }
+
+    /// Whether the approvals may be redeemed at `slot`.
+    pub fn is_live(&self, slot: u64) -> bool {
+        self.root != [0; 32] && slot <= self.expires_at_slot
+    }
+
+    /// Address and bump of the approval root of `dart`.
+    pub fn find_address(program_id: &Pubkey, dart: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[APPROVAL_ROOT_SEED, dart.as_ref()], program_id)
+    }
+}
+
+// Adding a field to `ApprovalRoot` breaks this pattern, forcing `LEN` to be revisited.
+const _: fn(&ApprovalRoot) = |approval| {
+    let ApprovalRoot {
+        discriminator: _,
+        version: _,
+        dart: _,
+        root: _,
+        expires_at_slot: _,
+        bump: _,
+        reserved: _,
+    } = approval;
+};
+
+impl IsInitialized for ApprovalRoot {
+    /// Is initialized
+    fn is_initialized(&self) -> bool {
+        self.discriminator == Self::SPL_DISCRIMINATOR_SLICE && self.version == Self::CURRENT_VERSION
+    }
+}
+
+/// Confidential note the DART attaches to a vault, such as an internal account
+/// number, sealed to the authority's X25519 key so only the authority can read it.
+/// Sealing happens off chain; the program only stores the ciphertext.
+#[derive(
+    Clone, Debug, BorshSerialize, BorshDeserialize, BorshSchema, PartialEq, SplDiscriminate,
+)]
+#[discriminator_hash_input("account:NoteRecord")]
+pub struct NoteRecord {
+    /// Account type discriminator, `SPL_DISCRIMINATOR` once initialized
+    discriminator: [u8; ArrayDiscriminator::LENGTH],
+
+    /// Struct version, allows for upgrades to the program
+    pub version: u8,
+
+    /// The vault record the note is attached to
+    pub vault: Pubkey,
+
+    /// The authority that set `note_key`
+    pub authority: Pubkey,
+
+    /// X25519 public key the note is sealed to
+    pub note_key: [u8; 32],
+
+    /// The sealed note; zero when none
+    pub encrypted_note: [u8; ENCRYPTED_NOTE_LEN],
+
+    /// PDA bump seed
+    pub bump: u8,
+
+    /// Zeroed padding; new fields are carved from here to avoid resizing accounts
+    reserved: [u8; NOTE_RESERVED_LEN],
+}
+
+impl NoteRecord {
+    /// Version to fill in on new created accounts
+    pub const CURRENT_VERSION: u8 = 1;
+    /// Packed note record space
+    pub const LEN: usize = ArrayDiscriminator::LENGTH
+        + size_of::<u8>()
+        + size_of::<Pubkey>()
+        + size_of::<Pubkey>()
+        + 32
+        + ENCRYPTED_NOTE_LEN
+        + size_of::<u8>()
+        + NOTE_RESERVED_LEN;
+
+    /// Create a current-version note record with `authority`'s key and no note.
+    pub fn new(vault: Pubkey, authority: Pubkey, note_key: [u8; 32], bump: u8) -> Self {
+        let mut discriminator = [0; ArrayDiscriminator::LENGTH];
+        discriminator.copy_from_slice(Self::SPL_DISCRIMINATOR_SLICE);
+        Self {
+            discriminator,
+            version: Self::CURRENT_VERSION,
+            vault,
+            authority,
+            note_key,
+            encrypted_note: [0; ENCRYPTED_NOTE_LEN],
+            bump,
+            reserved: [0; NOTE_RESERVED_LEN],
+        }
+    }
+
+    /// Address and bump of the note account of `vault`.
+    pub fn find_address(program_id: &Pubkey, vault: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[NOTE_SEED, vault.as_ref()], program_id)
+    }
+}
+
+// Adding a field to `NoteRecord` breaks this pattern, forcing `LEN` to be revisited.
+const _: fn(&NoteRecord) = |note| {
+    let NoteRecord {
+        discriminator: _,
+        version: _,
+        vault: _,
+        authority: _,
+        note_key: _,
+        encrypted_note: _,
+        bump: _,
+        reserved: _,
+    } = note;
+};
+
+impl IsInitialized for NoteRecord {
+    /// Is initialized
+    fn is_initialized(&self) -> bool {
+        self.discriminator == Self::SPL_DISCRIMINATOR_SLICE && self.version == Self::CURRENT_VERSION
+    }
+}
+
+/// The key an authority registered to be notified of its vault's authority changes
+/// and close. It only applies while `authority` holds the vault, so a transfer
+/// notifies the registering authority's monitor once, and the new authority registers
+/// its own.
+#[derive(
+    Clone, Debug, BorshSerialize, BorshDeserialize, BorshSchema, PartialEq, SplDiscriminate,
+)]
+#[discriminator_hash_input("account:NotifyRecord")]
+pub struct NotifyRecord {
+    /// Account type discriminator, `SPL_DISCRIMINATOR` once initialized
+    discriminator: [u8; ArrayDiscriminator::LENGTH],
+
+    /// Struct version, allows for upgrades to the program
+    pub version: u8,
+
+    /// The vault record watched
+    pub vault: Pubkey,
+
+    /// The authority that registered `notify`
+    pub authority: Pubkey,
+
+    /// Key whose notification account is written; the default pubkey when cleared
+    pub notify: Pubkey,
+
+    /// PDA bump seed
+    pub bump: u8,
+
+    /// Zeroed padding; new fields are carved from here to avoid resizing accounts
+    reserved: [u8; NOTIFY_RESERVED_LEN],
+}
+
+impl NotifyRecord {
+    /// Version to fill in on new created accounts
+    pub const CURRENT_VERSION: u8 = 1;
+    /// Packed notify record space
+    pub const LEN: usize = ArrayDiscriminator::LENGTH
+        + size_of::<u8>()
+        + size_of::<Pubkey>()
+        + size_of::<Pubkey>()
+        + size_of::<Pubkey>()
+        + size_of::<u8>()
+        + NOTIFY_RESERVED_LEN;
+
+    /// Create a current-version notify record of `authority`.
+    pub fn new(vault: Pubkey, authority: Pubkey, notify: Pubkey, bump: u8) -> Self {
+        let mut discriminator = [0; ArrayDiscriminator::LENGTH];
+        discriminator.copy_from_slice(Self::SPL_DISCRIMINATOR_SLICE);
+        Self {
+            discriminator,
+            version: Self::CURRENT_VERSION,
+            vault,
+            authority,
+            notify,
+            bump,
+            reserved: [0; NOTIFY_RESERVED_LEN],
+        }
+    }
+
+    /// Address and bump of the notify record of `vault`.
+    pub fn find_address(program_id: &Pubkey, vault: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[NOTIFY_SEED, vault.as_ref()], program_id)
+    }
+
+    /// Whether vault changes by `authority` are notified.
+    pub fn applies_to(&self, authority: &Pubkey) -> bool {
+        self.notify != Pubkey::default() && self.authority == *authority
+    }
+}
+
+// Adding a field to `NotifyRecord` breaks this pattern, forcing `LEN` to be revisited.
+const _: fn(&NotifyRecord) = |record| {
+    let NotifyRecord {
+        discriminator: _,
+        version: _,
+        vault: _,
+        authority: _,
+        notify: _,
+        bump: _,
+        reserved: _,
+    } = record;
+};
+
+impl IsInitialized for NotifyRecord {
+    /// Is initialized
+    fn is_initialized(&self) -> bool {
+        self.discriminator == Self::SPL_DISCRIMINATOR_SLICE && self.version == Self::CURRENT_VERSION
+    }
+}
+
+/// What a notification reports.
+#[derive(
+    Clone, Copy, Debug, Default, BorshSerialize, BorshDeserialize, BorshSchema, PartialEq, Eq,
+)]
+pub enum NotificationKind {
+    /// Nothing notified yet
+    #[default]
+    None,
+    /// The vault's authority changed
+    Transfer,
+    /// The vault was closed
+    Close,
+}
+
+/// The latest vault change notified to a key, for monitoring programs and services
+/// to watch. Every vault registering the key writes here; `sequence` counts the
+/// notifications, so a watcher can tell when it missed one.
+#[derive(
+    Clone, Debug, BorshSerialize, BorshDeserialize, BorshSchema, PartialEq, SplDiscriminate,
+)]
+#[discriminator_hash_input("account:Notification")]
+pub struct Notification {
+    /// Account type discriminator, `SPL_DISCRIMINATOR` once initialized
+    discriminator: [u8; ArrayDiscriminator::LENGTH],
+
+    /// Struct version, allows for upgrades to the program
+    pub version: u8,
+
+    /// The key notified
+    pub notify: Pubkey,
+
+    /// Notifications written so far
+    pub sequence: u64,
+
+    /// What the latest notification reports
+    pub kind: NotificationKind,
+
+    /// The vault record changed
+    pub vault: Pubkey,
+
+    /// The authority before the change
+    pub previous_authority: Pubkey,
+
+    /// The authority after a transfer; the default pubkey on close
+    pub authority: Pubkey,
+
+    /// Slot of the change
+    pub slot: u64,
+
+    /// PDA bump seed
+    pub bump: u8,
+
+    /// Zeroed padding; new fields are carved from here to avoid resizing accounts
+    reserved: [u8; NOTIFICATION_RESERVED_LEN],
+}
+
+impl Notification {
+    /// Version to fill in on new created accounts
+    pub const CURRENT_VERSION: u8 = 1;
+    /// Packed notification space
+    pub const LEN: usize = ArrayDiscriminator::LENGTH
+        + size_of::<u8>()
+        + size_of::<Pubkey>()
+        + size_of::<u64>()
+        + size_of::<u8>()
+        + size_of::<Pubkey>()
+        + size_of::<Pubkey>()
+        + size_of::<Pubkey>()
+        + size_of::<u64>()
+        + size_of::<u8>()
+        + NOTIFICATION_RESERVED_LEN;
+
+    /// Create a current-version notification account of `notify`, with nothing
+    /// notified.
+    pub fn new(notify: Pubkey, bump: u8) -> Self {
+        let mut discriminator = [0; ArrayDiscriminator::LENGTH];
+        discriminator.copy_from_slice(Self::SPL_DISCRIMINATOR_SLICE);
+        Self {
+            discriminator,
+            version: Self::CURRENT_VERSION,
+            notify,
+            sequence: 0,
+            kind: NotificationKind::None,
+            vault: Pubkey::default(),
+            previous_authority: Pubkey::default(),
+            authority: Pubkey::default(),
+            slot: 0,
+            bump,
+            reserved: [0; NOTIFICATION_RESERVED_LEN],
+        }
+    }
+
+    /// Address and bump of the notification account of `notify`.
+    pub fn find_address(program_id: &Pubkey, notify: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[NOTIFICATION_SEED, notify.as_ref()], program_id)
+    }
+
+    /// Replace the latest notification, counting it.
+    pub fn record(
+        &mut self,
+        kind: NotificationKind,
+        vault: Pubkey,
+        previous_authority: Pubkey,
+        authority: Pubkey,
+        slot: u64,
+    ) {
+        self.sequence = self.sequence.saturating_add(1);
+        self.kind = kind;
+        self.vault = vault;
+        self.previous_authority = previous_authority;
+        self.authority = authority;
+        self.slot = slot;
+    }
+}
+
+// Adding a field to `Notification` breaks this pattern, forcing `LEN` to be revisited.
+const _: fn(&Notification) = |notification| {
+    let Notification {
+        discriminator: _,
+        version: _,
+        notify: _,
+        sequence: _,
+        kind: _,
+        vault: _,
+        previous_authority: _,
+        authority: _,
+        slot: _,
+        bump: _,
+        reserved: _,
+    } = notification;
+};
+
+impl IsInitialized for Notification {
+    /// Is initialized
+    fn is_initialized(&self) -> bool {
+        self.discriminator == Self::SPL_DISCRIMINATOR_SLICE && self.version == Self::CURRENT_VERSION
+    }
+}
+
+/// Commitments the DART publishes to sensitive metadata of a vault, such as the
+/// position size or client id, without revealing it. Each slot holds a hash
+/// commitment (see `client::Opening`) that the DART can open to an auditor off chain.
+#[derive(
+    Clone, Debug, BorshSerialize, BorshDeserialize, BorshSchema, PartialEq, SplDiscriminate,
+)]
+#[discriminator_hash_input("account:CommitmentRecord")]
+pub struct CommitmentRecord {
+    /// Account type discriminator, `SPL_DISCRIMINATOR` once initialized
+    discriminator: [u8; ArrayDiscriminator::LENGTH],
+
+    /// Struct version, allows for upgrades to the program
+    pub version: u8,
+
+    /// The vault record the commitments describe
+    pub vault: Pubkey,
+
+    /// Commitment slots; zero when unused
+    pub commitments: [[u8; 32]; MAX_COMMITMENTS],
+
+    /// Slot of the last update
+    pub updated_slot: u64,
+
+    /// PDA bump seed
+    pub bump: u8,
+
+    /// Zeroed padding; new fields are carved from here to avoid resizing accounts
+    reserved: [u8; COMMITMENT_RESERVED_LEN],
+}
+
+impl CommitmentRecord {
+    /// Version to fill in on new created accounts
+    pub const CURRENT_VERSION: u8 = 1;
+    /// Packed commitment record space
+    pub const LEN: usize = ArrayDiscriminator::LENGTH
+        + size_of::<u8>()
+        + size_of::<Pubkey>()
+        + 32 * MAX_COMMITMENTS
+        + size_of::<u64>()
+        + size_of::<u8>()
+        + COMMITMENT_RESERVED_LEN;
+
+    /// Create a current-version commitment record.
+    pub fn new(
+        vault: Pubkey,
+        commitments: [[u8; 32]; MAX_COMMITMENTS],
+        updated_slot: u64,
+        bump: u8,
+    ) -> Self {
+        let mut discriminator = [0; ArrayDiscriminator::LENGTH];
+        discriminator.copy_from_slice(Self::SPL_DISCRIMINATOR_SLICE);
+        Self {
+            discriminator,
+            version: Self::CURRENT_VERSION,
+            vault,
+            commitments,
+            updated_slot,
+            bump,
+            reserved: [0; COMMITMENT_RESERVED_LEN],
+        }
+    }
+
+    /// Address and bump of the commitment account of `vault`.
+    pub fn find_address(program_id: &Pubkey, vault: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[COMMITMENT_SEED, vault.as_ref()], program_id)
+    }
+}
+
+// Adding a field to `CommitmentRecord` breaks this pattern, forcing `LEN` to be revisited.
+const _: fn(&CommitmentRecord) = |record| {
+    let CommitmentRecord {
+        discriminator: _,
+        version: _,
+        vault: _,
+        commitments: _,
+        updated_slot: _,
+        bump: _,
+        reserved: _,
+    } = record;
+};
+
+impl IsInitialized for CommitmentRecord {
+    /// Is initialized
+    fn is_initialized(&self) -> bool {
+        self.discriminator == Self::SPL_DISCRIMINATOR_SLICE && self.version == Self::CURRENT_VERSION
+    }
+}
+
+/// An annotation of a corporate action affecting a vault's position.
+#[derive(
+    Clone, Copy, Debug, Default, BorshSerialize, BorshDeserialize, BorshSchema, PartialEq, Eq,
+)]
+pub struct LogNote {
+    /// Hash of the annotation document, kept off chain
+    pub hash: [u8; 32],
+    /// Kind of corporate action, assigned by the DART
+    pub category: u16,
+    /// Slot the note was added
+    pub slot: u64,
+}
+
+impl LogNote {
+    /// Packed note space
+    pub const LEN: usize = 32 + size_of::<u16>() + size_of::<u64>();
+}
+
+/// The latest `MAX_LOG_NOTES` notes the DART added to a vault with `AddNote`, in a ring:
+/// note `n` (counting from zero) is at `n % MAX_LOG_NOTES`. Evicted notes remain in the
+/// `NoteAdded` events.
+#[derive(
+    Clone, Debug, BorshSerialize, BorshDeserialize, BorshSchema, PartialEq, SplDiscriminate,
+)]
+#[discriminator_hash_input("account:NotesLog")]
+pub struct NotesLog {
+    /// Account type discriminator, `SPL_DISCRIMINATOR` once initialized
+    discriminator: [u8; ArrayDiscriminator::LENGTH],
+
+    /// Struct version, allows for upgrades to the program
+    pub version: u8,
+
+    /// The vault record the notes annotate
+    pub vault: Pubkey,
+
+    /// Notes ever added, the sequence number of the next one
+    pub count: u64,
+
+    /// Ring of the latest notes
+    pub notes: [LogNote; MAX_LOG_NOTES],
+
+    /// PDA bump seed
+    pub bump: u8,
+
+    /// Zeroed padding; new fields are carved from here to avoid resizing accounts
+    reserved: [u8; NOTES_LOG_RESERVED_LEN],
+}
+
+impl NotesLog {
+    /// Version to fill in on new created accounts
+    pub const CURRENT_VERSION: u8 = 1;
+    /// Packed notes log space
+    pub const LEN: usize = ArrayDiscriminator::LENGTH
+        + size_of::<u8>()
+        + size_of::<Pubkey>()
+        + size_of::<u64>()
+        + LogNote::LEN * MAX_LOG_NOTES
+        + size_of::<u8>()
+        + NOTES_LOG_RESERVED_LEN;
+
+    /// Create an empty current-version notes log.
+    pub fn new(vault: Pubkey, bump: u8) -> Self {
+        let mut discriminator = [0; ArrayDiscriminator::LENGTH];
+        discriminator.copy_from_slice(Self::SPL_DISCRIMINATOR_SLICE);
+        Self {
+            discriminator,
+            version: Self::CURRENT_VERSION,
+            vault,
+            count: 0,
+            notes: [LogNote::default(); MAX_LOG_NOTES],
+            bump,
+            reserved: [0; NOTES_LOG_RESERVED_LEN],
+        }
+    }
+
+    /// Address and bump of the notes log of `vault`.
+    pub fn find_address(program_id: &Pubkey, vault: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[NOTES_LOG_SEED, vault.as_ref()], program_id)
+    }
+
+    /// Append `note`, returning its sequence number and the note it evicted, if any.
+    pub fn push(&mut self, note: LogNote) -> Result<(u64, Option<LogNote>), ProgramError> {
+        let sequence = self.count;
+        let index = (sequence % MAX_LOG_NOTES as u64) as usize;
+        let evicted = (sequence >= MAX_LOG_NOTES as u64).then_some(self.notes[index]);
+        self.notes[index] = note;
+        self.count = math::checked_add(sequence, 1)?;
+        Ok((sequence, evicted))
+    }
+
+    /// The kept notes with their sequence numbers, oldest first.
+    pub fn latest(&self) -> impl Iterator<Item = (u64, &LogNote)> {
+        let first = self.count.saturating_sub(MAX_LOG_NOTES as u64);
+        (first..self.count).map(|sequence| {
+            (
+                sequence,
+                &self.notes[(sequence % MAX_LOG_NOTES as u64) as usize],
+            )
+        })
+    }
+}
+
+// Adding a field to `NotesLog` breaks this pattern, forcing `LEN` to be revisited.
+const _: fn(&NotesLog) = |log| {
+    let NotesLog {
+        discriminator: _,
+        version: _,
+        vault: _,
+        count: _,
+        notes: _,
+        bump: _,
+        reserved: _,
+    } = log;
+};
+
+impl IsInitialized for NotesLog {
+    /// Is initialized
+    fn is_initialized(&self) -> bool {
+        self.discriminator == Self::SPL_DISCRIMINATOR_SLICE && self.version == Self::CURRENT_VERSION
+    }
+}
+
+/// A claim the DART attests about a vault until `expires_at_slot`, such as a completed
+/// audit. A zero `hash` marks an unused entry.
+#[derive(
+    Clone, Copy, Debug, Default, BorshSerialize, BorshDeserialize, BorshSchema, PartialEq, Eq,
+)]
+pub struct Attestation {
+    /// Kind of attestation, assigned by the DART
+    pub kind: u16,
+    /// Hash of the attestation document, kept off chain
+    pub hash: [u8; 32],
+    /// Last slot the attestation holds
+    pub expires_at_slot: u64,
+}
+
+impl Attestation {
+    /// Packed attestation space
+    pub const LEN: usize = size_of::<u16>() + 32 + size_of::<u64>();
+
+    /// Whether the entry is in use and unexpired at `slot`.
+    pub fn is_live(&self, slot: u64) -> bool {
+        self.hash != [0; 32] && slot <= self.expires_at_slot
+    }
+}
+
+/// Up to `MAX_ATTESTATIONS` attestations the DART attaches to a vault with
+/// `AddAttestation`. Programs accepting the vault, for example as collateral, read it
+/// to require a fresh attestation of some kind (see `live_attestation`).
+#[derive(
+    Clone, Debug, BorshSerialize, BorshDeserialize, BorshSchema, PartialEq, SplDiscriminate,
+)]
+#[discriminator_hash_input("account:AttestationList")]
+pub struct AttestationList {
+    /// Account type discriminator, `SPL_DISCRIMINATOR` once initialized
+    discriminator: [u8; ArrayDiscriminator::LENGTH],
+
+    /// Struct version, allows for upgrades to the program
+    pub version: u8,
+
+    /// The vault record attested
+    pub vault: Pubkey,
+
+    /// Attestation entries
+    pub attestations: [Attestation; MAX_ATTESTATIONS],
+
+    /// PDA bump seed
+    pub bump: u8,
+
+    /// Zeroed padding; new fields are carved from here to avoid resizing accounts
+    reserved: [u8; ATTESTATIONS_RESERVED_LEN],
+}
+
+impl AttestationList {
+    /// Version to fill in on new created accounts
+    pub const CURRENT_VERSION: u8 = 1;
+    /// Packed attestation list space
+    pub const LEN: usize = ArrayDiscriminator::LENGTH
+        + size_of::<u8>()
+        + size_of::<Pubkey>()
+        + Attestation::LEN * MAX_ATTESTATIONS
+        + size_of::<u8>()
+        + ATTESTATIONS_RESERVED_LEN;
+
+    /// Create an empty current-version attestation list.
+    pub fn new(vault: Pubkey, bump: u8) -> Self {
+        let mut discriminator = [0; ArrayDiscriminator::LENGTH];
+        discriminator.copy_from_slice(Self::SPL_DISCRIMINATOR_SLICE);
+        Self {
+            discriminator,
+            version: Self::CURRENT_VERSION,
+            vault,
+            attestations: [Attestation::default(); MAX_ATTESTATIONS],
+            bump,
+            reserved: [0; ATTESTATIONS_RESERVED_LEN],
+        }
+    }
+
+    /// Address and bump of the attestation list of `vault`.
+    pub fn find_address(program_id: &Pubkey, vault: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[ATTESTATIONS_SEED, vault.as_ref()], program_id)
+    }
+
+    /// Record `attestation` at `slot`: renew the entry of the same kind and hash, or
+    /// take an unused or expired one.
+    pub fn add(&mut self, attestation: Attestation, slot: u64) -> Result<(), ProgramError> {
+        let entry = self
+            .attestations
+            .iter()
+            .position(|entry| entry.kind == attestation.kind && entry.hash == attestation.hash)
+            .or_else(|| {
+                self.attestations
+                    .iter()
+                    .position(|entry| !entry.is_live(slot))
+            })
+            .ok_or_else(|| {
+                msg!("Attestation list is full");
+                VaultError::AttestationListFull
+            })?;
+        self.attestations[entry] = attestation;
+        Ok(())
+    }
+
+    /// Clear the entry of `kind` and `hash`.
+    pub fn remove(&mut self, kind: u16, hash: &[u8; 32]) -> Result<(), ProgramError> {
+        let entry = self
+            .attestations
+            .iter_mut()
+            .find(|entry| entry.kind == kind && entry.hash == *hash && *hash != [0; 32])
+            .ok_or(VaultError::AttestationNotFound)?;
+        *entry = Attestation::default();
+        Ok(())
+    }
+
+    /// The live attestation of `kind` at `slot` expiring last, if any.
+    pub fn live_attestation(&self, kind: u16, slot: u64) -> Option<&Attestation> {
+        self.attestations
+            .iter()
+            .filter(|entry| entry.kind == kind && entry.is_live(slot))
+            .max_by_key(|entry| entry.expires_at_slot)
+    }
+}
+
+// Adding a field to `AttestationList` breaks this pattern, forcing `LEN` to be revisited.
+const _: fn(&AttestationList) = |list| {
+    let AttestationList {
+        discriminator: _,
+        version: _,
+        vault: _,
+        attestations: _,
+        bump: _,
+        reserved: _,
+    } = list;
+};
+
+impl IsInitialized for AttestationList {
+    /// Is initialized
+    fn is_initialized(&self) -> bool {
+        self.discriminator == Self::SPL_DISCRIMINATOR_SLICE && self.version == Self::CURRENT_VERSION
+    }
+}
+
+/// The lender a vault is pledged to as collateral. While `VaultRecord::pledged` is set,
+/// the authority only changes with the lender's signature.
+#[derive(
+    Clone, Debug, BorshSerialize, BorshDeserialize, BorshSchema, PartialEq, SplDiscriminate,
+)]
+#[discriminator_hash_input("account:PledgeRecord")]
+pub struct PledgeRecord {
+    /// Account type discriminator, `SPL_DISCRIMINATOR` once initialized
+    discriminator: [u8; ArrayDiscriminator::LENGTH],
+
+    /// Struct version, allows for upgrades to the program
+    pub version: u8,
+
+    /// The pledged vault record
+    pub vault: Pubkey,
+
+    /// The lender; the default pubkey once released
+    pub lender: Pubkey,
+
+    /// PDA bump seed
+    pub bump: u8,
+
+    /// Zeroed padding; new fields are carved from here to avoid resizing accounts
+    reserved: [u8; PLEDGE_RESERVED_LEN],
+}
+
+impl PledgeRecord {
+    /// Version to fill in on new created accounts
+    pub const CURRENT_VERSION: u8 = 1;
+    /// Packed pledge record space
+    pub const LEN: usize = ArrayDiscriminator::LENGTH
+        + size_of::<u8>()
+        + size_of::<Pubkey>()
+        + size_of::<Pubkey>()
+        + size_of::<u8>()
+        + PLEDGE_RESERVED_LEN;
+
+    /// Create a current-version pledge record.
+    pub fn new(vault: Pubkey, lender: Pubkey, bump: u8) -> Self {
+        let mut discriminator = [0; ArrayDiscriminator::LENGTH];
+        discriminator.copy_from_slice(Self::SPL_DISCRIMINATOR_SLICE);
+        Self {
+            discriminator,
+            version: Self::CURRENT_VERSION,
+            vault,
+            lender,
+            bump,
+            reserved: [0; PLEDGE_RESERVED_LEN],
+        }
+    }
+
+    /// Address and bump of the pledge account of `vault`.
+    pub fn find_address(program_id: &Pubkey, vault: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[PLEDGE_SEED, vault.as_ref()], program_id)
+    }
+}
+
+// Adding a field to `PledgeRecord` breaks this pattern, forcing `LEN` to be revisited.
+const _: fn(&PledgeRecord) = |pledge| {
+    let PledgeRecord {
+        discriminator: _,
+        version: _,
+        vault: _,
+        lender: _,
+        bump: _,
+        reserved: _,
+    } = pledge;
+};
+
+impl IsInitialized for PledgeRecord {
+    /// Is initialized
+    fn is_initialized(&self) -> bool {
+        self.discriminator == Self::SPL_DISCRIMINATOR_SLICE && self.version == Self::CURRENT_VERSION
+    }
+}
+
+/// The security a vault holds and how much of it, as booked by the DART.
+#[derive(
+    Clone, Debug, BorshSerialize, BorshDeserialize, BorshSchema, PartialEq, SplDiscriminate,
+)]
+#[discriminator_hash_input("account:PositionRecord")]
+pub struct PositionRecord {
+    /// Account type discriminator, `SPL_DISCRIMINATOR` once initialized
+    discriminator: [u8; ArrayDiscriminator::LENGTH],
+
+    /// Struct version, allows for upgrades to the program
+    pub version: u8,
+
+    /// The vault record holding the position
+    pub vault: Pubkey,
+
+    /// Identifier of the security, assigned by the DART
+    pub asset_id: [u8; 32],
+
+    /// Units of the security held
+    pub balance: u64,
+
+    /// Slot of the last update
+    pub updated_slot: u64,
+
+    /// PDA bump seed
+    pub bump: u8,
+
+    /// Zeroed padding; new fields are carved from here to avoid resizing accounts
+    reserved: [u8; POSITION_RESERVED_LEN],
+}
+
+impl PositionRecord {
+    /// Version to fill in on new created accounts
+    pub const CURRENT_VERSION: u8 = 1;
+    /// Packed position record space
+    pub const LEN: usize = ArrayDiscriminator::LENGTH
+        + size_of::<u8>()
+        + size_of::<Pubkey>()
+        + 32
+        + size_of::<u64>()
+        + size_of::<u64>()
+        + size_of::<u8>()
+        + POSITION_RESERVED_LEN;
+
+    /// Create a current-version position record.
+    pub fn new(
+        vault: Pubkey,
+        asset_id: [u8; 32],
+        balance: u64,
+        updated_slot: u64,
+        bump: u8,
+    ) -> Self {
+        let mut discriminator = [0; ArrayDiscriminator::LENGTH];
+        discriminator.copy_from_slice(Self::SPL_DISCRIMINATOR_SLICE);
+        Self {
+            discriminator,
+            version: Self::CURRENT_VERSION,
+            vault,
+            asset_id,
+            balance,
+            updated_slot,
+            bump,
+            reserved: [0; POSITION_RESERVED_LEN],
+        }
+    }
+
+    /// Address and bump of the position account of `vault`.
+    pub fn find_address(program_id: &Pubkey, vault: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[POSITION_SEED, vault.as_ref()], program_id)
+    }
+
+    /// Apply a corporate action to the position: scale the balance by `ratio` for a
+    /// split or reverse split, rounding down, or move it to a new asset id.
+    pub fn apply(&mut self, action: &CorporateActionType, ratio: u64) -> Result<(), ProgramError> {
+        if ratio == 0 {
+            msg!("Corporate action ratio must be non-zero");
+            return Err(ProgramError::InvalidArgument);
+        }
+        match action {
+            CorporateActionType::Split => self.balance = math::checked_mul(self.balance, ratio)?,
+            CorporateActionType::ReverseSplit => self.balance /= ratio,
+            CorporateActionType::SymbolChange { asset_id } => {
+                if ratio != 1 {
+                    msg!("A symbol change keeps the balance; its ratio must be 1");
+                    return Err(ProgramError::InvalidArgument);
+                }
+                self.asset_id = *asset_id;
+            }
+        }
+        Ok(())
+    }
+}
+
+// Adding a field to `PositionRecord` breaks this pattern, forcing `LEN` to be revisited.
+const _: fn(&PositionRecord) = |position| {
+    let PositionRecord {
+        discriminator: _,
+        version: _,
+        vault: _,
+        asset_id: _,
+        balance: _,
+        updated_slot: _,
+        bump: _,
+        reserved: _,
+    } = position;
+};
+
+impl IsInitialized for PositionRecord {
+    /// Is initialized
+    fn is_initialized(&self) -> bool {
+        self.discriminator == Self::SPL_DISCRIMINATOR_SLICE && self.version == Self::CURRENT_VERSION
+    }
+}
+
+/// A corporate action the DART applies to positions with `ApplyCorporateAction`.
+#[derive(Clone, Copy, Debug, BorshSerialize, BorshDeserialize, BorshSchema, PartialEq, Eq)]
+pub enum CorporateActionType {
+    /// Multiply balances by the ratio
+    Split,
+    /// Divide balances by the ratio, rounding down
+    ReverseSplit,
+    /// Move positions to a new asset id, keeping balances
+    SymbolChange {
+        /// The new asset id
+        asset_id: [u8; 32],
+    },
+}
+
+/// Progress of a dividend or coupon distribution, paid by `Distribute` in batches of
+/// vaults taken in ascending address order, so each vault is paid once.
+#[derive(
+    Clone, Debug, BorshSerialize, BorshDeserialize, BorshSchema, PartialEq, SplDiscriminate,
+)]
+#[discriminator_hash_input("account:DistributionCursor")]
+pub struct DistributionCursor {
+    /// Account type discriminator, `SPL_DISCRIMINATOR` once initialized
+    discriminator: [u8; ArrayDiscriminator::LENGTH],
+
+    /// Struct version, allows for upgrades to the program
+    pub version: u8,
+
+    /// The DART paying the distribution
+    pub dart: Pubkey,
+
+    /// Identifier of the distribution, assigned by the DART
+    pub id: u64,
+
+    /// Asset id of the positions entitled to the distribution
+    pub asset_id: [u8; 32],
+
+    /// Mint of the tokens paid
+    pub mint: Pubkey,
+
+    /// Tokens paid per unit of position balance
+    pub amount_per_unit: u64,
+
+    /// The last vault paid; the default pubkey before the first batch
+    pub last_vault: Pubkey,
+
+    /// Vaults paid so far
+    pub paid_vaults: u64,
+
+    /// Tokens paid so far
+    pub total_paid: u64,
+
+    /// PDA bump seed
+    pub bump: u8,
+
+    /// Zeroed padding; new fields are carved from here to avoid resizing accounts
+    reserved: [u8; DISTRIBUTION_RESERVED_LEN],
+}
+
+impl DistributionCursor {
+    /// Version to fill in on new created accounts
+    pub const CURRENT_VERSION: u8 = 1;
+    /// Packed distribution cursor space
+    pub const LEN: usize = ArrayDiscriminator::LENGTH
+        + size_of::<u8>()
+        + size_of::<Pubkey>()
+        + size_of::<u64>()
+        + 32
+        + size_of::<Pubkey>()
+        + size_of::<u64>()
+        + size_of::<Pubkey>()
+        + size_of::<u64>()
+        + size_of::<u64>()
+        + size_of::<u8>()
+        + DISTRIBUTION_RESERVED_LEN;
+
+    /// Create a current-version distribution cursor, before the first batch.
+    pub fn new(
+        dart: Pubkey,
+        id: u64,
+        asset_id: [u8; 32],
+        mint: Pubkey,
+        amount_per_unit: u64,
+        bump: u8,
+    ) -> Self {
+        let mut discriminator = [0; ArrayDiscriminator::LENGTH];
+        discriminator.copy_from_slice(Self::SPL_DISCRIMINATOR_SLICE);
+        Self {
+            discriminator,
+            version: Self::CURRENT_VERSION,
+            dart,
+            id,
+            asset_id,
+            mint,
+            amount_per_unit,
+            last_vault: Pubkey::default(),
+            paid_vaults: 0,
+            total_paid: 0,
+            bump,
+            reserved: [0; DISTRIBUTION_RESERVED_LEN],
+        }
+    }
+
+    /// Address and bump of the cursor of distribution `id` by `dart`.
+    pub fn find_address(program_id: &Pubkey, dart: &Pubkey, id: u64) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[DISTRIBUTION_SEED, dart.as_ref(), &id.to_le_bytes()],
+            program_id,
+        )
+    }
+
+    /// Record the payment of `amount` to `vault`, which must come after the last vault
+    /// paid.
+    pub fn advance(&mut self, vault: Pubkey, amount: u64) -> Result<(), ProgramError> {
+        if vault <= self.last_vault {
+            msg!("Vault {} is not after {}", vault, self.last_vault);
+            return Err(VaultError::DistributionOutOfOrder.into());
+        }
+        self.last_vault = vault;
+        self.paid_vaults = math::checked_add(self.paid_vaults, 1)?;
+        self.total_paid = math::checked_add(self.total_paid, amount)?;
+        Ok(())
+    }
+}
+
+// Adding a field to `DistributionCursor` breaks this pattern, forcing `LEN` to be revisited.
+const _: fn(&DistributionCursor) = |cursor| {
+    let DistributionCursor {
+        discriminator: _,
+        version: _,
+        dart: _,
+        id: _,
+        asset_id: _,
+        mint: _,
+        amount_per_unit: _,
+        last_vault: _,
+        paid_vaults: _,
+        total_paid: _,
+        bump: _,
+        reserved: _,
+    } = cursor;
+};
+
+impl IsInitialized for DistributionCursor {
+    /// Is initialized
+    fn is_initialized(&self) -> bool {
+        self.discriminator == Self::SPL_DISCRIMINATOR_SLICE && self.version == Self::CURRENT_VERSION
+    }
+}
+
+/// A vault's authority and position as of a record date, written once by `Snapshot` so
+/// entitlements (dividends, votes) are computed against fixed state.
+#[derive(
+    Clone, Debug, BorshSerialize, BorshDeserialize, BorshSchema, PartialEq, SplDiscriminate,
+)]
+#[discriminator_hash_input("account:SnapshotRecord")]
+pub struct SnapshotRecord {
+    /// Account type discriminator, `SPL_DISCRIMINATOR` once initialized
+    discriminator: [u8; ArrayDiscriminator::LENGTH],
+
+    /// Struct version, allows for upgrades to the program
+    pub version: u8,
+
+    /// The vault record
+    pub vault: Pubkey,
+
+    /// The record date
+    pub record_date_slot: u64,
+
+    /// Slot the snapshot was taken, at or after the record date
+    pub taken_slot: u64,
+
+    /// The vault authority
+    pub authority: Pubkey,
+
+    /// Asset id of the position
+    pub asset_id: [u8; 32],
+
+    /// Balance of the position
+    pub balance: u64,
+
+    /// PDA bump seed
+    pub bump: u8,
+
+    /// Zeroed padding; new fields are carved from here to avoid resizing accounts
+    reserved: [u8; SNAPSHOT_RESERVED_LEN],
+}
+
+impl SnapshotRecord {
+    /// Version to fill in on new created accounts
+    pub const CURRENT_VERSION: u8 = 1;
+    /// Packed snapshot space
+    pub const LEN: usize = ArrayDiscriminator::LENGTH
+        + size_of::<u8>()
+        + size_of::<Pubkey>()
+        + size_of::<u64>()
+        + size_of::<u64>()
+        + size_of::<Pubkey>()
+        + 32
+        + size_of::<u64>()
+        + size_of::<u8>()
+        + SNAPSHOT_RESERVED_LEN;
+
+    /// Create a current-version snapshot of `record` and `position`.
+    pub fn new(
+        vault: Pubkey,
+        record_date_slot: u64,
+        taken_slot: u64,
+        record: &VaultRecord,
+        position: &PositionRecord,
+        bump: u8,
+    ) -> Self {
+        let mut discriminator = [0; ArrayDiscriminator::LENGTH];
+        discriminator.copy_from_slice(Self::SPL_DISCRIMINATOR_SLICE);
+        Self {
+            discriminator,
+            version: Self::CURRENT_VERSION,
+            vault,
+            record_date_slot,
+            taken_slot,
+            authority: record.authority,
+            asset_id: position.asset_id,
+            balance: position.balance,
+            bump,
+            reserved: [0; SNAPSHOT_RESERVED_LEN],
+        }
+    }
+
+    /// Address and bump of the snapshot of `vault` for `record_date_slot`.
+    pub fn find_address(
+        program_id: &Pubkey,
+        vault: &Pubkey,
+        record_date_slot: u64,
+    ) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[
+                SNAPSHOT_SEED,
+                vault.as_ref(),
+                &record_date_slot.to_le_bytes(),
+            ],
+            program_id,
+        )
+    }
+}
+
+// Adding a field to `SnapshotRecord` breaks this pattern, forcing `LEN` to be revisited.
+const _: fn(&SnapshotRecord) = |snapshot| {
+    let SnapshotRecord {
+        discriminator: _,
+        version: _,
+        vault: _,
+        record_date_slot: _,
+        taken_slot: _,
+        authority: _,
+        asset_id: _,
+        balance: _,
+        bump: _,
+        reserved: _,
+    } = snapshot;
+};
+
+impl IsInitialized for SnapshotRecord {
+    /// Is initialized
+    fn is_initialized(&self) -> bool {
+        self.discriminator == Self::SPL_DISCRIMINATOR_SLICE && self.version == Self::CURRENT_VERSION
+    }
+}
+
+/// Who may vote the securities custodied in a vault, as delegated by its authority with
+/// `DelegateVoting`. The delegation lapses if the authority changes.
+#[derive(
+    Clone, Debug, BorshSerialize, BorshDeserialize, BorshSchema, PartialEq, SplDiscriminate,
+)]
+#[discriminator_hash_input("account:VotingProxy")]
+pub struct VotingProxy {
+    /// Account type discriminator, `SPL_DISCRIMINATOR` once initialized
+    discriminator: [u8; ArrayDiscriminator::LENGTH],
+
+    /// Struct version, allows for upgrades to the program
+    pub version: u8,
+
+    /// The vault record
+    pub vault: Pubkey,
+
+    /// The authority that delegated its votes
+    pub authority: Pubkey,
+
+    /// The proxy, or the default pubkey when the delegation is revoked
+    pub proxy: Pubkey,
+
+    /// PDA bump seed
+    pub bump: u8,
+
+    /// Zeroed padding; new fields are carved from here to avoid resizing accounts
+    reserved: [u8; VOTING_PROXY_RESERVED_LEN],
+}
+
+impl VotingProxy {
+    /// Version to fill in on new created accounts
+    pub const CURRENT_VERSION: u8 = 1;
+    /// Packed voting proxy space
+    pub const LEN: usize = ArrayDiscriminator::LENGTH
+        + size_of::<u8>()
+        + size_of::<Pubkey>()
+        + size_of::<Pubkey>()
+        + size_of::<Pubkey>()
+        + size_of::<u8>()
+        + VOTING_PROXY_RESERVED_LEN;
+
+    /// Create a current-version voting proxy.
+    pub fn new(vault: Pubkey, authority: Pubkey, proxy: Pubkey, bump: u8) -> Self {
+        let mut discriminator = [0; ArrayDiscriminator::LENGTH];
+        discriminator.copy_from_slice(Self::SPL_DISCRIMINATOR_SLICE);
+        Self {
+            discriminator,
+            version: Self::CURRENT_VERSION,
+            vault,
+            authority,
+            proxy,
+            bump,
+            reserved: [0; VOTING_PROXY_RESERVED_LEN],
+        }
+    }
+
+    /// Address and bump of the voting proxy account of `vault`.
+    pub fn find_address(program_id: &Pubkey, vault: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[VOTING_PROXY_SEED, vault.as_ref()], program_id)
+    }
+
+    /// Who votes the vault of `record`: the proxy while the authority that delegated
+    /// holds the vault, otherwise the authority itself.
+    pub fn voter(&self, record: &VaultRecord) -> Pubkey {
+        if self.authority == record.authority && self.proxy != Pubkey::default() {
+            self.proxy
+        } else {
+            record.authority
+        }
+    }
+}
+
+// Adding a field to `VotingProxy` breaks this pattern, forcing `LEN` to be revisited.
+const _: fn(&VotingProxy) = |proxy| {
+    let VotingProxy {
+        discriminator: _,
+        version: _,
+        vault: _,
+        authority: _,
+        proxy: _,
+        bump: _,
+        reserved: _,
+    } = proxy;
+};
+
+impl IsInitialized for VotingProxy {
+    /// Is initialized
+    fn is_initialized(&self) -> bool {
+        self.discriminator == Self::SPL_DISCRIMINATOR_SLICE && self.version == Self::CURRENT_VERSION
+    }
+}
+
+/// Slots `[start_slot, end_slot)` transfers are refused in. An empty range, such as the
+/// default, marks an unused entry.
+#[derive(
+    Clone, Copy, Debug, Default, BorshSerialize, BorshDeserialize, BorshSchema, PartialEq, Eq,
+)]
+pub struct BlackoutRange {
+    /// First slot of the blackout
+    pub start_slot: u64,
+    /// First slot after the blackout
+    pub end_slot: u64,
+}
+
+impl BlackoutRange {
+    /// Packed blackout range space
+    pub const LEN: usize = size_of::<u64>() + size_of::<u64>();
+
+    /// Whether `slot` falls in the blackout.
+    pub fn contains(&self, slot: u64) -> bool {
+        (self.start_slot..self.end_slot).contains(&slot)
+    }
+}
+
+/// When the authority of a vault may change hands, set by the DART for a single vault
+/// or, keyed by the DART itself, for all of its vaults. `TransferAuthority` fails with
+/// `TransferWindowClosed` outside the window.
+#[derive(
+    Clone, Debug, BorshSerialize, BorshDeserialize, BorshSchema, PartialEq, SplDiscriminate,
+)]
+#[discriminator_hash_input("account:TransferWindow")]
+pub struct TransferWindow {
+    /// Account type discriminator, `SPL_DISCRIMINATOR` once initialized
+    discriminator: [u8; ArrayDiscriminator::LENGTH],
+
+    /// Struct version, allows for upgrades to the program
+    pub version: u8,
+
+    /// The vault record, or the DART for the window of all its vaults
+    pub scope: Pubkey,
+
+    /// First slot transfers are allowed in; zero when not locked up
+    pub lockup_until_slot: u64,
+
+    /// Slot ranges transfers are refused in
+    pub blackouts: [BlackoutRange; MAX_BLACKOUTS],
+
+    /// PDA bump seed
+    pub bump: u8,
+
+    /// Zeroed padding; new fields are carved from here to avoid resizing accounts
+    reserved: [u8; TRANSFER_WINDOW_RESERVED_LEN],
+}
+
+impl TransferWindow {
+    /// Version to fill in on new created accounts
+    pub const CURRENT_VERSION: u8 = 1;
+    /// Packed transfer window space
+    pub const LEN: usize = ArrayDiscriminator::LENGTH
+        + size_of::<u8>()
+        + size_of::<Pubkey>()
+        + size_of::<u64>()
+        + BlackoutRange::LEN * MAX_BLACKOUTS
+        + size_of::<u8>()
+        + TRANSFER_WINDOW_RESERVED_LEN;
+
+    /// Create a current-version transfer window.
+    pub fn new(
+        scope: Pubkey,
+        lockup_until_slot: u64,
+        blackouts: [BlackoutRange; MAX_BLACKOUTS],
+        bump: u8,
+    ) -> Self {
+        let mut discriminator = [0; ArrayDiscriminator::LENGTH];
+        discriminator.copy_from_slice(Self::SPL_DISCRIMINATOR_SLICE);
+        Self {
+            discriminator,
+            version: Self::CURRENT_VERSION,
+            scope,
+            lockup_until_slot,
+            blackouts,
+            bump,
+            reserved: [0; TRANSFER_WINDOW_RESERVED_LEN],
+        }
+    }
+
+    /// Address and bump of the transfer window of `scope`, a vault or a DART as `kind`
+    /// (`TRANSFER_WINDOW_VAULT` or `TRANSFER_WINDOW_DART`) tells. The kind keeps the
+    /// holder of a vault keypair from signing as a DART for the vault's own window.
+    pub fn find_address(program_id: &Pubkey, kind: &[u8], scope: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[TRANSFER_WINDOW_SEED, kind, scope.as_ref()], program_id)
+    }
+
+    /// Whether transfers are allowed at `slot`: past the lockup and outside every
+    /// blackout.
+    pub fn is_open(&self, slot: u64) -> bool {
+        slot >= self.lockup_until_slot && !self.blackouts.iter().any(|b| b.contains(slot))
+    }
+
+    /// Whether the window ever refuses a transfer.
+    pub fn is_restricted(&self) -> bool {
+        self.lockup_until_slot > 0 || self.blackouts.iter().any(|b| b.start_slot < b.end_slot)
+    }
+}
+
+// Adding a field to `TransferWindow` breaks this pattern, forcing `LEN` to be revisited.
+const _: fn(&TransferWindow) = |window| {
+    let TransferWindow {
+        discriminator: _,
+        version: _,
+        scope: _,
+        lockup_until_slot: _,
+        blackouts: _,
+        bump: _,
+        reserved: _,
+    } = window;
+};
+
+impl IsInitialized for TransferWindow {
+    /// Is initialized
+    fn is_initialized(&self) -> bool {
+        self.discriminator == Self::SPL_DISCRIMINATOR_SLICE && self.version == Self::CURRENT_VERSION
+    }
+}
+
+/// How many instructions acting on a vault it takes per epoch (see
+/// `VaultInstruction::is_rate_limited`), as a guardrail against runaway automation with
+/// the DART's key. Excess mutations fail with `RateLimited`.
+#[derive(
+    Clone, Debug, BorshSerialize, BorshDeserialize, BorshSchema, PartialEq, SplDiscriminate,
+)]
+#[discriminator_hash_input("account:RateLimit")]
+pub struct RateLimit {
+    /// Account type discriminator, `SPL_DISCRIMINATOR` once initialized
+    discriminator: [u8; ArrayDiscriminator::LENGTH],
+
+    /// Struct version, allows for upgrades to the program
+    pub version: u8,
+
+    /// The vault record
+    pub vault: Pubkey,
+
+    /// Mutations allowed per epoch; zero once the limit is lifted
+    pub max_per_epoch: u32,
+
+    /// Epoch `count` was taken in
+    pub epoch: u64,
+
+    /// Mutations so far in `epoch`
+    pub count: u32,
+
+    /// PDA bump seed
+    pub bump: u8,
+
+    /// Zeroed padding; new fields are carved from here to avoid resizing accounts
+    reserved: [u8; RATE_LIMIT_RESERVED_LEN],
+}
+
+impl RateLimit {
+    /// Version to fill in on new created accounts
+    pub const CURRENT_VERSION: u8 = 1;
+    /// Packed rate limit space
+    pub const LEN: usize = ArrayDiscriminator::LENGTH
+        + size_of::<u8>()
+        + size_of::<Pubkey>()
+        + size_of::<u32>()
+        + size_of::<u64>()
+        + size_of::<u32>()
+        + size_of::<u8>()
+        + RATE_LIMIT_RESERVED_LEN;
+
+    /// Create a current-version rate limit with no mutations counted.
+    pub fn new(vault: Pubkey, max_per_epoch: u32, bump: u8) -> Self {
+        let mut discriminator = [0; ArrayDiscriminator::LENGTH];
+        discriminator.copy_from_slice(Self::SPL_DISCRIMINATOR_SLICE);
+        Self {
+            discriminator,
+            version: Self::CURRENT_VERSION,
+            vault,
+            max_per_epoch,
+            epoch: 0,
+            count: 0,
+            bump,
+            reserved: [0; RATE_LIMIT_RESERVED_LEN],
+        }
+    }
+
+    /// Address and bump of the rate limit account of `vault`.
+    pub fn find_address(program_id: &Pubkey, vault: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[RATE_LIMIT_SEED, vault.as_ref()], program_id)
+    }
+
+    /// Count a mutation in `epoch`, restarting the count in a new epoch, unless the
+    /// epoch's allowance is used up.
+    pub fn consume(&mut self, epoch: u64) -> Result<(), VaultError> {
+        if epoch != self.epoch {
+            self.epoch = epoch;
+            self.count = 0;
+        }
+        if self.count >= self.max_per_epoch {
+            return Err(VaultError::RateLimited);
+        }
+        self.count += 1;
+        Ok(())
+    }
+}
+
+// Adding a field to `RateLimit` breaks this pattern, forcing `LEN` to be revisited.
+const _: fn(&RateLimit) = |limit| {
+    let RateLimit {
+        discriminator: _,
+        version: _,
+        vault: _,
+        max_per_epoch: _,
+        epoch: _,
+        count: _,
+        bump: _,
+        reserved: _,
+    } = limit;
+};
+
+impl IsInitialized for RateLimit {
+    /// Is initialized
+    fn is_initialized(&self) -> bool {
+        self.discriminator == Self::SPL_DISCRIMINATOR_SLICE && self.version == Self::CURRENT_VERSION
+    }
+}
+
+/// A mutation recorded in an audit trail.
+#[derive(
+    Clone, Copy, Debug, Default, BorshSerialize, BorshDeserialize, BorshSchema, PartialEq, Eq,
+)]
+pub struct AuditEntry {
+    /// Slot of the mutation
+    pub slot: u64,
+    /// Native tag of the instruction (see `VaultInstruction::tag`)
+    pub action: u8,
+    /// First signer of the instruction after the vault record
+    pub actor: Pubkey,
+}
+
+impl AuditEntry {
+    /// Packed entry space
+    pub const LEN: usize = size_of::<u64>() + size_of::<u8>() + size_of::<Pubkey>();
+}
+
+/// Header of a vault's audit trail, an opt-in history of the mutations counted by rate
+/// limits (see `VaultInstruction::is_rate_limited`). `capacity` entries follow the
+/// header in a ring: entry `n` (counting from zero) is at `n % capacity`.
+#[derive(
+    Clone, Debug, BorshSerialize, BorshDeserialize, BorshSchema, PartialEq, SplDiscriminate,
+)]
+#[discriminator_hash_input("account:AuditTrail")]
+pub struct AuditTrail {
+    /// Account type discriminator, `SPL_DISCRIMINATOR` once initialized
+    discriminator: [u8; ArrayDiscriminator::LENGTH],
+
+    /// Struct version, allows for upgrades to the program
+    pub version: u8,
+
+    /// The vault record
+    pub vault: Pubkey,
+
+    /// Entries the ring holds, fixed at creation
+    pub capacity: u16,
+
+    /// Entries ever recorded, the sequence number of the next one
+    pub count: u64,
+
+    /// PDA bump seed
+    pub bump: u8,
+
+    /// Zeroed padding; new fields are carved from here to avoid resizing accounts
+    reserved: [u8; AUDIT_TRAIL_RESERVED_LEN],
+}
+
+impl AuditTrail {
+    /// Version to fill in on new created accounts
+    pub const CURRENT_VERSION: u8 = 1;
+    /// Packed header space; the entries follow
+    pub const LEN: usize = ArrayDiscriminator::LENGTH
+        + size_of::<u8>()
+        + size_of::<Pubkey>()
+        + size_of::<u16>()
+        + size_of::<u64>()
+        + size_of::<u8>()
+        + AUDIT_TRAIL_RESERVED_LEN;
+
+    /// Create an empty current-version audit trail of `capacity` entries.
+    pub fn new(vault: Pubkey, capacity: u16, bump: u8) -> Self {
+        let mut discriminator = [0; ArrayDiscriminator::LENGTH];
+        discriminator.copy_from_slice(Self::SPL_DISCRIMINATOR_SLICE);
+        Self {
+            discriminator,
+            version: Self::CURRENT_VERSION,
+            vault,
+            capacity,
+            count: 0,
+            bump,
+            reserved: [0; AUDIT_TRAIL_RESERVED_LEN],
+        }
+    }
+
+    /// Address and bump of the audit trail of `vault`.
+    pub fn find_address(program_id: &Pubkey, vault: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[AUDIT_TRAIL_SEED, vault.as_ref()], program_id)
+    }
+
+    /// Account data length of a trail of `capacity` entries, header included.
+    pub fn space(capacity: u16) -> usize {
+        Self::LEN + AuditEntry::LEN * usize::from(capacity)
+    }
+
+    /// Read the header at the start of the account data.
+    pub fn from_account_data(data: &[u8]) -> Result<Self, ProgramError> {
+        let header = data
+            .get(..Self::LEN)
+            .ok_or(ProgramError::AccountDataTooSmall)?;
+        Ok(Self::try_from_slice(header)?)
+    }
+
+    /// Append `entry` to the trail held in `data`, overwriting the oldest entry once
+    /// full, and write back the header. Returns the entry's sequence number.
+    pub fn push(&mut self, data: &mut [u8], entry: &AuditEntry) -> Result<u64, ProgramError> {
+        if data.len() != Self::space(self.capacity) || self.capacity == 0 {
+            return Err(VaultError::InvalidDataLength.into());
+        }
+        let sequence = self.count;
+        let offset = Self::LEN + AuditEntry::LEN * (sequence % u64::from(self.capacity)) as usize;
+        data[offset..offset + AuditEntry::LEN].copy_from_slice(&borsh::to_vec(entry)?);
+        self.count = math::checked_add(sequence, 1)?;
+        data[..Self::LEN].copy_from_slice(&borsh::to_vec(self)?);
+        Ok(sequence)
+    }
+
+    /// The kept entries of the trail held in `data` with their sequence numbers, oldest
+    /// first.
+    pub fn entries<'a>(
+        &'a self,
+        data: &'a [u8],
+    ) -> impl Iterator<Item = Result<(u64, AuditEntry), ProgramError>> + 'a {
+        let capacity = u64::from(self.capacity);
+        let first = self.count.saturating_sub(capacity);
+        (first..self.count).map(move |sequence| {
+            let offset = Self::LEN + AuditEntry::LEN * (sequence % capacity) as usize;
+            let bytes = data
+                .get(offset..offset + AuditEntry::LEN)
+                .ok_or(ProgramError::AccountDataTooSmall)?;
+            Ok((sequence, AuditEntry::try_from_slice(bytes)?))
+        })
+    }
+}
+
+// Adding a field to `AuditTrail` breaks this pattern, forcing `LEN` to be revisited.
+const _: fn(&AuditTrail) = |trail| {
+    let AuditTrail {
+        discriminator: _,
+        version: _,
+        vault: _,
+        capacity: _,
+        count: _,
+        bump: _,
+        reserved: _,
+    } = trail;
+};
+
+impl IsInitialized for AuditTrail {
+    /// Is initialized
+    fn is_initialized(&self) -> bool {
+        self.discriminator == Self::SPL_DISCRIMINATOR_SLICE && self.version == Self::CURRENT_VERSION
+    }
+}
+
+/// Settings a DART gives its new vaults, so clients need not pass each of them at
+/// initialize. Default pubkeys leave the record's setting unset.
+#[derive(
+    Clone, Copy, Debug, Default, BorshSerialize, BorshDeserialize, BorshSchema, PartialEq, Eq,
+)]
+pub struct PolicyDefaults {
+    /// Category tag
+    pub tag: u16,
+    /// Price feed gating authority changes
+    pub oracle: Pubkey,
+    /// Slots the oracle may go without publishing
+    pub oracle_max_staleness_slots: u64,
+    /// Lowest oracle price, in the feed's exponent, at which transfers are allowed
+    pub oracle_min_price: i64,
+    /// Account paid when the vault is closed
+    pub close_recipient: Pubkey,
+    /// Whether instructions acting on the vault need the DART's signature
+    pub cosign: bool,
+    /// Close fee charged when no close fee tier matches, in place of the fee config's
+    /// default; zero keeps the default
+    pub close_fee: u64,
+    /// Bits of `restriction` barring the vault from instructions
+    pub restrictions: u8,
+}
+
+impl PolicyDefaults {
+    /// Packed policy defaults space
+    pub const LEN: usize = size_of::<u16>()
+        + size_of::<Pubkey>()
+        + size_of::<u64>()
+        + size_of::<i64>()
+        + size_of::<Pubkey>()
+        + size_of::<bool>()
+        + size_of::<u64>()
+        + size_of::<u8>();
+
+    /// The settings `record` carries, as `CloneVault` copies them to a new vault, the
+    /// close recipient aside.
+    pub fn of(record: &VaultRecord) -> Self {
+        Self {
+            tag: record.tag,
+            oracle: record.oracle,
+            oracle_max_staleness_slots: record.oracle_max_staleness_slots,
+            oracle_min_price: record.oracle_min_price,
+            close_recipient: record.close_recipient,
+            cosign: record.cosign,
+            close_fee: record.close_fee,
+            restrictions: record.restrictions,
+        }
+    }
+
+    /// Give a new `record` the defaults.
+    pub fn apply(&self, record: &mut VaultRecord) {
+        record.tag = self.tag;
+        record.cosign = self.cosign;
+        record.close_fee = self.close_fee;
+        record.restrictions = self.restrictions;
+        if self.oracle != Pubkey::default() {
+            record.oracle = self.oracle;
+            record.oracle_max_staleness_slots = self.oracle_max_staleness_slots;
+            record.oracle_min_price = self.oracle_min_price;
+        }
+        if self.close_recipient != Pubkey::default() {
+            record.close_recipient = self.close_recipient;
+        }
+    }
+}
+
+/// A DART's policy for the vaults it initializes, consulted by `Initialize` when passed.
+#[derive(
+    Clone, Debug, BorshSerialize, BorshDeserialize, BorshSchema, PartialEq, SplDiscriminate,
+)]
+#[discriminator_hash_input("account:DartPolicy")]
+pub struct DartPolicy {
+    /// Account type discriminator, `SPL_DISCRIMINATOR` once initialized
+    discriminator: [u8; ArrayDiscriminator::LENGTH],
+
+    /// Struct version, allows for upgrades to the program
+    pub version: u8,
+
+    /// The DART, or DART identity, the policy belongs to
+    pub dart: Pubkey,
+
+    /// Settings new vaults take
+    pub defaults: PolicyDefaults,
+
+    /// PDA bump seed
+    pub bump: u8,
+
+    /// Zeroed padding; new fields are carved from here to avoid resizing accounts
+    reserved: [u8; DART_POLICY_RESERVED_LEN],
+}
+
+impl DartPolicy {
+    /// Version to fill in on new created accounts
+    pub const CURRENT_VERSION: u8 = 1;
+    /// Packed DART policy space
+    pub const LEN: usize = ArrayDiscriminator::LENGTH
+        + size_of::<u8>()
+        + size_of::<Pubkey>()
+        + PolicyDefaults::LEN
+        + size_of::<u8>()
+        + DART_POLICY_RESERVED_LEN;
+
+    /// Create a current-version DART policy.
+    pub fn new(dart: Pubkey, defaults: PolicyDefaults, bump: u8) -> Self {
+        let mut discriminator = [0; ArrayDiscriminator::LENGTH];
+        discriminator.copy_from_slice(Self::SPL_DISCRIMINATOR_SLICE);
+        Self {
+            discriminator,
+            version: Self::CURRENT_VERSION,
+            dart,
+            defaults,
+            bump,
+            reserved: [0; DART_POLICY_RESERVED_LEN],
+        }
+    }
+
+    /// Address and bump of the policy of `dart`.
+    pub fn find_address(program_id: &Pubkey, dart: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[DART_POLICY_SEED, dart.as_ref()], program_id)
+    }
+}
+
+// Adding a field to `DartPolicy` breaks this pattern, forcing `LEN` to be revisited.
+const _: fn(&DartPolicy) = |policy| {
+    let DartPolicy {
+        discriminator: _,
+        version: _,
+        dart: _,
+        defaults: _,
+        bump: _,
+        reserved: _,
+    } = policy;
+};
+
+impl IsInitialized for DartPolicy {
+    /// Is initialized
+    fn is_initialized(&self) -> bool {
+        self.discriminator == Self::SPL_DISCRIMINATOR_SLICE && self.version == Self::CURRENT_VERSION
+    }
+}
+
+/// The vaults of an authority, so a wallet can list them by reading one account rather
+/// than scanning the program. Vaults are added when created and removed when closed
+/// with the index passed; `SyncVaultIndex` catches up with transfers and older vaults.
+#[derive(
+    Clone, Debug, BorshSerialize, BorshDeserialize, BorshSchema, PartialEq, SplDiscriminate,
+)]
+#[discriminator_hash_input("account:VaultIndex")]
+pub struct VaultIndex {
+    /// Account type discriminator, `SPL_DISCRIMINATOR` once initialized
+    discriminator: [u8; ArrayDiscriminator::LENGTH],
+
+    /// Struct version, allows for upgrades to the program
+    pub version: u8,
+
+    /// The authority whose vaults are listed
+    pub authority: Pubkey,
+
+    /// Vault record addresses; unused slots hold the default pubkey
+    pub vaults: [Pubkey; MAX_INDEXED_VAULTS],
+
+    /// PDA bump seed
+    pub bump: u8,
+
+    /// Zeroed padding; new fields are carved from here to avoid resizing accounts
+    reserved: [u8; VAULT_INDEX_RESERVED_LEN],
+}
+
+impl VaultIndex {
+    /// Version to fill in on new created accounts
+    pub const CURRENT_VERSION: u8 = 1;
+    /// Packed vault index space
+    pub const LEN: usize = ArrayDiscriminator::LENGTH
+        + size_of::<u8>()
+        + size_of::<Pubkey>()
+        + size_of::<Pubkey>() * MAX_INDEXED_VAULTS
+        + size_of::<u8>()
+        + VAULT_INDEX_RESERVED_LEN;
+
+    /// Create an empty current-version vault index.
+    pub fn new(authority: Pubkey, bump: u8) -> Self {
+        let mut discriminator = [0; ArrayDiscriminator::LENGTH];
+        discriminator.copy_from_slice(Self::SPL_DISCRIMINATOR_SLICE);
+        Self {
+            discriminator,
+            version: Self::CURRENT_VERSION,
+            authority,
+            vaults: [Pubkey::default(); MAX_INDEXED_VAULTS],
+            bump,
+            reserved: [0; VAULT_INDEX_RESERVED_LEN],
+        }
+    }
+
+    /// Address and bump of the vault index of `authority`.
+    pub fn find_address(program_id: &Pubkey, authority: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[VAULT_INDEX_SEED, authority.as_ref()], program_id)
+    }
+
+    /// The listed vaults.
+    pub fn vaults(&self) -> impl Iterator<Item = &Pubkey> {
+        self.vaults
+            .iter()
+            .filter(|vault| **vault != Pubkey::default())
+    }
+
+    /// List `vault`, unless it already is.
+    pub fn insert(&mut self, vault: Pubkey) -> Result<(), ProgramError> {
+        if self.vaults.contains(&vault) {
+            return Ok(());
+        }
+        let slot = self
+            .vaults
+            .iter_mut()
+            .find(|slot| **slot == Pubkey::default())
+            .ok_or_else(|| {
+                msg!("Vault index is full");
+                VaultError::VaultIndexFull
+            })?;
+        *slot = vault;
+        Ok(())
+    }
+
+    /// Unlist `vault`, returning whether it was listed.
+    pub fn remove(&mut self, vault: &Pubkey) -> bool {
+        match self.vaults.iter_mut().find(|slot| *slot == vault) {
+            Some(slot) => {
+                *slot = Pubkey::default();
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+// Adding a field to `VaultIndex` breaks this pattern, forcing `LEN` to be revisited.
+const _: fn(&VaultIndex) = |index| {
+    let VaultIndex {
+        discriminator: _,
+        version: _,
+        authority: _,
+        vaults: _,
+        bump: _,
+        reserved: _,
+    } = index;
+};
+
+impl IsInitialized for VaultIndex {
+    /// Is initialized
+    fn is_initialized(&self) -> bool {
+        self.discriminator == Self::SPL_DISCRIMINATOR_SLICE && self.version == Self::CURRENT_VERSION
+    }
+}
+
+/// Address of the BPF Upgradeable Loader `ProgramData` account of `program_id`.
+pub fn program_data_address(program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[program_id.as_ref()], &bpf_loader_upgradeable::id()).0
+}
+
+/// The upgrade authority recorded in `data`, the data of a BPF Upgradeable Loader
+/// `ProgramData` account: `None` for accounts of another kind and for programs
+/// deployed as immutable.
+pub fn upgrade_authority(data: &[u8]) -> Option<Pubkey> {
+    // bincode of `UpgradeableLoaderState::ProgramData { slot, upgrade_authority_address }`:
+    // a u32 variant index, a u64 slot, then an `Option<Pubkey>`.
+    const PROGRAM_DATA: [u8; 4] = 3u32.to_le_bytes();
+    if data.get(..4)? != PROGRAM_DATA || *data.get(12)? != 1 {
+        return None;
+    }
+    Pubkey::try_from(data.get(13..45)?).ok()
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use solana_program::{borsh1::get_packed_len, hash::hash, program_error::ProgramError};
+
+    /// Version for tests
+    pub const TEST_VERSION: u8 = 6;
+    /// `sha256("account:VaultRecord")[..8]`
+    pub const TEST_DISCRIMINATOR: [u8; 8] = [0x2f, 0x01, 0xda, 0x74, 0x52, 0x46, 0x7c, 0x77];
+    /// Authority pubkey
+    pub const AUTH_PUBKEY: Pubkey = Pubkey::new_from_array([99; 32]);
+    /// DART pubkey
+    pub const DART_PUBKEY: Pubkey = Pubkey::new_from_array([66; 32]);
+    /// VaultRecord for tests
+    pub const TEST_RECORD_DATA: VaultRecord = VaultRecord {
+        discriminator: TEST_DISCRIMINATOR,
+        version: TEST_VERSION,
+        authority: AUTH_PUBKEY,
+        dart: DART_PUBKEY,
+        status: VaultStatus::Active,
+        pending_dart: Pubkey::new_from_array([0; 32]),
+        tag: 0,
+        data_len: 0,
+        target_len: 0,
+        rent_payer: Pubkey::new_from_array([0; 32]),
+        close_recipient: Pubkey::new_from_array([0; 32]),
+        attestation: [0; 32],
+        pending_authority: Pubkey::new_from_array([0; 32]),
+        transfer_expires_at_slot: 0,
+        oracle: Pubkey::new_from_array([0; 32]),
+        oracle_max_staleness_slots: 0,
+        oracle_min_price: 0,
+        receipt_mint: Pubkey::new_from_array([0; 32]),
+        receipt_is_authority: false,
+        checksum: 0,
+        pledged: false,
+        transfer_windowed: false,
+        rate_limited: false,
+        created_slot: 0,
+        audited: false,
+        approval_nonce: 0,
+        cosign: false,
+        close_fee: 0,
+        restrictions: 0,
+        reserved: [0; RESERVED_LEN],
+    };
+
+    #[test]
+    fn serialize_data() {
+        let mut expected = TEST_DISCRIMINATOR.to_vec();
+        expected.push(TEST_VERSION);
+        expected.extend_from_slice(&AUTH_PUBKEY.to_bytes());
+        expected.extend_from_slice(&DART_PUBKEY.to_bytes());
+        expected.push(0);
+        expected.extend_from_slice(&Pubkey::default().to_bytes());
+        expected.extend_from_slice(&[0; 2]);
+        expected.extend_from_slice(&[0; 4]);
+        expected.extend_from_slice(&[0; 4]);
+        expected.extend_from_slice(&Pubkey::default().to_bytes());
+        expected.extend_from_slice(&Pubkey::default().to_bytes());
+        expected.extend_from_slice(&[0; 32]);
+        expected.extend_from_slice(&Pubkey::default().to_bytes());
+        expected.extend_from_slice(&[0; 8]);
+        expected.extend_from_slice(&Pubkey::default().to_bytes());
+        expected.extend_from_slice(&[0; 8]);
+        expected.extend_from_slice(&[0; 8]);
+        expected.extend_from_slice(&Pubkey::default().to_bytes());
+        expected.push(0);
+        expected.extend_from_slice(&[0; 4]);
+        expected.push(0);
+        expected.push(0);
+        expected.push(0);
+        expected.extend_from_slice(&[0; 8]);
+        expected.push(0);
+        expected.extend_from_slice(&[0; 8]);
+        expected.push(0);
+        expected.extend_from_slice(&[0; 8]);
+        expected.push(0);
+        expected.extend_from_slice(&[0; RESERVED_LEN]);
+        assert_eq!(borsh::to_vec(&TEST_RECORD_DATA).unwrap(), expected);
+        assert_eq!(
+            VaultRecord::try_from_slice(&expected).unwrap(),
+            TEST_RECORD_DATA
+        );
+    }
+
+    #[test]
+    fn packed_len_matches_schema() {
+        assert_eq!(get_packed_len::<VaultRecord>(), VaultRecord::LEN);
+        assert_eq!(
+            borsh::to_vec(&TEST_RECORD_DATA).unwrap().len(),
+            VaultRecord::LEN
+        );
+    }
+
+    #[test]
+    fn discriminator_matches_hash_input() {
+        assert_eq!(VaultRecord::SPL_DISCRIMINATOR_SLICE, TEST_DISCRIMINATOR);
+        assert_eq!(
+            hash(b"account:VaultRecord").to_bytes()[..8],
+            TEST_DISCRIMINATOR
+        );
+    }
+
+    #[test]
+    fn initialized_requires_discriminator() {
+        let mut record = TEST_RECORD_DATA;
+        assert!(record.is_initialized());
+        record.discriminator = [0; ArrayDiscriminator::LENGTH];
+        assert!(!record.is_initialized());
+    }
+
+    #[test]
+    fn new_record() {
+        assert_eq!(VaultRecord::new(AUTH_PUBKEY, DART_PUBKEY), TEST_RECORD_DATA);
+    }
+
+    #[test]
+    fn wire_compatible_with_fixture() {
+        // Recorded with borsh 0.10 before the borsh 1.x migration.
+        let fixture = include_bytes!("../tests/fixtures/vault_record_v3.bin");
+        let mut record = TEST_RECORD_DATA;
+        record.version = 3;
+        assert!(record.is_initialized());
+        assert_eq!(record.header_len(), fixture.len());
+
+        let mut data = vec![0; VaultRecord::LEN_V3];
+        record.pack_into(&mut data).unwrap();
+        assert_eq!(data, fixture);
+        assert_eq!(VaultRecord::from_account_data(fixture).unwrap(), record);
+    }
+
+    #[test]
+    fn v3_records_reject_new_fields() {
+        let mut record = TEST_RECORD_DATA;
+        record.version = 3;
+        record.rent_payer = AUTH_PUBKEY;
+        let mut data = vec![0; VaultRecord::LEN];
+        assert_eq!(
+            record.pack_into(&mut data),
+            Err(ProgramError::InvalidAccountData)
+        );
+        record.rent_payer = Pubkey::default();
+        record.close_recipient = AUTH_PUBKEY;
+        assert_eq!(
+            record.pack_into(&mut data),
+            Err(ProgramError::InvalidAccountData)
+        );
+
+        record.version = 0;
+        assert!(!record.is_initialized());
+        assert_eq!(
+            VaultRecord::from_account_data(&data[..VaultRecord::LEN_V3 - 1]),
+            Err(ProgramError::AccountDataTooSmall)
+        );
+    }
+
+    #[test]
+    fn version_1_records_load_and_close() {
+        // Recorded from the 65 byte record of the first release.
+        let data = include_bytes!("../tests/fixtures/vault_record_v1.bin");
+        let mut record = VaultRecord::from_account_data(data).unwrap();
+        assert!(record.is_initialized());
+        assert_eq!(record.version, 1);
+        assert_eq!(record.authority, AUTH_PUBKEY);
+        assert_eq!(record.dart, DART_PUBKEY);
+        assert_eq!(record.status, VaultStatus::Active);
+        assert_eq!(record.header_len(), data.len());
+
+        let mut packed = vec![0; VaultRecord::LEN_V1];
+        record.pack_into(&mut packed).unwrap();
+        assert_eq!(packed, data);
+
+        // The closed flag is kept when the account has room for it, and the record
+        // stops loading when it has not.
+        record.status = VaultStatus::Closed;
+        let mut flagged = vec![0; VaultRecord::LEN_V1 + 1];
+        record.pack_into(&mut flagged).unwrap();
+        assert_eq!(flagged[VaultRecord::LEN_V1], 1);
+        assert_eq!(VaultRecord::from_account_data(&flagged).unwrap(), record);
+        record.pack_into(&mut packed).unwrap();
+        assert!(VaultRecord::from_account_data(&packed).map_or(true, |r| !r.is_initialized()));
+
+        record.status = VaultStatus::Frozen;
+        assert_eq!(
+            record.pack_into(&mut flagged),
+            Err(ProgramError::InvalidAccountData)
+        );
+    }
+
+    #[test]
+    fn version_2_records_load() {
+        let mut record = TEST_RECORD_DATA;
+        record.version = 3;
+        record.tag = 7;
+        let mut data = vec![0; VaultRecord::LEN_V3];
+        record.pack_into(&mut data).unwrap();
+        data[ArrayDiscriminator::LENGTH] = 2;
+        let data = &data[ArrayDiscriminator::LENGTH..];
+
+        record.version = 2;
+        assert_eq!(VaultRecord::from_account_data(data).unwrap(), record);
+        assert_eq!(record.header_len(), VaultRecord::LEN_V2);
+        let mut packed = vec![0; VaultRecord::LEN_V2];
+        record.pack_into(&mut packed).unwrap();
+        assert_eq!(packed, data);
+    }
+
+    #[test]
+    fn legacy_layouts_need_their_length() {
+        // An approval root's discriminator starts with a version 2 record's version byte;
+        // neither the account nor the same bytes in a longer one read as a record.
+        let root = ApprovalRoot::new(DART_PUBKEY, [1; 32], 42, 255);
+        let mut data = borsh::to_vec(&root).unwrap();
+        assert_eq!(data[0], 2);
+        for len in [ApprovalRoot::LEN, VaultRecord::LEN_V3, VaultRecord::LEN] {
+            data.resize(len, 0);
+            assert!(VaultRecord::from_account_data(&data).map_or(true, |r| !r.is_initialized()));
+        }
+
+        // Likewise a version 1 record's byte at other lengths.
+        let mut data = include_bytes!("../tests/fixtures/vault_record_v1.bin").to_vec();
+        data.resize(VaultRecord::LEN_V3, 0);
+        assert!(VaultRecord::from_account_data(&data).map_or(true, |r| !r.is_initialized()));
+    }
+
+    #[test]
+    fn checksum_detects_corruption() {
+        let mut data = vec![0; VaultRecord::LEN];
+        TEST_RECORD_DATA.pack_into(&mut data).unwrap();
+        let record = VaultRecord::from_account_data(&data).unwrap();
+        assert_ne!(record.checksum, 0);
+        assert_eq!(record.checksum, VaultRecord::checksum_of(&data));
+
+        data[VaultRecord::TAG_OFFSET] ^= 1;
+        assert_eq!(
+            VaultRecord::from_account_data(&data),
+            Err(VaultError::ChecksumMismatch.into())
+        );
+
+        // Uninitialized accounts are not checked.
+        assert!(VaultRecord::from_account_data(&[0; VaultRecord::LEN]).is_ok());
+    }
+
+    #[test]
+    fn v4_records_upgrade_on_write() {
+        let mut record = TEST_RECORD_DATA;
+        record.version = 4;
+        let mut data = borsh::to_vec(&record).unwrap();
+        data[VaultRecord::TAG_OFFSET] = 7;
+        assert_eq!(VaultRecord::from_account_data(&data).unwrap().tag, 7);
+
+        record.pack_into(&mut data).unwrap();
+        let upgraded = VaultRecord::from_account_data(&data).unwrap();
+        assert_eq!(upgraded.version, VaultRecord::CHECKSUM_VERSION);
+        assert_eq!(upgraded.tag, 0);
+    }
+
+    #[test]
+    fn v5_records_keep_their_layout() {
+        let mut record = TEST_RECORD_DATA;
+        record.version = 5;
+        record.tag = 7;
+        let mut data = vec![0; VaultRecord::LEN_V5];
+        record.pack_into(&mut data).unwrap();
+        let decoded = VaultRecord::from_account_data(&data).unwrap();
+        assert_eq!(decoded.header_len(), VaultRecord::LEN_V5);
+        assert_eq!(decoded.tag, 7);
+
+        // Fields past the version 5 layout cannot be written to it.
+        for record in [
+            VaultRecord {
+                created_slot: 1,
+                ..record
+            },
+            VaultRecord {
+                audited: true,
+                ..record
+            },
+            VaultRecord {
+                approval_nonce: 1,
+                ..record
+            },
+            VaultRecord {
+                cosign: true,
+                ..record
+            },
+            VaultRecord {
+                close_fee: 1,
+                ..record
+            },
+            VaultRecord {
+                restrictions: restriction::NO_RECEIPT,
+                ..record
+            },
+        ] {
+            assert_eq!(
+                record.pack_into(&mut data),
+                Err(ProgramError::InvalidAccountData)
+            );
+        }
+    }
+
+    #[test]
+    fn tag_filter_matches_layout() {
+        let mut record = TEST_RECORD_DATA;
+        record.tag = 0x0102;
+        let data = borsh::to_vec(&record).unwrap();
+
+        let (offset, bytes) = VaultRecord::tag_filter(0x0102);
+        assert_eq!(&data[offset..offset + bytes.len()], bytes);
+        let (offset, bytes) = VaultRecord::discriminator_filter();
+        assert_eq!(&data[offset..offset + bytes.len()], bytes);
+        let (offset, bytes) = VaultRecord::dart_filter(&record.dart);
+        assert_eq!(&data[offset..offset + bytes.len()], bytes);
+        let (offset, bytes) = VaultRecord::authority_filter(&record.authority);
+        assert_eq!(&data[offset..offset + bytes.len()], bytes);
+    }
+
+    #[test]
+    fn from_account_data_ignores_metadata() {
+        let mut record = VaultRecord::new(AUTH_PUBKEY, DART_PUBKEY);
+        record.data_len = 512;
+        let mut data = vec![7; 512];
+        record.pack_into(&mut data).unwrap();
+
+        assert!(VaultRecord::try_from_slice(&data).is_err());
+        let decoded = VaultRecord::from_account_data(&data).unwrap();
+        record.checksum = decoded.checksum;
+        assert_eq!(decoded, record);
+        assert_eq!(decoded.account_len(), 512);
+        assert_eq!(TEST_RECORD_DATA.account_len(), VaultRecord::LEN);
+    }
+
+    #[test]
+    fn deserialize_invalid_slice() {
+        let mut expected = TEST_DISCRIMINATOR.to_vec();
+        expected.push(TEST_VERSION);
+        expected.extend_from_slice(&AUTH_PUBKEY.to_bytes());
+        let err: ProgramError = VaultRecord::try_from_slice(&expected).unwrap_err().into();
+        assert!(matches!(err, ProgramError::BorshIoError(_)));
+    }
+
+    #[test]
+    fn dart_identity_layout() {
+        let mut identity = DartIdentity::new(AUTH_PUBKEY);
+        assert!(identity.is_initialized());
+        assert_eq!(get_packed_len::<DartIdentity>(), DartIdentity::LEN);
+        assert_eq!(borsh::to_vec(&identity).unwrap().len(), DartIdentity::LEN);
+        assert_eq!(
+            DartIdentity::SPL_DISCRIMINATOR_SLICE,
+            &hash(b"account:DartIdentity").to_bytes()[..8]
+        );
+
+        assert!(!identity.is_operational(&DART_PUBKEY));
+        assert!(!identity.is_operational(&Pubkey::default()));
+        identity.operational_keys[2] = DART_PUBKEY;
+        assert!(identity.is_operational(&DART_PUBKEY));
+        assert!(!identity.is_operational(&AUTH_PUBKEY));
+
+        // The quorum fields were carved from the reserved bytes.
+        assert_eq!(
+            DartIdentity::LEN,
+            8 + 1 + 32 + 32 * MAX_OPERATIONAL_KEYS + 64
+        );
+        identity.quorum = 2;
+        identity.approval_nonce = 7;
+        let data = borsh::to_vec(&identity).unwrap();
+        assert_eq!(data[169], 2);
+        assert_eq!(data[170..178], 7u64.to_le_bytes());
+    }
+
+    #[test]
+    fn session_record_layout() {
+        let session = SessionRecord::new(
+            Pubkey::new_from_array([11; 32]),
+            AUTH_PUBKEY,
+            DART_PUBKEY,
+            42,
+            session_scope::TRANSFER_AUTHORITY,
+            255,
+        );
+        assert!(session.is_initialized());
+        assert_eq!(get_packed_len::<SessionRecord>(), SessionRecord::LEN);
+        assert_eq!(borsh::to_vec(&session).unwrap().len(), SessionRecord::LEN);
+        assert_eq!(
+            SessionRecord::SPL_DISCRIMINATOR_SLICE,
+            &hash(b"account:SessionRecord").to_bytes()[..8]
+        );
+    }
+
+    #[test]
+    fn recovery_record_guardians() {
+        let mut recovery = RecoveryRecord::new(Pubkey::new_from_array([11; 32]), 255);
+        assert!(recovery.is_initialized());
+        assert_eq!(get_packed_len::<RecoveryRecord>(), RecoveryRecord::LEN);
+        assert_eq!(borsh::to_vec(&recovery).unwrap().len(), RecoveryRecord::LEN);
+        assert_eq!(
+            RecoveryRecord::SPL_DISCRIMINATOR_SLICE,
+            &hash(b"account:RecoveryRecord").to_bytes()[..8]
+        );
+
+        assert!(recovery.is_valid_threshold(0));
+        assert!(!recovery.is_valid_threshold(1));
+        assert_eq!(recovery.guardian_index(&Pubkey::default()), None);
+
+        recovery.guardians[1] = AUTH_PUBKEY;
+        recovery.guardians[3] = DART_PUBKEY;
+        assert_eq!(recovery.guardian_count(), 2);
+        assert_eq!(recovery.guardian_index(&DART_PUBKEY), Some(3));
+        assert!(!recovery.is_valid_threshold(0));
+        assert!(recovery.is_valid_threshold(2));
+        assert!(!recovery.is_valid_threshold(3));
+
+        recovery.pending_authority = AUTH_PUBKEY;
+        recovery.approvals = 0b1010;
+        assert_eq!(recovery.approval_count(), 2);
+        recovery.clear_pending();
+        assert_eq!(recovery.pending_authority, Pubkey::default());
+        assert_eq!(recovery.approval_count(), 0);
+    }
+
+    #[test]
+    fn vaa_digest_skips_signatures() {
+        let body = b"custody event";
+        let mut vaa = vec![1, 0, 0, 0, 3, 2];
+        vaa.extend_from_slice(&[0xaa; 2 * 66]);
+        vaa.extend_from_slice(body);
+        let expected = keccak::hash(&keccak::hash(body).to_bytes()).to_bytes();
+        assert_eq!(vaa_digest(&vaa).unwrap(), expected);
+
+        assert_eq!(
+            vaa_digest(&vaa[..6 + 66]),
+            Err(ProgramError::InvalidInstructionData)
+        );
+        assert_eq!(
+            vaa_digest(&vaa[..5]),
+            Err(ProgramError::InvalidInstructionData)
+        );
+    }
+
+    #[test]
+    fn inheritance_record_claimable_at() {
+        let mut inheritance = InheritanceRecord::new(Pubkey::new_from_array([11; 32]), 254);
+        assert!(inheritance.is_initialized());
+        assert_eq!(
+            get_packed_len::<InheritanceRecord>(),
+            InheritanceRecord::LEN
+        );
+        assert_eq!(
+            borsh::to_vec(&inheritance).unwrap().len(),
+            InheritanceRecord::LEN
+        );
+        assert_eq!(
+            InheritanceRecord::SPL_DISCRIMINATOR_SLICE,
+            &hash(b"account:InheritanceRecord").to_bytes()[..8]
+        );
+
+        inheritance.last_activity_slot = 100;
+        inheritance.inactivity_slots = 50;
+        assert_eq!(inheritance.claimable_at_slot(), Ok(150));
+        inheritance.inactivity_slots = u64::MAX;
+        assert_eq!(
+            inheritance.claimable_at_slot(),
+            Err(VaultError::Overflow.into())
+        );
+    }
+
+    #[test]
+    fn transfer_hook_config_allowlist() {
+        let mut config = TransferHookConfig::new(
+            Pubkey::new_from_array([12; 32]),
+            Pubkey::new_from_array([13; 32]),
+            253,
+        );
+        assert!(config.is_initialized());
+        assert_eq!(
+            get_packed_len::<TransferHookConfig>(),
+            TransferHookConfig::LEN
+        );
+        assert_eq!(
+            borsh::to_vec(&config).unwrap().len(),
+            TransferHookConfig::LEN
+        );
+        assert_eq!(
+            TransferHookConfig::SPL_DISCRIMINATOR_SLICE,
+            &hash(b"account:TransferHookConfig").to_bytes()[..8]
+        );
+
+        let (listed, other) = (
+            Pubkey::new_from_array([14; 32]),
+            Pubkey::new_from_array([15; 32]),
+        );
+        assert!(config.is_allowed(&listed));
+        assert!(config.is_allowed(&other));
+        config.allowlist[MAX_ALLOWLIST - 1] = listed;
+        assert!(config.is_allowed(&listed));
+        assert!(!config.is_allowed(&other));
+        assert!(!config.is_allowed(&Pubkey::default()));
+    }
+
+    #[test]
+    fn program_config_layout() {
+        let mut config = ProgramConfig::new(Pubkey::new_from_array([16; 32]), 252);
+        assert!(config.is_initialized());
+        assert!(!config.is_migrated());
+        assert_eq!(get_packed_len::<ProgramConfig>(), ProgramConfig::LEN);
+        assert_eq!(borsh::to_vec(&config).unwrap().len(), ProgramConfig::LEN);
+        assert_eq!(
+            ProgramConfig::SPL_DISCRIMINATOR_SLICE,
+            &hash(b"account:ProgramConfig").to_bytes()[..8]
+        );
+
+        // The successor was carved from the reserved bytes.
+        assert_eq!(ProgramConfig::LEN, 8 + 1 + 32 + 1 + 64);
+        config.successor = Pubkey::new_from_array([18; 32]);
+        assert!(config.is_migrated());
+        assert_eq!(borsh::to_vec(&config).unwrap()[42..74], [18; 32]);
+
+        // So were the crank rules.
+        config.crank_bounty = 5000;
+        config.crank_grace_slots = 7;
+        let bytes = borsh::to_vec(&config).unwrap();
+        assert_eq!(bytes[74..82], 5000u64.to_le_bytes());
+        assert_eq!(bytes[82..90], 7u64.to_le_bytes());
+
+        assert!(!config.is_sweepable(10, 17));
+        assert!(config.is_sweepable(10, 18));
+        assert!(!config.is_sweepable(u64::MAX, u64::MAX));
+    }
+
+    #[test]
+    fn approval_root_layout() {
+        let mut approval = ApprovalRoot::new(Pubkey::new_from_array([19; 32]), [20; 32], 100, 253);
+        assert!(approval.is_initialized());
+        assert_eq!(get_packed_len::<ApprovalRoot>(), ApprovalRoot::LEN);
+        assert_eq!(borsh::to_vec(&approval).unwrap().len(), ApprovalRoot::LEN);
+        assert_eq!(
+            ApprovalRoot::SPL_DISCRIMINATOR_SLICE,
+            &hash(b"account:ApprovalRoot").to_bytes()[..8]
+        );
+
+        assert!(approval.is_live(100));
+        assert!(!approval.is_live(101));
+        approval.root = [0; 32];
+        assert!(!approval.is_live(0));
+    }
+
+    #[test]
+    fn note_record_layout() {
+        let note = NoteRecord::new(
+            Pubkey::new_from_array([21; 32]),
+            Pubkey::new_from_array([22; 32]),
+            [23; 32],
+            251,
+        );
+        assert!(note.is_initialized());
+        assert_eq!(get_packed_len::<NoteRecord>(), NoteRecord::LEN);
+        assert_eq!(borsh::to_vec(&note).unwrap().len(), NoteRecord::LEN);
+        assert_eq!(
+            NoteRecord::SPL_DISCRIMINATOR_SLICE,
+            &hash(b"account:NoteRecord").to_bytes()[..8]
+        );
+    }
+
+    #[test]
+    fn notify_record_applies_to_registering_authority() {
+        let mut record = NotifyRecord::new(
+            Pubkey::new_from_array([21; 32]),
+            AUTH_PUBKEY,
+            Pubkey::new_from_array([23; 32]),
+            251,
+        );
+        assert!(record.is_initialized());
+        assert_eq!(get_packed_len::<NotifyRecord>(), NotifyRecord::LEN);
+        assert_eq!(borsh::to_vec(&record).unwrap().len(), NotifyRecord::LEN);
+        assert_eq!(
+            NotifyRecord::SPL_DISCRIMINATOR_SLICE,
+            &hash(b"account:NotifyRecord").to_bytes()[..8]
+        );
+
+        assert!(record.applies_to(&AUTH_PUBKEY));
+        assert!(!record.applies_to(&DART_PUBKEY));
+        record.notify = Pubkey::default();
+        assert!(!record.applies_to(&AUTH_PUBKEY));
+    }
+
+    #[test]
+    fn notification_counts_records() {
+        let mut notification = Notification::new(Pubkey::new_from_array([23; 32]), 252);
+        assert!(notification.is_initialized());
+        assert_eq!(get_packed_len::<Notification>(), Notification::LEN);
+        assert_eq!(
+            borsh::to_vec(&notification).unwrap().len(),
+            Notification::LEN
+        );
+        assert_eq!(
+            Notification::SPL_DISCRIMINATOR_SLICE,
+            &hash(b"account:Notification").to_bytes()[..8]
+        );
+
+        let vault = Pubkey::new_from_array([21; 32]);
+        notification.record(
+            NotificationKind::Transfer,
+            vault,
+            AUTH_PUBKEY,
+            DART_PUBKEY,
+            7,
+        );
+        notification.record(
+            NotificationKind::Close,
+            vault,
+            DART_PUBKEY,
+            Pubkey::default(),
+            9,
+        );
+        assert_eq!(notification.sequence, 2);
+        assert_eq!(notification.kind, NotificationKind::Close);
+        assert_eq!(notification.previous_authority, DART_PUBKEY);
+        assert_eq!(notification.slot, 9);
+    }
+
+    #[test]
+    fn commitment_record_layout() {
+        let record = CommitmentRecord::new(
+            Pubkey::new_from_array([24; 32]),
+            [[25; 32]; MAX_COMMITMENTS],
+            26,
+            250,
+        );
+        assert!(record.is_initialized());
+        assert_eq!(get_packed_len::<CommitmentRecord>(), CommitmentRecord::LEN);
+        assert_eq!(borsh::to_vec(&record).unwrap().len(), CommitmentRecord::LEN);
+        assert_eq!(
+            CommitmentRecord::SPL_DISCRIMINATOR_SLICE,
+            &hash(b"account:CommitmentRecord").to_bytes()[..8]
+        );
+    }
+
+    #[test]
+    fn notes_log_layout() {
+        let log = NotesLog::new(Pubkey::new_from_array([27; 32]), 250);
+        assert!(log.is_initialized());
+        assert_eq!(get_packed_len::<NotesLog>(), NotesLog::LEN);
+        assert_eq!(borsh::to_vec(&log).unwrap().len(), NotesLog::LEN);
+        assert_eq!(
+            NotesLog::SPL_DISCRIMINATOR_SLICE,
+            &hash(b"account:NotesLog").to_bytes()[..8]
+        );
+    }
+
+    #[test]
+    fn notes_log_evicts_oldest() {
+        let mut log = NotesLog::new(Pubkey::new_from_array([27; 32]), 250);
+        let note = |n: u8| LogNote {
+            hash: [n; 32],
+            category: n.into(),
+            slot: n.into(),
+        };
+        assert_eq!(log.latest().count(), 0);
+        for n in 0..MAX_LOG_NOTES as u8 {
+            assert_eq!(log.push(note(n)).unwrap(), (n.into(), None));
+        }
+        assert_eq!(
+            log.push(note(100)).unwrap(),
+            (MAX_LOG_NOTES as u64, Some(note(0)))
+        );
+        assert_eq!(
+            log.push(note(101)).unwrap(),
+            (MAX_LOG_NOTES as u64 + 1, Some(note(1)))
+        );
+
+        let latest: Vec<_> = log.latest().collect();
+        assert_eq!(latest.len(), MAX_LOG_NOTES);
+        assert_eq!(latest[0], (2, &note(2)));
+        assert_eq!(
+            latest[MAX_LOG_NOTES - 1],
+            (MAX_LOG_NOTES as u64 + 1, &note(101))
+        );
+    }
+
+    #[test]
+    fn attestation_list_layout() {
+        let list = AttestationList::new(Pubkey::new_from_array([28; 32]), 250);
+        assert!(list.is_initialized());
+        assert_eq!(get_packed_len::<AttestationList>(), AttestationList::LEN);
+        assert_eq!(borsh::to_vec(&list).unwrap().len(), AttestationList::LEN);
+        assert_eq!(
+            AttestationList::SPL_DISCRIMINATOR_SLICE,
+            &hash(b"account:AttestationList").to_bytes()[..8]
+        );
+    }
+
+    #[test]
+    fn pledge_record_layout() {
+        let pledge = PledgeRecord::new(
+            Pubkey::new_from_array([29; 32]),
+            Pubkey::new_from_array([30; 32]),
+            250,
+        );
+        assert!(pledge.is_initialized());
+        assert_eq!(get_packed_len::<PledgeRecord>(), PledgeRecord::LEN);
+        assert_eq!(borsh::to_vec(&pledge).unwrap().len(), PledgeRecord::LEN);
+        assert_eq!(
+            PledgeRecord::SPL_DISCRIMINATOR_SLICE,
+            &hash(b"account:PledgeRecord").to_bytes()[..8]
+        );
+    }
+
+    #[test]
+    fn position_record_layout() {
+        let position = PositionRecord::new(Pubkey::new_from_array([31; 32]), [32; 32], 33, 34, 250);
+        assert!(position.is_initialized());
+        assert_eq!(get_packed_len::<PositionRecord>(), PositionRecord::LEN);
+        assert_eq!(borsh::to_vec(&position).unwrap().len(), PositionRecord::LEN);
+        assert_eq!(
+            PositionRecord::SPL_DISCRIMINATOR_SLICE,
+            &hash(b"account:PositionRecord").to_bytes()[..8]
+        );
+    }
+
+    #[test]
+    fn position_corporate_actions() {
+        let mut position =
+            PositionRecord::new(Pubkey::new_from_array([31; 32]), [1; 32], 7, 0, 250);
+        position.apply(&CorporateActionType::Split, 3).unwrap();
+        assert_eq!(position.balance, 21);
+        position
+            .apply(&CorporateActionType::ReverseSplit, 10)
+            .unwrap();
+        assert_eq!(position.balance, 2);
+        let symbol_change = CorporateActionType::SymbolChange { asset_id: [2; 32] };
+        assert_eq!(
+            position.apply(&symbol_change, 2),
+            Err(ProgramError::InvalidArgument)
+        );
+        position.apply(&symbol_change, 1).unwrap();
+        assert_eq!((position.asset_id, position.balance), ([2; 32], 2));
+
+        assert_eq!(
+            position.apply(&CorporateActionType::Split, 0),
+            Err(ProgramError::InvalidArgument)
+        );
+        assert_eq!(
+            position.apply(&CorporateActionType::Split, u64::MAX),
+            Err(VaultError::Overflow.into())
+        );
+        assert_eq!(position.balance, 2);
+    }
+
+    #[test]
+    fn distribution_cursor_layout() {
+        let cursor = DistributionCursor::new(
+            Pubkey::new_from_array([35; 32]),
+            36,
+            [37; 32],
+            Pubkey::new_from_array([38; 32]),
+            39,
+            250,
+        );
+        assert!(cursor.is_initialized());
+        assert_eq!(
+            get_packed_len::<DistributionCursor>(),
+            DistributionCursor::LEN
+        );
+        assert_eq!(
+            borsh::to_vec(&cursor).unwrap().len(),
+            DistributionCursor::LEN
+        );
+        assert_eq!(
+            DistributionCursor::SPL_DISCRIMINATOR_SLICE,
+            &hash(b"account:DistributionCursor").to_bytes()[..8]
+        );
+    }
+
+    #[test]
+    fn distribution_cursor_advances_in_order() {
+        let mut cursor =
+            DistributionCursor::new(Pubkey::default(), 1, [1; 32], Pubkey::default(), 5, 250);
+        let [a, b] = [1, 2].map(|n| Pubkey::new_from_array([n; 32]));
+        cursor.advance(a, 10).unwrap();
+        cursor.advance(b, 15).unwrap();
+        for vault in [a, b, Pubkey::default()] {
+            assert_eq!(
+                cursor.advance(vault, 1),
+                Err(VaultError::DistributionOutOfOrder.into())
+            );
+        }
+        assert_eq!(
+            (cursor.last_vault, cursor.paid_vaults, cursor.total_paid),
+            (b, 2, 25)
+        );
+    }
+
+    #[test]
+    fn snapshot_record_layout() {
+        let position = PositionRecord::new(Pubkey::new_from_array([40; 32]), [41; 32], 42, 43, 250);
+        let snapshot =
+            SnapshotRecord::new(position.vault, 44, 45, &TEST_RECORD_DATA, &position, 250);
+        assert!(snapshot.is_initialized());
+        assert_eq!(snapshot.authority, TEST_RECORD_DATA.authority);
+        assert_eq!((snapshot.asset_id, snapshot.balance), ([41; 32], 42));
+        assert_eq!(get_packed_len::<SnapshotRecord>(), SnapshotRecord::LEN);
+        assert_eq!(borsh::to_vec(&snapshot).unwrap().len(), SnapshotRecord::LEN);
+        assert_eq!(
+            SnapshotRecord::SPL_DISCRIMINATOR_SLICE,
+            &hash(b"account:SnapshotRecord").to_bytes()[..8]
+        );
+    }
+
+    #[test]
+    fn voting_proxy_layout() {
+        let proxy = Pubkey::new_from_array([46; 32]);
+        let mut voting = VotingProxy::new(
+            Pubkey::new_from_array([47; 32]),
+            TEST_RECORD_DATA.authority,
+            proxy,
+            250,
+        );
+        assert!(voting.is_initialized());
+        assert_eq!(get_packed_len::<VotingProxy>(), VotingProxy::LEN);
+        assert_eq!(borsh::to_vec(&voting).unwrap().len(), VotingProxy::LEN);
+        assert_eq!(
+            VotingProxy::SPL_DISCRIMINATOR_SLICE,
+            &hash(b"account:VotingProxy").to_bytes()[..8]
+        );
+
+        assert_eq!(voting.voter(&TEST_RECORD_DATA), proxy);
+        let mut record = TEST_RECORD_DATA;
+        record.authority = Pubkey::new_from_array([48; 32]);
+        assert_eq!(voting.voter(&record), record.authority);
+        voting.proxy = Pubkey::default();
+        assert_eq!(voting.voter(&TEST_RECORD_DATA), TEST_RECORD_DATA.authority);
+    }
+
+    #[test]
+    fn transfer_window_slots() {
+        let blackout = BlackoutRange {
+            start_slot: 200,
+            end_slot: 300,
+        };
+        let mut blackouts = [BlackoutRange::default(); MAX_BLACKOUTS];
+        blackouts[1] = blackout;
+        let window = TransferWindow::new(Pubkey::new_from_array([49; 32]), 100, blackouts, 250);
+        assert!(window.is_initialized());
+        assert_eq!(get_packed_len::<TransferWindow>(), TransferWindow::LEN);
+        assert_eq!(borsh::to_vec(&window).unwrap().len(), TransferWindow::LEN);
+        assert_eq!(
+            TransferWindow::SPL_DISCRIMINATOR_SLICE,
+            &hash(b"account:TransferWindow").to_bytes()[..8]
+        );
+
+        assert!(window.is_restricted());
+        assert!(!window.is_open(99));
+        assert!(window.is_open(100));
+        assert!(!window.is_open(200));
+        assert!(!window.is_open(299));
+        assert!(window.is_open(300));
+
+        let open = TransferWindow::new(Pubkey::default(), 0, Default::default(), 250);
+        assert!(!open.is_restricted());
+        assert!(open.is_open(0));
+    }
+
+    #[test]
+    fn rate_limit_resets_each_epoch() {
+        let mut limit = RateLimit::new(Pubkey::new_from_array([50; 32]), 2, 250);
+        assert!(limit.is_initialized());
+        assert_eq!(get_packed_len::<RateLimit>(), RateLimit::LEN);
+        assert_eq!(borsh::to_vec(&limit).unwrap().len(), RateLimit::LEN);
+        assert_eq!(
+            RateLimit::SPL_DISCRIMINATOR_SLICE,
+            &hash(b"account:RateLimit").to_bytes()[..8]
+        );
+
+        assert_eq!(limit.consume(3), Ok(()));
+        assert_eq!(limit.consume(3), Ok(()));
+        assert_eq!(limit.consume(3), Err(VaultError::RateLimited));
+        assert_eq!(limit.consume(4), Ok(()));
+        assert_eq!((limit.epoch, limit.count), (4, 1));
+
+        limit.max_per_epoch = 0;
+        assert_eq!(limit.consume(5), Err(VaultError::RateLimited));
+    }
+
+    #[test]
+    fn dart_policy_defaults() {
+        let defaults = PolicyDefaults {
+            tag: 7,
+            oracle: Pubkey::new_from_array([51; 32]),
+            oracle_max_staleness_slots: 25,
+            oracle_min_price: -3,
+            close_recipient: Pubkey::default(),
+            cosign: true,
+            close_fee: 5000,
+            restrictions: restriction::NO_SESSIONS,
+        };
+        let policy = DartPolicy::new(DART_PUBKEY, defaults, 250);
+        assert!(policy.is_initialized());
+        assert_eq!(get_packed_len::<DartPolicy>(), DartPolicy::LEN);
+        assert_eq!(borsh::to_vec(&policy).unwrap().len(), DartPolicy::LEN);
+        assert_eq!(
+            DartPolicy::SPL_DISCRIMINATOR_SLICE,
+            &hash(b"account:DartPolicy").to_bytes()[..8]
+        );
+
+        let mut record = VaultRecord::new(AUTH_PUBKEY, DART_PUBKEY);
+        record.close_recipient = Pubkey::new_from_array([52; 32]);
+        policy.defaults.apply(&mut record);
+        assert_eq!(record.tag, 7);
+        assert_eq!(record.oracle, defaults.oracle);
+        assert_eq!(
+            (record.oracle_max_staleness_slots, record.oracle_min_price),
+            (25, -3)
+        );
+        assert!(record.cosign);
+        assert_eq!(record.close_fee, 5000);
+        assert_eq!(record.restrictions, restriction::NO_SESSIONS);
+        // Unset defaults leave the record's setting alone.
+        assert_eq!(record.close_recipient, Pubkey::new_from_array([52; 32]));
+
+        let mut clone = VaultRecord::new(Pubkey::new_from_array([53; 32]), DART_PUBKEY);
+        PolicyDefaults::of(&record).apply(&mut clone);
+        assert_eq!(PolicyDefaults::of(&clone), PolicyDefaults::of(&record));
+    }
+
+    #[test]
+    fn vault_index_slots() {
+        let mut index = VaultIndex::new(AUTH_PUBKEY, 250);
+        assert!(index.is_initialized());
+        assert_eq!(get_packed_len::<VaultIndex>(), VaultIndex::LEN);
+        assert_eq!(borsh::to_vec(&index).unwrap().len(), VaultIndex::LEN);
+        assert_eq!(
+            VaultIndex::SPL_DISCRIMINATOR_SLICE,
+            &hash(b"account:VaultIndex").to_bytes()[..8]
+        );
+
+        let vaults: Vec<Pubkey> = (1..=MAX_INDEXED_VAULTS as u8)
+            .map(|byte| Pubkey::new_from_array([byte; 32]))
+            .collect();
+        for vault in &vaults {
+            index.insert(*vault).unwrap();
+        }
+        index.insert(vaults[0]).unwrap();
+        assert_eq!(
+            index.insert(Pubkey::new_unique()),
+            Err(VaultError::VaultIndexFull.into())
+        );
+
+        assert!(index.remove(&vaults[3]));
+        assert!(!index.remove(&vaults[3]));
+        index.insert(Pubkey::new_from_array([200; 32])).unwrap();
+        assert_eq!(index.vaults[3], Pubkey::new_from_array([200; 32]));
+        assert_eq!(index.vaults().count(), MAX_INDEXED_VAULTS);
+    }
+
+    #[test]
+    fn program_stats_count_in_place() {
+        let stats = ProgramStats::new(250);
+        assert!(stats.is_initialized());
+        assert_eq!(get_packed_len::<ProgramStats>(), ProgramStats::LEN);
+        assert_eq!(
+            ProgramStats::SPL_DISCRIMINATOR_SLICE,
+            &hash(b"account:ProgramStats").to_bytes()[..8]
+        );
+
+        let mut data = borsh::to_vec(&stats).unwrap();
+        ProgramStats::count(&mut data, 0).unwrap();
+        ProgramStats::count(&mut data, 5).unwrap();
+        ProgramStats::count(&mut data, 5).unwrap();
+        ProgramStats::count(&mut data, (MAX_STATS_TAGS - 1) as u8).unwrap();
+        let counted = ProgramStats::try_from_slice(&data).unwrap();
+        assert_eq!(counted.invocations[0], 1);
+        assert_eq!(counted.invocations[5], 2);
+        assert_eq!(counted.invocations[MAX_STATS_TAGS - 1], 1);
+        assert_eq!(counted.invocations.iter().sum::<u64>(), 4);
+        assert_eq!(counted.bump, 250);
+
+        assert_eq!(
+            ProgramStats::count(&mut data, MAX_STATS_TAGS as u8),
+            Err(ProgramError::InvalidAccountData)
+        );
+        assert_eq!(
+            ProgramStats::count(&mut data[1..], 0),
+            Err(ProgramError::InvalidAccountData)
+        );
+    }
+
+    #[test]
+    fn fee_config_exemptions() {
+        let mut config = FeeConfig::new(251);
+        assert!(config.is_initialized());
+        assert_eq!(get_packed_len::<FeeConfig>(), FeeConfig::LEN);
+        assert_eq!(borsh::to_vec(&config).unwrap().len(), FeeConfig::LEN);
+        assert_eq!(
+            FeeConfig::SPL_DISCRIMINATOR_SLICE,
+            &hash(b"account:FeeConfig").to_bytes()[..8]
+        );
+
+        let record = VaultRecord::new(AUTH_PUBKEY, DART_PUBKEY);
+        assert!(!config.is_exempt(&record));
+        config.exempt[3] = DART_PUBKEY;
+        assert!(config.is_exempt(&record));
+        config.exempt[3] = Pubkey::default();
+        config.exempt[MAX_FEE_EXEMPT - 1] = AUTH_PUBKEY;
+        assert!(config.is_exempt(&record));
+        assert!(!config.is_exempt(&VaultRecord::new(
+            Pubkey::new_from_array([60; 32]),
+            DART_PUBKEY
+        )));
+    }
+
+    #[test]
+    fn fee_config_close_fees() {
+        let mut config = FeeConfig::new(251);
+        let mut record = VaultRecord::new(AUTH_PUBKEY, DART_PUBKEY);
+        assert_eq!(config.close_fee(&record, 0), 0);
+        config.default_close_fee = 5_000;
+        assert_eq!(config.close_fee(&record, 0), 5_000);
+
+        record.tag = 7;
+        config.close_fee_tiers[1] = CloseFeeTier {
+            active: false,
+            tag: 7,
+            lamports: 9_000,
+            min_age_slots: 0,
+        };
+        assert_eq!(config.close_fee(&record, 0), 5_000);
+        config.close_fee_tiers[1].active = true;
+        assert_eq!(config.close_fee(&record, 0), 9_000);
+        // The first matching tier applies, a free tier included.
+        config.close_fee_tiers[0] = CloseFeeTier {
+            active: true,
+            tag: 7,
+            lamports: 0,
+            min_age_slots: 0,
+        };
+        assert_eq!(config.close_fee(&record, 0), 0);
+        record.tag = 8;
+        assert_eq!(config.close_fee(&record, 0), 5_000);
+        // The vault's own close fee replaces the default, but not a matching tier.
+        record.close_fee = 6_000;
+        assert_eq!(config.close_fee(&record, 0), 6_000);
+        record.tag = 7;
+        assert_eq!(config.close_fee(&record, 0), 0);
+        record.tag = 8;
+
+        config.exempt[0] = DART_PUBKEY;
+        assert_eq!(config.close_fee(&record, 0), 0);
+        assert_eq!(borsh::to_vec(&config).unwrap().len(), FeeConfig::LEN);
+    }
+
+    #[test]
+    fn fee_config_close_fees_by_age() {
+        let mut config = FeeConfig::new(251);
+        config.close_fee_tiers[0] = CloseFeeTier {
+            active: true,
+            tag: 0,
+            lamports: 1_000,
+            min_age_slots: 100,
+        };
+        config.close_fee_tiers[1] = CloseFeeTier {
+            active: true,
+            tag: 0,
+            lamports: 9_000,
+            min_age_slots: 0,
+        };
+        let mut record = VaultRecord::new(AUTH_PUBKEY, DART_PUBKEY);
+        record.created_slot = 50;
+        assert_eq!(config.close_fee(&record, 50), 9_000);
+        assert_eq!(config.close_fee(&record, 149), 9_000);
+        assert_eq!(config.close_fee(&record, 150), 1_000);
+
+        // Records from before creation slots count as created at slot zero.
+        record.created_slot = 0;
+        assert_eq!(config.close_fee(&record, 100), 1_000);
+    }
+
+    #[test]
+    fn audit_trail_ring() {
+        let mut trail = AuditTrail::new(AUTH_PUBKEY, 3, 252);
+        assert!(trail.is_initialized());
+        assert_eq!(get_packed_len::<AuditTrail>(), AuditTrail::LEN);
+        assert_eq!(get_packed_len::<AuditEntry>(), AuditEntry::LEN);
+        assert_eq!(
+            AuditTrail::SPL_DISCRIMINATOR_SLICE,
+            &hash(b"account:AuditTrail").to_bytes()[..8]
+        );
+
+        let mut data = vec![0; AuditTrail::space(3)];
+        for slot in 1..=4 {
+            let entry = AuditEntry {
+                slot,
+                action: slot as u8,
+                actor: DART_PUBKEY,
+            };
+            assert_eq!(trail.push(&mut data, &entry), Ok(slot - 1));
+        }
+        assert_eq!(AuditTrail::from_account_data(&data).unwrap(), trail);
+        let entries: Vec<_> = trail.entries(&data).map(Result::unwrap).collect();
+        assert_eq!(
+            entries
+                .iter()
+                .map(|(sequence, entry)| (*sequence, entry.slot))
+                .collect::<Vec<_>>(),
+            vec![(1, 2), (2, 3), (3, 4)]
+        );
+
+        let mut short = vec![0; AuditTrail::space(2)];
+        assert_eq!(
+            trail.push(&mut short, &AuditEntry::default()),
+            Err(VaultError::InvalidDataLength.into())
+        );
+    }
+
+    #[test]
+    fn attestation_list_entries() {
+        let mut list = AttestationList::new(Pubkey::new_from_array([28; 32]), 250);
+        let attestation = |n: u8, expires_at_slot| Attestation {
+            kind: 1,
+            hash: [n; 32],
+            expires_at_slot,
+        };
+        for n in 1..=MAX_ATTESTATIONS as u8 {
+            list.add(attestation(n, 100 + u64::from(n)), 10).unwrap();
+        }
+        assert_eq!(
+            list.add(attestation(20, 200), 10).unwrap_err(),
+            VaultError::AttestationListFull.into()
+        );
+        assert_eq!(list.live_attestation(1, 10), Some(&attestation(8, 108)));
+        assert_eq!(list.live_attestation(2, 10), None);
+
+        // Renewing keeps one entry; expired entries are reused.
+        list.add(attestation(1, 300), 10).unwrap();
+        assert_eq!(list.live_attestation(1, 10), Some(&attestation(1, 300)));
+        list.add(attestation(20, 200), 103).unwrap();
+        assert!(list.attestations.contains(&attestation(20, 200)));
+        assert!(!list.attestations.contains(&attestation(2, 102)));
+        assert_eq!(list.live_attestation(1, 250), Some(&attestation(1, 300)));
+        assert_eq!(list.live_attestation(1, 301), None);
+
+        list.remove(1, &[1; 32]).unwrap();
+        assert_eq!(
+            list.remove(1, &[1; 32]).unwrap_err(),
+            VaultError::AttestationNotFound.into()
+        );
+        assert_eq!(
+            list.remove(0, &[0; 32]).unwrap_err(),
+            VaultError::AttestationNotFound.into()
+        );
+    }
+
+    #[test]
+    fn upgrade_authority_of_program_data() {
+        let authority = Pubkey::new_from_array([17; 32]);
+        let mut data = vec![3, 0, 0, 0];
+        data.extend_from_slice(&42u64.to_le_bytes());
+        data.push(1);
+        data.extend_from_slice(authority.as_ref());
+        assert_eq!(
+            data.len(),
+            bpf_loader_upgradeable::UpgradeableLoaderState::size_of_programdata_metadata()
+        );
+        assert_eq!(upgrade_authority(&data), Some(authority));
+        // Program bytes follow the metadata.
+        data.extend_from_slice(&[0xff; 16]);
+        assert_eq!(upgrade_authority(&data), Some(authority));
+
+        // Immutable programs, other account kinds and short data have none.
+        let mut immutable = data[..12].to_vec();
+        immutable.push(0);
+        assert_eq!(upgrade_authority(&immutable), None);
+        data[0] = 2;
+        assert_eq!(upgrade_authority(&data), None);
+        assert_eq!(upgrade_authority(&[3, 0, 0, 0]), None);
+    }
+
+    #[test]
+    fn status_transitions() {
+        use VaultStatus::*;
+        let statuses = [Active, Frozen, PendingTransfer, Seized, Closed];
+        let allowed = [
+            (Active, Frozen),
+            (Active, PendingTransfer),
+            (Active, Seized),
+            (Active, Closed),
+            (Frozen, Active),
+            (Frozen, Seized),
+            (PendingTransfer, Active),
+            (PendingTransfer, Frozen),
+            (PendingTransfer, Seized),
+            (Seized, Active),
+            (Seized, Closed),
+            (Closed, Active),
+        ];
+        for from in statuses {
+            for to in statuses {
+                let expected = allowed.contains(&(from, to));
+                assert_eq!(from.can_transition_to(to), expected, "{from:?} -> {to:?}");
+
+                let mut status = from;
+                if expected {
+                    assert_eq!(status.transition_to(to), Ok(()));
+                    assert_eq!(status, to);
+                } else {
+                    assert_eq!(
+                        status.transition_to(to),
+                        Err(VaultError::InvalidStatusTransition)
+                    );
+                    assert_eq!(status, from);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn serialize_status() {
+        assert_eq!(borsh::to_vec(&VaultStatus::Active).unwrap(), vec![0]);
+        assert_eq!(borsh::to_vec(&VaultStatus::Closed).unwrap(), vec![4]);
+        assert_eq!(get_packed_len::<VaultStatus>(), size_of::<VaultStatus>());
+    }
+}
+
+#[cfg(kani)]
+impl kani::Arbitrary for VaultStatus {
+    fn any() -> Self {
+        match kani::any::<u8>() % 5 {
+            0 => Self::Active,
+            1 => Self::Frozen,
+            2 => Self::PendingTransfer,
+            3 => Self::Seized,
+            _ => Self::Closed,
+        }
+    }
+}
+
+#[cfg(kani)]
+mod verification {
+    use super::*;
+
+    #[kani::proof]
+    fn transition_changes_status_only_when_allowed() {
+        let from: VaultStatus = kani::any();
+        let next: VaultStatus = kani::any();
+        let mut status = from;
+        match status.transition_to(next) {
+            Ok(()) => {
+                assert!(from.can_transition_to(next));
+                assert_eq!(status, next);
+            }
+            Err(error) => {
+                assert!(!from.can_transition_to(next));
+                assert_eq!(error, VaultError::InvalidStatusTransition);
+                assert_eq!(status, from);
+            }
+        }
+    }
+
+    #[kani::proof]
+    fn closed_only_reopens() {
+        let next: VaultStatus = kani::any();
+        assert_eq!(
+            VaultStatus::Closed.can_transition_to(next),
+            next == VaultStatus::Active
+        );
     }
 }
@@ -0,0 +1,269 @@
+//! `ProgramTest` helpers for suites that exercise the vault, including downstream programs
+//! that CPI into it.
+use {
+    crate::{
+        id, instruction,
+        processor::Processor,
+        state::{DartIdentity, VaultRecord, VaultStatus},
+    },
+    solana_program::{
+        clock::Clock, hash::Hash, instruction::Instruction, pubkey::Pubkey, rent::Rent,
+        system_instruction,
+    },
+    solana_program_test::{processor, BanksClient, ProgramTest, ProgramTestContext},
+    solana_sdk::{
+        signature::{Keypair, Signer},
+        transaction::{Transaction, TransactionError},
+    },
+};
+
+/// A `ProgramTest` running the vault program.
+pub fn program_test() -> ProgramTest {
+    ProgramTest::new("vault", id(), processor!(Processor::process_instruction))
+}
+
+/// Register the vault program with another program's `ProgramTest`.
+pub fn add_vault_program(program_test: &mut ProgramTest) {
+    program_test.add_program("vault", id(), processor!(Processor::process_instruction));
+}
+
+/// Fresh keypairs for each vault role.
+pub struct VaultKeypairs {
+    pub pda: Keypair,
+    pub dart: Keypair,
+    pub authority: Keypair,
+}
+
+impl VaultKeypairs {
+    pub fn new() -> Self {
+        Self {
+            pda: Keypair::new(),
+            dart: Keypair::new(),
+            authority: Keypair::new(),
+        }
+    }
+}
+
+impl Default for VaultKeypairs {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Vault shortcuts for `BanksClient`; failures panic, as in any other test assertion.
+#[allow(async_fn_in_trait)]
+pub trait VaultBanksClientExt {
+    /// Fetch and decode the vault record at `pda`.
+    async fn get_vault_record(&mut self, pda: Pubkey) -> VaultRecord;
+
+    /// Assert that the vault at `pda` is closed: either drained and purged, or tombstoned.
+    async fn assert_vault_closed(&mut self, pda: Pubkey);
+
+    /// Fund a rent-exempt vault account from `payer` and initialize it.
+    async fn airdrop_and_create_vault(
+        &mut self,
+        payer: &Keypair,
+        recent_blockhash: Hash,
+        pda: &Keypair,
+        dart: &Keypair,
+        authority: &Keypair,
+    );
+}
+
+impl VaultBanksClientExt for BanksClient {
+    async fn get_vault_record(&mut self, pda: Pubkey) -> VaultRecord {
+        let account = self.get_account(pda).await.unwrap().unwrap();
+        VaultRecord::from_account_data(&account.data).unwrap()
+    }
+
+    async fn assert_vault_closed(&mut self, pda: Pubkey) {
+        if let Some(account) = self.get_account(pda).await.unwrap() {
+            let record = VaultRecord::from_account_data(&account.data).unwrap();
+            assert_eq!(record.status, VaultStatus::Closed, "vault {pda} not closed");
+        }
+    }
+
+    async fn airdrop_and_create_vault(
+        &mut self,
+        payer: &Keypair,
+        recent_blockhash: Hash,
+        pda: &Keypair,
+        dart: &Keypair,
+        authority: &Keypair,
+    ) {
+        let space = VaultRecord::LEN;
+        let lamports = Rent::default().minimum_balance(space);
+
+        let transaction = Transaction::new_signed_with_payer(
+            &[
+                system_instruction::create_account(
+                    &payer.pubkey(),
+                    &pda.pubkey(),
+                    lamports,
+                    space as u64,
+                    &id(),
+                ),
+                instruction::initialize(id(), &pda.pubkey(), &dart.pubkey(), &authority.pubkey()),
+            ],
+            Some(&payer.pubkey()),
+            &[payer, pda, dart],
+            recent_blockhash,
+        );
+        self.process_transaction(transaction).await.unwrap();
+    }
+}
+
+/// Create a rent-exempt vault account and initialize it, paid for by the context payer.
+pub async fn initialize_account(
+    context: &mut ProgramTestContext,
+    pda: &Keypair,
+    dart: &Keypair,
+    authority: &Keypair,
+) {
+    context
+        .banks_client
+        .airdrop_and_create_vault(&context.payer, context.last_blockhash, pda, dart, authority)
+        .await;
+}
+
+/// Create a DART identity owned by `master` and fill its first operational key slots.
+pub async fn initialize_dart_identity(
+    context: &mut ProgramTestContext,
+    identity: &Keypair,
+    master: &Keypair,
+    operational_keys: &[Pubkey],
+) {
+    let space = DartIdentity::LEN;
+    let lamports = Rent::default().minimum_balance(space);
+
+    let mut instructions = vec![
+        system_instruction::create_account(
+            &context.payer.pubkey(),
+            &identity.pubkey(),
+            lamports,
+            space as u64,
+            &id(),
+        ),
+        instruction::initialize_dart_identity(id(), &identity.pubkey(), &master.pubkey()),
+    ];
+    for (slot, key) in operational_keys.iter().enumerate() {
+        instructions.push(instruction::set_operational_key(
+            id(),
+            &identity.pubkey(),
+            &master.pubkey(),
+            slot as u8,
+            *key,
+        ));
+    }
+    let transaction = Transaction::new_signed_with_payer(
+        &instructions,
+        Some(&context.payer.pubkey()),
+        &[&context.payer, identity, master],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+}
+
+/// The slot of the bank `context` processes transactions in.
+pub async fn current_slot(context: &mut ProgramTestContext) -> u64 {
+    context
+        .banks_client
+        .get_sysvar::<Clock>()
+        .await
+        .unwrap()
+        .slot
+}
+
+/// Warp `context` to `slot` and take a fresh blockhash, so a transaction repeated after
+/// the warp is not rejected as already processed.
+pub async fn warp_to_slot(context: &mut ProgramTestContext, slot: u64) {
+    context.warp_to_slot(slot).unwrap();
+    context.last_blockhash = context.get_new_latest_blockhash().await.unwrap();
+}
+
+/// Warp `context` `slots` past its current slot, returning the new slot.
+pub async fn warp_forward(context: &mut ProgramTestContext, slots: u64) -> u64 {
+    let slot = current_slot(context).await + slots;
+    warp_to_slot(context, slot).await;
+    slot
+}
+
+/// A scripted slot progression: transactions sent at non-decreasing slots, each with
+/// its expected outcome, for deterministic tests of expiring and time-locked features.
+#[derive(Default)]
+pub struct SlotScenario {
+    steps: Vec<SlotStep>,
+}
+
+struct SlotStep {
+    slot: u64,
+    instructions: Vec<Instruction>,
+    signers: Vec<Keypair>,
+    expected: Result<(), TransactionError>,
+}
+
+impl SlotScenario {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Send `instructions`, paid by the context payer and signed by `signers`, at `slot`,
+    /// expecting success.
+    pub fn at(mut self, slot: u64, instructions: Vec<Instruction>, signers: &[&Keypair]) -> Self {
+        self.steps.push(SlotStep {
+            slot,
+            instructions,
+            signers: signers
+                .iter()
+                .map(|signer| signer.insecure_clone())
+                .collect(),
+            expected: Ok(()),
+        });
+        self
+    }
+
+    /// Expect the transaction of the last step to fail with `error`.
+    pub fn fails_with(mut self, error: TransactionError) -> Self {
+        self.steps
+            .last_mut()
+            .expect("no step to expect a failure of")
+            .expected = Err(error);
+        self
+    }
+
+    /// Run the steps in order, warping to each step's slot; panics on an unexpected outcome.
+    pub async fn run(self, context: &mut ProgramTestContext) {
+        for (index, step) in self.steps.into_iter().enumerate() {
+            let slot = current_slot(context).await;
+            assert!(
+                step.slot >= slot,
+                "step {index} at slot {} is before the current slot {slot}",
+                step.slot
+            );
+            if step.slot > slot {
+                warp_to_slot(context, step.slot).await;
+            } else {
+                context.last_blockhash = context.get_new_latest_blockhash().await.unwrap();
+            }
+
+            let mut signers = vec![&context.payer];
+            signers.extend(&step.signers);
+            let transaction = Transaction::new_signed_with_payer(
+                &step.instructions,
+                Some(&context.payer.pubkey()),
+                &signers,
+                context.last_blockhash,
+            );
+            let result = context
+                .banks_client
+                .process_transaction(transaction)
+                .await
+                .map_err(|error| error.unwrap());
+            assert_eq!(result, step.expected, "step {index} at slot {}", step.slot);
+        }
+    }
+}
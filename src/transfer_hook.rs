@@ -0,0 +1,128 @@
+//! SPL transfer-hook interface: a Token-2022 mint whose transfer hook is this program
+//! routes every transfer through the compliance checks of a vault.
+//!
+//! Token-2022 invokes `Execute` with the accounts:
+//!
+//! 0. `[]` The source token account
+//! 1. `[]` The mint
+//! 2. `[]` The destination token account
+//! 3. `[]` The source account owner or delegate
+//! 4. `[]` The extra account metas, `[EXTRA_ACCOUNT_METAS_SEED, mint]`
+//! 5. `[]` The transfer hook config, `[TRANSFER_HOOK_SEED, mint]`
+//! 6. `[]` The vault record account
+//!
+//! Accounts 5 and 6 are resolved by the token program from the extra account metas
+//! written by `InitializeTransferHook`.
+use solana_program::{pubkey, pubkey::Pubkey};
+
+/// `sha256("spl-transfer-hook-interface:execute")[..8]`, which prefixes `Execute`
+/// instruction data and tags its extra account metas.
+pub const EXECUTE_DISCRIMINATOR: [u8; 8] = [0x69, 0x25, 0x65, 0xc5, 0x4b, 0xfb, 0x66, 0x1a];
+
+/// Seed prefix of the extra account metas the token program resolves:
+/// `[EXTRA_ACCOUNT_METAS_SEED, mint]`.
+pub const EXTRA_ACCOUNT_METAS_SEED: &[u8] = b"extra-account-metas";
+
+/// The Token-2022 program.
+pub const TOKEN_2022_PROGRAM_ID: Pubkey = pubkey!("TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb");
+
+/// Packed `ExtraAccountMeta`: discriminator, address config, signer and writable flags.
+const EXTRA_ACCOUNT_META_LEN: usize = 35;
+
+/// `ExtraAccountMeta` discriminator of a fixed address.
+const FIXED_ADDRESS: u8 = 0;
+
+/// Find the extra account metas address of `mint`.
+pub fn find_extra_account_metas_address(program_id: &Pubkey, mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[EXTRA_ACCOUNT_METAS_SEED, mint.as_ref()], program_id)
+}
+
+/// Whether `input` is an `Execute` instruction.
+pub fn is_execute(input: &[u8]) -> bool {
+    input.len() == EXECUTE_DISCRIMINATOR.len() + 8 && input.starts_with(&EXECUTE_DISCRIMINATOR)
+}
+
+/// Space of an extra account metas list of `count` entries.
+pub const fn extra_account_metas_len(count: usize) -> usize {
+    EXECUTE_DISCRIMINATOR.len() + 4 + 4 + count * EXTRA_ACCOUNT_META_LEN
+}
+
+/// Pack `addresses` as a TLV extra account metas list for `Execute`, each a fixed,
+/// readonly, non-signer account.
+pub fn pack_extra_account_metas(addresses: &[Pubkey]) -> Vec<u8> {
+    let value_len = 4 + addresses.len() * EXTRA_ACCOUNT_META_LEN;
+    let mut data = Vec::with_capacity(extra_account_metas_len(addresses.len()));
+    data.extend_from_slice(&EXECUTE_DISCRIMINATOR);
+    data.extend_from_slice(&(value_len as u32).to_le_bytes());
+    data.extend_from_slice(&(addresses.len() as u32).to_le_bytes());
+    for address in addresses {
+        data.push(FIXED_ADDRESS);
+        data.extend_from_slice(address.as_ref());
+        data.extend_from_slice(&[0, 0]);
+    }
+    data
+}
+
+/// Mint authority of a packed mint, shared by the SPL Token and Token-2022 layouts.
+pub fn mint_authority(data: &[u8]) -> Option<Pubkey> {
+    match data.get(..36)?.split_at(4) {
+        ([1, 0, 0, 0], authority) => Some(Pubkey::new_from_array(authority.try_into().ok()?)),
+        _ => None,
+    }
+}
+
+/// Owner of a packed token account, shared by the SPL Token and Token-2022 layouts.
+pub fn token_account_owner(data: &[u8]) -> Option<Pubkey> {
+    Some(Pubkey::new_from_array(data.get(32..64)?.try_into().ok()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, solana_program::hash::hash};
+
+    #[test]
+    fn execute_discriminator() {
+        let hash = hash(b"spl-transfer-hook-interface:execute");
+        assert_eq!(EXECUTE_DISCRIMINATOR, hash.as_ref()[..8]);
+
+        let mut input = EXECUTE_DISCRIMINATOR.to_vec();
+        assert!(!is_execute(&input));
+        input.extend_from_slice(&5u64.to_le_bytes());
+        assert!(is_execute(&input));
+        input[0] = 0;
+        assert!(!is_execute(&input));
+    }
+
+    #[test]
+    fn pack_fixed_extra_account_metas() {
+        let (config, vault) = (
+            Pubkey::new_from_array([3; 32]),
+            Pubkey::new_from_array([4; 32]),
+        );
+        let data = pack_extra_account_metas(&[config, vault]);
+        assert_eq!(data.len(), extra_account_metas_len(2));
+        assert_eq!(data[..8], EXECUTE_DISCRIMINATOR);
+        assert_eq!(data[8..12], 74u32.to_le_bytes());
+        assert_eq!(data[12..16], 2u32.to_le_bytes());
+        assert_eq!(data[16], FIXED_ADDRESS);
+        assert_eq!(data[17..49], config.to_bytes());
+        assert_eq!(data[49..51], [0, 0]);
+        assert_eq!(data[52..84], vault.to_bytes());
+    }
+
+    #[test]
+    fn read_token_layouts() {
+        let authority = Pubkey::new_from_array([9; 32]);
+        let mut mint = vec![0; 82];
+        assert_eq!(mint_authority(&mint), None);
+        mint[..4].copy_from_slice(&1u32.to_le_bytes());
+        mint[4..36].copy_from_slice(authority.as_ref());
+        assert_eq!(mint_authority(&mint), Some(authority));
+        assert_eq!(mint_authority(&mint[..35]), None);
+
+        let mut account = vec![0; 165];
+        account[32..64].copy_from_slice(authority.as_ref());
+        assert_eq!(token_account_owner(&account), Some(authority));
+        assert_eq!(token_account_owner(&account[..63]), None);
+    }
+}
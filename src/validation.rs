@@ -0,0 +1,132 @@
+//! Account checks behind every vault instruction. Programs embedding the vault as a
+//! library can use them to reject accounts with exactly the errors the vault returns.
+use {
+    crate::error::VaultError,
+    solana_program::{
+        account_info::AccountInfo, entrypoint::ProgramResult, msg, program_error::ProgramError,
+        program_pack::IsInitialized, pubkey::Pubkey,
+    },
+};
+
+/// Require `account` to have signed: `ProgramError::MissingRequiredSignature` otherwise.
+pub fn require_signer(account: &AccountInfo) -> ProgramResult {
+    if !account.is_signer {
+        msg!("Missing required signature of {}", account.key);
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    Ok(())
+}
+
+/// Require `account` to be owned by `owner`: `ProgramError::IncorrectProgramId` otherwise.
+pub fn require_owner(account: &AccountInfo, owner: &Pubkey) -> ProgramResult {
+    if account.owner != owner {
+        msg!("Account {} not owned by {}", account.key, owner);
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    Ok(())
+}
+
+/// Require `state`, read from `account`, to be initialized:
+/// `ProgramError::UninitializedAccount` otherwise.
+pub fn require_initialized<T: IsInitialized>(account: &AccountInfo, state: &T) -> ProgramResult {
+    if !state.is_initialized() {
+        msg!("Account {} not initialized", account.key);
+        return Err(ProgramError::UninitializedAccount);
+    }
+    Ok(())
+}
+
+/// Require `account` to be writable: `VaultError::ReadOnlyAccount` otherwise.
+pub fn require_writable(account: &AccountInfo) -> ProgramResult {
+    if !account.is_writable {
+        msg!("Account {} must be writable", account.key);
+        return Err(VaultError::ReadOnlyAccount.into());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct State(bool);
+
+    impl IsInitialized for State {
+        fn is_initialized(&self) -> bool {
+            self.0
+        }
+    }
+
+    fn check(
+        is_signer: bool,
+        is_writable: bool,
+        test: impl FnOnce(&AccountInfo) -> ProgramResult,
+    ) -> ProgramResult {
+        let key = Pubkey::new_unique();
+        let owner = crate::id();
+        let mut lamports = 0;
+        let mut data = [];
+        let account = AccountInfo::new(
+            &key,
+            is_signer,
+            is_writable,
+            &mut lamports,
+            &mut data,
+            &owner,
+            false,
+            0,
+        );
+        test(&account)
+    }
+
+    #[test]
+    fn require_signer_errors() {
+        assert_eq!(check(true, false, require_signer), Ok(()));
+        assert_eq!(
+            check(false, true, require_signer),
+            Err(ProgramError::MissingRequiredSignature)
+        );
+    }
+
+    #[test]
+    fn require_owner_errors() {
+        assert_eq!(
+            check(false, false, |account| require_owner(account, &crate::id())),
+            Ok(())
+        );
+        assert_eq!(
+            check(false, false, |account| require_owner(
+                account,
+                &Pubkey::new_unique()
+            )),
+            Err(ProgramError::IncorrectProgramId)
+        );
+    }
+
+    #[test]
+    fn require_initialized_errors() {
+        assert_eq!(
+            check(false, false, |account| require_initialized(
+                account,
+                &State(true)
+            )),
+            Ok(())
+        );
+        assert_eq!(
+            check(false, false, |account| require_initialized(
+                account,
+                &State(false)
+            )),
+            Err(ProgramError::UninitializedAccount)
+        );
+    }
+
+    #[test]
+    fn require_writable_errors() {
+        assert_eq!(check(false, true, require_writable), Ok(()));
+        assert_eq!(
+            check(true, false, require_writable),
+            Err(VaultError::ReadOnlyAccount.into())
+        );
+    }
+}
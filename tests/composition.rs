@@ -0,0 +1,290 @@
+#![cfg(feature = "test-sbf")]
+//! A mock settlement program composing with the vault through CPI. Its desk PDA is the DART
+//! of the vaults it opens, and its escrow PDA holds them as authority until it delivers them
+//! to a buyer, so every vault role it plays is signed with `invoke_signed`. The program builds
+//! its CPIs with `vault::instruction`, so a change to the instruction set that would break
+//! composing programs fails here.
+use {
+    borsh::{BorshDeserialize, BorshSerialize},
+    solana_program::{
+        account_info::AccountInfo,
+        entrypoint::ProgramResult,
+        instruction::{AccountMeta, Instruction, InstructionError},
+        program::invoke_signed,
+        pubkey::Pubkey,
+        system_program,
+    },
+    solana_program_test::*,
+    solana_sdk::{
+        signature::{Keypair, Signer},
+        transaction::{Transaction, TransactionError},
+    },
+    vault::{
+        error::VaultError,
+        id, instruction,
+        state::{
+            find_asset_vault_address, NotifyRecord, ProgramConfig, TransferWindow, VaultIndex,
+            VaultStatus, TRANSFER_WINDOW_DART,
+        },
+        test_utils::{program_test, VaultBanksClientExt},
+    },
+};
+
+/// Seed of the settlement desk, the DART of the vaults the program opens.
+const DESK_SEED: &[u8] = b"desk";
+/// Seed of the escrow, the authority of vaults awaiting delivery.
+const ESCROW_SEED: &[u8] = b"escrow";
+
+/// Instructions of the mock settlement program.
+#[derive(BorshSerialize, BorshDeserialize)]
+enum SettlementInstruction {
+    /// Open the escrowed vault of `asset_id` (`CreateAssetVault` by CPI).
+    ///
+    /// 0. `[]` The vault program
+    /// 1. `[writable]` The vault account, uncreated
+    /// 2. `[]` The desk
+    /// 3. `[]` The escrow
+    /// 4. `[signer, writable]` The rent payer
+    /// 5. `[]` The system program
+    /// 6. `[]` The vault program config
+    /// 7. `[writable]` The escrow's vault index
+    Open { asset_id: [u8; 32] },
+
+    /// Deliver an escrowed vault to a buyer (`TransferAuthority` by CPI).
+    ///
+    /// 0. `[]` The vault program
+    /// 1. `[writable]` The vault account
+    /// 2. `[]` The desk
+    /// 3. `[]` The escrow
+    /// 4. `[]` The buyer
+    /// 5. `[]` The vault program config
+    /// 6. `[]` The desk's transfer window
+    /// 7. `[]` The vault's notify record
+    /// 8. `[writable]` The escrow's vault index
+    /// 9. `[writable]` The buyer's vault index
+    Deliver,
+}
+
+fn settlement_process_instruction(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    input: &[u8],
+) -> ProgramResult {
+    let (desk, desk_bump) = Pubkey::find_program_address(&[DESK_SEED], program_id);
+    let (escrow, escrow_bump) = Pubkey::find_program_address(&[ESCROW_SEED], program_id);
+    let vault_program = *accounts[0].key;
+    let instruction = match SettlementInstruction::try_from_slice(input)? {
+        SettlementInstruction::Open { asset_id } => instruction::create_asset_vault(
+            vault_program,
+            &desk,
+            &escrow,
+            accounts[4].key,
+            asset_id,
+        ),
+        SettlementInstruction::Deliver => instruction::transfer_authority(
+            vault_program,
+            accounts[1].key,
+            &desk,
+            &escrow,
+            accounts[4].key,
+        ),
+    };
+    invoke_signed(
+        &instruction,
+        accounts,
+        &[&[DESK_SEED, &[desk_bump]], &[ESCROW_SEED, &[escrow_bump]]],
+    )
+}
+
+fn desk(settlement: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[DESK_SEED], settlement).0
+}
+
+fn escrow(settlement: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[ESCROW_SEED], settlement).0
+}
+
+fn escrowed_vault(settlement: &Pubkey, asset_id: &[u8; 32]) -> Pubkey {
+    find_asset_vault_address(&id(), &desk(settlement), &escrow(settlement), asset_id).0
+}
+
+fn open(settlement: &Pubkey, payer: &Pubkey, asset_id: [u8; 32]) -> Instruction {
+    Instruction::new_with_borsh(
+        *settlement,
+        &SettlementInstruction::Open { asset_id },
+        vec![
+            AccountMeta::new_readonly(id(), false),
+            AccountMeta::new(escrowed_vault(settlement, &asset_id), false),
+            AccountMeta::new_readonly(desk(settlement), false),
+            AccountMeta::new_readonly(escrow(settlement), false),
+            AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(ProgramConfig::find_address(&id()).0, false),
+            AccountMeta::new(
+                VaultIndex::find_address(&id(), &escrow(settlement)).0,
+                false,
+            ),
+        ],
+    )
+}
+
+fn deliver(settlement: &Pubkey, vault: &Pubkey, buyer: &Pubkey) -> Instruction {
+    Instruction::new_with_borsh(
+        *settlement,
+        &SettlementInstruction::Deliver,
+        vec![
+            AccountMeta::new_readonly(id(), false),
+            AccountMeta::new(*vault, false),
+            AccountMeta::new_readonly(desk(settlement), false),
+            AccountMeta::new_readonly(escrow(settlement), false),
+            AccountMeta::new_readonly(*buyer, false),
+            AccountMeta::new_readonly(ProgramConfig::find_address(&id()).0, false),
+            AccountMeta::new_readonly(
+                TransferWindow::find_address(&id(), TRANSFER_WINDOW_DART, &desk(settlement)).0,
+                false,
+            ),
+            AccountMeta::new_readonly(NotifyRecord::find_address(&id(), vault).0, false),
+            AccountMeta::new(
+                VaultIndex::find_address(&id(), &escrow(settlement)).0,
+                false,
+            ),
+            AccountMeta::new(VaultIndex::find_address(&id(), buyer).0, false),
+        ],
+    )
+}
+
+async fn start(settlement: &Pubkey) -> ProgramTestContext {
+    let mut program_test = program_test();
+    program_test.add_program(
+        "settlement",
+        *settlement,
+        processor!(settlement_process_instruction),
+    );
+    program_test.start_with_context().await
+}
+
+async fn process(
+    context: &mut ProgramTestContext,
+    instruction: Instruction,
+) -> Result<(), TransactionError> {
+    context.last_blockhash = context.get_new_latest_blockhash().await.unwrap();
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction],
+        Some(&context.payer.pubkey()),
+        &[&context.payer],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .map_err(|error| error.unwrap())
+}
+
+#[tokio::test]
+async fn settlement_opens_escrowed_vault() {
+    let settlement = Pubkey::new_unique();
+    let mut context = start(&settlement).await;
+
+    let payer = context.payer.pubkey();
+    process(&mut context, open(&settlement, &payer, [1; 32]))
+        .await
+        .unwrap();
+
+    let vault = escrowed_vault(&settlement, &[1; 32]);
+    let record = context.banks_client.get_vault_record(vault).await;
+    assert_eq!(record.status, VaultStatus::Active);
+    assert_eq!(record.dart, desk(&settlement));
+    assert_eq!(record.authority, escrow(&settlement));
+    assert_eq!(record.rent_payer, payer);
+}
+
+#[tokio::test]
+async fn settlement_delivers_vault() {
+    let settlement = Pubkey::new_unique();
+    let mut context = start(&settlement).await;
+
+    let payer = context.payer.pubkey();
+    process(&mut context, open(&settlement, &payer, [1; 32]))
+        .await
+        .unwrap();
+    let vault = escrowed_vault(&settlement, &[1; 32]);
+    let buyer = Pubkey::new_unique();
+    process(&mut context, deliver(&settlement, &vault, &buyer))
+        .await
+        .unwrap();
+
+    let record = context.banks_client.get_vault_record(vault).await;
+    assert_eq!(record.authority, buyer);
+    assert_eq!(record.dart, desk(&settlement));
+}
+
+#[tokio::test]
+async fn settlement_fail_deliver_twice() {
+    let settlement = Pubkey::new_unique();
+    let mut context = start(&settlement).await;
+
+    let payer = context.payer.pubkey();
+    process(&mut context, open(&settlement, &payer, [1; 32]))
+        .await
+        .unwrap();
+    let vault = escrowed_vault(&settlement, &[1; 32]);
+    process(
+        &mut context,
+        deliver(&settlement, &vault, &Pubkey::new_unique()),
+    )
+    .await
+    .unwrap();
+
+    // Once delivered, the escrow no longer signs for the vault.
+    assert_eq!(
+        process(
+            &mut context,
+            deliver(&settlement, &vault, &Pubkey::new_unique())
+        )
+        .await,
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::IncorrectAuthority as u32)
+        ))
+    );
+}
+
+#[tokio::test]
+async fn settlement_fail_deliver_other_dart_vault() {
+    let settlement = Pubkey::new_unique();
+    let mut context = start(&settlement).await;
+
+    // A vault escrowed with the program, but held through another DART.
+    let dart = Keypair::new();
+    let vault = find_asset_vault_address(&id(), &dart.pubkey(), &escrow(&settlement), &[2; 32]).0;
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::create_asset_vault(
+            id(),
+            &dart.pubkey(),
+            &escrow(&settlement),
+            &context.payer.pubkey(),
+            [2; 32],
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &dart],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        process(
+            &mut context,
+            deliver(&settlement, &vault, &Pubkey::new_unique())
+        )
+        .await,
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::IncorrectDart as u32)
+        ))
+    );
+}
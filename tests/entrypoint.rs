@@ -0,0 +1,175 @@
+//! `input::deserialize_into`, the `no-alloc-entrypoint` deserializer, against the
+//! `deserialize` of the default entrypoint: the same accounts from the same input, with
+//! the allocations of each counted.
+use {
+    solana_program::{
+        account_info::AccountInfo,
+        entrypoint::{deserialize, BPF_ALIGN_OF_U128, MAX_PERMITTED_DATA_INCREASE, NON_DUP_MARKER},
+        pubkey::Pubkey,
+    },
+    std::{
+        alloc::{GlobalAlloc, Layout, System},
+        cell::Cell,
+        mem::MaybeUninit,
+    },
+    vault::input::{self, MAX_STACK_ACCOUNTS},
+};
+
+// Counts the allocations of the current thread, so tests running alongside do not
+// skew each other.
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+fn allocations<T>(f: impl FnOnce() -> T) -> (T, usize) {
+    let before = ALLOCATIONS.with(Cell::get);
+    let result = f();
+    (result, ALLOCATIONS.with(Cell::get) - before)
+}
+
+struct Account {
+    key: Pubkey,
+    owner: Pubkey,
+    lamports: u64,
+    data: Vec<u8>,
+    is_signer: bool,
+    is_writable: bool,
+}
+
+enum Input {
+    Account(Account),
+    // A duplicate of the account at this index.
+    Duplicate(u8),
+}
+
+// Serialize `accounts` as the runtime does for the program entrypoint. The buffer is
+// `u64`s so reads of its fields are aligned.
+fn serialize(accounts: &[Input], instruction_data: &[u8], program_id: &Pubkey) -> Vec<u64> {
+    let mut bytes = (accounts.len() as u64).to_le_bytes().to_vec();
+    for account in accounts {
+        match account {
+            Input::Account(account) => {
+                bytes.extend([
+                    NON_DUP_MARKER,
+                    account.is_signer as u8,
+                    account.is_writable as u8,
+                    0,
+                ]);
+                bytes.extend([0; 4]);
+                bytes.extend(account.key.as_ref());
+                bytes.extend(account.owner.as_ref());
+                bytes.extend(account.lamports.to_le_bytes());
+                bytes.extend((account.data.len() as u64).to_le_bytes());
+                bytes.extend(&account.data);
+                bytes.resize(bytes.len() + MAX_PERMITTED_DATA_INCREASE, 0);
+                bytes.resize(bytes.len().next_multiple_of(BPF_ALIGN_OF_U128), 0);
+                bytes.extend(7u64.to_le_bytes());
+            }
+            Input::Duplicate(index) => bytes.extend([*index, 0, 0, 0, 0, 0, 0, 0]),
+        }
+    }
+    bytes.extend((instruction_data.len() as u64).to_le_bytes());
+    bytes.extend(instruction_data);
+    bytes.extend(program_id.as_ref());
+    bytes.resize(bytes.len().next_multiple_of(8), 0);
+    bytes
+        .chunks_exact(8)
+        .map(|chunk| u64::from_le_bytes(chunk.try_into().unwrap()))
+        .collect()
+}
+
+fn accounts(len: usize) -> Vec<Input> {
+    (0..len)
+        .map(|index| {
+            // Every third account repeats the one before it.
+            if index % 3 == 2 {
+                return Input::Duplicate(index as u8 - 1);
+            }
+            Input::Account(Account {
+                key: Pubkey::new_unique(),
+                owner: Pubkey::new_unique(),
+                lamports: index as u64 * 1_000,
+                data: vec![index as u8; index * 5],
+                is_signer: index % 2 == 0,
+                is_writable: index % 4 < 2,
+            })
+        })
+        .collect()
+}
+
+fn assert_same(left: &[AccountInfo], right: &[AccountInfo]) {
+    assert_eq!(left.len(), right.len());
+    for (left, right) in left.iter().zip(right) {
+        assert_eq!(left.key, right.key);
+        assert_eq!(left.owner, right.owner);
+        assert_eq!(left.lamports(), right.lamports());
+        assert_eq!(*left.data.borrow(), *right.data.borrow());
+        assert_eq!(left.is_signer, right.is_signer);
+        assert_eq!(left.is_writable, right.is_writable);
+        assert_eq!(left.executable, right.executable);
+        assert_eq!(left.rent_epoch, right.rent_epoch);
+    }
+}
+
+#[test]
+fn deserialize_into_matches_deserialize() {
+    let program_id = Pubkey::new_unique();
+    let accounts = accounts(MAX_STACK_ACCOUNTS);
+    let distinct = accounts
+        .iter()
+        .filter(|account| matches!(account, Input::Account(_)))
+        .count();
+    let mut default_input = serialize(&accounts, &[1, 2, 3], &program_id);
+    let mut stack_input = default_input.clone();
+
+    let ((default_program_id, default_accounts, default_data), default_allocations) =
+        allocations(|| unsafe { deserialize(default_input.as_mut_ptr() as *mut u8) });
+    let mut stack = [const { MaybeUninit::<AccountInfo>::uninit() }; MAX_STACK_ACCOUNTS];
+    let (written, stack_allocations) = allocations(|| unsafe {
+        input::deserialize_into(stack_input.as_mut_ptr() as *mut u8, &mut stack)
+    });
+    let (stack_program_id, len, stack_data) = written.unwrap();
+
+    assert_eq!(stack_program_id, default_program_id);
+    assert_eq!(stack_data, default_data);
+    assert_same(unsafe { input::written(&stack, len) }, &default_accounts);
+    // Both rewrite the original data lengths in the padding alike.
+    assert_eq!(stack_input, default_input);
+
+    // Two `Rc`s per distinct account either way; the default also allocates the `Vec`.
+    assert_eq!(stack_allocations, 2 * distinct);
+    assert_eq!(default_allocations, 2 * distinct + 1);
+
+    // Duplicates share their original's lamports and data.
+    let stack_accounts = unsafe { input::written(&stack, len) };
+    **stack_accounts[2].lamports.borrow_mut() = 42;
+    assert_eq!(stack_accounts[1].lamports(), 42);
+}
+
+#[test]
+fn deserialize_into_leaves_long_inputs_to_deserialize() {
+    let program_id = Pubkey::new_unique();
+    let mut input = serialize(&accounts(MAX_STACK_ACCOUNTS + 1), &[], &program_id);
+    let untouched = input.clone();
+
+    let mut stack = [const { MaybeUninit::<AccountInfo>::uninit() }; MAX_STACK_ACCOUNTS];
+    let written = unsafe { input::deserialize_into(input.as_mut_ptr() as *mut u8, &mut stack) };
+    assert!(written.is_none());
+    assert_eq!(input, untouched);
+}
@@ -1,55 +1,50 @@
 #![cfg(feature = "test-sbf")]
 use {
+    borsh::BorshDeserialize,
     solana_program::{
-        borsh0_10::get_packed_len, instruction::InstructionError, pubkey::Pubkey, rent::Rent,
-        system_instruction,
+        account_info::AccountInfo,
+        borsh1::get_packed_len,
+        bpf_loader_upgradeable,
+        clock::Clock,
+        entrypoint::ProgramResult,
+        instruction::{AccountMeta, Instruction, InstructionError},
+        program::invoke_signed,
+        program_pack::Pack,
+        pubkey::Pubkey,
+        rent::Rent,
+        system_instruction, system_program,
     },
     solana_program_test::*,
     solana_sdk::{
+        account::Account,
         signature::{Keypair, Signer},
         transaction::{Transaction, TransactionError},
     },
-    vault::{error::VaultError, id, instruction, processor::Processor, state::VaultRecord},
+    vault::{
+        approval,
+        error::VaultError,
+        event::{self, CloseReceipt, TransferCheck},
+        id, instruction, oracle, quorum, receipt,
+        state::{
+            find_asset_vault_address, program_data_address, restriction, session_scope, vaa_digest,
+            AttestationList, AuditTrail, BlackoutRange, CloseFeeTier, CommitmentRecord,
+            CorporateActionType, DartIdentity, DistributionCursor, FeeConfig, InheritanceRecord,
+            NoteRecord, NotesLog, Notification, NotificationKind, NotifyRecord, PledgeRecord,
+            PolicyDefaults, PositionRecord, ProgramConfig, ProgramStats, RecoveryRecord,
+            SessionRecord, SnapshotRecord, TransferHookConfig, TransferWindow, VaultIndex,
+            VaultRecord, VaultStatus, VotingProxy, ENCRYPTED_NOTE_LEN, MAX_AUDIT_ENTRIES,
+            MAX_BLACKOUTS, MAX_CLOSE_FEE_TIERS, MAX_COMMITMENTS, MAX_CRANK_BOUNTY_BPS,
+            MAX_FEE_EXEMPT, MAX_LOG_NOTES, MAX_OPERATIONAL_KEYS, RECOVERY_CHALLENGE_SLOTS,
+            TRANSFER_WINDOW_DART, TRANSFER_WINDOW_VAULT,
+        },
+        test_utils::{
+            initialize_account, initialize_dart_identity, program_test, warp_to_slot, SlotScenario,
+            VaultBanksClientExt,
+        },
+        transfer_hook,
+    },
 };
 
-fn program_test() -> ProgramTest {
-    ProgramTest::new("vault", id(), processor!(Processor::process_instruction))
-}
-
-// Helper: create and initialize a vault account.
-async fn initialize_account(
-    context: &mut ProgramTestContext,
-    pda: &Keypair,
-    dart: &Keypair,
-    authority: &Keypair,
-) {
-    // Rent
-    let space = VaultRecord::LEN;
-    let lamports = Rent::default().minimum_balance(space);
-    println!("rent lamports = {lamports}");
-
-    let transaction = Transaction::new_signed_with_payer(
-        &[
-            system_instruction::create_account(
-                &context.payer.pubkey(),
-                &pda.pubkey(),
-                lamports,
-                space as u64,
-                &id(),
-            ),
-            instruction::initialize(id(), &pda.pubkey(), &dart.pubkey(), &authority.pubkey()),
-        ],
-        Some(&context.payer.pubkey()),
-        &[&context.payer, pda, dart],
-        context.last_blockhash,
-    );
-    context
-        .banks_client
-        .process_transaction(transaction)
-        .await
-        .unwrap();
-}
-
 #[tokio::test]
 async fn initialize_success() {
     let mut context = program_test().start_with_context().await;
@@ -59,11 +54,7 @@ async fn initialize_success() {
     let authority = Keypair::new();
 
     initialize_account(&mut context, &pda, &dart, &authority).await;
-    let account_data = context
-        .banks_client
-        .get_account_data_with_borsh::<VaultRecord>(pda.pubkey())
-        .await
-        .unwrap();
+    let account_data = context.banks_client.get_vault_record(pda.pubkey()).await;
     assert_eq!(account_data.dart, dart.pubkey());
     assert_eq!(account_data.authority, authority.pubkey());
     assert_eq!(account_data.version, VaultRecord::CURRENT_VERSION);
@@ -105,11 +96,7 @@ async fn initialize_with_seed_success() {
         .process_transaction(transaction)
         .await
         .unwrap();
-    let account_data = context
-        .banks_client
-        .get_account_data_with_borsh::<VaultRecord>(pda)
-        .await
-        .unwrap();
+    let account_data = context.banks_client.get_vault_record(pda).await;
     assert_eq!(account_data.dart, dart.pubkey());
     assert_eq!(account_data.authority, authority.pubkey());
     assert_eq!(account_data.version, VaultRecord::CURRENT_VERSION);
@@ -149,6 +136,126 @@ async fn initialize_twice_fail() {
     );
 }
 
+#[tokio::test]
+async fn initialize_fail_authority_is_pda() {
+    let mut context = program_test().start_with_context().await;
+
+    let pda = Keypair::new();
+    let dart = Keypair::new();
+
+    // The vault account cannot be its own authority
+    let space = VaultRecord::LEN;
+    let lamports = Rent::default().minimum_balance(space);
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            system_instruction::create_account(
+                &context.payer.pubkey(),
+                &pda.pubkey(),
+                lamports,
+                space as u64,
+                &id(),
+            ),
+            instruction::initialize(id(), &pda.pubkey(), &dart.pubkey(), &pda.pubkey()),
+        ],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &pda, &dart],
+        context.last_blockhash,
+    );
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(VaultError::DuplicateAccount as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn initialize_fail_authority_is_dart() {
+    let mut context = program_test().start_with_context().await;
+
+    let pda = Keypair::new();
+    let dart = Keypair::new();
+
+    // DART and authority must be distinct signers
+    let space = VaultRecord::LEN;
+    let lamports = Rent::default().minimum_balance(space);
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            system_instruction::create_account(
+                &context.payer.pubkey(),
+                &pda.pubkey(),
+                lamports,
+                space as u64,
+                &id(),
+            ),
+            instruction::initialize(id(), &pda.pubkey(), &dart.pubkey(), &dart.pubkey()),
+        ],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &pda, &dart],
+        context.last_blockhash,
+    );
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(VaultError::DuplicateAccount as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn initialize_fail_unusable_authority() {
+    let mut context = program_test().start_with_context().await;
+
+    let dart = Keypair::new();
+
+    // No one can sign as these, so the vault could never be used. The default pubkey
+    // is the system program id.
+    for authority in [system_program::id(), id()] {
+        let pda = Keypair::new();
+        let space = VaultRecord::LEN;
+        let lamports = Rent::default().minimum_balance(space);
+        let transaction = Transaction::new_signed_with_payer(
+            &[
+                system_instruction::create_account(
+                    &context.payer.pubkey(),
+                    &pda.pubkey(),
+                    lamports,
+                    space as u64,
+                    &id(),
+                ),
+                instruction::initialize(id(), &pda.pubkey(), &dart.pubkey(), &authority),
+            ],
+            Some(&context.payer.pubkey()),
+            &[&context.payer, &pda, &dart],
+            context.last_blockhash,
+        );
+        assert_eq!(
+            context
+                .banks_client
+                .process_transaction(transaction)
+                .await
+                .unwrap_err()
+                .unwrap(),
+            TransactionError::InstructionError(
+                1,
+                InstructionError::Custom(VaultError::InvalidRoleKey as u32)
+            )
+        );
+    }
+}
+
 #[tokio::test]
 async fn transfer_authority_success() {
     let mut context = program_test().start_with_context().await;
@@ -181,13 +288,102 @@ async fn transfer_authority_success() {
         .await
         .unwrap();
 
-    let record = context
+    let record = context.banks_client.get_vault_record(pda.pubkey()).await;
+
+    // Ensure the new owner was set in the record.
+    assert_eq!(record.authority, new_authority.pubkey());
+}
+
+#[tokio::test]
+async fn transfer_authority_versioned_success() {
+    let mut context = program_test().start_with_context().await;
+
+    let pda = Keypair::new();
+    let dart = Keypair::new();
+    let authority = Keypair::new();
+
+    initialize_account(&mut context, &pda, &dart, &authority).await;
+
+    let new_authority = Keypair::new();
+    let mut ix = instruction::transfer_authority(
+        id(),
+        &pda.pubkey(),
+        &dart.pubkey(),
+        &authority.pubkey(),
+        &new_authority.pubkey(),
+    );
+    ix.data = instruction::VaultInstruction::TransferAuthority.pack();
+
+    let transaction = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &dart, &authority],
+        context.last_blockhash,
+    );
+    context
         .banks_client
-        .get_account_data_with_borsh::<VaultRecord>(pda.pubkey())
+        .process_transaction(transaction)
         .await
         .unwrap();
 
-    // Ensure the new owner was set in the record.
+    let record = context.banks_client.get_vault_record(pda.pubkey()).await;
+    assert_eq!(record.authority, new_authority.pubkey());
+}
+
+#[tokio::test]
+async fn transfer_authority_fail_expired() {
+    let mut context = program_test().start_with_context().await;
+
+    let pda = Keypair::new();
+    let dart = Keypair::new();
+    let authority = Keypair::new();
+
+    initialize_account(&mut context, &pda, &dart, &authority).await;
+    warp_to_slot(&mut context, 100).await;
+
+    let new_authority = Keypair::new();
+    let transfer = instruction::transfer_authority(
+        id(),
+        &pda.pubkey(),
+        &dart.pubkey(),
+        &authority.pubkey(),
+        &new_authority.pubkey(),
+    );
+
+    // Bounded to a slot that has passed.
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::with_valid_until_slot(transfer.clone(), 99)],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &dart, &authority],
+        context.last_blockhash,
+    );
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::InstructionExpired as u32)
+        )
+    );
+
+    // Still within its bound.
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::with_valid_until_slot(transfer, 1_000)],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &dart, &authority],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+
+    let record = context.banks_client.get_vault_record(pda.pubkey()).await;
     assert_eq!(record.authority, new_authority.pubkey());
 }
 
@@ -235,7 +431,7 @@ async fn transfer_authority_fail_wrong_authority() {
 }
 
 #[tokio::test]
-async fn close_account_success() {
+async fn transfer_authority_fail_wrong_dart() {
     let mut context = program_test().start_with_context().await;
 
     let pda = Keypair::new();
@@ -244,37 +440,76 @@ async fn close_account_success() {
 
     initialize_account(&mut context, &pda, &dart, &authority).await;
 
+    let new_authority = Keypair::new();
+    let wrong_dart = Keypair::new();
+
     let transaction = Transaction::new_signed_with_payer(
-        &[instruction::close_account(
+        &[instruction::transfer_authority(
+            id(),
+            &pda.pubkey(),
+            &wrong_dart.pubkey(),
+            &authority.pubkey(),
+            &new_authority.pubkey(),
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &wrong_dart, &authority],
+        context.last_blockhash,
+    );
+
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::IncorrectDart as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn transfer_authority_fail_unchanged_authority() {
+    let mut context = program_test().start_with_context().await;
+
+    let pda = Keypair::new();
+    let dart = Keypair::new();
+    let authority = Keypair::new();
+
+    initialize_account(&mut context, &pda, &dart, &authority).await;
+
+    // Transfer to the current authority
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::transfer_authority(
             id(),
             &pda.pubkey(),
             &dart.pubkey(),
             &authority.pubkey(),
+            &authority.pubkey(),
         )],
         Some(&context.payer.pubkey()),
         &[&context.payer, &dart, &authority],
         context.last_blockhash,
     );
-    context
-        .banks_client
-        .process_transaction(transaction)
-        .await
-        .unwrap();
 
-    let recipient = context
-        .banks_client
-        .get_account(authority.pubkey())
-        .await
-        .unwrap()
-        .unwrap();
     assert_eq!(
-        recipient.lamports,
-        Rent::default().minimum_balance(get_packed_len::<VaultRecord>())
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::AuthorityUnchanged as u32)
+        )
     );
 }
 
 #[tokio::test]
-async fn close_account_fail_wrong_authority() {
+async fn transfer_authority_fail_default_authority() {
     let mut context = program_test().start_with_context().await;
 
     let pda = Keypair::new();
@@ -283,18 +518,20 @@ async fn close_account_fail_wrong_authority() {
 
     initialize_account(&mut context, &pda, &dart, &authority).await;
 
-    let wrong_authority = Keypair::new();
+    // Transfer to the default pubkey
     let transaction = Transaction::new_signed_with_payer(
-        &[instruction::close_account(
+        &[instruction::transfer_authority(
             id(),
             &pda.pubkey(),
             &dart.pubkey(),
-            &wrong_authority.pubkey(),
+            &authority.pubkey(),
+            &Pubkey::default(),
         )],
         Some(&context.payer.pubkey()),
-        &[&context.payer, &dart, &wrong_authority],
+        &[&context.payer, &dart, &authority],
         context.last_blockhash,
     );
+
     assert_eq!(
         context
             .banks_client
@@ -304,7 +541,7778 @@ async fn close_account_fail_wrong_authority() {
             .unwrap(),
         TransactionError::InstructionError(
             0,
-            InstructionError::Custom(VaultError::IncorrectAuthority as u32)
+            InstructionError::Custom(VaultError::InvalidAuthority as u32)
         )
     );
 }
+
+#[tokio::test]
+async fn transfer_authority_fail_authority_is_dart() {
+    let mut context = program_test().start_with_context().await;
+
+    let pda = Keypair::new();
+    let dart = Keypair::new();
+    let authority = Keypair::new();
+
+    initialize_account(&mut context, &pda, &dart, &authority).await;
+
+    // Transfer to the DART
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::transfer_authority(
+            id(),
+            &pda.pubkey(),
+            &dart.pubkey(),
+            &authority.pubkey(),
+            &dart.pubkey(),
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &dart, &authority],
+        context.last_blockhash,
+    );
+
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::AuthorityIsDart as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn transfer_authority_fail_new_authority_is_pda() {
+    let mut context = program_test().start_with_context().await;
+
+    let pda = Keypair::new();
+    let dart = Keypair::new();
+    let authority = Keypair::new();
+
+    initialize_account(&mut context, &pda, &dart, &authority).await;
+
+    // Transfer the vault to itself
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::transfer_authority(
+            id(),
+            &pda.pubkey(),
+            &dart.pubkey(),
+            &authority.pubkey(),
+            &pda.pubkey(),
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &dart, &authority],
+        context.last_blockhash,
+    );
+
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::DuplicateAccount as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn transfer_authority_fail_readonly_pda() {
+    let mut context = program_test().start_with_context().await;
+
+    let pda = Keypair::new();
+    let dart = Keypair::new();
+    let authority = Keypair::new();
+
+    initialize_account(&mut context, &pda, &dart, &authority).await;
+
+    // Submit the vault account as read-only
+    let mut transfer = instruction::transfer_authority(
+        id(),
+        &pda.pubkey(),
+        &dart.pubkey(),
+        &authority.pubkey(),
+        &Keypair::new().pubkey(),
+    );
+    transfer.accounts[0].is_writable = false;
+
+    let transaction = Transaction::new_signed_with_payer(
+        &[transfer],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &dart, &authority],
+        context.last_blockhash,
+    );
+
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::ReadOnlyAccount as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn transfer_authority_fail_executable_new_authority() {
+    let mut context = program_test().start_with_context().await;
+
+    let pda = Keypair::new();
+    let dart = Keypair::new();
+    let authority = Keypair::new();
+
+    initialize_account(&mut context, &pda, &dart, &authority).await;
+
+    // Transfer the vault to a program account
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::transfer_authority(
+            id(),
+            &pda.pubkey(),
+            &dart.pubkey(),
+            &authority.pubkey(),
+            &system_program::id(),
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &dart, &authority],
+        context.last_blockhash,
+    );
+
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::ExecutableAccount as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn close_account_success() {
+    let mut context = program_test().start_with_context().await;
+
+    let pda = Keypair::new();
+    let dart = Keypair::new();
+    let authority = Keypair::new();
+
+    initialize_account(&mut context, &pda, &dart, &authority).await;
+
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::close_account(
+            id(),
+            &pda.pubkey(),
+            &dart.pubkey(),
+            &authority.pubkey(),
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &dart, &authority],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+
+    let recipient = context
+        .banks_client
+        .get_account(authority.pubkey())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        recipient.lamports,
+        Rent::default().minimum_balance(get_packed_len::<VaultRecord>())
+    );
+    context.banks_client.assert_vault_closed(pda.pubkey()).await;
+}
+
+#[tokio::test]
+async fn close_account_checked_success() {
+    let mut context = program_test().start_with_context().await;
+
+    let pda = Keypair::new();
+    let dart = Keypair::new();
+    let authority = Keypair::new();
+
+    initialize_account(&mut context, &pda, &dart, &authority).await;
+
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::close_account_checked(
+            id(),
+            &pda.pubkey(),
+            &dart.pubkey(),
+            &authority.pubkey(),
+            false,
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &dart, &authority],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+
+    context.banks_client.assert_vault_closed(pda.pubkey()).await;
+}
+
+#[tokio::test]
+async fn transfer_authority_checked_fail_stale_state() {
+    let mut context = program_test().start_with_context().await;
+
+    let pda = Keypair::new();
+    let dart = Keypair::new();
+    let authority = Keypair::new();
+
+    initialize_account(&mut context, &pda, &dart, &authority).await;
+
+    // The authority moves before the checked transfer, built against the old
+    // authority, executes.
+    let new_authority = Keypair::new();
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            instruction::transfer_authority(
+                id(),
+                &pda.pubkey(),
+                &dart.pubkey(),
+                &authority.pubkey(),
+                &new_authority.pubkey(),
+            ),
+            instruction::transfer_authority_checked(
+                id(),
+                &pda.pubkey(),
+                &dart.pubkey(),
+                &authority.pubkey(),
+                &Pubkey::new_unique(),
+            ),
+        ],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &dart, &authority],
+        context.last_blockhash,
+    );
+
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(VaultError::StaleState as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn close_account_fail_wrong_authority() {
+    let mut context = program_test().start_with_context().await;
+
+    let pda = Keypair::new();
+    let dart = Keypair::new();
+    let authority = Keypair::new();
+
+    initialize_account(&mut context, &pda, &dart, &authority).await;
+
+    let wrong_authority = Keypair::new();
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::close_account(
+            id(),
+            &pda.pubkey(),
+            &dart.pubkey(),
+            &wrong_authority.pubkey(),
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &dart, &wrong_authority],
+        context.last_blockhash,
+    );
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::IncorrectAuthority as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn close_account_fail_wrong_dart() {
+    let mut context = program_test().start_with_context().await;
+
+    let pda = Keypair::new();
+    let dart = Keypair::new();
+    let authority = Keypair::new();
+
+    initialize_account(&mut context, &pda, &dart, &authority).await;
+
+    let wrong_dart = Keypair::new();
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::close_account(
+            id(),
+            &pda.pubkey(),
+            &wrong_dart.pubkey(),
+            &authority.pubkey(),
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &wrong_dart, &authority],
+        context.last_blockhash,
+    );
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::IncorrectDart as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn reopen_success() {
+    let mut context = program_test().start_with_context().await;
+
+    let pda = Keypair::new();
+    let dart = Keypair::new();
+    let authority = Keypair::new();
+
+    initialize_account(&mut context, &pda, &dart, &authority).await;
+
+    // Close, restore rent, and reopen in a single transaction.
+    let lamports = Rent::default().minimum_balance(VaultRecord::LEN);
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            instruction::close_account(id(), &pda.pubkey(), &dart.pubkey(), &authority.pubkey()),
+            system_instruction::transfer(&context.payer.pubkey(), &pda.pubkey(), lamports),
+            instruction::reopen(id(), &pda.pubkey(), &dart.pubkey()),
+        ],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &dart, &authority],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+
+    let record = context.banks_client.get_vault_record(pda.pubkey()).await;
+    assert_eq!(record.status, VaultStatus::Active);
+    assert_eq!(record.authority, authority.pubkey());
+    assert_eq!(record.dart, dart.pubkey());
+}
+
+#[tokio::test]
+async fn reopen_fail_not_rent_exempt() {
+    let mut context = program_test().start_with_context().await;
+
+    let pda = Keypair::new();
+    let dart = Keypair::new();
+    let authority = Keypair::new();
+
+    initialize_account(&mut context, &pda, &dart, &authority).await;
+
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            instruction::close_account(id(), &pda.pubkey(), &dart.pubkey(), &authority.pubkey()),
+            instruction::reopen(id(), &pda.pubkey(), &dart.pubkey()),
+        ],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &dart, &authority],
+        context.last_blockhash,
+    );
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(1, InstructionError::AccountNotRentExempt)
+    );
+}
+
+#[tokio::test]
+async fn reopen_fail_not_closed() {
+    let mut context = program_test().start_with_context().await;
+
+    let pda = Keypair::new();
+    let dart = Keypair::new();
+    let authority = Keypair::new();
+
+    initialize_account(&mut context, &pda, &dart, &authority).await;
+
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::reopen(id(), &pda.pubkey(), &dart.pubkey())],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &dart],
+        context.last_blockhash,
+    );
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::AccountNotClosed as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn transfer_authority_fail_revived_account() {
+    let mut context = program_test().start_with_context().await;
+
+    let pda = Keypair::new();
+    let dart = Keypair::new();
+    let authority = Keypair::new();
+
+    initialize_account(&mut context, &pda, &dart, &authority).await;
+
+    // Re-funding a closed vault must not make it usable again.
+    let lamports = Rent::default().minimum_balance(VaultRecord::LEN);
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            instruction::close_account(id(), &pda.pubkey(), &dart.pubkey(), &authority.pubkey()),
+            system_instruction::transfer(&context.payer.pubkey(), &pda.pubkey(), lamports),
+            instruction::transfer_authority(
+                id(),
+                &pda.pubkey(),
+                &dart.pubkey(),
+                &authority.pubkey(),
+                &Keypair::new().pubkey(),
+            ),
+        ],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &dart, &authority],
+        context.last_blockhash,
+    );
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            2,
+            InstructionError::Custom(VaultError::AccountClosed as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn propose_and_accept_dart_success() {
+    let mut context = program_test().start_with_context().await;
+
+    let pda = Keypair::new();
+    let dart = Keypair::new();
+    let authority = Keypair::new();
+    let new_dart = Keypair::new();
+
+    initialize_account(&mut context, &pda, &dart, &authority).await;
+
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::propose_dart(
+            id(),
+            &pda.pubkey(),
+            &dart.pubkey(),
+            &new_dart.pubkey(),
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &dart],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+
+    // The current DART stays in control until the proposal is accepted.
+    let record = context.banks_client.get_vault_record(pda.pubkey()).await;
+    assert_eq!(record.dart, dart.pubkey());
+    assert_eq!(record.pending_dart, new_dart.pubkey());
+
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::accept_dart(
+            id(),
+            &pda.pubkey(),
+            &new_dart.pubkey(),
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &new_dart],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+
+    let record = context.banks_client.get_vault_record(pda.pubkey()).await;
+    assert_eq!(record.dart, new_dart.pubkey());
+    assert_eq!(record.pending_dart, Pubkey::default());
+    assert_eq!(record.authority, authority.pubkey());
+}
+
+#[tokio::test]
+async fn propose_dart_fail_wrong_dart() {
+    let mut context = program_test().start_with_context().await;
+
+    let pda = Keypair::new();
+    let dart = Keypair::new();
+    let authority = Keypair::new();
+
+    initialize_account(&mut context, &pda, &dart, &authority).await;
+
+    let wrong_dart = Keypair::new();
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::propose_dart(
+            id(),
+            &pda.pubkey(),
+            &wrong_dart.pubkey(),
+            &Keypair::new().pubkey(),
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &wrong_dart],
+        context.last_blockhash,
+    );
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::IncorrectDart as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn propose_dart_fail_unchanged_dart() {
+    let mut context = program_test().start_with_context().await;
+
+    let pda = Keypair::new();
+    let dart = Keypair::new();
+    let authority = Keypair::new();
+
+    initialize_account(&mut context, &pda, &dart, &authority).await;
+
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::propose_dart(
+            id(),
+            &pda.pubkey(),
+            &dart.pubkey(),
+            &dart.pubkey(),
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &dart],
+        context.last_blockhash,
+    );
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::DartUnchanged as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn accept_dart_fail_not_proposed() {
+    let mut context = program_test().start_with_context().await;
+
+    let pda = Keypair::new();
+    let dart = Keypair::new();
+    let authority = Keypair::new();
+
+    initialize_account(&mut context, &pda, &dart, &authority).await;
+
+    let new_dart = Keypair::new();
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::accept_dart(
+            id(),
+            &pda.pubkey(),
+            &new_dart.pubkey(),
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &new_dart],
+        context.last_blockhash,
+    );
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::NoPendingDart as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn accept_dart_fail_wrong_dart() {
+    let mut context = program_test().start_with_context().await;
+
+    let pda = Keypair::new();
+    let dart = Keypair::new();
+    let authority = Keypair::new();
+
+    initialize_account(&mut context, &pda, &dart, &authority).await;
+
+    // A proposal cannot be accepted by anyone but the proposed DART.
+    let new_dart = Keypair::new();
+    let wrong_dart = Keypair::new();
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            instruction::propose_dart(id(), &pda.pubkey(), &dart.pubkey(), &new_dart.pubkey()),
+            instruction::accept_dart(id(), &pda.pubkey(), &wrong_dart.pubkey()),
+        ],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &dart, &wrong_dart],
+        context.last_blockhash,
+    );
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(VaultError::IncorrectDart as u32)
+        )
+    );
+}
+
+// Helper: create a vault whose DART role is held by `identity`, initialized by `operator`.
+async fn initialize_account_with_identity(
+    context: &mut ProgramTestContext,
+    pda: &Keypair,
+    identity: &Pubkey,
+    operator: &Keypair,
+    authority: &Keypair,
+) {
+    let lamports = Rent::default().minimum_balance(VaultRecord::LEN);
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            system_instruction::create_account(
+                &context.payer.pubkey(),
+                &pda.pubkey(),
+                lamports,
+                VaultRecord::LEN as u64,
+                &id(),
+            ),
+            instruction::with_dart_identity(
+                instruction::initialize(
+                    id(),
+                    &pda.pubkey(),
+                    &operator.pubkey(),
+                    &authority.pubkey(),
+                ),
+                identity,
+            ),
+        ],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, pda, operator],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn dart_identity_operational_key_success() {
+    let mut context = program_test().start_with_context().await;
+
+    let identity = Keypair::new();
+    let master = Keypair::new();
+    let operator = Keypair::new();
+    initialize_dart_identity(&mut context, &identity, &master, &[operator.pubkey()]).await;
+
+    let pda = Keypair::new();
+    let authority = Keypair::new();
+    initialize_account_with_identity(
+        &mut context,
+        &pda,
+        &identity.pubkey(),
+        &operator,
+        &authority,
+    )
+    .await;
+    let record = context.banks_client.get_vault_record(pda.pubkey()).await;
+    assert_eq!(record.dart, identity.pubkey());
+
+    let new_authority = Keypair::new();
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::with_dart_identity(
+            instruction::transfer_authority(
+                id(),
+                &pda.pubkey(),
+                &operator.pubkey(),
+                &authority.pubkey(),
+                &new_authority.pubkey(),
+            ),
+            &identity.pubkey(),
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &operator, &authority],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+
+    let record = context.banks_client.get_vault_record(pda.pubkey()).await;
+    assert_eq!(record.authority, new_authority.pubkey());
+}
+
+#[tokio::test]
+async fn dart_identity_fail_rotated_key() {
+    let mut context = program_test().start_with_context().await;
+
+    let identity = Keypair::new();
+    let master = Keypair::new();
+    let operator = Keypair::new();
+    initialize_dart_identity(&mut context, &identity, &master, &[operator.pubkey()]).await;
+
+    let pda = Keypair::new();
+    let authority = Keypair::new();
+    initialize_account_with_identity(
+        &mut context,
+        &pda,
+        &identity.pubkey(),
+        &operator,
+        &authority,
+    )
+    .await;
+
+    // Rotating the slot revokes the old operational key.
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            instruction::set_operational_key(
+                id(),
+                &identity.pubkey(),
+                &master.pubkey(),
+                0,
+                Keypair::new().pubkey(),
+            ),
+            instruction::with_dart_identity(
+                instruction::close_account(
+                    id(),
+                    &pda.pubkey(),
+                    &operator.pubkey(),
+                    &authority.pubkey(),
+                ),
+                &identity.pubkey(),
+            ),
+        ],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &master, &operator, &authority],
+        context.last_blockhash,
+    );
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(VaultError::IncorrectDart as u32)
+        )
+    );
+}
+
+// Operational key signatures of the approval message of `instruction` at `nonce`.
+fn quorum_approval(instruction: &Instruction, nonce: u64, signers: &[&Keypair]) -> Instruction {
+    let message = approval::message(instruction, nonce);
+    let signatures: Vec<_> = signers
+        .iter()
+        .map(|signer| {
+            let signature = signer.sign_message(&message);
+            (signer.pubkey(), signature.as_ref().try_into().unwrap())
+        })
+        .collect();
+    quorum::verify_signatures(&signatures, &message)
+}
+
+#[tokio::test]
+async fn dart_quorum_approval_success() {
+    let mut context = program_test().start_with_context().await;
+
+    let identity = Keypair::new();
+    let master = Keypair::new();
+    let operators = [Keypair::new(), Keypair::new(), Keypair::new()];
+    let keys: Vec<_> = operators.iter().map(Keypair::pubkey).collect();
+    initialize_dart_identity(&mut context, &identity, &master, &keys).await;
+
+    let pda = Keypair::new();
+    let authority = Keypair::new();
+    initialize_account_with_identity(
+        &mut context,
+        &pda,
+        &identity.pubkey(),
+        &operators[0],
+        &authority,
+    )
+    .await;
+
+    let set_tag = instruction::with_dart_quorum(
+        instruction::set_tag(id(), &pda.pubkey(), &identity.pubkey(), 7),
+        &identity.pubkey(),
+    );
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            instruction::set_dart_quorum(id(), &identity.pubkey(), &master.pubkey(), 2),
+            quorum_approval(&set_tag, 0, &[&operators[0], &operators[2]]),
+            set_tag,
+        ],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &master],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+
+    let record = context.banks_client.get_vault_record(pda.pubkey()).await;
+    assert_eq!(record.tag, 7);
+    let account = context
+        .banks_client
+        .get_account(identity.pubkey())
+        .await
+        .unwrap()
+        .unwrap();
+    let identity = DartIdentity::try_from_slice(&account.data).unwrap();
+    assert_eq!(identity.quorum, 2);
+    assert_eq!(identity.approval_nonce, 1);
+}
+
+#[tokio::test]
+async fn dart_quorum_approval_fail_quorum_not_met() {
+    let mut context = program_test().start_with_context().await;
+
+    let identity = Keypair::new();
+    let master = Keypair::new();
+    let operators = [Keypair::new(), Keypair::new()];
+    let keys: Vec<_> = operators.iter().map(Keypair::pubkey).collect();
+    initialize_dart_identity(&mut context, &identity, &master, &keys).await;
+
+    let pda = Keypair::new();
+    let authority = Keypair::new();
+    initialize_account_with_identity(
+        &mut context,
+        &pda,
+        &identity.pubkey(),
+        &operators[0],
+        &authority,
+    )
+    .await;
+
+    // One operational key, and one outsider, sign a 2-of-2 approval; the other
+    // signature covers a stale nonce.
+    let set_tag = instruction::with_dart_quorum(
+        instruction::set_tag(id(), &pda.pubkey(), &identity.pubkey(), 7),
+        &identity.pubkey(),
+    );
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            instruction::set_dart_quorum(id(), &identity.pubkey(), &master.pubkey(), 2),
+            quorum_approval(&set_tag, 0, &[&operators[0], &Keypair::new()]),
+            quorum_approval(&set_tag, 1, &[&operators[1]]),
+            set_tag,
+        ],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &master],
+        context.last_blockhash,
+    );
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            3,
+            InstructionError::Custom(VaultError::QuorumNotMet as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn dart_quorum_approval_fail_incorrect_sysvar() {
+    let mut context = program_test().start_with_context().await;
+
+    let identity = Keypair::new();
+    let master = Keypair::new();
+    let operators = [Keypair::new(), Keypair::new()];
+    let keys: Vec<_> = operators.iter().map(Keypair::pubkey).collect();
+    initialize_dart_identity(&mut context, &identity, &master, &keys).await;
+
+    let pda = Keypair::new();
+    let authority = Keypair::new();
+    initialize_account_with_identity(
+        &mut context,
+        &pda,
+        &identity.pubkey(),
+        &operators[0],
+        &authority,
+    )
+    .await;
+
+    // Another account passed in place of the instructions sysvar.
+    let mut set_tag = instruction::with_dart_quorum(
+        instruction::set_tag(id(), &pda.pubkey(), &identity.pubkey(), 7),
+        &identity.pubkey(),
+    );
+    set_tag.accounts.last_mut().unwrap().pubkey = Pubkey::new_unique();
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            instruction::set_dart_quorum(id(), &identity.pubkey(), &master.pubkey(), 2),
+            quorum_approval(&set_tag, 0, &[&operators[0], &operators[1]]),
+            set_tag,
+        ],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &master],
+        context.last_blockhash,
+    );
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            2,
+            InstructionError::Custom(VaultError::IncorrectSysvar as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn set_operational_key_fail_wrong_master() {
+    let mut context = program_test().start_with_context().await;
+
+    let identity = Keypair::new();
+    let master = Keypair::new();
+    let operator = Keypair::new();
+    initialize_dart_identity(&mut context, &identity, &master, &[operator.pubkey()]).await;
+
+    // Operational keys cannot rotate themselves.
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::set_operational_key(
+            id(),
+            &identity.pubkey(),
+            &operator.pubkey(),
+            1,
+            Keypair::new().pubkey(),
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &operator],
+        context.last_blockhash,
+    );
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::IncorrectMasterKey as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn set_operational_key_fail_invalid_slot() {
+    let mut context = program_test().start_with_context().await;
+
+    let identity = Keypair::new();
+    let master = Keypair::new();
+    initialize_dart_identity(&mut context, &identity, &master, &[]).await;
+
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::set_operational_key(
+            id(),
+            &identity.pubkey(),
+            &master.pubkey(),
+            MAX_OPERATIONAL_KEYS as u8,
+            Keypair::new().pubkey(),
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &master],
+        context.last_blockhash,
+    );
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::InvalidOperationalKeySlot as u32)
+        )
+    );
+}
+
+// Helper: authorize `session` to sign for the vault authority.
+async fn create_session(
+    context: &mut ProgramTestContext,
+    pda: &Keypair,
+    authority: &Keypair,
+    session: &Keypair,
+    expires_at_slot: u64,
+    scope: u8,
+) {
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::create_session(
+            id(),
+            &pda.pubkey(),
+            &authority.pubkey(),
+            &context.payer.pubkey(),
+            session.pubkey(),
+            expires_at_slot,
+            scope,
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, authority],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+}
+
+// Transfer authority with `session` signing in the authority role.
+fn session_transfer(
+    context: &ProgramTestContext,
+    pda: &Keypair,
+    dart: &Keypair,
+    authority: &Pubkey,
+    session: &Keypair,
+    new_authority: &Pubkey,
+) -> Transaction {
+    Transaction::new_signed_with_payer(
+        &[instruction::with_vault_index(
+            instruction::with_session(
+                instruction::transfer_authority(
+                    id(),
+                    &pda.pubkey(),
+                    &dart.pubkey(),
+                    &session.pubkey(),
+                    new_authority,
+                ),
+                &pda.pubkey(),
+                &session.pubkey(),
+            ),
+            authority,
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, dart, session],
+        context.last_blockhash,
+    )
+}
+
+#[tokio::test]
+async fn session_transfer_authority_success() {
+    let mut context = program_test().start_with_context().await;
+
+    let pda = Keypair::new();
+    let dart = Keypair::new();
+    let authority = Keypair::new();
+    let session = Keypair::new();
+
+    initialize_account(&mut context, &pda, &dart, &authority).await;
+    create_session(
+        &mut context,
+        &pda,
+        &authority,
+        &session,
+        u64::MAX,
+        session_scope::TRANSFER_AUTHORITY,
+    )
+    .await;
+
+    let (address, _) = SessionRecord::find_address(&id(), &pda.pubkey(), &session.pubkey());
+    let record = context
+        .banks_client
+        .get_account_data_with_borsh::<SessionRecord>(address)
+        .await
+        .unwrap();
+    assert_eq!(record.authority, authority.pubkey());
+    assert_eq!(record.key, session.pubkey());
+
+    let new_authority = Keypair::new();
+    let transaction = session_transfer(
+        &context,
+        &pda,
+        &dart,
+        &authority.pubkey(),
+        &session,
+        &new_authority.pubkey(),
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+
+    let record = context.banks_client.get_vault_record(pda.pubkey()).await;
+    assert_eq!(record.authority, new_authority.pubkey());
+}
+
+#[tokio::test]
+async fn session_fail_expired() {
+    let mut context = program_test().start_with_context().await;
+
+    let pda = Keypair::new();
+    let dart = Keypair::new();
+    let authority = Keypair::new();
+    let session = Keypair::new();
+
+    initialize_account(&mut context, &pda, &dart, &authority).await;
+    create_session(
+        &mut context,
+        &pda,
+        &authority,
+        &session,
+        50,
+        session_scope::TRANSFER_AUTHORITY,
+    )
+    .await;
+
+    warp_to_slot(&mut context, 100).await;
+
+    let transaction = session_transfer(
+        &context,
+        &pda,
+        &dart,
+        &authority.pubkey(),
+        &session,
+        &Pubkey::new_unique(),
+    );
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::SessionExpired as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn session_fail_out_of_scope() {
+    let mut context = program_test().start_with_context().await;
+
+    let pda = Keypair::new();
+    let dart = Keypair::new();
+    let authority = Keypair::new();
+    let session = Keypair::new();
+
+    initialize_account(&mut context, &pda, &dart, &authority).await;
+    create_session(&mut context, &pda, &authority, &session, u64::MAX, 0).await;
+
+    let transaction = session_transfer(
+        &context,
+        &pda,
+        &dart,
+        &authority.pubkey(),
+        &session,
+        &Pubkey::new_unique(),
+    );
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::SessionScopeNotAllowed as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn create_session_fail_wrong_authority() {
+    let mut context = program_test().start_with_context().await;
+
+    let pda = Keypair::new();
+    let dart = Keypair::new();
+    let authority = Keypair::new();
+
+    initialize_account(&mut context, &pda, &dart, &authority).await;
+
+    let wrong_authority = Keypair::new();
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::create_session(
+            id(),
+            &pda.pubkey(),
+            &wrong_authority.pubkey(),
+            &context.payer.pubkey(),
+            Pubkey::new_unique(),
+            u64::MAX,
+            session_scope::TRANSFER_AUTHORITY,
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &wrong_authority],
+        context.last_blockhash,
+    );
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::IncorrectAuthority as u32)
+        )
+    );
+}
+
+async fn add_guardians(
+    context: &mut ProgramTestContext,
+    pda: &Keypair,
+    authority: &Keypair,
+    guardians: &[Keypair],
+    threshold: u8,
+) {
+    for (i, guardian) in guardians.iter().enumerate() {
+        let transaction = Transaction::new_signed_with_payer(
+            &[instruction::add_guardian(
+                id(),
+                &pda.pubkey(),
+                &authority.pubkey(),
+                &guardian.pubkey(),
+                &context.payer.pubkey(),
+                threshold.min(i as u8 + 1),
+            )],
+            Some(&context.payer.pubkey()),
+            &[&context.payer, authority],
+            context.last_blockhash,
+        );
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap();
+    }
+}
+
+async fn initiate_and_approve(
+    context: &mut ProgramTestContext,
+    pda: &Keypair,
+    dart: &Keypair,
+    new_authority: &Pubkey,
+    approvers: &[&Keypair],
+) {
+    let mut instructions = vec![instruction::initiate_recovery(
+        id(),
+        &pda.pubkey(),
+        &dart.pubkey(),
+        new_authority,
+    )];
+    let mut signers = vec![&context.payer, dart];
+    for guardian in approvers {
+        instructions.push(instruction::approve_recovery(
+            id(),
+            &pda.pubkey(),
+            &guardian.pubkey(),
+        ));
+        signers.push(guardian);
+    }
+    let transaction = Transaction::new_signed_with_payer(
+        &instructions,
+        Some(&context.payer.pubkey()),
+        &signers,
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+}
+
+fn complete_recovery(
+    context: &ProgramTestContext,
+    pda: &Keypair,
+    dart: &Keypair,
+    authority: &Pubkey,
+    new_authority: &Pubkey,
+) -> Transaction {
+    Transaction::new_signed_with_payer(
+        &[instruction::complete_recovery(
+            id(),
+            &pda.pubkey(),
+            &dart.pubkey(),
+            authority,
+            new_authority,
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, dart],
+        context.last_blockhash,
+    )
+}
+
+#[tokio::test]
+async fn recovery_success() {
+    let mut context = program_test().start_with_context().await;
+
+    let pda = Keypair::new();
+    let dart = Keypair::new();
+    let authority = Keypair::new();
+    let guardians = [Keypair::new(), Keypair::new(), Keypair::new()];
+
+    initialize_account(&mut context, &pda, &dart, &authority).await;
+    add_guardians(&mut context, &pda, &authority, &guardians, 2).await;
+
+    let (address, _) = RecoveryRecord::find_address(&id(), &pda.pubkey());
+    let recovery = context
+        .banks_client
+        .get_account_data_with_borsh::<RecoveryRecord>(address)
+        .await
+        .unwrap();
+    assert_eq!(recovery.guardian_count(), 3);
+    assert_eq!(recovery.threshold, 2);
+
+    let new_authority = Keypair::new();
+    initiate_and_approve(
+        &mut context,
+        &pda,
+        &dart,
+        &new_authority.pubkey(),
+        &[&guardians[0], &guardians[2]],
+    )
+    .await;
+
+    let recovery = context
+        .banks_client
+        .get_account_data_with_borsh::<RecoveryRecord>(address)
+        .await
+        .unwrap();
+    assert_eq!(recovery.pending_authority, new_authority.pubkey());
+    assert_eq!(recovery.approval_count(), 2);
+    warp_to_slot(
+        &mut context,
+        recovery.initiated_at_slot + RECOVERY_CHALLENGE_SLOTS,
+    )
+    .await;
+
+    let transaction = complete_recovery(
+        &context,
+        &pda,
+        &dart,
+        &authority.pubkey(),
+        &new_authority.pubkey(),
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+
+    let record = context.banks_client.get_vault_record(pda.pubkey()).await;
+    assert_eq!(record.authority, new_authority.pubkey());
+    let recovery = context
+        .banks_client
+        .get_account_data_with_borsh::<RecoveryRecord>(address)
+        .await
+        .unwrap();
+    assert_eq!(recovery.pending_authority, Pubkey::default());
+    assert_eq!(recovery.approvals, 0);
+}
+
+#[tokio::test]
+async fn recovery_fail_challenge_period_active() {
+    let mut context = program_test().start_with_context().await;
+
+    let pda = Keypair::new();
+    let dart = Keypair::new();
+    let authority = Keypair::new();
+    let guardians = [Keypair::new(), Keypair::new()];
+
+    initialize_account(&mut context, &pda, &dart, &authority).await;
+    add_guardians(&mut context, &pda, &authority, &guardians, 1).await;
+    initiate_and_approve(
+        &mut context,
+        &pda,
+        &dart,
+        &Pubkey::new_unique(),
+        &[&guardians[1]],
+    )
+    .await;
+
+    let transaction = complete_recovery(
+        &context,
+        &pda,
+        &dart,
+        &authority.pubkey(),
+        &Pubkey::new_unique(),
+    );
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::ChallengePeriodActive as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn recovery_fail_threshold_not_met() {
+    let mut context = program_test().start_with_context().await;
+
+    let pda = Keypair::new();
+    let dart = Keypair::new();
+    let authority = Keypair::new();
+    let guardians = [Keypair::new(), Keypair::new()];
+
+    initialize_account(&mut context, &pda, &dart, &authority).await;
+    add_guardians(&mut context, &pda, &authority, &guardians, 2).await;
+    initiate_and_approve(
+        &mut context,
+        &pda,
+        &dart,
+        &Pubkey::new_unique(),
+        &[&guardians[0]],
+    )
+    .await;
+
+    let transaction = complete_recovery(
+        &context,
+        &pda,
+        &dart,
+        &authority.pubkey(),
+        &Pubkey::new_unique(),
+    );
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::RecoveryThresholdNotMet as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn recovery_cancelled_by_authority() {
+    let mut context = program_test().start_with_context().await;
+
+    let pda = Keypair::new();
+    let dart = Keypair::new();
+    let authority = Keypair::new();
+    let guardians = [Keypair::new()];
+
+    initialize_account(&mut context, &pda, &dart, &authority).await;
+    add_guardians(&mut context, &pda, &authority, &guardians, 1).await;
+    initiate_and_approve(
+        &mut context,
+        &pda,
+        &dart,
+        &Pubkey::new_unique(),
+        &[&guardians[0]],
+    )
+    .await;
+
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            instruction::cancel_recovery(id(), &pda.pubkey(), &authority.pubkey()),
+            instruction::complete_recovery(
+                id(),
+                &pda.pubkey(),
+                &dart.pubkey(),
+                &authority.pubkey(),
+                &Pubkey::new_unique(),
+            ),
+        ],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &authority, &dart],
+        context.last_blockhash,
+    );
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(VaultError::RecoveryNotPending as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn add_guardian_fail_wrong_authority() {
+    let mut context = program_test().start_with_context().await;
+
+    let pda = Keypair::new();
+    let dart = Keypair::new();
+    let authority = Keypair::new();
+
+    initialize_account(&mut context, &pda, &dart, &authority).await;
+
+    let wrong_authority = Keypair::new();
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::add_guardian(
+            id(),
+            &pda.pubkey(),
+            &wrong_authority.pubkey(),
+            &Pubkey::new_unique(),
+            &context.payer.pubkey(),
+            1,
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &wrong_authority],
+        context.last_blockhash,
+    );
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::IncorrectAuthority as u32)
+        )
+    );
+}
+
+async fn set_beneficiary(
+    context: &mut ProgramTestContext,
+    pda: &Keypair,
+    authority: &Keypair,
+    beneficiary: &Pubkey,
+    inactivity_slots: u64,
+) {
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::set_beneficiary(
+            id(),
+            &pda.pubkey(),
+            &authority.pubkey(),
+            beneficiary,
+            &context.payer.pubkey(),
+            inactivity_slots,
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, authority],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+}
+
+fn claim_inheritance(
+    context: &ProgramTestContext,
+    pda: &Keypair,
+    dart: &Keypair,
+    authority: &Keypair,
+    beneficiary: &Keypair,
+) -> Transaction {
+    Transaction::new_signed_with_payer(
+        &[instruction::claim_inheritance(
+            id(),
+            &pda.pubkey(),
+            &dart.pubkey(),
+            &authority.pubkey(),
+            &beneficiary.pubkey(),
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, dart, beneficiary],
+        context.last_blockhash,
+    )
+}
+
+#[tokio::test]
+async fn claim_inheritance_success() {
+    let mut context = program_test().start_with_context().await;
+
+    let pda = Keypair::new();
+    let dart = Keypair::new();
+    let authority = Keypair::new();
+    let beneficiary = Keypair::new();
+
+    initialize_account(&mut context, &pda, &dart, &authority).await;
+    set_beneficiary(&mut context, &pda, &authority, &beneficiary.pubkey(), 1_000).await;
+
+    let (address, _) = InheritanceRecord::find_address(&id(), &pda.pubkey());
+    let inheritance = context
+        .banks_client
+        .get_account_data_with_borsh::<InheritanceRecord>(address)
+        .await
+        .unwrap();
+    assert_eq!(inheritance.authority, authority.pubkey());
+    assert_eq!(inheritance.beneficiary, beneficiary.pubkey());
+
+    warp_to_slot(&mut context, inheritance.claimable_at_slot().unwrap()).await;
+
+    let transaction = claim_inheritance(&context, &pda, &dart, &authority, &beneficiary);
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+
+    let record = context.banks_client.get_vault_record(pda.pubkey()).await;
+    assert_eq!(record.authority, beneficiary.pubkey());
+}
+
+#[tokio::test]
+async fn claim_inheritance_fail_after_check_in() {
+    let mut context = program_test().start_with_context().await;
+
+    let pda = Keypair::new();
+    let dart = Keypair::new();
+    let authority = Keypair::new();
+    let beneficiary = Keypair::new();
+
+    initialize_account(&mut context, &pda, &dart, &authority).await;
+    set_beneficiary(&mut context, &pda, &authority, &beneficiary.pubkey(), 1_000).await;
+
+    warp_to_slot(&mut context, 900).await;
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::check_in(
+            id(),
+            &pda.pubkey(),
+            &authority.pubkey(),
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &authority],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+
+    warp_to_slot(&mut context, 1_200).await;
+    let transaction = claim_inheritance(&context, &pda, &dart, &authority, &beneficiary);
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::InactivityPeriodActive as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn claim_inheritance_fail_authority_changed() {
+    let mut context = program_test().start_with_context().await;
+
+    let pda = Keypair::new();
+    let dart = Keypair::new();
+    let authority = Keypair::new();
+    let beneficiary = Keypair::new();
+
+    initialize_account(&mut context, &pda, &dart, &authority).await;
+    set_beneficiary(&mut context, &pda, &authority, &beneficiary.pubkey(), 10).await;
+
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::transfer_authority(
+            id(),
+            &pda.pubkey(),
+            &dart.pubkey(),
+            &authority.pubkey(),
+            &Pubkey::new_unique(),
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &dart, &authority],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+
+    warp_to_slot(&mut context, 100).await;
+    let transaction = claim_inheritance(&context, &pda, &dart, &authority, &beneficiary);
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::NoBeneficiary as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn set_tag_success() {
+    let mut context = program_test().start_with_context().await;
+
+    let pda = Keypair::new();
+    let dart = Keypair::new();
+    let authority = Keypair::new();
+
+    initialize_account(&mut context, &pda, &dart, &authority).await;
+
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::set_tag(id(), &pda.pubkey(), &dart.pubkey(), 7)],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &dart],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+
+    let record = context.banks_client.get_vault_record(pda.pubkey()).await;
+    assert_eq!(record.tag, 7);
+    let data = context
+        .banks_client
+        .get_account(pda.pubkey())
+        .await
+        .unwrap()
+        .unwrap()
+        .data;
+    let (offset, bytes) = VaultRecord::tag_filter(7);
+    assert_eq!(&data[offset..offset + bytes.len()], bytes);
+}
+
+#[tokio::test]
+async fn set_tag_fail_authority_signer() {
+    let mut context = program_test().start_with_context().await;
+
+    let pda = Keypair::new();
+    let dart = Keypair::new();
+    let authority = Keypair::new();
+
+    initialize_account(&mut context, &pda, &dart, &authority).await;
+
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::set_tag(
+            id(),
+            &pda.pubkey(),
+            &authority.pubkey(),
+            7,
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &authority],
+        context.last_blockhash,
+    );
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::IncorrectDart as u32)
+        )
+    );
+}
+
+fn create_sized_vault(
+    context: &ProgramTestContext,
+    pda: &Keypair,
+    dart: &Keypair,
+    authority: &Keypair,
+    space: usize,
+    data_len: u32,
+) -> Transaction {
+    Transaction::new_signed_with_payer(
+        &[
+            system_instruction::create_account(
+                &context.payer.pubkey(),
+                &pda.pubkey(),
+                Rent::default().minimum_balance(space),
+                space as u64,
+                &id(),
+            ),
+            instruction::initialize_sized(
+                id(),
+                &pda.pubkey(),
+                &dart.pubkey(),
+                &authority.pubkey(),
+                data_len,
+            ),
+        ],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, pda, dart],
+        context.last_blockhash,
+    )
+}
+
+#[tokio::test]
+async fn initialize_sized_success() {
+    let mut context = program_test().start_with_context().await;
+
+    let pda = Keypair::new();
+    let dart = Keypair::new();
+    let authority = Keypair::new();
+
+    let transaction = create_sized_vault(&context, &pda, &dart, &authority, 1024, 1024);
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+
+    let record = context.banks_client.get_vault_record(pda.pubkey()).await;
+    assert_eq!(record.authority, authority.pubkey());
+    assert_eq!(record.data_len, 1024);
+    assert_eq!(record.account_len(), 1024);
+
+    // Sized vaults accept the same instructions as fixed-size ones.
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::set_tag(id(), &pda.pubkey(), &dart.pubkey(), 3)],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &dart],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+    let record = context.banks_client.get_vault_record(pda.pubkey()).await;
+    assert_eq!(record.tag, 3);
+}
+
+#[tokio::test]
+async fn initialize_sized_fail_length_mismatch() {
+    let mut context = program_test().start_with_context().await;
+
+    let pda = Keypair::new();
+    let dart = Keypair::new();
+    let authority = Keypair::new();
+
+    let transaction = create_sized_vault(&context, &pda, &dart, &authority, 1024, 2048);
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(VaultError::InvalidDataLength as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn initialize_fail_wrong_account_size() {
+    let mut context = program_test().start_with_context().await;
+
+    let dart = Keypair::new();
+    let authority = Keypair::new();
+
+    // Neither the current nor the version 3 layout: rejected before deserializing.
+    for space in [64, VaultRecord::LEN - 1, VaultRecord::LEN + 1] {
+        let pda = Keypair::new();
+        let transaction = Transaction::new_signed_with_payer(
+            &[
+                system_instruction::create_account(
+                    &context.payer.pubkey(),
+                    &pda.pubkey(),
+                    Rent::default().minimum_balance(space),
+                    space as u64,
+                    &id(),
+                ),
+                instruction::initialize(id(), &pda.pubkey(), &dart.pubkey(), &authority.pubkey()),
+            ],
+            Some(&context.payer.pubkey()),
+            &[&context.payer, &pda, &dart],
+            context.last_blockhash,
+        );
+        assert_eq!(
+            context
+                .banks_client
+                .process_transaction(transaction)
+                .await
+                .unwrap_err()
+                .unwrap(),
+            TransactionError::InstructionError(
+                1,
+                InstructionError::Custom(VaultError::InvalidDataLength as u32)
+            ),
+            "{space} byte account"
+        );
+    }
+}
+
+#[tokio::test]
+async fn initialize_sized_fail_below_header() {
+    let mut context = program_test().start_with_context().await;
+
+    let pda = Keypair::new();
+    let dart = Keypair::new();
+    let authority = Keypair::new();
+
+    let transaction = create_sized_vault(
+        &context,
+        &pda,
+        &dart,
+        &authority,
+        VaultRecord::LEN - 1,
+        VaultRecord::LEN as u32 - 1,
+    );
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(VaultError::InvalidDataLength as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn grow_account_success() {
+    let mut context = program_test().start_with_context().await;
+
+    let pda = Keypair::new();
+    let dart = Keypair::new();
+    let authority = Keypair::new();
+
+    initialize_account(&mut context, &pda, &dart, &authority).await;
+
+    let target_len = 25_000;
+    let mut lengths = vec![];
+    for _ in 0..4 {
+        context.last_blockhash = context.get_new_latest_blockhash().await.unwrap();
+        let transaction = Transaction::new_signed_with_payer(
+            &[instruction::grow_account(
+                id(),
+                &pda.pubkey(),
+                &dart.pubkey(),
+                &context.payer.pubkey(),
+                target_len,
+            )],
+            Some(&context.payer.pubkey()),
+            &[&context.payer, &dart],
+            context.last_blockhash,
+        );
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap();
+        let record = context.banks_client.get_vault_record(pda.pubkey()).await;
+        assert_eq!(record.target_len, target_len);
+        lengths.push(record.data_len);
+    }
+    let len = VaultRecord::LEN as u32;
+    assert_eq!(lengths, [len + 10_240, len + 20_480, 25_000, 25_000]);
+
+    let account = context
+        .banks_client
+        .get_account(pda.pubkey())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(account.data.len(), 25_000);
+    assert_eq!(account.lamports, Rent::default().minimum_balance(25_000));
+}
+
+#[tokio::test]
+async fn grow_account_fail_shrink() {
+    let mut context = program_test().start_with_context().await;
+
+    let pda = Keypair::new();
+    let dart = Keypair::new();
+    let authority = Keypair::new();
+
+    initialize_account(&mut context, &pda, &dart, &authority).await;
+
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::grow_account(
+            id(),
+            &pda.pubkey(),
+            &dart.pubkey(),
+            &context.payer.pubkey(),
+            VaultRecord::LEN as u32 - 1,
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &dart],
+        context.last_blockhash,
+    );
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::InvalidDataLength as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn grow_account_fail_incorrect_system_program() {
+    let mut context = program_test().start_with_context().await;
+
+    let pda = Keypair::new();
+    let dart = Keypair::new();
+    let authority = Keypair::new();
+
+    initialize_account(&mut context, &pda, &dart, &authority).await;
+
+    let mut grow = instruction::grow_account(
+        id(),
+        &pda.pubkey(),
+        &dart.pubkey(),
+        &context.payer.pubkey(),
+        VaultRecord::LEN as u32 + 1_024,
+    );
+    grow.accounts[instruction::GROW_ACCOUNT_SYSTEM_PROGRAM_INDEX].pubkey = Pubkey::new_unique();
+    let transaction = Transaction::new_signed_with_payer(
+        &[grow],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &dart],
+        context.last_blockhash,
+    );
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        expect_vault_error(VaultError::IncorrectProgram)
+    );
+}
+
+#[tokio::test]
+async fn create_session_fail_incorrect_system_program() {
+    let mut context = program_test().start_with_context().await;
+
+    let pda = Keypair::new();
+    let dart = Keypair::new();
+    let authority = Keypair::new();
+
+    initialize_account(&mut context, &pda, &dart, &authority).await;
+
+    let mut create = instruction::create_session(
+        id(),
+        &pda.pubkey(),
+        &authority.pubkey(),
+        &context.payer.pubkey(),
+        Pubkey::new_unique(),
+        u64::MAX,
+        session_scope::TRANSFER_AUTHORITY,
+    );
+    create.accounts[instruction::CREATE_SESSION_SYSTEM_PROGRAM_INDEX].pubkey = Pubkey::new_unique();
+    let transaction = Transaction::new_signed_with_payer(
+        &[create],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &authority],
+        context.last_blockhash,
+    );
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        expect_vault_error(VaultError::IncorrectProgram)
+    );
+}
+
+// Fund `funder` with `lamports`, then have it pay for and initialize a vault account.
+async fn initialize_account_with_rent_payer(
+    context: &mut ProgramTestContext,
+    pda: &Keypair,
+    dart: &Keypair,
+    authority: &Keypair,
+    funder: &Keypair,
+    lamports: u64,
+) {
+    let space = VaultRecord::LEN;
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            system_instruction::transfer(&context.payer.pubkey(), &funder.pubkey(), lamports),
+            system_instruction::create_account(
+                &funder.pubkey(),
+                &pda.pubkey(),
+                Rent::default().minimum_balance(space),
+                space as u64,
+                &id(),
+            ),
+            instruction::with_rent_payer(
+                instruction::initialize(id(), &pda.pubkey(), &dart.pubkey(), &authority.pubkey()),
+                &funder.pubkey(),
+            ),
+        ],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, funder, pda, dart],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn close_account_refunds_rent_payer() {
+    let mut context = program_test().start_with_context().await;
+
+    let pda = Keypair::new();
+    let dart = Keypair::new();
+    let authority = Keypair::new();
+    let funder = Keypair::new();
+    let rent = Rent::default().minimum_balance(VaultRecord::LEN);
+
+    initialize_account_with_rent_payer(&mut context, &pda, &dart, &authority, &funder, 2 * rent)
+        .await;
+    let record = context.banks_client.get_vault_record(pda.pubkey()).await;
+    assert_eq!(record.rent_payer, funder.pubkey());
+
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::with_rent_refund(
+            instruction::close_account(id(), &pda.pubkey(), &dart.pubkey(), &authority.pubkey()),
+            &funder.pubkey(),
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &dart, &authority],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+
+    let funder_account = context
+        .banks_client
+        .get_account(funder.pubkey())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(funder_account.lamports, 2 * rent);
+    assert!(context
+        .banks_client
+        .get_account(authority.pubkey())
+        .await
+        .unwrap()
+        .is_none());
+    context.banks_client.assert_vault_closed(pda.pubkey()).await;
+}
+
+#[tokio::test]
+async fn close_account_with_refund_to_authority() {
+    let mut context = program_test().start_with_context().await;
+
+    let pda = Keypair::new();
+    let dart = Keypair::new();
+    let authority = Keypair::new();
+    let funder = Keypair::new();
+    let rent = Rent::default().minimum_balance(VaultRecord::LEN);
+
+    initialize_account_with_rent_payer(&mut context, &pda, &dart, &authority, &funder, 2 * rent)
+        .await;
+
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::close_account_with_refund(
+            id(),
+            &pda.pubkey(),
+            &dart.pubkey(),
+            &authority.pubkey(),
+            true,
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &dart, &authority],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+
+    let recipient = context
+        .banks_client
+        .get_account(authority.pubkey())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(recipient.lamports, rent);
+    context.banks_client.assert_vault_closed(pda.pubkey()).await;
+}
+
+#[tokio::test]
+async fn close_account_fail_missing_rent_payer() {
+    let mut context = program_test().start_with_context().await;
+
+    let pda = Keypair::new();
+    let dart = Keypair::new();
+    let authority = Keypair::new();
+    let funder = Keypair::new();
+    let rent = Rent::default().minimum_balance(VaultRecord::LEN);
+
+    initialize_account_with_rent_payer(&mut context, &pda, &dart, &authority, &funder, 2 * rent)
+        .await;
+
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::close_account(
+            id(),
+            &pda.pubkey(),
+            &dart.pubkey(),
+            &authority.pubkey(),
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &dart, &authority],
+        context.last_blockhash,
+    );
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(0, InstructionError::NotEnoughAccountKeys)
+    );
+}
+
+#[tokio::test]
+async fn initialize_legacy_layout_success() {
+    let mut context = program_test().start_with_context().await;
+
+    let pda = Keypair::new();
+    let dart = Keypair::new();
+    let authority = Keypair::new();
+    let new_authority = Keypair::new();
+
+    let space = VaultRecord::LEN_V3;
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            system_instruction::create_account(
+                &context.payer.pubkey(),
+                &pda.pubkey(),
+                Rent::default().minimum_balance(space),
+                space as u64,
+                &id(),
+            ),
+            instruction::initialize(id(), &pda.pubkey(), &dart.pubkey(), &authority.pubkey()),
+            instruction::transfer_authority(
+                id(),
+                &pda.pubkey(),
+                &dart.pubkey(),
+                &authority.pubkey(),
+                &new_authority.pubkey(),
+            ),
+        ],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &pda, &dart, &authority],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+
+    let record = context.banks_client.get_vault_record(pda.pubkey()).await;
+    assert_eq!(record.version, VaultRecord::DISCRIMINATOR_VERSION);
+    assert_eq!(record.authority, new_authority.pubkey());
+    assert_eq!(record.account_len(), VaultRecord::LEN_V3);
+}
+
+#[tokio::test]
+async fn close_version_1_record_success() {
+    let pda = Pubkey::new_unique();
+    let dart = Keypair::new();
+    let authority = Keypair::new();
+
+    // A record written before the discriminator and the closed flag.
+    let mut data = vec![1];
+    data.extend_from_slice(authority.pubkey().as_ref());
+    data.extend_from_slice(dart.pubkey().as_ref());
+    let lamports = Rent::default().minimum_balance(VaultRecord::LEN_V1);
+    let mut program_test = program_test();
+    program_test.add_account(
+        pda,
+        Account {
+            lamports,
+            data,
+            owner: id(),
+            ..Account::default()
+        },
+    );
+    let mut context = program_test.start_with_context().await;
+
+    let record = context.banks_client.get_vault_record(pda).await;
+    assert_eq!(record.version, 1);
+    assert_eq!(record.authority, authority.pubkey());
+
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::close_account(
+            id(),
+            &pda,
+            &dart.pubkey(),
+            &authority.pubkey(),
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &dart, &authority],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+
+    let recipient = context
+        .banks_client
+        .get_account(authority.pubkey())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(recipient.lamports, lamports);
+    context.banks_client.assert_vault_closed(pda).await;
+}
+
+fn create_vault_with_close_recipient(
+    context: &ProgramTestContext,
+    pda: &Keypair,
+    dart: &Keypair,
+    authority: &Keypair,
+    recipient: &Pubkey,
+) -> Transaction {
+    let space = VaultRecord::LEN;
+    Transaction::new_signed_with_payer(
+        &[
+            system_instruction::create_account(
+                &context.payer.pubkey(),
+                &pda.pubkey(),
+                Rent::default().minimum_balance(space),
+                space as u64,
+                &id(),
+            ),
+            instruction::with_close_recipient(
+                instruction::initialize(id(), &pda.pubkey(), &dart.pubkey(), &authority.pubkey()),
+                recipient,
+            ),
+        ],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, pda, dart],
+        context.last_blockhash,
+    )
+}
+
+#[tokio::test]
+async fn close_account_pays_close_recipient() {
+    let mut context = program_test().start_with_context().await;
+
+    let pda = Keypair::new();
+    let dart = Keypair::new();
+    let authority = Keypair::new();
+    let recipient = Pubkey::new_unique();
+
+    let transaction =
+        create_vault_with_close_recipient(&context, &pda, &dart, &authority, &recipient);
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+    let record = context.banks_client.get_vault_record(pda.pubkey()).await;
+    assert_eq!(record.close_recipient, recipient);
+
+    // Asking for an authority refund does not redirect the lamports.
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::with_rent_refund(
+            instruction::close_account_with_refund(
+                id(),
+                &pda.pubkey(),
+                &dart.pubkey(),
+                &authority.pubkey(),
+                true,
+            ),
+            &recipient,
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &dart, &authority],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+
+    let recipient_account = context
+        .banks_client
+        .get_account(recipient)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        recipient_account.lamports,
+        Rent::default().minimum_balance(VaultRecord::LEN)
+    );
+    context.banks_client.assert_vault_closed(pda.pubkey()).await;
+}
+
+#[tokio::test]
+async fn initialize_fail_close_recipient_is_pda() {
+    let mut context = program_test().start_with_context().await;
+
+    let pda = Keypair::new();
+    let dart = Keypair::new();
+    let authority = Keypair::new();
+
+    let transaction =
+        create_vault_with_close_recipient(&context, &pda, &dart, &authority, &pda.pubkey());
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(VaultError::InvalidCloseRecipient as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn record_attestation_success() {
+    let mut context = program_test().start_with_context().await;
+
+    let pda = Keypair::new();
+    let dart = Keypair::new();
+    let authority = Keypair::new();
+
+    initialize_account(&mut context, &pda, &dart, &authority).await;
+
+    let mut vaa = vec![1, 0, 0, 0, 0, 0];
+    vaa.extend_from_slice(b"custody event");
+    let vaa_hash = vaa_digest(&vaa).unwrap();
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::record_attestation(
+            id(),
+            &pda.pubkey(),
+            &dart.pubkey(),
+            vaa_hash,
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &dart],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+
+    let record = context.banks_client.get_vault_record(pda.pubkey()).await;
+    assert_eq!(record.attestation, vaa_hash);
+}
+
+#[tokio::test]
+async fn record_attestation_fail_wrong_dart() {
+    let mut context = program_test().start_with_context().await;
+
+    let pda = Keypair::new();
+    let dart = Keypair::new();
+    let authority = Keypair::new();
+
+    initialize_account(&mut context, &pda, &dart, &authority).await;
+
+    let wrong_dart = Keypair::new();
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::record_attestation(
+            id(),
+            &pda.pubkey(),
+            &wrong_dart.pubkey(),
+            [7; 32],
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &wrong_dart],
+        context.last_blockhash,
+    );
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::IncorrectDart as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn swap_authorities_success() {
+    let mut context = program_test().start_with_context().await;
+
+    let pda_a = Keypair::new();
+    let pda_b = Keypair::new();
+    let dart = Keypair::new();
+    let authority_a = Keypair::new();
+    let authority_b = Keypair::new();
+
+    initialize_account(&mut context, &pda_a, &dart, &authority_a).await;
+    initialize_account(&mut context, &pda_b, &dart, &authority_b).await;
+
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::swap_authorities(
+            id(),
+            &pda_a.pubkey(),
+            &pda_b.pubkey(),
+            &dart.pubkey(),
+            &authority_a.pubkey(),
+            &authority_b.pubkey(),
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &dart, &authority_a, &authority_b],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+
+    let record_a = context.banks_client.get_vault_record(pda_a.pubkey()).await;
+    let record_b = context.banks_client.get_vault_record(pda_b.pubkey()).await;
+    assert_eq!(record_a.authority, authority_b.pubkey());
+    assert_eq!(record_b.authority, authority_a.pubkey());
+}
+
+#[tokio::test]
+async fn swap_authorities_fail_different_darts() {
+    let mut context = program_test().start_with_context().await;
+
+    let pda_a = Keypair::new();
+    let pda_b = Keypair::new();
+    let dart = Keypair::new();
+    let other_dart = Keypair::new();
+    let authority_a = Keypair::new();
+    let authority_b = Keypair::new();
+
+    initialize_account(&mut context, &pda_a, &dart, &authority_a).await;
+    initialize_account(&mut context, &pda_b, &other_dart, &authority_b).await;
+
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::swap_authorities(
+            id(),
+            &pda_a.pubkey(),
+            &pda_b.pubkey(),
+            &dart.pubkey(),
+            &authority_a.pubkey(),
+            &authority_b.pubkey(),
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &dart, &authority_a, &authority_b],
+        context.last_blockhash,
+    );
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::IncorrectDart as u32)
+        )
+    );
+
+    // Neither record changed.
+    let record_a = context.banks_client.get_vault_record(pda_a.pubkey()).await;
+    assert_eq!(record_a.authority, authority_a.pubkey());
+}
+
+async fn propose_transfer(
+    context: &mut ProgramTestContext,
+    pda: &Keypair,
+    dart: &Keypair,
+    authority: &Keypair,
+    new_authority: &Pubkey,
+    timeout_slots: u64,
+) {
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::propose_transfer(
+            id(),
+            &pda.pubkey(),
+            &dart.pubkey(),
+            &authority.pubkey(),
+            new_authority,
+            timeout_slots,
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, dart, authority],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn escrowed_transfer_success() {
+    let mut context = program_test().start_with_context().await;
+
+    let pda = Keypair::new();
+    let dart = Keypair::new();
+    let authority = Keypair::new();
+    let new_authority = Keypair::new();
+
+    initialize_account(&mut context, &pda, &dart, &authority).await;
+    propose_transfer(
+        &mut context,
+        &pda,
+        &dart,
+        &authority,
+        &new_authority.pubkey(),
+        1_000,
+    )
+    .await;
+
+    let record = context.banks_client.get_vault_record(pda.pubkey()).await;
+    assert_eq!(record.status, VaultStatus::PendingTransfer);
+    assert_eq!(record.pending_authority, new_authority.pubkey());
+
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::accept_transfer(
+            id(),
+            &pda.pubkey(),
+            &dart.pubkey(),
+            &authority.pubkey(),
+            &new_authority.pubkey(),
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &new_authority],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+
+    let record = context.banks_client.get_vault_record(pda.pubkey()).await;
+    assert_eq!(record.status, VaultStatus::Active);
+    assert_eq!(record.authority, new_authority.pubkey());
+    assert_eq!(record.pending_authority, Pubkey::default());
+}
+
+#[tokio::test]
+async fn escrowed_transfer_expires() {
+    let mut context = program_test().start_with_context().await;
+
+    let pda = Keypair::new();
+    let dart = Keypair::new();
+    let authority = Keypair::new();
+    let new_authority = Keypair::new();
+
+    initialize_account(&mut context, &pda, &dart, &authority).await;
+    propose_transfer(
+        &mut context,
+        &pda,
+        &dart,
+        &authority,
+        &new_authority.pubkey(),
+        100,
+    )
+    .await;
+
+    // Too early to expire.
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::expire_transfer(id(), &pda.pubkey())],
+        Some(&context.payer.pubkey()),
+        &[&context.payer],
+        context.last_blockhash,
+    );
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::TransferNotExpired as u32)
+        )
+    );
+
+    let record = context.banks_client.get_vault_record(pda.pubkey()).await;
+    warp_to_slot(&mut context, record.transfer_expires_at_slot + 1).await;
+
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::accept_transfer(
+            id(),
+            &pda.pubkey(),
+            &dart.pubkey(),
+            &authority.pubkey(),
+            &new_authority.pubkey(),
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &new_authority],
+        context.last_blockhash,
+    );
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::TransferExpired as u32)
+        )
+    );
+
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::expire_transfer(id(), &pda.pubkey())],
+        Some(&context.payer.pubkey()),
+        &[&context.payer],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+
+    let record = context.banks_client.get_vault_record(pda.pubkey()).await;
+    assert_eq!(record.status, VaultStatus::Active);
+    assert_eq!(record.authority, authority.pubkey());
+    assert_eq!(record.pending_authority, Pubkey::default());
+}
+
+fn price_feed(trading: bool) -> Account {
+    let data = oracle::pyth_price_data(100, trading, 0);
+    Account {
+        lamports: Rent::default().minimum_balance(data.len()),
+        data,
+        owner: Pubkey::new_unique(),
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+#[tokio::test]
+async fn oracle_gates_transfer_authority() {
+    let feed = Pubkey::new_unique();
+    let mut program_test = program_test();
+    program_test.add_account(feed, price_feed(false));
+    let mut context = program_test.start_with_context().await;
+
+    let pda = Keypair::new();
+    let dart = Keypair::new();
+    let authority = Keypair::new();
+    let new_authority = Keypair::new();
+
+    initialize_account(&mut context, &pda, &dart, &authority).await;
+
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::set_oracle(
+            id(),
+            &pda.pubkey(),
+            &dart.pubkey(),
+            Some(&feed),
+            1_000,
+            50,
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &dart],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+
+    let transfer = instruction::transfer_authority(
+        id(),
+        &pda.pubkey(),
+        &dart.pubkey(),
+        &authority.pubkey(),
+        &new_authority.pubkey(),
+    );
+    for (instruction, error) in [
+        (transfer.clone(), VaultError::OracleUnavailable),
+        (
+            instruction::with_oracle(transfer.clone(), &feed),
+            VaultError::OracleRejected,
+        ),
+    ] {
+        let transaction = Transaction::new_signed_with_payer(
+            &[instruction],
+            Some(&context.payer.pubkey()),
+            &[&context.payer, &dart, &authority],
+            context.last_blockhash,
+        );
+        assert_eq!(
+            context
+                .banks_client
+                .process_transaction(transaction)
+                .await
+                .unwrap_err()
+                .unwrap(),
+            TransactionError::InstructionError(0, InstructionError::Custom(error as u32))
+        );
+    }
+
+    // Once the market opens the transfer goes through.
+    context.set_account(&feed, &price_feed(true).into());
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::with_oracle(transfer, &feed)],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &dart, &authority],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+
+    let record = context.banks_client.get_vault_record(pda.pubkey()).await;
+    assert_eq!(record.authority, new_authority.pubkey());
+}
+
+#[tokio::test]
+async fn receipt_minted_and_burned_on_close() {
+    let mut context = program_test().start_with_context().await;
+
+    let pda = Keypair::new();
+    let dart = Keypair::new();
+    let authority = Keypair::new();
+
+    initialize_account(&mut context, &pda, &dart, &authority).await;
+
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::mint_receipt(
+            id(),
+            &pda.pubkey(),
+            &dart.pubkey(),
+            &context.payer.pubkey(),
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &dart],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+
+    let (mint, _) = receipt::find_mint_address(&id(), &pda.pubkey());
+    let (token_account, _) = receipt::find_token_account_address(&id(), &pda.pubkey());
+    let record = context.banks_client.get_vault_record(pda.pubkey()).await;
+    assert_eq!(record.receipt_mint, mint);
+    let account = context
+        .banks_client
+        .get_account(token_account)
+        .await
+        .unwrap()
+        .unwrap();
+    let token = spl_token::state::Account::unpack(&account.data).unwrap();
+    assert_eq!(token.owner, authority.pubkey());
+    assert_eq!(token.amount, 1);
+
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::with_receipt_burn(
+            instruction::close_account(id(), &pda.pubkey(), &dart.pubkey(), &authority.pubkey()),
+            &pda.pubkey(),
+            &token_account,
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &dart, &authority],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+
+    assert!(context
+        .banks_client
+        .get_account(token_account)
+        .await
+        .unwrap()
+        .is_none());
+    let account = context
+        .banks_client
+        .get_account(mint)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        spl_token::state::Mint::unpack(&account.data)
+            .unwrap()
+            .supply,
+        0
+    );
+    context.banks_client.assert_vault_closed(pda.pubkey()).await;
+}
+
+#[tokio::test]
+async fn mint_receipt_fail_incorrect_token_program() {
+    let mut context = program_test().start_with_context().await;
+
+    let pda = Keypair::new();
+    let dart = Keypair::new();
+    let authority = Keypair::new();
+
+    initialize_account(&mut context, &pda, &dart, &authority).await;
+
+    let mut mint =
+        instruction::mint_receipt(id(), &pda.pubkey(), &dart.pubkey(), &context.payer.pubkey());
+    mint.accounts[instruction::MINT_RECEIPT_TOKEN_PROGRAM_INDEX].pubkey = Pubkey::new_unique();
+    let transaction = Transaction::new_signed_with_payer(
+        &[mint],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &dart],
+        context.last_blockhash,
+    );
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        expect_vault_error(VaultError::IncorrectProgram)
+    );
+}
+
+#[tokio::test]
+async fn receipt_holder_is_authority() {
+    let mut context = program_test().start_with_context().await;
+
+    let pda = Keypair::new();
+    let dart = Keypair::new();
+    let authority = Keypair::new();
+    let buyer = Keypair::new();
+    let buyer_account = Keypair::new();
+
+    initialize_account(&mut context, &pda, &dart, &authority).await;
+
+    let (mint, _) = receipt::find_mint_address(&id(), &pda.pubkey());
+    let (token_account, _) = receipt::find_token_account_address(&id(), &pda.pubkey());
+    let space = spl_token::state::Account::LEN;
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            instruction::mint_receipt(id(), &pda.pubkey(), &dart.pubkey(), &context.payer.pubkey()),
+            instruction::set_receipt_authority(
+                id(),
+                &pda.pubkey(),
+                &dart.pubkey(),
+                &authority.pubkey(),
+                &token_account,
+                true,
+            ),
+            system_instruction::create_account(
+                &context.payer.pubkey(),
+                &buyer_account.pubkey(),
+                Rent::default().minimum_balance(space),
+                space as u64,
+                &spl_token::id(),
+            ),
+            spl_token::instruction::initialize_account3(
+                &spl_token::id(),
+                &buyer_account.pubkey(),
+                &mint,
+                &buyer.pubkey(),
+            )
+            .unwrap(),
+            spl_token::instruction::transfer(
+                &spl_token::id(),
+                &token_account,
+                &buyer_account.pubkey(),
+                &authority.pubkey(),
+                &[],
+                1,
+            )
+            .unwrap(),
+        ],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &dart, &authority, &buyer_account],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+
+    // The former authority no longer controls the vault.
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::with_receipt_holder(
+            instruction::close_account(id(), &pda.pubkey(), &dart.pubkey(), &authority.pubkey()),
+            &buyer_account.pubkey(),
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &dart, &authority],
+        context.last_blockhash,
+    );
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::IncorrectAuthority as u32)
+        )
+    );
+
+    // The buyer does, and authority transfers must go through the token.
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::with_receipt_holder(
+            instruction::transfer_authority(
+                id(),
+                &pda.pubkey(),
+                &dart.pubkey(),
+                &buyer.pubkey(),
+                &authority.pubkey(),
+            ),
+            &buyer_account.pubkey(),
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &dart, &buyer],
+        context.last_blockhash,
+    );
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::AuthorityFollowsReceipt as u32)
+        )
+    );
+
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::with_vault_index(
+            instruction::with_receipt_burn(
+                instruction::close_account(id(), &pda.pubkey(), &dart.pubkey(), &buyer.pubkey()),
+                &pda.pubkey(),
+                &buyer_account.pubkey(),
+            ),
+            &authority.pubkey(),
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &dart, &buyer],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+    context.banks_client.assert_vault_closed(pda.pubkey()).await;
+}
+
+fn token_2022_account(data: Vec<u8>) -> Account {
+    Account {
+        lamports: Rent::default().minimum_balance(data.len()),
+        data,
+        owner: transfer_hook::TOKEN_2022_PROGRAM_ID,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+fn token_2022_mint(authority: &Pubkey) -> Account {
+    let mut data = vec![0; 82];
+    data[..4].copy_from_slice(&1u32.to_le_bytes());
+    data[4..36].copy_from_slice(authority.as_ref());
+    data[45] = 1;
+    token_2022_account(data)
+}
+
+fn token_2022_holding(mint: &Pubkey, owner: &Pubkey) -> Account {
+    let mut data = vec![0; 165];
+    data[..32].copy_from_slice(mint.as_ref());
+    data[32..64].copy_from_slice(owner.as_ref());
+    data[108] = 1;
+    token_2022_account(data)
+}
+
+async fn execute_transfer_hook(
+    context: &mut ProgramTestContext,
+    mint: &Pubkey,
+    destination: &Pubkey,
+    pda: &Keypair,
+    amount: u64,
+) -> Result<(), BanksClientError> {
+    let source = Pubkey::new_unique();
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::transfer_hook_execute(
+            id(),
+            &source,
+            mint,
+            destination,
+            &Pubkey::new_unique(),
+            &pda.pubkey(),
+            amount,
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer],
+        context.last_blockhash,
+    );
+    context.banks_client.process_transaction(transaction).await
+}
+
+#[tokio::test]
+async fn transfer_hook_enforces_vault_compliance() {
+    let mint = Pubkey::new_unique();
+    let mint_authority = Keypair::new();
+    let (holder, other) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let (destination, other_destination) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let mut program_test = program_test();
+    program_test.add_account(mint, token_2022_mint(&mint_authority.pubkey()));
+    program_test.add_account(destination, token_2022_holding(&mint, &holder));
+    program_test.add_account(other_destination, token_2022_holding(&mint, &other));
+    let mut context = program_test.start_with_context().await;
+
+    let pda = Keypair::new();
+    let dart = Keypair::new();
+    let authority = Keypair::new();
+
+    initialize_account(&mut context, &pda, &dart, &authority).await;
+
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::initialize_transfer_hook(
+            id(),
+            &pda.pubkey(),
+            &dart.pubkey(),
+            &mint_authority.pubkey(),
+            &context.payer.pubkey(),
+            &mint,
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &dart, &mint_authority],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+
+    // The token program resolves the config and the vault from the extra account metas.
+    let (config, _) = TransferHookConfig::find_address(&id(), &mint);
+    let (metas, _) = transfer_hook::find_extra_account_metas_address(&id(), &mint);
+    let account = context
+        .banks_client
+        .get_account(metas)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        account.data,
+        transfer_hook::pack_extra_account_metas(&[config, pda.pubkey()])
+    );
+
+    // Anyone may receive the token while the allowlist is empty.
+    execute_transfer_hook(&mut context, &mint, &other_destination, &pda, 1)
+        .await
+        .unwrap();
+
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::set_allowlist_entry(
+            id(),
+            &pda.pubkey(),
+            &dart.pubkey(),
+            &mint,
+            0,
+            holder,
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &dart],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+
+    execute_transfer_hook(&mut context, &mint, &destination, &pda, 2)
+        .await
+        .unwrap();
+    assert_eq!(
+        execute_transfer_hook(&mut context, &mint, &other_destination, &pda, 3)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::HolderNotAllowed as u32)
+        )
+    );
+
+    // Freezing the vault halts every transfer of the token.
+    let mut account = context
+        .banks_client
+        .get_account(pda.pubkey())
+        .await
+        .unwrap()
+        .unwrap();
+    let mut record = VaultRecord::from_account_data(&account.data).unwrap();
+    record.status = VaultStatus::Frozen;
+    record.pack_into(&mut account.data).unwrap();
+    context.set_account(&pda.pubkey(), &account.into());
+
+    assert_eq!(
+        execute_transfer_hook(&mut context, &mint, &destination, &pda, 4)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::TransfersRestricted as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn initialize_transfer_hook_fail_wrong_mint_authority() {
+    let mint = Pubkey::new_unique();
+    let mut program_test = program_test();
+    program_test.add_account(mint, token_2022_mint(&Pubkey::new_unique()));
+    let mut context = program_test.start_with_context().await;
+
+    let pda = Keypair::new();
+    let dart = Keypair::new();
+    let authority = Keypair::new();
+    let mint_authority = Keypair::new();
+
+    initialize_account(&mut context, &pda, &dart, &authority).await;
+
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::initialize_transfer_hook(
+            id(),
+            &pda.pubkey(),
+            &dart.pubkey(),
+            &mint_authority.pubkey(),
+            &context.payer.pubkey(),
+            &mint,
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &dart, &mint_authority],
+        context.last_blockhash,
+    );
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::IncorrectAuthority as u32)
+        )
+    );
+}
+
+/// Seed of the mock caller's treasury, standing in for an SPL Governance treasury or a
+/// Squads vault.
+const TREASURY_SEED: &[u8] = b"treasury";
+
+// Mock caller: forwards its instruction data to the vault program (account 0) with the
+// remaining accounts, signing for its treasury PDA.
+fn treasury_process_instruction(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    input: &[u8],
+) -> ProgramResult {
+    let (treasury, bump) = Pubkey::find_program_address(&[TREASURY_SEED], program_id);
+    let instruction = Instruction {
+        program_id: *accounts[0].key,
+        accounts: accounts[1..]
+            .iter()
+            .map(|account| AccountMeta {
+                pubkey: *account.key,
+                is_signer: account.is_signer || *account.key == treasury,
+                is_writable: account.is_writable,
+            })
+            .collect(),
+        data: input.to_vec(),
+    };
+    invoke_signed(&instruction, accounts, &[&[TREASURY_SEED, &[bump]]])
+}
+
+fn treasury(caller: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[TREASURY_SEED], caller).0
+}
+
+// Route a vault instruction through `caller`, which signs for its treasury.
+fn via_treasury(caller: &Pubkey, instruction: Instruction) -> Instruction {
+    let treasury = treasury(caller);
+    let mut accounts = vec![AccountMeta::new_readonly(instruction.program_id, false)];
+    accounts.extend(instruction.accounts.into_iter().map(|mut meta| {
+        meta.is_signer &= meta.pubkey != treasury;
+        meta
+    }));
+    Instruction {
+        program_id: *caller,
+        accounts,
+        data: instruction.data,
+    }
+}
+
+async fn initialize_treasury_vault(
+    context: &mut ProgramTestContext,
+    pda: &Keypair,
+    dart: &Keypair,
+    authority: &Pubkey,
+) {
+    let space = VaultRecord::LEN;
+    let lamports = Rent::default().minimum_balance(space);
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            system_instruction::create_account(
+                &context.payer.pubkey(),
+                &pda.pubkey(),
+                lamports,
+                space as u64,
+                &id(),
+            ),
+            instruction::initialize(id(), &pda.pubkey(), &dart.pubkey(), authority),
+        ],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, pda, dart],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn pda_authority_via_cpi_success() {
+    let caller = Pubkey::new_unique();
+    let mut program_test = program_test();
+    program_test.add_program("treasury", caller, processor!(treasury_process_instruction));
+    let mut context = program_test.start_with_context().await;
+
+    let pda = Keypair::new();
+    let dart = Keypair::new();
+    let new_authority = Keypair::new();
+    let authority = treasury(&caller);
+
+    initialize_treasury_vault(&mut context, &pda, &dart, &authority).await;
+
+    let transaction = Transaction::new_signed_with_payer(
+        &[via_treasury(
+            &caller,
+            instruction::transfer_authority(
+                id(),
+                &pda.pubkey(),
+                &dart.pubkey(),
+                &authority,
+                &new_authority.pubkey(),
+            ),
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &dart],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+
+    let record = context.banks_client.get_vault_record(pda.pubkey()).await;
+    assert_eq!(record.authority, new_authority.pubkey());
+}
+
+#[tokio::test]
+async fn pda_authority_via_cpi_fail_other_program() {
+    let (caller, other_caller) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let mut program_test = program_test();
+    for program_id in [caller, other_caller] {
+        program_test.add_program(
+            "treasury",
+            program_id,
+            processor!(treasury_process_instruction),
+        );
+    }
+    let mut context = program_test.start_with_context().await;
+
+    let pda = Keypair::new();
+    let dart = Keypair::new();
+    let new_authority = Keypair::new();
+
+    initialize_treasury_vault(&mut context, &pda, &dart, &treasury(&caller)).await;
+
+    // The same seeds under another program derive a different signer.
+    let transaction = Transaction::new_signed_with_payer(
+        &[via_treasury(
+            &other_caller,
+            instruction::transfer_authority(
+                id(),
+                &pda.pubkey(),
+                &dart.pubkey(),
+                &treasury(&other_caller),
+                &new_authority.pubkey(),
+            ),
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &dart],
+        context.last_blockhash,
+    );
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::IncorrectAuthority as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn transfer_authority_emits_event() {
+    let mut context = program_test().start_with_context().await;
+
+    let pda = Keypair::new();
+    let dart = Keypair::new();
+    let authority = Keypair::new();
+    let new_authority = Keypair::new();
+
+    initialize_account(&mut context, &pda, &dart, &authority).await;
+
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::with_event_authority(
+            instruction::transfer_authority(
+                id(),
+                &pda.pubkey(),
+                &dart.pubkey(),
+                &authority.pubkey(),
+                &new_authority.pubkey(),
+            ),
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &dart, &authority],
+        context.last_blockhash,
+    );
+    let result = context
+        .banks_client
+        .process_transaction_with_metadata(transaction)
+        .await
+        .unwrap();
+    assert_eq!(result.result, Ok(()));
+    let logs = result.metadata.unwrap().log_messages;
+    assert!(logs.iter().any(|log| log.starts_with("Program data: ")));
+
+    // With `emit-cpi` the event is also recorded as the program invoking itself.
+    let self_cpi = format!("Program {} invoke [2]", id());
+    assert_eq!(logs.contains(&self_cpi), cfg!(feature = "emit-cpi"));
+}
+
+#[tokio::test]
+async fn rekey_success() {
+    let mut context = program_test().start_with_context().await;
+
+    let pda = Keypair::new();
+    let new_pda = Keypair::new();
+    let dart = Keypair::new();
+    let authority = Keypair::new();
+
+    initialize_account(&mut context, &pda, &dart, &authority).await;
+    let record = context.banks_client.get_vault_record(pda.pubkey()).await;
+
+    let space = VaultRecord::LEN;
+    let lamports = Rent::default().minimum_balance(space);
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            system_instruction::create_account(
+                &context.payer.pubkey(),
+                &new_pda.pubkey(),
+                lamports,
+                space as u64,
+                &id(),
+            ),
+            instruction::rekey(
+                id(),
+                &pda.pubkey(),
+                &new_pda.pubkey(),
+                &dart.pubkey(),
+                &authority.pubkey(),
+            ),
+        ],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &new_pda, &dart, &authority],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        context
+            .banks_client
+            .get_vault_record(new_pda.pubkey())
+            .await,
+        record
+    );
+    assert_eq!(
+        context
+            .banks_client
+            .get_account(pda.pubkey())
+            .await
+            .unwrap(),
+        None
+    );
+    assert_eq!(
+        context
+            .banks_client
+            .get_balance(authority.pubkey())
+            .await
+            .unwrap(),
+        lamports
+    );
+}
+
+#[tokio::test]
+async fn rekey_drops_companion_flags() {
+    let mut context = program_test().start_with_context().await;
+
+    let pda = Keypair::new();
+    let new_pda = Keypair::new();
+    let dart = Keypair::new();
+    let authority = Keypair::new();
+    initialize_account(&mut context, &pda, &dart, &authority).await;
+
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            instruction::create_audit_trail(
+                id(),
+                &pda.pubkey(),
+                &dart.pubkey(),
+                &context.payer.pubkey(),
+                2,
+            ),
+            instruction::with_audit_trail(
+                instruction::set_rate_limit(
+                    id(),
+                    &pda.pubkey(),
+                    &dart.pubkey(),
+                    &context.payer.pubkey(),
+                    4,
+                ),
+                &pda.pubkey(),
+            ),
+        ],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &dart],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+    let record = context.banks_client.get_vault_record(pda.pubkey()).await;
+    assert!(record.audited && record.rate_limited);
+
+    let space = VaultRecord::LEN;
+    let rekey = instruction::rekey(
+        id(),
+        &pda.pubkey(),
+        &new_pda.pubkey(),
+        &dart.pubkey(),
+        &authority.pubkey(),
+    );
+    let rekey = instruction::with_rate_limit(
+        instruction::with_audit_trail(rekey, &pda.pubkey()),
+        &pda.pubkey(),
+    );
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            system_instruction::create_account(
+                &context.payer.pubkey(),
+                &new_pda.pubkey(),
+                Rent::default().minimum_balance(space),
+                space as u64,
+                &id(),
+            ),
+            rekey,
+        ],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &new_pda, &dart, &authority],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+
+    let record = context
+        .banks_client
+        .get_vault_record(new_pda.pubkey())
+        .await;
+    assert!(!record.audited && !record.rate_limited && !record.transfer_windowed);
+
+    // The new vault takes instructions without the old vault's companion accounts.
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::set_tag(
+            id(),
+            &new_pda.pubkey(),
+            &dart.pubkey(),
+            5,
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &dart],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+    assert_eq!(
+        context
+            .banks_client
+            .get_vault_record(new_pda.pubkey())
+            .await
+            .tag,
+        5
+    );
+}
+
+#[tokio::test]
+async fn rekey_fail_length_mismatch() {
+    let mut context = program_test().start_with_context().await;
+
+    let pda = Keypair::new();
+    let new_pda = Keypair::new();
+    let dart = Keypair::new();
+    let authority = Keypair::new();
+
+    initialize_account(&mut context, &pda, &dart, &authority).await;
+
+    let space = VaultRecord::LEN + 1;
+    let lamports = Rent::default().minimum_balance(space);
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            system_instruction::create_account(
+                &context.payer.pubkey(),
+                &new_pda.pubkey(),
+                lamports,
+                space as u64,
+                &id(),
+            ),
+            instruction::rekey(
+                id(),
+                &pda.pubkey(),
+                &new_pda.pubkey(),
+                &dart.pubkey(),
+                &authority.pubkey(),
+            ),
+        ],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &new_pda, &dart, &authority],
+        context.last_blockhash,
+    );
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(VaultError::InvalidDataLength as u32)
+        )
+    );
+}
+
+// The `ProgramData` account of the vault program, upgradeable by `authority`.
+fn program_data(authority: Option<&Pubkey>) -> Account {
+    let mut data = vec![3, 0, 0, 0];
+    data.extend_from_slice(&0u64.to_le_bytes());
+    match authority {
+        Some(authority) => {
+            data.push(1);
+            data.extend_from_slice(authority.as_ref());
+        }
+        None => data.extend_from_slice(&[0; 33]),
+    }
+    Account {
+        lamports: Rent::default().minimum_balance(data.len()),
+        data,
+        owner: bpf_loader_upgradeable::id(),
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+#[tokio::test]
+async fn initialize_config_by_upgrade_authority() {
+    let upgrade_authority = Keypair::new();
+    let mut program_test = program_test();
+    program_test.add_account(
+        program_data_address(&id()),
+        program_data(Some(&upgrade_authority.pubkey())),
+    );
+    let mut context = program_test.start_with_context().await;
+
+    // Anyone else is refused, even paying for the config themselves.
+    let impostor = Keypair::new();
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::initialize_config(
+            id(),
+            &impostor.pubkey(),
+            &context.payer.pubkey(),
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &impostor],
+        context.last_blockhash,
+    );
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::NotUpgradeAuthority as u32)
+        )
+    );
+
+    let initialize =
+        instruction::initialize_config(id(), &upgrade_authority.pubkey(), &context.payer.pubkey());
+    let transaction = Transaction::new_signed_with_payer(
+        std::slice::from_ref(&initialize),
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &upgrade_authority],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+    let (address, bump) = ProgramConfig::find_address(&id());
+    let account = context
+        .banks_client
+        .get_account(address)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        ProgramConfig::try_from_slice(&account.data).unwrap(),
+        ProgramConfig::new(upgrade_authority.pubkey(), bump)
+    );
+
+    context.last_blockhash = context.get_new_latest_blockhash().await.unwrap();
+    let transaction = Transaction::new_signed_with_payer(
+        &[initialize],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &upgrade_authority],
+        context.last_blockhash,
+    );
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(0, InstructionError::AccountAlreadyInitialized)
+    );
+}
+
+#[tokio::test]
+async fn initialize_config_fail_immutable_program() {
+    let admin = Keypair::new();
+    let mut program_test = program_test();
+    program_test.add_account(program_data_address(&id()), program_data(None));
+    let mut context = program_test.start_with_context().await;
+
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::initialize_config(
+            id(),
+            &admin.pubkey(),
+            &context.payer.pubkey(),
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &admin],
+        context.last_blockhash,
+    );
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::NotUpgradeAuthority as u32)
+        )
+    );
+}
+
+// Start `program_test` with the vault config initialized by `admin`, its upgrade
+// authority.
+async fn start_with_config(mut program_test: ProgramTest, admin: &Keypair) -> ProgramTestContext {
+    program_test.add_account(
+        program_data_address(&id()),
+        program_data(Some(&admin.pubkey())),
+    );
+    let mut context = program_test.start_with_context().await;
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::initialize_config(
+            id(),
+            &admin.pubkey(),
+            &context.payer.pubkey(),
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, admin],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+    context
+}
+
+#[tokio::test]
+async fn program_config_required() {
+    let mut context = program_test().start_with_context().await;
+
+    let pda = Keypair::new();
+    let dart = Keypair::new();
+    let authority = Keypair::new();
+    initialize_account(&mut context, &pda, &dart, &authority).await;
+
+    // Leaving out the config fails, whether or not it exists, rather than skipping the
+    // decommission check.
+    let (config, _) = ProgramConfig::find_address(&id());
+    let mut instruction = instruction::transfer_authority(
+        id(),
+        &pda.pubkey(),
+        &dart.pubkey(),
+        &authority.pubkey(),
+        &Pubkey::new_unique(),
+    );
+    instruction.accounts.retain(|meta| meta.pubkey != config);
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &dart, &authority],
+        context.last_blockhash,
+    );
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(0, InstructionError::NotEnoughAccountKeys)
+    );
+    let record = context.banks_client.get_vault_record(pda.pubkey()).await;
+    assert_eq!(record.authority, authority.pubkey());
+}
+
+#[tokio::test]
+async fn decommission_fails_instructions_with_config() {
+    let admin = Keypair::new();
+    let mut context = start_with_config(program_test(), &admin).await;
+
+    let pda = Keypair::new();
+    let dart = Keypair::new();
+    let authority = Keypair::new();
+    let new_authority = Keypair::new();
+    initialize_account(&mut context, &pda, &dart, &authority).await;
+
+    // Only the admin may decommission.
+    let successor = Pubkey::new_unique();
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::decommission(id(), &dart.pubkey(), successor)],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &dart],
+        context.last_blockhash,
+    );
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::IncorrectAuthority as u32)
+        )
+    );
+
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::decommission(id(), &admin.pubkey(), successor)],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &admin],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+    let (address, _) = ProgramConfig::find_address(&id());
+    let account = context
+        .banks_client
+        .get_account(address)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        ProgramConfig::try_from_slice(&account.data)
+            .unwrap()
+            .successor,
+        successor
+    );
+
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::with_program_config(
+            instruction::transfer_authority(
+                id(),
+                &pda.pubkey(),
+                &dart.pubkey(),
+                &authority.pubkey(),
+                &new_authority.pubkey(),
+            ),
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &dart, &authority],
+        context.last_blockhash,
+    );
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::ProgramMigrated as u32)
+        )
+    );
+    let record = context.banks_client.get_vault_record(pda.pubkey()).await;
+    assert_eq!(record.authority, authority.pubkey());
+}
+
+// Start the vault program, decommissioned by `admin` in favor of a second instance of
+// itself at `successor`.
+async fn start_decommissioned(admin: &Keypair, successor: &Pubkey) -> ProgramTestContext {
+    let mut program_test = program_test();
+    program_test.add_program(
+        "vault_successor",
+        *successor,
+        processor!(vault::processor::Processor::process_instruction),
+    );
+    let mut context = start_with_config(program_test, admin).await;
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::decommission(id(), &admin.pubkey(), *successor)],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, admin],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+    context
+}
+
+#[tokio::test]
+async fn migrate_to_program_success() {
+    let admin = Keypair::new();
+    let successor = Pubkey::new_unique();
+    let mut context = start_decommissioned(&admin, &successor).await;
+
+    let pda = Keypair::new();
+    let new_pda = Keypair::new();
+    let dart = Keypair::new();
+    let authority = Keypair::new();
+    initialize_account(&mut context, &pda, &dart, &authority).await;
+    let pda_lamports = context
+        .banks_client
+        .get_balance(pda.pubkey())
+        .await
+        .unwrap();
+    let authority_lamports = context
+        .banks_client
+        .get_balance(authority.pubkey())
+        .await
+        .unwrap();
+
+    let space = VaultRecord::LEN;
+    let lamports = Rent::default().minimum_balance(space);
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            system_instruction::create_account(
+                &context.payer.pubkey(),
+                &new_pda.pubkey(),
+                lamports,
+                space as u64,
+                &successor,
+            ),
+            instruction::migrate_to_program(
+                id(),
+                &pda.pubkey(),
+                &new_pda.pubkey(),
+                &dart.pubkey(),
+                &authority.pubkey(),
+                &successor,
+            ),
+        ],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &new_pda, &dart, &authority],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+
+    let account = context
+        .banks_client
+        .get_account(new_pda.pubkey())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(account.owner, successor);
+    let record = VaultRecord::from_account_data(&account.data).unwrap();
+    assert_eq!(record.dart, dart.pubkey());
+    assert_eq!(record.authority, authority.pubkey());
+    assert_eq!(
+        context
+            .banks_client
+            .get_account(pda.pubkey())
+            .await
+            .unwrap(),
+        None
+    );
+    assert_eq!(
+        context
+            .banks_client
+            .get_balance(authority.pubkey())
+            .await
+            .unwrap(),
+        authority_lamports + pda_lamports
+    );
+}
+
+#[tokio::test]
+async fn migrate_to_program_fail_not_successor() {
+    let admin = Keypair::new();
+    let successor = Pubkey::new_unique();
+    let mut context = start_decommissioned(&admin, &successor).await;
+
+    let pda = Keypair::new();
+    let dart = Keypair::new();
+    let authority = Keypair::new();
+    initialize_account(&mut context, &pda, &dart, &authority).await;
+
+    // A program other than the recorded successor, here the system program.
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::migrate_to_program(
+            id(),
+            &pda.pubkey(),
+            &Pubkey::new_unique(),
+            &dart.pubkey(),
+            &authority.pubkey(),
+            &system_program::id(),
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &dart, &authority],
+        context.last_blockhash,
+    );
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::NotSuccessor as u32)
+        )
+    );
+    let record = context.banks_client.get_vault_record(pda.pubkey()).await;
+    assert_eq!(record.authority, authority.pubkey());
+}
+
+#[tokio::test]
+async fn transfer_with_proof_success() {
+    let mut context = program_test().start_with_context().await;
+
+    let pda = Keypair::new();
+    let dart = Keypair::new();
+    let authority = Keypair::new();
+    let new_authority = Keypair::new();
+    initialize_account(&mut context, &pda, &dart, &authority).await;
+
+    // The DART approves a batch of transfers, one of them this vault's.
+    let mut leaves: Vec<[u8; 32]> = (0..5)
+        .map(|_| {
+            approval::transfer_leaf(
+                &id(),
+                &Pubkey::new_unique(),
+                0,
+                &Pubkey::new_unique(),
+                &Pubkey::new_unique(),
+            )
+        })
+        .collect();
+    leaves.push(approval::transfer_leaf(
+        &id(),
+        &pda.pubkey(),
+        0,
+        &authority.pubkey(),
+        &new_authority.pubkey(),
+    ));
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::set_approval_root(
+            id(),
+            &dart.pubkey(),
+            &context.payer.pubkey(),
+            approval::merkle_root(&leaves),
+            u64::MAX,
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &dart],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+
+    // A transfer outside the batch is refused.
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::transfer_with_proof(
+            id(),
+            &pda.pubkey(),
+            &dart.pubkey(),
+            &authority.pubkey(),
+            &Pubkey::new_unique(),
+            approval::merkle_proof(&leaves, 5).unwrap(),
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &authority],
+        context.last_blockhash,
+    );
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::InvalidProof as u32)
+        )
+    );
+
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::transfer_with_proof(
+            id(),
+            &pda.pubkey(),
+            &dart.pubkey(),
+            &authority.pubkey(),
+            &new_authority.pubkey(),
+            approval::merkle_proof(&leaves, 5).unwrap(),
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &authority],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+    let record = context.banks_client.get_vault_record(pda.pubkey()).await;
+    assert_eq!(record.authority, new_authority.pubkey());
+    assert_eq!(record.approval_nonce, 1);
+
+    // Back with the original authority, the redeemed approval cannot be replayed.
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::transfer_authority(
+            id(),
+            &pda.pubkey(),
+            &dart.pubkey(),
+            &new_authority.pubkey(),
+            &authority.pubkey(),
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &dart, &new_authority],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+    context.last_blockhash = context.get_new_latest_blockhash().await.unwrap();
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::transfer_with_proof(
+            id(),
+            &pda.pubkey(),
+            &dart.pubkey(),
+            &authority.pubkey(),
+            &new_authority.pubkey(),
+            approval::merkle_proof(&leaves, 5).unwrap(),
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &authority],
+        context.last_blockhash,
+    );
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::InvalidProof as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn transfer_with_proof_fail_revoked() {
+    let mut context = program_test().start_with_context().await;
+
+    let pda = Keypair::new();
+    let dart = Keypair::new();
+    let authority = Keypair::new();
+    let new_authority = Keypair::new();
+    initialize_account(&mut context, &pda, &dart, &authority).await;
+
+    let leaf = approval::transfer_leaf(
+        &id(),
+        &pda.pubkey(),
+        0,
+        &authority.pubkey(),
+        &new_authority.pubkey(),
+    );
+    // Set the root, then revoke it with a zero root.
+    for root in [leaf, [0; 32]] {
+        let transaction = Transaction::new_signed_with_payer(
+            &[instruction::set_approval_root(
+                id(),
+                &dart.pubkey(),
+                &context.payer.pubkey(),
+                root,
+                u64::MAX,
+            )],
+            Some(&context.payer.pubkey()),
+            &[&context.payer, &dart],
+            context.last_blockhash,
+        );
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap();
+        context.last_blockhash = context.get_new_latest_blockhash().await.unwrap();
+    }
+
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::transfer_with_proof(
+            id(),
+            &pda.pubkey(),
+            &dart.pubkey(),
+            &authority.pubkey(),
+            &new_authority.pubkey(),
+            vec![],
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &authority],
+        context.last_blockhash,
+    );
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::InstructionExpired as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn set_note_sealed_to_authority_key() {
+    let mut context = program_test().start_with_context().await;
+
+    let pda = Keypair::new();
+    let dart = Keypair::new();
+    let authority = Keypair::new();
+    let new_authority = Keypair::new();
+    initialize_account(&mut context, &pda, &dart, &authority).await;
+
+    // No key yet.
+    let set_note = |note: [u8; ENCRYPTED_NOTE_LEN], blockhash| {
+        Transaction::new_signed_with_payer(
+            &[instruction::set_note(
+                id(),
+                &pda.pubkey(),
+                &dart.pubkey(),
+                note,
+            )],
+            Some(&context.payer.pubkey()),
+            &[&context.payer, &dart],
+            blockhash,
+        )
+    };
+    let transaction = set_note([1; ENCRYPTED_NOTE_LEN], context.last_blockhash);
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::NoteKeyNotSet as u32)
+        )
+    );
+
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::set_note_key(
+            id(),
+            &pda.pubkey(),
+            &authority.pubkey(),
+            &context.payer.pubkey(),
+            [9; 32],
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &authority],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+    let transaction = set_note([2; ENCRYPTED_NOTE_LEN], context.last_blockhash);
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+
+    let (address, _) = NoteRecord::find_address(&id(), &pda.pubkey());
+    let account = context
+        .banks_client
+        .get_account(address)
+        .await
+        .unwrap()
+        .unwrap();
+    let note = NoteRecord::try_from_slice(&account.data).unwrap();
+    assert_eq!(note.authority, authority.pubkey());
+    assert_eq!(note.note_key, [9; 32]);
+    assert_eq!(note.encrypted_note, [2; ENCRYPTED_NOTE_LEN]);
+
+    // The new authority must set its own key before the DART seals another note.
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::transfer_authority(
+            id(),
+            &pda.pubkey(),
+            &dart.pubkey(),
+            &authority.pubkey(),
+            &new_authority.pubkey(),
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &dart, &authority],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+    let transaction = set_note([3; ENCRYPTED_NOTE_LEN], context.last_blockhash);
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::NoteKeyNotSet as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn update_commitments_dart_only() {
+    let mut context = program_test().start_with_context().await;
+
+    let pda = Keypair::new();
+    let dart = Keypair::new();
+    let authority = Keypair::new();
+    initialize_account(&mut context, &pda, &dart, &authority).await;
+
+    // The authority cannot publish commitments.
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::update_commitments(
+            id(),
+            &pda.pubkey(),
+            &authority.pubkey(),
+            &context.payer.pubkey(),
+            [[1; 32]; MAX_COMMITMENTS],
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &authority],
+        context.last_blockhash,
+    );
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::IncorrectDart as u32)
+        )
+    );
+
+    // The DART creates the account, then replaces the commitments.
+    for commitments in [
+        [[1; 32]; MAX_COMMITMENTS],
+        [[2; 32], [0; 32], [3; 32], [0; 32]],
+    ] {
+        context.last_blockhash = context.get_new_latest_blockhash().await.unwrap();
+        let transaction = Transaction::new_signed_with_payer(
+            &[instruction::update_commitments(
+                id(),
+                &pda.pubkey(),
+                &dart.pubkey(),
+                &context.payer.pubkey(),
+                commitments,
+            )],
+            Some(&context.payer.pubkey()),
+            &[&context.payer, &dart],
+            context.last_blockhash,
+        );
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap();
+
+        let (address, _) = CommitmentRecord::find_address(&id(), &pda.pubkey());
+        let account = context
+            .banks_client
+            .get_account(address)
+            .await
+            .unwrap()
+            .unwrap();
+        let record = CommitmentRecord::try_from_slice(&account.data).unwrap();
+        assert_eq!(record.vault, pda.pubkey());
+        assert_eq!(record.commitments, commitments);
+    }
+}
+
+#[tokio::test]
+async fn add_note_evicts_oldest() {
+    let mut context = program_test().start_with_context().await;
+
+    let pda = Keypair::new();
+    let dart = Keypair::new();
+    let authority = Keypair::new();
+    initialize_account(&mut context, &pda, &dart, &authority).await;
+
+    let notes = MAX_LOG_NOTES as u8 + 2;
+    for n in 0..notes {
+        let transaction = Transaction::new_signed_with_payer(
+            &[instruction::add_note(
+                id(),
+                &pda.pubkey(),
+                &dart.pubkey(),
+                &context.payer.pubkey(),
+                [n; 32],
+                n.into(),
+            )],
+            Some(&context.payer.pubkey()),
+            &[&context.payer, &dart],
+            context.last_blockhash,
+        );
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap();
+    }
+
+    let (address, _) = NotesLog::find_address(&id(), &pda.pubkey());
+    let account = context
+        .banks_client
+        .get_account(address)
+        .await
+        .unwrap()
+        .unwrap();
+    let log = NotesLog::try_from_slice(&account.data).unwrap();
+    assert_eq!(log.vault, pda.pubkey());
+    assert_eq!(log.count, u64::from(notes));
+    let kept: Vec<_> = log.latest().map(|(_, note)| note.hash[0]).collect();
+    assert_eq!(kept, (2..notes).collect::<Vec<_>>());
+
+    // The authority cannot add notes.
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::add_note(
+            id(),
+            &pda.pubkey(),
+            &authority.pubkey(),
+            &context.payer.pubkey(),
+            [1; 32],
+            1,
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &authority],
+        context.last_blockhash,
+    );
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::IncorrectDart as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn attestations_expire() {
+    let mut context = program_test().start_with_context().await;
+
+    let pda = Keypair::new();
+    let dart = Keypair::new();
+    let authority = Keypair::new();
+    initialize_account(&mut context, &pda, &dart, &authority).await;
+
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            instruction::add_attestation(
+                id(),
+                &pda.pubkey(),
+                &dart.pubkey(),
+                &context.payer.pubkey(),
+                1,
+                [1; 32],
+                100,
+            ),
+            instruction::add_attestation(
+                id(),
+                &pda.pubkey(),
+                &dart.pubkey(),
+                &context.payer.pubkey(),
+                2,
+                [2; 32],
+                1_000,
+            ),
+        ],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &dart],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+
+    let (address, _) = AttestationList::find_address(&id(), &pda.pubkey());
+    let account = context
+        .banks_client
+        .get_account(address)
+        .await
+        .unwrap()
+        .unwrap();
+    let list = AttestationList::try_from_slice(&account.data).unwrap();
+    assert_eq!(list.vault, pda.pubkey());
+    assert!(list.live_attestation(1, 100).is_some());
+    assert!(list.live_attestation(1, 101).is_none());
+
+    // An attestation expiring in the past is refused.
+    warp_to_slot(&mut context, 200).await;
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::add_attestation(
+            id(),
+            &pda.pubkey(),
+            &dart.pubkey(),
+            &context.payer.pubkey(),
+            1,
+            [1; 32],
+            100,
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &dart],
+        context.last_blockhash,
+    );
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(0, InstructionError::InvalidArgument)
+    );
+
+    // Withdrawn attestations are gone; removing one twice fails.
+    for expected in [None, Some(VaultError::AttestationNotFound)] {
+        context.last_blockhash = context.get_new_latest_blockhash().await.unwrap();
+        let transaction = Transaction::new_signed_with_payer(
+            &[instruction::remove_attestation(
+                id(),
+                &pda.pubkey(),
+                &dart.pubkey(),
+                2,
+                [2; 32],
+            )],
+            Some(&context.payer.pubkey()),
+            &[&context.payer, &dart],
+            context.last_blockhash,
+        );
+        let result = context.banks_client.process_transaction(transaction).await;
+        match expected {
+            None => result.unwrap(),
+            Some(error) => assert_eq!(
+                result.unwrap_err().unwrap(),
+                TransactionError::InstructionError(0, InstructionError::Custom(error as u32))
+            ),
+        }
+    }
+    let account = context
+        .banks_client
+        .get_account(address)
+        .await
+        .unwrap()
+        .unwrap();
+    let list = AttestationList::try_from_slice(&account.data).unwrap();
+    assert!(list.live_attestation(2, 200).is_none());
+}
+
+fn expect_vault_error(error: VaultError) -> TransactionError {
+    TransactionError::InstructionError(0, InstructionError::Custom(error as u32))
+}
+
+#[tokio::test]
+async fn valid_until_slot_expires_on_schedule() {
+    let mut context = program_test().start_with_context().await;
+
+    let pda = Keypair::new();
+    let dart = Keypair::new();
+    let authority = Keypair::new();
+    let next = Keypair::new();
+    initialize_account(&mut context, &pda, &dart, &authority).await;
+
+    let transfer = |from: &Keypair, to: &Pubkey| {
+        instruction::with_valid_until_slot(
+            instruction::transfer_authority(
+                id(),
+                &pda.pubkey(),
+                &dart.pubkey(),
+                &from.pubkey(),
+                to,
+            ),
+            200,
+        )
+    };
+    // Valid through slot 200 inclusive.
+    SlotScenario::new()
+        .at(
+            200,
+            vec![transfer(&authority, &next.pubkey())],
+            &[&dart, &authority],
+        )
+        .at(
+            201,
+            vec![transfer(&next, &authority.pubkey())],
+            &[&dart, &next],
+        )
+        .fails_with(expect_vault_error(VaultError::InstructionExpired))
+        .run(&mut context)
+        .await;
+}
+
+#[tokio::test]
+async fn session_expires_on_schedule() {
+    let mut context = program_test().start_with_context().await;
+
+    let dart = Keypair::new();
+    let vaults = [Keypair::new(), Keypair::new()];
+    let authorities = [Keypair::new(), Keypair::new()];
+    let sessions = [Keypair::new(), Keypair::new()];
+    for ((pda, authority), session) in vaults.iter().zip(&authorities).zip(&sessions) {
+        initialize_account(&mut context, pda, &dart, authority).await;
+        create_session(
+            &mut context,
+            pda,
+            authority,
+            session,
+            300,
+            session_scope::TRANSFER_AUTHORITY,
+        )
+        .await;
+    }
+
+    let transfer = |pda: &Keypair, authority: &Keypair, session: &Keypair| {
+        vec![instruction::with_vault_index(
+            instruction::with_session(
+                instruction::transfer_authority(
+                    id(),
+                    &pda.pubkey(),
+                    &dart.pubkey(),
+                    &session.pubkey(),
+                    &Pubkey::new_unique(),
+                ),
+                &pda.pubkey(),
+                &session.pubkey(),
+            ),
+            &authority.pubkey(),
+        )]
+    };
+    // A session signs through its expiry slot, and not after.
+    SlotScenario::new()
+        .at(
+            300,
+            transfer(&vaults[0], &authorities[0], &sessions[0]),
+            &[&dart, &sessions[0]],
+        )
+        .at(
+            301,
+            transfer(&vaults[1], &authorities[1], &sessions[1]),
+            &[&dart, &sessions[1]],
+        )
+        .fails_with(expect_vault_error(VaultError::SessionExpired))
+        .run(&mut context)
+        .await;
+}
+
+#[tokio::test]
+async fn recovery_unlocks_on_schedule() {
+    let mut context = program_test().start_with_context().await;
+
+    let pda = Keypair::new();
+    let dart = Keypair::new();
+    let authority = Keypair::new();
+    let guardians = [Keypair::new(), Keypair::new()];
+    let new_authority = Keypair::new();
+    initialize_account(&mut context, &pda, &dart, &authority).await;
+    add_guardians(&mut context, &pda, &authority, &guardians, 1).await;
+
+    let initiated_at = 100;
+    let unlocked_at = initiated_at + RECOVERY_CHALLENGE_SLOTS;
+    let complete = || {
+        vec![instruction::complete_recovery(
+            id(),
+            &pda.pubkey(),
+            &dart.pubkey(),
+            &authority.pubkey(),
+            &new_authority.pubkey(),
+        )]
+    };
+    SlotScenario::new()
+        .at(
+            initiated_at,
+            vec![
+                instruction::initiate_recovery(
+                    id(),
+                    &pda.pubkey(),
+                    &dart.pubkey(),
+                    &new_authority.pubkey(),
+                ),
+                instruction::approve_recovery(id(), &pda.pubkey(), &guardians[0].pubkey()),
+            ],
+            &[&dart, &guardians[0]],
+        )
+        .at(unlocked_at - 1, complete(), &[&dart])
+        .fails_with(expect_vault_error(VaultError::ChallengePeriodActive))
+        .at(unlocked_at, complete(), &[&dart])
+        .run(&mut context)
+        .await;
+
+    let record = context.banks_client.get_vault_record(pda.pubkey()).await;
+    assert_eq!(record.authority, new_authority.pubkey());
+}
+
+#[tokio::test]
+async fn inheritance_claimable_on_schedule() {
+    let mut context = program_test().start_with_context().await;
+
+    let pda = Keypair::new();
+    let dart = Keypair::new();
+    let authority = Keypair::new();
+    let beneficiary = Keypair::new();
+    initialize_account(&mut context, &pda, &dart, &authority).await;
+    warp_to_slot(&mut context, 100).await;
+    set_beneficiary(&mut context, &pda, &authority, &beneficiary.pubkey(), 1_000).await;
+
+    let claim = || {
+        vec![instruction::claim_inheritance(
+            id(),
+            &pda.pubkey(),
+            &dart.pubkey(),
+            &authority.pubkey(),
+            &beneficiary.pubkey(),
+        )]
+    };
+    // Checking in at 600 moves the claim from slot 1_100 to 1_600.
+    SlotScenario::new()
+        .at(
+            600,
+            vec![instruction::check_in(
+                id(),
+                &pda.pubkey(),
+                &authority.pubkey(),
+            )],
+            &[&authority],
+        )
+        .at(1_100, claim(), &[&dart, &beneficiary])
+        .fails_with(expect_vault_error(VaultError::InactivityPeriodActive))
+        .at(1_599, claim(), &[&dart, &beneficiary])
+        .fails_with(expect_vault_error(VaultError::InactivityPeriodActive))
+        .at(1_600, claim(), &[&dart, &beneficiary])
+        .run(&mut context)
+        .await;
+
+    let record = context.banks_client.get_vault_record(pda.pubkey()).await;
+    assert_eq!(record.authority, beneficiary.pubkey());
+}
+
+#[tokio::test]
+async fn pledged_vault_transfers_need_lender() {
+    let mut context = program_test().start_with_context().await;
+
+    let pda = Keypair::new();
+    let dart = Keypair::new();
+    let authority = Keypair::new();
+    let lender = Keypair::new();
+    let next = Keypair::new();
+    initialize_account(&mut context, &pda, &dart, &authority).await;
+
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::pledge_as_collateral(
+            id(),
+            &pda.pubkey(),
+            &authority.pubkey(),
+            &context.payer.pubkey(),
+            lender.pubkey(),
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &authority],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+    assert!(
+        context
+            .banks_client
+            .get_vault_record(pda.pubkey())
+            .await
+            .pledged
+    );
+    let (address, _) = PledgeRecord::find_address(&id(), &pda.pubkey());
+    let account = context
+        .banks_client
+        .get_account(address)
+        .await
+        .unwrap()
+        .unwrap();
+    let pledge = PledgeRecord::try_from_slice(&account.data).unwrap();
+    assert_eq!(pledge.lender, lender.pubkey());
+
+    let transfer = instruction::transfer_authority(
+        id(),
+        &pda.pubkey(),
+        &dart.pubkey(),
+        &authority.pubkey(),
+        &next.pubkey(),
+    );
+    let transaction = Transaction::new_signed_with_payer(
+        std::slice::from_ref(&transfer),
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &dart, &authority],
+        context.last_blockhash,
+    );
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        expect_vault_error(VaultError::VaultPledged)
+    );
+
+    // With the lender's signature the transfer goes through, and the pledge holds.
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::with_pledge(
+            transfer,
+            &pda.pubkey(),
+            &lender.pubkey(),
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &dart, &authority, &lender],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+    let record = context.banks_client.get_vault_record(pda.pubkey()).await;
+    assert_eq!(record.authority, next.pubkey());
+    assert!(record.pledged);
+
+    // Only the lender releases the vault.
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::release_collateral(
+            id(),
+            &pda.pubkey(),
+            &next.pubkey(),
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &next],
+        context.last_blockhash,
+    );
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        expect_vault_error(VaultError::IncorrectAuthority)
+    );
+
+    let release = instruction::release_collateral(id(), &pda.pubkey(), &lender.pubkey());
+    let transaction = Transaction::new_signed_with_payer(
+        std::slice::from_ref(&release),
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &lender],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+    assert!(
+        !context
+            .banks_client
+            .get_vault_record(pda.pubkey())
+            .await
+            .pledged
+    );
+
+    context.last_blockhash = context.get_new_latest_blockhash().await.unwrap();
+    let transaction = Transaction::new_signed_with_payer(
+        &[release],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &lender],
+        context.last_blockhash,
+    );
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        expect_vault_error(VaultError::NotPledged)
+    );
+}
+
+#[tokio::test]
+async fn corporate_action_adjusts_batch() {
+    let mut context = program_test().start_with_context().await;
+
+    let dart = Keypair::new();
+    let vaults = [Keypair::new(), Keypair::new()];
+    for (pda, balance) in vaults.iter().zip([10, 15]) {
+        let authority = Keypair::new();
+        initialize_account(&mut context, pda, &dart, &authority).await;
+        let transaction = Transaction::new_signed_with_payer(
+            &[instruction::set_position(
+                id(),
+                &pda.pubkey(),
+                &dart.pubkey(),
+                &context.payer.pubkey(),
+                [1; 32],
+                balance,
+            )],
+            Some(&context.payer.pubkey()),
+            &[&context.payer, &dart],
+            context.last_blockhash,
+        );
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap();
+    }
+    let pdas = [vaults[0].pubkey(), vaults[1].pubkey()];
+
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            instruction::apply_corporate_action(
+                id(),
+                &dart.pubkey(),
+                &pdas,
+                CorporateActionType::Split,
+                3,
+            ),
+            instruction::apply_corporate_action(
+                id(),
+                &dart.pubkey(),
+                &pdas,
+                CorporateActionType::ReverseSplit,
+                2,
+            ),
+            instruction::apply_corporate_action(
+                id(),
+                &dart.pubkey(),
+                &pdas,
+                CorporateActionType::SymbolChange { asset_id: [2; 32] },
+                1,
+            ),
+        ],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &dart],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+    for (pda, balance) in pdas.iter().zip([15, 22]) {
+        let (address, _) = PositionRecord::find_address(&id(), pda);
+        let account = context
+            .banks_client
+            .get_account(address)
+            .await
+            .unwrap()
+            .unwrap();
+        let position = PositionRecord::try_from_slice(&account.data).unwrap();
+        assert_eq!((position.asset_id, position.balance), ([2; 32], balance));
+    }
+
+    // A vault may not appear twice in a batch.
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::apply_corporate_action(
+            id(),
+            &dart.pubkey(),
+            &[pdas[0], pdas[0]],
+            CorporateActionType::Split,
+            2,
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &dart],
+        context.last_blockhash,
+    );
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        expect_vault_error(VaultError::DuplicateAccount)
+    );
+
+    // Each vault of the batch counts against its own rate limit.
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::set_rate_limit(
+            id(),
+            &pdas[1],
+            &dart.pubkey(),
+            &context.payer.pubkey(),
+            1,
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &dart],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+    let split = instruction::apply_corporate_action(
+        id(),
+        &dart.pubkey(),
+        &pdas,
+        CorporateActionType::Split,
+        2,
+    );
+    let limited = instruction::with_rate_limit(split.clone(), &pdas[1]);
+    let cases = [
+        (vec![split], 0, InstructionError::NotEnoughAccountKeys),
+        (
+            vec![limited.clone(), limited],
+            1,
+            InstructionError::Custom(VaultError::RateLimited as u32),
+        ),
+    ];
+    for (instructions, index, error) in cases {
+        let transaction = Transaction::new_signed_with_payer(
+            &instructions,
+            Some(&context.payer.pubkey()),
+            &[&context.payer, &dart],
+            context.last_blockhash,
+        );
+        assert_eq!(
+            context
+                .banks_client
+                .process_transaction(transaction)
+                .await
+                .unwrap_err()
+                .unwrap(),
+            TransactionError::InstructionError(index, error)
+        );
+    }
+}
+
+fn token_holding(mint: &Pubkey, owner: &Pubkey, amount: u64) -> Account {
+    let mut data = vec![0; spl_token::state::Account::LEN];
+    spl_token::state::Account {
+        mint: *mint,
+        owner: *owner,
+        amount,
+        state: spl_token::state::AccountState::Initialized,
+        ..Default::default()
+    }
+    .pack_into_slice(&mut data);
+    Account {
+        lamports: Rent::default().minimum_balance(data.len()),
+        data,
+        owner: spl_token::id(),
+        ..Account::default()
+    }
+}
+
+#[tokio::test]
+async fn distribution_resumes_from_cursor() {
+    let mut program_test = program_test();
+    let dart = Keypair::new();
+    let mint = Pubkey::new_unique();
+    let source = Pubkey::new_unique();
+    program_test.add_account(
+        dart.pubkey(),
+        Account::new(1_000_000_000, 0, &system_program::id()),
+    );
+    program_test.add_account(source, token_holding(&mint, &dart.pubkey(), 1_000));
+    let mut vaults = Vec::new();
+    for _ in 0..2 {
+        let (pda, authority, destination) = (Keypair::new(), Keypair::new(), Pubkey::new_unique());
+        program_test.add_account(destination, token_holding(&mint, &authority.pubkey(), 0));
+        vaults.push((pda, authority, destination));
+    }
+    vaults.sort_by_key(|(pda, _, _)| pda.pubkey());
+    let mut context = program_test.start_with_context().await;
+
+    for ((pda, authority, _), balance) in vaults.iter().zip([10, 15]) {
+        initialize_account(&mut context, pda, &dart, authority).await;
+        let transaction = Transaction::new_signed_with_payer(
+            &[instruction::set_position(
+                id(),
+                &pda.pubkey(),
+                &dart.pubkey(),
+                &context.payer.pubkey(),
+                [1; 32],
+                balance,
+            )],
+            Some(&context.payer.pubkey()),
+            &[&context.payer, &dart],
+            context.last_blockhash,
+        );
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap();
+    }
+
+    // One vault per batch, resuming after the cursor.
+    let payments: Vec<_> = vaults
+        .iter()
+        .map(|(pda, _, destination)| (pda.pubkey(), *destination))
+        .collect();
+    for payment in &payments {
+        let transaction = Transaction::new_signed_with_payer(
+            &[instruction::distribute(
+                id(),
+                &dart.pubkey(),
+                &source,
+                1,
+                [1; 32],
+                3,
+                &[*payment],
+            )],
+            Some(&context.payer.pubkey()),
+            &[&context.payer, &dart],
+            context.last_blockhash,
+        );
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap();
+    }
+    for ((_, destination), amount) in payments.iter().zip([30, 45]) {
+        let account = context
+            .banks_client
+            .get_account(*destination)
+            .await
+            .unwrap()
+            .unwrap();
+        let token = spl_token::state::Account::unpack(&account.data).unwrap();
+        assert_eq!(token.amount, amount);
+    }
+    let (address, _) = DistributionCursor::find_address(&id(), &dart.pubkey(), 1);
+    let account = context
+        .banks_client
+        .get_account(address)
+        .await
+        .unwrap()
+        .unwrap();
+    let cursor = DistributionCursor::try_from_slice(&account.data).unwrap();
+    assert_eq!(cursor.last_vault, payments[1].0);
+    assert_eq!((cursor.paid_vaults, cursor.total_paid), (2, 75));
+
+    // A vault already paid is not paid again.
+    context.last_blockhash = context.get_new_latest_blockhash().await.unwrap();
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::distribute(
+            id(),
+            &dart.pubkey(),
+            &source,
+            1,
+            [1; 32],
+            3,
+            &payments[..1],
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &dart],
+        context.last_blockhash,
+    );
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        expect_vault_error(VaultError::DistributionOutOfOrder)
+    );
+}
+
+#[tokio::test]
+async fn distribute_fail_incorrect_token_program() {
+    let mut context = program_test().start_with_context().await;
+
+    let dart = Keypair::new();
+    let mut distribute = instruction::distribute(
+        id(),
+        &dart.pubkey(),
+        &Pubkey::new_unique(),
+        1,
+        [1; 32],
+        3,
+        &[],
+    );
+    distribute.accounts[instruction::DISTRIBUTE_TOKEN_PROGRAM_INDEX].pubkey = Pubkey::new_unique();
+    let transaction = Transaction::new_signed_with_payer(
+        &[distribute],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &dart],
+        context.last_blockhash,
+    );
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        expect_vault_error(VaultError::IncorrectProgram)
+    );
+}
+
+#[tokio::test]
+async fn snapshot_fixes_record_date_state() {
+    let mut context = program_test().start_with_context().await;
+
+    let pda = Keypair::new();
+    let dart = Keypair::new();
+    let authority = Keypair::new();
+    let next = Keypair::new();
+    initialize_account(&mut context, &pda, &dart, &authority).await;
+
+    let snapshot = instruction::snapshot(
+        id(),
+        &pda.pubkey(),
+        &dart.pubkey(),
+        &context.payer.pubkey(),
+        100,
+    );
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::set_position(
+            id(),
+            &pda.pubkey(),
+            &dart.pubkey(),
+            &context.payer.pubkey(),
+            [1; 32],
+            10,
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &dart],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+
+    // Not before the record date.
+    let transaction = Transaction::new_signed_with_payer(
+        std::slice::from_ref(&snapshot),
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &dart],
+        context.last_blockhash,
+    );
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(0, InstructionError::InvalidArgument)
+    );
+
+    warp_to_slot(&mut context, 100).await;
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            snapshot.clone(),
+            instruction::transfer_authority(
+                id(),
+                &pda.pubkey(),
+                &dart.pubkey(),
+                &authority.pubkey(),
+                &next.pubkey(),
+            ),
+        ],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &dart, &authority],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+
+    let (address, _) = SnapshotRecord::find_address(&id(), &pda.pubkey(), 100);
+    let account = context
+        .banks_client
+        .get_account(address)
+        .await
+        .unwrap()
+        .unwrap();
+    let record = SnapshotRecord::try_from_slice(&account.data).unwrap();
+    assert_eq!(record.authority, authority.pubkey());
+    assert_eq!((record.asset_id, record.balance), ([1; 32], 10));
+    assert!(record.taken_slot >= 100);
+
+    // The snapshot is written once.
+    let transaction = Transaction::new_signed_with_payer(
+        &[snapshot],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &dart],
+        context.last_blockhash,
+    );
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(0, InstructionError::AccountAlreadyInitialized)
+    );
+}
+
+#[tokio::test]
+async fn voting_delegation_lapses_on_transfer() {
+    let mut context = program_test().start_with_context().await;
+
+    let pda = Keypair::new();
+    let dart = Keypair::new();
+    let authority = Keypair::new();
+    let proxy = Pubkey::new_unique();
+    let next = Keypair::new();
+    initialize_account(&mut context, &pda, &dart, &authority).await;
+    let (address, _) = VotingProxy::find_address(&id(), &pda.pubkey());
+
+    // The authority is not its own proxy.
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::delegate_voting(
+            id(),
+            &pda.pubkey(),
+            &authority.pubkey(),
+            &context.payer.pubkey(),
+            authority.pubkey(),
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &authority],
+        context.last_blockhash,
+    );
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(0, InstructionError::InvalidArgument)
+    );
+
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::delegate_voting(
+            id(),
+            &pda.pubkey(),
+            &authority.pubkey(),
+            &context.payer.pubkey(),
+            proxy,
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &authority],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+    let account = context
+        .banks_client
+        .get_account(address)
+        .await
+        .unwrap()
+        .unwrap();
+    let voting = VotingProxy::try_from_slice(&account.data).unwrap();
+    let record = context.banks_client.get_vault_record(pda.pubkey()).await;
+    assert_eq!(voting.voter(&record), proxy);
+
+    // The new authority votes after a transfer.
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::transfer_authority(
+            id(),
+            &pda.pubkey(),
+            &dart.pubkey(),
+            &authority.pubkey(),
+            &next.pubkey(),
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &dart, &authority],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+    let record = context.banks_client.get_vault_record(pda.pubkey()).await;
+    assert_eq!(voting.voter(&record), next.pubkey());
+
+    // The new authority delegates, then revokes.
+    for proxy in [proxy, Pubkey::default()] {
+        let transaction = Transaction::new_signed_with_payer(
+            &[instruction::delegate_voting(
+                id(),
+                &pda.pubkey(),
+                &next.pubkey(),
+                &context.payer.pubkey(),
+                proxy,
+            )],
+            Some(&context.payer.pubkey()),
+            &[&context.payer, &next],
+            context.last_blockhash,
+        );
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap();
+        let account = context
+            .banks_client
+            .get_account(address)
+            .await
+            .unwrap()
+            .unwrap();
+        let voting = VotingProxy::try_from_slice(&account.data).unwrap();
+        assert_eq!(voting.authority, next.pubkey());
+        let expected = if proxy == Pubkey::default() {
+            next.pubkey()
+        } else {
+            proxy
+        };
+        assert_eq!(voting.voter(&record), expected);
+    }
+}
+
+#[tokio::test]
+async fn transfer_windows_gate_transfers() {
+    let mut context = program_test().start_with_context().await;
+
+    let pda = Keypair::new();
+    let dart = Keypair::new();
+    let authority = Keypair::new();
+    let next = Keypair::new();
+    initialize_account(&mut context, &pda, &dart, &authority).await;
+
+    let mut blackouts = [BlackoutRange::default(); MAX_BLACKOUTS];
+    blackouts[0] = BlackoutRange {
+        start_slot: 200,
+        end_slot: 300,
+    };
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            instruction::set_dart_transfer_window(
+                id(),
+                &dart.pubkey(),
+                &context.payer.pubkey(),
+                0,
+                blackouts,
+            ),
+            instruction::set_transfer_window(
+                id(),
+                &pda.pubkey(),
+                &dart.pubkey(),
+                &context.payer.pubkey(),
+                100,
+                Default::default(),
+            ),
+        ],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &dart],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+    let record = context.banks_client.get_vault_record(pda.pubkey()).await;
+    assert!(record.transfer_windowed);
+
+    let transfer = instruction::transfer_authority(
+        id(),
+        &pda.pubkey(),
+        &dart.pubkey(),
+        &authority.pubkey(),
+        &next.pubkey(),
+    );
+    let windowed =
+        instruction::with_transfer_windows(transfer.clone(), &pda.pubkey(), &dart.pubkey());
+    let cases = [
+        // The vault's window must be passed.
+        (0, transfer, InstructionError::NotEnoughAccountKeys),
+        // Locked up by the vault's window.
+        (
+            0,
+            windowed.clone(),
+            InstructionError::Custom(VaultError::TransferWindowClosed as u32),
+        ),
+        // Blacked out by the DART's window.
+        (
+            250,
+            windowed.clone(),
+            InstructionError::Custom(VaultError::TransferWindowClosed as u32),
+        ),
+    ];
+    for (slot, instruction, error) in cases {
+        if slot > 0 {
+            warp_to_slot(&mut context, slot).await;
+        }
+        let transaction = Transaction::new_signed_with_payer(
+            &[instruction],
+            Some(&context.payer.pubkey()),
+            &[&context.payer, &dart, &authority],
+            context.last_blockhash,
+        );
+        assert_eq!(
+            context
+                .banks_client
+                .process_transaction(transaction)
+                .await
+                .unwrap_err()
+                .unwrap(),
+            TransactionError::InstructionError(0, error)
+        );
+    }
+
+    warp_to_slot(&mut context, 300).await;
+    let transaction = Transaction::new_signed_with_payer(
+        &[windowed],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &dart, &authority],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+    let record = context.banks_client.get_vault_record(pda.pubkey()).await;
+    assert_eq!(record.authority, next.pubkey());
+}
+
+#[tokio::test]
+async fn dart_transfer_window_kept_apart_from_vault_windows() {
+    let mut context = program_test().start_with_context().await;
+
+    let pda = Keypair::new();
+    let dart = Keypair::new();
+    let authority = Keypair::new();
+    initialize_account(&mut context, &pda, &dart, &authority).await;
+
+    // The holder of the vault keypair signs as a DART for the vault's key, which sets a
+    // DART window of its own rather than the vault's.
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            instruction::set_transfer_window(
+                id(),
+                &pda.pubkey(),
+                &dart.pubkey(),
+                &context.payer.pubkey(),
+                100,
+                Default::default(),
+            ),
+            instruction::set_dart_transfer_window(
+                id(),
+                &pda.pubkey(),
+                &context.payer.pubkey(),
+                0,
+                Default::default(),
+            ),
+        ],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &dart, &pda],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+    let (vault_window, _) =
+        TransferWindow::find_address(&id(), TRANSFER_WINDOW_VAULT, &pda.pubkey());
+    let window = context
+        .banks_client
+        .get_account_data_with_borsh::<TransferWindow>(vault_window)
+        .await
+        .unwrap();
+    assert_eq!(window.lockup_until_slot, 100);
+
+    // An operational key sets the window of its DART identity.
+    let identity = Keypair::new();
+    let master = Keypair::new();
+    let operator = Keypair::new();
+    initialize_dart_identity(&mut context, &identity, &master, &[operator.pubkey()]).await;
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::with_dart_identity(
+            instruction::set_dart_transfer_window(
+                id(),
+                &operator.pubkey(),
+                &context.payer.pubkey(),
+                7,
+                Default::default(),
+            ),
+            &identity.pubkey(),
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &operator],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+    let (identity_window, _) =
+        TransferWindow::find_address(&id(), TRANSFER_WINDOW_DART, &identity.pubkey());
+    let window = context
+        .banks_client
+        .get_account_data_with_borsh::<TransferWindow>(identity_window)
+        .await
+        .unwrap();
+    assert_eq!(window.scope, identity.pubkey());
+    assert_eq!(window.lockup_until_slot, 7);
+}
+
+#[tokio::test]
+async fn dart_transfer_window_gates_every_authority_change() {
+    let mut context = program_test().start_with_context().await;
+
+    let pda = Keypair::new();
+    let others = [Keypair::new(), Keypair::new()];
+    let dart = Keypair::new();
+    let authority = Keypair::new();
+    let other_authorities = [Keypair::new(), Keypair::new()];
+    let next = Keypair::new();
+    initialize_account(&mut context, &pda, &dart, &authority).await;
+    for (other, other_authority) in others.iter().zip(&other_authorities) {
+        initialize_account(&mut context, other, &dart, other_authority).await;
+    }
+    propose_transfer(&mut context, &pda, &dart, &authority, &next.pubkey(), 1_000).await;
+
+    let mut blackouts = [BlackoutRange::default(); MAX_BLACKOUTS];
+    blackouts[0] = BlackoutRange {
+        start_slot: 0,
+        end_slot: 300,
+    };
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::set_dart_transfer_window(
+            id(),
+            &dart.pubkey(),
+            &context.payer.pubkey(),
+            0,
+            blackouts,
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &dart],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+
+    let (dart_window, _) =
+        TransferWindow::find_address(&id(), TRANSFER_WINDOW_DART, &dart.pubkey());
+    let accept = instruction::accept_transfer(
+        id(),
+        &pda.pubkey(),
+        &dart.pubkey(),
+        &authority.pubkey(),
+        &next.pubkey(),
+    );
+    let mut unwindowed = accept.clone();
+    unwindowed
+        .accounts
+        .retain(|meta| meta.pubkey != dart_window);
+    let swap = instruction::swap_authorities(
+        id(),
+        &others[0].pubkey(),
+        &others[1].pubkey(),
+        &dart.pubkey(),
+        &other_authorities[0].pubkey(),
+        &other_authorities[1].pubkey(),
+    );
+    let cases = [
+        // The DART's window must be passed.
+        (
+            unwindowed,
+            vec![&next],
+            InstructionError::NotEnoughAccountKeys,
+        ),
+        // Blacked out by the DART's window.
+        (
+            accept.clone(),
+            vec![&next],
+            InstructionError::Custom(VaultError::TransferWindowClosed as u32),
+        ),
+        (
+            swap,
+            vec![&dart, &other_authorities[0], &other_authorities[1]],
+            InstructionError::Custom(VaultError::TransferWindowClosed as u32),
+        ),
+    ];
+    for (instruction, mut signers, error) in cases {
+        signers.insert(0, &context.payer);
+        let transaction = Transaction::new_signed_with_payer(
+            &[instruction],
+            Some(&context.payer.pubkey()),
+            &signers,
+            context.last_blockhash,
+        );
+        assert_eq!(
+            context
+                .banks_client
+                .process_transaction(transaction)
+                .await
+                .unwrap_err()
+                .unwrap(),
+            TransactionError::InstructionError(0, error)
+        );
+    }
+
+    warp_to_slot(&mut context, 300).await;
+    let transaction = Transaction::new_signed_with_payer(
+        &[accept],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &next],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+    let record = context.banks_client.get_vault_record(pda.pubkey()).await;
+    assert_eq!(record.authority, next.pubkey());
+}
+
+#[tokio::test]
+async fn rate_limit_caps_mutations_per_epoch() {
+    let mut context = program_test().start_with_context().await;
+
+    let pda = Keypair::new();
+    let dart = Keypair::new();
+    let authority = Keypair::new();
+    initialize_account(&mut context, &pda, &dart, &authority).await;
+
+    let set_limit = |max_mutations_per_epoch| {
+        instruction::set_rate_limit(
+            id(),
+            &pda.pubkey(),
+            &dart.pubkey(),
+            &context.payer.pubkey(),
+            max_mutations_per_epoch,
+        )
+    };
+    let (limit, lift) = (set_limit(2), set_limit(0));
+    let set_tag = |tag| instruction::set_tag(id(), &pda.pubkey(), &dart.pubkey(), tag);
+    let limited_tag = |tag| instruction::with_rate_limit(set_tag(tag), &pda.pubkey());
+
+    let transaction = Transaction::new_signed_with_payer(
+        &[limit, limited_tag(1), limited_tag(2)],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &dart],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+    assert!(
+        context
+            .banks_client
+            .get_vault_record(pda.pubkey())
+            .await
+            .rate_limited
+    );
+
+    let cases = [
+        (set_tag(3), InstructionError::NotEnoughAccountKeys),
+        (
+            limited_tag(3),
+            InstructionError::Custom(VaultError::RateLimited as u32),
+        ),
+    ];
+    for (instruction, error) in cases {
+        let transaction = Transaction::new_signed_with_payer(
+            &[instruction],
+            Some(&context.payer.pubkey()),
+            &[&context.payer, &dart],
+            context.last_blockhash,
+        );
+        assert_eq!(
+            context
+                .banks_client
+                .process_transaction(transaction)
+                .await
+                .unwrap_err()
+                .unwrap(),
+            TransactionError::InstructionError(0, error)
+        );
+    }
+
+    // The allowance renews with the epoch.
+    let clock = context.banks_client.get_sysvar::<Clock>().await.unwrap();
+    let next_epoch = context
+        .genesis_config()
+        .epoch_schedule
+        .get_first_slot_in_epoch(clock.epoch + 1);
+    warp_to_slot(&mut context, next_epoch).await;
+    let transaction = Transaction::new_signed_with_payer(
+        &[limited_tag(3), lift, set_tag(4)],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &dart],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+    let record = context.banks_client.get_vault_record(pda.pubkey()).await;
+    assert!(!record.rate_limited);
+    assert_eq!(record.tag, 4);
+}
+
+#[tokio::test]
+async fn initialize_takes_dart_policy() {
+    let mut context = program_test().start_with_context().await;
+
+    let dart = Keypair::new();
+    let recipient = Pubkey::new_unique();
+    let defaults = PolicyDefaults {
+        tag: 7,
+        close_recipient: recipient,
+        cosign: true,
+        close_fee: 5_000,
+        restrictions: restriction::NO_SESSIONS,
+        ..PolicyDefaults::default()
+    };
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::set_dart_policy(
+            id(),
+            &dart.pubkey(),
+            &context.payer.pubkey(),
+            defaults,
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &dart],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+
+    // The second vault's explicit close recipient prevails over the policy.
+    let explicit = Pubkey::new_unique();
+    for close_recipient in [None, Some(explicit)] {
+        let pda = Keypair::new();
+        let authority = Keypair::new();
+        let mut initialize =
+            instruction::initialize(id(), &pda.pubkey(), &dart.pubkey(), &authority.pubkey());
+        if let Some(close_recipient) = close_recipient {
+            initialize = instruction::with_close_recipient(initialize, &close_recipient);
+        }
+        let space = VaultRecord::LEN;
+        let transaction = Transaction::new_signed_with_payer(
+            &[
+                system_instruction::create_account(
+                    &context.payer.pubkey(),
+                    &pda.pubkey(),
+                    Rent::default().minimum_balance(space),
+                    space as u64,
+                    &id(),
+                ),
+                instruction::with_dart_policy(initialize, &dart.pubkey()),
+            ],
+            Some(&context.payer.pubkey()),
+            &[&context.payer, &pda, &dart],
+            context.last_blockhash,
+        );
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap();
+
+        let record = context.banks_client.get_vault_record(pda.pubkey()).await;
+        assert_eq!(record.tag, 7);
+        assert_eq!(record.close_recipient, close_recipient.unwrap_or(recipient));
+        assert_eq!(record.oracle, Pubkey::default());
+        assert!(record.cosign);
+        assert_eq!(record.close_fee, 5_000);
+        assert_eq!(record.restrictions, restriction::NO_SESSIONS);
+
+        // The authority alone cannot act on the vault, and with the DART co-signing
+        // it is still barred from sessions.
+        let session = instruction::create_session(
+            id(),
+            &pda.pubkey(),
+            &authority.pubkey(),
+            &context.payer.pubkey(),
+            Pubkey::new_unique(),
+            u64::MAX,
+            0,
+        );
+        let mut cosigned = session.clone();
+        cosigned
+            .accounts
+            .push(AccountMeta::new_readonly(dart.pubkey(), true));
+        for (instruction, signers, error) in [
+            (
+                session,
+                vec![&context.payer, &authority],
+                InstructionError::MissingRequiredSignature,
+            ),
+            (
+                cosigned,
+                vec![&context.payer, &authority, &dart],
+                InstructionError::Custom(VaultError::VaultRestricted as u32),
+            ),
+        ] {
+            let transaction = Transaction::new_signed_with_payer(
+                &[instruction],
+                Some(&context.payer.pubkey()),
+                &signers,
+                context.last_blockhash,
+            );
+            assert_eq!(
+                context
+                    .banks_client
+                    .process_transaction(transaction)
+                    .await
+                    .unwrap_err()
+                    .unwrap(),
+                TransactionError::InstructionError(0, error)
+            );
+        }
+    }
+}
+
+#[tokio::test]
+async fn clone_vault_copies_settings() {
+    let mut context = program_test().start_with_context().await;
+
+    let pda = Keypair::new();
+    let dart = Keypair::new();
+    let authority = Keypair::new();
+    initialize_account(&mut context, &pda, &dart, &authority).await;
+
+    let oracle = Pubkey::new_unique();
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            instruction::set_tag(id(), &pda.pubkey(), &dart.pubkey(), 7),
+            instruction::set_oracle(id(), &pda.pubkey(), &dart.pubkey(), Some(&oracle), 25, 3),
+        ],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &dart],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+    let source = context.banks_client.get_vault_record(pda.pubkey()).await;
+
+    let new_pda = Keypair::new();
+    let new_authority = Keypair::new();
+    let space = VaultRecord::LEN;
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            system_instruction::create_account(
+                &context.payer.pubkey(),
+                &new_pda.pubkey(),
+                Rent::default().minimum_balance(space),
+                space as u64,
+                &id(),
+            ),
+            instruction::clone_vault(
+                id(),
+                &pda.pubkey(),
+                &new_pda.pubkey(),
+                &dart.pubkey(),
+                &new_authority.pubkey(),
+            ),
+        ],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &new_pda, &dart],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+
+    let record = context
+        .banks_client
+        .get_vault_record(new_pda.pubkey())
+        .await;
+    assert_eq!(record.authority, new_authority.pubkey());
+    assert_eq!(record.dart, dart.pubkey());
+    assert_eq!(PolicyDefaults::of(&record), PolicyDefaults::of(&source));
+    assert_eq!(record.oracle, oracle);
+
+    // The clone is a new vault, so cloning into it again fails.
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::clone_vault(
+            id(),
+            &pda.pubkey(),
+            &new_pda.pubkey(),
+            &dart.pubkey(),
+            &new_authority.pubkey(),
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &dart],
+        context.last_blockhash,
+    );
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(0, InstructionError::AccountAlreadyInitialized)
+    );
+}
+
+#[tokio::test]
+async fn create_asset_vaults_per_asset() {
+    let mut context = program_test().start_with_context().await;
+
+    let dart = Keypair::new();
+    let authority = Keypair::new();
+    let assets = [[1; 32], [2; 32]];
+    let transaction = Transaction::new_signed_with_payer(
+        &assets.map(|asset_id| {
+            instruction::create_asset_vault(
+                id(),
+                &dart.pubkey(),
+                &authority.pubkey(),
+                &context.payer.pubkey(),
+                asset_id,
+            )
+        }),
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &dart],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+
+    for asset_id in &assets {
+        let (pda, _) =
+            find_asset_vault_address(&id(), &dart.pubkey(), &authority.pubkey(), asset_id);
+        let record = context.banks_client.get_vault_record(pda).await;
+        assert_eq!(record.authority, authority.pubkey());
+        assert_eq!(record.dart, dart.pubkey());
+        assert_eq!(record.rent_payer, context.payer.pubkey());
+    }
+
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::create_asset_vault(
+            id(),
+            &dart.pubkey(),
+            &authority.pubkey(),
+            &context.payer.pubkey(),
+            assets[0],
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &dart],
+        context.last_blockhash,
+    );
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(0, InstructionError::AccountAlreadyInitialized)
+    );
+}
+
+#[tokio::test]
+async fn vault_index_follows_create_and_close() {
+    let mut context = program_test().start_with_context().await;
+
+    let pda = Keypair::new();
+    let dart = Keypair::new();
+    let authority = Keypair::new();
+    initialize_account(&mut context, &pda, &dart, &authority).await;
+
+    // The vault predates the index, so it is listed by syncing.
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::sync_vault_index(
+            id(),
+            &authority.pubkey(),
+            &context.payer.pubkey(),
+            &[pda.pubkey()],
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+
+    let asset_id = [1; 32];
+    let (asset_vault, _) =
+        find_asset_vault_address(&id(), &dart.pubkey(), &authority.pubkey(), &asset_id);
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::with_vault_index(
+            instruction::create_asset_vault(
+                id(),
+                &dart.pubkey(),
+                &authority.pubkey(),
+                &context.payer.pubkey(),
+                asset_id,
+            ),
+            &authority.pubkey(),
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &dart],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+
+    let (index_address, _) = VaultIndex::find_address(&id(), &authority.pubkey());
+    let index = context
+        .banks_client
+        .get_account_data_with_borsh::<VaultIndex>(index_address)
+        .await
+        .unwrap();
+    assert_eq!(
+        index.vaults().copied().collect::<Vec<_>>(),
+        [pda.pubkey(), asset_vault]
+    );
+
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::with_vault_index(
+            instruction::close_account(id(), &pda.pubkey(), &dart.pubkey(), &authority.pubkey()),
+            &authority.pubkey(),
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &dart, &authority],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+
+    let index = context
+        .banks_client
+        .get_account_data_with_borsh::<VaultIndex>(index_address)
+        .await
+        .unwrap();
+    assert_eq!(index.vaults().copied().collect::<Vec<_>>(), [asset_vault]);
+}
+
+#[tokio::test]
+async fn vault_index_follows_authority_transfers() {
+    let mut context = program_test().start_with_context().await;
+
+    let pda = Keypair::new();
+    let dart = Keypair::new();
+    let authority = Keypair::new();
+    let new_authority = Keypair::new();
+    initialize_account(&mut context, &pda, &dart, &authority).await;
+
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            instruction::sync_vault_index(
+                id(),
+                &authority.pubkey(),
+                &context.payer.pubkey(),
+                &[pda.pubkey()],
+            ),
+            instruction::sync_vault_index(
+                id(),
+                &new_authority.pubkey(),
+                &context.payer.pubkey(),
+                &[],
+            ),
+        ],
+        Some(&context.payer.pubkey()),
+        &[&context.payer],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+
+    // Leaving out the index of either authority fails the transfer.
+    let (index_address, _) = VaultIndex::find_address(&id(), &authority.pubkey());
+    let (new_index_address, _) = VaultIndex::find_address(&id(), &new_authority.pubkey());
+    let mut transfer = instruction::transfer_authority(
+        id(),
+        &pda.pubkey(),
+        &dart.pubkey(),
+        &authority.pubkey(),
+        &new_authority.pubkey(),
+    );
+    transfer
+        .accounts
+        .retain(|meta| meta.pubkey != new_index_address);
+    let transaction = Transaction::new_signed_with_payer(
+        &[transfer],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &dart, &authority],
+        context.last_blockhash,
+    );
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(0, InstructionError::NotEnoughAccountKeys)
+    );
+
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::transfer_authority(
+            id(),
+            &pda.pubkey(),
+            &dart.pubkey(),
+            &authority.pubkey(),
+            &new_authority.pubkey(),
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &dart, &authority],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+
+    let index = context
+        .banks_client
+        .get_account_data_with_borsh::<VaultIndex>(index_address)
+        .await
+        .unwrap();
+    assert_eq!(index.vaults().count(), 0);
+    let index = context
+        .banks_client
+        .get_account_data_with_borsh::<VaultIndex>(new_index_address)
+        .await
+        .unwrap();
+    assert_eq!(index.vaults().copied().collect::<Vec<_>>(), [pda.pubkey()]);
+}
+
+#[tokio::test]
+async fn crank_sweeps_expired_sessions() {
+    let admin = Keypair::new();
+    let mut context = start_with_config(program_test(), &admin).await;
+
+    let pda = Keypair::new();
+    let dart = Keypair::new();
+    let authority = Keypair::new();
+    let sessions = [Keypair::new(), Keypair::new()];
+    let cranker = Keypair::new();
+    initialize_account(&mut context, &pda, &dart, &authority).await;
+    for session in &sessions {
+        create_session(
+            &mut context,
+            &pda,
+            &authority,
+            session,
+            10,
+            session_scope::TRANSFER_AUTHORITY,
+        )
+        .await;
+    }
+
+    // Fund the cranker's account.
+    let rent = Rent::default().minimum_balance(0);
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            instruction::set_crank_rules(id(), &admin.pubkey(), 1000, 5),
+            system_instruction::transfer(&context.payer.pubkey(), &cranker.pubkey(), rent),
+        ],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &admin],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+
+    let session_addresses = sessions
+        .each_ref()
+        .map(|session| SessionRecord::find_address(&id(), &pda.pubkey(), &session.pubkey()).0);
+    let session_lamports = context
+        .banks_client
+        .get_balance(session_addresses[0])
+        .await
+        .unwrap();
+    let authority_lamports = context
+        .banks_client
+        .get_balance(authority.pubkey())
+        .await
+        .unwrap();
+    let crank = |context: &ProgramTestContext, session_address: Pubkey| {
+        Transaction::new_signed_with_payer(
+            &[instruction::crank(
+                id(),
+                &cranker.pubkey(),
+                &[(session_address, authority.pubkey())],
+            )],
+            Some(&context.payer.pubkey()),
+            &[&context.payer, &cranker],
+            context.last_blockhash,
+        )
+    };
+
+    // Still within the grace period.
+    warp_to_slot(&mut context, 15).await;
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(crank(&context, session_addresses[0]))
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VaultError::NotSweepable as u32)
+        )
+    );
+
+    // The bounty comes out of the session's lamports, the rest going to its creator.
+    warp_to_slot(&mut context, 16).await;
+    context
+        .banks_client
+        .process_transaction(crank(&context, session_addresses[0]))
+        .await
+        .unwrap();
+
+    assert_eq!(
+        context
+            .banks_client
+            .get_account(session_addresses[0])
+            .await
+            .unwrap(),
+        None
+    );
+    assert_eq!(
+        context
+            .banks_client
+            .get_balance(authority.pubkey())
+            .await
+            .unwrap(),
+        authority_lamports + session_lamports - 1000
+    );
+    assert_eq!(
+        context
+            .banks_client
+            .get_balance(cranker.pubkey())
+            .await
+            .unwrap(),
+        rent + 1000
+    );
+
+    // However large the configured bounty, the cranker takes at most its share.
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::set_crank_rules(
+            id(),
+            &admin.pubkey(),
+            u64::MAX,
+            5,
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &admin],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+    context
+        .banks_client
+        .process_transaction(crank(&context, session_addresses[1]))
+        .await
+        .unwrap();
+    let share = session_lamports * MAX_CRANK_BOUNTY_BPS / 10_000;
+    assert_eq!(
+        context
+            .banks_client
+            .get_balance(authority.pubkey())
+            .await
+            .unwrap(),
+        authority_lamports + 2 * session_lamports - 1000 - share
+    );
+    assert_eq!(
+        context
+            .banks_client
+            .get_balance(cranker.pubkey())
+            .await
+            .unwrap(),
+        rent + 1000 + share
+    );
+}
+
+#[tokio::test]
+async fn close_fails_while_position_held() {
+    let mut context = program_test().start_with_context().await;
+
+    let pda = Keypair::new();
+    let dart = Keypair::new();
+    let authority = Keypair::new();
+    initialize_account(&mut context, &pda, &dart, &authority).await;
+
+    let close =
+        instruction::close_account(id(), &pda.pubkey(), &dart.pubkey(), &authority.pubkey());
+    let (position, _) = PositionRecord::find_address(&id(), &pda.pubkey());
+    let mut without_position = close.clone();
+    without_position
+        .accounts
+        .retain(|meta| meta.pubkey != position);
+    let with_token_account =
+        instruction::with_asset_token_account(close.clone(), &pda.pubkey(), &[1; 32]);
+    let cases = [
+        // The position must be passed, even before it exists.
+        (
+            None,
+            without_position,
+            InstructionError::NotEnoughAccountKeys,
+        ),
+        // Once it does, so must the token account of its asset.
+        (Some(0), close, InstructionError::NotEnoughAccountKeys),
+        (
+            Some(100),
+            with_token_account.clone(),
+            InstructionError::Custom(VaultError::VaultNotEmpty as u32),
+        ),
+    ];
+    for (balance, close, error) in cases {
+        let mut instructions = vec![];
+        if let Some(balance) = balance {
+            instructions.push(instruction::set_position(
+                id(),
+                &pda.pubkey(),
+                &dart.pubkey(),
+                &context.payer.pubkey(),
+                [1; 32],
+                balance,
+            ));
+        }
+        let index = instructions.len() as u8;
+        instructions.push(close);
+        let transaction = Transaction::new_signed_with_payer(
+            &instructions,
+            Some(&context.payer.pubkey()),
+            &[&context.payer, &dart, &authority],
+            context.last_blockhash,
+        );
+        assert_eq!(
+            context
+                .banks_client
+                .process_transaction(transaction)
+                .await
+                .unwrap_err()
+                .unwrap(),
+            TransactionError::InstructionError(index, error)
+        );
+    }
+
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            instruction::set_position(
+                id(),
+                &pda.pubkey(),
+                &dart.pubkey(),
+                &context.payer.pubkey(),
+                [1; 32],
+                0,
+            ),
+            with_token_account,
+        ],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &dart, &authority],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+    let record = context
+        .banks_client
+        .get_account(pda.pubkey())
+        .await
+        .unwrap();
+    assert_eq!(record, None);
+}
+
+#[tokio::test]
+async fn set_fee_exemption_by_admin() {
+    let admin = Keypair::new();
+    let mut context = start_with_config(program_test(), &admin).await;
+
+    let dart = Pubkey::new_unique();
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::set_fee_exemption(
+            id(),
+            &admin.pubkey(),
+            &context.payer.pubkey(),
+            0,
+            dart,
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &admin],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+
+    let (address, _) = FeeConfig::find_address(&id());
+    let fee_config = context
+        .banks_client
+        .get_account_data_with_borsh::<FeeConfig>(address)
+        .await
+        .unwrap();
+    assert!(fee_config.is_exempt(&VaultRecord::new(Pubkey::new_unique(), dart)));
+
+    // Only the admin manages the list, within its slots.
+    let impostor = Keypair::new();
+    for (signer, slot, error) in [
+        (&impostor, 1, VaultError::IncorrectAuthority),
+        (
+            &admin,
+            MAX_FEE_EXEMPT as u8,
+            VaultError::InvalidFeeExemptionSlot,
+        ),
+    ] {
+        let transaction = Transaction::new_signed_with_payer(
+            &[instruction::set_fee_exemption(
+                id(),
+                &signer.pubkey(),
+                &context.payer.pubkey(),
+                slot,
+                dart,
+            )],
+            Some(&context.payer.pubkey()),
+            &[&context.payer, signer],
+            context.last_blockhash,
+        );
+        assert_eq!(
+            context
+                .banks_client
+                .process_transaction(transaction)
+                .await
+                .unwrap_err()
+                .unwrap(),
+            TransactionError::InstructionError(0, InstructionError::Custom(error as u32))
+        );
+    }
+}
+
+#[tokio::test]
+async fn collect_fees_to_destination() {
+    let admin = Keypair::new();
+    let mut context = start_with_config(program_test(), &admin).await;
+
+    // Fees accumulate in the fee config; enough here to open the destination.
+    let destination = Pubkey::new_unique();
+    let (fee_config, _) = FeeConfig::find_address(&id());
+    let fees = Rent::default().minimum_balance(0);
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            instruction::set_fee_destination(
+                id(),
+                &admin.pubkey(),
+                &context.payer.pubkey(),
+                destination,
+            ),
+            system_instruction::transfer(&context.payer.pubkey(), &fee_config, fees),
+        ],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &admin],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+
+    // Fees only go to the destination set in the fee config.
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::collect_fees(
+            id(),
+            &admin.pubkey(),
+            &context.payer.pubkey(),
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &admin],
+        context.last_blockhash,
+    );
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(0, InstructionError::InvalidArgument)
+    );
+
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::collect_fees(
+            id(),
+            &admin.pubkey(),
+            &destination,
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &admin],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        context.banks_client.get_balance(destination).await.unwrap(),
+        fees
+    );
+    assert_eq!(
+        context.banks_client.get_balance(fee_config).await.unwrap(),
+        Rent::default().minimum_balance(FeeConfig::LEN)
+    );
+}
+
+#[tokio::test]
+async fn close_account_charges_close_fee_tier() {
+    let admin = Keypair::new();
+    let mut context = start_with_config(program_test(), &admin).await;
+
+    let mut tiers = [CloseFeeTier::default(); MAX_CLOSE_FEE_TIERS];
+    tiers[0] = CloseFeeTier {
+        active: true,
+        tag: 4,
+        lamports: 3_000,
+        min_age_slots: 0,
+    };
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::set_close_fees(
+            id(),
+            &admin.pubkey(),
+            &context.payer.pubkey(),
+            1_000,
+            tiers,
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &admin],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+
+    let (fee_config, _) = FeeConfig::find_address(&id());
+    let rent = Rent::default().minimum_balance(get_packed_len::<VaultRecord>());
+    for (tag, fee) in [(0, 1_000), (4, 3_000)] {
+        let pda = Keypair::new();
+        let dart = Keypair::new();
+        let authority = Keypair::new();
+        initialize_account(&mut context, &pda, &dart, &authority).await;
+
+        let fee_config_lamports = context.banks_client.get_balance(fee_config).await.unwrap();
+        let transaction = Transaction::new_signed_with_payer(
+            &[
+                instruction::set_tag(id(), &pda.pubkey(), &dart.pubkey(), tag),
+                instruction::close_account(
+                    id(),
+                    &pda.pubkey(),
+                    &dart.pubkey(),
+                    &authority.pubkey(),
+                ),
+            ],
+            Some(&context.payer.pubkey()),
+            &[&context.payer, &dart, &authority],
+            context.last_blockhash,
+        );
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            context
+                .banks_client
+                .get_balance(authority.pubkey())
+                .await
+                .unwrap(),
+            rent - fee
+        );
+        assert_eq!(
+            context.banks_client.get_balance(fee_config).await.unwrap(),
+            fee_config_lamports + fee
+        );
+        context.banks_client.assert_vault_closed(pda.pubkey()).await;
+    }
+}
+
+#[tokio::test]
+async fn close_account_waives_fee_for_exempt_dart() {
+    let admin = Keypair::new();
+    let mut context = start_with_config(program_test(), &admin).await;
+
+    let pda = Keypair::new();
+    let dart = Keypair::new();
+    let authority = Keypair::new();
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            instruction::set_close_fees(
+                id(),
+                &admin.pubkey(),
+                &context.payer.pubkey(),
+                1_000,
+                [CloseFeeTier::default(); MAX_CLOSE_FEE_TIERS],
+            ),
+            instruction::set_fee_exemption(
+                id(),
+                &admin.pubkey(),
+                &context.payer.pubkey(),
+                0,
+                dart.pubkey(),
+            ),
+        ],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &admin],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+    initialize_account(&mut context, &pda, &dart, &authority).await;
+
+    let (fee_config, _) = FeeConfig::find_address(&id());
+    let fee_config_lamports = context.banks_client.get_balance(fee_config).await.unwrap();
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::close_account(
+            id(),
+            &pda.pubkey(),
+            &dart.pubkey(),
+            &authority.pubkey(),
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &dart, &authority],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+
+    // The exempt DART's vault pays out in full.
+    let rent = Rent::default().minimum_balance(get_packed_len::<VaultRecord>());
+    assert_eq!(
+        context
+            .banks_client
+            .get_balance(authority.pubkey())
+            .await
+            .unwrap(),
+        rent
+    );
+    assert_eq!(
+        context.banks_client.get_balance(fee_config).await.unwrap(),
+        fee_config_lamports
+    );
+    context.banks_client.assert_vault_closed(pda.pubkey()).await;
+}
+
+#[tokio::test]
+async fn close_account_charges_close_fee_by_age() {
+    let admin = Keypair::new();
+    let mut context = start_with_config(program_test(), &admin).await;
+
+    let mut tiers = [CloseFeeTier::default(); MAX_CLOSE_FEE_TIERS];
+    tiers[0] = CloseFeeTier {
+        active: true,
+        tag: 0,
+        lamports: 1_000,
+        min_age_slots: 1_000,
+    };
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::set_close_fees(
+            id(),
+            &admin.pubkey(),
+            &context.payer.pubkey(),
+            5_000,
+            tiers,
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &admin],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+
+    let pda = Keypair::new();
+    let dart = Keypair::new();
+    let authority = Keypair::new();
+    initialize_account(&mut context, &pda, &dart, &authority).await;
+    let record = context.banks_client.get_vault_record(pda.pubkey()).await;
+    assert_ne!(record.created_slot, 0);
+
+    // Only vaults at least `min_age_slots` old get the cheaper tier.
+    warp_to_slot(&mut context, record.created_slot + 1_000).await;
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::close_account(
+            id(),
+            &pda.pubkey(),
+            &dart.pubkey(),
+            &authority.pubkey(),
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &dart, &authority],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+    let rent = Rent::default().minimum_balance(get_packed_len::<VaultRecord>());
+    assert_eq!(
+        context
+            .banks_client
+            .get_balance(authority.pubkey())
+            .await
+            .unwrap(),
+        rent - 1_000
+    );
+}
+
+#[tokio::test]
+async fn close_account_fail_missing_fee_config() {
+    let mut context = program_test().start_with_context().await;
+
+    let pda = Keypair::new();
+    let dart = Keypair::new();
+    let authority = Keypair::new();
+    initialize_account(&mut context, &pda, &dart, &authority).await;
+
+    let (fee_config, _) = FeeConfig::find_address(&id());
+    let mut close =
+        instruction::close_account(id(), &pda.pubkey(), &dart.pubkey(), &authority.pubkey());
+    close.accounts.retain(|meta| meta.pubkey != fee_config);
+    let transaction = Transaction::new_signed_with_payer(
+        &[close],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &dart, &authority],
+        context.last_blockhash,
+    );
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(0, InstructionError::NotEnoughAccountKeys)
+    );
+}
+
+#[tokio::test]
+async fn close_returns_receipt() {
+    let mut context = program_test().start_with_context().await;
+
+    let pda = Keypair::new();
+    let dart = Keypair::new();
+    let authority = Keypair::new();
+    initialize_account(&mut context, &pda, &dart, &authority).await;
+    let lamports = context
+        .banks_client
+        .get_balance(pda.pubkey())
+        .await
+        .unwrap();
+
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::close_account(
+            id(),
+            &pda.pubkey(),
+            &dart.pubkey(),
+            &authority.pubkey(),
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &dart, &authority],
+        context.last_blockhash,
+    );
+    let result = context
+        .banks_client
+        .process_transaction_with_metadata(transaction)
+        .await
+        .unwrap();
+    assert_eq!(result.result, Ok(()));
+    let return_data = result.metadata.unwrap().return_data.unwrap();
+    assert_eq!(return_data.program_id, id());
+
+    let clock = context.banks_client.get_sysvar::<Clock>().await.unwrap();
+    assert_eq!(
+        event::decode::<CloseReceipt>(&return_data.data),
+        Some(CloseReceipt {
+            vault: pda.pubkey(),
+            authority: authority.pubkey(),
+            lamports,
+            slot: clock.slot,
+        })
+    );
+}
+
+#[tokio::test]
+async fn audit_trail_records_mutations() {
+    let mut context = program_test().start_with_context().await;
+
+    let pda = Keypair::new();
+    let dart = Keypair::new();
+    let authority = Keypair::new();
+    initialize_account(&mut context, &pda, &dart, &authority).await;
+
+    let mut instructions = vec![instruction::create_audit_trail(
+        id(),
+        &pda.pubkey(),
+        &dart.pubkey(),
+        &context.payer.pubkey(),
+        2,
+    )];
+    // Three tagged mutations overflow the ring.
+    for tag in 1..=3 {
+        instructions.push(instruction::with_audit_trail(
+            instruction::set_tag(id(), &pda.pubkey(), &dart.pubkey(), tag),
+            &pda.pubkey(),
+        ));
+    }
+    let transaction = Transaction::new_signed_with_payer(
+        &instructions,
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &dart],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+    assert!(
+        context
+            .banks_client
+            .get_vault_record(pda.pubkey())
+            .await
+            .audited
+    );
+
+    let (address, _) = AuditTrail::find_address(&id(), &pda.pubkey());
+    let data = context
+        .banks_client
+        .get_account(address)
+        .await
+        .unwrap()
+        .unwrap()
+        .data;
+    assert_eq!(data.len(), AuditTrail::space(2));
+    let trail = AuditTrail::from_account_data(&data).unwrap();
+    assert_eq!(trail.count, 3);
+    let entries: Vec<_> = trail.entries(&data).map(Result::unwrap).collect();
+    assert_eq!(entries.len(), 2);
+    for (sequence, entry) in entries {
+        assert!(sequence >= 1);
+        assert_eq!(
+            entry.action,
+            instruction::VaultInstruction::SetTag { tag: 0 }.tag()
+        );
+        assert_eq!(entry.actor, dart.pubkey());
+    }
+
+    // Once the vault has a trail, an instruction acting on it cannot leave it out.
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::set_tag(id(), &pda.pubkey(), &dart.pubkey(), 4)],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &dart],
+        context.last_blockhash,
+    );
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(0, InstructionError::NotEnoughAccountKeys)
+    );
+
+    // Both vaults of a swap are recorded, each in its own trail.
+    let swapped = Keypair::new();
+    let swapped_authority = Keypair::new();
+    initialize_account(&mut context, &swapped, &dart, &swapped_authority).await;
+    let swap = instruction::swap_authorities(
+        id(),
+        &pda.pubkey(),
+        &swapped.pubkey(),
+        &dart.pubkey(),
+        &authority.pubkey(),
+        &swapped_authority.pubkey(),
+    );
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            instruction::create_audit_trail(
+                id(),
+                &swapped.pubkey(),
+                &dart.pubkey(),
+                &context.payer.pubkey(),
+                1,
+            ),
+            instruction::with_audit_trail(
+                instruction::with_audit_trail(swap, &pda.pubkey()),
+                &swapped.pubkey(),
+            ),
+        ],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &dart, &authority, &swapped_authority],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+    for (vault, count) in [(&pda, 4), (&swapped, 1)] {
+        let (address, _) = AuditTrail::find_address(&id(), &vault.pubkey());
+        let data = context
+            .banks_client
+            .get_account(address)
+            .await
+            .unwrap()
+            .unwrap()
+            .data;
+        let trail = AuditTrail::from_account_data(&data).unwrap();
+        assert_eq!(trail.count, count);
+        let (_, entry) = trail.entries(&data).last().unwrap().unwrap();
+        assert_eq!(
+            entry.action,
+            instruction::VaultInstruction::SwapAuthorities.tag()
+        );
+    }
+
+    // The trail is opened once, within `MAX_AUDIT_ENTRIES`.
+    let other = Keypair::new();
+    initialize_account(&mut context, &other, &dart, &authority).await;
+    for (vault, capacity, error) in [
+        (&pda, 2, InstructionError::AccountAlreadyInitialized),
+        (
+            &other,
+            MAX_AUDIT_ENTRIES + 1,
+            InstructionError::InvalidArgument,
+        ),
+    ] {
+        let transaction = Transaction::new_signed_with_payer(
+            &[instruction::create_audit_trail(
+                id(),
+                &vault.pubkey(),
+                &dart.pubkey(),
+                &context.payer.pubkey(),
+                capacity,
+            )],
+            Some(&context.payer.pubkey()),
+            &[&context.payer, &dart],
+            context.last_blockhash,
+        );
+        assert_eq!(
+            context
+                .banks_client
+                .process_transaction(transaction)
+                .await
+                .unwrap_err()
+                .unwrap(),
+            TransactionError::InstructionError(0, error)
+        );
+    }
+}
+
+#[tokio::test]
+async fn program_stats_count_invocations() {
+    let admin = Keypair::new();
+    let mut context = start_with_config(program_test(), &admin).await;
+
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::initialize_stats(
+            id(),
+            &admin.pubkey(),
+            &context.payer.pubkey(),
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &admin],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+
+    let pda = Keypair::new();
+    let dart = Keypair::new();
+    let authority = Keypair::new();
+    initialize_account(&mut context, &pda, &dart, &authority).await;
+    let set_tag = |tag| instruction::set_tag(id(), &pda.pubkey(), &dart.pubkey(), tag);
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            instruction::with_stats(set_tag(1)),
+            instruction::with_stats(set_tag(2)),
+            set_tag(3),
+        ],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &dart],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+
+    // A failed instruction rolls its count back.
+    let impostor = Keypair::new();
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::with_stats(instruction::set_tag(
+            id(),
+            &pda.pubkey(),
+            &impostor.pubkey(),
+            4,
+        ))],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &impostor],
+        context.last_blockhash,
+    );
+    assert!(context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .is_err());
+
+    let (address, _) = ProgramStats::find_address(&id());
+    let stats = context
+        .banks_client
+        .get_account_data_with_borsh::<ProgramStats>(address)
+        .await
+        .unwrap();
+    let tag = instruction::VaultInstruction::SetTag { tag: 0 }.tag();
+    assert_eq!(stats.invocations[usize::from(tag)], 2);
+    assert_eq!(stats.invocations.iter().sum::<u64>(), 2);
+}
+
+#[tokio::test]
+async fn transfer_and_close_notify_registered_key() {
+    let mut context = program_test().start_with_context().await;
+
+    let pda = Keypair::new();
+    let dart = Keypair::new();
+    let authority = Keypair::new();
+    let new_authority = Keypair::new();
+    let notify = Pubkey::new_unique();
+    initialize_account(&mut context, &pda, &dart, &authority).await;
+
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::set_notify(
+            id(),
+            &pda.pubkey(),
+            &authority.pubkey(),
+            &context.payer.pubkey(),
+            notify,
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &authority],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::with_notify(
+            instruction::transfer_authority(
+                id(),
+                &pda.pubkey(),
+                &dart.pubkey(),
+                &authority.pubkey(),
+                &new_authority.pubkey(),
+            ),
+            &pda.pubkey(),
+            &notify,
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &dart, &authority],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+
+    let (address, _) = Notification::find_address(&id(), &notify);
+    let account = context
+        .banks_client
+        .get_account(address)
+        .await
+        .unwrap()
+        .unwrap();
+    let notification = Notification::try_from_slice(&account.data).unwrap();
+    assert_eq!(notification.sequence, 1);
+    assert_eq!(notification.kind, NotificationKind::Transfer);
+    assert_eq!(notification.vault, pda.pubkey());
+    assert_eq!(notification.previous_authority, authority.pubkey());
+    assert_eq!(notification.authority, new_authority.pubkey());
+
+    // The registration lapsed with the transfer: transferring back notifies nothing.
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::with_notify(
+            instruction::transfer_authority(
+                id(),
+                &pda.pubkey(),
+                &dart.pubkey(),
+                &new_authority.pubkey(),
+                &authority.pubkey(),
+            ),
+            &pda.pubkey(),
+            &notify,
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &dart, &new_authority],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+    let account = context
+        .banks_client
+        .get_account(address)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        Notification::try_from_slice(&account.data).unwrap(),
+        notification
+    );
+
+    // Registered again, the close is notified.
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            instruction::set_notify(
+                id(),
+                &pda.pubkey(),
+                &authority.pubkey(),
+                &context.payer.pubkey(),
+                notify,
+            ),
+            instruction::with_notify(
+                instruction::close_account(
+                    id(),
+                    &pda.pubkey(),
+                    &dart.pubkey(),
+                    &authority.pubkey(),
+                ),
+                &pda.pubkey(),
+                &notify,
+            ),
+        ],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &dart, &authority],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+    let account = context
+        .banks_client
+        .get_account(address)
+        .await
+        .unwrap()
+        .unwrap();
+    let notification = Notification::try_from_slice(&account.data).unwrap();
+    assert_eq!(notification.sequence, 2);
+    assert_eq!(notification.kind, NotificationKind::Close);
+    assert_eq!(notification.previous_authority, authority.pubkey());
+    assert_eq!(notification.authority, Pubkey::default());
+}
+
+#[tokio::test]
+async fn transfer_fail_missing_notification() {
+    let mut context = program_test().start_with_context().await;
+
+    let pda = Keypair::new();
+    let dart = Keypair::new();
+    let authority = Keypair::new();
+    let notify = Pubkey::new_unique();
+    initialize_account(&mut context, &pda, &dart, &authority).await;
+
+    let transfer = instruction::transfer_authority(
+        id(),
+        &pda.pubkey(),
+        &dart.pubkey(),
+        &authority.pubkey(),
+        &Pubkey::new_unique(),
+    );
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            instruction::set_notify(
+                id(),
+                &pda.pubkey(),
+                &authority.pubkey(),
+                &context.payer.pubkey(),
+                notify,
+            ),
+            transfer,
+        ],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &dart, &authority],
+        context.last_blockhash,
+    );
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(1, InstructionError::NotEnoughAccountKeys)
+    );
+}
+
+#[tokio::test]
+async fn transfer_and_close_fail_missing_notify_record() {
+    let mut context = program_test().start_with_context().await;
+
+    let pda = Keypair::new();
+    let dart = Keypair::new();
+    let authority = Keypair::new();
+    let notify = Pubkey::new_unique();
+    initialize_account(&mut context, &pda, &dart, &authority).await;
+
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::set_notify(
+            id(),
+            &pda.pubkey(),
+            &authority.pubkey(),
+            &context.payer.pubkey(),
+            notify,
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &authority],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+
+    // Leaving the notify record out cannot skip the notification.
+    let (notify_record, _) = NotifyRecord::find_address(&id(), &pda.pubkey());
+    let transfer = instruction::transfer_authority(
+        id(),
+        &pda.pubkey(),
+        &dart.pubkey(),
+        &authority.pubkey(),
+        &Pubkey::new_unique(),
+    );
+    let close =
+        instruction::close_account(id(), &pda.pubkey(), &dart.pubkey(), &authority.pubkey());
+    for mut instruction in [transfer, close] {
+        instruction
+            .accounts
+            .retain(|meta| meta.pubkey != notify_record);
+        context.last_blockhash = context.get_new_latest_blockhash().await.unwrap();
+        let transaction = Transaction::new_signed_with_payer(
+            &[instruction],
+            Some(&context.payer.pubkey()),
+            &[&context.payer, &dart, &authority],
+            context.last_blockhash,
+        );
+        assert_eq!(
+            context
+                .banks_client
+                .process_transaction(transaction)
+                .await
+                .unwrap_err()
+                .unwrap(),
+            TransactionError::InstructionError(0, InstructionError::NotEnoughAccountKeys)
+        );
+    }
+}
+
+async fn check_transfer(
+    context: &mut ProgramTestContext,
+    pda: &Pubkey,
+    dart: &Pubkey,
+    authority: &Pubkey,
+    new_authority: &Pubkey,
+) -> TransferCheck {
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::check_transfer(
+            id(),
+            pda,
+            dart,
+            authority,
+            new_authority,
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer],
+        context.last_blockhash,
+    );
+    let result = context
+        .banks_client
+        .process_transaction_with_metadata(transaction)
+        .await
+        .unwrap();
+    assert_eq!(result.result, Ok(()));
+    let return_data = result.metadata.unwrap().return_data.unwrap();
+    assert_eq!(return_data.program_id, id());
+    event::decode::<TransferCheck>(&return_data.data).unwrap()
+}
+
+#[tokio::test]
+async fn check_transfer_reports_without_signatures() {
+    let mut context = program_test().start_with_context().await;
+
+    let pda = Keypair::new();
+    let dart = Keypair::new();
+    let authority = Keypair::new();
+    initialize_account(&mut context, &pda, &dart, &authority).await;
+    let record = context.banks_client.get_vault_record(pda.pubkey()).await;
+
+    let new_authority = Pubkey::new_unique();
+    let check = check_transfer(
+        &mut context,
+        &pda.pubkey(),
+        &dart.pubkey(),
+        &authority.pubkey(),
+        &new_authority,
+    )
+    .await;
+    assert_eq!(check.vault, pda.pubkey());
+    assert_eq!(check.new_authority, new_authority);
+    assert_eq!(check.result(), Ok(()));
+
+    let check = check_transfer(
+        &mut context,
+        &pda.pubkey(),
+        &dart.pubkey(),
+        &authority.pubkey(),
+        &dart.pubkey(),
+    )
+    .await;
+    assert_eq!(check.result(), Err(VaultError::AuthorityIsDart.into()));
+
+    let check = check_transfer(
+        &mut context,
+        &pda.pubkey(),
+        &dart.pubkey(),
+        &new_authority,
+        &Pubkey::new_unique(),
+    )
+    .await;
+    assert_eq!(check.result(), Err(VaultError::IncorrectAuthority.into()));
+
+    // Nothing was written.
+    assert_eq!(
+        context.banks_client.get_vault_record(pda.pubkey()).await,
+        record
+    );
+}
@@ -0,0 +1,283 @@
+#![cfg(feature = "test-sbf")]
+//! Random instruction sequences driven through `ProgramTest`, checked against vault invariants:
+//! lamports are conserved, the authority only changes with both DART and authority signatures,
+//! and closed vaults stay closed.
+//!
+//! Runs use a fixed seed, so they are reproducible; set `VAULT_FUZZ_SEED` to explore others.
+use {
+    solana_program::{instruction::Instruction, pubkey::Pubkey, rent::Rent, system_instruction},
+    solana_program_test::*,
+    solana_sdk::{
+        signature::{Keypair, Signer},
+        transaction::Transaction,
+    },
+    vault::{
+        id, instruction,
+        state::{VaultRecord, VaultStatus},
+        test_utils::program_test,
+    },
+};
+
+const ROUNDS: usize = 8;
+const STEPS: usize = 16;
+// Seed of every run unless `VAULT_FUZZ_SEED` replaces it.
+const DEFAULT_SEED: u64 = 0x5eed_5eed_5eed_5eed;
+
+// xorshift64*, so a failing sequence can be replayed from its seed without extra dependencies.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+}
+
+// How a role is presented to the program.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Sig {
+    Valid,
+    WrongKey,
+    Unsigned,
+}
+
+impl Sig {
+    fn random(rng: &mut Rng) -> Self {
+        [Sig::Valid, Sig::WrongKey, Sig::Unsigned][rng.below(3)]
+    }
+}
+
+#[derive(Debug)]
+enum Op {
+    Transfer { new_authority: usize },
+    Close,
+    CloseAndReopen,
+    CloseAndRevive { new_authority: usize },
+}
+
+struct Vault {
+    pda: Keypair,
+    dart: Keypair,
+    impostor: Keypair,
+    // Candidate authorities; `authority` indexes the current one.
+    pool: Vec<Keypair>,
+    authority: usize,
+    closed: bool,
+}
+
+impl Vault {
+    fn tracked(&self) -> Vec<Pubkey> {
+        [&self.pda, &self.dart, &self.impostor]
+            .into_iter()
+            .chain(&self.pool)
+            .map(|keypair| keypair.pubkey())
+            .collect()
+    }
+
+    fn role<'a>(&'a self, sig: Sig, valid: &'a Keypair) -> &'a Keypair {
+        match sig {
+            Sig::WrongKey => &self.impostor,
+            Sig::Valid | Sig::Unsigned => valid,
+        }
+    }
+}
+
+// Drop the signer flag of `key` on every instruction, so the transaction goes out unsigned by it.
+fn strip_signer(instructions: &mut [Instruction], key: &Pubkey) {
+    for meta in instructions
+        .iter_mut()
+        .flat_map(|ix| ix.accounts.iter_mut())
+    {
+        if meta.pubkey == *key {
+            meta.is_signer = false;
+        }
+    }
+}
+
+async fn total_lamports(context: &mut ProgramTestContext, keys: &[Pubkey]) -> u64 {
+    let mut total = 0;
+    for key in keys {
+        total += context.banks_client.get_balance(*key).await.unwrap();
+    }
+    total
+}
+
+async fn create_vault(context: &mut ProgramTestContext) -> Vault {
+    let vault = Vault {
+        pda: Keypair::new(),
+        dart: Keypair::new(),
+        impostor: Keypair::new(),
+        pool: (0..3).map(|_| Keypair::new()).collect(),
+        authority: 0,
+        closed: false,
+    };
+    let lamports = Rent::default().minimum_balance(VaultRecord::LEN);
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            system_instruction::create_account(
+                &context.payer.pubkey(),
+                &vault.pda.pubkey(),
+                lamports,
+                VaultRecord::LEN as u64,
+                &id(),
+            ),
+            instruction::initialize(
+                id(),
+                &vault.pda.pubkey(),
+                &vault.dart.pubkey(),
+                &vault.pool[0].pubkey(),
+            ),
+        ],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &vault.pda, &vault.dart],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+    vault
+}
+
+#[tokio::test]
+async fn random_sequences_hold_invariants() {
+    let seed = std::env::var("VAULT_FUZZ_SEED")
+        .ok()
+        .and_then(|seed| seed.parse().ok())
+        .unwrap_or(DEFAULT_SEED)
+        | 1;
+    let mut rng = Rng(seed);
+
+    let mut context = program_test().start_with_context().await;
+    let rent = Rent::default().minimum_balance(VaultRecord::LEN);
+
+    for round in 0..ROUNDS {
+        let mut vault = create_vault(&mut context).await;
+
+        for step in 0..STEPS {
+            let op = match rng.below(4) {
+                0 | 1 => Op::Transfer {
+                    new_authority: rng.below(vault.pool.len()),
+                },
+                2 => Op::Close,
+                _ if rng.below(2) == 0 => Op::CloseAndReopen,
+                _ => Op::CloseAndRevive {
+                    new_authority: rng.below(vault.pool.len()),
+                },
+            };
+            let dart_sig = Sig::random(&mut rng);
+            let authority_sig = Sig::random(&mut rng);
+            let dart = vault.role(dart_sig, &vault.dart);
+            let authority = vault.role(authority_sig, &vault.pool[vault.authority]);
+            let pda = vault.pda.pubkey();
+
+            let close = instruction::close_account(id(), &pda, &dart.pubkey(), &authority.pubkey());
+            let refund = system_instruction::transfer(&context.payer.pubkey(), &pda, rent);
+            let (mut instructions, refunded) = match op {
+                Op::Transfer { new_authority } => (
+                    vec![instruction::transfer_authority(
+                        id(),
+                        &pda,
+                        &dart.pubkey(),
+                        &authority.pubkey(),
+                        &vault.pool[new_authority].pubkey(),
+                    )],
+                    0,
+                ),
+                Op::Close => (vec![close], 0),
+                Op::CloseAndReopen => (
+                    vec![
+                        close,
+                        refund,
+                        instruction::reopen(id(), &pda, &dart.pubkey()),
+                    ],
+                    rent,
+                ),
+                Op::CloseAndRevive { new_authority } => (
+                    vec![
+                        close,
+                        refund,
+                        instruction::transfer_authority(
+                            id(),
+                            &pda,
+                            &dart.pubkey(),
+                            &authority.pubkey(),
+                            &vault.pool[new_authority].pubkey(),
+                        ),
+                    ],
+                    rent,
+                ),
+            };
+
+            context.last_blockhash = context.get_new_latest_blockhash().await.unwrap();
+            let mut signers = vec![&context.payer];
+            for (sig, keypair) in [(dart_sig, dart), (authority_sig, authority)] {
+                if sig == Sig::Unsigned {
+                    strip_signer(&mut instructions, &keypair.pubkey());
+                } else if signers.iter().all(|s| s.pubkey() != keypair.pubkey()) {
+                    signers.push(keypair);
+                }
+            }
+
+            let transaction = Transaction::new_signed_with_payer(
+                &instructions,
+                Some(&context.payer.pubkey()),
+                &signers,
+                context.last_blockhash,
+            );
+
+            let tracked = vault.tracked();
+            let before = total_lamports(&mut context, &tracked).await;
+            let result = context.banks_client.process_transaction(transaction).await;
+            let after = total_lamports(&mut context, &tracked).await;
+            let trace = format!(
+                "seed {seed} round {round} step {step}: {op:?} dart={dart_sig:?} authority={authority_sig:?} -> {result:?}"
+            );
+
+            // Lamports only enter the tracked set through a committed refund.
+            let expected = before + if result.is_ok() { refunded } else { 0 };
+            assert_eq!(after, expected, "lamports not conserved, {trace}");
+
+            // A closed vault can never be revived by a later instruction in the same transaction.
+            if let Op::CloseAndRevive { .. } = op {
+                assert!(result.is_err(), "closed vault revived, {trace}");
+            }
+
+            let account = context.banks_client.get_account(pda).await.unwrap();
+            if vault.closed {
+                assert!(account.is_none(), "closed vault reappeared, {trace}");
+                continue;
+            }
+            let Some(account) = account else {
+                assert!(
+                    matches!(op, Op::Close) && result.is_ok(),
+                    "vault vanished, {trace}"
+                );
+                vault.closed = true;
+                continue;
+            };
+            let record = borsh::from_slice::<VaultRecord>(&account.data).unwrap();
+            assert_eq!(record.status, VaultStatus::Active, "{trace}");
+
+            let authority = vault
+                .pool
+                .iter()
+                .position(|keypair| keypair.pubkey() == record.authority)
+                .expect("authority outside the candidate pool");
+            if authority != vault.authority {
+                assert!(
+                    dart_sig == Sig::Valid && authority_sig == Sig::Valid,
+                    "authority changed without both signatures, {trace}"
+                );
+                vault.authority = authority;
+            }
+        }
+    }
+}